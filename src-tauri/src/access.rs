@@ -0,0 +1,163 @@
+//! 文件访问范围
+//!
+//! 前端可以把任意路径传给后端命令，被注入的脚本也就能读写整个磁盘。因此所有按路径读写文件的
+//! 命令在开始时都调用 `ensure_access`，只接受以下范围内的路径（均按 `paths::canonicalize`
+//! 规范化后比较，符号链接和 `..` 无法越界）：
+//!
+//! - 当前工作区和打开过的工作区；
+//! - 设置中的 `access.roots`；
+//! - 用户通过 `request_access` 确认过的路径（保存在设置的 `access.grants` 中）；
+//! - 本次运行中用户在系统文件对话框里选择的文件和目录（dialog 插件写入 fs 插件的范围）；
+//! - 通过文件关联、命令行或其它实例传入的文件。
+//!
+//! `update_settings` 只能收紧 `access`（开启检查、移除目录），否则前端改一下设置就能绕过；
+//! 关闭检查（`access.enforce: false`）或添加目录需要直接编辑 `settings.json`。
+//! 同理，外部工具、OCR、转写和 pandoc 的命令、发布目标和日记目录也不能通过 `update_settings`
+//! 修改（见 `Settings::keep_protected`），否则可以借它们运行任意程序或写入任意目录。
+
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use serde::Serialize;
+use tauri::{AppHandle, Emitter, Manager};
+use tauri_plugin_dialog::{DialogExt, MessageDialogButtons, MessageDialogKind};
+use tauri_plugin_fs::FsExt;
+
+use crate::error::VividError;
+use crate::paths;
+use crate::session::SessionStore;
+use crate::settings::{SettingsStore, SETTINGS_CHANGED_EVENT};
+use crate::workspace::Workspace;
+
+/// 访问方式，用于日志和错误信息
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AccessKind {
+    Read,
+    Write,
+    Delete,
+}
+
+/// 本次运行中由后端授予的路径
+#[derive(Default)]
+pub struct AccessControl {
+    session: Mutex<Vec<PathBuf>>,
+}
+
+impl AccessControl {
+    /// 允许访问该路径直到应用退出（用于系统传入的文件）
+    pub fn grant_session(&self, path: &Path) {
+        let path = paths::canonicalize(path);
+        if let Ok(mut session) = self.session.lock() {
+            if !session.contains(&path) {
+                log::debug!("[access] Session grant: {}", path.display());
+                session.push(path);
+            }
+        }
+    }
+
+    fn session_grants(&self) -> Vec<PathBuf> {
+        self.session.lock().map(|s| s.clone()).unwrap_or_default()
+    }
+}
+
+/// 允许访问的目录和文件（尚未规范化）
+fn allowed_entries(app: &AppHandle) -> Vec<PathBuf> {
+    let settings = app.state::<SettingsStore>().get();
+    let mut entries: Vec<PathBuf> = settings
+        .access
+        .roots
+        .iter()
+        .chain(&settings.access.grants)
+        .filter(|p| !p.trim().is_empty())
+        .map(PathBuf::from)
+        .collect();
+    entries.extend(app.state::<Workspace>().root());
+    entries.extend(app.state::<SessionStore>().recent_workspaces());
+    entries.extend(app.state::<AccessControl>().session_grants());
+    entries
+}
+
+/// 路径是否在允许访问的范围内
+pub fn is_allowed(app: &AppHandle, path: &Path) -> bool {
+    if !app.state::<SettingsStore>().get().access.enforce {
+        return true;
+    }
+    let target = paths::canonicalize(path);
+    if allowed_entries(app)
+        .iter()
+        .any(|entry| target.starts_with(paths::canonicalize(entry)))
+    {
+        return true;
+    }
+    app.try_fs_scope()
+        .is_some_and(|scope| scope.is_allowed(path) || scope.is_allowed(&target))
+}
+
+/// 检查访问权限，不在范围内时返回 `PermissionDenied`
+pub fn ensure_access(app: &AppHandle, path: &Path, kind: AccessKind) -> Result<(), VividError> {
+    if is_allowed(app, path) {
+        return Ok(());
+    }
+    log::warn!("[access] Denied {:?} access to {}", kind, path.display());
+    Err(VividError::permission_denied(
+        path,
+        format!(
+            "Access to {} is outside the allowed folders; call request_access first",
+            path.display()
+        ),
+    ))
+}
+
+// 请求访问工作区以外的路径：已允许时直接返回 true，否则弹窗询问，确认后记入设置
+#[tauri::command]
+pub async fn request_access(app: AppHandle, path: String) -> Result<bool, VividError> {
+    let path_buf = PathBuf::from(&path);
+    if is_allowed(&app, &path_buf) {
+        return Ok(true);
+    }
+    log::info!("[request_access] Asking for access to {}", path);
+
+    let dialog = app
+        .dialog()
+        .message(format!(
+            "A command is asking to read and write:\n\n{}\n\nAllow VividMark to access this location?",
+            path
+        ))
+        .title("File access")
+        .kind(MessageDialogKind::Warning)
+        .buttons(MessageDialogButtons::OkCancelCustom(
+            "Allow".to_string(),
+            "Deny".to_string(),
+        ));
+    // 对话框会阻塞到用户作出选择，不能占用异步运行时的线程
+    let allowed = tauri::async_runtime::spawn_blocking(move || dialog.blocking_show())
+        .await
+        .map_err(|e| format!("Access dialog failed: {}", e))?;
+    if !allowed {
+        log::info!("[request_access] Denied by user: {}", path);
+        return Ok(false);
+    }
+
+    let grant = paths::canonicalize(&path_buf).to_string_lossy().to_string();
+    let settings = app
+        .state::<SettingsStore>()
+        .update_with(|settings| {
+            if !settings.access.grants.contains(&grant) {
+                settings.access.grants.push(grant.clone());
+            }
+        })
+        .map_err(|e| {
+            log::error!("[request_access] {}", e);
+            VividError::from(e)
+        })?;
+    if let Err(e) = app.emit(SETTINGS_CHANGED_EVENT, &settings) {
+        log::warn!(
+            "[request_access] Failed to emit {}: {}",
+            SETTINGS_CHANGED_EVENT,
+            e
+        );
+    }
+    log::info!("[request_access] ✓ Granted: {}", grant);
+    Ok(true)
+}
@@ -0,0 +1,582 @@
+//! 工作区压缩包备份
+//!
+//! `backup_workspace_to_zip` 把整个工作区打包为带时间戳的 zip（`<工作区名称>-YYYYMMDD-HHMMSS.zip`），
+//! 不依赖任何同步服务。`.git`、`node_modules`、`target` 和系统生成的 `.DS_Store` / `Thumbs.db`
+//! 不打包，其余文件（包括 `.vividmark/` 这类隐藏的配置）按相对路径写入，保留修改时间；
+//! 符号链接不打包。可以另外传入排除规则：
+//!
+//! - 不含 `/` 的规则匹配任意层级的文件名或目录名，如 `*.pdf`、`drafts`；
+//! - 含 `/` 的规则匹配相对工作区根目录的路径，如 `assets/raw/**`、`/archive`；
+//! - `*` 和 `?` 不跨越 `/`，`**` 匹配任意层级目录。
+//!
+//! 设置口令时每个文件用 WinZip AES-256 加密，解压软件输入同一口令即可打开。
+//!
+//! `restore_workspace_from_zip` 把压缩包解压到目标目录，已存在的文件按覆盖策略处理；
+//! 覆盖前按设置备份原文件（见 `backup`）。压缩包中指向目标目录之外的路径会被忽略。
+
+use std::fs::{self, File};
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+use std::time::{Instant, SystemTime};
+
+use chrono::{DateTime, Datelike, Local, NaiveDate, TimeZone, Timelike};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager};
+use zip::result::ZipError;
+use zip::write::SimpleFileOptions;
+use zip::{AesMode, CompressionMethod, ZipArchive, ZipWriter};
+
+use crate::access::{self, AccessKind};
+use crate::backup::BackupStore;
+use crate::error::VividError;
+use crate::settings::SettingsStore;
+use crate::storage;
+use crate::workspace::{self, Workspace};
+
+/// 始终不打包的文件和目录
+const DEFAULT_EXCLUDES: &[&str] = &[".git", "node_modules", "target", ".DS_Store", "Thumbs.db"];
+
+/// 备份选项
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct ZipBackupOptions {
+    /// 额外的排除规则
+    pub exclude: Vec<String>,
+    /// 加密口令，为空时不加密
+    pub password: Option<String>,
+}
+
+/// 备份结果
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ZipBackupResult {
+    pub path: String,
+    pub files: usize,
+    /// 打包前的总字节数
+    pub bytes: u64,
+    /// 压缩包大小
+    pub size: u64,
+    /// 按排除规则跳过的文件和目录数
+    pub excluded: usize,
+    pub encrypted: bool,
+}
+
+/// 目标目录中已存在同名文件时的处理方式
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OverwritePolicy {
+    /// 保留现有文件
+    #[default]
+    Skip,
+    Overwrite,
+    /// 压缩包中的文件较新时覆盖
+    IfNewer,
+    /// 另存为 `名称 (restored).扩展名`
+    KeepBoth,
+}
+
+/// 恢复选项
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct ZipRestoreOptions {
+    pub overwrite: OverwritePolicy,
+    /// 加密压缩包的口令
+    pub password: Option<String>,
+}
+
+/// 未能恢复的文件
+#[derive(Debug, Serialize, Deserialize)]
+pub struct FailedEntry {
+    pub path: String,
+    pub error: String,
+}
+
+/// 恢复结果，路径均为目标目录中的路径
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ZipRestoreResult {
+    pub target: String,
+    pub restored: Vec<String>,
+    /// 按覆盖策略保留的现有文件
+    pub skipped: Vec<String>,
+    pub failed: Vec<FailedEntry>,
+}
+
+/// 排除规则转换为正则：`**/` 匹配零或多层目录，`*` / `?` 不跨越 `/`
+fn glob_regex(pattern: &str) -> Option<Regex> {
+    let mut regex = String::from("^");
+    let mut chars = pattern.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '*' if chars.peek() == Some(&'*') => {
+                chars.next();
+                if chars.peek() == Some(&'/') {
+                    chars.next();
+                    regex.push_str("(?:.*/)?");
+                } else {
+                    regex.push_str(".*");
+                }
+            }
+            '*' => regex.push_str("[^/]*"),
+            '?' => regex.push_str("[^/]"),
+            c => regex.push_str(&regex::escape(&c.to_string())),
+        }
+    }
+    regex.push('$');
+    Regex::new(&regex).ok()
+}
+
+/// 排除规则
+struct Excludes {
+    /// 匹配文件名的规则
+    names: Vec<Regex>,
+    /// 匹配相对路径的规则
+    paths: Vec<Regex>,
+}
+
+impl Excludes {
+    fn new(patterns: &[String]) -> Self {
+        let mut excludes = Excludes {
+            names: Vec::new(),
+            paths: Vec::new(),
+        };
+        let defaults = DEFAULT_EXCLUDES.iter().map(|p| p.to_string());
+        for pattern in defaults.chain(patterns.iter().cloned()) {
+            let pattern = pattern.trim().replace('\\', "/");
+            let pattern = pattern.trim_end_matches('/');
+            if pattern.is_empty() {
+                continue;
+            }
+            let Some(regex) = glob_regex(pattern.trim_start_matches('/')) else {
+                log::warn!("[archive] Invalid exclude pattern: {}", pattern);
+                continue;
+            };
+            if pattern.contains('/') {
+                excludes.paths.push(regex);
+            } else {
+                excludes.names.push(regex);
+            }
+        }
+        excludes
+    }
+
+    /// `rel` 为相对根目录、以 `/` 分隔的路径
+    fn matches(&self, name: &str, rel: &str) -> bool {
+        self.names.iter().any(|re| re.is_match(name))
+            || self.paths.iter().any(|re| re.is_match(rel))
+    }
+}
+
+/// 本地时间转换为 zip 的修改时间，1980 年之前的时间无法表示
+fn zip_time(time: SystemTime) -> Option<zip::DateTime> {
+    let time = DateTime::<Local>::from(time);
+    zip::DateTime::from_date_and_time(
+        u16::try_from(time.year()).ok()?,
+        time.month() as u8,
+        time.day() as u8,
+        time.hour() as u8,
+        time.minute() as u8,
+        time.second() as u8,
+    )
+    .ok()
+}
+
+fn system_time(time: zip::DateTime) -> Option<SystemTime> {
+    let local =
+        NaiveDate::from_ymd_opt(time.year().into(), time.month().into(), time.day().into())?
+            .and_hms_opt(
+                time.hour().into(),
+                time.minute().into(),
+                time.second().into(),
+            )?;
+    Local
+        .from_local_datetime(&local)
+        .earliest()
+        .map(SystemTime::from)
+}
+
+/// 压缩包路径：目标是 `.zip` 文件时直接使用，否则在该目录下按工作区名称和时间命名
+fn archive_path(root: &Path, destination: &Path) -> PathBuf {
+    let is_zip = destination
+        .extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("zip"));
+    if is_zip && !destination.is_dir() {
+        return destination.to_path_buf();
+    }
+    let name = root
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_else(|| "workspace".to_string());
+    let stamp = Local::now().format("%Y%m%d-%H%M%S");
+    destination.join(format!("{}-{}.zip", name, stamp))
+}
+
+/// 待打包的文件和目录（相对路径，目录以 `/` 结尾）
+fn collect_entries(
+    root: &Path,
+    excludes: &Excludes,
+    skip: &[&Path],
+) -> (Vec<(PathBuf, String)>, usize) {
+    let mut entries = Vec::new();
+    let mut excluded = 0;
+    let mut pending = vec![root.to_path_buf()];
+    while let Some(dir) = pending.pop() {
+        let Ok(read) = fs::read_dir(&dir) else {
+            log::warn!("[archive] Failed to read directory: {}", dir.display());
+            continue;
+        };
+        for entry in read.flatten() {
+            let path = entry.path();
+            let Ok(rel) = path.strip_prefix(root) else {
+                continue;
+            };
+            let rel = rel.to_string_lossy().replace('\\', "/");
+            let name = entry.file_name().to_string_lossy().to_string();
+            if skip.contains(&path.as_path()) {
+                continue;
+            }
+            if excludes.matches(&name, &rel) {
+                excluded += 1;
+                continue;
+            }
+            match entry.file_type() {
+                Ok(t) if t.is_dir() => {
+                    entries.push((path.clone(), format!("{}/", rel)));
+                    pending.push(path);
+                }
+                Ok(t) if t.is_file() => entries.push((path, rel)),
+                _ => {}
+            }
+        }
+    }
+    entries.sort_by(|a, b| a.1.cmp(&b.1));
+    (entries, excluded)
+}
+
+/// 逐个写入文件，返回写入的文件数和字节数；读取失败的文件跳过
+fn write_entries(
+    zip: &mut ZipWriter<File>,
+    entries: &[(PathBuf, String)],
+    password: Option<&str>,
+) -> Result<(usize, u64), String> {
+    let mut files = 0;
+    let mut bytes = 0;
+    for (path, name) in entries {
+        let mut file_options =
+            SimpleFileOptions::default().compression_method(CompressionMethod::Deflated);
+        let modified = fs::metadata(path).and_then(|m| m.modified()).ok();
+        if let Some(time) = modified.and_then(zip_time) {
+            file_options = file_options.last_modified_time(time);
+        }
+        if name.ends_with('/') {
+            zip.add_directory(name.as_str(), file_options)
+                .map_err(|e| format!("Failed to add {}: {}", name, e))?;
+            continue;
+        }
+        let mut source = match File::open(path) {
+            Ok(source) => source,
+            Err(e) => {
+                log::warn!(
+                    "[archive] Skipping unreadable file {}: {}",
+                    path.display(),
+                    e
+                );
+                continue;
+            }
+        };
+        match password {
+            Some(password) => zip.start_file(
+                name.as_str(),
+                file_options.with_aes_encryption(AesMode::Aes256, password),
+            ),
+            None => zip.start_file(name.as_str(), file_options),
+        }
+        .map_err(|e| format!("Failed to add {}: {}", name, e))?;
+        bytes +=
+            io::copy(&mut source, zip).map_err(|e| format!("Failed to add {}: {}", name, e))?;
+        files += 1;
+    }
+    Ok((files, bytes))
+}
+
+fn write_archive(
+    root: &Path,
+    output: &Path,
+    options: &ZipBackupOptions,
+) -> Result<ZipBackupResult, VividError> {
+    let password = options.password.as_deref().filter(|p| !p.is_empty());
+    // 先写入临时文件，失败时不留下不完整的压缩包
+    let tmp = output.with_extension("zip.tmp");
+    let (entries, excluded) =
+        collect_entries(root, &Excludes::new(&options.exclude), &[output, &tmp]);
+
+    let file = File::create(&tmp).map_err(|e| VividError::io("Failed to create file", &tmp, &e))?;
+    let mut zip = ZipWriter::new(file);
+    let written = write_entries(&mut zip, &entries, password).and_then(|counts| {
+        zip.finish()
+            .map(|_| counts)
+            .map_err(|e| format!("Failed to write archive: {}", e))
+    });
+    let (files, bytes) = match written {
+        Ok(counts) => counts,
+        Err(e) => {
+            let _ = fs::remove_file(&tmp);
+            return Err(e.into());
+        }
+    };
+    fs::rename(&tmp, output).map_err(|e| {
+        let _ = fs::remove_file(&tmp);
+        VividError::io("Failed to write file", output, &e)
+    })?;
+
+    Ok(ZipBackupResult {
+        path: output.to_string_lossy().to_string(),
+        files,
+        bytes,
+        size: fs::metadata(output).map(|m| m.len()).unwrap_or(0),
+        excluded,
+        encrypted: password.is_some(),
+    })
+}
+
+// 把工作区打包为带时间戳的 zip，可设置排除规则和加密口令
+#[tauri::command]
+pub async fn backup_workspace_to_zip(
+    app: AppHandle,
+    root: String,
+    destination: String,
+    options: Option<ZipBackupOptions>,
+) -> Result<ZipBackupResult, VividError> {
+    let start = Instant::now();
+    let root = PathBuf::from(root);
+    let options = options.unwrap_or_default();
+    log::info!(
+        "[backup_workspace_to_zip] {} -> {} (exclude: {:?}, encrypted: {})",
+        root.display(),
+        destination,
+        options.exclude,
+        options.password.as_deref().is_some_and(|p| !p.is_empty())
+    );
+
+    if !root.is_dir() {
+        log::error!(
+            "[backup_workspace_to_zip] Directory does not exist: {}",
+            root.display()
+        );
+        return Err(VividError::dir_not_found(&root));
+    }
+    access::ensure_access(&app, &root, AccessKind::Read)?;
+    let output = archive_path(&root, Path::new(&destination));
+    if let Some(parent) = output.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|e| VividError::io("Failed to create directory", parent, &e))?;
+    }
+    access::ensure_access(&app, &output, AccessKind::Write)?;
+
+    let result =
+        tauri::async_runtime::spawn_blocking(move || write_archive(&root, &output, &options))
+            .await
+            .map_err(|e| format!("Backup task failed: {}", e))??;
+    log::info!(
+        "[backup_workspace_to_zip] ✓ Success: {} file(s), {} bytes -> {} ({} bytes) in {:?}",
+        result.files,
+        result.bytes,
+        result.path,
+        result.size,
+        start.elapsed()
+    );
+    Ok(result)
+}
+
+/// `名称 (restored).扩展名`，已存在时追加序号
+fn restored_copy_path(path: &Path) -> PathBuf {
+    let stem = path
+        .file_stem()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let ext = path
+        .extension()
+        .map(|ext| format!(".{}", ext.to_string_lossy()))
+        .unwrap_or_default();
+    let mut copy = path.with_file_name(format!("{} (restored){}", stem, ext));
+    let mut n = 2;
+    while copy.exists() {
+        copy = path.with_file_name(format!("{} (restored {}){}", stem, n, ext));
+        n += 1;
+    }
+    copy
+}
+
+fn extract_archive(
+    app: &AppHandle,
+    archive_path: &Path,
+    target: &Path,
+    options: &ZipRestoreOptions,
+) -> Result<ZipRestoreResult, VividError> {
+    let file = File::open(archive_path)
+        .map_err(|e| VividError::io("Failed to read file", archive_path, &e))?;
+    let mut archive = ZipArchive::new(file)
+        .map_err(|e| VividError::invalid_input(format!("Invalid zip archive: {}", e)))?;
+    let password = options.password.as_deref().filter(|p| !p.is_empty());
+    let encrypted =
+        (0..archive.len()).any(|i| archive.by_index_raw(i).is_ok_and(|e| e.encrypted()));
+    if encrypted && password.is_none() {
+        return Err(VividError::invalid_input(
+            "Archive is encrypted, password required",
+        ));
+    }
+    fs::create_dir_all(target)
+        .map_err(|e| VividError::io("Failed to create directory", target, &e))?;
+
+    let settings = app.state::<SettingsStore>().get();
+    let backups = app.state::<BackupStore>();
+    let mut result = ZipRestoreResult {
+        target: target.to_string_lossy().to_string(),
+        restored: Vec::new(),
+        skipped: Vec::new(),
+        failed: Vec::new(),
+    };
+    for i in 0..archive.len() {
+        let entry = match password {
+            Some(password) => archive.by_index_decrypt(i, password.as_bytes()),
+            None => archive.by_index(i),
+        };
+        let mut entry = match entry {
+            Ok(entry) => entry,
+            Err(ZipError::InvalidPassword) => {
+                return Err(VividError::invalid_input("Wrong password"));
+            }
+            Err(e) => {
+                result.failed.push(FailedEntry {
+                    path: format!("#{}", i),
+                    error: e.to_string(),
+                });
+                continue;
+            }
+        };
+        let Some(rel) = entry.enclosed_name() else {
+            log::warn!("[archive] Ignoring unsafe entry: {}", entry.name());
+            continue;
+        };
+        let path = target.join(rel);
+        if entry.is_dir() {
+            if let Err(e) = fs::create_dir_all(&path) {
+                result.failed.push(FailedEntry {
+                    path: path.to_string_lossy().to_string(),
+                    error: e.to_string(),
+                });
+            }
+            continue;
+        }
+
+        let modified = entry.last_modified().and_then(system_time);
+        let path = if path.exists() {
+            let newer = || {
+                let existing = fs::metadata(&path).and_then(|m| m.modified()).ok();
+                matches!((modified, existing), (Some(a), Some(b)) if a > b)
+            };
+            match options.overwrite {
+                OverwritePolicy::Overwrite => path,
+                OverwritePolicy::IfNewer if newer() => path,
+                OverwritePolicy::KeepBoth => restored_copy_path(&path),
+                OverwritePolicy::Skip | OverwritePolicy::IfNewer => {
+                    result.skipped.push(path.to_string_lossy().to_string());
+                    continue;
+                }
+            }
+        } else {
+            path
+        };
+
+        let mut data = Vec::new();
+        let written = entry
+            .read_to_end(&mut data)
+            .map_err(|e| match e.kind() {
+                io::ErrorKind::InvalidData if password.is_some() => {
+                    "Wrong password or corrupted archive".to_string()
+                }
+                _ => format!("Failed to read {}: {}", entry.name(), e),
+            })
+            .and_then(|_| {
+                if let Some(parent) = path.parent() {
+                    fs::create_dir_all(parent)
+                        .map_err(|e| format!("Failed to create directory: {}", e))?;
+                }
+                if path.exists() {
+                    backups.before_save(&settings.backup, &path);
+                }
+                storage::write_atomic(&path, &data)
+            });
+        match written {
+            Ok(()) => {
+                if let Some(time) = modified {
+                    let _ = File::options()
+                        .write(true)
+                        .open(&path)
+                        .and_then(|file| file.set_modified(time));
+                }
+                result.restored.push(path.to_string_lossy().to_string());
+            }
+            Err(error) => {
+                log::warn!("[archive] Failed to restore {}: {}", path.display(), error);
+                result.failed.push(FailedEntry {
+                    path: path.to_string_lossy().to_string(),
+                    error,
+                });
+            }
+        }
+    }
+
+    // 恢复到当前工作区时重建索引
+    let root = app.state::<Workspace>().root();
+    if let Some(root) = root.filter(|root| target.starts_with(root) || root.starts_with(target)) {
+        if !result.restored.is_empty() {
+            workspace::reindex(app, &root, &|_, _| {}, &|| false);
+        }
+    }
+    Ok(result)
+}
+
+// 把 zip 备份解压到目标目录，已存在的文件按覆盖策略处理
+#[tauri::command]
+pub async fn restore_workspace_from_zip(
+    app: AppHandle,
+    archive: String,
+    target: String,
+    options: Option<ZipRestoreOptions>,
+) -> Result<ZipRestoreResult, VividError> {
+    let start = Instant::now();
+    let archive = PathBuf::from(archive);
+    let target = PathBuf::from(target);
+    let options = options.unwrap_or_default();
+    log::info!(
+        "[restore_workspace_from_zip] {} -> {} (overwrite: {:?})",
+        archive.display(),
+        target.display(),
+        options.overwrite
+    );
+
+    if !archive.is_file() {
+        log::error!(
+            "[restore_workspace_from_zip] Archive does not exist: {}",
+            archive.display()
+        );
+        return Err(VividError::not_found(&archive));
+    }
+    access::ensure_access(&app, &archive, AccessKind::Read)?;
+    access::ensure_access(&app, &target, AccessKind::Write)?;
+
+    let handle = app.clone();
+    let result = tauri::async_runtime::spawn_blocking(move || {
+        extract_archive(&handle, &archive, &target, &options)
+    })
+    .await
+    .map_err(|e| format!("Restore task failed: {}", e))??;
+    log::info!(
+        "[restore_workspace_from_zip] ✓ Success: {} restored, {} skipped, {} failed in {:?}",
+        result.restored.len(),
+        result.skipped.len(),
+        result.failed.len(),
+        start.elapsed()
+    );
+    Ok(result)
+}
@@ -0,0 +1,1034 @@
+//! 文档资源（图片）管理
+//!
+//! 粘贴或拖入的图片保存到文档旁的资源目录中，按内容哈希去重，
+//! 返回可直接插入 Markdown 的相对链接；也可以把文档引用的远程图片下载到本地。
+//!
+//! 工作区中的资源可以列出并查找没有被任何文档引用的文件；移动或重命名资源时同时改写
+//! 所有文档中的 Markdown 链接、`[[...]]` 嵌入和 HTML `src`。
+
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::ops::Range;
+use std::path::{Component, Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use futures_util::StreamExt;
+use pulldown_cmark::{Event, LinkType, Tag};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use tauri::{AppHandle, Manager};
+
+use crate::access::{self, AccessKind};
+use crate::error::VividError;
+use crate::workspace::{self, Workspace};
+use crate::{frontmatter, links, markdown, render, search, storage, workspace_config};
+
+/// 默认资源目录（相对于文档所在目录）
+pub const DEFAULT_ASSETS_DIR: &str = "assets";
+/// 文件名中保留的哈希长度
+const HASH_LEN: usize = 12;
+/// 清洗后的文件名最大长度（字符）
+const MAX_STEM_LEN: usize = 48;
+/// 远程图片同时下载的数量
+const DOWNLOAD_CONCURRENCY: usize = 4;
+/// 单张远程图片的默认下载超时
+const DEFAULT_DOWNLOAD_TIMEOUT_SECS: u64 = 20;
+/// 单张远程图片的大小上限
+const MAX_DOWNLOAD_BYTES: usize = 20 * 1024 * 1024;
+
+/// 保存资源参数
+#[derive(Debug, Deserialize)]
+pub struct SaveAssetParams {
+    /// 当前文档路径
+    pub document_path: String,
+    /// 图片内容
+    pub bytes: Vec<u8>,
+    /// 建议文件名（如剪贴板或拖入文件的原名）
+    pub suggested_name: Option<String>,
+    /// 资源目录，相对路径基于文档所在目录，默认 `assets`
+    pub assets_dir: Option<String>,
+}
+
+/// 保存资源结果
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SaveAssetResult {
+    /// 资源文件的绝对路径
+    pub path: String,
+    /// 相对于文档的链接（已做 URL 转义）
+    pub link: String,
+    /// 可直接插入的 Markdown 图片语法
+    pub markdown: String,
+    /// 是否复用了内容相同的已有文件
+    pub deduplicated: bool,
+}
+
+/// 计算内容的 SHA-256 十六进制摘要
+pub fn content_hash(bytes: &[u8]) -> String {
+    Sha256::digest(bytes)
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
+
+/// 根据文件头识别图片格式，返回扩展名
+pub fn sniff_image_extension(bytes: &[u8]) -> Option<&'static str> {
+    if bytes.starts_with(b"\x89PNG\r\n\x1a\n") {
+        Some("png")
+    } else if bytes.starts_with(&[0xFF, 0xD8, 0xFF]) {
+        Some("jpg")
+    } else if bytes.starts_with(b"GIF87a") || bytes.starts_with(b"GIF89a") {
+        Some("gif")
+    } else if bytes.len() >= 12 && &bytes[0..4] == b"RIFF" && &bytes[8..12] == b"WEBP" {
+        Some("webp")
+    } else if bytes.starts_with(b"BM") {
+        Some("bmp")
+    } else if bytes.len() >= 12 && &bytes[4..8] == b"ftyp" && &bytes[8..12] == b"avif" {
+        Some("avif")
+    } else {
+        let head = String::from_utf8_lossy(&bytes[..bytes.len().min(256)]).to_ascii_lowercase();
+        if head.contains("<svg") || (head.starts_with("<?xml") && head.contains("svg")) {
+            Some("svg")
+        } else {
+            None
+        }
+    }
+}
+
+/// 清洗文件名：保留字母、数字、CJK 字符与 `-`/`_`，其余替换为 `-`
+pub fn sanitize_file_stem(name: &str) -> String {
+    let mut out = String::new();
+    for c in name.chars() {
+        if c.is_alphanumeric() || c == '-' || c == '_' {
+            out.push(c);
+        } else if !out.ends_with('-') {
+            out.push('-');
+        }
+    }
+    let out: String = out.trim_matches('-').chars().take(MAX_STEM_LEN).collect();
+    if out.is_empty() {
+        "image".to_string()
+    } else {
+        out
+    }
+}
+
+/// 解析资源目录：绝对路径原样使用，相对路径基于文档目录
+pub fn resolve_assets_dir(document_path: &Path, assets_dir: Option<&str>) -> PathBuf {
+    let dir = PathBuf::from(assets_dir.unwrap_or(DEFAULT_ASSETS_DIR));
+    if dir.is_absolute() {
+        return dir;
+    }
+    document_path.parent().map(|p| p.join(&dir)).unwrap_or(dir)
+}
+
+/// 文档的资源目录设置：命令参数 > 工作区配置 > 全局设置（与拖放、附件使用同一个目录）
+pub(crate) fn assets_dir_setting(
+    app: &AppHandle,
+    document: &Path,
+    requested: Option<String>,
+) -> String {
+    requested
+        .filter(|dir| !dir.trim().is_empty())
+        .unwrap_or_else(|| workspace_config::effective_for(app, document).assets_dir)
+}
+
+/// 计算 `target` 相对于 `base` 目录的路径，无法表示时返回 `None`
+pub fn relative_path(base: &Path, target: &Path) -> Option<PathBuf> {
+    let base: Vec<_> = base.components().collect();
+    let target: Vec<_> = target.components().collect();
+    let common = base
+        .iter()
+        .zip(target.iter())
+        .take_while(|(a, b)| a == b)
+        .count();
+    // 没有公共前缀（如 Windows 上不同盘符）时无法使用相对路径
+    let is_anchor = |c: &Component| matches!(c, Component::Prefix(_) | Component::RootDir);
+    if common == 0 && (base.first().is_some_and(is_anchor) || target.first().is_some_and(is_anchor))
+    {
+        return None;
+    }
+
+    let mut rel = PathBuf::new();
+    for _ in common..base.len() {
+        rel.push("..");
+    }
+    for component in &target[common..] {
+        rel.push(component);
+    }
+    Some(rel)
+}
+
+/// 将路径转为 Markdown 链接：统一 `/` 分隔并转义空格、括号等字符
+pub fn path_to_link(path: &Path) -> String {
+    let mut link = String::new();
+    for component in path.components() {
+        if component == Component::RootDir {
+            link.push('/');
+            continue;
+        }
+        if !link.is_empty() && !link.ends_with('/') {
+            link.push('/');
+        }
+        for c in component.as_os_str().to_string_lossy().chars() {
+            match c {
+                ' ' => link.push_str("%20"),
+                '(' => link.push_str("%28"),
+                ')' => link.push_str("%29"),
+                '<' => link.push_str("%3C"),
+                '>' => link.push_str("%3E"),
+                '#' => link.push_str("%23"),
+                '?' => link.push_str("%3F"),
+                _ => link.push(c),
+            }
+        }
+    }
+    link
+}
+
+/// 在资源目录中查找哈希相同的已有文件
+fn find_existing_asset(dir: &Path, hash: &str, bytes: &[u8]) -> Option<PathBuf> {
+    let marker = format!("-{}.", &hash[..HASH_LEN]);
+    fs::read_dir(dir)
+        .ok()?
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .find(|path| {
+            path.is_file()
+                && path
+                    .file_name()
+                    .and_then(|n| n.to_str())
+                    .is_some_and(|n| n.contains(&marker))
+                // 文件名只含哈希前缀，再比较完整内容以防碰撞或文件被修改
+                && fs::read(path).is_ok_and(|existing| existing == bytes)
+        })
+}
+
+/// 将资源写入资源目录（同内容只保存一份），返回文件路径与是否复用
+pub fn store_asset(
+    dir: &Path,
+    bytes: &[u8],
+    suggested_name: Option<&str>,
+) -> Result<(PathBuf, bool), String> {
+    let hash = content_hash(bytes);
+    if let Some(existing) = find_existing_asset(dir, &hash, bytes) {
+        return Ok((existing, true));
+    }
+
+    let suggested = suggested_name.map(Path::new);
+    let extension = sniff_image_extension(bytes)
+        .map(str::to_string)
+        .or_else(|| {
+            suggested
+                .and_then(|p| p.extension())
+                .and_then(|e| e.to_str())
+                .map(|e| e.to_ascii_lowercase())
+        })
+        .unwrap_or_else(|| "png".to_string());
+    let stem = suggested
+        .and_then(|p| p.file_stem())
+        .and_then(|s| s.to_str())
+        .map(sanitize_file_stem)
+        .unwrap_or_else(|| "image".to_string());
+
+    fs::create_dir_all(dir).map_err(|e| format!("Failed to create assets directory: {}", e))?;
+    let path = dir.join(format!("{}-{}.{}", stem, &hash[..HASH_LEN], extension));
+    fs::write(&path, bytes).map_err(|e| format!("Failed to write asset: {}", e))?;
+    Ok((path, false))
+}
+
+// 保存粘贴 / 拖入的图片
+#[tauri::command]
+pub fn save_asset(app: AppHandle, params: SaveAssetParams) -> Result<SaveAssetResult, VividError> {
+    let start = Instant::now();
+    let document = PathBuf::from(&params.document_path);
+
+    log::info!("[save_asset] Starting asset save operation");
+    log::debug!(
+        "[save_asset] Document: {}, size: {} bytes, suggested name: {:?}",
+        params.document_path,
+        params.bytes.len(),
+        params.suggested_name
+    );
+
+    if params.document_path.trim().is_empty() || document.parent().is_none() {
+        log::warn!("[save_asset] Document has no path, cannot resolve assets directory");
+        return Err(VividError::invalid_input(
+            "Document must be saved before adding assets",
+        ));
+    }
+    if params.bytes.is_empty() {
+        return Err(VividError::invalid_input("Asset content is empty"));
+    }
+
+    let assets_dir = assets_dir_setting(&app, &document, params.assets_dir.clone());
+    let dir = resolve_assets_dir(&document, Some(&assets_dir));
+    access::ensure_access(&app, &dir, AccessKind::Write)?;
+    let (path, deduplicated) = store_asset(&dir, &params.bytes, params.suggested_name.as_deref())
+        .map_err(|e| {
+        log::error!("[save_asset] {}", e);
+        e
+    })?;
+
+    let document_dir = document.parent().unwrap_or(Path::new("."));
+    let link = relative_path(document_dir, &path)
+        .map(|rel| path_to_link(&rel))
+        .unwrap_or_else(|| path_to_link(&path));
+    let alt = path
+        .file_stem()
+        .and_then(|s| s.to_str())
+        .map(|s| s.rsplit_once('-').map(|(stem, _)| stem).unwrap_or(s))
+        .unwrap_or("image")
+        .to_string();
+
+    log::info!(
+        "[save_asset] ✓ Success: {:?} (deduplicated: {}) in {:?}",
+        path,
+        deduplicated,
+        start.elapsed()
+    );
+
+    Ok(SaveAssetResult {
+        path: path.to_string_lossy().to_string(),
+        markdown: format!("![{}]({})", alt, link),
+        link,
+        deduplicated,
+    })
+}
+
+/// 本地化远程图片参数
+#[derive(Debug, Deserialize)]
+pub struct LocalizeRemoteImagesParams {
+    /// 文档路径
+    pub path: String,
+    /// 编辑器中的内容；提供时只返回改写后的内容，不写回文件
+    pub content: Option<String>,
+    /// 资源目录，规则同 `save_asset`
+    pub assets_dir: Option<String>,
+    /// 单张图片的下载超时（秒），默认 20
+    pub timeout_secs: Option<u64>,
+}
+
+/// 下载失败的图片
+#[derive(Debug, Serialize, Deserialize)]
+pub struct FailedImage {
+    pub url: String,
+    pub error: String,
+}
+
+/// 本地化远程图片结果
+#[derive(Debug, Serialize, Deserialize)]
+pub struct LocalizeRemoteImagesResult {
+    /// 改写链接后的文档内容
+    pub content: String,
+    /// 成功本地化的图片数量（按 URL 去重）
+    pub localized: usize,
+    pub failed: Vec<FailedImage>,
+    /// 是否已写回文件
+    pub saved: bool,
+}
+
+/// 文档中的一处图片链接
+struct ImageLink {
+    url: String,
+    /// 链接目标所在的源码片段（行内图片为 `![..](..)`，引用式图片为链接定义行）
+    span: Range<usize>,
+}
+
+/// 收集文档中的所有图片链接（包括引用式图片的链接定义）
+fn collect_image_links(content: &str) -> Vec<ImageLink> {
+    let mut links = Vec::new();
+    let mut reference_ids = Vec::new();
+    let mut iter = markdown::parser(content).into_offset_iter();
+
+    for (event, range) in iter.by_ref() {
+        if let Event::Start(Tag::Image {
+            link_type,
+            dest_url,
+            id,
+            ..
+        }) = event
+        {
+            match link_type {
+                LinkType::Reference | LinkType::Collapsed | LinkType::Shortcut => {
+                    reference_ids.push(id.to_string());
+                }
+                _ => links.push(ImageLink {
+                    url: dest_url.to_string(),
+                    span: range,
+                }),
+            }
+        }
+    }
+
+    let definitions = iter.reference_definitions();
+    for id in reference_ids {
+        if let Some(def) = definitions.get(&id) {
+            links.push(ImageLink {
+                url: def.dest.to_string(),
+                span: def.span.clone(),
+            });
+        }
+    }
+    links
+}
+
+/// 文档中引用的 http(s) 图片 URL（去重，保持出现顺序）
+pub fn remote_image_urls(content: &str) -> Vec<String> {
+    let mut urls: Vec<String> = Vec::new();
+    for link in collect_image_links(content) {
+        let is_remote = link.url.starts_with("http://") || link.url.starts_with("https://");
+        if is_remote && !urls.contains(&link.url) {
+            urls.push(link.url);
+        }
+    }
+    urls
+}
+
+/// 按映射表替换图片链接目标，未出现在映射表中的链接保持不变
+pub fn rewrite_image_links(content: &str, replacements: &HashMap<String, String>) -> String {
+    let mut edits: Vec<(Range<usize>, &str)> = Vec::new();
+    for link in collect_image_links(content) {
+        let Some(new_url) = replacements.get(&link.url) else {
+            continue;
+        };
+        // 在源码片段中定位原始 URL；含转义字符等无法直接匹配的链接跳过
+        if let Some(offset) = content[link.span.clone()].find(&link.url) {
+            let start = link.span.start + offset;
+            edits.push((start..start + link.url.len(), new_url));
+        }
+    }
+
+    edits.sort_by_key(|(range, _)| range.start);
+    edits.dedup_by_key(|(range, _)| range.start);
+
+    let mut out = String::with_capacity(content.len());
+    let mut last = 0;
+    for (range, new_url) in edits {
+        out.push_str(&content[last..range.start]);
+        out.push_str(new_url);
+        last = range.end;
+    }
+    out.push_str(&content[last..]);
+    out
+}
+
+/// 下载单张远程图片
+async fn download_image(client: &reqwest::Client, url: &str) -> Result<Vec<u8>, String> {
+    let response = client
+        .get(url)
+        .send()
+        .await
+        .and_then(|r| r.error_for_status())
+        .map_err(|e| format!("Request failed: {}", e))?;
+
+    if response
+        .content_length()
+        .is_some_and(|len| len as usize > MAX_DOWNLOAD_BYTES)
+    {
+        return Err("Image is too large".to_string());
+    }
+    let is_image_type = response
+        .headers()
+        .get(reqwest::header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|v| v.starts_with("image/"));
+
+    let bytes = response
+        .bytes()
+        .await
+        .map_err(|e| format!("Failed to read response: {}", e))?;
+    if bytes.len() > MAX_DOWNLOAD_BYTES {
+        return Err("Image is too large".to_string());
+    }
+    if !is_image_type && sniff_image_extension(&bytes).is_none() {
+        return Err("Response is not an image".to_string());
+    }
+    Ok(bytes.to_vec())
+}
+
+/// 从 URL 中取出文件名，作为本地资源的建议名称
+fn url_file_name(url: &str) -> Option<String> {
+    let url = url.split(['?', '#']).next()?;
+    let (_, rest) = url.split_once("://")?;
+    // 跳过主机名，只取路径的最后一段
+    let (_, path) = rest.split_once('/')?;
+    let name = path.rsplit('/').next()?;
+    if name.is_empty() {
+        return None;
+    }
+    Some(render::percent_decode(name))
+}
+
+// 下载文档中的远程图片并改写为本地链接
+#[tauri::command]
+pub async fn localize_remote_images(
+    app: AppHandle,
+    params: LocalizeRemoteImagesParams,
+) -> Result<LocalizeRemoteImagesResult, VividError> {
+    let start = Instant::now();
+    let document = PathBuf::from(&params.path);
+    let kind = if params.content.is_none() {
+        AccessKind::Write
+    } else {
+        AccessKind::Read
+    };
+    access::ensure_access(&app, &document, kind)?;
+    let assets_dir = assets_dir_setting(&app, &document, params.assets_dir.clone());
+    let dir = resolve_assets_dir(&document, Some(&assets_dir));
+    access::ensure_access(&app, &dir, AccessKind::Write)?;
+
+    log::info!("[localize_remote_images] Starting remote image localization");
+    log::debug!("[localize_remote_images] Document: {}", params.path);
+
+    if document.parent().is_none() {
+        return Err(VividError::invalid_input(
+            "Document must be saved before adding assets",
+        ));
+    }
+    let write_back = params.content.is_none();
+    let content = match params.content {
+        Some(content) => content,
+        None => fs::read_to_string(&document).map_err(|e| {
+            log::error!("[localize_remote_images] Failed to read document: {}", e);
+            format!("Failed to read file: {}", e)
+        })?,
+    };
+
+    let urls = remote_image_urls(&content);
+    log::debug!(
+        "[localize_remote_images] Found {} remote image(s)",
+        urls.len()
+    );
+    if urls.is_empty() {
+        return Ok(LocalizeRemoteImagesResult {
+            content,
+            localized: 0,
+            failed: Vec::new(),
+            saved: false,
+        });
+    }
+
+    let timeout = Duration::from_secs(
+        params
+            .timeout_secs
+            .unwrap_or(DEFAULT_DOWNLOAD_TIMEOUT_SECS)
+            .max(1),
+    );
+    let client = reqwest::Client::builder()
+        .timeout(timeout)
+        .user_agent(concat!("VividMark/", env!("CARGO_PKG_VERSION")))
+        .build()
+        .map_err(|e| format!("Failed to create HTTP client: {}", e))?;
+
+    let downloads: Vec<(String, Result<Vec<u8>, String>)> = futures_util::stream::iter(urls)
+        .map(|url| {
+            let client = &client;
+            async move {
+                let result = download_image(client, &url).await;
+                (url, result)
+            }
+        })
+        .buffer_unordered(DOWNLOAD_CONCURRENCY)
+        .collect()
+        .await;
+
+    let document_dir = document.parent().unwrap_or(Path::new("."));
+    let mut replacements = HashMap::new();
+    let mut failed = Vec::new();
+
+    for (url, result) in downloads {
+        let stored = result.and_then(|bytes| {
+            store_asset(&dir, &bytes, url_file_name(&url).as_deref()).map(|(path, _)| path)
+        });
+        match stored {
+            Ok(path) => {
+                let link = relative_path(document_dir, &path)
+                    .map(|rel| path_to_link(&rel))
+                    .unwrap_or_else(|| path_to_link(&path));
+                log::debug!("[localize_remote_images] {} -> {}", url, link);
+                replacements.insert(url, link);
+            }
+            Err(error) => {
+                log::warn!(
+                    "[localize_remote_images] Failed to localize {}: {}",
+                    url,
+                    error
+                );
+                failed.push(FailedImage { url, error });
+            }
+        }
+    }
+
+    let localized = replacements.len();
+    let content = rewrite_image_links(&content, &replacements);
+    let saved = write_back && localized > 0;
+    if saved {
+        fs::write(&document, &content).map_err(|e| {
+            log::error!("[localize_remote_images] Failed to write document: {}", e);
+            format!("Failed to write file: {}", e)
+        })?;
+    }
+
+    log::info!(
+        "[localize_remote_images] ✓ Success: {} localized, {} failed in {:?}",
+        localized,
+        failed.len(),
+        start.elapsed()
+    );
+
+    Ok(LocalizeRemoteImagesResult {
+        content,
+        localized,
+        failed,
+        saved,
+    })
+}
+
+/// 资源管理中视为资源的扩展名：图片和常见附件
+const ASSET_EXTENSIONS: &[&str] = &[
+    "png", "jpg", "jpeg", "gif", "webp", "svg", "bmp", "ico", "avif", "pdf", "mp3", "wav", "ogg",
+    "mp4", "webm", "mov", "zip",
+];
+
+/// 工作区中的资源文件
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AssetInfo {
+    pub path: String,
+    pub name: String,
+    pub size: u64,
+    /// 修改时间（Unix 毫秒）
+    pub modified: i64,
+    /// 引用该资源的文档
+    pub referenced_by: Vec<String>,
+}
+
+/// 移动资源结果
+#[derive(Debug, Serialize, Deserialize)]
+pub struct MoveAssetResult {
+    pub path: String,
+    /// 改写了引用的文档
+    pub updated_files: Vec<String>,
+    pub updated_links: usize,
+}
+
+/// 对源码的一处替换
+pub(crate) type LinkEdit = markdown::Replacement;
+
+/// 文档中对本地文件的一处引用
+pub(crate) struct AssetRef {
+    /// 解码后的目标（不含 `#` 和 `?` 之后的部分）
+    pub target: String,
+    /// `[[...]]` 按文件名解析，其它按相对于文档的路径解析
+    pub wiki: bool,
+    /// 源码中目标文本的字节区间，无法定位时为空（只计数，不改写）
+    pub range: Option<Range<usize>>,
+}
+
+fn is_asset(path: &Path) -> bool {
+    path.extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| ASSET_EXTENSIONS.iter().any(|e| ext.eq_ignore_ascii_case(e)))
+}
+
+/// 递归列出目录下的资源文件，跳过隐藏目录和依赖目录
+fn asset_files(root: &Path) -> Vec<PathBuf> {
+    let mut files = Vec::new();
+    let mut pending = vec![root.to_path_buf()];
+    while let Some(dir) = pending.pop() {
+        let Ok(entries) = fs::read_dir(&dir) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            if workspace::is_ignored(&entry.file_name().to_string_lossy()) {
+                continue;
+            }
+            let path = entry.path();
+            match entry.file_type() {
+                Ok(t) if t.is_dir() => pending.push(path),
+                Ok(t) if t.is_file() && is_asset(&path) => files.push(path),
+                _ => {}
+            }
+        }
+    }
+    files.sort();
+    files
+}
+
+/// 在 `span` 中定位原始目标文本
+fn locate(content: &str, span: &Range<usize>, raw: &str) -> Option<Range<usize>> {
+    if raw.is_empty() {
+        return None;
+    }
+    content[span.clone()]
+        .rfind(raw)
+        .map(|offset| span.start + offset..span.start + offset + raw.len())
+}
+
+/// 收集文档中的所有本地文件引用：Markdown 链接和图片、`[[...]]`、HTML 的 `src` / `href`
+pub(crate) fn collect_asset_refs(content: &str) -> Vec<AssetRef> {
+    let body_start = frontmatter::find_front_matter(content).map_or(0, |block| block.body_start);
+    let body = &content[body_start..];
+    let html_attr = Regex::new(r#"(?i)\b(?:src|href)\s*=\s*["']([^"']+)["']"#).ok();
+    let mut refs = Vec::new();
+    let mut push = |raw: &str, wiki: bool, range: Option<Range<usize>>| {
+        if raw.is_empty() || links::is_external(raw) {
+            return;
+        }
+        let target = raw.split(['#', '?']).next().unwrap_or(raw);
+        refs.push(AssetRef {
+            target: if wiki {
+                target.to_string()
+            } else {
+                render::percent_decode(target)
+            },
+            wiki,
+            // 只替换路径部分，保留 `#标题` 和查询参数
+            range: range.map(|range| range.start..range.start + target.len()),
+        });
+    };
+
+    let mut reference_ids = Vec::new();
+    let mut iter = markdown::parser(body).into_offset_iter();
+    for (event, range) in iter.by_ref() {
+        let range = body_start + range.start..body_start + range.end;
+        match event {
+            Event::Start(Tag::Link {
+                link_type,
+                dest_url,
+                id,
+                ..
+            })
+            | Event::Start(Tag::Image {
+                link_type,
+                dest_url,
+                id,
+                ..
+            }) => match link_type {
+                LinkType::Reference | LinkType::Collapsed | LinkType::Shortcut => {
+                    reference_ids.push(id.to_string());
+                }
+                _ => push(&dest_url, false, locate(content, &range, &dest_url)),
+            },
+            Event::Html(html) | Event::InlineHtml(html) => {
+                for caps in html_attr.iter().flat_map(|re| re.captures_iter(&html)) {
+                    if let Some(m) = caps.get(1) {
+                        let start = range.start + m.start();
+                        let exact = content.get(start..start + m.len()) == Some(m.as_str());
+                        push(m.as_str(), false, exact.then(|| start..start + m.len()));
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+    let definitions = iter.reference_definitions();
+    reference_ids.sort();
+    reference_ids.dedup();
+    for id in reference_ids {
+        if let Some(def) = definitions.get(&id) {
+            let span = body_start + def.span.start..body_start + def.span.end;
+            push(&def.dest, false, locate(content, &span, &def.dest));
+        }
+    }
+
+    for link in links::extract_links(content) {
+        if matches!(link.kind, links::LinkKind::Wiki | links::LinkKind::Embed) {
+            let span = link.start..link.end;
+            push(&link.target, true, locate(content, &span, &link.target));
+        }
+    }
+    refs
+}
+
+/// 工作区中的资源，用于解析引用
+struct AssetLookup {
+    root: PathBuf,
+    paths: HashSet<PathBuf>,
+    /// 小写文件名 → 同名资源
+    by_name: HashMap<String, Vec<PathBuf>>,
+}
+
+impl AssetLookup {
+    fn new(root: &Path, assets: &[PathBuf]) -> Self {
+        let mut by_name: HashMap<String, Vec<PathBuf>> = HashMap::new();
+        for path in assets {
+            if let Some(name) = path.file_name() {
+                by_name
+                    .entry(name.to_string_lossy().to_lowercase())
+                    .or_default()
+                    .push(path.clone());
+            }
+        }
+        AssetLookup {
+            root: root.to_path_buf(),
+            paths: assets.iter().cloned().collect(),
+            by_name,
+        }
+    }
+
+    /// 引用可能指向的资源；`[[name.png]]` 有多个同名资源时全部返回，避免误判为未引用
+    fn resolve(&self, document: &Path, reference: &AssetRef) -> Vec<PathBuf> {
+        let dir = document.parent().unwrap_or(Path::new("."));
+        if !reference.wiki {
+            let path =
+                links::normalize_path(&render::resolve_local_path(&reference.target, Some(dir)));
+            return self.paths.get(&path).cloned().into_iter().collect();
+        }
+
+        for base in [dir, self.root.as_path()] {
+            let path = links::normalize_path(&base.join(&reference.target));
+            if self.paths.contains(&path) {
+                return vec![path];
+            }
+        }
+        let target = reference.target.replace('\\', "/").to_lowercase();
+        let name = target.rsplit('/').next().unwrap_or(&target);
+        self.by_name
+            .get(name)
+            .map(|paths| {
+                paths
+                    .iter()
+                    .filter(|path| {
+                        path.to_string_lossy()
+                            .replace('\\', "/")
+                            .to_lowercase()
+                            .ends_with(&target)
+                    })
+                    .cloned()
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+}
+
+/// 列出资源并统计引用它们的文档
+fn scan_assets(root: &Path) -> Vec<AssetInfo> {
+    let assets = asset_files(root);
+    let lookup = AssetLookup::new(root, &assets);
+    let mut referenced_by: HashMap<PathBuf, Vec<String>> = HashMap::new();
+    for document in workspace::markdown_files(root) {
+        let Ok(content) = fs::read_to_string(&document) else {
+            continue;
+        };
+        let source = document.to_string_lossy().to_string();
+        for reference in collect_asset_refs(&content) {
+            for asset in lookup.resolve(&document, &reference) {
+                let docs = referenced_by.entry(asset).or_default();
+                if !docs.contains(&source) {
+                    docs.push(source.clone());
+                }
+            }
+        }
+    }
+
+    assets
+        .into_iter()
+        .map(|path| {
+            let (modified, size) = search::file_stamp(&path).unwrap_or((0, 0));
+            AssetInfo {
+                name: path
+                    .file_name()
+                    .map(|n| n.to_string_lossy().to_string())
+                    .unwrap_or_default(),
+                referenced_by: referenced_by.remove(&path).unwrap_or_default(),
+                path: path.to_string_lossy().to_string(),
+                size: size as u64,
+                modified,
+            }
+        })
+        .collect()
+}
+
+/// 移动后引用的新写法
+fn moved_link(
+    document: &Path,
+    root: &Path,
+    reference: &AssetRef,
+    old: &Path,
+    new: &Path,
+) -> Option<String> {
+    if reference.wiki {
+        // `[[name.png]]` 按文件名解析，只在文件名变化时改写；带目录的按工作区相对路径改写
+        if reference.target.contains('/') || reference.target.contains('\\') {
+            let rel = relative_path(root, new)?;
+            return Some(rel.to_string_lossy().replace('\\', "/"));
+        }
+        if old.file_name() == new.file_name() {
+            return None;
+        }
+        return new.file_name().map(|n| n.to_string_lossy().to_string());
+    }
+    let dir = document.parent()?;
+    Some(
+        relative_path(dir, new)
+            .map(|rel| path_to_link(&rel))
+            .unwrap_or_else(|| path_to_link(new)),
+    )
+}
+
+pub(crate) fn root_or_parent(root: Option<PathBuf>, path: &Path) -> PathBuf {
+    match root {
+        Some(root) if path.starts_with(&root) => root,
+        _ => path.parent().map(Path::to_path_buf).unwrap_or_default(),
+    }
+}
+
+/// 移动资源并改写引用它的文档
+fn move_and_relink(
+    root: &Path,
+    old_path: &Path,
+    new_path: &Path,
+) -> Result<MoveAssetResult, VividError> {
+    // 先找出引用（移动前旧路径仍然存在），再移动文件，最后改写文档
+    let mut assets = asset_files(root);
+    if !assets.iter().any(|path| path == old_path) {
+        assets.push(old_path.to_path_buf());
+    }
+    let lookup = AssetLookup::new(root, &assets);
+    let mut edits: Vec<(PathBuf, String, Vec<LinkEdit>)> = Vec::new();
+    for document in workspace::markdown_files(root) {
+        let Ok(content) = fs::read_to_string(&document) else {
+            continue;
+        };
+        let changes: Vec<LinkEdit> = collect_asset_refs(&content)
+            .into_iter()
+            .filter(|reference| {
+                matches!(lookup.resolve(&document, reference).as_slice(), [path] if path == old_path)
+            })
+            .filter_map(|reference| {
+                let link = moved_link(&document, root, &reference, old_path, new_path)?;
+                Some((reference.range?, link))
+            })
+            .collect();
+        if !changes.is_empty() {
+            edits.push((document, content, changes));
+        }
+    }
+
+    if let Some(parent) = new_path.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|e| VividError::io("Failed to create directory", parent, &e))?;
+    }
+    if fs::rename(old_path, new_path).is_err() {
+        // 跨磁盘移动时改为复制后删除
+        fs::copy(old_path, new_path)
+            .map_err(|e| VividError::io("Failed to move file", old_path, &e))?;
+        fs::remove_file(old_path)
+            .map_err(|e| VividError::io("Failed to remove file", old_path, &e))?;
+    }
+
+    let mut updated_files = Vec::new();
+    let mut updated_links = 0;
+    for (document, content, mut changes) in edits {
+        let out = markdown::apply_edits(&content, &mut changes);
+        if let Err(e) = storage::write_atomic(&document, out.as_bytes()) {
+            log::error!(
+                "[move_asset] Failed to update {}: {}",
+                document.display(),
+                e
+            );
+            continue;
+        }
+        updated_links += changes.len();
+        updated_files.push(document.to_string_lossy().to_string());
+    }
+
+    Ok(MoveAssetResult {
+        path: new_path.to_string_lossy().to_string(),
+        updated_files,
+        updated_links,
+    })
+}
+
+// 列出工作区中的资源文件及引用它们的文档
+#[tauri::command]
+pub async fn list_assets(app: AppHandle, root: String) -> Result<Vec<AssetInfo>, VividError> {
+    let start = Instant::now();
+    let root = PathBuf::from(root);
+    access::ensure_access(&app, &root, AccessKind::Read)?;
+    if !root.is_dir() {
+        log::error!("[list_assets] Not a directory: {}", root.display());
+        return Err(VividError::dir_not_found(&root));
+    }
+    let assets = tauri::async_runtime::spawn_blocking(move || scan_assets(&root))
+        .await
+        .map_err(|e| format!("Asset scan failed: {}", e))?;
+    log::info!(
+        "[list_assets] ✓ Success: {} asset(s) in {:?}",
+        assets.len(),
+        start.elapsed()
+    );
+    Ok(assets)
+}
+
+// 查找没有被任何文档引用的资源
+#[tauri::command]
+pub async fn find_orphaned_assets(
+    app: AppHandle,
+    root: String,
+) -> Result<Vec<AssetInfo>, VividError> {
+    let start = Instant::now();
+    let root = PathBuf::from(root);
+    access::ensure_access(&app, &root, AccessKind::Read)?;
+    if !root.is_dir() {
+        log::error!("[find_orphaned_assets] Not a directory: {}", root.display());
+        return Err(VividError::dir_not_found(&root));
+    }
+    let orphans: Vec<AssetInfo> = tauri::async_runtime::spawn_blocking(move || {
+        scan_assets(&root)
+            .into_iter()
+            .filter(|asset| asset.referenced_by.is_empty())
+            .collect()
+    })
+    .await
+    .map_err(|e| format!("Asset scan failed: {}", e))?;
+    log::info!(
+        "[find_orphaned_assets] ✓ Success: {} orphaned asset(s) in {:?}",
+        orphans.len(),
+        start.elapsed()
+    );
+    Ok(orphans)
+}
+
+// 移动或重命名资源，并改写工作区中所有指向它的链接
+#[tauri::command]
+pub async fn move_asset(
+    app: AppHandle,
+    old: String,
+    new: String,
+) -> Result<MoveAssetResult, VividError> {
+    let start = Instant::now();
+    let old_path = links::normalize_path(Path::new(&old));
+    let new_path = links::normalize_path(Path::new(&new));
+    log::info!("[move_asset] {} -> {}", old, new);
+    access::ensure_access(&app, &old_path, AccessKind::Write)?;
+    access::ensure_access(&app, &new_path, AccessKind::Write)?;
+
+    if !old_path.is_file() {
+        log::error!("[move_asset] File does not exist: {}", old);
+        return Err(VividError::not_found(&old_path));
+    }
+    if new_path.exists() {
+        log::error!("[move_asset] Target already exists: {}", new);
+        return Err(VividError::conflict(
+            &new_path,
+            format!("File already exists: {}", new),
+        ));
+    }
+    let root = root_or_parent(app.state::<Workspace>().root(), &old_path);
+    // 改写链接时会写入该目录下的文档
+    access::ensure_access(&app, &root, AccessKind::Write)?;
+
+    let result =
+        tauri::async_runtime::spawn_blocking(move || move_and_relink(&root, &old_path, &new_path))
+            .await
+            .map_err(|e| format!("Move task failed: {}", e))??;
+    log::info!(
+        "[move_asset] ✓ Success: {} link(s) in {} file(s) updated in {:?}",
+        result.updated_links,
+        result.updated_files.len(),
+        start.elapsed()
+    );
+    Ok(result)
+}
@@ -0,0 +1,807 @@
+//! 附件
+//!
+//! 拖入或粘贴的任意文件（音频、视频、压缩包等）与图片一样保存到文档旁的资源目录，
+//! 按内容去重，返回可直接插入的链接：图片用 `![...]()`，其它文件用 `[...]()`。
+//!
+//! `get_attachment_metadata` 读取附件的 MIME 类型、大小，以及从文件头解析出的图片尺寸、
+//! 音视频时长（WAV、FLAC、Ogg Vorbis / Opus、MP3、MP4 / M4A / MOV），不解码内容。
+//!
+//! 音频附件可以转写为文字：转写后端实现 [`TranscriptionBackend`]，目前由设置的
+//! `transcription.command` 提供（如 whisper.cpp），转写结果写入音频旁的
+//! `<文件名>.transcript.md`。设置 `transcription.auto` 后添加音频附件时在后台自动转写，
+//! 完成后发送 `attachment-transcribed` 事件。
+
+use std::fs::{self, File};
+use std::io::{Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::access::{self, AccessKind};
+use crate::error::VividError;
+use crate::settings::{SettingsStore, TranscriptionSettings};
+use crate::{assets, diagram, render, storage, workspace_config};
+
+/// 后台转写完成事件，负载为 [`TranscriptionEvent`]
+pub const TRANSCRIBED_EVENT: &str = "attachment-transcribed";
+/// 单个附件的大小上限
+const MAX_ATTACHMENT_BYTES: u64 = 100 * 1024 * 1024;
+/// 解析 MP4 时读取的 `moov` 盒子大小上限
+const MAX_MOOV_BYTES: u64 = 64 * 1024 * 1024;
+/// 转写文件的后缀
+const TRANSCRIPT_SUFFIX: &str = ".transcript.md";
+
+/// 附件类别
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AttachmentKind {
+    Image,
+    Audio,
+    Video,
+    Pdf,
+    Other,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AttachmentMetadata {
+    pub path: String,
+    pub name: String,
+    pub size: u64,
+    pub mime: String,
+    pub kind: AttachmentKind,
+    /// 图片或视频的宽高（像素）
+    pub width: Option<u32>,
+    pub height: Option<u32>,
+    /// 音视频时长（秒）
+    pub duration_secs: Option<f64>,
+    /// 音频的采样率和声道数
+    pub sample_rate: Option<u32>,
+    pub channels: Option<u16>,
+    /// 已有的转写文件
+    pub transcript: Option<String>,
+}
+
+/// 保存附件参数
+#[derive(Debug, Deserialize)]
+pub struct SaveAttachmentParams {
+    /// 当前文档路径
+    pub document_path: String,
+    pub bytes: Vec<u8>,
+    /// 原文件名，决定扩展名和链接文字
+    pub name: String,
+    /// 资源目录，相对路径基于文档所在目录，默认使用设置（含工作区配置）中的资源目录
+    pub assets_dir: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SaveAttachmentResult {
+    /// 附件的绝对路径
+    pub path: String,
+    /// 可直接插入的 Markdown
+    pub markdown: String,
+    pub kind: AttachmentKind,
+    /// 是否复用了内容相同的已有文件
+    pub deduplicated: bool,
+    /// 是否已开始后台转写
+    pub transcribing: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TranscriptResult {
+    /// 音频文件
+    pub source: String,
+    /// 转写文件
+    pub transcript: String,
+    pub text: String,
+    pub backend: String,
+}
+
+/// 后台转写的结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TranscriptionEvent {
+    pub source: String,
+    pub result: Option<TranscriptResult>,
+    pub error: Option<String>,
+}
+
+/// 从文件头解析出的媒体信息
+#[derive(Debug, Default, PartialEq)]
+struct MediaInfo {
+    duration_secs: Option<f64>,
+    sample_rate: Option<u32>,
+    channels: Option<u16>,
+    width: Option<u32>,
+    height: Option<u32>,
+}
+
+/// 转写后端：把音频文件转为文字
+pub trait TranscriptionBackend: Send + Sync {
+    /// 写入转写文件的后端名称
+    fn name(&self) -> String;
+    fn transcribe(&self, audio: &Path, language: Option<&str>) -> Result<String, String>;
+}
+
+/// 运行设置中的转写命令，读取标准输出
+struct CommandBackend {
+    settings: TranscriptionSettings,
+    command: String,
+}
+
+impl TranscriptionBackend for CommandBackend {
+    fn name(&self) -> String {
+        Path::new(&self.command)
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().to_string())
+            .unwrap_or_else(|| self.command.clone())
+    }
+
+    fn transcribe(&self, audio: &Path, language: Option<&str>) -> Result<String, String> {
+        let input = audio.to_string_lossy();
+        let language = language.unwrap_or("auto");
+        let mut command = Command::new(&self.command);
+        command.args(self.settings.args.iter().map(|arg| {
+            arg.replace("{input}", &input)
+                .replace("{language}", language)
+        }));
+        if let Some(dir) = audio.parent() {
+            command.current_dir(dir);
+        }
+        let output = diagram::run_command(
+            command,
+            &self.command,
+            "",
+            Duration::from_secs(self.settings.timeout_secs),
+        )?;
+        let text = String::from_utf8_lossy(&output).trim().to_string();
+        if text.is_empty() {
+            return Err(format!("{} returned no text", self.command));
+        }
+        Ok(text)
+    }
+}
+
+/// 设置中配置的转写后端
+pub fn configured_backend(
+    settings: &TranscriptionSettings,
+) -> Option<Box<dyn TranscriptionBackend>> {
+    let command = settings
+        .command
+        .as_deref()
+        .map(str::trim)
+        .filter(|c| !c.is_empty())?;
+    Some(Box::new(CommandBackend {
+        settings: settings.clone(),
+        command: command.to_string(),
+    }))
+}
+
+/// 按扩展名判断 MIME 类型
+pub fn mime_type(path: &Path) -> &'static str {
+    if let Some(mime) = render::image_mime_type(path) {
+        return mime;
+    }
+    let ext = path
+        .extension()
+        .map(|ext| ext.to_string_lossy().to_ascii_lowercase())
+        .unwrap_or_default();
+    match ext.as_str() {
+        "pdf" => "application/pdf",
+        "mp3" => "audio/mpeg",
+        "wav" => "audio/wav",
+        "flac" => "audio/flac",
+        "ogg" | "oga" => "audio/ogg",
+        "opus" => "audio/opus",
+        "m4a" => "audio/mp4",
+        "aac" => "audio/aac",
+        "webm" => "video/webm",
+        "mp4" | "m4v" => "video/mp4",
+        "mov" => "video/quicktime",
+        "mkv" => "video/x-matroska",
+        "txt" => "text/plain",
+        "csv" => "text/csv",
+        "json" => "application/json",
+        "zip" => "application/zip",
+        "doc" => "application/msword",
+        "docx" => "application/vnd.openxmlformats-officedocument.wordprocessingml.document",
+        "xlsx" => "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet",
+        "pptx" => "application/vnd.openxmlformats-officedocument.presentationml.presentation",
+        _ => "application/octet-stream",
+    }
+}
+
+fn kind_of(mime: &str) -> AttachmentKind {
+    match mime.split('/').next() {
+        Some("image") => AttachmentKind::Image,
+        Some("audio") => AttachmentKind::Audio,
+        Some("video") => AttachmentKind::Video,
+        _ if mime == "application/pdf" => AttachmentKind::Pdf,
+        _ => AttachmentKind::Other,
+    }
+}
+
+fn u16_le(b: &[u8], at: usize) -> Option<u16> {
+    Some(u16::from_le_bytes(b.get(at..at + 2)?.try_into().ok()?))
+}
+
+fn u32_le(b: &[u8], at: usize) -> Option<u32> {
+    Some(u32::from_le_bytes(b.get(at..at + 4)?.try_into().ok()?))
+}
+
+fn u32_be(b: &[u8], at: usize) -> Option<u32> {
+    Some(u32::from_be_bytes(b.get(at..at + 4)?.try_into().ok()?))
+}
+
+fn u64_be(b: &[u8], at: usize) -> Option<u64> {
+    Some(u64::from_be_bytes(b.get(at..at + 8)?.try_into().ok()?))
+}
+
+fn read_head(file: &mut File, len: usize) -> Vec<u8> {
+    let mut head = Vec::with_capacity(len);
+    let _ = file.take(len as u64).read_to_end(&mut head);
+    head
+}
+
+/// WAV：`fmt ` 块中的采样参数和 `data` 块大小
+fn probe_wav(head: &[u8]) -> Option<MediaInfo> {
+    let mut info = MediaInfo::default();
+    let mut byte_rate = None;
+    let mut at = 12;
+    while at + 8 <= head.len() {
+        let id = &head[at..at + 4];
+        let size = u32_le(head, at + 4)? as usize;
+        if id == b"fmt " {
+            info.channels = u16_le(head, at + 10);
+            info.sample_rate = u32_le(head, at + 12);
+            byte_rate = u32_le(head, at + 16);
+        } else if id == b"data" {
+            let rate = byte_rate.filter(|&r| r > 0)?;
+            info.duration_secs = Some(size as f64 / rate as f64);
+            return Some(info);
+        }
+        at += 8 + size + size % 2;
+    }
+    None
+}
+
+/// FLAC：STREAMINFO 中的采样率、声道数和总采样数
+fn probe_flac(head: &[u8]) -> Option<MediaInfo> {
+    let bits = u64_be(head, 18)?;
+    let sample_rate = (bits >> 44) as u32;
+    let channels = ((bits >> 41) & 0x7) as u16 + 1;
+    let total = bits & 0xF_FFFF_FFFF;
+    Some(MediaInfo {
+        duration_secs: (sample_rate > 0 && total > 0).then(|| total as f64 / sample_rate as f64),
+        sample_rate: Some(sample_rate),
+        channels: Some(channels),
+        ..Default::default()
+    })
+}
+
+/// Ogg：第一页的 Vorbis / Opus 头和最后一页的 granule position
+fn probe_ogg(file: &mut File, head: &[u8], size: u64) -> Option<MediaInfo> {
+    // (granule 的计数频率, 原始采样率, 声道数, 开头跳过的采样数)
+    let (rate, sample_rate, channels, pre_skip) = if let Some(at) = find(head, b"\x01vorbis") {
+        let rate = u32_le(head, at + 12)?;
+        (rate, rate, head.get(at + 11).map(|&c| c as u16), 0)
+    } else if let Some(at) = find(head, b"OpusHead") {
+        // Opus 的 granule position 总是以 48 kHz 计
+        (
+            48_000,
+            u32_le(head, at + 12).filter(|&r| r > 0).unwrap_or(48_000),
+            head.get(at + 9).map(|&c| c as u16),
+            u16_le(head, at + 10)? as u64,
+        )
+    } else {
+        return None;
+    };
+
+    let tail_len = size.min(64 * 1024);
+    file.seek(SeekFrom::Start(size - tail_len)).ok()?;
+    let tail = read_head(file, tail_len as usize);
+    let last = tail.windows(4).rposition(|w| w == b"OggS")?;
+    let granule = u64::from_le_bytes(tail.get(last + 6..last + 14)?.try_into().ok()?);
+    Some(MediaInfo {
+        duration_secs: (rate > 0).then(|| granule.saturating_sub(pre_skip) as f64 / rate as f64),
+        sample_rate: Some(sample_rate),
+        channels,
+        ..Default::default()
+    })
+}
+
+fn find(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack.windows(needle.len()).position(|w| w == needle)
+}
+
+/// MP3（MPEG Layer III）：有 Xing / Info 头时按帧数计算，否则按第一帧的码率估算
+fn probe_mp3(head: &[u8], size: u64) -> Option<MediaInfo> {
+    let mut at = 0;
+    if head.starts_with(b"ID3") && head.len() >= 10 {
+        // ID3v2 标签长度为 4 个 7 位字节
+        let len = head[6..10]
+            .iter()
+            .fold(0usize, |acc, &b| (acc << 7) | (b & 0x7F) as usize);
+        at = 10 + len;
+    }
+    while at + 4 <= head.len() && !(head[at] == 0xFF && head[at + 1] & 0xE0 == 0xE0) {
+        at += 1;
+    }
+    let header = u32_be(head, at)?;
+    let version = (header >> 19) & 0x3; // 0: 2.5, 2: 2, 3: 1
+    let layer = (header >> 17) & 0x3; // 1: Layer III
+    if layer != 1 || version == 1 {
+        return None;
+    }
+    let bitrate_index = ((header >> 12) & 0xF) as usize;
+    let rate_index = ((header >> 10) & 0x3) as usize;
+    let mono = (header >> 6) & 0x3 == 3;
+    const MPEG1_BITRATES: [u32; 15] = [
+        0, 32, 40, 48, 56, 64, 80, 96, 112, 128, 160, 192, 224, 256, 320,
+    ];
+    const MPEG2_BITRATES: [u32; 15] =
+        [0, 8, 16, 24, 32, 40, 48, 56, 64, 80, 96, 112, 128, 144, 160];
+    let sample_rate = match version {
+        3 => [44_100, 48_000, 32_000].get(rate_index),
+        2 => [22_050, 24_000, 16_000].get(rate_index),
+        _ => [11_025, 12_000, 8_000].get(rate_index),
+    }
+    .copied()?;
+    let bitrate = if version == 3 {
+        MPEG1_BITRATES.get(bitrate_index)
+    } else {
+        MPEG2_BITRATES.get(bitrate_index)
+    }
+    .copied()?;
+    let samples_per_frame = if version == 3 { 1152 } else { 576 };
+
+    let side_info = match (version == 3, mono) {
+        (true, false) => 32,
+        (true, true) | (false, false) => 17,
+        (false, true) => 9,
+    };
+    let xing = at + 4 + side_info;
+    let frames = head
+        .get(xing..xing + 4)
+        .filter(|tag| *tag == b"Xing" || *tag == b"Info")
+        .and_then(|_| u32_be(head, xing + 4))
+        .filter(|flags| flags & 1 == 1)
+        .and_then(|_| u32_be(head, xing + 8));
+    let duration = match frames {
+        Some(frames) => Some(frames as f64 * samples_per_frame as f64 / sample_rate as f64),
+        None if bitrate > 0 => {
+            Some(size.saturating_sub(at as u64) as f64 * 8.0 / (bitrate as f64 * 1000.0))
+        }
+        None => None,
+    };
+    Some(MediaInfo {
+        duration_secs: duration,
+        sample_rate: Some(sample_rate),
+        channels: Some(if mono { 1 } else { 2 }),
+        ..Default::default()
+    })
+}
+
+/// 拆分 MP4 盒子序列，返回各盒子的类型和内容
+fn mp4_boxes(data: &[u8]) -> Vec<(&[u8], &[u8])> {
+    let mut boxes = Vec::new();
+    let mut at = 0;
+    while at + 8 <= data.len() {
+        let Some(size) = u32_be(data, at) else {
+            break;
+        };
+        let (header, size) = match size {
+            0 => (8, data.len() - at),
+            1 => match u64_be(data, at + 8) {
+                Some(size) => (16, size as usize),
+                None => break,
+            },
+            size => (8, size as usize),
+        };
+        if size < header || at + size > data.len() {
+            break;
+        }
+        boxes.push((&data[at + 4..at + 8], &data[at + header..at + size]));
+        at += size;
+    }
+    boxes
+}
+
+/// MP4 / M4A / MOV：`moov/mvhd` 中的时长和视频轨道 `tkhd` 中的宽高
+fn probe_mp4(file: &mut File, size: u64) -> Option<MediaInfo> {
+    // 顶层盒子逐个跳过，只读取 `moov`
+    let mut offset = 0;
+    let moov = loop {
+        if offset + 8 > size {
+            return None;
+        }
+        file.seek(SeekFrom::Start(offset)).ok()?;
+        let header = read_head(file, 16);
+        let box_size = match u32_be(&header, 0)? as u64 {
+            0 => size - offset,
+            1 => u64_be(&header, 8)?,
+            size => size,
+        };
+        if box_size < 8 {
+            return None;
+        }
+        if &header[4..8] == b"moov" {
+            if box_size > MAX_MOOV_BYTES {
+                return None;
+            }
+            file.seek(SeekFrom::Start(offset)).ok()?;
+            break read_head(file, box_size as usize);
+        }
+        offset += box_size;
+    };
+
+    let mut info = MediaInfo::default();
+    for (kind, body) in mp4_boxes(moov.get(8..)?) {
+        match kind {
+            b"mvhd" => {
+                let (timescale, duration) = if body.first() == Some(&1) {
+                    (u32_be(body, 20)?, u64_be(body, 24)?)
+                } else {
+                    (u32_be(body, 12)?, u32_be(body, 16)? as u64)
+                };
+                if timescale > 0 {
+                    info.duration_secs = Some(duration as f64 / timescale as f64);
+                }
+            }
+            b"trak" => {
+                for (kind, tkhd) in mp4_boxes(body) {
+                    if kind != b"tkhd" {
+                        continue;
+                    }
+                    let base = if tkhd.first() == Some(&1) { 88 } else { 76 };
+                    let width = u32_be(tkhd, base).map(|w| w >> 16).unwrap_or(0);
+                    let height = u32_be(tkhd, base + 4).map(|h| h >> 16).unwrap_or(0);
+                    if width > 0 && height > 0 && info.width.is_none() {
+                        info.width = Some(width);
+                        info.height = Some(height);
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+    Some(info)
+}
+
+/// 按文件头识别格式并解析媒体信息
+fn probe_media(path: &Path) -> Option<MediaInfo> {
+    let mut file = File::open(path).ok()?;
+    let size = file.metadata().ok()?.len();
+    let head = read_head(&mut file, 256 * 1024);
+    if head.starts_with(b"RIFF") && head.get(8..12) == Some(b"WAVE") {
+        probe_wav(&head)
+    } else if head.starts_with(b"fLaC") {
+        probe_flac(&head)
+    } else if head.starts_with(b"OggS") {
+        probe_ogg(&mut file, &head, size)
+    } else if head.get(4..8) == Some(b"ftyp") {
+        probe_mp4(&mut file, size)
+    } else if head.starts_with(b"ID3")
+        || (head.len() > 1 && head[0] == 0xFF && head[1] & 0xE0 == 0xE0)
+    {
+        probe_mp3(&head, size)
+    } else {
+        None
+    }
+}
+
+/// 音频旁的转写文件路径：`meeting.m4a` → `meeting.transcript.md`
+pub fn transcript_path(audio: &Path) -> PathBuf {
+    let stem = audio
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().to_string())
+        .unwrap_or_else(|| "audio".to_string());
+    audio.with_file_name(format!("{}{}", stem, TRANSCRIPT_SUFFIX))
+}
+
+fn metadata(path: &Path) -> Result<AttachmentMetadata, String> {
+    let size = fs::metadata(path)
+        .map_err(|e| format!("Failed to read file: {}", e))?
+        .len();
+    let mime = mime_type(path);
+    let kind = kind_of(mime);
+    let mut media = match kind {
+        AttachmentKind::Audio | AttachmentKind::Video => probe_media(path).unwrap_or_default(),
+        _ => MediaInfo::default(),
+    };
+    if kind == AttachmentKind::Image {
+        if let Ok((width, height)) = image::image_dimensions(path) {
+            media.width = Some(width);
+            media.height = Some(height);
+        }
+    }
+    let transcript = transcript_path(path);
+    Ok(AttachmentMetadata {
+        path: path.to_string_lossy().to_string(),
+        name: crate::file_name_of(path),
+        size,
+        mime: mime.to_string(),
+        kind,
+        width: media.width,
+        height: media.height,
+        duration_secs: media.duration_secs,
+        sample_rate: media.sample_rate,
+        channels: media.channels,
+        transcript: transcript
+            .is_file()
+            .then(|| transcript.to_string_lossy().to_string()),
+    })
+}
+
+/// 链接文字中去掉会破坏 Markdown 语法的方括号
+fn link_text(name: &str) -> String {
+    name.chars().filter(|c| !matches!(c, '[' | ']')).collect()
+}
+
+/// 附件相对文档的 Markdown 链接，图片使用图片语法
+pub fn attachment_markdown(document: &Path, attachment: &Path, name: &str) -> String {
+    let document_dir = document.parent().unwrap_or(Path::new("."));
+    let link = assets::relative_path(document_dir, attachment)
+        .map(|rel| assets::path_to_link(&rel))
+        .unwrap_or_else(|| assets::path_to_link(attachment));
+    if render::image_mime_type(attachment).is_some() {
+        let alt = Path::new(name)
+            .file_stem()
+            .and_then(|s| s.to_str())
+            .map(assets::sanitize_file_stem)
+            .unwrap_or_else(|| "image".to_string());
+        format!("![{}]({})", alt, link)
+    } else {
+        format!("[{}]({})", link_text(name), link)
+    }
+}
+
+/// 把附件内容写入文档的资源目录，返回附件路径和是否复用了已有文件
+pub fn store_attachment(
+    document: &Path,
+    assets_dir: &str,
+    bytes: &[u8],
+    name: &str,
+) -> Result<(PathBuf, bool), String> {
+    if bytes.len() as u64 > MAX_ATTACHMENT_BYTES {
+        return Err(format!(
+            "File is larger than {} MB",
+            MAX_ATTACHMENT_BYTES / 1024 / 1024
+        ));
+    }
+    let dir = assets::resolve_assets_dir(document, Some(assets_dir));
+    assets::store_asset(&dir, bytes, Some(name))
+}
+
+/// 复制拖入的文件到文档的资源目录，返回附件路径、Markdown 和是否去重
+pub fn copy_attachment(
+    source: &Path,
+    document: &Path,
+    assets_dir: &str,
+) -> Result<(PathBuf, String, bool), String> {
+    let size = fs::metadata(source)
+        .map_err(|e| format!("Failed to read file: {}", e))?
+        .len();
+    if size > MAX_ATTACHMENT_BYTES {
+        return Err(format!(
+            "File is larger than {} MB",
+            MAX_ATTACHMENT_BYTES / 1024 / 1024
+        ));
+    }
+    let bytes = fs::read(source).map_err(|e| format!("Failed to read file: {}", e))?;
+    let name = crate::file_name_of(source);
+    let (path, deduplicated) = store_attachment(document, assets_dir, &bytes, &name)?;
+    Ok((
+        path.clone(),
+        attachment_markdown(document, &path, &name),
+        deduplicated,
+    ))
+}
+
+/// 转写音频并写入转写文件
+fn transcribe(
+    backend: &dyn TranscriptionBackend,
+    audio: &Path,
+    language: Option<&str>,
+) -> Result<TranscriptResult, String> {
+    let start = Instant::now();
+    let text = backend.transcribe(audio, language)?;
+    let name = crate::file_name_of(audio);
+    let duration = probe_media(audio).and_then(|media| media.duration_secs);
+
+    let mut content = format!(
+        "---\nsource: \"{}\"\ntranscribed: {}\nbackend: \"{}\"\n",
+        name.replace('"', "\\\""),
+        chrono::Local::now().format("%Y-%m-%dT%H:%M:%S%:z"),
+        backend.name().replace('"', "\\\"")
+    );
+    if let Some(duration) = duration {
+        content.push_str(&format!("duration: {:.1}\n", duration));
+    }
+    if let Some(language) = language {
+        content.push_str(&format!("language: {}\n", language));
+    }
+    content.push_str(&format!(
+        "---\n\n# Transcript: {}\n\n[{}]({})\n\n{}\n",
+        name,
+        link_text(&name),
+        assets::path_to_link(Path::new(&name)),
+        text
+    ));
+
+    let output = transcript_path(audio);
+    storage::write_atomic(&output, content.as_bytes())?;
+    log::info!(
+        "[attachments] Transcribed {:?} with {} in {:?}",
+        audio,
+        backend.name(),
+        start.elapsed()
+    );
+    Ok(TranscriptResult {
+        source: audio.to_string_lossy().to_string(),
+        transcript: output.to_string_lossy().to_string(),
+        text,
+        backend: backend.name(),
+    })
+}
+
+/// 按设置在后台转写新添加的音频附件，返回是否开始转写
+pub fn transcribe_in_background(app: &AppHandle, audio: &Path) -> bool {
+    let settings = app.state::<SettingsStore>().get().transcription;
+    if !settings.auto
+        || kind_of(mime_type(audio)) != AttachmentKind::Audio
+        || transcript_path(audio).exists()
+    {
+        return false;
+    }
+    let Some(backend) = configured_backend(&settings) else {
+        return false;
+    };
+    let app = app.clone();
+    let audio = audio.to_path_buf();
+    tauri::async_runtime::spawn_blocking(move || {
+        let result = transcribe(backend.as_ref(), &audio, settings.language.as_deref());
+        if let Err(e) = &result {
+            log::warn!("[attachments] Transcription of {:?} failed: {}", audio, e);
+        }
+        let (result, error) = match result {
+            Ok(result) => (Some(result), None),
+            Err(e) => (None, Some(e)),
+        };
+        let event = TranscriptionEvent {
+            source: audio.to_string_lossy().to_string(),
+            result,
+            error,
+        };
+        if let Err(e) = app.emit(TRANSCRIBED_EVENT, &event) {
+            log::warn!("[attachments] Failed to emit {}: {}", TRANSCRIBED_EVENT, e);
+        }
+    });
+    true
+}
+
+// 保存粘贴或拖入编辑器的任意文件到文档的资源目录
+#[tauri::command]
+pub async fn save_attachment(
+    app: AppHandle,
+    params: SaveAttachmentParams,
+) -> Result<SaveAttachmentResult, VividError> {
+    let start = Instant::now();
+    let document = PathBuf::from(&params.document_path);
+    log::info!(
+        "[save_attachment] {} ({} bytes) for {}",
+        params.name,
+        params.bytes.len(),
+        params.document_path
+    );
+    if params.bytes.is_empty() {
+        return Err(VividError::invalid_input("Attachment is empty"));
+    }
+    if document.parent().is_none() {
+        return Err(VividError::invalid_input(
+            "Document must be saved before adding attachments",
+        ));
+    }
+    access::ensure_access(&app, &document, AccessKind::Write)?;
+    let assets_dir = params
+        .assets_dir
+        .unwrap_or_else(|| workspace_config::effective_for(&app, &document).assets_dir);
+
+    let name = params.name.clone();
+    let stored_document = document.clone();
+    let (path, deduplicated) = tauri::async_runtime::spawn_blocking(move || {
+        store_attachment(&stored_document, &assets_dir, &params.bytes, &name)
+    })
+    .await
+    .map_err(|e| format!("Attachment task failed: {}", e))?
+    .map_err(|e| {
+        log::error!("[save_attachment] {}", e);
+        VividError::from(e)
+    })?;
+
+    let result = SaveAttachmentResult {
+        markdown: attachment_markdown(&document, &path, &params.name),
+        kind: kind_of(mime_type(&path)),
+        transcribing: transcribe_in_background(&app, &path),
+        path: path.to_string_lossy().to_string(),
+        deduplicated,
+    };
+    log::info!(
+        "[save_attachment] ✓ Success: {} (deduplicated: {}) in {:?}",
+        result.path,
+        deduplicated,
+        start.elapsed()
+    );
+    Ok(result)
+}
+
+// 读取附件的 MIME 类型、大小、图片尺寸和音视频时长
+#[tauri::command]
+pub async fn get_attachment_metadata(
+    app: AppHandle,
+    path: String,
+) -> Result<AttachmentMetadata, VividError> {
+    let path_buf = PathBuf::from(&path);
+    if !path_buf.is_file() {
+        log::error!("[get_attachment_metadata] File does not exist: {}", path);
+        return Err(VividError::not_found(&path_buf));
+    }
+    access::ensure_access(&app, &path_buf, AccessKind::Read)?;
+    let metadata = tauri::async_runtime::spawn_blocking(move || metadata(&path_buf))
+        .await
+        .map_err(|e| format!("Metadata task failed: {}", e))??;
+    log::debug!(
+        "[get_attachment_metadata] {}: {} ({} bytes, {:?}s)",
+        path,
+        metadata.mime,
+        metadata.size,
+        metadata.duration_secs
+    );
+    Ok(metadata)
+}
+
+// 用设置的转写后端转写音频附件，结果写入音频旁的 `.transcript.md`
+#[tauri::command]
+pub async fn transcribe_attachment(
+    app: AppHandle,
+    path: String,
+    language: Option<String>,
+) -> Result<TranscriptResult, VividError> {
+    let audio = PathBuf::from(&path);
+    if !audio.is_file() {
+        log::error!("[transcribe_attachment] File does not exist: {}", path);
+        return Err(VividError::not_found(&audio));
+    }
+    if kind_of(mime_type(&audio)) != AttachmentKind::Audio {
+        return Err(VividError::invalid_input(format!(
+            "Not an audio file: {}",
+            path
+        )));
+    }
+    access::ensure_access(&app, &audio, AccessKind::Read)?;
+    access::ensure_access(&app, &transcript_path(&audio), AccessKind::Write)?;
+    let settings = app.state::<SettingsStore>().get().transcription;
+    let backend = configured_backend(&settings)
+        .ok_or_else(|| VividError::invalid_input("No transcription backend is configured"))?;
+    let language = language
+        .filter(|l| !l.trim().is_empty())
+        .or(settings.language);
+    log::info!("[transcribe_attachment] {} with {}", path, backend.name());
+
+    let result = tauri::async_runtime::spawn_blocking(move || {
+        transcribe(backend.as_ref(), &audio, language.as_deref())
+    })
+    .await
+    .map_err(|e| format!("Transcription task failed: {}", e))?
+    .map_err(|e| {
+        log::error!("[transcribe_attachment] {}", e);
+        VividError::from(e)
+    })?;
+    log::info!(
+        "[transcribe_attachment] ✓ Success: {} ({} chars)",
+        result.transcript,
+        result.text.chars().count()
+    );
+    Ok(result)
+}
@@ -0,0 +1,217 @@
+//! 保存前备份
+//!
+//! 开启后，`save_file` / `apply_patch` 覆盖文件前先把磁盘上的原文件复制到备份目录，
+//! 文件名为时间戳（`20261014-150405-123.md`，同时作为备份 id）。
+//! 每次备份后按设置中的数量、保留天数和总大小轮换，删除最旧的备份。
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use chrono::{Local, NaiveDateTime, TimeZone};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use tauri::{AppHandle, Manager, State};
+
+use crate::access::{self, AccessKind};
+use crate::error::VividError;
+use crate::largefile::LargeFileStore;
+use crate::settings::{BackupLocation, BackupSettings, SettingsStore};
+use crate::{storage, FileInfo};
+
+const BACKUPS_DIR: &str = ".backups";
+const APP_BACKUPS_DIR: &str = "backups";
+/// 备份 id 的时间格式
+const ID_FORMAT: &str = "%Y%m%d-%H%M%S-%3f";
+
+/// 一份备份
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BackupInfo {
+    pub id: String,
+    pub path: String,
+    /// 备份时间（Unix 毫秒）
+    pub created_at: u64,
+    pub size: u64,
+}
+
+/// 应用数据目录中的备份位置
+pub struct BackupStore {
+    dir: PathBuf,
+}
+
+/// 备份时间，无法解析的文件名不是备份
+fn parse_id(id: &str) -> Option<u64> {
+    let time = NaiveDateTime::parse_from_str(id, ID_FORMAT).ok()?;
+    let time = Local.from_local_datetime(&time).earliest()?;
+    u64::try_from(time.timestamp_millis()).ok()
+}
+
+impl BackupStore {
+    pub fn new(dir: PathBuf) -> Self {
+        BackupStore { dir }
+    }
+
+    /// 文件的备份目录
+    fn backup_dir(&self, location: BackupLocation, path: &Path) -> Result<PathBuf, String> {
+        let name = path
+            .file_name()
+            .ok_or_else(|| format!("Invalid path: {}", path.display()))?;
+        match location {
+            BackupLocation::Sibling => Ok(path
+                .parent()
+                .unwrap_or(Path::new("."))
+                .join(BACKUPS_DIR)
+                .join(name)),
+            // 按完整路径的哈希分目录，避免不同目录下的同名文件混在一起
+            BackupLocation::AppData => {
+                let absolute = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+                let hash: String = Sha256::digest(absolute.to_string_lossy().as_bytes())
+                    .iter()
+                    .take(8)
+                    .map(|b| format!("{:02x}", b))
+                    .collect();
+                Ok(self.dir.join(APP_BACKUPS_DIR).join(hash).join(name))
+            }
+        }
+    }
+
+    /// 文件的所有备份，最新的在前
+    pub fn list(&self, location: BackupLocation, path: &Path) -> Result<Vec<BackupInfo>, String> {
+        let dir = self.backup_dir(location, path)?;
+        let entries = match fs::read_dir(&dir) {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(format!("Failed to read directory: {}", e)),
+        };
+        let mut backups: Vec<BackupInfo> = entries
+            .flatten()
+            .filter_map(|entry| {
+                let path = entry.path();
+                let id = path.file_stem()?.to_str()?.to_string();
+                let created_at = parse_id(&id)?;
+                let size = entry.metadata().ok().filter(|m| m.is_file())?.len();
+                Some(BackupInfo {
+                    path: path.to_string_lossy().to_string(),
+                    id,
+                    created_at,
+                    size,
+                })
+            })
+            .collect();
+        backups.sort_by(|a, b| b.id.cmp(&a.id));
+        Ok(backups)
+    }
+
+    /// 复制当前磁盘上的文件，返回新备份；文件不存在或距上次备份太近时返回 `None`
+    fn create(
+        &self,
+        settings: &BackupSettings,
+        path: &Path,
+        force: bool,
+    ) -> Result<Option<BackupInfo>, String> {
+        if !path.is_file() {
+            return Ok(None);
+        }
+        let now = storage::now_millis();
+        let existing = self.list(settings.location, path)?;
+        if let Some(latest) = existing.first() {
+            let interval = settings.min_interval_secs.saturating_mul(1000);
+            if !force && now.saturating_sub(latest.created_at) < interval {
+                return Ok(None);
+            }
+        }
+
+        let dir = self.backup_dir(settings.location, path)?;
+        fs::create_dir_all(&dir).map_err(|e| format!("Failed to create directory: {}", e))?;
+        let id = Local::now().format(ID_FORMAT).to_string();
+        let mut target = dir.join(&id);
+        if let Some(ext) = path.extension() {
+            target.set_extension(ext);
+        }
+        let size = fs::copy(path, &target).map_err(|e| format!("Failed to copy file: {}", e))?;
+        log::info!("[backup] Backed up {:?} to {:?}", path, target);
+
+        self.rotate(settings, path);
+        Ok(Some(BackupInfo {
+            created_at: parse_id(&id).unwrap_or(now),
+            path: target.to_string_lossy().to_string(),
+            id,
+            size,
+        }))
+    }
+
+    /// 按数量、保留天数和总大小删除最旧的备份，至少保留最新的一份
+    fn rotate(&self, settings: &BackupSettings, path: &Path) {
+        let Ok(backups) = self.list(settings.location, path) else {
+            return;
+        };
+        let now = storage::now_millis();
+        let max_age = settings.max_age_days.saturating_mul(24 * 60 * 60 * 1000);
+        let mut total = 0u64;
+        for (i, backup) in backups.iter().enumerate() {
+            total += backup.size;
+            if i == 0 {
+                continue;
+            }
+            let expired = (settings.max_count > 0 && i >= settings.max_count)
+                || (max_age > 0 && now.saturating_sub(backup.created_at) > max_age)
+                || (settings.max_total_bytes > 0 && total > settings.max_total_bytes);
+            if expired {
+                match fs::remove_file(&backup.path) {
+                    Ok(()) => log::debug!("[backup] Rotated out {}", backup.path),
+                    Err(e) => log::warn!("[backup] Failed to remove {}: {}", backup.path, e),
+                }
+            }
+        }
+    }
+
+    /// 保存前调用：按设置备份即将被覆盖的文件
+    pub fn before_save(&self, settings: &BackupSettings, path: &Path) {
+        if !settings.enabled {
+            return;
+        }
+        if let Err(e) = self.create(settings, path, false) {
+            log::warn!("[backup] Backup failed, saving anyway: {}", e);
+        }
+    }
+}
+
+// 列出文件的备份（最新的在前）
+#[tauri::command]
+pub fn list_backups(
+    app: AppHandle,
+    store: State<'_, BackupStore>,
+    settings: State<'_, SettingsStore>,
+    path: String,
+) -> Result<Vec<BackupInfo>, VividError> {
+    access::ensure_access(&app, Path::new(&path), AccessKind::Read)?;
+    Ok(store.list(settings.get().backup.location, Path::new(&path))?)
+}
+
+// 用备份覆盖文件，覆盖前先备份当前内容以便撤销，返回恢复后的文件信息
+#[tauri::command]
+pub fn restore_backup(app: AppHandle, path: String, id: String) -> Result<FileInfo, VividError> {
+    access::ensure_access(&app, Path::new(&path), AccessKind::Write)?;
+    log::info!("[restore_backup] Restoring {} from backup {}", path, id);
+    let store = app.state::<BackupStore>();
+    let settings = app.state::<SettingsStore>().get().backup;
+    let path_buf = PathBuf::from(&path);
+    let backup = store
+        .list(settings.location, &path_buf)?
+        .into_iter()
+        .find(|backup| backup.id == id)
+        .ok_or_else(|| format!("Backup not found: {}", id))?;
+
+    // 先读出备份内容，当前文件的备份触发轮换时可能删除这份旧备份
+    let content = fs::read(&backup.path).map_err(|e| format!("Failed to read backup: {}", e))?;
+    store.create(&settings, &path_buf, true)?;
+    fs::write(&path_buf, content).map_err(|e| {
+        log::error!("[restore_backup] Failed to restore: {}", e);
+        format!("Failed to restore backup: {}", e)
+    })?;
+    log::info!("[restore_backup] ✓ Success: {}", path);
+    crate::load_file(
+        app.state::<LargeFileStore>(),
+        app.state::<crate::encryption::EncryptionKeys>(),
+        path,
+    )
+}
@@ -0,0 +1,883 @@
+//! 参考文献与引用
+//!
+//! 每个工作区可以关联一个 BibTeX / BibLaTeX（`.bib`）或 CSL-JSON（`.json`）文献库和一种 CSL
+//! 引用样式（内置样式名，如 `apa`、`ieee`，或工作区中的 `.csl` 文件），配置保存在应用数据目录的
+//! `bibliography.json` 中。`search_citations` 为编辑器的引用补全提供候选条目。
+//!
+//! 预览和导出时，`resolve_citations` 按所选样式（由 hayagriva 实现 CSL）格式化 Pandoc 风格的引用：
+//!
+//! - `[@key]`、`[@a; @b]`：一处引用多个条目；
+//! - `[@key, p. 12]`：键后的文字作为定位（页码、章节等）；
+//! - `[see @key]`：键前的文字放在格式化后的引用之前；
+//! - `[-@key]`：只显示年份（仅限单个条目）。
+//!
+//! 脚注类样式（如 Chicago notes）的引用转换为脚注，文末追加参考文献列表。结果仍是 Markdown，
+//! 各导出格式都能直接使用；不在文献库中的键保持原样。
+
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock};
+use std::time::Instant;
+
+use hayagriva::archive::{self, ArchivedStyle};
+use hayagriva::citationberg::json::{self as csl_json, DateValue, NameValue};
+use hayagriva::citationberg::taxonomy::Locator;
+use hayagriva::citationberg::{FontStyle, FontWeight, IndependentStyle, Locale, Style, StyleClass};
+use hayagriva::{
+    BibliographyDriver, BibliographyRequest, CitationItem, CitationRequest, CitePurpose, ElemChild,
+    Entry, Formatting, LocatorPayload, Rendered, SpecificLocator,
+};
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager};
+
+use crate::access::{self, AccessKind};
+use crate::error::VividError;
+use crate::links;
+use crate::search::file_stamp;
+use crate::storage;
+use crate::workspace::Workspace;
+
+const DEFAULT_STYLE: &str = "apa";
+const DEFAULT_LIMIT: usize = 20;
+const MAX_LIMIT: usize = 200;
+/// 追加在文末的参考文献列表标题
+const REFERENCES_HEADING: &str = "References";
+
+/// 定位标签（按前缀匹配，较长的写在前面）
+const LOCATOR_LABELS: &[(&str, Locator)] = &[
+    ("pp.", Locator::Page),
+    ("p.", Locator::Page),
+    ("chap.", Locator::Chapter),
+    ("ch.", Locator::Chapter),
+    ("sec.", Locator::Section),
+    ("§§", Locator::Section),
+    ("§", Locator::Section),
+    ("para.", Locator::Paragraph),
+    ("¶", Locator::Paragraph),
+    ("fig.", Locator::Figure),
+    ("vol.", Locator::Volume),
+    ("ll.", Locator::Line),
+    ("l.", Locator::Line),
+    ("n.", Locator::Note),
+];
+
+/// 工作区的文献库配置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct WorkspaceBibliography {
+    /// 文献库文件的绝对路径
+    path: String,
+    /// 引用样式，`None` 表示默认样式
+    #[serde(default)]
+    style: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum BibliographyFormat {
+    Bibtex,
+    CslJson,
+}
+
+/// 文献库中的一个条目，用于引用补全
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CitationEntry {
+    pub key: String,
+    /// 条目类型（如 `article`、`book`）
+    pub kind: Option<String>,
+    pub title: Option<String>,
+    pub authors: Vec<String>,
+    pub year: Option<i32>,
+    /// 期刊、论文集等所属出版物
+    pub container: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BibliographyInfo {
+    pub root: String,
+    pub path: String,
+    pub format: BibliographyFormat,
+    pub style: String,
+    pub style_title: String,
+    pub entries: usize,
+}
+
+/// 内置的引用样式
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CitationStyle {
+    pub name: String,
+    pub title: String,
+}
+
+enum Entries {
+    Bibtex(Vec<Entry>),
+    CslJson(Vec<csl_json::Item>),
+}
+
+/// 已加载的文献库
+struct Loaded {
+    root: PathBuf,
+    path: PathBuf,
+    stamp: Option<(i64, i64)>,
+    format: BibliographyFormat,
+    entries: Entries,
+    /// 键 → `entries` 中的下标
+    keys: HashMap<String, usize>,
+    summary: Vec<CitationEntry>,
+    style_name: String,
+    style_title: String,
+    style: IndependentStyle,
+}
+
+/// 各工作区的文献库配置和当前工作区已加载的文献库
+pub struct BibliographyStore {
+    config: PathBuf,
+    loaded: Mutex<Option<Loaded>>,
+}
+
+/// 文中的一处引用
+struct Cite {
+    range: Range<usize>,
+    items: Vec<CiteItem>,
+}
+
+impl Cite {
+    /// `[-@key]`：只显示年份。hayagriva 按特殊用途格式化时不加样式的括号且会重新排序，
+    /// 所以只支持单个条目
+    fn year_only(&self) -> bool {
+        matches!(self.items.as_slice(), [item] if item.suppress_author)
+    }
+}
+
+struct CiteItem {
+    prefix: String,
+    key: String,
+    suppress_author: bool,
+    locator: Option<(Locator, String)>,
+}
+
+fn locales() -> &'static [Locale] {
+    static LOCALES: OnceLock<Vec<Locale>> = OnceLock::new();
+    LOCALES.get_or_init(archive::locales)
+}
+
+fn person_name(given: Option<&str>, family: &str) -> String {
+    match given.filter(|g| !g.is_empty()) {
+        Some(given) => format!("{} {}", given, family),
+        None => family.to_string(),
+    }
+}
+
+fn summarize_entry(entry: &Entry) -> CitationEntry {
+    CitationEntry {
+        key: entry.key().to_string(),
+        kind: serde_json::to_value(entry.entry_type())
+            .ok()
+            .and_then(|v| v.as_str().map(str::to_string)),
+        title: entry.title().map(|t| t.value.to_string()),
+        authors: entry
+            .authors()
+            .unwrap_or_default()
+            .iter()
+            .map(|p| person_name(p.given_name.as_deref(), &p.name))
+            .collect(),
+        year: entry.date().map(|d| d.year),
+        container: entry
+            .parents()
+            .first()
+            .and_then(|parent| parent.title())
+            .map(|t| t.value.to_string()),
+    }
+}
+
+fn summarize_item(key: &str, item: &csl_json::Item) -> CitationEntry {
+    let text = |name: &str| {
+        item.0
+            .get(name)
+            .and_then(|v| v.to_str())
+            .map(|s| s.into_owned())
+    };
+    let authors = match item.0.get("author") {
+        Some(csl_json::Value::Names(names)) => names
+            .iter()
+            .map(|name| match name {
+                NameValue::Literal(name) => name.literal.clone(),
+                NameValue::Item(name) => person_name(name.given.as_deref(), &name.family),
+            })
+            .collect(),
+        _ => Vec::new(),
+    };
+    let year = match item.0.get("issued") {
+        Some(csl_json::Value::Date(DateValue::DateParts { date_parts, .. })) => date_parts
+            .0
+            .first()
+            .and_then(|date| date.0.first())
+            .map(|&year| year as i32),
+        Some(csl_json::Value::Date(DateValue::Raw { raw, .. })) => Some(raw.start.year as i32),
+        _ => None,
+    };
+    CitationEntry {
+        key: key.to_string(),
+        kind: item.type_().map(|s| s.into_owned()),
+        title: text("title"),
+        authors,
+        year,
+        container: text("container-title"),
+    }
+}
+
+/// 解析文献库，CSL-JSON 按扩展名或以 `[` 开头识别
+fn parse_entries(path: &Path, text: &str) -> Result<(BibliographyFormat, Entries), String> {
+    let is_json = path
+        .extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("json"))
+        || text.trim_start().starts_with('[');
+    if is_json {
+        let items: Vec<csl_json::Item> =
+            serde_json::from_str(text).map_err(|e| format!("Invalid CSL-JSON: {}", e))?;
+        Ok((BibliographyFormat::CslJson, Entries::CslJson(items)))
+    } else {
+        let library = hayagriva::io::from_biblatex_str(text).map_err(|errors| {
+            let messages: Vec<String> = errors.iter().take(3).map(ToString::to_string).collect();
+            format!("Invalid BibTeX: {}", messages.join("; "))
+        })?;
+        Ok((
+            BibliographyFormat::Bibtex,
+            Entries::Bibtex(library.iter().cloned().collect()),
+        ))
+    }
+}
+
+/// 依赖样式只改写了元数据，实际格式来自内置的父样式
+fn independent(style: Style) -> Result<IndependentStyle, String> {
+    match style {
+        Style::Independent(style) => Ok(style),
+        Style::Dependent(style) => {
+            match ArchivedStyle::by_id(&style.parent_link.href).map(ArchivedStyle::get) {
+                Some(Style::Independent(parent)) => Ok(parent),
+                _ => Err(format!(
+                    "Parent style is not available: {}",
+                    style.parent_link.href
+                )),
+            }
+        }
+    }
+}
+
+/// 加载引用样式，返回样式和显示名称
+fn load_style(name: &str, root: &Path) -> Result<(IndependentStyle, String), String> {
+    if name.to_ascii_lowercase().ends_with(".csl") {
+        let path = resolve_path(root, name);
+        let xml = fs::read_to_string(&path).map_err(|e| format!("Failed to read style: {}", e))?;
+        let style = Style::from_xml(&xml).map_err(|e| format!("Invalid CSL style: {}", e))?;
+        let title = style.info().title.value.clone();
+        return Ok((independent(style)?, title));
+    }
+    let archived =
+        ArchivedStyle::by_name(name).ok_or_else(|| format!("Unknown citation style: {}", name))?;
+    Ok((
+        independent(archived.get())?,
+        archived.display_name().to_string(),
+    ))
+}
+
+fn resolve_path(root: &Path, path: &str) -> PathBuf {
+    let path = Path::new(path);
+    if path.is_absolute() {
+        path.to_path_buf()
+    } else {
+        root.join(path)
+    }
+}
+
+fn load(root: &Path, config: &WorkspaceBibliography) -> Result<Loaded, String> {
+    let path = PathBuf::from(&config.path);
+    let text = fs::read_to_string(&path).map_err(|e| format!("Failed to read file: {}", e))?;
+    let (format, entries) = parse_entries(&path, &text)?;
+    let style_name = config
+        .style
+        .clone()
+        .filter(|s| !s.trim().is_empty())
+        .unwrap_or_else(|| DEFAULT_STYLE.to_string());
+    let (style, style_title) = load_style(&style_name, root)?;
+
+    let mut keys = HashMap::new();
+    let summary: Vec<CitationEntry> = match &entries {
+        Entries::Bibtex(entries) => entries.iter().map(summarize_entry).collect(),
+        Entries::CslJson(items) => items
+            .iter()
+            .map(|item| summarize_item(&item.id().unwrap_or_default(), item))
+            .collect(),
+    };
+    for (index, entry) in summary.iter().enumerate() {
+        if !entry.key.is_empty() {
+            keys.entry(entry.key.clone()).or_insert(index);
+        }
+    }
+    Ok(Loaded {
+        root: root.to_path_buf(),
+        stamp: file_stamp(&path),
+        path,
+        format,
+        entries,
+        keys,
+        summary,
+        style_name,
+        style_title,
+        style,
+    })
+}
+
+/// 解析引用中的一个条目：`[前缀] [-]@key[, 定位]`
+fn parse_cite_item(part: &str) -> Option<CiteItem> {
+    let at = part
+        .char_indices()
+        .find(|&(i, c)| {
+            c == '@'
+                && part[..i]
+                    .chars()
+                    .next_back()
+                    .map_or(true, |p| p.is_whitespace() || p == '-')
+        })?
+        .0;
+    let mut prefix = part[..at].trim();
+    let suppress_author = prefix.ends_with('-');
+    if suppress_author {
+        prefix = prefix[..prefix.len() - 1].trim_end();
+    }
+
+    let rest = &part[at + 1..];
+    let (key, suffix) = if let Some(inner) = rest.strip_prefix('{') {
+        let end = inner.find('}')?;
+        (&inner[..end], &inner[end + 1..])
+    } else {
+        let end = rest
+            .find(|c: char| !(c.is_alphanumeric() || "_:.#$%&-+?<>~/".contains(c)))
+            .unwrap_or(rest.len());
+        // 键内部可以有标点，末尾的标点属于后面的文字
+        let key = rest[..end].trim_end_matches(|c: char| !c.is_alphanumeric() && c != '_');
+        (key, &rest[key.len()..])
+    };
+    if key.is_empty() {
+        return None;
+    }
+    let suffix = suffix.trim_start().trim_start_matches(',').trim();
+    Some(CiteItem {
+        prefix: prefix.to_string(),
+        key: key.to_string(),
+        suppress_author,
+        locator: parse_locator(suffix),
+    })
+}
+
+/// `p. 12` → 页码；只有数字时视为页码，其它文字原样显示
+fn parse_locator(suffix: &str) -> Option<(Locator, String)> {
+    if suffix.is_empty() {
+        return None;
+    }
+    for (label, locator) in LOCATOR_LABELS {
+        if let Some(value) = suffix.strip_prefix(label) {
+            return Some((*locator, value.trim().to_string()));
+        }
+    }
+    if suffix.starts_with(|c: char| c.is_ascii_digit()) {
+        Some((Locator::Page, suffix.to_string()))
+    } else {
+        Some((Locator::Custom, suffix.to_string()))
+    }
+}
+
+/// 找出文中的 `[...@key...]` 引用，跳过代码、公式、链接和图片
+fn find_citations(content: &str) -> Vec<Cite> {
+    let literals = links::literal_ranges(content);
+    let mut cites = Vec::new();
+    let mut search = 0;
+    while let Some(open) = content[search..].find('[').map(|i| search + i) {
+        search = open + 1;
+        let Some(close) = content[open + 1..].find(']').map(|i| open + 1 + i) else {
+            break;
+        };
+        let inner = &content[open + 1..close];
+        if !inner.contains('@') || inner.contains('[') || inner.contains("\n\n") {
+            continue;
+        }
+        let before = content[..open].chars().next_back();
+        let after = content[close + 1..].chars().next();
+        if matches!(before, Some('!' | '\\')) || matches!(after, Some('(' | '[' | ':')) {
+            continue;
+        }
+        if literals.iter().any(|r| r.contains(&open)) {
+            continue;
+        }
+        let items: Option<Vec<CiteItem>> = inner.split(';').map(parse_cite_item).collect();
+        if let Some(items) = items {
+            cites.push(Cite {
+                range: open..close + 1,
+                items,
+            });
+            search = close + 1;
+        }
+    }
+    cites
+}
+
+pub(crate) fn escape_markdown(text: &str, out: &mut String) {
+    for c in text.chars() {
+        if matches!(
+            c,
+            '\\' | '`' | '*' | '_' | '[' | ']' | '<' | '>' | '$' | '~'
+        ) {
+            out.push('\\');
+        }
+        out.push(c);
+    }
+}
+
+/// 把格式化结果展开为 (文字, 格式, 链接) 片段
+fn flatten(children: &[ElemChild], out: &mut Vec<(String, Formatting, Option<String>)>) {
+    for child in children {
+        match child {
+            ElemChild::Text(text) => out.push((text.text.clone(), text.formatting, None)),
+            ElemChild::Elem(elem) => flatten(&elem.children.0, out),
+            ElemChild::Markup(text) => out.push((text.clone(), Formatting::default(), None)),
+            ElemChild::Link { text, url } => {
+                out.push((text.text.clone(), text.formatting, Some(url.clone())))
+            }
+            ElemChild::Transparent { .. } => {}
+        }
+    }
+}
+
+/// 把 hayagriva 的格式化结果转换为 Markdown（保留斜体、粗体和链接）
+fn to_markdown(children: &[ElemChild]) -> String {
+    let mut segments = Vec::new();
+    flatten(children, &mut segments);
+    // 合并格式相同的相邻片段，避免 `*a**b*` 这样无法解析的强调
+    let mut merged: Vec<(String, Formatting, Option<String>)> = Vec::new();
+    for (text, formatting, url) in segments {
+        match merged.last_mut() {
+            Some((last, last_formatting, None))
+                if url.is_none() && *last_formatting == formatting =>
+            {
+                last.push_str(&text)
+            }
+            _ => merged.push((text, formatting, url)),
+        }
+    }
+
+    let mut out = String::new();
+    for (text, formatting, url) in merged {
+        let marker = match (
+            formatting.font_style == FontStyle::Italic,
+            formatting.font_weight == FontWeight::Bold,
+        ) {
+            (true, true) => "***",
+            (true, false) => "*",
+            (false, true) => "**",
+            (false, false) => "",
+        };
+        let trimmed = text.trim();
+        if trimmed.is_empty() {
+            out.push_str(&text);
+            continue;
+        }
+        // 强调符号不能紧挨空白，首尾空白放在外面
+        let leading = &text[..text.len() - text.trim_start().len()];
+        let trailing = &text[text.trim_end().len()..];
+        out.push_str(leading);
+        out.push_str(marker);
+        if let Some(url) = url {
+            out.push('[');
+            escape_markdown(trimmed, &mut out);
+            out.push_str(&format!("](<{}>)", url));
+        } else {
+            escape_markdown(trimmed, &mut out);
+        }
+        out.push_str(marker);
+        out.push_str(trailing);
+    }
+    out
+}
+
+/// 两种文献库的条目类型不同，格式化的流程相同
+macro_rules! render_cites {
+    ($entries:expr, $loaded:expr, $cites:expr) => {{
+        let mut driver = BibliographyDriver::new();
+        for (n, cite) in $cites.iter().enumerate() {
+            let items =
+                cite.items
+                    .iter()
+                    .map(|item| {
+                        let locator = item.locator.as_ref().map(|(kind, value)| {
+                            SpecificLocator(*kind, LocatorPayload::Str(value))
+                        });
+                        let entry = &$entries[$loaded.keys[&item.key]];
+                        let citation = CitationItem::with_locator(entry, locator);
+                        if cite.year_only() {
+                            citation.kind(CitePurpose::Year)
+                        } else {
+                            citation
+                        }
+                    })
+                    .collect();
+            driver.citation(CitationRequest::new(
+                items,
+                &$loaded.style,
+                None,
+                locales(),
+                Some(n + 1),
+            ));
+        }
+        driver.finish(BibliographyRequest::new(&$loaded.style, None, locales()))
+    }};
+}
+
+impl Loaded {
+    fn info(&self) -> BibliographyInfo {
+        BibliographyInfo {
+            root: self.root.to_string_lossy().to_string(),
+            path: self.path.to_string_lossy().to_string(),
+            format: self.format,
+            style: self.style_name.clone(),
+            style_title: self.style_title.clone(),
+            entries: self.summary.len(),
+        }
+    }
+
+    /// 按键、作者、年份、标题匹配条目，每个词都要匹配
+    fn search(&self, query: &str, limit: usize) -> Vec<CitationEntry> {
+        let terms: Vec<String> = query.split_whitespace().map(str::to_lowercase).collect();
+        let mut scored: Vec<(u32, &CitationEntry)> = self
+            .summary
+            .iter()
+            .filter(|entry| !entry.key.is_empty())
+            .filter_map(|entry| {
+                let key = entry.key.to_lowercase();
+                let authors = entry.authors.join(" ").to_lowercase();
+                let year = entry.year.map(|y| y.to_string()).unwrap_or_default();
+                let title = entry.title.as_deref().unwrap_or_default().to_lowercase();
+                let container = entry
+                    .container
+                    .as_deref()
+                    .unwrap_or_default()
+                    .to_lowercase();
+                let mut score = 0;
+                for term in &terms {
+                    score += if key.starts_with(term.as_str()) {
+                        8
+                    } else if key.contains(term.as_str()) {
+                        4
+                    } else if authors.contains(term.as_str()) {
+                        3
+                    } else if year == *term {
+                        2
+                    } else if title.contains(term.as_str()) || container.contains(term.as_str()) {
+                        1
+                    } else {
+                        return None;
+                    };
+                }
+                Some((score, entry))
+            })
+            .collect();
+        scored.sort_by(|a, b| b.0.cmp(&a.0).then_with(|| a.1.key.cmp(&b.1.key)));
+        scored
+            .into_iter()
+            .take(limit)
+            .map(|(_, entry)| entry.clone())
+            .collect()
+    }
+
+    /// 替换文中的引用并追加参考文献列表，没有可解析的引用时返回 `None`
+    fn resolve(&self, content: &str) -> Option<String> {
+        let cites: Vec<Cite> = find_citations(content)
+            .into_iter()
+            .filter(|cite| {
+                let missing: Vec<&str> = cite
+                    .items
+                    .iter()
+                    .filter(|item| !self.keys.contains_key(&item.key))
+                    .map(|item| item.key.as_str())
+                    .collect();
+                if !missing.is_empty() {
+                    log::debug!(
+                        "[bibliography] Unknown citation key(s): {}",
+                        missing.join(", ")
+                    );
+                }
+                missing.is_empty()
+            })
+            .collect();
+        if cites.is_empty() {
+            return None;
+        }
+        let rendered: Rendered = match &self.entries {
+            Entries::Bibtex(entries) => render_cites!(entries, self, cites),
+            Entries::CslJson(items) => render_cites!(items, self, cites),
+        };
+        let note_style = self.style.settings.class == StyleClass::Note;
+
+        let mut out = String::with_capacity(content.len() + 1024);
+        let mut notes = Vec::new();
+        let mut last = 0;
+        for (cite, citation) in cites.iter().zip(&rendered.citations) {
+            out.push_str(&content[last..cite.range.start]);
+            last = cite.range.end;
+            let mut text = String::new();
+            for item in cite.items.iter().filter(|item| !item.prefix.is_empty()) {
+                escape_markdown(&item.prefix, &mut text);
+                text.push(' ');
+            }
+            let layout = &self.style.citation.layout;
+            let year_only = cite.year_only() && !note_style;
+            if let (true, Some(prefix)) = (year_only, &layout.prefix) {
+                escape_markdown(prefix, &mut text);
+            }
+            text.push_str(&to_markdown(&citation.citation.0));
+            if let (true, Some(suffix)) = (year_only, &layout.suffix) {
+                escape_markdown(suffix, &mut text);
+            }
+            if note_style {
+                notes.push(text);
+                out.push_str(&format!("[^cite-{}]", notes.len()));
+            } else {
+                out.push_str(&text);
+            }
+        }
+        out.push_str(&content[last..]);
+        out.truncate(out.trim_end().len());
+        out.push('\n');
+
+        for (i, note) in notes.iter().enumerate() {
+            out.push_str(&format!("\n[^cite-{}]: {}\n", i + 1, note));
+        }
+        if let Some(bibliography) = rendered.bibliography.filter(|b| !b.items.is_empty()) {
+            out.push_str(&format!("\n## {}\n", REFERENCES_HEADING));
+            for item in bibliography.items {
+                out.push('\n');
+                if let Some(first) = item.first_field {
+                    out.push_str(&to_markdown(std::slice::from_ref(&first)));
+                    out.push(' ');
+                }
+                out.push_str(&to_markdown(&item.content.0));
+                out.push('\n');
+            }
+        }
+        Some(out)
+    }
+}
+
+impl BibliographyStore {
+    pub fn new(config: PathBuf) -> Self {
+        BibliographyStore {
+            config,
+            loaded: Mutex::new(None),
+        }
+    }
+
+    fn configs(&self) -> BTreeMap<String, WorkspaceBibliography> {
+        storage::load_json(&self.config)
+    }
+
+    /// 用当前工作区的文献库执行 `f`；未配置时返回 `None`，文件变化后重新加载
+    fn with_loaded<R>(
+        &self,
+        root: &Path,
+        f: impl FnOnce(&Loaded) -> R,
+    ) -> Result<Option<R>, String> {
+        let mut loaded = self
+            .loaded
+            .lock()
+            .map_err(|_| "Bibliography lock poisoned".to_string())?;
+        let fresh = loaded
+            .as_ref()
+            .is_some_and(|l| l.root == root && l.stamp == file_stamp(&l.path));
+        if !fresh {
+            *loaded = None;
+            let Some(config) = self.configs().remove(root.to_string_lossy().as_ref()) else {
+                return Ok(None);
+            };
+            *loaded = Some(load(root, &config)?);
+        }
+        Ok(loaded.as_ref().map(f))
+    }
+
+    /// 设置工作区的文献库（加载成功后才保存），`None` 表示取消关联
+    fn configure(
+        &self,
+        root: &Path,
+        config: Option<WorkspaceBibliography>,
+    ) -> Result<Option<BibliographyInfo>, String> {
+        let mut loaded = self
+            .loaded
+            .lock()
+            .map_err(|_| "Bibliography lock poisoned".to_string())?;
+        let key = root.to_string_lossy().to_string();
+        let mut configs = self.configs();
+        let info = match config {
+            Some(config) => {
+                let bibliography = load(root, &config)?;
+                let info = bibliography.info();
+                configs.insert(key, config);
+                *loaded = Some(bibliography);
+                Some(info)
+            }
+            None => {
+                configs.remove(&key);
+                *loaded = None;
+                None
+            }
+        };
+        storage::save_json(&self.config, &configs)?;
+        Ok(info)
+    }
+}
+
+/// 按当前工作区的文献库解析文中的引用；没有文献库或引用时原样返回
+pub fn resolve_citations(app: &AppHandle, content: String) -> String {
+    if !content.contains('@') {
+        return content;
+    }
+    let Some(root) = app.state::<Workspace>().root() else {
+        return content;
+    };
+    match app
+        .state::<BibliographyStore>()
+        .with_loaded(&root, |bibliography| bibliography.resolve(&content))
+    {
+        Ok(Some(Some(resolved))) => resolved,
+        Ok(_) => content,
+        Err(e) => {
+            log::warn!("[bibliography] Failed to resolve citations: {}", e);
+            content
+        }
+    }
+}
+
+fn workspace_root(app: &AppHandle) -> Result<PathBuf, VividError> {
+    app.state::<Workspace>()
+        .root()
+        .ok_or_else(|| VividError::invalid_input("No workspace is open"))
+}
+
+// 为当前工作区关联文献库和引用样式；`path` 为空时取消关联，`style` 省略时保留原样式
+#[tauri::command]
+pub async fn load_bibliography(
+    app: AppHandle,
+    path: Option<String>,
+    style: Option<String>,
+) -> Result<Option<BibliographyInfo>, VividError> {
+    let start = Instant::now();
+    let root = workspace_root(&app)?;
+    let config = match path.filter(|p| !p.trim().is_empty()) {
+        Some(path) => {
+            let file = resolve_path(&root, &path);
+            if !file.is_file() {
+                log::error!(
+                    "[load_bibliography] File does not exist: {}",
+                    file.display()
+                );
+                return Err(VividError::not_found(&file));
+            }
+            access::ensure_access(&app, &file, AccessKind::Read)?;
+            let style = style.or_else(|| {
+                app.state::<BibliographyStore>()
+                    .configs()
+                    .remove(root.to_string_lossy().as_ref())
+                    .and_then(|config| config.style)
+            });
+            let file = file.canonicalize().unwrap_or(file);
+            Some(WorkspaceBibliography {
+                path: file.to_string_lossy().to_string(),
+                style,
+            })
+        }
+        None => None,
+    };
+    log::info!(
+        "[load_bibliography] {}: {:?}",
+        root.display(),
+        config.as_ref().map(|c| &c.path)
+    );
+
+    let info = tauri::async_runtime::spawn_blocking(move || {
+        app.state::<BibliographyStore>().configure(&root, config)
+    })
+    .await
+    .map_err(|e| format!("Bibliography task failed: {}", e))?
+    .map_err(|e| {
+        log::error!("[load_bibliography] {}", e);
+        VividError::from(e)
+    })?;
+    match &info {
+        Some(info) => log::info!(
+            "[load_bibliography] ✓ Success: {} entr(ies), style {} in {:?}",
+            info.entries,
+            info.style,
+            start.elapsed()
+        ),
+        None => log::info!("[load_bibliography] ✓ Bibliography removed"),
+    }
+    Ok(info)
+}
+
+// 当前工作区关联的文献库
+#[tauri::command]
+pub async fn get_bibliography(app: AppHandle) -> Result<Option<BibliographyInfo>, VividError> {
+    let Some(root) = app.state::<Workspace>().root() else {
+        return Ok(None);
+    };
+    let info = tauri::async_runtime::spawn_blocking(move || {
+        app.state::<BibliographyStore>()
+            .with_loaded(&root, Loaded::info)
+    })
+    .await
+    .map_err(|e| format!("Bibliography task failed: {}", e))?
+    .map_err(|e| {
+        log::error!("[get_bibliography] {}", e);
+        VividError::from(e)
+    })?;
+    Ok(info)
+}
+
+// 在当前工作区的文献库中搜索条目，用于引用补全
+#[tauri::command]
+pub async fn search_citations(
+    app: AppHandle,
+    query: String,
+    limit: Option<usize>,
+) -> Result<Vec<CitationEntry>, VividError> {
+    let start = Instant::now();
+    let root = workspace_root(&app)?;
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).clamp(1, MAX_LIMIT);
+    let entries = tauri::async_runtime::spawn_blocking(move || {
+        app.state::<BibliographyStore>()
+            .with_loaded(&root, |bibliography| bibliography.search(&query, limit))
+    })
+    .await
+    .map_err(|e| format!("Bibliography task failed: {}", e))?
+    .map_err(|e| {
+        log::error!("[search_citations] {}", e);
+        VividError::from(e)
+    })?
+    .unwrap_or_default();
+    log::debug!(
+        "[search_citations] {} result(s) in {:?}",
+        entries.len(),
+        start.elapsed()
+    );
+    Ok(entries)
+}
+
+// 列出内置的引用样式
+#[tauri::command]
+pub fn list_citation_styles() -> Vec<CitationStyle> {
+    ArchivedStyle::all()
+        .iter()
+        .map(|style| CitationStyle {
+            name: style.names()[0].to_string(),
+            title: style.display_name().to_string(),
+        })
+        .collect()
+}
@@ -0,0 +1,5 @@
+// 控制台版本的命令行入口：发布版主程序在 Windows 上是图形界面程序，
+// 终端不等待它退出，脚本和 CI 在 Windows 上使用本程序，参数与 `vividmark <子命令>` 相同
+fn main() {
+  std::process::exit(app_lib::run_cli());
+}
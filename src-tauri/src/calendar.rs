@@ -0,0 +1,251 @@
+//! 日历视图
+//!
+//! `query_by_date` 按日期汇总工作区和日记目录中的笔记，供日历导航和热力图使用。
+//! 笔记的日期有两个来源：
+//!
+//! - 日记：文件路径符合日记设置中的路径模式（见 `daily`），或文件名本身就是 `YYYY-MM-DD`；
+//! - front matter 中的 `date`、`created`、`updated` / `modified` 字段，只取日期部分。
+//!
+//! 一篇笔记可能出现在多天（例如创建和更新日期不同），同一天内只计一次。
+
+use std::collections::{BTreeMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tauri::{AppHandle, Manager};
+
+use crate::error::VividError;
+use crate::largefile::LARGE_FILE_THRESHOLD;
+use crate::settings::SettingsStore;
+use crate::workspace::{self, Workspace};
+use crate::{daily, frontmatter, parse, stats};
+
+/// 查询的日期范围（`YYYY-MM-DD`，包含首尾两天）
+#[derive(Debug, Deserialize)]
+pub struct DateRange {
+    pub start: String,
+    pub end: String,
+}
+
+/// 笔记在某一天出现的原因
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DateSource {
+    Daily,
+    Date,
+    Created,
+    Updated,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CalendarNote {
+    pub path: String,
+    pub title: String,
+    pub words: usize,
+    pub sources: Vec<DateSource>,
+}
+
+/// 一天的汇总，没有笔记的日期不返回
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CalendarDay {
+    pub date: String,
+    pub note_count: usize,
+    /// 当天笔记的总字数
+    pub words: usize,
+    pub notes: Vec<CalendarNote>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CalendarResult {
+    pub start: String,
+    pub end: String,
+    pub days: Vec<CalendarDay>,
+    /// 热力图的最大值，便于前端计算颜色深浅
+    pub max_notes: usize,
+    pub max_words: usize,
+    /// 范围内的笔记总数（不重复计算）
+    pub total_notes: usize,
+}
+
+fn parse_date(value: &str) -> Option<NaiveDate> {
+    let date = value.trim().get(..10)?;
+    NaiveDate::parse_from_str(date, "%Y-%m-%d").ok()
+}
+
+/// front matter 中的日期字段
+fn front_matter_dates(content: &str) -> Vec<(NaiveDate, DateSource)> {
+    let Ok(Some((_, Value::Object(fields)))) = frontmatter::parse_front_matter(content) else {
+        return Vec::new();
+    };
+    let mut dates = Vec::new();
+    for (key, value) in &fields {
+        let source = match key.to_lowercase().as_str() {
+            "date" => DateSource::Date,
+            "created" => DateSource::Created,
+            "updated" | "modified" => DateSource::Updated,
+            _ => continue,
+        };
+        if let Some(date) = value.as_str().and_then(parse_date) {
+            dates.push((date, source));
+        }
+    }
+    dates
+}
+
+fn note_title(path: &Path, content: &str) -> String {
+    let body_start = frontmatter::find_front_matter(content).map_or(0, |block| block.body_start);
+    parse::outline(&content[body_start..])
+        .into_iter()
+        .find(|heading| heading.level == 1)
+        .map(|heading| heading.text)
+        .unwrap_or_else(|| {
+            path.file_stem()
+                .map(|stem| stem.to_string_lossy().to_string())
+                .unwrap_or_default()
+        })
+}
+
+/// 要扫描的目录：当前工作区和日记根目录
+fn scan_roots(app: &AppHandle) -> (Vec<PathBuf>, Option<PathBuf>) {
+    let settings = app.state::<SettingsStore>().get();
+    let daily_root = daily::daily_root(app, &settings).ok();
+    let mut roots: Vec<PathBuf> = app.state::<Workspace>().root().into_iter().collect();
+    if let Some(root) = &daily_root {
+        if !roots.iter().any(|r| root.starts_with(r)) {
+            roots.push(root.clone());
+        }
+    }
+    (roots, daily_root)
+}
+
+fn query(
+    roots: &[PathBuf],
+    daily_root: Option<&Path>,
+    pattern: &str,
+    start: NaiveDate,
+    end: NaiveDate,
+) -> CalendarResult {
+    let mut seen = HashSet::new();
+    let mut days: BTreeMap<NaiveDate, Vec<CalendarNote>> = BTreeMap::new();
+    for path in roots
+        .iter()
+        .flat_map(|root| workspace::markdown_files(root))
+    {
+        if !seen.insert(path.clone()) {
+            continue;
+        }
+        if fs::metadata(&path).map_or(true, |m| m.len() > LARGE_FILE_THRESHOLD) {
+            continue;
+        }
+        let Ok(content) = fs::read_to_string(&path) else {
+            continue;
+        };
+
+        let mut dates = front_matter_dates(&content);
+        let daily_date = daily_root
+            .and_then(|root| path.strip_prefix(root).ok())
+            .and_then(|relative| daily::date_from_path(pattern, relative))
+            .or_else(|| {
+                path.file_stem()
+                    .and_then(|stem| stem.to_str())
+                    .filter(|stem| stem.len() == 10)
+                    .and_then(parse_date)
+            });
+        if let Some(date) = daily_date {
+            dates.push((date, DateSource::Daily));
+        }
+        dates.retain(|(date, _)| (start..=end).contains(date));
+        if dates.is_empty() {
+            continue;
+        }
+
+        let title = note_title(&path, &content);
+        let words = stats::document_stats(&content).words;
+        let mut by_day: BTreeMap<NaiveDate, Vec<DateSource>> = BTreeMap::new();
+        for (date, source) in dates {
+            let sources = by_day.entry(date).or_default();
+            if !sources.contains(&source) {
+                sources.push(source);
+            }
+        }
+        for (date, mut sources) in by_day {
+            sources.sort();
+            days.entry(date).or_default().push(CalendarNote {
+                path: path.to_string_lossy().to_string(),
+                title: title.clone(),
+                words,
+                sources,
+            });
+        }
+    }
+
+    let total_notes = days
+        .values()
+        .flatten()
+        .map(|note| note.path.as_str())
+        .collect::<HashSet<_>>()
+        .len();
+    let days: Vec<CalendarDay> = days
+        .into_iter()
+        .map(|(date, mut notes)| {
+            notes.sort_by(|a, b| a.path.cmp(&b.path));
+            CalendarDay {
+                date: date.format("%Y-%m-%d").to_string(),
+                note_count: notes.len(),
+                words: notes.iter().map(|note| note.words).sum(),
+                notes,
+            }
+        })
+        .collect();
+    CalendarResult {
+        start: start.format("%Y-%m-%d").to_string(),
+        end: end.format("%Y-%m-%d").to_string(),
+        max_notes: days.iter().map(|day| day.note_count).max().unwrap_or(0),
+        max_words: days.iter().map(|day| day.words).max().unwrap_or(0),
+        total_notes,
+        days,
+    }
+}
+
+// 按日期汇总日记和 front matter 中带日期的笔记，用于日历视图和热力图
+#[tauri::command]
+pub async fn query_by_date(app: AppHandle, range: DateRange) -> Result<CalendarResult, VividError> {
+    let start_time = Instant::now();
+    let parse_day = |value: &str| {
+        NaiveDate::parse_from_str(value.trim(), "%Y-%m-%d")
+            .map_err(|e| VividError::invalid_input(format!("Invalid date {}: {}", value, e)))
+    };
+    let start = parse_day(&range.start)?;
+    let end = parse_day(&range.end)?;
+    if start > end {
+        return Err(VividError::invalid_input(format!(
+            "Start date {} is after end date {}",
+            start, end
+        )));
+    }
+    let (roots, daily_root) = scan_roots(&app);
+    let pattern = app.state::<SettingsStore>().get().daily_notes.pattern;
+    log::info!(
+        "[query_by_date] {} to {} in {} folder(s)",
+        start,
+        end,
+        roots.len()
+    );
+
+    let result = tauri::async_runtime::spawn_blocking(move || {
+        query(&roots, daily_root.as_deref(), &pattern, start, end)
+    })
+    .await
+    .map_err(|e| format!("Calendar query failed: {}", e))?;
+    log::info!(
+        "[query_by_date] ✓ Success: {} note(s) on {} day(s) in {:?}",
+        result.total_notes,
+        result.days.len(),
+        start_time.elapsed()
+    );
+    Ok(result)
+}
@@ -0,0 +1,464 @@
+//! 命令行模式
+//!
+//! 第一个参数是子命令时不打开窗口，直接在终端执行后退出，便于脚本和 CI 使用：
+//!
+//! ```text
+//! vividmark convert input.md -o out.pdf      # 按输出扩展名导出 PDF / HTML / DOCX
+//! vividmark lint notes/ README.md            # 检查 Markdown，有警告时退出码为 1
+//! vividmark export --format html notes/      # 导出整个目录
+//! ```
+//!
+//! 其他参数（如双击打开的文件路径）照常启动图形界面。命令行模式不读取应用设置，
+//! 检查规则和导出选项使用默认值，检查时文件所在工作区的 `.vividmark/config.toml` 仍然生效；
+//! 没有应用上下文，文献引用不展开。
+//!
+//! 退出码：0 成功，1 检查发现问题或有文件导出失败，2 参数错误或执行失败。
+//!
+//! 发布版主程序在 Windows 上是图形界面程序：从终端启动时先连接父进程的控制台才能输出，
+//! 而且终端不会等待它退出。脚本和 CI 应使用控制台程序 `vividmark-cli`（`src/bin/vividmark-cli.rs`），
+//! 参数相同，退出码可靠。
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::export::html;
+use crate::export::pdf::PdfExportOptions;
+use crate::export::theme::ExportTheme;
+use crate::export::workspace::{
+    self as workspace_export, ExportWorkspaceOptions, WorkspaceExportFormat,
+};
+use crate::lint::{self, LintDiagnostic, Severity};
+use crate::settings::Settings;
+use crate::workspace;
+use crate::workspace_config;
+
+const USAGE: &str = "\
+Usage:
+  vividmark convert <input.md> -o <output.pdf|html|docx> [--format pdf|html|docx]
+                    [--theme github|dark|academic|sepia] [--no-embed] [--template <reference.docx>]
+  vividmark lint <file-or-dir>... [--rules rule,rule] [--max-line-length N] [--fix] [--json]
+  vividmark export --format html|pdf <dir> [-o <output-dir>] [--theme github|dark|academic|sepia]
+  vividmark help
+  vividmark --version
+
+Without a subcommand VividMark starts the editor and opens the given files.
+On Windows, use vividmark-cli in scripts and CI.";
+
+/// 退出码
+const EXIT_OK: i32 = 0;
+const EXIT_FAILURES: i32 = 1;
+const EXIT_ERROR: i32 = 2;
+
+/// 解析后的参数：位置参数和 `--name value` / `--flag` 形式的选项
+#[derive(Debug, Default)]
+struct Args {
+    positional: Vec<String>,
+    options: Vec<(String, Option<String>)>,
+}
+
+impl Args {
+    /// `value_options` 中的选项需要一个值，其余选项视为开关
+    fn parse(args: &[String], value_options: &[&str]) -> Result<Self, String> {
+        let mut parsed = Args::default();
+        let mut iter = args.iter();
+        while let Some(arg) = iter.next() {
+            if arg == "--" {
+                parsed.positional.extend(iter.by_ref().cloned());
+                break;
+            }
+            if !arg.starts_with('-') || arg == "-" {
+                parsed.positional.push(arg.clone());
+                continue;
+            }
+            let (name, inline) = match arg.split_once('=') {
+                Some((name, value)) => (name, Some(value.to_string())),
+                None => (arg.as_str(), None),
+            };
+            let name = match name {
+                "-o" => "--output",
+                "-f" => "--format",
+                other => other,
+            };
+            if value_options.contains(&name) {
+                let value = match inline {
+                    Some(value) => value,
+                    None => iter
+                        .next()
+                        .cloned()
+                        .ok_or_else(|| format!("Option {} requires a value", name))?,
+                };
+                parsed.options.push((name.to_string(), Some(value)));
+            } else if inline.is_some() {
+                return Err(format!("Option {} does not take a value", name));
+            } else {
+                parsed.options.push((name.to_string(), None));
+            }
+        }
+        Ok(parsed)
+    }
+
+    fn value(&self, name: &str) -> Option<&str> {
+        self.options
+            .iter()
+            .rev()
+            .find(|(option, _)| option == name)
+            .and_then(|(_, value)| value.as_deref())
+    }
+
+    fn flag(&self, name: &str) -> bool {
+        self.options.iter().any(|(option, _)| option == name)
+    }
+
+    /// 检查是否有不支持的选项
+    fn ensure_known(&self, known: &[&str]) -> Result<(), String> {
+        match self
+            .options
+            .iter()
+            .find(|(option, _)| !known.contains(&option.as_str()))
+        {
+            Some((option, _)) => Err(format!("Unknown option {}", option)),
+            None => Ok(()),
+        }
+    }
+}
+
+/// 连接启动本程序的终端的控制台，使图形界面程序的 `println!` / `eprintln!` 可见
+///
+/// 输出已被重定向（管道、文件）或已有控制台时不做任何事。
+#[cfg(windows)]
+fn attach_parent_console() {
+    const ATTACH_PARENT_PROCESS: u32 = u32::MAX;
+    const STD_OUTPUT_HANDLE: u32 = -11i32 as u32;
+
+    #[link(name = "kernel32")]
+    extern "system" {
+        fn AttachConsole(process_id: u32) -> i32;
+        fn GetStdHandle(std_handle: u32) -> *mut std::ffi::c_void;
+    }
+
+    // SAFETY: 两个函数都没有指针参数，失败时只返回错误值
+    unsafe {
+        if GetStdHandle(STD_OUTPUT_HANDLE).is_null() {
+            AttachConsole(ATTACH_PARENT_PROCESS);
+        }
+    }
+}
+
+#[cfg(not(windows))]
+fn attach_parent_console() {}
+
+/// 第一个参数是子命令时执行并返回退出码，否则返回 `None`，由调用方启动图形界面
+pub fn run(args: &[String]) -> Option<i32> {
+    let (command, rest) = args.get(1..)?.split_first()?;
+    if is_command(command) {
+        attach_parent_console();
+    }
+    let result = match command.as_str() {
+        "convert" => convert(rest),
+        "lint" => lint_paths(rest),
+        "export" => export(rest),
+        "help" | "--help" | "-h" => {
+            println!("{}", USAGE);
+            Ok(EXIT_OK)
+        }
+        "--version" | "-V" => {
+            println!("vividmark {}", env!("CARGO_PKG_VERSION"));
+            Ok(EXIT_OK)
+        }
+        _ => return None,
+    };
+    Some(result.unwrap_or_else(|e| {
+        eprintln!("vividmark {}: {}", command, e);
+        eprintln!("Run `vividmark help` for usage.");
+        EXIT_ERROR
+    }))
+}
+
+fn is_command(arg: &str) -> bool {
+    matches!(
+        arg,
+        "convert" | "lint" | "export" | "help" | "--help" | "-h" | "--version" | "-V"
+    )
+}
+
+/// 控制台程序 `vividmark-cli` 的入口：没有子命令时打印用法并返回参数错误
+pub fn run_console(args: &[String]) -> i32 {
+    run(args).unwrap_or_else(|| {
+        eprintln!("{}", USAGE);
+        EXIT_ERROR
+    })
+}
+
+fn read_markdown(path: &Path) -> Result<String, String> {
+    fs::read_to_string(path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))
+}
+
+fn create_parent(path: &Path) -> Result<(), String> {
+    match path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create {}: {}", parent.display(), e)),
+        _ => Ok(()),
+    }
+}
+
+/// 单个文档转换为 PDF / HTML / DOCX
+fn convert(args: &[String]) -> Result<i32, String> {
+    let args = Args::parse(args, &["--output", "--format", "--theme", "--template"])?;
+    args.ensure_known(&[
+        "--output",
+        "--format",
+        "--theme",
+        "--template",
+        "--no-embed",
+    ])?;
+    let [input] = args.positional.as_slice() else {
+        return Err("Expected exactly one input file".to_string());
+    };
+    let input = PathBuf::from(input);
+    let output = PathBuf::from(args.value("--output").ok_or("Missing -o <output>")?);
+    let format = args
+        .value("--format")
+        .map(str::to_string)
+        .or_else(|| {
+            output
+                .extension()
+                .map(|ext| ext.to_string_lossy().to_lowercase())
+        })
+        .ok_or("Cannot infer the format from the output path, use --format")?;
+
+    let content = read_markdown(&input)?;
+    let base_dir = input.parent().map(Path::to_path_buf);
+    let stem = input
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().to_string())
+        .unwrap_or_default();
+    create_parent(&output)?;
+
+    match format.as_str() {
+        "html" | "htm" => {
+            let title = html::first_heading(&content).unwrap_or(stem);
+            let document = html::build_standalone_html(
+                &content,
+                &title,
+                &ExportTheme::builtin(args.value("--theme")),
+                !args.flag("--no-embed"),
+                base_dir.as_deref(),
+            );
+            fs::write(&output, document)
+                .map_err(|e| format!("Failed to write {}: {}", output.display(), e))?;
+        }
+        "pdf" => {
+            let options = PdfExportOptions {
+                base_dir: base_dir.map(|dir| dir.to_string_lossy().to_string()),
+                palette: Some(
+                    ExportTheme::builtin(args.value("--theme"))
+                        .palette()
+                        .clone(),
+                ),
+                ..Default::default()
+            };
+            let pages =
+                crate::export::pdf::export_markdown_to_pdf(&content, &output, &stem, &options)?;
+            eprintln!("{} page(s)", pages);
+        }
+        "docx" => {
+            let template = args.value("--template").map(Path::new);
+            let bytes =
+                crate::export::docx::markdown_to_docx(&content, base_dir.as_deref(), template)?;
+            fs::write(&output, bytes)
+                .map_err(|e| format!("Failed to write {}: {}", output.display(), e))?;
+        }
+        other => return Err(format!("Unsupported output format: {}", other)),
+    }
+    println!("{} -> {}", input.display(), output.display());
+    Ok(EXIT_OK)
+}
+
+/// 参数中的文件和目录展开为 Markdown 文件列表
+fn expand_paths(paths: &[String]) -> Result<Vec<PathBuf>, String> {
+    let mut files = Vec::new();
+    for path in paths {
+        let path = PathBuf::from(path);
+        if path.is_dir() {
+            files.extend(workspace::markdown_files(&path));
+        } else if path.is_file() {
+            files.push(path);
+        } else {
+            return Err(format!("No such file or directory: {}", path.display()));
+        }
+    }
+    Ok(files)
+}
+
+/// 诊断位置的列号（从 1 开始，按字符计）
+fn column(content: &str, diagnostic: &LintDiagnostic) -> usize {
+    let line = content
+        .split('\n')
+        .nth(diagnostic.line_index)
+        .unwrap_or_default();
+    let line_start: usize = content
+        .split('\n')
+        .take(diagnostic.line_index)
+        .map(|line| line.encode_utf16().count() + 1)
+        .sum();
+    let mut offset = 0;
+    let mut chars = 0;
+    for ch in line.chars() {
+        if line_start + offset >= diagnostic.char_index {
+            break;
+        }
+        offset += ch.len_utf16();
+        chars += 1;
+    }
+    chars + 1
+}
+
+fn severity_name(severity: Severity) -> &'static str {
+    match severity {
+        Severity::Error => "error",
+        Severity::Warning => "warning",
+        Severity::Info => "info",
+    }
+}
+
+/// 检查 Markdown 文件；`--fix` 先应用可自动修复的问题再报告剩余问题
+fn lint_paths(args: &[String]) -> Result<i32, String> {
+    let args = Args::parse(args, &["--rules", "--max-line-length"])?;
+    args.ensure_known(&["--rules", "--max-line-length", "--fix", "--json"])?;
+    if args.positional.is_empty() {
+        return Err("Expected at least one file or directory".to_string());
+    }
+    let max_line_length: Option<usize> = args
+        .value("--max-line-length")
+        .map(|max| {
+            max.parse()
+                .map_err(|_| format!("Invalid --max-line-length: {}", max))
+        })
+        .transpose()?;
+    let rules: Option<Vec<String>> = args.value("--rules").map(|rules| {
+        rules
+            .split(',')
+            .map(|rule| rule.trim().to_string())
+            .filter(|rule| !rule.is_empty())
+            .collect()
+    });
+    if let Some(unknown) = rules.iter().flatten().find(|name| {
+        !lint::LintRule::ALL
+            .iter()
+            .any(|rule| rule.name() == name.as_str())
+    }) {
+        return Err(format!("Unknown lint rule: {}", unknown));
+    }
+    let json = args.flag("--json");
+
+    let mut report = Vec::new();
+    let mut problems = 0;
+    let mut fixed = 0;
+    for path in expand_paths(&args.positional)? {
+        let mut content = match read_markdown(&path) {
+            Ok(content) => content,
+            Err(e) => {
+                eprintln!("{}", e);
+                continue;
+            }
+        };
+        // 文件所在工作区的 `.vividmark/config.toml` 中的检查设置同样生效
+        let mut settings = workspace_config::resolve(&Settings::default(), &path).lint;
+        if let Some(max) = max_line_length {
+            settings.max_line_length = max;
+        }
+        if args.flag("--fix") {
+            let (updated, count) = lint::fix_content(&content, &settings, rules.as_deref());
+            if count > 0 {
+                fs::write(&path, &updated)
+                    .map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+                fixed += count;
+                content = updated;
+            }
+        }
+        let diagnostics = lint::lint_content(&content, &settings, rules.as_deref());
+        problems += diagnostics
+            .iter()
+            .filter(|d| d.severity != Severity::Info)
+            .count();
+        if json {
+            report.push(serde_json::json!({
+                "path": path.to_string_lossy(),
+                "diagnostics": diagnostics,
+            }));
+            continue;
+        }
+        for diagnostic in &diagnostics {
+            println!(
+                "{}:{}:{}: {} [{}] {}",
+                path.display(),
+                diagnostic.line_index + 1,
+                column(&content, diagnostic),
+                severity_name(diagnostic.severity),
+                diagnostic.rule.name(),
+                diagnostic.message
+            );
+        }
+    }
+
+    if json {
+        let text = serde_json::to_string_pretty(&report).map_err(|e| e.to_string())?;
+        println!("{}", text);
+    }
+    if fixed > 0 {
+        eprintln!("Fixed {} problem(s)", fixed);
+    }
+    Ok(if problems > 0 { EXIT_FAILURES } else { EXIT_OK })
+}
+
+/// 导出整个目录，进度输出到 stderr
+fn export(args: &[String]) -> Result<i32, String> {
+    let args = Args::parse(args, &["--output", "--format", "--theme"])?;
+    args.ensure_known(&["--output", "--format", "--theme"])?;
+    let format = match args.value("--format").ok_or("Missing --format html|pdf")? {
+        "html" => WorkspaceExportFormat::Html,
+        "pdf" => WorkspaceExportFormat::Pdf,
+        other => return Err(format!("Unsupported export format: {}", other)),
+    };
+    let [root] = args.positional.as_slice() else {
+        return Err("Expected exactly one directory".to_string());
+    };
+    let root = PathBuf::from(root);
+    if !root.is_dir() {
+        return Err(format!("Directory does not exist: {}", root.display()));
+    }
+    let options = ExportWorkspaceOptions {
+        output: args.value("--output").map(str::to_string),
+        theme: args.value("--theme").map(str::to_string),
+        pdf: None,
+    };
+    let output = workspace_export::output_dir(&root, &options).map_err(|e| e.to_string())?;
+    let theme = ExportTheme::builtin(options.theme.as_deref());
+
+    let result = workspace_export::run(
+        &root,
+        &output,
+        format,
+        &options,
+        &theme,
+        &|progress| {
+            if let Some(path) = progress.path {
+                eprintln!("[{}/{}] {}", progress.done + 1, progress.total, path);
+            }
+        },
+        &|| false,
+    );
+    for failed in &result.failed {
+        eprintln!("Failed: {}: {}", failed.path, failed.error);
+    }
+    println!(
+        "Exported {} file(s) to {}",
+        result.exported.len(),
+        result.output
+    );
+    Ok(if result.failed.is_empty() {
+        EXIT_OK
+    } else {
+        EXIT_FAILURES
+    })
+}
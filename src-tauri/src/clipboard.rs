@@ -0,0 +1,124 @@
+//! 剪贴板互通
+//!
+//! 从浏览器、Word、Google 文档复制的富文本以 HTML 形式放在剪贴板中，
+//! 粘贴时在后端转换为 Markdown，而不是把原始 HTML 插入文档。
+//! 提供文档路径时，内嵌（data URI）和本地临时文件中的图片会保存到文档的资源目录。
+
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+
+use serde::{Deserialize, Serialize};
+use tauri::AppHandle;
+
+use crate::access::{self, AccessKind};
+use crate::assets;
+use crate::error::VividError;
+use crate::import::{html, AssetWriter};
+
+/// 读取剪贴板的结果
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ClipboardMarkdown {
+    pub markdown: String,
+    /// 是否由 HTML 转换而来（否则为剪贴板中的纯文本）
+    pub from_html: bool,
+}
+
+/// 粘贴的图片写入的资源目录（与 `save_asset` 相同），并检查该目录的写入权限；
+/// 没有文档路径时不保存图片
+fn assets_dir_for(
+    app: &AppHandle,
+    document_path: Option<&str>,
+    assets_dir: Option<String>,
+) -> Result<Option<String>, VividError> {
+    let Some(document) = document_path
+        .filter(|p| !p.trim().is_empty())
+        .map(Path::new)
+    else {
+        return Ok(assets_dir);
+    };
+    let assets_dir = assets::assets_dir_setting(app, document, assets_dir);
+    access::ensure_access(
+        app,
+        &assets::resolve_assets_dir(document, Some(&assets_dir)),
+        AccessKind::Write,
+    )?;
+    Ok(Some(assets_dir))
+}
+
+/// 转换 HTML，只保存粘贴内容自带的图片，远程和相对地址保持不变
+fn convert(html: &str, document_path: Option<&str>, assets_dir: Option<&str>) -> String {
+    let document = document_path
+        .filter(|p| !p.trim().is_empty())
+        .map(PathBuf::from)
+        .filter(|p| p.parent().is_some());
+    let Some(document) = document else {
+        return html::html_to_markdown(html, &mut |_| None);
+    };
+
+    let mut assets = AssetWriter::new(&document, assets_dir);
+    let markdown = html::html_to_markdown(html, &mut |src| {
+        if src.starts_with("data:") || src.starts_with("file:") {
+            assets.import_source(src, Path::new(""))
+        } else {
+            None
+        }
+    });
+    for warning in assets.warnings() {
+        log::warn!("[clipboard] Image not saved: {}", warning);
+    }
+    markdown
+}
+
+// 将粘贴的 HTML 转换为 Markdown
+#[tauri::command]
+pub fn convert_html_to_markdown(
+    app: AppHandle,
+    html: String,
+    document_path: Option<String>,
+    assets_dir: Option<String>,
+) -> Result<String, VividError> {
+    let start = Instant::now();
+    let assets_dir = assets_dir_for(&app, document_path.as_deref(), assets_dir)?;
+    log::debug!("[convert_html_to_markdown] HTML size: {} bytes", html.len());
+    let markdown = convert(&html, document_path.as_deref(), assets_dir.as_deref());
+    log::info!(
+        "[convert_html_to_markdown] ✓ Success: {} bytes in {:?}",
+        markdown.len(),
+        start.elapsed()
+    );
+    Ok(markdown)
+}
+
+// 读取剪贴板：有 HTML 时转换为 Markdown，否则返回纯文本
+#[tauri::command]
+pub async fn read_clipboard_markdown(
+    app: AppHandle,
+    document_path: Option<String>,
+    assets_dir: Option<String>,
+) -> Result<ClipboardMarkdown, VividError> {
+    let assets_dir = assets_dir_for(&app, document_path.as_deref(), assets_dir)?;
+    tauri::async_runtime::spawn_blocking(move || {
+        let mut clipboard =
+            arboard::Clipboard::new().map_err(|e| format!("Failed to open clipboard: {}", e))?;
+        if let Ok(html) = clipboard.get().html() {
+            if !html.trim().is_empty() {
+                let markdown = convert(&html, document_path.as_deref(), assets_dir.as_deref());
+                log::info!("[read_clipboard_markdown] ✓ Converted HTML from clipboard");
+                return Ok(ClipboardMarkdown {
+                    markdown,
+                    from_html: true,
+                });
+            }
+        }
+        let text = clipboard
+            .get()
+            .text()
+            .map_err(|e| format!("Clipboard does not contain text: {}", e))?;
+        Ok(ClipboardMarkdown {
+            markdown: text,
+            from_html: false,
+        })
+    })
+    .await
+    .map_err(|e| format!("Clipboard task failed: {}", e))?
+}
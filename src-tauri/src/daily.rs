@@ -0,0 +1,174 @@
+//! 日记
+//!
+//! `open_daily_note` 按设置中的目录和路径模式（默认 `journal/YYYY/MM/YYYY-MM-DD.md`）
+//! 找到指定日期的日记，不存在时用日记模板创建，返回与 `read_file` 相同的文件信息。
+
+use std::collections::HashMap;
+use std::path::{Component, Path, PathBuf};
+
+use chrono::{Datelike, Local, NaiveDate, TimeZone};
+use tauri::{AppHandle, Manager};
+
+use crate::access::{self, AccessKind};
+use crate::error::VividError;
+use crate::largefile::LargeFileStore;
+use crate::settings::{Settings, SettingsStore};
+use crate::templates::{self, TemplateStore};
+use crate::workspace::{self, Workspace};
+use crate::FileInfo;
+
+/// 将路径模式中的 `YYYY` / `YY` / `MM` / `DD` 替换为日期
+pub fn expand_pattern(pattern: &str, date: NaiveDate) -> String {
+    let tokens = [
+        ("YYYY", format!("{:04}", date.year())),
+        ("YY", format!("{:02}", date.year().rem_euclid(100))),
+        ("MM", format!("{:02}", date.month())),
+        ("DD", format!("{:02}", date.day())),
+    ];
+    let mut out = String::with_capacity(pattern.len() + 8);
+    let mut rest = pattern;
+    'outer: while !rest.is_empty() {
+        for (token, value) in &tokens {
+            if let Some(after) = rest.strip_prefix(token) {
+                out.push_str(value);
+                rest = after;
+                continue 'outer;
+            }
+        }
+        let c = rest.chars().next().unwrap_or_default();
+        out.push(c);
+        rest = &rest[c.len_utf8()..];
+    }
+    out
+}
+
+/// 从日记的相对路径反推日期，与 `expand_pattern` 相反；模式中缺少年、月或日时返回 `None`
+pub fn date_from_path(pattern: &str, relative: &Path) -> Option<NaiveDate> {
+    let pattern = pattern
+        .trim_end_matches(".md")
+        .trim_end_matches(".markdown");
+    let mut regex = String::from("^");
+    let mut tokens = Vec::new();
+    let mut rest = pattern;
+    'outer: while !rest.is_empty() {
+        for token in ["YYYY", "YY", "MM", "DD"] {
+            if let Some(after) = rest.strip_prefix(token) {
+                regex.push_str(if token == "YYYY" {
+                    r"(\d{4})"
+                } else {
+                    r"(\d{2})"
+                });
+                tokens.push(token);
+                rest = after;
+                continue 'outer;
+            }
+        }
+        let c = rest.chars().next().unwrap_or_default();
+        regex.push_str(&regex::escape(&c.to_string()));
+        rest = &rest[c.len_utf8()..];
+    }
+    regex.push('$');
+
+    let relative = relative.with_extension("");
+    let relative = relative.to_string_lossy().replace('\\', "/");
+    let caps = regex::Regex::new(&regex).ok()?.captures(&relative)?;
+    let (mut year, mut month, mut day) = (None, None, None);
+    for (i, token) in tokens.iter().enumerate() {
+        let value: u32 = caps.get(i + 1)?.as_str().parse().ok()?;
+        let slot = match *token {
+            "YYYY" => (&mut year, value as i32),
+            "YY" => (&mut year, 2000 + value as i32),
+            "MM" => (&mut month, value as i32),
+            _ => (&mut day, value as i32),
+        };
+        // 同一部分出现多次时必须一致
+        if slot.0.is_some_and(|v| v != slot.1) {
+            return None;
+        }
+        *slot.0 = Some(slot.1);
+    }
+    NaiveDate::from_ymd_opt(year?, month? as u32, day? as u32)
+}
+
+/// 日记文件路径：模式必须是相对路径，且不能跳出日记根目录
+pub fn daily_note_path(root: &Path, pattern: &str, date: NaiveDate) -> Result<PathBuf, String> {
+    let relative = PathBuf::from(expand_pattern(pattern, date));
+    let escapes = relative
+        .components()
+        .any(|c| !matches!(c, Component::Normal(_) | Component::CurDir));
+    if relative.as_os_str().is_empty() || escapes {
+        return Err(format!("Invalid daily note pattern: {}", pattern));
+    }
+    let mut path = root.join(relative);
+    if !workspace::is_markdown(&path) {
+        let mut name = path.file_name().unwrap_or_default().to_os_string();
+        name.push(".md");
+        path.set_file_name(name);
+    }
+    Ok(path)
+}
+
+/// 日记根目录：设置中的目录 > 当前工作区 > 默认保存目录；不在允许访问的范围内时返回错误
+pub(crate) fn daily_root(app: &AppHandle, settings: &Settings) -> Result<PathBuf, VividError> {
+    let folder = settings
+        .daily_notes
+        .folder
+        .as_deref()
+        .filter(|f| !f.trim().is_empty());
+    let root = match (folder, app.state::<Workspace>().root()) {
+        (Some(folder), _) => PathBuf::from(folder),
+        (None, Some(root)) => root,
+        (None, None) => settings
+            .default_save_dir
+            .as_ref()
+            .map(PathBuf::from)
+            .ok_or("No folder configured for daily notes")?,
+    };
+    access::ensure_access(app, &root, AccessKind::Write)?;
+    Ok(root)
+}
+
+// 打开指定日期（`YYYY-MM-DD`，默认今天）的日记，不存在时从模板创建
+#[tauri::command]
+pub fn open_daily_note(app: AppHandle, date: Option<String>) -> Result<FileInfo, VividError> {
+    let date = match date.as_deref() {
+        Some(date) => NaiveDate::parse_from_str(date.trim(), "%Y-%m-%d")
+            .map_err(|e| format!("Invalid date {}: {}", date, e))?,
+        None => Local::now().date_naive(),
+    };
+    let settings = app.state::<SettingsStore>().get();
+    let root = daily_root(&app, &settings)?;
+    let path = daily_note_path(&root, &settings.daily_notes.pattern, date)?;
+    log::info!("[open_daily_note] {} -> {:?}", date, path);
+
+    if path.exists() {
+        return crate::load_file(
+            app.state::<LargeFileStore>(),
+            app.state::<crate::encryption::EncryptionKeys>(),
+            path.to_string_lossy().to_string(),
+        );
+    }
+
+    // 模板中的日期变量使用日记的日期（保留当前时刻）
+    let now = Local::now();
+    let at = Local
+        .from_local_datetime(&date.and_time(now.time()))
+        .earliest()
+        .unwrap_or(now);
+    let title = date.format("%Y-%m-%d").to_string();
+    let empty_note = format!("# {}\n\n", title);
+    let variables = HashMap::from([("title".to_string(), title)]);
+    let content = match settings.daily_notes.template.as_deref() {
+        Some(id) => app
+            .state::<TemplateStore>()
+            .instantiate(id, &path, variables, &at)
+            .unwrap_or_else(|e| {
+                log::warn!("[open_daily_note] {}, creating an empty note", e);
+                empty_note
+            }),
+        None => empty_note,
+    };
+    let info = templates::write_new_document(&path, content)?;
+    log::info!("[open_daily_note] ✓ Created {}", info.path);
+    Ok(info)
+}
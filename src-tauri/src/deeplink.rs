@@ -0,0 +1,152 @@
+//! `vividmark://` 深度链接
+//!
+//! 任务管理器、终端脚本等外部工具可以用 `vividmark://open?path=<文件>&line=<行号>`
+//! 直接跳转到某篇笔记的某一行。`path` 可以是绝对路径，也可以是相对当前工作区的路径；
+//! 目标必须位于当前或最近打开过的工作区内，避免任意链接打开系统中的其它文件。
+//! 校验通过后发送 `deep-link-navigate` 事件，前端就绪前收到的链接由 `instance` 排队。
+
+use std::path::PathBuf;
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager, Url};
+use tauri_plugin_deep_link::DeepLinkExt;
+
+use crate::instance::{self, Launch};
+use crate::session::SessionStore;
+use crate::workspace::Workspace;
+
+/// 注册的 URL 协议
+pub const SCHEME: &str = "vividmark";
+
+/// 链接校验通过后发送的事件
+pub const DEEP_LINK_EVENT: &str = "deep-link-navigate";
+
+/// 链接指向的笔记位置
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DeepLinkTarget {
+    pub path: String,
+    /// 从 1 开始的行号
+    pub line: Option<u32>,
+    /// 笔记所在的工作区根目录
+    pub workspace: String,
+}
+
+/// 当前工作区和最近打开过的工作区，当前工作区在前
+fn known_workspaces(app: &AppHandle) -> Vec<PathBuf> {
+    let mut roots: Vec<PathBuf> = app.state::<Workspace>().root().into_iter().collect();
+    for root in app.state::<SessionStore>().recent_workspaces() {
+        if !roots.contains(&root) {
+            roots.push(root);
+        }
+    }
+    roots
+}
+
+/// 解析并校验链接
+pub fn parse(url: &Url, workspaces: &[PathBuf]) -> Result<DeepLinkTarget, String> {
+    if url.scheme() != SCHEME {
+        return Err(format!("Unsupported URL scheme: {}", url.scheme()));
+    }
+    // `vividmark://open?...` 中 `open` 是主机名，`vividmark:open?...` 中是路径
+    let action = url
+        .host_str()
+        .unwrap_or_else(|| url.path().trim_matches('/'));
+    if action != "open" {
+        return Err(format!("Unsupported deep link action: {}", action));
+    }
+
+    let mut path = None;
+    let mut line = None;
+    for (key, value) in url.query_pairs() {
+        match key.as_ref() {
+            "path" => path = Some(value.to_string()),
+            "line" => {
+                line = Some(
+                    value
+                        .parse::<u32>()
+                        .ok()
+                        .filter(|line| *line > 0)
+                        .ok_or_else(|| format!("Invalid line number: {}", value))?,
+                )
+            }
+            _ => {}
+        }
+    }
+    let path = path
+        .filter(|p| !p.trim().is_empty())
+        .ok_or_else(|| "Deep link is missing the path parameter".to_string())?;
+
+    let path = PathBuf::from(path);
+    let candidates: Vec<PathBuf> = if path.is_absolute() {
+        vec![path.clone()]
+    } else {
+        workspaces.iter().map(|root| root.join(&path)).collect()
+    };
+    for candidate in candidates {
+        let Ok(resolved) = candidate.canonicalize() else {
+            continue;
+        };
+        let Some(root) = workspaces.iter().find(|root| {
+            root.canonicalize()
+                .is_ok_and(|root| resolved.starts_with(root))
+        }) else {
+            continue;
+        };
+        if !instance::is_openable(&resolved) {
+            return Err(format!("Not a Markdown file: {}", resolved.display()));
+        }
+        return Ok(DeepLinkTarget {
+            path: resolved.to_string_lossy().to_string(),
+            line,
+            workspace: root.to_string_lossy().to_string(),
+        });
+    }
+    Err(format!(
+        "File is not inside a known workspace: {}",
+        path.display()
+    ))
+}
+
+/// 处理收到的链接
+fn handle_url(app: &AppHandle, url: &Url) {
+    if url.scheme() != SCHEME {
+        return;
+    }
+    log::info!("[deep_link] Received {}", url);
+    match parse(url, &known_workspaces(app)) {
+        Ok(target) => {
+            if let Some(window) = app.get_webview_window("main") {
+                let _ = window.unminimize();
+                let _ = window.set_focus();
+            }
+            instance::dispatch(app, Launch::Navigate(target));
+        }
+        Err(e) => log::warn!("[deep_link] Rejected {}: {}", url, e),
+    }
+}
+
+/// 注册协议并处理启动时带入的链接，在 `setup` 中调用
+pub fn init(app: &AppHandle) {
+    // Linux 和 Windows 开发模式下没有安装包写入的协议注册，运行时注册
+    #[cfg(any(target_os = "linux", all(debug_assertions, windows)))]
+    if let Err(e) = app.deep_link().register_all() {
+        log::warn!("[deep_link] Failed to register URL scheme: {}", e);
+    }
+
+    let handle = app.clone();
+    app.deep_link().on_open_url(move |event| {
+        for url in event.urls() {
+            handle_url(&handle, &url);
+        }
+    });
+
+    match app.deep_link().get_current() {
+        Ok(Some(urls)) => {
+            for url in &urls {
+                handle_url(app, url);
+            }
+        }
+        Ok(None) => {}
+        Err(e) => log::warn!("[deep_link] Failed to read launch URL: {}", e),
+    }
+}
@@ -0,0 +1,321 @@
+//! 图表渲染
+//!
+//! 将 Mermaid / PlantUML / Graphviz 代码块渲染为 SVG。与 Git 集成一样调用系统中安装的命令行工具
+//! （`mmdc`、`plantuml`、`dot`），不在应用中内置 JS 运行时或 Java。
+//! 结果按图表类型和源码的哈希缓存在内存中，预览和导出（HTML / PDF）共用同一份缓存。
+
+use std::collections::HashMap;
+use std::fs;
+use std::io::{Read, Write};
+use std::process::{Command, Stdio};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use base64::Engine;
+use resvg::{tiny_skia, usvg};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::error::VividError;
+
+/// 单个图表的渲染超时（`mmdc` 需要启动无头浏览器，比较慢）
+const RENDER_TIMEOUT: Duration = Duration::from_secs(30);
+/// 内存缓存的最大条目数，超出后整体清空
+const MAX_CACHE_ENTRIES: usize = 256;
+/// 关闭 HTML 标签，生成纯 SVG 文本，PDF 导出栅格化时才能显示文字
+const MERMAID_CONFIG: &str = r#"{"htmlLabels":false,"flowchart":{"htmlLabels":false}}"#;
+
+/// 图表类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DiagramKind {
+    Mermaid,
+    #[serde(alias = "puml")]
+    Plantuml,
+    #[serde(alias = "dot")]
+    Graphviz,
+}
+
+impl DiagramKind {
+    /// 根据代码块语言识别图表类型
+    pub fn from_lang(lang: &str) -> Option<Self> {
+        match lang.to_ascii_lowercase().as_str() {
+            "mermaid" => Some(DiagramKind::Mermaid),
+            "plantuml" | "puml" => Some(DiagramKind::Plantuml),
+            "dot" | "graphviz" => Some(DiagramKind::Graphviz),
+            _ => None,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            DiagramKind::Mermaid => "mermaid",
+            DiagramKind::Plantuml => "plantuml",
+            DiagramKind::Graphviz => "graphviz",
+        }
+    }
+}
+
+type DiagramCache = Mutex<HashMap<String, Arc<str>>>;
+
+fn cache() -> &'static DiagramCache {
+    static CACHE: OnceLock<DiagramCache> = OnceLock::new();
+    CACHE.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+/// 缓存键：图表类型 + 源码的 SHA-256
+pub fn diagram_hash(kind: DiagramKind, source: &str) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(kind.name().as_bytes());
+    hasher.update([0]);
+    hasher.update(source.as_bytes());
+    hasher
+        .finalize()
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
+
+/// 运行外部命令：`input` 写入标准输入，返回标准输出；超时后结束进程
+pub fn run_tool(program: &str, args: &[&str], input: &str) -> Result<Vec<u8>, String> {
+    run_tool_with_timeout(program, args, input, RENDER_TIMEOUT)
+}
+
+/// 同 [`run_tool`]，使用指定的超时
+pub fn run_tool_with_timeout(
+    program: &str,
+    args: &[&str],
+    input: &str,
+    timeout: Duration,
+) -> Result<Vec<u8>, String> {
+    let mut command = Command::new(program);
+    command.args(args);
+    run_command(command, program, input, timeout)
+}
+
+/// 运行已设置好参数的命令，通过标准输入传入 `input`，返回标准输出
+pub fn run_command(
+    mut command: Command,
+    program: &str,
+    input: &str,
+    timeout: Duration,
+) -> Result<Vec<u8>, String> {
+    command
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped());
+    // 避免 Windows 上弹出控制台窗口
+    #[cfg(windows)]
+    {
+        use std::os::windows::process::CommandExt;
+        const CREATE_NO_WINDOW: u32 = 0x0800_0000;
+        command.creation_flags(CREATE_NO_WINDOW);
+    }
+
+    log::debug!("[diagram] {} {:?}", program, command.get_args());
+    let mut child = command.spawn().map_err(|e| {
+        if e.kind() == std::io::ErrorKind::NotFound {
+            format!("{} is not installed or not in PATH", program)
+        } else {
+            format!("Failed to run {}: {}", program, e)
+        }
+    })?;
+
+    // 标准输入输出在单独的线程中读写，避免管道缓冲区写满后互相等待
+    let mut stdin = child.stdin.take();
+    let input = input.to_string();
+    let writer = thread::spawn(move || {
+        if let Some(stdin) = stdin.as_mut() {
+            let _ = stdin.write_all(input.as_bytes());
+        }
+    });
+    let mut stdout = child.stdout.take();
+    let reader = thread::spawn(move || {
+        let mut buf = Vec::new();
+        if let Some(stdout) = stdout.as_mut() {
+            let _ = stdout.read_to_end(&mut buf);
+        }
+        buf
+    });
+    let mut stderr = child.stderr.take();
+    let error_reader = thread::spawn(move || {
+        let mut buf = String::new();
+        if let Some(stderr) = stderr.as_mut() {
+            let _ = stderr.read_to_string(&mut buf);
+        }
+        buf
+    });
+
+    let deadline = Instant::now() + timeout;
+    let status = loop {
+        match child.try_wait() {
+            Ok(Some(status)) => break status,
+            Ok(None) if Instant::now() >= deadline => {
+                let _ = child.kill();
+                let _ = child.wait();
+                return Err(format!("{} timed out after {:?}", program, timeout));
+            }
+            Ok(None) => thread::sleep(Duration::from_millis(20)),
+            Err(e) => return Err(format!("Failed to run {}: {}", program, e)),
+        }
+    };
+    let _ = writer.join();
+    let output = reader.join().unwrap_or_default();
+    let errors = error_reader.join().unwrap_or_default();
+
+    if status.success() {
+        Ok(output)
+    } else {
+        Err(format!("{} failed: {}", program, errors.trim()))
+    }
+}
+
+/// Mermaid：`mmdc` 不能稳定地读写标准输入输出，借助临时文件
+fn render_mermaid(source: &str, hash: &str) -> Result<Vec<u8>, String> {
+    let dir = std::env::temp_dir();
+    let input = dir.join(format!("vividmark-{}.mmd", hash));
+    let output = dir.join(format!("vividmark-{}.svg", hash));
+    let config = dir.join("vividmark-mermaid.json");
+    let write = |path: &std::path::Path, data: &str| {
+        fs::write(path, data).map_err(|e| format!("Failed to write {:?}: {}", path, e))
+    };
+    write(&input, source)?;
+    write(&config, MERMAID_CONFIG)?;
+
+    let result = run_tool(
+        "mmdc",
+        &[
+            "-i",
+            &input.to_string_lossy(),
+            "-o",
+            &output.to_string_lossy(),
+            "-c",
+            &config.to_string_lossy(),
+            "-b",
+            "transparent",
+            "-q",
+        ],
+        "",
+    )
+    .and_then(|_| fs::read(&output).map_err(|e| format!("Failed to read mmdc output: {}", e)));
+    let _ = fs::remove_file(&input);
+    let _ = fs::remove_file(&output);
+    result
+}
+
+/// PlantUML：设置了 `PLANTUML_JAR` 时用 `java -jar` 运行，否则调用 `plantuml`
+fn render_plantuml(source: &str) -> Result<Vec<u8>, String> {
+    let args = ["-tsvg", "-pipe", "-charset", "UTF-8"];
+    match std::env::var("PLANTUML_JAR") {
+        Ok(jar) if !jar.is_empty() => {
+            let mut java_args = vec!["-Djava.awt.headless=true", "-jar", jar.as_str()];
+            java_args.extend(args);
+            run_tool("java", &java_args, source)
+        }
+        _ => run_tool("plantuml", &args, source),
+    }
+}
+
+/// 渲染图表为 SVG，相同的源码直接返回缓存
+pub fn render_svg(kind: DiagramKind, source: &str) -> Result<Arc<str>, String> {
+    let hash = diagram_hash(kind, source);
+    if let Some(svg) = cache().lock().map_err(|e| e.to_string())?.get(&hash) {
+        return Ok(svg.clone());
+    }
+
+    let start = Instant::now();
+    let output = match kind {
+        DiagramKind::Mermaid => render_mermaid(source, &hash)?,
+        DiagramKind::Plantuml => render_plantuml(source)?,
+        DiagramKind::Graphviz => run_tool("dot", &["-Tsvg"], source)?,
+    };
+    let svg = String::from_utf8(output).map_err(|_| "Renderer output is not valid UTF-8")?;
+    if !svg.contains("<svg") {
+        return Err(format!("{} renderer did not produce SVG", kind.name()));
+    }
+    log::debug!(
+        "[diagram] Rendered {} diagram ({} bytes) in {:?}",
+        kind.name(),
+        svg.len(),
+        start.elapsed()
+    );
+
+    let svg: Arc<str> = svg.into();
+    let mut cache = cache().lock().map_err(|e| e.to_string())?;
+    if cache.len() >= MAX_CACHE_ENTRIES {
+        cache.clear();
+    }
+    cache.insert(hash, svg.clone());
+    Ok(svg)
+}
+
+/// SVG 的 data URI，用作 `<img>` 地址
+///
+/// 以图片形式嵌入而不是内联 `<svg>`：多个 Mermaid 图表的元素 id 和样式不会互相冲突，
+/// 也能通过预览的 HTML 过滤。
+pub fn svg_data_uri(svg: &str) -> String {
+    format!(
+        "data:image/svg+xml;base64,{}",
+        base64::engine::general_purpose::STANDARD.encode(svg)
+    )
+}
+
+fn font_database() -> Arc<usvg::fontdb::Database> {
+    static FONTS: OnceLock<Arc<usvg::fontdb::Database>> = OnceLock::new();
+    FONTS
+        .get_or_init(|| {
+            let mut fonts = usvg::fontdb::Database::new();
+            fonts.load_system_fonts();
+            Arc::new(fonts)
+        })
+        .clone()
+}
+
+/// 将 SVG 栅格化为白底 RGB 图片（PDF 导出不支持矢量图和透明通道）
+///
+/// `max_width` 为最大像素宽度，较宽的图表按比例缩小，较窄的按 `scale` 放大以保证清晰度。
+pub fn rasterize_svg(svg: &str, max_width: u32, scale: f32) -> Result<image::RgbImage, String> {
+    let options = usvg::Options {
+        fontdb: font_database(),
+        ..usvg::Options::default()
+    };
+    let tree = usvg::Tree::from_str(svg, &options).map_err(|e| format!("Invalid SVG: {}", e))?;
+    let size = tree.size();
+    let mut scale = scale;
+    if max_width > 0 && size.width() * scale > max_width as f32 {
+        scale = max_width as f32 / size.width();
+    }
+    let width = (size.width() * scale).ceil().max(1.0) as u32;
+    let height = (size.height() * scale).ceil().max(1.0) as u32;
+
+    let mut pixmap =
+        tiny_skia::Pixmap::new(width, height).ok_or("SVG is too large to rasterize")?;
+    pixmap.fill(tiny_skia::Color::WHITE);
+    resvg::render(
+        &tree,
+        tiny_skia::Transform::from_scale(scale, scale),
+        &mut pixmap.as_mut(),
+    );
+
+    // 背景不透明，预乘 alpha 后的颜色即为最终颜色
+    let rgb: Vec<u8> = pixmap
+        .data()
+        .chunks_exact(4)
+        .flat_map(|p| [p[0], p[1], p[2]])
+        .collect();
+    image::RgbImage::from_raw(width, height, rgb).ok_or_else(|| "Invalid image size".to_string())
+}
+
+// 将图表源码渲染为 SVG
+#[tauri::command]
+pub async fn render_diagram(kind: DiagramKind, source: String) -> Result<String, VividError> {
+    let svg = tauri::async_runtime::spawn_blocking(move || render_svg(kind, &source))
+        .await
+        .map_err(|e| format!("Diagram task failed: {}", e))?
+        .map_err(|e| {
+            log::warn!("[render_diagram] {}", e);
+            e
+        })?;
+    Ok(svg.to_string())
+}
@@ -0,0 +1,144 @@
+//! 文档界面状态
+//!
+//! 记录每篇文档上次的光标、滚动位置、折叠的章节和编辑 / 预览 / 分栏模式，重新打开长文档时
+//! 回到上次离开的位置。与会话（见 `session`）不同，关闭标签页后状态仍然保留。
+//!
+//! 状态按 `paths::canonicalize` 规范化后的路径保存在应用数据目录的 `doc_state.json` 中，
+//! 最多保留最近更新的 1000 篇文档；`rename_with_link_update` 移动文件时同时迁移状态。
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use serde::{Deserialize, Serialize};
+use tauri::State;
+
+use crate::error::VividError;
+use crate::session::CursorPosition;
+use crate::{paths, storage};
+
+const DOC_STATE_FILE: &str = "doc_state.json";
+/// 最多保留的文档数，超出后丢弃最久未更新的
+const MAX_DOCUMENTS: usize = 1000;
+
+/// 编辑区的显示模式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ViewMode {
+    Edit,
+    Preview,
+    Split,
+}
+
+/// 一篇文档的界面状态
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DocState {
+    /// 光标、选区和编辑区的滚动位置
+    pub cursor: CursorPosition,
+    /// 预览区的滚动位置
+    pub preview_scroll_top: Option<f64>,
+    /// 折叠的章节（标题的 slug，与 `parse_markdown` 返回的大纲一致）
+    pub folded: Vec<String>,
+    pub view_mode: Option<ViewMode>,
+    /// 更新时间（Unix 毫秒），由后端填写
+    pub updated_at: u64,
+}
+
+/// 文档界面状态的存储
+pub struct DocStateStore {
+    path: PathBuf,
+    states: Mutex<HashMap<String, DocState>>,
+}
+
+fn key(path: &Path) -> String {
+    paths::canonicalize(path).to_string_lossy().to_string()
+}
+
+impl DocStateStore {
+    pub fn load(dir: PathBuf) -> Self {
+        let path = dir.join(DOC_STATE_FILE);
+        let states = storage::load_json(&path);
+        DocStateStore {
+            path,
+            states: Mutex::new(states),
+        }
+    }
+
+    pub fn get(&self, path: &Path) -> Option<DocState> {
+        self.states.lock().ok()?.get(&key(path)).cloned()
+    }
+
+    /// 保存状态，返回填写了更新时间的状态
+    pub fn save(&self, path: &Path, mut state: DocState) -> Result<DocState, String> {
+        let mut states = self.states.lock().map_err(|e| e.to_string())?;
+        state.updated_at = storage::now_millis();
+        states.insert(key(path), state.clone());
+        if states.len() > MAX_DOCUMENTS {
+            let mut stale: Vec<(String, u64)> = states
+                .iter()
+                .map(|(path, state)| (path.clone(), state.updated_at))
+                .collect();
+            stale.sort_by_key(|(_, updated_at)| *updated_at);
+            for (path, _) in stale.into_iter().take(states.len() - MAX_DOCUMENTS) {
+                states.remove(&path);
+            }
+        }
+        storage::save_json(&self.path, &*states)?;
+        Ok(state)
+    }
+
+    /// 文件或目录移动后迁移其下所有文档的状态
+    pub fn move_path(&self, old: &Path, new: &Path) {
+        let Ok(mut states) = self.states.lock() else {
+            return;
+        };
+        let (old, new) = (paths::canonicalize(old), paths::canonicalize(new));
+        let moved: Vec<String> = states
+            .keys()
+            .filter(|path| Path::new(path).starts_with(&old))
+            .cloned()
+            .collect();
+        if moved.is_empty() {
+            return;
+        }
+        for path in moved {
+            if let Some(state) = states.remove(&path) {
+                let rel = Path::new(&path).strip_prefix(&old).unwrap_or(Path::new(""));
+                let target = if rel.as_os_str().is_empty() {
+                    new.clone()
+                } else {
+                    new.join(rel)
+                };
+                states.insert(target.to_string_lossy().to_string(), state);
+            }
+        }
+        if let Err(e) = storage::save_json(&self.path, &*states) {
+            log::warn!("[doc_state] Failed to save moved state: {}", e);
+        }
+    }
+}
+
+// 保存文档的光标、滚动位置、折叠章节和显示模式
+#[tauri::command]
+pub fn save_doc_state(
+    store: State<'_, DocStateStore>,
+    path: String,
+    state: DocState,
+) -> Result<DocState, VividError> {
+    log::debug!(
+        "[save_doc_state] {} (offset: {}, folded: {})",
+        path,
+        state.cursor.offset,
+        state.folded.len()
+    );
+    Ok(store.save(Path::new(&path), state)?)
+}
+
+// 读取文档上次的界面状态，没有记录时返回空
+#[tauri::command]
+pub fn get_doc_state(store: State<'_, DocStateStore>, path: String) -> Option<DocState> {
+    let state = store.get(Path::new(&path));
+    log::debug!("[get_doc_state] {} -> {}", path, state.is_some());
+    state
+}
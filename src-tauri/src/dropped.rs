@@ -0,0 +1,193 @@
+//! 拖入窗口的文件
+//!
+//! 窗口收到拖放事件时记下拖入的路径，前端随后调用 `handle_dropped_paths` 决定如何处理：
+//!
+//! - Markdown 文件和加密文档：由前端打开；
+//! - 其它文件（图片、PDF、音频等附件）：复制到当前文档的资源目录（按内容去重），
+//!   返回可直接插入的 Markdown 链接（见 `attachments`）；
+//! - 文件夹：由前端询问是否作为工作区打开。
+//!
+//! 只处理最近确实拖入过窗口的路径，并为它们授予本次运行的访问权限（见 `access`），
+//! 前端无法借此访问任意路径。
+
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager};
+
+use crate::access::{self, AccessControl, AccessKind};
+use crate::error::VividError;
+use crate::settings::SettingsStore;
+use crate::{attachments, instance, paths, workspace_config};
+
+/// 拖入的路径在多长时间内可以交给 `handle_dropped_paths`
+const DROP_TTL: Duration = Duration::from_secs(60);
+
+/// 一个拖入路径的处理结果
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum DroppedItem {
+    /// 可以在编辑器中打开的文档
+    Open { path: String },
+    /// 已复制到资源目录的附件
+    Asset {
+        source: String,
+        /// 资源文件的绝对路径
+        path: String,
+        /// 可直接插入的 Markdown
+        markdown: String,
+        /// 是否复用了内容相同的已有文件
+        deduplicated: bool,
+    },
+    /// 可以作为工作区打开的文件夹
+    Workspace { path: String },
+    /// 无法处理的路径
+    Skipped { path: String, reason: String },
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DropResult {
+    pub items: Vec<DroppedItem>,
+    /// 所有资源链接，每行一个，可一次插入到光标处
+    pub markdown: String,
+}
+
+/// 最近拖入窗口的路径（规范化后）
+#[derive(Default)]
+pub struct DroppedPaths {
+    recent: Mutex<Vec<(Instant, PathBuf)>>,
+}
+
+impl DroppedPaths {
+    /// 记录窗口拖放事件中的路径
+    pub fn record(&self, dropped: &[PathBuf]) {
+        let Ok(mut recent) = self.recent.lock() else {
+            return;
+        };
+        let now = Instant::now();
+        recent.retain(|(at, _)| now.duration_since(*at) < DROP_TTL);
+        recent.extend(dropped.iter().map(|path| (now, paths::canonicalize(path))));
+        log::debug!("[dropped] {} path(s) dropped", dropped.len());
+    }
+
+    fn was_dropped(&self, path: &Path) -> bool {
+        let path = paths::canonicalize(path);
+        self.recent.lock().is_ok_and(|recent| {
+            recent
+                .iter()
+                .any(|(at, dropped)| *dropped == path && at.elapsed() < DROP_TTL)
+        })
+    }
+}
+
+fn classify(
+    app: &AppHandle,
+    path: &Path,
+    document: Option<&Path>,
+    assets_dir: &str,
+) -> DroppedItem {
+    let display = path.to_string_lossy().to_string();
+    let skipped = |reason: &str| DroppedItem::Skipped {
+        path: display.clone(),
+        reason: reason.to_string(),
+    };
+
+    if path.is_dir() {
+        app.state::<AccessControl>().grant_session(path);
+        return DroppedItem::Workspace { path: display };
+    }
+    if !path.is_file() {
+        return skipped("File does not exist");
+    }
+    if instance::is_openable(path) {
+        app.state::<AccessControl>().grant_session(path);
+        return DroppedItem::Open { path: display };
+    }
+    let Some(document) = document else {
+        return skipped("Document must be saved before adding assets");
+    };
+    match attachments::copy_attachment(path, document, assets_dir) {
+        Ok((asset, markdown, deduplicated)) => {
+            attachments::transcribe_in_background(app, &asset);
+            DroppedItem::Asset {
+                source: display,
+                path: asset.to_string_lossy().to_string(),
+                markdown,
+                deduplicated,
+            }
+        }
+        Err(e) => {
+            log::warn!("[handle_dropped_paths] {}: {}", display, e);
+            skipped(&e)
+        }
+    }
+}
+
+// 处理拖入窗口的文件：文档交给前端打开，其它文件作为附件复制到资源目录，文件夹作为工作区候选
+#[tauri::command]
+pub async fn handle_dropped_paths(
+    app: AppHandle,
+    paths: Vec<String>,
+    target_document: Option<String>,
+) -> Result<DropResult, VividError> {
+    let start = Instant::now();
+    log::info!(
+        "[handle_dropped_paths] {} path(s), target: {:?}",
+        paths.len(),
+        target_document
+    );
+    let document = target_document
+        .filter(|p| !p.trim().is_empty())
+        .map(PathBuf::from)
+        .filter(|p| p.parent().is_some());
+    if let Some(document) = &document {
+        // 资源写在文档旁边，文档本身必须允许写入
+        access::ensure_access(&app, document, AccessKind::Write)?;
+    }
+    let assets_dir = match &document {
+        Some(document) => workspace_config::effective_for(&app, document).assets_dir,
+        None => app.state::<SettingsStore>().get().assets_dir,
+    };
+
+    let result = tauri::async_runtime::spawn_blocking(move || {
+        let dropped = app.state::<DroppedPaths>();
+        let items: Vec<DroppedItem> = paths
+            .iter()
+            .map(PathBuf::from)
+            .map(|path| {
+                if dropped.was_dropped(&path) {
+                    classify(&app, &path, document.as_deref(), &assets_dir)
+                } else {
+                    log::warn!(
+                        "[handle_dropped_paths] Not dropped onto a window: {}",
+                        path.display()
+                    );
+                    DroppedItem::Skipped {
+                        path: path.to_string_lossy().to_string(),
+                        reason: "Path was not dropped onto the window".to_string(),
+                    }
+                }
+            })
+            .collect();
+        let markdown = items
+            .iter()
+            .filter_map(|item| match item {
+                DroppedItem::Asset { markdown, .. } => Some(markdown.as_str()),
+                _ => None,
+            })
+            .collect::<Vec<_>>()
+            .join("\n");
+        DropResult { items, markdown }
+    })
+    .await
+    .map_err(|e| format!("Drop task failed: {}", e))?;
+
+    log::info!(
+        "[handle_dropped_paths] ✓ Success: {} item(s) in {:?}",
+        result.items.len(),
+        start.elapsed()
+    );
+    Ok(result)
+}
@@ -0,0 +1,365 @@
+//! 重复与相似笔记检测
+//!
+//! `find_duplicates` 扫描目录下的笔记，找出重复粘贴或保留的旧副本：
+//!
+//! - 完全重复：去掉 front matter 和 Markdown 标记、统一大小写和空白后内容的 BLAKE3 相同；
+//! - 内容相似：正文按词（中日韩文字按字）切分为连续 5 个词的片段（shingle），用 MinHash 签名
+//!   和 LSH 分桶找出候选对，再计算片段集合的 Jaccard 相似度，不低于阈值的视为相似。
+//!
+//! 相互重复或相似的笔记合并为一组返回，组内列出每对笔记的相似度，供用户合并或删除。
+
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::hash::{DefaultHasher, Hash, Hasher};
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+
+use serde::{Deserialize, Serialize};
+use tauri::AppHandle;
+
+use crate::access::{self, AccessKind};
+use crate::error::VividError;
+use crate::fileinfo::iso_time;
+use crate::largefile::LARGE_FILE_THRESHOLD;
+use crate::markdown::is_cjk_char;
+use crate::{frontmatter, search, workspace};
+
+/// 每个片段包含的词数
+const SHINGLE_SIZE: usize = 5;
+/// MinHash 签名长度 = 分桶数 × 每桶行数
+const BANDS: usize = 32;
+const ROWS: usize = 4;
+/// 默认相似度阈值
+const DEFAULT_THRESHOLD: f64 = 0.8;
+/// 少于该词数的笔记只参与完全重复检测，太短的笔记片段相似没有意义
+const MIN_WORDS: usize = 20;
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct FindDuplicatesOptions {
+    /// 相似度阈值（0–1），默认 0.8
+    pub threshold: Option<f64>,
+    /// 只检测完全重复
+    pub exact_only: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DuplicateKind {
+    /// 组内笔记内容完全相同
+    Exact,
+    Similar,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DuplicateFile {
+    pub path: String,
+    pub size: u64,
+    pub modified: Option<String>,
+    pub words: usize,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SimilarPair {
+    pub a: String,
+    pub b: String,
+    /// 片段集合的 Jaccard 相似度，完全重复为 1
+    pub similarity: f64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DuplicateCluster {
+    pub kind: DuplicateKind,
+    /// 组内最高的相似度
+    pub similarity: f64,
+    /// 按修改时间从新到旧排列
+    pub files: Vec<DuplicateFile>,
+    pub pairs: Vec<SimilarPair>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DuplicateReport {
+    pub scanned: usize,
+    pub clusters: Vec<DuplicateCluster>,
+}
+
+/// 一篇笔记的指纹
+struct Fingerprint {
+    path: PathBuf,
+    size: u64,
+    modified: Option<std::time::SystemTime>,
+    words: usize,
+    hash: blake3::Hash,
+    shingles: HashSet<u64>,
+}
+
+/// 正文切分为小写的词，中日韩文字每字一个词
+fn tokens(text: &str) -> Vec<String> {
+    let mut tokens = Vec::new();
+    let mut word = String::new();
+    for c in text.chars() {
+        if is_cjk_char(c) {
+            if !word.is_empty() {
+                tokens.push(std::mem::take(&mut word));
+            }
+            if c.is_alphanumeric() {
+                tokens.push(c.to_string());
+            }
+        } else if c.is_alphanumeric() {
+            word.extend(c.to_lowercase());
+        } else if !word.is_empty() {
+            tokens.push(std::mem::take(&mut word));
+        }
+    }
+    if !word.is_empty() {
+        tokens.push(word);
+    }
+    tokens
+}
+
+fn hash_of(value: impl Hash) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    value.hash(&mut hasher);
+    hasher.finish()
+}
+
+fn shingles(tokens: &[String]) -> HashSet<u64> {
+    if tokens.len() < SHINGLE_SIZE {
+        return tokens.iter().map(hash_of).collect();
+    }
+    tokens.windows(SHINGLE_SIZE).map(hash_of).collect()
+}
+
+/// SplitMix64，用不同的种子把一个哈希值变换为相互独立的哈希
+fn mix(mut x: u64) -> u64 {
+    x = x.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    x = (x ^ (x >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    x = (x ^ (x >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    x ^ (x >> 31)
+}
+
+fn minhash(shingles: &HashSet<u64>) -> Vec<u64> {
+    (0..BANDS * ROWS)
+        .map(|seed| {
+            let salt = mix(seed as u64);
+            shingles
+                .iter()
+                .map(|&shingle| mix(shingle ^ salt))
+                .min()
+                .unwrap_or(u64::MAX)
+        })
+        .collect()
+}
+
+fn jaccard(a: &HashSet<u64>, b: &HashSet<u64>) -> f64 {
+    let union = a.len() + b.len() - a.intersection(b).count();
+    if union == 0 {
+        return 0.0;
+    }
+    a.intersection(b).count() as f64 / union as f64
+}
+
+fn fingerprint(path: &Path) -> Option<Fingerprint> {
+    let metadata = fs::metadata(path).ok()?;
+    if metadata.len() > LARGE_FILE_THRESHOLD {
+        return None;
+    }
+    let content = fs::read_to_string(path).ok()?;
+    let body = match frontmatter::find_front_matter(&content) {
+        Some(block) => &content[block.body_start..],
+        None => content.as_str(),
+    };
+    let tokens = tokens(&search::plain_text(body));
+    if tokens.is_empty() {
+        return None;
+    }
+    Some(Fingerprint {
+        path: path.to_path_buf(),
+        size: metadata.len(),
+        modified: metadata.modified().ok(),
+        words: tokens.len(),
+        hash: blake3::hash(tokens.join(" ").as_bytes()),
+        shingles: shingles(&tokens),
+    })
+}
+
+/// 并查集，合并相互重复的笔记
+struct Groups(Vec<usize>);
+
+impl Groups {
+    fn find(&mut self, i: usize) -> usize {
+        let parent = self.0[i];
+        if parent == i {
+            return i;
+        }
+        let root = self.find(parent);
+        self.0[i] = root;
+        root
+    }
+
+    fn union(&mut self, a: usize, b: usize) {
+        let (a, b) = (self.find(a), self.find(b));
+        if a != b {
+            self.0[b] = a;
+        }
+    }
+}
+
+/// 找出完全重复和相似的笔记对（下标和相似度）
+fn similar_pairs(
+    notes: &[Fingerprint],
+    threshold: f64,
+    exact_only: bool,
+) -> Vec<(usize, usize, f64)> {
+    let mut pairs = Vec::new();
+    let mut seen = HashSet::new();
+
+    let mut by_hash: HashMap<blake3::Hash, Vec<usize>> = HashMap::new();
+    for (i, note) in notes.iter().enumerate() {
+        by_hash.entry(note.hash).or_default().push(i);
+    }
+    for group in by_hash.values() {
+        for (n, &a) in group.iter().enumerate() {
+            for &b in &group[n + 1..] {
+                seen.insert((a, b));
+                pairs.push((a, b, 1.0));
+            }
+        }
+    }
+    if exact_only {
+        return pairs;
+    }
+
+    // LSH：签名中任一分桶完全相同的笔记成为候选对
+    let mut buckets: HashMap<(usize, u64), Vec<usize>> = HashMap::new();
+    for (i, note) in notes.iter().enumerate() {
+        if note.words < MIN_WORDS {
+            continue;
+        }
+        let signature = minhash(&note.shingles);
+        for (band, rows) in signature.chunks(ROWS).enumerate() {
+            buckets.entry((band, hash_of(rows))).or_default().push(i);
+        }
+    }
+    for bucket in buckets.values().filter(|bucket| bucket.len() > 1) {
+        for (n, &a) in bucket.iter().enumerate() {
+            for &b in &bucket[n + 1..] {
+                let key = (a.min(b), a.max(b));
+                if !seen.insert(key) {
+                    continue;
+                }
+                let similarity = jaccard(&notes[a].shingles, &notes[b].shingles);
+                if similarity >= threshold {
+                    pairs.push((key.0, key.1, similarity));
+                }
+            }
+        }
+    }
+    pairs
+}
+
+fn file_entry(note: &Fingerprint) -> DuplicateFile {
+    DuplicateFile {
+        path: note.path.to_string_lossy().to_string(),
+        size: note.size,
+        modified: note.modified.map(iso_time),
+        words: note.words,
+    }
+}
+
+fn find(root: &Path, options: &FindDuplicatesOptions) -> DuplicateReport {
+    let threshold = options
+        .threshold
+        .unwrap_or(DEFAULT_THRESHOLD)
+        .clamp(0.1, 1.0);
+    let notes: Vec<Fingerprint> = workspace::markdown_files(root)
+        .iter()
+        .filter_map(|path| fingerprint(path))
+        .collect();
+    let pairs = similar_pairs(&notes, threshold, options.exact_only);
+
+    let mut groups = Groups((0..notes.len()).collect());
+    for &(a, b, _) in &pairs {
+        groups.union(a, b);
+    }
+    let mut clusters: HashMap<usize, (Vec<usize>, Vec<(usize, usize, f64)>)> = HashMap::new();
+    for &(a, b, similarity) in &pairs {
+        let root = groups.find(a);
+        clusters.entry(root).or_default().1.push((a, b, similarity));
+    }
+    for i in 0..notes.len() {
+        let root = groups.find(i);
+        if let Some((members, _)) = clusters.get_mut(&root) {
+            members.push(i);
+        }
+    }
+
+    let mut clusters: Vec<DuplicateCluster> = clusters
+        .into_values()
+        .map(|(mut members, mut pairs)| {
+            members.sort_by(|&a, &b| notes[b].modified.cmp(&notes[a].modified));
+            pairs.sort_by(|x, y| y.2.total_cmp(&x.2));
+            let exact = members
+                .iter()
+                .all(|&i| notes[i].hash == notes[members[0]].hash);
+            DuplicateCluster {
+                kind: if exact {
+                    DuplicateKind::Exact
+                } else {
+                    DuplicateKind::Similar
+                },
+                similarity: pairs.first().map_or(0.0, |pair| pair.2),
+                files: members.iter().map(|&i| file_entry(&notes[i])).collect(),
+                pairs: pairs
+                    .into_iter()
+                    .map(|(a, b, similarity)| SimilarPair {
+                        a: notes[a].path.to_string_lossy().to_string(),
+                        b: notes[b].path.to_string_lossy().to_string(),
+                        similarity,
+                    })
+                    .collect(),
+            }
+        })
+        .collect();
+    clusters.sort_by(|a, b| {
+        b.similarity
+            .total_cmp(&a.similarity)
+            .then(b.files.len().cmp(&a.files.len()))
+    });
+    DuplicateReport {
+        scanned: notes.len(),
+        clusters,
+    }
+}
+
+// 查找目录下完全重复或内容相似的笔记
+#[tauri::command]
+pub async fn find_duplicates(
+    app: AppHandle,
+    root: String,
+    options: Option<FindDuplicatesOptions>,
+) -> Result<DuplicateReport, VividError> {
+    let start = Instant::now();
+    let root = PathBuf::from(root);
+    if !root.is_dir() {
+        log::error!(
+            "[find_duplicates] Directory does not exist: {}",
+            root.display()
+        );
+        return Err(VividError::dir_not_found(&root));
+    }
+    access::ensure_access(&app, &root, AccessKind::Read)?;
+    let options = options.unwrap_or_default();
+    log::info!("[find_duplicates] {} {:?}", root.display(), options);
+
+    let report = tauri::async_runtime::spawn_blocking(move || find(&root, &options))
+        .await
+        .map_err(|e| format!("Duplicate scan failed: {}", e))?;
+    log::info!(
+        "[find_duplicates] ✓ Success: {} cluster(s) among {} note(s) in {:?}",
+        report.clusters.len(),
+        report.scanned,
+        start.elapsed()
+    );
+    Ok(report)
+}
@@ -0,0 +1,590 @@
+//! 文档加密
+//!
+//! 加密文件保存为 `<原文件名>.enc`，格式为：
+//! `VMENC` + 版本号（1 字节）+ Argon2id 参数（m/t/p，各 4 字节小端）+ 盐（16 字节）
+//! + XChaCha20-Poly1305 随机数（24 字节）+ 密文。文件头同时作为关联数据参与认证。
+//!
+//! 解密成功后派生出的密钥按路径缓存在内存中，之后的自动保存无需再次输入口令；
+//! 密钥不会写入磁盘，应用退出或调用 `lock_encrypted_file` 后需要重新输入；
+//! 选择记住口令时口令保存在系统钥匙串中（见 `secrets`）。
+
+use std::collections::HashMap;
+use std::fs;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use argon2::{Algorithm, Argon2, Params, Version};
+use chacha20poly1305::aead::rand_core::RngCore;
+use chacha20poly1305::aead::{Aead, KeyInit, OsRng, Payload};
+use chacha20poly1305::{XChaCha20Poly1305, XNonce};
+use serde::{Deserialize, Serialize};
+use tauri::State;
+
+use crate::access::{self, AccessKind};
+use crate::error::VividError;
+use crate::{file_name_of, paths, readonly, revision, secrets, storage, FileInfo};
+
+/// 加密文件的扩展名
+pub const EXTENSION: &str = "enc";
+
+const MAGIC: &[u8] = b"VMENC";
+const VERSION: u8 = 1;
+const SALT_LEN: usize = 16;
+const NONCE_LEN: usize = 24;
+const KEY_LEN: usize = 32;
+const HEADER_LEN: usize = MAGIC.len() + 1 + 12 + SALT_LEN + NONCE_LEN;
+
+/// 新文件使用的 Argon2id 参数：64 MiB 内存、3 轮、单线程
+const DEFAULT_KDF: KdfParams = KdfParams {
+    memory_kib: 64 * 1024,
+    iterations: 3,
+    parallelism: 1,
+};
+/// 读取文件时接受的最大参数，避免被篡改的文件头耗尽内存或长时间占用 CPU
+const MAX_MEMORY_KIB: u32 = 1024 * 1024;
+const MAX_ITERATIONS: u32 = 64;
+const MAX_PARALLELISM: u32 = 16;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct KdfParams {
+    memory_kib: u32,
+    iterations: u32,
+    parallelism: u32,
+}
+
+/// 由口令派生出的密钥及其参数
+#[derive(Clone)]
+struct DerivedKey {
+    key: [u8; KEY_LEN],
+    salt: [u8; SALT_LEN],
+    params: KdfParams,
+}
+
+struct Header {
+    params: KdfParams,
+    salt: [u8; SALT_LEN],
+    nonce: [u8; NONCE_LEN],
+}
+
+/// 保存时的加密选项
+#[derive(Debug, Default, Deserialize)]
+pub struct EncryptOptions {
+    /// 新口令；为空时使用解密时缓存的密钥
+    pub passphrase: Option<String>,
+    /// 为 `false` 时以明文保存并丢弃缓存的密钥
+    #[serde(default = "default_keep_encrypted")]
+    pub keep_encrypted: bool,
+}
+
+fn default_keep_encrypted() -> bool {
+    true
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct EncryptResult {
+    /// 加密后的文件路径
+    pub path: String,
+    pub revision: String,
+}
+
+/// 已解锁文件的密钥缓存
+#[derive(Default)]
+pub struct EncryptionKeys {
+    keys: Mutex<HashMap<PathBuf, DerivedKey>>,
+}
+
+/// 内容是否为加密格式
+pub fn is_encrypted(bytes: &[u8]) -> bool {
+    bytes.starts_with(MAGIC)
+}
+
+/// 只读取文件头判断文件是否已加密
+pub fn is_encrypted_file(path: &Path) -> bool {
+    let mut magic = [0u8; MAGIC.len()];
+    fs::File::open(path)
+        .and_then(|mut file| file.read_exact(&mut magic))
+        .is_ok()
+        && is_encrypted(&magic)
+}
+
+fn has_encrypted_extension(path: &Path) -> bool {
+    path.extension().is_some_and(|ext| ext == EXTENSION)
+}
+
+fn derive_key(
+    passphrase: &str,
+    params: KdfParams,
+    salt: [u8; SALT_LEN],
+) -> Result<DerivedKey, String> {
+    if passphrase.is_empty() {
+        return Err("Passphrase must not be empty".to_string());
+    }
+    let argon_params = Params::new(
+        params.memory_kib,
+        params.iterations,
+        params.parallelism,
+        Some(KEY_LEN),
+    )
+    .map_err(|e| format!("Invalid key derivation parameters: {}", e))?;
+    let mut key = [0u8; KEY_LEN];
+    Argon2::new(Algorithm::Argon2id, Version::V0x13, argon_params)
+        .hash_password_into(passphrase.as_bytes(), &salt, &mut key)
+        .map_err(|e| format!("Failed to derive key: {}", e))?;
+    Ok(DerivedKey { key, salt, params })
+}
+
+/// 用新的随机盐派生密钥
+fn new_key(passphrase: &str) -> Result<DerivedKey, String> {
+    let mut salt = [0u8; SALT_LEN];
+    OsRng.fill_bytes(&mut salt);
+    derive_key(passphrase, DEFAULT_KDF, salt)
+}
+
+fn encode_header(header: &Header) -> Vec<u8> {
+    let mut out = Vec::with_capacity(HEADER_LEN);
+    out.extend_from_slice(MAGIC);
+    out.push(VERSION);
+    out.extend_from_slice(&header.params.memory_kib.to_le_bytes());
+    out.extend_from_slice(&header.params.iterations.to_le_bytes());
+    out.extend_from_slice(&header.params.parallelism.to_le_bytes());
+    out.extend_from_slice(&header.salt);
+    out.extend_from_slice(&header.nonce);
+    out
+}
+
+fn decode_header(bytes: &[u8]) -> Result<Header, String> {
+    if !is_encrypted(bytes) || bytes.len() < HEADER_LEN {
+        return Err("Not an encrypted document".to_string());
+    }
+    let version = bytes[MAGIC.len()];
+    if version != VERSION {
+        return Err(format!("Unsupported encryption version: {}", version));
+    }
+    let u32_at = |offset: usize| {
+        let mut buf = [0u8; 4];
+        buf.copy_from_slice(&bytes[offset..offset + 4]);
+        u32::from_le_bytes(buf)
+    };
+    let base = MAGIC.len() + 1;
+    let params = KdfParams {
+        memory_kib: u32_at(base),
+        iterations: u32_at(base + 4),
+        parallelism: u32_at(base + 8),
+    };
+    if params.memory_kib > MAX_MEMORY_KIB
+        || params.iterations > MAX_ITERATIONS
+        || params.parallelism > MAX_PARALLELISM
+    {
+        return Err("Invalid key derivation parameters".to_string());
+    }
+    let mut salt = [0u8; SALT_LEN];
+    salt.copy_from_slice(&bytes[base + 12..base + 12 + SALT_LEN]);
+    let mut nonce = [0u8; NONCE_LEN];
+    nonce.copy_from_slice(&bytes[base + 12 + SALT_LEN..HEADER_LEN]);
+    Ok(Header {
+        params,
+        salt,
+        nonce,
+    })
+}
+
+/// 用密钥加密，每次使用新的随机数
+fn seal(key: &DerivedKey, plaintext: &[u8]) -> Result<Vec<u8>, String> {
+    let mut nonce = [0u8; NONCE_LEN];
+    OsRng.fill_bytes(&mut nonce);
+    let mut out = encode_header(&Header {
+        params: key.params,
+        salt: key.salt,
+        nonce,
+    });
+    let cipher = XChaCha20Poly1305::new((&key.key).into());
+    let ciphertext = cipher
+        .encrypt(
+            XNonce::from_slice(&nonce),
+            Payload {
+                msg: plaintext,
+                aad: &out,
+            },
+        )
+        .map_err(|_| "Failed to encrypt document".to_string())?;
+    out.extend_from_slice(&ciphertext);
+    Ok(out)
+}
+
+/// 解密内容；`cached` 的盐和参数与文件头一致时直接使用，否则由口令重新派生
+fn open(
+    bytes: &[u8],
+    passphrase: Option<&str>,
+    cached: Option<&DerivedKey>,
+) -> Result<(String, DerivedKey), String> {
+    let header = decode_header(bytes)?;
+    let key = match (passphrase, cached) {
+        (Some(passphrase), _) => derive_key(passphrase, header.params, header.salt)?,
+        (None, Some(key)) if key.salt == header.salt && key.params == header.params => key.clone(),
+        _ => return Err("Passphrase required".to_string()),
+    };
+    let cipher = XChaCha20Poly1305::new((&key.key).into());
+    let plaintext = cipher
+        .decrypt(
+            XNonce::from_slice(&header.nonce),
+            Payload {
+                msg: &bytes[HEADER_LEN..],
+                aad: &bytes[..HEADER_LEN],
+            },
+        )
+        .map_err(|_| "Wrong passphrase or corrupted file".to_string())?;
+    let content = String::from_utf8(plaintext)
+        .map_err(|_| "Decrypted content is not valid UTF-8".to_string())?;
+    Ok((content, key))
+}
+
+impl EncryptionKeys {
+    // 缓存以规范化路径为键，`a/../b.md` 与符号链接等写法共享同一把密钥
+    fn cached(&self, path: &Path) -> Option<DerivedKey> {
+        let keys = self.keys.lock().ok()?;
+        keys.get(&paths::canonicalize(path)).cloned()
+    }
+
+    fn remember(&self, path: &Path, key: DerivedKey) {
+        if let Ok(mut keys) = self.keys.lock() {
+            keys.insert(paths::canonicalize(path), key);
+        }
+    }
+
+    fn forget(&self, path: &Path) -> bool {
+        self.keys
+            .lock()
+            .is_ok_and(|mut keys| keys.remove(&paths::canonicalize(path)).is_some())
+    }
+
+    /// 用缓存的密钥解密已读取的文件内容，没有可用密钥时返回 `None`
+    pub fn unlock_cached(&self, path: &Path, bytes: &[u8]) -> Option<String> {
+        let cached = self.cached(path)?;
+        open(bytes, None, Some(&cached))
+            .ok()
+            .map(|(content, _)| content)
+    }
+
+    /// 按 [`prepare_save`](Self::prepare_save) 的规则判断保存时是否加密
+    pub fn will_encrypt(&self, path: &Path, options: Option<&EncryptOptions>) -> bool {
+        if options.is_some_and(|o| !o.keep_encrypted) {
+            return false;
+        }
+        options.is_some_and(|o| o.passphrase.is_some())
+            || self.cached(path).is_some()
+            || has_encrypted_extension(path)
+            || is_encrypted_file(path)
+    }
+
+    /// 保存前按需加密内容，返回要写入磁盘的字节
+    ///
+    /// 以 `.enc` 结尾、磁盘上已加密或已缓存密钥的文件会保持加密；
+    /// `options.keep_encrypted` 为 `false` 时改为明文保存。
+    pub fn prepare_save(
+        &self,
+        path: &Path,
+        content: &str,
+        options: Option<&EncryptOptions>,
+    ) -> Result<Vec<u8>, String> {
+        if options.is_some_and(|o| !o.keep_encrypted) {
+            if self.forget(path) {
+                log::info!("[encryption] Saving {:?} as plain text", path);
+            }
+            return Ok(content.as_bytes().to_vec());
+        }
+
+        let passphrase = options.and_then(|o| o.passphrase.as_deref());
+        let cached = self.cached(path);
+        let encrypt = passphrase.is_some()
+            || cached.is_some()
+            || has_encrypted_extension(path)
+            || is_encrypted_file(path);
+        if !encrypt {
+            return Ok(content.as_bytes().to_vec());
+        }
+
+        let key = match (passphrase, cached) {
+            (Some(passphrase), _) => new_key(passphrase)?,
+            (None, Some(key)) => key,
+            (None, None) => return Err("Passphrase required to save encrypted file".to_string()),
+        };
+        let sealed = seal(&key, content.as_bytes())?;
+        self.remember(path, key);
+        Ok(sealed)
+    }
+}
+
+// 加密文件：写入 `<path>.enc` 并删除原文件，`remember` 为 true 时把口令存入系统钥匙串
+#[tauri::command]
+pub async fn encrypt_file(
+    app: tauri::AppHandle,
+    path: String,
+    passphrase: String,
+    remember: Option<bool>,
+) -> Result<EncryptResult, VividError> {
+    access::ensure_access(&app, Path::new(&path), AccessKind::Write)?;
+    tauri::async_runtime::spawn_blocking(move || {
+        use tauri::Manager;
+
+        let source = PathBuf::from(&path);
+        log::info!("[encrypt_file] Encrypting {}", path);
+        let bytes = fs::read(&source).map_err(|e| format!("Failed to read file: {}", e))?;
+        if is_encrypted(&bytes) {
+            return Err("File is already encrypted".to_string());
+        }
+        let content =
+            String::from_utf8(bytes).map_err(|_| "File is not valid UTF-8".to_string())?;
+
+        let mut target = source.clone().into_os_string();
+        target.push(".");
+        target.push(EXTENSION);
+        let target = PathBuf::from(target);
+        if target.exists() {
+            return Err(format!("File already exists: {}", target.display()));
+        }
+
+        let key = new_key(&passphrase)?;
+        let sealed = seal(&key, content.as_bytes())?;
+        storage::write_atomic(&target, &sealed)?;
+        // 确认密文可以解开后才删除原文件
+        open(&sealed, None, Some(&key))?;
+        fs::remove_file(&source).map_err(|e| format!("Failed to remove original file: {}", e))?;
+
+        app.state::<EncryptionKeys>().remember(&target, key);
+        if remember.unwrap_or(false) {
+            secrets::store(&secret_key(&target), &passphrase)?;
+        }
+        let revision = revision::revision_for(&sealed, fs::metadata(&target).ok().as_ref());
+        log::info!("[encrypt_file] ✓ Success: {:?}", target);
+        Ok(EncryptResult {
+            path: target.to_string_lossy().to_string(),
+            revision,
+        })
+    })
+    .await
+    .map_err(|e| format!("Encryption task failed: {}", e))?
+    .map_err(VividError::from)
+}
+
+/// 口令在系统钥匙串中的条目名
+fn secret_key(path: &Path) -> String {
+    format!("encryption:{}", path.display())
+}
+
+// 解密文件；默认只在内存中解密，`restore` 为 true 时写回明文文件并删除加密文件
+// 未提供口令时使用钥匙串中保存的口令，`remember` 为 true 时解密成功后把口令存入钥匙串
+#[tauri::command]
+pub async fn decrypt_file(
+    app: tauri::AppHandle,
+    path: String,
+    passphrase: Option<String>,
+    restore: Option<bool>,
+    remember: Option<bool>,
+) -> Result<FileInfo, VividError> {
+    let kind = if restore.unwrap_or(false) {
+        AccessKind::Write
+    } else {
+        AccessKind::Read
+    };
+    access::ensure_access(&app, Path::new(&path), kind)?;
+    tauri::async_runtime::spawn_blocking(move || {
+        use tauri::Manager;
+
+        let source = PathBuf::from(&path);
+        log::info!("[decrypt_file] Decrypting {}", path);
+        let bytes = fs::read(&source).map_err(|e| format!("Failed to read file: {}", e))?;
+        let passphrase = match passphrase {
+            Some(passphrase) => passphrase,
+            None => secrets::get(&secret_key(&source))?
+                .ok_or_else(|| "Passphrase required".to_string())?,
+        };
+        let (content, key) = open(&bytes, Some(&passphrase), None).map_err(|e| {
+            log::warn!("[decrypt_file] {}", e);
+            e
+        })?;
+        let keys = app.state::<EncryptionKeys>();
+
+        if !restore.unwrap_or(false) {
+            if remember.unwrap_or(false) {
+                secrets::store(&secret_key(&source), &passphrase)?;
+            }
+            keys.remember(&source, key);
+            let revision = revision::revision_for(&bytes, fs::metadata(&source).ok().as_ref());
+            log::info!(
+                "[decrypt_file] ✓ Success: {} ({} bytes)",
+                path,
+                content.len()
+            );
+            return Ok(FileInfo {
+                name: file_name_of(&source),
+                path,
+                content,
+                revision,
+                large: None,
+                encrypted: true,
+                read_only: readonly::is_read_only(&source),
+            });
+        }
+
+        let target = if has_encrypted_extension(&source) {
+            source.with_extension("")
+        } else {
+            source.clone()
+        };
+        if target != source && target.exists() {
+            return Err(format!("File already exists: {}", target.display()));
+        }
+        storage::write_atomic(&target, content.as_bytes())?;
+        if target != source {
+            fs::remove_file(&source)
+                .map_err(|e| format!("Failed to remove encrypted file: {}", e))?;
+        }
+        keys.forget(&source);
+        // 文件不再加密，钥匙串中的口令也一并删除
+        if let Err(e) = secrets::delete(&secret_key(&source)) {
+            log::warn!("[decrypt_file] {}", e);
+        }
+
+        let revision =
+            revision::revision_for(content.as_bytes(), fs::metadata(&target).ok().as_ref());
+        log::info!("[decrypt_file] ✓ Success: restored {:?}", target);
+        Ok(FileInfo {
+            name: file_name_of(&target),
+            path: target.to_string_lossy().to_string(),
+            content,
+            revision,
+            large: None,
+            encrypted: false,
+            read_only: readonly::is_read_only(&target),
+        })
+    })
+    .await
+    .map_err(|e| format!("Decryption task failed: {}", e))?
+    .map_err(VividError::from)
+}
+
+// 丢弃某个文件缓存的密钥，之后打开或保存需要重新输入口令
+#[tauri::command]
+pub fn lock_encrypted_file(keys: State<'_, EncryptionKeys>, path: String) -> bool {
+    keys.forget(Path::new(&path))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 测试用的低成本参数，避免每个用例都花费 64 MiB 内存派生密钥
+    const TEST_KDF: KdfParams = KdfParams {
+        memory_kib: 64,
+        iterations: 1,
+        parallelism: 1,
+    };
+
+    fn test_key(passphrase: &str) -> DerivedKey {
+        derive_key(passphrase, TEST_KDF, [7u8; SALT_LEN]).unwrap()
+    }
+
+    fn header_with(params: KdfParams) -> Vec<u8> {
+        encode_header(&Header {
+            params,
+            salt: [1u8; SALT_LEN],
+            nonce: [2u8; NONCE_LEN],
+        })
+    }
+
+    #[test]
+    fn seal_and_open_round_trip() {
+        let key = test_key("correct horse");
+        let sealed = seal(&key, "# 秘密\n\ncontent".as_bytes()).unwrap();
+        assert!(is_encrypted(&sealed));
+
+        let (content, derived) = open(&sealed, Some("correct horse"), None).unwrap();
+        assert_eq!(content, "# 秘密\n\ncontent");
+        assert_eq!(derived.key, key.key);
+
+        let (content, _) = open(&sealed, None, Some(&key)).unwrap();
+        assert_eq!(content, "# 秘密\n\ncontent");
+    }
+
+    #[test]
+    fn each_seal_uses_a_new_nonce() {
+        let key = test_key("correct horse");
+        assert_ne!(seal(&key, b"same").unwrap(), seal(&key, b"same").unwrap());
+    }
+
+    #[test]
+    fn wrong_passphrase_fails() {
+        let sealed = seal(&test_key("correct horse"), b"content").unwrap();
+        assert!(open(&sealed, Some("wrong horse"), None).is_err());
+        assert!(open(&sealed, Some(""), None).is_err());
+        assert!(open(&sealed, None, None).is_err());
+    }
+
+    #[test]
+    fn cached_key_with_other_salt_is_not_used() {
+        let sealed = seal(&test_key("correct horse"), b"content").unwrap();
+        let other = derive_key("correct horse", TEST_KDF, [8u8; SALT_LEN]).unwrap();
+        assert!(open(&sealed, None, Some(&other)).is_err());
+    }
+
+    #[test]
+    fn tampered_header_or_content_fails() {
+        let mut sealed = seal(&test_key("correct horse"), b"content").unwrap();
+        let last = sealed.len() - 1;
+        sealed[last] ^= 1;
+        assert!(open(&sealed, Some("correct horse"), None).is_err());
+
+        let mut sealed = seal(&test_key("correct horse"), b"content").unwrap();
+        sealed[HEADER_LEN - 1] ^= 1;
+        assert!(open(&sealed, Some("correct horse"), None).is_err());
+    }
+
+    #[test]
+    fn header_round_trip() {
+        let header = decode_header(&header_with(DEFAULT_KDF)).unwrap();
+        assert_eq!(header.params, DEFAULT_KDF);
+        assert_eq!(header.salt, [1u8; SALT_LEN]);
+        assert_eq!(header.nonce, [2u8; NONCE_LEN]);
+    }
+
+    #[test]
+    fn header_rejects_oversized_parameters() {
+        for params in [
+            KdfParams {
+                memory_kib: MAX_MEMORY_KIB + 1,
+                ..DEFAULT_KDF
+            },
+            KdfParams {
+                iterations: MAX_ITERATIONS + 1,
+                ..DEFAULT_KDF
+            },
+            KdfParams {
+                parallelism: MAX_PARALLELISM + 1,
+                ..DEFAULT_KDF
+            },
+        ] {
+            assert!(decode_header(&header_with(params)).is_err());
+        }
+        let limits = KdfParams {
+            memory_kib: MAX_MEMORY_KIB,
+            iterations: MAX_ITERATIONS,
+            parallelism: MAX_PARALLELISM,
+        };
+        assert!(decode_header(&header_with(limits)).is_ok());
+    }
+
+    #[test]
+    fn header_rejects_bad_magic_version_and_length() {
+        let header = header_with(DEFAULT_KDF);
+        assert!(decode_header(&header[..HEADER_LEN - 1]).is_err());
+
+        let mut bad_magic = header.clone();
+        bad_magic[0] = b'X';
+        assert!(decode_header(&bad_magic).is_err());
+
+        let mut bad_version = header;
+        bad_version[MAGIC.len()] = VERSION + 1;
+        assert!(decode_header(&bad_version).is_err());
+    }
+}
@@ -0,0 +1,194 @@
+//! 命令错误类型
+//!
+//! 所有命令的错误都序列化为 `{ "code": "...", "message": "...", "localized_message": "...",
+//! "locale": "...", ... }`，前端按 `code` 分支处理，而不是匹配英文错误消息。`message` 是面向日志的
+//! 英文描述，`localized_message` 是按当前语言翻译后可以直接展示的提示（见 `i18n`）。
+//!
+//! 内部函数大多返回 `Result<_, String>`，经 `?` 转换为 `Other`；文件读写等能明确分类的错误
+//! 在命令中用 `VividError::io` 等构造函数带上路径和类别。
+
+use std::fmt;
+use std::io;
+use std::path::Path;
+
+use serde::ser::SerializeMap;
+use serde::{Serialize, Serializer};
+
+use crate::i18n;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum VividError {
+    /// 文件或目录不存在
+    NotFound {
+        path: Option<String>,
+        message: String,
+    },
+    /// 没有读写权限，或文件只读
+    PermissionDenied {
+        path: Option<String>,
+        message: String,
+    },
+    /// 文件在打开后被其它程序修改，或目标已存在
+    Conflict {
+        path: Option<String>,
+        message: String,
+    },
+    /// 文件不是有效的 UTF-8 文本
+    Encoding {
+        path: Option<String>,
+        message: String,
+    },
+    /// 其它 IO 错误，`kind` 为 `std::io::ErrorKind` 的名称
+    Io {
+        path: Option<String>,
+        kind: String,
+        message: String,
+    },
+    /// 参数不合法
+    InvalidInput { message: String },
+    /// 未分类的错误
+    Other { message: String },
+}
+
+fn path_string(path: &Path) -> Option<String> {
+    Some(path.to_string_lossy().to_string())
+}
+
+/// 按 IO 错误类别分类
+fn classify(path: Option<String>, error: &io::Error, message: String) -> VividError {
+    match error.kind() {
+        io::ErrorKind::NotFound => VividError::NotFound { path, message },
+        io::ErrorKind::PermissionDenied => VividError::PermissionDenied { path, message },
+        io::ErrorKind::AlreadyExists => VividError::Conflict { path, message },
+        io::ErrorKind::InvalidData => VividError::Encoding { path, message },
+        kind => VividError::Io {
+            path,
+            kind: format!("{:?}", kind),
+            message,
+        },
+    }
+}
+
+impl VividError {
+    /// IO 错误，`context` 描述失败的操作（如 `Failed to read file`）
+    pub fn io(context: &str, path: &Path, error: &io::Error) -> Self {
+        classify(path_string(path), error, format!("{}: {}", context, error))
+    }
+
+    pub fn not_found(path: impl AsRef<Path>) -> Self {
+        let path = path.as_ref();
+        VividError::NotFound {
+            message: format!("File does not exist: {}", path.display()),
+            path: path_string(path),
+        }
+    }
+
+    pub fn dir_not_found(path: impl AsRef<Path>) -> Self {
+        let path = path.as_ref();
+        VividError::NotFound {
+            message: format!("Directory does not exist: {}", path.display()),
+            path: path_string(path),
+        }
+    }
+
+    pub fn permission_denied(path: impl AsRef<Path>, message: impl Into<String>) -> Self {
+        VividError::PermissionDenied {
+            path: path_string(path.as_ref()),
+            message: message.into(),
+        }
+    }
+
+    pub fn conflict(path: impl AsRef<Path>, message: impl Into<String>) -> Self {
+        VividError::Conflict {
+            path: path_string(path.as_ref()),
+            message: message.into(),
+        }
+    }
+
+    pub fn invalid_input(message: impl Into<String>) -> Self {
+        VividError::InvalidInput {
+            message: message.into(),
+        }
+    }
+
+    /// 错误类别，即序列化后的 `code`
+    pub fn code(&self) -> &'static str {
+        match self {
+            VividError::NotFound { .. } => "not_found",
+            VividError::PermissionDenied { .. } => "permission_denied",
+            VividError::Conflict { .. } => "conflict",
+            VividError::Encoding { .. } => "encoding",
+            VividError::Io { .. } => "io",
+            VividError::InvalidInput { .. } => "invalid_input",
+            VividError::Other { .. } => "other",
+        }
+    }
+
+    /// 按当前语言翻译的消息
+    pub fn localized_message(&self) -> String {
+        i18n::localize(i18n::current(), self.code(), self.message())
+    }
+
+    pub fn message(&self) -> &str {
+        match self {
+            VividError::NotFound { message, .. }
+            | VividError::PermissionDenied { message, .. }
+            | VividError::Conflict { message, .. }
+            | VividError::Encoding { message, .. }
+            | VividError::Io { message, .. }
+            | VividError::InvalidInput { message }
+            | VividError::Other { message } => message,
+        }
+    }
+}
+
+impl fmt::Display for VividError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.message())
+    }
+}
+
+impl std::error::Error for VividError {}
+
+impl Serialize for VividError {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut map = serializer.serialize_map(None)?;
+        map.serialize_entry("code", self.code())?;
+        match self {
+            VividError::NotFound { path, .. }
+            | VividError::PermissionDenied { path, .. }
+            | VividError::Conflict { path, .. }
+            | VividError::Encoding { path, .. } => map.serialize_entry("path", path)?,
+            VividError::Io { path, kind, .. } => {
+                map.serialize_entry("path", path)?;
+                map.serialize_entry("kind", kind)?;
+            }
+            VividError::InvalidInput { .. } | VividError::Other { .. } => {}
+        }
+        map.serialize_entry("message", self.message())?;
+        map.serialize_entry("localized_message", &self.localized_message())?;
+        map.serialize_entry("locale", &i18n::current())?;
+        map.end()
+    }
+}
+
+impl From<String> for VividError {
+    fn from(message: String) -> Self {
+        VividError::Other { message }
+    }
+}
+
+impl From<&str> for VividError {
+    fn from(message: &str) -> Self {
+        VividError::Other {
+            message: message.to_string(),
+        }
+    }
+}
+
+impl From<io::Error> for VividError {
+    fn from(error: io::Error) -> Self {
+        let message = error.to_string();
+        classify(None, &error, message)
+    }
+}
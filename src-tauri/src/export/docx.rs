@@ -0,0 +1,721 @@
+//! 导出为 Word 文档（.docx）
+//!
+//! 将 Markdown 的标题、列表、表格、代码块和图片映射为 Word 的段落样式与编号，
+//! 可选使用一份参考 .docx 模板中的样式（与 pandoc 的 `--reference-doc` 类似）。
+
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::io::{Cursor, Read, Write};
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+
+use docx_rs::{
+    AbstractNumbering, AlignmentType, BorderType, BreakType, Docx, Footnote, Hyperlink,
+    HyperlinkType, IndentLevel, Level, LevelJc, LevelText, NumberFormat, Numbering, NumberingId,
+    Paragraph, ParagraphBorder, ParagraphBorderPosition, ParagraphBorders, Pic, Run, RunFonts,
+    Shading, SpecialIndentType, Start, Style, StyleType, Table, TableCell, TableRow,
+};
+use image::GenericImageView;
+use pulldown_cmark::{Event, HeadingLevel, Tag, TagEnd};
+use serde::Deserialize;
+use tauri::{AppHandle, Manager};
+
+use super::ExportResult;
+use crate::access::{self, AccessKind};
+use crate::bibliography;
+use crate::error::VividError;
+use crate::markdown;
+use crate::settings::SettingsStore;
+
+/// 项目符号列表使用的编号定义 ID
+const BULLET_NUMBERING_ID: usize = 1;
+/// 有序列表从该 ID 开始分配，每个列表独立编号
+const FIRST_ORDERED_NUMBERING_ID: usize = 2;
+/// 图片最大宽度（px，按 96 DPI 约等于 A4 版心宽度）
+const MAX_IMAGE_WIDTH_PX: u32 = 600;
+const CODE_FONT: &str = "Consolas";
+const STYLES_XML: &str = "word/styles.xml";
+
+/// DOCX 导出参数
+#[derive(Debug, Deserialize)]
+pub struct ExportDocxParams {
+    /// 输出 .docx 路径
+    pub path: String,
+    /// Markdown 内容
+    pub content: String,
+    /// 参考模板 .docx，使用其中的样式定义；为空时使用设置中的 `export.docx_template`
+    pub template: Option<String>,
+    /// 解析相对图片路径的基准目录（通常为源文档所在目录）
+    pub base_dir: Option<String>,
+}
+
+/// 将 Markdown 转换为 .docx 字节
+pub fn markdown_to_docx(
+    content: &str,
+    base_dir: Option<&Path>,
+    template: Option<&Path>,
+) -> Result<Vec<u8>, String> {
+    let mut builder = DocxBuilder::new(base_dir.map(Path::to_path_buf), collect_footnotes(content));
+    for event in markdown::parser(content) {
+        builder.handle(event);
+    }
+    let docx = builder.finish();
+
+    let mut buffer = Cursor::new(Vec::new());
+    docx.build()
+        .pack(&mut buffer)
+        .map_err(|e| format!("Failed to build docx: {}", e))?;
+    let bytes = buffer.into_inner();
+
+    match template {
+        Some(template) => apply_template_styles(&bytes, template),
+        None => Ok(bytes),
+    }
+}
+
+/// 文档内置样式：标题、引用、代码、超链接
+fn base_styles(docx: Docx) -> Docx {
+    let heading_sizes = [32, 28, 26, 24, 22, 22];
+    let mut docx = docx
+        .default_size(22)
+        .default_fonts(RunFonts::new().east_asia("Microsoft YaHei"));
+    for (i, size) in heading_sizes.iter().enumerate() {
+        let level = i + 1;
+        docx = docx.add_style(
+            Style::new(format!("Heading{}", level), StyleType::Paragraph)
+                .name(format!("heading {}", level))
+                .based_on("Normal")
+                .next("Normal")
+                .q_format(true)
+                .size(*size)
+                .bold()
+                .outline_lvl(i),
+        );
+    }
+    docx.add_style(
+        Style::new("Quote", StyleType::Paragraph)
+            .name("Quote")
+            .based_on("Normal")
+            .italic()
+            .color("595959")
+            .indent(Some(720), None, None, None),
+    )
+    .add_style(
+        Style::new("SourceCode", StyleType::Paragraph)
+            .name("Source Code")
+            .based_on("Normal")
+            .fonts(
+                RunFonts::new()
+                    .ascii(CODE_FONT)
+                    .hi_ansi(CODE_FONT)
+                    .cs(CODE_FONT),
+            )
+            .size(19),
+    )
+    .add_style(
+        Style::new("VerbatimChar", StyleType::Character)
+            .name("Verbatim Char")
+            .fonts(
+                RunFonts::new()
+                    .ascii(CODE_FONT)
+                    .hi_ansi(CODE_FONT)
+                    .cs(CODE_FONT),
+            )
+            .color("C7254E"),
+    )
+    .add_style(
+        Style::new("Hyperlink", StyleType::Character)
+            .name("Hyperlink")
+            .color("0563C1")
+            .underline("single"),
+    )
+}
+
+/// 预先收集脚注定义的纯文本，遇到脚注引用时生成 Word 脚注
+fn collect_footnotes(content: &str) -> HashMap<String, String> {
+    let mut footnotes = HashMap::new();
+    let mut current: Option<(String, String)> = None;
+    for event in markdown::parser(content) {
+        match event {
+            Event::Start(Tag::FootnoteDefinition(label)) => {
+                current = Some((label.to_string(), String::new()));
+            }
+            Event::End(TagEnd::FootnoteDefinition) => {
+                if let Some((label, text)) = current.take() {
+                    footnotes.insert(label, text.trim().to_string());
+                }
+            }
+            Event::Text(text) | Event::Code(text) => {
+                if let Some((_, buf)) = current.as_mut() {
+                    buf.push_str(&text);
+                }
+            }
+            Event::SoftBreak | Event::HardBreak => {
+                if let Some((_, buf)) = current.as_mut() {
+                    buf.push(' ');
+                }
+            }
+            _ => {}
+        }
+    }
+    footnotes
+}
+
+/// 当前行内格式
+#[derive(Default, Clone, Copy)]
+struct InlineState {
+    bold: usize,
+    italic: usize,
+    strike: usize,
+}
+
+struct TableState {
+    rows: Vec<TableRow>,
+    cells: Vec<TableCell>,
+    in_head: bool,
+}
+
+/// 将 pulldown-cmark 事件流转换为 docx-rs 文档
+struct DocxBuilder {
+    docx: Docx,
+    base_dir: Option<PathBuf>,
+    footnotes: HashMap<String, String>,
+    paragraph: Option<Paragraph>,
+    paragraph_style: Option<String>,
+    inline: InlineState,
+    link: Option<Hyperlink>,
+    /// 列表栈：`None` 为无序列表，`Some(id)` 为有序列表的编号 ID
+    lists: Vec<Option<usize>>,
+    next_numbering_id: usize,
+    quote_depth: usize,
+    code_block: Option<String>,
+    table: Option<TableState>,
+    image: Option<(String, String)>,
+    in_footnote_definition: bool,
+}
+
+impl DocxBuilder {
+    fn new(base_dir: Option<PathBuf>, footnotes: HashMap<String, String>) -> Self {
+        let docx = base_styles(Docx::new())
+            .add_abstract_numbering(bullet_numbering())
+            .add_numbering(Numbering::new(BULLET_NUMBERING_ID, BULLET_NUMBERING_ID));
+        DocxBuilder {
+            docx,
+            base_dir,
+            footnotes,
+            paragraph: None,
+            paragraph_style: None,
+            inline: InlineState::default(),
+            link: None,
+            lists: Vec::new(),
+            next_numbering_id: FIRST_ORDERED_NUMBERING_ID,
+            quote_depth: 0,
+            code_block: None,
+            table: None,
+            image: None,
+            in_footnote_definition: false,
+        }
+    }
+
+    fn finish(mut self) -> Docx {
+        self.flush_paragraph();
+        self.docx
+    }
+
+    fn handle(&mut self, event: Event<'_>) {
+        if let Some(code) = self.code_block.as_mut() {
+            match event {
+                Event::Text(text) => code.push_str(&text),
+                Event::End(TagEnd::CodeBlock) => self.finish_code_block(),
+                _ => {}
+            }
+            return;
+        }
+        if let Some((_, alt)) = self.image.as_mut() {
+            match event {
+                Event::Text(text) | Event::Code(text) => alt.push_str(&text),
+                Event::End(TagEnd::Image) => self.finish_image(),
+                _ => {}
+            }
+            return;
+        }
+        // 脚注定义已在预处理阶段收集，这里跳过正文中的定义块
+        if self.in_footnote_definition {
+            if let Event::End(TagEnd::FootnoteDefinition) = event {
+                self.in_footnote_definition = false;
+            }
+            return;
+        }
+
+        match event {
+            Event::Start(tag) => self.start_tag(tag),
+            Event::End(tag) => self.end_tag(tag),
+            Event::Text(text) => {
+                let run = self.styled_run(Run::new().add_text(text.to_string()));
+                self.push_run(run);
+            }
+            Event::Code(text) => {
+                let run =
+                    self.styled_run(Run::new().add_text(text.to_string()).style("VerbatimChar"));
+                self.push_run(run);
+            }
+            Event::InlineMath(text) => {
+                let run = self.styled_run(Run::new().add_text(text.to_string()).italic());
+                self.push_run(run);
+            }
+            Event::DisplayMath(text) => {
+                self.flush_paragraph();
+                let paragraph = Paragraph::new()
+                    .align(AlignmentType::Center)
+                    .add_run(Run::new().add_text(text.to_string()).italic());
+                self.docx = std::mem::take(&mut self.docx).add_paragraph(paragraph);
+            }
+            Event::SoftBreak => self.push_run(Run::new().add_text(" ")),
+            Event::HardBreak => self.push_run(Run::new().add_break(BreakType::TextWrapping)),
+            Event::Rule => {
+                self.flush_paragraph();
+                let mut paragraph = Paragraph::new();
+                paragraph.property = paragraph.property.set_borders(
+                    ParagraphBorders::with_empty().set(
+                        ParagraphBorder::new(ParagraphBorderPosition::Bottom)
+                            .val(BorderType::Single)
+                            .size(6)
+                            .color("BFBFBF"),
+                    ),
+                );
+                self.add_paragraph(paragraph);
+            }
+            Event::TaskListMarker(checked) => {
+                let marker = if checked { "☒ " } else { "☐ " };
+                self.push_run(Run::new().add_text(marker));
+            }
+            Event::FootnoteReference(label) => {
+                let text = self
+                    .footnotes
+                    .get(label.as_ref())
+                    .cloned()
+                    .unwrap_or_default();
+                let mut footnote = Footnote::new();
+                footnote.add_content(Paragraph::new().add_run(Run::new().add_text(text)));
+                self.push_run(Run::new().add_footnote_reference(footnote));
+            }
+            Event::Html(_) | Event::InlineHtml(_) => {}
+        }
+    }
+
+    fn start_tag(&mut self, tag: Tag<'_>) {
+        match tag {
+            Tag::Paragraph => self.flush_paragraph(),
+            Tag::Heading { level, .. } => {
+                self.flush_paragraph();
+                let level = match level {
+                    HeadingLevel::H1 => 1,
+                    HeadingLevel::H2 => 2,
+                    HeadingLevel::H3 => 3,
+                    HeadingLevel::H4 => 4,
+                    HeadingLevel::H5 => 5,
+                    HeadingLevel::H6 => 6,
+                };
+                self.paragraph_style = Some(format!("Heading{}", level));
+            }
+            Tag::BlockQuote(_) => {
+                self.flush_paragraph();
+                self.quote_depth += 1;
+            }
+            Tag::CodeBlock(_) => {
+                self.flush_paragraph();
+                self.code_block = Some(String::new());
+            }
+            Tag::List(start) => {
+                self.flush_paragraph();
+                let numbering = start.map(|start| self.add_ordered_numbering(start as usize));
+                self.lists.push(numbering);
+            }
+            Tag::Item => self.flush_paragraph(),
+            Tag::FootnoteDefinition(_) => {
+                self.flush_paragraph();
+                self.in_footnote_definition = true;
+            }
+            Tag::Table(_) => {
+                self.flush_paragraph();
+                self.table = Some(TableState {
+                    rows: Vec::new(),
+                    cells: Vec::new(),
+                    in_head: false,
+                });
+            }
+            Tag::TableHead => {
+                if let Some(table) = self.table.as_mut() {
+                    table.in_head = true;
+                }
+            }
+            Tag::TableCell => self.paragraph = Some(Paragraph::new()),
+            Tag::Emphasis => self.inline.italic += 1,
+            Tag::Strong => self.inline.bold += 1,
+            Tag::Strikethrough => self.inline.strike += 1,
+            Tag::Link { dest_url, .. } => {
+                self.link = Some(Hyperlink::new(
+                    dest_url.to_string(),
+                    HyperlinkType::External,
+                ));
+            }
+            Tag::Image { dest_url, .. } => {
+                self.image = Some((dest_url.to_string(), String::new()));
+            }
+            _ => {}
+        }
+    }
+
+    fn end_tag(&mut self, tag: TagEnd) {
+        match tag {
+            TagEnd::Paragraph => self.flush_paragraph(),
+            TagEnd::Heading(_) => {
+                self.flush_paragraph();
+                self.paragraph_style = None;
+            }
+            TagEnd::BlockQuote(_) => {
+                self.flush_paragraph();
+                self.quote_depth = self.quote_depth.saturating_sub(1);
+            }
+            TagEnd::List(_) => {
+                self.flush_paragraph();
+                self.lists.pop();
+            }
+            TagEnd::Item => self.flush_paragraph(),
+            TagEnd::TableCell => {
+                let paragraph = self.paragraph.take().unwrap_or_default();
+                if let Some(table) = self.table.as_mut() {
+                    let mut cell = TableCell::new().add_paragraph(paragraph);
+                    if table.in_head {
+                        cell = cell.shading(Shading::new().fill("F2F2F2"));
+                    }
+                    table.cells.push(cell);
+                }
+            }
+            TagEnd::TableHead | TagEnd::TableRow => {
+                if let Some(table) = self.table.as_mut() {
+                    let cells = std::mem::take(&mut table.cells);
+                    table.rows.push(TableRow::new(cells));
+                    table.in_head = false;
+                }
+            }
+            TagEnd::Table => {
+                if let Some(table) = self.table.take() {
+                    let docx = std::mem::take(&mut self.docx);
+                    self.docx = docx.add_table(Table::new(table.rows));
+                }
+            }
+            TagEnd::Emphasis => self.inline.italic = self.inline.italic.saturating_sub(1),
+            TagEnd::Strong => self.inline.bold = self.inline.bold.saturating_sub(1),
+            TagEnd::Strikethrough => self.inline.strike = self.inline.strike.saturating_sub(1),
+            TagEnd::Link => {
+                if let Some(link) = self.link.take() {
+                    let paragraph = self.paragraph.take().unwrap_or_default();
+                    self.paragraph = Some(paragraph.add_hyperlink(link));
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn styled_run(&self, mut run: Run) -> Run {
+        let in_table_head = self.table.as_ref().is_some_and(|t| t.in_head);
+        if self.inline.bold > 0 || in_table_head {
+            run = run.bold();
+        }
+        if self.inline.italic > 0 {
+            run = run.italic();
+        }
+        if self.inline.strike > 0 {
+            run = run.strike();
+        }
+        if self.link.is_some() {
+            run = run.style("Hyperlink");
+        }
+        run
+    }
+
+    fn push_run(&mut self, run: Run) {
+        if let Some(link) = self.link.take() {
+            self.link = Some(link.add_run(run));
+            return;
+        }
+        let paragraph = self.paragraph.take().unwrap_or_default();
+        self.paragraph = Some(paragraph.add_run(run));
+    }
+
+    /// 结束当前段落，附加标题/引用/列表等段落属性后写入文档
+    fn flush_paragraph(&mut self) {
+        if self.table.is_some() {
+            return;
+        }
+        let Some(mut paragraph) = self.paragraph.take() else {
+            return;
+        };
+        if let Some(style) = &self.paragraph_style {
+            paragraph = paragraph.style(style);
+        } else if self.quote_depth > 0 {
+            paragraph = paragraph.style("Quote");
+        }
+        if let Some(list) = self.lists.last() {
+            let level = IndentLevel::new(self.lists.len().saturating_sub(1).min(8));
+            let id = list.unwrap_or(BULLET_NUMBERING_ID);
+            paragraph = paragraph.numbering(NumberingId::new(id), level);
+        }
+        self.add_paragraph(paragraph);
+    }
+
+    fn add_paragraph(&mut self, paragraph: Paragraph) {
+        let docx = std::mem::take(&mut self.docx);
+        self.docx = docx.add_paragraph(paragraph);
+    }
+
+    fn add_ordered_numbering(&mut self, start: usize) -> usize {
+        let id = self.next_numbering_id;
+        self.next_numbering_id += 1;
+        let mut abstract_numbering = AbstractNumbering::new(id);
+        for level in 0..9 {
+            abstract_numbering = abstract_numbering.add_level(
+                Level::new(
+                    level,
+                    Start::new(if level == 0 { start } else { 1 }),
+                    NumberFormat::new(if level % 2 == 0 {
+                        "decimal"
+                    } else {
+                        "lowerLetter"
+                    }),
+                    LevelText::new(format!("%{}.", level + 1)),
+                    LevelJc::new("left"),
+                )
+                .indent(
+                    Some(720 * (level as i32 + 1)),
+                    Some(SpecialIndentType::Hanging(360)),
+                    None,
+                    None,
+                ),
+            );
+        }
+        let docx = std::mem::take(&mut self.docx);
+        self.docx = docx
+            .add_abstract_numbering(abstract_numbering)
+            .add_numbering(Numbering::new(id, id));
+        id
+    }
+
+    fn finish_code_block(&mut self) {
+        let Some(code) = self.code_block.take() else {
+            return;
+        };
+        let mut paragraph = Paragraph::new().style("SourceCode");
+        for (i, line) in code.trim_end_matches('\n').split('\n').enumerate() {
+            let mut run = Run::new();
+            if i > 0 {
+                run = run.add_break(BreakType::TextWrapping);
+            }
+            paragraph = paragraph.add_run(run.add_text(line.replace('\t', "    ")));
+        }
+        paragraph.property = paragraph.property.shading(Shading::new().fill("F6F8FA"));
+        self.add_paragraph(paragraph);
+    }
+
+    fn finish_image(&mut self) {
+        let Some((url, alt)) = self.image.take() else {
+            return;
+        };
+        match load_picture(&url, self.base_dir.as_deref()) {
+            Ok(pic) => self.push_run(Run::new().add_image(pic)),
+            Err(e) => {
+                log::warn!("[export_docx] Image not embedded ({}): {}", url, e);
+                let label = if alt.is_empty() { url } else { alt };
+                self.push_run(Run::new().add_text(format!("[{}]", label)).italic());
+            }
+        }
+    }
+}
+
+fn bullet_numbering() -> AbstractNumbering {
+    let bullets = ["•", "◦", "▪"];
+    let mut numbering = AbstractNumbering::new(BULLET_NUMBERING_ID);
+    for level in 0..9 {
+        numbering = numbering.add_level(
+            Level::new(
+                level,
+                Start::new(1),
+                NumberFormat::new("bullet"),
+                LevelText::new(bullets[level % bullets.len()]),
+                LevelJc::new("left"),
+            )
+            .indent(
+                Some(720 * (level as i32 + 1)),
+                Some(SpecialIndentType::Hanging(360)),
+                None,
+                None,
+            ),
+        );
+    }
+    numbering
+}
+
+/// 读取本地图片并统一转换为 PNG（docx-rs 只支持内嵌 PNG）
+fn load_picture(url: &str, base_dir: Option<&Path>) -> Result<Pic, String> {
+    if url.starts_with("http://") || url.starts_with("https://") || url.starts_with("data:") {
+        return Err("remote images are not supported".to_string());
+    }
+    let path = crate::render::resolve_local_path(url, base_dir);
+    let image = image::open(&path).map_err(|e| format!("{:?}: {}", path, e))?;
+    let (width, height) = image.dimensions();
+
+    let mut png = Vec::new();
+    image
+        .write_to(&mut png, image::ImageOutputFormat::Png)
+        .map_err(|e| format!("Failed to encode image: {}", e))?;
+
+    let (display_w, display_h) = if width > MAX_IMAGE_WIDTH_PX {
+        let h = (u64::from(height) * u64::from(MAX_IMAGE_WIDTH_PX) / u64::from(width)).max(1);
+        (MAX_IMAGE_WIDTH_PX, h as u32)
+    } else {
+        (width, height)
+    };
+    Ok(Pic::new_with_dimensions(png, display_w, display_h))
+}
+
+/// 使用模板中的 `styles.xml` 替换生成文档的样式
+///
+/// 模板中未定义、但文档用到的样式（如代码样式）会从生成的样式表中补充进去。
+fn apply_template_styles(docx: &[u8], template: &Path) -> Result<Vec<u8>, String> {
+    let template_bytes =
+        fs::read(template).map_err(|e| format!("Failed to read template {:?}: {}", template, e))?;
+    let template_styles = read_zip_entry(&template_bytes, STYLES_XML)
+        .map_err(|e| format!("Invalid template {:?}: {}", template, e))?;
+    let generated_styles = read_zip_entry(docx, STYLES_XML)?;
+    let merged = merge_styles(&template_styles, &generated_styles);
+
+    let mut archive =
+        zip::ZipArchive::new(Cursor::new(docx)).map_err(|e| format!("Invalid docx: {}", e))?;
+    let mut out = zip::ZipWriter::new(Cursor::new(Vec::new()));
+    let options = zip::write::SimpleFileOptions::default()
+        .compression_method(zip::CompressionMethod::Deflated);
+
+    for i in 0..archive.len() {
+        let mut entry = archive.by_index(i).map_err(|e| e.to_string())?;
+        let name = entry.name().to_string();
+        out.start_file(name.as_str(), options)
+            .map_err(|e| e.to_string())?;
+        if name == STYLES_XML {
+            out.write_all(merged.as_bytes())
+                .map_err(|e| e.to_string())?;
+        } else {
+            let mut data = Vec::new();
+            entry.read_to_end(&mut data).map_err(|e| e.to_string())?;
+            out.write_all(&data).map_err(|e| e.to_string())?;
+        }
+    }
+
+    out.finish()
+        .map(Cursor::into_inner)
+        .map_err(|e| format!("Failed to write docx: {}", e))
+}
+
+fn read_zip_entry(bytes: &[u8], name: &str) -> Result<String, String> {
+    let mut archive =
+        zip::ZipArchive::new(Cursor::new(bytes)).map_err(|e| format!("not a zip file: {}", e))?;
+    let mut entry = archive
+        .by_name(name)
+        .map_err(|e| format!("missing {}: {}", name, e))?;
+    let mut xml = String::new();
+    entry
+        .read_to_string(&mut xml)
+        .map_err(|e| format!("failed to read {}: {}", name, e))?;
+    Ok(xml)
+}
+
+/// 将生成样式表中模板缺少的 `<w:style>` 追加到模板样式表末尾
+fn merge_styles(template: &str, generated: &str) -> String {
+    let template_ids: HashSet<String> = style_blocks(template)
+        .filter_map(|block| style_id(block).map(str::to_string))
+        .collect();
+    let missing: String = style_blocks(generated)
+        .filter(|block| style_id(block).is_some_and(|id| !template_ids.contains(id)))
+        .collect();
+
+    match template.rfind("</w:styles>") {
+        Some(pos) if !missing.is_empty() => {
+            format!("{}{}{}", &template[..pos], missing, &template[pos..])
+        }
+        _ => template.to_string(),
+    }
+}
+
+fn style_blocks(xml: &str) -> impl Iterator<Item = &str> {
+    let mut rest = xml;
+    std::iter::from_fn(move || {
+        let start = rest.find("<w:style ")?;
+        let end = rest[start..].find("</w:style>")? + start + "</w:style>".len();
+        let block = &rest[start..end];
+        rest = &rest[end..];
+        Some(block)
+    })
+}
+
+fn style_id(block: &str) -> Option<&str> {
+    let start = block.find("w:styleId=\"")? + "w:styleId=\"".len();
+    let len = block[start..].find('"')?;
+    Some(&block[start..start + len])
+}
+
+// 导出 Word 文档
+#[tauri::command]
+pub async fn export_docx(
+    app: AppHandle,
+    params: ExportDocxParams,
+) -> Result<ExportResult, VividError> {
+    let start = Instant::now();
+    let output = PathBuf::from(&params.path);
+    access::ensure_access(&app, &output, AccessKind::Write)?;
+
+    log::info!("[export_docx] Starting DOCX export operation");
+    log::debug!("[export_docx] Target path: {}", params.path);
+    log::debug!(
+        "[export_docx] Markdown size: {} bytes",
+        params.content.len()
+    );
+
+    let base_dir = params.base_dir.map(PathBuf::from);
+    let template = params
+        .template
+        .or_else(|| app.state::<SettingsStore>().get().export.docx_template)
+        .filter(|template| !template.trim().is_empty())
+        .map(PathBuf::from);
+    log::debug!("[export_docx] Template: {:?}", template);
+    if let Some(template) = &template {
+        access::ensure_access(&app, template, AccessKind::Read)?;
+    }
+    let content = params.content;
+    let result = tauri::async_runtime::spawn_blocking(move || {
+        let content = bibliography::resolve_citations(&app, content);
+        markdown_to_docx(&content, base_dir.as_deref(), template.as_deref())
+    })
+    .await
+    .map_err(|e| format!("DOCX export task failed: {}", e))?;
+
+    let bytes = match result {
+        Ok(bytes) => bytes,
+        Err(e) => {
+            log::error!("[export_docx] Conversion failed: {}", e);
+            return Ok(ExportResult::failed(e));
+        }
+    };
+
+    if let Err(e) = fs::write(&output, &bytes) {
+        log::error!("[export_docx] Failed to write output: {}", e);
+        return Ok(ExportResult::failed(format!("Failed to write file: {}", e)));
+    }
+
+    log::info!(
+        "[export_docx] ✓ Success: {} ({} bytes) in {:?}",
+        params.path,
+        bytes.len(),
+        start.elapsed()
+    );
+    Ok(ExportResult::succeeded(&output))
+}
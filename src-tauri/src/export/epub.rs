@@ -0,0 +1,945 @@
+//! 导出为 EPUB 电子书
+//!
+//! 生成 EPUB 3：传入多个文件（或文件夹）时每个文件一章；只有一个文件时按文中最高一级的标题
+//! 分章。书中包含目录导航 `nav.xhtml`（另附 `toc.ncx` 供只认 EPUB 2 的阅读器使用）、可选的
+//! 封面图片和嵌入字体。
+//!
+//! 章节由预览的渲染管线生成 HTML，再逐个节点写成 XHTML：本地图片和内嵌的图表复制到书中，
+//! 指向其它章节的链接改写为对应的章节文件，指向书外本地文件的链接去掉地址。
+
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::io::{Cursor, Write};
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+
+use base64::Engine;
+use kuchikiki::traits::*;
+use kuchikiki::{NodeData, NodeRef};
+use pulldown_cmark::{Event, Tag, TagEnd};
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+use tauri::AppHandle;
+
+use super::html::first_heading;
+use super::ExportResult;
+use crate::access::{self, AccessKind};
+use crate::error::VividError;
+use crate::render::{self, RenderOptions};
+use crate::{frontmatter, highlight, links, markdown, workspace};
+
+const EPUB_CSS: &str = include_str!("themes/epub.css");
+const MIMETYPE: &str = "application/epub+zip";
+const CONTAINER_XML: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<container version="1.0" xmlns="urn:oasis:names:tc:opendocument:xmlns:container">
+  <rootfiles>
+    <rootfile full-path="OEBPS/content.opf" media-type="application/oebps-package+xml"/>
+  </rootfiles>
+</container>
+"#;
+const MATHML_NS: &str = "http://www.w3.org/1998/Math/MathML";
+const SVG_NS: &str = "http://www.w3.org/2000/svg";
+/// XHTML 中需要写成 `<br/>` 形式的空元素
+const VOID_TAGS: &[&str] = &[
+    "area", "base", "br", "col", "embed", "hr", "img", "input", "link", "meta", "source", "track",
+    "wbr",
+];
+/// 阅读器中不执行脚本，这些元素直接丢弃
+const SKIPPED_TAGS: &[&str] = &["script", "noscript", "iframe", "object", "template"];
+
+/// 书籍信息
+#[derive(Debug, Default, Deserialize)]
+pub struct EpubMetadata {
+    /// 书名，默认取单个文件的标题或文件夹名
+    pub title: Option<String>,
+    pub author: Option<String>,
+    /// 语言代码（如 `zh`、`en`），默认按内容是否包含中日韩文字判断
+    pub language: Option<String>,
+    pub publisher: Option<String>,
+    pub description: Option<String>,
+    /// 唯一标识（如 ISBN 或 `urn:uuid:...`），默认自动生成
+    pub identifier: Option<String>,
+    /// 封面图片路径
+    pub cover: Option<String>,
+    /// 嵌入的字体文件（.ttf / .otf / .woff / .woff2），第一个用作正文字体
+    #[serde(default)]
+    pub fonts: Vec<String>,
+}
+
+/// EPUB 导出参数
+#[derive(Debug, Deserialize)]
+pub struct ExportEpubParams {
+    /// Markdown 文件或文件夹，按顺序成为章节；文件夹中的文件按路径排序
+    pub paths: Vec<String>,
+    /// 输出 .epub 路径，默认与第一个文件（或文件夹）同名
+    pub output: Option<String>,
+    #[serde(default)]
+    pub metadata: EpubMetadata,
+}
+
+struct Chapter {
+    title: String,
+    content: String,
+    /// 源文件，用于解析相对路径和改写章节之间的链接
+    source: PathBuf,
+}
+
+/// 书中的一个文件
+struct ManifestItem {
+    id: String,
+    href: String,
+    media_type: String,
+    properties: Option<String>,
+}
+
+/// 打包前的书籍内容
+#[derive(Default)]
+struct Book {
+    /// `OEBPS/` 下的文件
+    files: Vec<(String, Vec<u8>)>,
+    manifest: Vec<ManifestItem>,
+    /// 已复制的图片：源路径（或远程地址、data URI 的哈希）-> 书中的路径
+    images: HashMap<String, String>,
+}
+
+impl Book {
+    fn add(&mut self, id: &str, href: &str, media_type: &str, data: Vec<u8>) {
+        self.files.push((href.to_string(), data));
+        self.manifest.push(ManifestItem {
+            id: id.to_string(),
+            href: href.to_string(),
+            media_type: media_type.to_string(),
+            properties: None,
+        });
+    }
+
+    /// 复制一张图片，同一张图片只保存一次
+    fn add_image(&mut self, key: String, mime: &str, data: Vec<u8>) -> String {
+        if let Some(href) = self.images.get(&key) {
+            return href.clone();
+        }
+        let n = self.images.len() + 1;
+        let href = format!("images/image-{}.{}", n, mime_extension(mime));
+        self.add(&format!("image-{}", n), &href, mime, data);
+        self.images.insert(key, href.clone());
+        href
+    }
+}
+
+fn mime_extension(mime: &str) -> &str {
+    match mime {
+        "image/jpeg" => "jpg",
+        "image/svg+xml" => "svg",
+        "image/x-icon" => "ico",
+        other => other.rsplit('/').next().unwrap_or("img"),
+    }
+}
+
+fn font_mime_type(path: &Path) -> Option<&'static str> {
+    let ext = path.extension()?.to_str()?.to_ascii_lowercase();
+    Some(match ext.as_str() {
+        "ttf" => "font/ttf",
+        "otf" => "font/otf",
+        "woff" => "font/woff",
+        "woff2" => "font/woff2",
+        _ => return None,
+    })
+}
+
+/// 转义 XML 文本，并去掉 XML 中不允许出现的控制字符
+fn escape_xml(text: &str) -> String {
+    let text: String = text
+        .chars()
+        .filter(|c| !c.is_control() || matches!(c, '\t' | '\n' | '\r'))
+        .collect();
+    markdown::escape_html(&text)
+}
+
+/// HTML 解析器接受、但 XML 中不是合法名称的属性名（如 `x[y]`）跳过不写
+fn is_xml_name(name: &str) -> bool {
+    name.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_')
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '.' | ':'))
+}
+
+/// 依次展开文件和文件夹，去掉重复的文件
+fn collect_sources(paths: &[String]) -> Result<Vec<PathBuf>, VividError> {
+    let mut seen = HashSet::new();
+    let mut sources = Vec::new();
+    for path in paths.iter().map(PathBuf::from) {
+        let files = if path.is_dir() {
+            workspace::markdown_files(&path)
+        } else if path.is_file() {
+            vec![path]
+        } else {
+            return Err(VividError::not_found(&path));
+        };
+        sources.extend(files.into_iter().filter(|file| seen.insert(file.clone())));
+    }
+    Ok(sources)
+}
+
+fn strip_front_matter(content: &str) -> &str {
+    match frontmatter::find_front_matter(content) {
+        Some(block) => &content[block.body_start..],
+        None => content,
+    }
+}
+
+/// 按文中最高一级的标题拆分；不足两个这样的标题时不拆分
+fn split_by_headings(content: &str) -> Vec<(Option<String>, &str)> {
+    let mut headings: Vec<(usize, usize, String)> = Vec::new();
+    let mut current: Option<(usize, usize, String)> = None;
+    for (event, range) in markdown::parser(content).into_offset_iter() {
+        match event {
+            Event::Start(Tag::Heading { level, .. }) => {
+                current = Some((level as usize, range.start, String::new()));
+            }
+            Event::Text(text) | Event::Code(text) => {
+                if let Some((_, _, title)) = current.as_mut() {
+                    title.push_str(&text);
+                }
+            }
+            Event::End(TagEnd::Heading(_)) => headings.extend(current.take()),
+            _ => {}
+        }
+    }
+    let Some(top) = headings.iter().map(|(level, _, _)| *level).min() else {
+        return vec![(None, content)];
+    };
+    let starts: Vec<(usize, String)> = headings
+        .into_iter()
+        .filter(|(level, _, _)| *level == top)
+        .map(|(_, start, title)| (start, title.trim().to_string()))
+        .collect();
+    if starts.len() < 2 {
+        return vec![(None, content)];
+    }
+
+    let mut parts = Vec::new();
+    let preamble = &content[..starts[0].0];
+    if !preamble.trim().is_empty() {
+        parts.push((None, preamble));
+    }
+    for (i, (start, title)) in starts.iter().enumerate() {
+        let end = starts.get(i + 1).map_or(content.len(), |(next, _)| *next);
+        parts.push((Some(title.clone()), &content[*start..end]));
+    }
+    parts
+}
+
+fn read_chapters(sources: &[PathBuf], book_title: &str) -> Result<Vec<Chapter>, VividError> {
+    let mut chapters = Vec::new();
+    for source in sources {
+        let content = fs::read_to_string(source).map_err(|e| {
+            log::error!("[export_epub] Failed to read {}: {}", source.display(), e);
+            VividError::io("Failed to read file", source, &e)
+        })?;
+        let body = strip_front_matter(&content);
+        if sources.len() > 1 {
+            chapters.push(Chapter {
+                title: first_heading(body).unwrap_or_else(|| crate::file_name_of(source)),
+                content: body.to_string(),
+                source: source.clone(),
+            });
+            continue;
+        }
+        for (title, part) in split_by_headings(body) {
+            chapters.push(Chapter {
+                title: title.unwrap_or_else(|| book_title.to_string()),
+                content: part.to_string(),
+                source: source.clone(),
+            });
+        }
+    }
+    Ok(chapters)
+}
+
+/// 文中各标题的文字
+fn heading_texts(content: &str) -> Vec<String> {
+    let mut headings = Vec::new();
+    let mut current: Option<String> = None;
+    for event in markdown::parser(content) {
+        match event {
+            Event::Start(Tag::Heading { .. }) => current = Some(String::new()),
+            Event::Text(text) | Event::Code(text) => {
+                if let Some(title) = current.as_mut() {
+                    title.push_str(&text);
+                }
+            }
+            Event::End(TagEnd::Heading(_)) => headings.extend(current.take()),
+            _ => {}
+        }
+    }
+    headings
+}
+
+/// 章节之间的链接目标
+#[derive(Default)]
+struct LinkTargets {
+    /// 源文件 -> 章节文件（拆分出多个章节时为第一章）
+    files: HashMap<PathBuf, String>,
+    /// （源文件，标题 id）-> 该标题所在的章节文件
+    anchors: HashMap<(PathBuf, String), String>,
+}
+
+impl LinkTargets {
+    fn new(chapters: &[Chapter]) -> Self {
+        let mut targets = LinkTargets::default();
+        for (i, chapter) in chapters.iter().enumerate() {
+            let file = format!("chapter-{}.xhtml", i + 1);
+            let source = links::normalize_path(&chapter.source);
+            for heading in heading_texts(&chapter.content) {
+                targets
+                    .anchors
+                    .entry((source.clone(), markdown::slugify(&heading)))
+                    .or_insert_with(|| file.clone());
+            }
+            targets.files.entry(source).or_insert(file);
+        }
+        targets
+    }
+
+    fn anchor(&self, source: &Path, fragment: &str) -> Option<&String> {
+        self.anchors
+            .get(&(source.to_path_buf(), markdown::slugify(fragment)))
+    }
+}
+
+/// 把渲染出的 HTML 写成 XHTML，同时收集章节用到的图片
+struct XhtmlWriter<'a> {
+    book: &'a mut Book,
+    base_dir: Option<&'a Path>,
+    targets: &'a LinkTargets,
+    /// 当前章节的源文件和章节文件
+    source: &'a Path,
+    file: &'a str,
+    /// 本章已使用的标题 id
+    ids: HashSet<String>,
+    /// manifest 中本章的 `properties`
+    properties: HashSet<&'static str>,
+    out: String,
+}
+
+impl XhtmlWriter<'_> {
+    fn write_children(&mut self, node: &NodeRef) {
+        for child in node.children() {
+            self.write_node(&child);
+        }
+    }
+
+    fn write_node(&mut self, node: &NodeRef) {
+        match node.data() {
+            NodeData::Text(text) => self.out.push_str(&escape_xml(&text.borrow())),
+            NodeData::Element(element) => {
+                let tag = element.name.local.to_string();
+                if SKIPPED_TAGS.contains(&tag.as_str()) {
+                    return;
+                }
+                let mut attributes: Vec<(String, String)> = element
+                    .attributes
+                    .borrow()
+                    .map
+                    .iter()
+                    .map(|(name, attr)| (name.local.to_string(), attr.value.clone()))
+                    .filter(|(name, _)| is_xml_name(name) && !name.starts_with("on"))
+                    .collect();
+
+                match tag.as_str() {
+                    // 无法放入书中的图片显示替代文字
+                    "img" if !self.rewrite_image(&mut attributes) => {
+                        if let Some((_, alt)) = attributes.iter().find(|(n, _)| n == "alt") {
+                            self.out.push_str(&escape_xml(alt));
+                        }
+                        return;
+                    }
+                    "a" => self.rewrite_link(&mut attributes),
+                    "h1" | "h2" | "h3" | "h4" | "h5" | "h6"
+                        if !attributes.iter().any(|(n, _)| n == "id") =>
+                    {
+                        let id = self.unique_id(&node.text_contents());
+                        attributes.push(("id".to_string(), id));
+                    }
+                    "math" => {
+                        self.properties.insert("mathml");
+                        attributes.retain(|(n, _)| n != "xmlns");
+                        attributes.push(("xmlns".to_string(), MATHML_NS.to_string()));
+                    }
+                    "svg" => {
+                        self.properties.insert("svg");
+                        attributes.retain(|(n, _)| n != "xmlns");
+                        attributes.push(("xmlns".to_string(), SVG_NS.to_string()));
+                    }
+                    _ => {}
+                }
+
+                self.out.push('<');
+                self.out.push_str(&tag);
+                for (name, value) in &attributes {
+                    self.out
+                        .push_str(&format!(" {}=\"{}\"", name, escape_xml(value)));
+                }
+                if VOID_TAGS.contains(&tag.as_str()) {
+                    self.out.push_str("/>");
+                    return;
+                }
+                self.out.push('>');
+                self.write_children(node);
+                self.out.push_str(&format!("</{}>", tag));
+            }
+            NodeData::Document(_) | NodeData::DocumentFragment => self.write_children(node),
+            _ => {}
+        }
+    }
+
+    fn unique_id(&mut self, text: &str) -> String {
+        let base = markdown::slugify(text);
+        let base = if base.is_empty() {
+            "section".to_string()
+        } else {
+            base
+        };
+        let mut id = base.clone();
+        let mut n = 1;
+        while !self.ids.insert(id.clone()) {
+            id = format!("{}-{}", base, n);
+            n += 1;
+        }
+        id
+    }
+
+    /// 把图片放入书中并改写地址，无法放入时返回 false
+    fn rewrite_image(&mut self, attributes: &mut [(String, String)]) -> bool {
+        let Some((_, src)) = attributes.iter_mut().find(|(n, _)| n == "src") else {
+            return false;
+        };
+        if let Some(data) = src.strip_prefix("data:") {
+            // 渲染后的图表等内嵌图片
+            let Some((mime, encoded)) = data.split_once(";base64,") else {
+                return false;
+            };
+            let Ok(bytes) = base64::engine::general_purpose::STANDARD.decode(encoded.trim()) else {
+                return false;
+            };
+            let key = Sha256::digest(&bytes)
+                .iter()
+                .map(|b| format!("{:02x}", b))
+                .collect();
+            *src = self.book.add_image(key, mime, bytes);
+            return true;
+        }
+        if src.starts_with("http://") || src.starts_with("https://") {
+            // 远程图片保留地址，由阅读器联网加载
+            let url = src.split(['?', '#']).next().unwrap_or(src);
+            let Some(mime) = render::image_mime_type(Path::new(url)) else {
+                return false;
+            };
+            if !self.book.images.contains_key(src.as_str()) {
+                let n = self.book.images.len() + 1;
+                self.book.manifest.push(ManifestItem {
+                    id: format!("image-{}", n),
+                    href: src.clone(),
+                    media_type: mime.to_string(),
+                    properties: None,
+                });
+                self.book.images.insert(src.clone(), src.clone());
+            }
+            self.properties.insert("remote-resources");
+            return true;
+        }
+
+        let path = render::resolve_local_path(src, self.base_dir);
+        let Some(mime) = render::image_mime_type(&path) else {
+            return false;
+        };
+        let key = path.to_string_lossy().to_string();
+        if let Some(href) = self.book.images.get(&key) {
+            *src = href.clone();
+            return true;
+        }
+        match fs::read(&path) {
+            Ok(bytes) => {
+                *src = self.book.add_image(key, mime, bytes);
+                true
+            }
+            Err(e) => {
+                log::warn!("[export_epub] Image not included {:?}: {}", path, e);
+                false
+            }
+        }
+    }
+
+    /// 指向其它章节的链接改为章节文件，指向书外本地文件的链接去掉地址
+    fn rewrite_link(&mut self, attributes: &mut Vec<(String, String)>) {
+        let Some(index) = attributes.iter().position(|(n, _)| n == "href") else {
+            return;
+        };
+        let href = &attributes[index].1;
+        if let Some(fragment) = href.strip_prefix('#') {
+            // 同一文件拆出的章节之间用 `#标题` 互相引用
+            if let Some(file) = self
+                .targets
+                .anchor(self.source, fragment)
+                .filter(|file| file.as_str() != self.file)
+            {
+                attributes[index].1 = format!("{}#{}", file, markdown::slugify(fragment));
+            }
+            return;
+        }
+        if links::is_external(href) {
+            return;
+        }
+        let (path, fragment) = href.split_once('#').unwrap_or((href, ""));
+        let target = links::normalize_path(&render::resolve_local_path(path, self.base_dir));
+        let Some(file) = self.targets.files.get(&target) else {
+            attributes.remove(index);
+            return;
+        };
+        attributes[index].1 = if fragment.is_empty() {
+            file.clone()
+        } else {
+            let file = self.targets.anchor(&target, fragment).unwrap_or(file);
+            format!("{}#{}", file, markdown::slugify(fragment))
+        };
+    }
+}
+
+fn chapter_xhtml(title: &str, language: &str, body: &str) -> String {
+    format!(
+        r#"<?xml version="1.0" encoding="UTF-8"?>
+<!DOCTYPE html>
+<html xmlns="http://www.w3.org/1999/xhtml" xmlns:epub="http://www.idpf.org/2007/ops" lang="{lang}" xml:lang="{lang}">
+<head>
+<meta charset="UTF-8"/>
+<title>{title}</title>
+<link rel="stylesheet" type="text/css" href="style.css"/>
+</head>
+<body>
+{body}
+</body>
+</html>
+"#,
+        lang = escape_xml(language),
+        title = escape_xml(title),
+        body = body
+    )
+}
+
+fn nav_xhtml(chapters: &[(String, String)], language: &str) -> String {
+    let heading = if language.starts_with("zh") {
+        "目录"
+    } else {
+        "Contents"
+    };
+    let items: String = chapters
+        .iter()
+        .map(|(href, title)| {
+            format!(
+                "<li><a href=\"{}\">{}</a></li>\n",
+                escape_xml(href),
+                escape_xml(title)
+            )
+        })
+        .collect();
+    chapter_xhtml(
+        heading,
+        language,
+        &format!(
+            "<nav epub:type=\"toc\" id=\"toc\">\n<h1>{}</h1>\n<ol>\n{}</ol>\n</nav>",
+            heading, items
+        ),
+    )
+}
+
+fn toc_ncx(chapters: &[(String, String)], identifier: &str, title: &str) -> String {
+    let points: String = chapters
+        .iter()
+        .enumerate()
+        .map(|(i, (href, title))| {
+            format!(
+                "<navPoint id=\"nav-{n}\" playOrder=\"{n}\"><navLabel><text>{}</text></navLabel><content src=\"{}\"/></navPoint>\n",
+                escape_xml(title),
+                escape_xml(href),
+                n = i + 1
+            )
+        })
+        .collect();
+    format!(
+        r#"<?xml version="1.0" encoding="UTF-8"?>
+<ncx xmlns="http://www.daisy.org/z3986/2005/ncx/" version="2005-1">
+<head><meta name="dtb:uid" content="{}"/></head>
+<docTitle><text>{}</text></docTitle>
+<navMap>
+{}</navMap>
+</ncx>
+"#,
+        escape_xml(identifier),
+        escape_xml(title),
+        points
+    )
+}
+
+/// 未指定标识时按书名、来源和时间生成 `urn:uuid:`
+fn generate_identifier(title: &str, sources: &[PathBuf], modified: &str) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(title.as_bytes());
+    for source in sources {
+        hasher.update(source.to_string_lossy().as_bytes());
+    }
+    hasher.update(modified.as_bytes());
+    let hex: String = hasher
+        .finalize()
+        .iter()
+        .take(16)
+        .map(|b| format!("{:02x}", b))
+        .collect();
+    format!(
+        "urn:uuid:{}-{}-{}-{}-{}",
+        &hex[..8],
+        &hex[8..12],
+        &hex[12..16],
+        &hex[16..20],
+        &hex[20..32]
+    )
+}
+
+fn content_opf(
+    book: &Book,
+    metadata: &EpubMetadata,
+    title: &str,
+    language: &str,
+    identifier: &str,
+    modified: &str,
+    spine: &[String],
+) -> String {
+    let mut meta = vec![
+        format!(
+            "<dc:identifier id=\"book-id\">{}</dc:identifier>",
+            escape_xml(identifier)
+        ),
+        format!("<dc:title>{}</dc:title>", escape_xml(title)),
+        format!("<dc:language>{}</dc:language>", escape_xml(language)),
+        format!("<meta property=\"dcterms:modified\">{}</meta>", modified),
+    ];
+    let optional = [
+        ("creator", &metadata.author),
+        ("publisher", &metadata.publisher),
+        ("description", &metadata.description),
+    ];
+    for (name, value) in optional {
+        if let Some(value) = value.as_deref().filter(|v| !v.trim().is_empty()) {
+            meta.push(format!("<dc:{0}>{1}</dc:{0}>", name, escape_xml(value)));
+        }
+    }
+    if book.manifest.iter().any(|item| item.id == "cover-image") {
+        meta.push("<meta name=\"cover\" content=\"cover-image\"/>".to_string());
+    }
+
+    let manifest: String = book
+        .manifest
+        .iter()
+        .map(|item| {
+            let properties = item
+                .properties
+                .as_deref()
+                .map(|p| format!(" properties=\"{}\"", p))
+                .unwrap_or_default();
+            format!(
+                "<item id=\"{}\" href=\"{}\" media-type=\"{}\"{}/>\n",
+                item.id,
+                escape_xml(&item.href),
+                item.media_type,
+                properties
+            )
+        })
+        .collect();
+    let spine: String = spine
+        .iter()
+        .map(|id| format!("<itemref idref=\"{}\"/>\n", id))
+        .collect();
+
+    format!(
+        r#"<?xml version="1.0" encoding="UTF-8"?>
+<package xmlns="http://www.idpf.org/2007/opf" version="3.0" unique-identifier="book-id" xml:lang="{}">
+<metadata xmlns:dc="http://purl.org/dc/elements/1.1/">
+{}
+</metadata>
+<manifest>
+{}</manifest>
+<spine toc="ncx">
+{}</spine>
+</package>
+"#,
+        escape_xml(language),
+        meta.join("\n"),
+        manifest,
+        spine
+    )
+}
+
+/// 嵌入字体并生成对应的 `@font-face`
+fn add_fonts(book: &mut Book, fonts: &[String]) -> Result<String, VividError> {
+    let mut css = String::new();
+    let mut families = Vec::new();
+    for (i, font) in fonts.iter().enumerate() {
+        let path = PathBuf::from(font);
+        let mime = font_mime_type(&path).ok_or_else(|| {
+            VividError::invalid_input(format!("Unsupported font file: {}", path.display()))
+        })?;
+        let bytes =
+            fs::read(&path).map_err(|e| VividError::io("Failed to read font", &path, &e))?;
+        let file_name = path
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_default();
+        let file_name: String = file_name
+            .chars()
+            .map(|c| {
+                if c.is_ascii_alphanumeric() || matches!(c, '.' | '-' | '_') {
+                    c
+                } else {
+                    '_'
+                }
+            })
+            .collect();
+        let href = format!("fonts/{}-{}", i + 1, file_name);
+        book.add(&format!("font-{}", i + 1), &href, mime, bytes);
+
+        let family = format!("EmbeddedFont{}", i + 1);
+        css.push_str(&format!(
+            "@font-face {{ font-family: \"{}\"; src: url(\"{}\"); }}\n",
+            family, href
+        ));
+        families.push(family);
+    }
+    if let Some(family) = families.first() {
+        css.push_str(&format!("body {{ font-family: \"{}\", serif; }}\n", family));
+    }
+    Ok(css)
+}
+
+/// 生成 EPUB 文件的字节
+fn build_epub(sources: &[PathBuf], metadata: &EpubMetadata) -> Result<Vec<u8>, VividError> {
+    let default_title = match sources {
+        [source] => fs::read_to_string(source)
+            .ok()
+            .and_then(|content| first_heading(strip_front_matter(&content)))
+            .unwrap_or_else(|| crate::file_name_of(source)),
+        _ => sources
+            .first()
+            .and_then(|first| first.parent())
+            .map(crate::file_name_of)
+            .unwrap_or_else(|| "Untitled".to_string()),
+    };
+    let title = metadata
+        .title
+        .clone()
+        .filter(|t| !t.trim().is_empty())
+        .unwrap_or(default_title);
+    let chapters = read_chapters(sources, &title)?;
+    let language = metadata
+        .language
+        .clone()
+        .filter(|l| !l.trim().is_empty())
+        .unwrap_or_else(|| {
+            let cjk = chapters.iter().any(|c| markdown::contains_cjk(&c.content));
+            if cjk { "zh" } else { "en" }.to_string()
+        });
+    let modified = chrono::Utc::now().format("%Y-%m-%dT%H:%M:%SZ").to_string();
+    let identifier = metadata
+        .identifier
+        .clone()
+        .filter(|id| !id.trim().is_empty())
+        .unwrap_or_else(|| generate_identifier(&title, sources, &modified));
+
+    let mut book = Book::default();
+    let mut spine = Vec::new();
+    let font_css = add_fonts(&mut book, &metadata.fonts)?;
+    book.add(
+        "style",
+        "style.css",
+        "text/css",
+        format!("{}{}", EPUB_CSS, font_css).into_bytes(),
+    );
+
+    if let Some(cover) = metadata.cover.as_deref() {
+        let path = PathBuf::from(cover);
+        let mime = render::image_mime_type(&path).ok_or_else(|| {
+            VividError::invalid_input(format!("Unsupported cover image: {}", path.display()))
+        })?;
+        let bytes =
+            fs::read(&path).map_err(|e| VividError::io("Failed to read cover", &path, &e))?;
+        let href = format!("images/cover.{}", mime_extension(mime));
+        book.add("cover-image", &href, mime, bytes);
+        if let Some(item) = book.manifest.last_mut() {
+            item.properties = Some("cover-image".to_string());
+        }
+        let page = chapter_xhtml(
+            &title,
+            &language,
+            &format!(
+                "<div class=\"cover\"><img src=\"{}\" alt=\"{}\"/></div>",
+                href,
+                escape_xml(&title)
+            ),
+        );
+        book.add(
+            "cover",
+            "cover.xhtml",
+            "application/xhtml+xml",
+            page.into_bytes(),
+        );
+        spine.push("cover".to_string());
+    }
+
+    let targets = LinkTargets::new(&chapters);
+    let options = RenderOptions {
+        highlight_theme: Some(highlight::LIGHT_THEME.to_string()),
+        render_math: true,
+        embed_images: false,
+        base_dir: None,
+        hard_breaks: true,
+        render_diagrams: true,
+    };
+    let mut toc = Vec::new();
+    for (i, chapter) in chapters.iter().enumerate() {
+        let id = format!("chapter-{}", i + 1);
+        let href = format!("{}.xhtml", id);
+        let source = links::normalize_path(&chapter.source);
+        let base_dir = chapter.source.parent();
+        let html = render::markdown_to_html(&chapter.content, &options);
+        let document = kuchikiki::parse_html().one(html.as_str()).document_node;
+        let mut writer = XhtmlWriter {
+            book: &mut book,
+            base_dir,
+            targets: &targets,
+            source: &source,
+            file: &href,
+            ids: HashSet::new(),
+            properties: HashSet::new(),
+            out: String::new(),
+        };
+        if let Ok(body) = document.select_first("body") {
+            writer.write_children(body.as_node());
+        }
+        let body = std::mem::take(&mut writer.out);
+        let mut properties: Vec<&str> = writer.properties.into_iter().collect();
+        properties.sort_unstable();
+
+        let page = chapter_xhtml(&chapter.title, &language, &body);
+        book.add(&id, &href, "application/xhtml+xml", page.into_bytes());
+        if let Some(item) = book.manifest.last_mut() {
+            item.properties = (!properties.is_empty()).then(|| properties.join(" "));
+        }
+        spine.push(id);
+        toc.push((href, chapter.title.clone()));
+    }
+
+    book.add(
+        "nav",
+        "nav.xhtml",
+        "application/xhtml+xml",
+        nav_xhtml(&toc, &language).into_bytes(),
+    );
+    if let Some(item) = book.manifest.last_mut() {
+        item.properties = Some("nav".to_string());
+    }
+    book.add(
+        "ncx",
+        "toc.ncx",
+        "application/x-dtbncx+xml",
+        toc_ncx(&toc, &identifier, &title).into_bytes(),
+    );
+    let opf = content_opf(
+        &book,
+        metadata,
+        &title,
+        &language,
+        &identifier,
+        &modified,
+        &spine,
+    );
+
+    package(&book, &opf).map_err(VividError::from)
+}
+
+/// 打包为 zip：`mimetype` 必须是第一个文件且不压缩
+fn package(book: &Book, opf: &str) -> Result<Vec<u8>, String> {
+    let mut zip = zip::ZipWriter::new(Cursor::new(Vec::new()));
+    let stored =
+        zip::write::SimpleFileOptions::default().compression_method(zip::CompressionMethod::Stored);
+    let deflated = zip::write::SimpleFileOptions::default()
+        .compression_method(zip::CompressionMethod::Deflated);
+
+    let mut write = |name: &str, data: &[u8], options| -> Result<(), String> {
+        zip.start_file(name, options).map_err(|e| e.to_string())?;
+        zip.write_all(data).map_err(|e| e.to_string())
+    };
+    write("mimetype", MIMETYPE.as_bytes(), stored)?;
+    write("META-INF/container.xml", CONTAINER_XML.as_bytes(), deflated)?;
+    write("OEBPS/content.opf", opf.as_bytes(), deflated)?;
+    for (href, data) in &book.files {
+        write(&format!("OEBPS/{}", href), data, deflated)?;
+    }
+
+    zip.finish()
+        .map(Cursor::into_inner)
+        .map_err(|e| format!("Failed to write epub: {}", e))
+}
+
+// 导出 EPUB 电子书
+#[tauri::command]
+pub async fn export_epub(
+    app: AppHandle,
+    params: ExportEpubParams,
+) -> Result<ExportResult, VividError> {
+    let start = Instant::now();
+    log::info!("[export_epub] Starting EPUB export operation");
+    log::debug!("[export_epub] Sources: {:?}", params.paths);
+
+    if params.paths.is_empty() {
+        return Err(VividError::invalid_input("No files to export"));
+    }
+    for path in &params.paths {
+        access::ensure_access(&app, Path::new(path), AccessKind::Read)?;
+    }
+    let sources = collect_sources(&params.paths)?;
+    if sources.is_empty() {
+        log::error!("[export_epub] No Markdown files in {:?}", params.paths);
+        return Err(VividError::invalid_input("No Markdown files to export"));
+    }
+    let output = match &params.output {
+        Some(output) => PathBuf::from(output),
+        None => PathBuf::from(&params.paths[0]).with_extension("epub"),
+    };
+    access::ensure_access(&app, &output, AccessKind::Write)?;
+    log::debug!("[export_epub] Target path: {}", output.display());
+
+    let metadata = params.metadata;
+    let chapters = sources.len();
+    let result = tauri::async_runtime::spawn_blocking(move || build_epub(&sources, &metadata))
+        .await
+        .map_err(|e| format!("EPUB export task failed: {}", e))?;
+
+    let bytes = match result {
+        Ok(bytes) => bytes,
+        Err(e) => {
+            log::error!("[export_epub] Conversion failed: {}", e);
+            return Err(e);
+        }
+    };
+    if let Err(e) = fs::write(&output, &bytes) {
+        log::error!("[export_epub] Failed to write output: {}", e);
+        return Ok(ExportResult::failed(format!("Failed to write file: {}", e)));
+    }
+
+    log::info!(
+        "[export_epub] ✓ Success: {} ({} file(s), {} bytes) in {:?}",
+        output.display(),
+        chapters,
+        bytes.len(),
+        start.elapsed()
+    );
+    Ok(ExportResult::succeeded(&output))
+}
@@ -0,0 +1,164 @@
+//! 导出为独立 HTML 文件
+//!
+//! 生成的文件内联全部样式、代码高亮为内联样式、本地图片转为 base64，
+//! 可以直接通过邮件发送，在没有安装 VividMark 的电脑上打开。
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+
+use pulldown_cmark::{Event, HeadingLevel, Tag, TagEnd};
+use serde::Deserialize;
+use tauri::{AppHandle, Manager};
+
+use super::theme::{ExportTheme, ThemeStore};
+use super::ExportResult;
+use crate::access::{self, AccessKind};
+use crate::bibliography;
+use crate::error::VividError;
+use crate::markdown;
+use crate::render::{self, RenderOptions};
+use crate::workspace_config;
+
+/// HTML 导出参数
+#[derive(Debug, Deserialize)]
+pub struct ExportHtmlParams {
+    /// 源 Markdown 文件路径，用于解析相对图片路径和默认输出位置
+    pub path: String,
+    /// 编辑器中尚未保存的内容；为空时从 `path` 读取
+    pub content: Option<String>,
+    /// 输出文件路径，默认与源文件同目录同名 `.html`
+    pub output: Option<String>,
+    /// 导出主题 id（见 `list_export_themes`），默认使用设置中的 HTML 导出主题
+    pub theme: Option<String>,
+    /// 是否将本地图片以 base64 内嵌，默认使用设置
+    pub embed_assets: Option<bool>,
+}
+
+/// 生成完整的独立 HTML 文档
+pub fn build_standalone_html(
+    content: &str,
+    title: &str,
+    theme: &ExportTheme,
+    embed_assets: bool,
+    base_dir: Option<&Path>,
+) -> String {
+    let options = RenderOptions {
+        highlight_theme: Some(theme.highlight_theme().to_string()),
+        render_math: true,
+        embed_images: embed_assets,
+        base_dir: base_dir.map(Path::to_path_buf),
+        hard_breaks: true,
+        render_diagrams: true,
+    };
+    let body = render::markdown_to_html(content, &options);
+
+    format!(
+        r#"<!DOCTYPE html>
+<html>
+<head>
+    <meta charset="UTF-8">
+    <meta name="viewport" content="width=device-width, initial-scale=1">
+    <meta name="generator" content="VividMark">
+    <title>{}</title>
+    <style>
+{}
+    </style>
+</head>
+<body>
+<article class="markdown-body">
+{}
+</article>
+</body>
+</html>
+"#,
+        markdown::escape_html(title),
+        theme.css(),
+        body
+    )
+}
+
+/// 提取文档第一个一级标题作为标题
+pub fn first_heading(content: &str) -> Option<String> {
+    let mut in_heading = false;
+    let mut title = String::new();
+    for event in markdown::parser(content) {
+        match event {
+            Event::Start(Tag::Heading {
+                level: HeadingLevel::H1,
+                ..
+            }) => in_heading = true,
+            Event::End(TagEnd::Heading(HeadingLevel::H1)) => {
+                let title = title.trim().to_string();
+                return if title.is_empty() { None } else { Some(title) };
+            }
+            Event::Text(text) | Event::Code(text) if in_heading => title.push_str(&text),
+            _ => {}
+        }
+    }
+    None
+}
+
+// 导出独立 HTML
+#[tauri::command]
+pub async fn export_html(
+    app: AppHandle,
+    params: ExportHtmlParams,
+) -> Result<ExportResult, VividError> {
+    let start = Instant::now();
+    let source = PathBuf::from(&params.path);
+    // 未指定的选项使用设置（含工作区配置）中的导出默认值
+    let defaults = workspace_config::effective_for(&app, &source).export;
+    let embed_assets = params.embed_assets.unwrap_or(defaults.embed_assets);
+    let theme_name = params.theme.as_deref().unwrap_or(&defaults.html_theme);
+    let theme = app.state::<ThemeStore>().get(Some(theme_name));
+
+    log::info!("[export_html] Starting HTML export operation");
+    log::debug!("[export_html] Source: {}", params.path);
+    log::debug!("[export_html] Theme: {}, embed assets: {}", theme.id(), embed_assets);
+
+    if params.content.is_none() {
+        access::ensure_access(&app, &source, AccessKind::Read)?;
+    }
+    let content = match params.content {
+        Some(content) => content,
+        None => fs::read_to_string(&source).map_err(|e| {
+            log::error!("[export_html] Failed to read source: {}", e);
+            format!("Failed to read file: {}", e)
+        })?,
+    };
+
+    let output = params
+        .output
+        .map(PathBuf::from)
+        .unwrap_or_else(|| source.with_extension("html"));
+    access::ensure_access(&app, &output, AccessKind::Write)?;
+    let title = first_heading(&content).unwrap_or_else(|| {
+        source
+            .file_stem()
+            .and_then(|n| n.to_str())
+            .unwrap_or("VividMark Export")
+            .to_string()
+    });
+    let base_dir = source.parent().map(Path::to_path_buf);
+
+    let html = tauri::async_runtime::spawn_blocking(move || {
+        let content = bibliography::resolve_citations(&app, content);
+        build_standalone_html(&content, &title, &theme, embed_assets, base_dir.as_deref())
+    })
+    .await
+    .map_err(|e| format!("HTML export task failed: {}", e))?;
+
+    if let Err(e) = fs::write(&output, &html) {
+        log::error!("[export_html] Failed to write output {:?}: {}", output, e);
+        return Ok(ExportResult::failed(format!("Failed to write file: {}", e)));
+    }
+
+    log::info!(
+        "[export_html] ✓ Success: {:?} ({} bytes) in {:?}",
+        output,
+        html.len(),
+        start.elapsed()
+    );
+    Ok(ExportResult::succeeded(&output))
+}
@@ -0,0 +1,45 @@
+//! 文档导出
+//!
+//! 各导出格式的实现都放在独立的子模块中，命令在 `lib.rs` 中统一注册。
+
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+pub mod docx;
+pub mod epub;
+pub mod html;
+pub mod pandoc;
+pub mod pdf;
+pub mod print;
+pub mod publish;
+pub mod slides;
+pub mod theme;
+pub mod workspace;
+
+/// 导出命令的通用返回结果
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ExportResult {
+    pub success: bool,
+    /// 实际写入的文件路径
+    pub path: Option<String>,
+    pub error: Option<String>,
+}
+
+impl ExportResult {
+    pub fn succeeded(path: &Path) -> Self {
+        ExportResult {
+            success: true,
+            path: Some(path.to_string_lossy().to_string()),
+            error: None,
+        }
+    }
+
+    pub fn failed(error: impl Into<String>) -> Self {
+        ExportResult {
+            success: false,
+            path: None,
+            error: Some(error.into()),
+        }
+    }
+}
@@ -0,0 +1,288 @@
+//! 通过 pandoc 转换格式
+//!
+//! ODT、EPUB、LaTeX、reStructuredText 等格式交给 pandoc 转换，不在应用中逐一实现。
+//! 与图表渲染一样调用外部命令，按以下顺序查找 pandoc：
+//!
+//! 1. 设置中的 `export.pandoc_path`；
+//! 2. 随应用附带、与可执行文件放在同一目录的 `pandoc`（Tauri sidecar）；
+//! 3. PATH 中的 `pandoc`。
+//!
+//! 没有找到 pandoc 时 `get_pandoc_status` 返回 `available: false`，设置界面据此提示安装。
+
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager};
+
+use super::ExportResult;
+use crate::access::{self, AccessKind};
+use crate::diagram::run_tool_with_timeout;
+use crate::error::VividError;
+use crate::settings::SettingsStore;
+
+/// 查询版本和支持格式的超时
+const PROBE_TIMEOUT: Duration = Duration::from_secs(10);
+/// 单次转换的超时（较大的 EPUB 或 PDF 需要较长时间）
+const CONVERT_TIMEOUT: Duration = Duration::from_secs(300);
+
+/// pandoc 的可用状态，显示在设置界面中
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PandocStatus {
+    pub available: bool,
+    /// 实际使用的可执行文件
+    pub path: Option<String>,
+    pub version: Option<String>,
+    pub input_formats: Vec<String>,
+    pub output_formats: Vec<String>,
+}
+
+/// 转换选项
+#[derive(Debug, Default, Deserialize)]
+pub struct PandocOptions {
+    /// 输出文件路径，默认与输入文件同目录、按目标格式更换扩展名
+    pub output: Option<String>,
+    /// 生成完整文档（`--standalone`），默认开启
+    pub standalone: Option<bool>,
+    /// 生成目录（`--toc`）
+    #[serde(default)]
+    pub table_of_contents: bool,
+    /// DOCX / ODT / PPTX 的参考样式文档（`--reference-doc`）
+    pub reference_doc: Option<String>,
+    /// 追加的 pandoc 参数
+    #[serde(default)]
+    pub extra_args: Vec<String>,
+}
+
+/// 随应用附带的 pandoc
+fn sidecar_path() -> Option<PathBuf> {
+    let exe = std::env::current_exe().ok()?;
+    let name = if cfg!(windows) {
+        "pandoc.exe"
+    } else {
+        "pandoc"
+    };
+    let path = exe.parent()?.join(name);
+    path.is_file().then_some(path)
+}
+
+/// 按查找顺序排列的候选可执行文件
+fn candidates(configured: Option<&str>) -> Vec<PathBuf> {
+    let mut candidates = Vec::new();
+    if let Some(path) = configured.map(str::trim).filter(|p| !p.is_empty()) {
+        candidates.push(PathBuf::from(path));
+    }
+    candidates.extend(sidecar_path());
+    candidates.push(PathBuf::from("pandoc"));
+    candidates
+}
+
+fn run_pandoc(program: &Path, args: &[&str], timeout: Duration) -> Result<String, String> {
+    let output = run_tool_with_timeout(&program.to_string_lossy(), args, "", timeout)?;
+    Ok(String::from_utf8_lossy(&output).into_owned())
+}
+
+/// 第一个能运行的 pandoc 及其版本号
+fn detect(configured: Option<&str>) -> Option<(PathBuf, String)> {
+    candidates(configured).into_iter().find_map(|program| {
+        match run_pandoc(&program, &["--version"], PROBE_TIMEOUT) {
+            Ok(output) => {
+                // 第一行形如 `pandoc 3.1.11`
+                let version = output
+                    .lines()
+                    .next()
+                    .and_then(|line| line.split_whitespace().last())
+                    .unwrap_or_default()
+                    .to_string();
+                Some((program, version))
+            }
+            Err(e) => {
+                log::debug!("[pandoc] {}: {}", program.display(), e);
+                None
+            }
+        }
+    })
+}
+
+fn configured_path(app: &AppHandle) -> Option<String> {
+    app.state::<SettingsStore>().get().export.pandoc_path
+}
+
+fn list_formats(program: &Path, flag: &str) -> Vec<String> {
+    run_pandoc(program, &[flag], PROBE_TIMEOUT)
+        .map(|output| {
+            output
+                .lines()
+                .map(str::trim)
+                .filter(|line| !line.is_empty())
+                .map(str::to_string)
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// 格式名只能包含字母、数字和扩展语法（如 `markdown+smart-raw_html`），不能以 `-` 开头
+fn is_valid_format(format: &str) -> bool {
+    format.starts_with(|c: char| c.is_ascii_alphanumeric())
+        && format
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '+' | '-'))
+}
+
+/// 目标格式对应的文件扩展名
+fn output_extension(format: &str) -> &str {
+    // 去掉 `+ext` / `-ext` 扩展语法
+    let name = format.split(['+', '-']).next().unwrap_or(format);
+    match name {
+        "epub" | "epub2" | "epub3" => "epub",
+        "latex" | "beamer" | "context" => "tex",
+        "html" | "html4" | "html5" | "revealjs" | "slidy" | "slideous" | "s5" | "dzslides" => {
+            "html"
+        }
+        "markdown" | "markdown_strict" | "markdown_phpextra" | "markdown_mmd" | "gfm"
+        | "commonmark" | "commonmark_x" => "md",
+        "asciidoc" | "asciidoctor" => "adoc",
+        "mediawiki" | "dokuwiki" | "xwiki" | "zimwiki" => "wiki",
+        "docbook" | "docbook4" | "docbook5" | "jats" | "tei" => "xml",
+        "plain" => "txt",
+        "typst" => "typ",
+        "man" => "1",
+        other => other,
+    }
+}
+
+fn convert(
+    program: &Path,
+    input: &Path,
+    output: &Path,
+    from: Option<&str>,
+    to: &str,
+    options: &PandocOptions,
+) -> Result<(), String> {
+    let input = input.to_string_lossy().to_string();
+    let output = output.to_string_lossy().to_string();
+    let mut args = vec![input.as_str(), "-o", output.as_str(), "-t", to];
+    if let Some(from) = from {
+        args.extend(["-f", from]);
+    }
+    // 相对路径的图片等资源相对输入文件所在目录查找
+    let resource_path = Path::new(&input)
+        .parent()
+        .map(|dir| dir.to_string_lossy().to_string());
+    if let Some(dir) = resource_path.as_deref().filter(|dir| !dir.is_empty()) {
+        args.extend(["--resource-path", dir]);
+    }
+    if options.standalone.unwrap_or(true) {
+        args.push("--standalone");
+    }
+    if options.table_of_contents {
+        args.push("--toc");
+    }
+    if let Some(reference) = options.reference_doc.as_deref() {
+        args.extend(["--reference-doc", reference]);
+    }
+    args.extend(options.extra_args.iter().map(String::as_str));
+
+    run_pandoc(program, &args, CONVERT_TIMEOUT).map(|_| ())
+}
+
+// 查询 pandoc 是否可用、版本以及支持的输入输出格式
+#[tauri::command]
+pub async fn get_pandoc_status(app: AppHandle) -> Result<PandocStatus, VividError> {
+    let configured = configured_path(&app);
+    let status =
+        tauri::async_runtime::spawn_blocking(move || match detect(configured.as_deref()) {
+            Some((program, version)) => PandocStatus {
+                available: true,
+                path: Some(program.to_string_lossy().to_string()),
+                version: Some(version),
+                input_formats: list_formats(&program, "--list-input-formats"),
+                output_formats: list_formats(&program, "--list-output-formats"),
+            },
+            None => PandocStatus {
+                available: false,
+                path: None,
+                version: None,
+                input_formats: Vec::new(),
+                output_formats: Vec::new(),
+            },
+        })
+        .await
+        .map_err(|e| format!("Pandoc task failed: {}", e))?;
+    log::info!(
+        "[get_pandoc_status] Available: {} ({:?} {:?})",
+        status.available,
+        status.path,
+        status.version
+    );
+    Ok(status)
+}
+
+// 用 pandoc 转换文件格式，`from` 为空时由 pandoc 按扩展名判断
+#[tauri::command]
+pub async fn convert_via_pandoc(
+    app: AppHandle,
+    input: String,
+    from: Option<String>,
+    to: String,
+    options: Option<PandocOptions>,
+) -> Result<ExportResult, VividError> {
+    let start = Instant::now();
+    let options = options.unwrap_or_default();
+    let input = PathBuf::from(&input);
+    access::ensure_access(&app, &input, AccessKind::Read)?;
+    log::info!(
+        "[convert_via_pandoc] {} ({:?} -> {})",
+        input.display(),
+        from,
+        to
+    );
+
+    if !input.is_file() {
+        log::error!(
+            "[convert_via_pandoc] File does not exist: {}",
+            input.display()
+        );
+        return Err(VividError::not_found(&input));
+    }
+    if let Some(format) = std::iter::once(&to)
+        .chain(from.as_ref())
+        .find(|format| !is_valid_format(format))
+    {
+        return Err(VividError::invalid_input(format!(
+            "Invalid pandoc format: {}",
+            format
+        )));
+    }
+    let output = match &options.output {
+        Some(output) => PathBuf::from(output),
+        None => input.with_extension(output_extension(&to)),
+    };
+    access::ensure_access(&app, &output, AccessKind::Write)?;
+    if output == input {
+        return Err(VividError::invalid_input(
+            "Output file must differ from the input",
+        ));
+    }
+
+    let configured = configured_path(&app);
+    let target = output.clone();
+    let result = tauri::async_runtime::spawn_blocking(move || {
+        let (program, _) = detect(configured.as_deref())
+            .ok_or_else(|| "Pandoc is not installed or not in PATH".to_string())?;
+        convert(&program, &input, &target, from.as_deref(), &to, &options)
+    })
+    .await
+    .map_err(|e| format!("Pandoc task failed: {}", e))?;
+
+    if let Err(e) = result {
+        log::error!("[convert_via_pandoc] Conversion failed: {}", e);
+        return Ok(ExportResult::failed(e));
+    }
+    log::info!(
+        "[convert_via_pandoc] ✓ Success: {} in {:?}",
+        output.display(),
+        start.elapsed()
+    );
+    Ok(ExportResult::succeeded(&output))
+}
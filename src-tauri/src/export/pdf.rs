@@ -0,0 +1,1008 @@
+//! Markdown → PDF 后端渲染
+//!
+//! 使用 pulldown-cmark 解析 Markdown，再通过 genpdf 排版生成 PDF。
+//! 字体从系统字体目录中查找并嵌入 PDF，检测到中日韩文字时优先使用 CJK 字体，
+//! 避免导出后出现方块字。文档中单独一行的 `<!-- pagebreak -->` 会强制分页。
+
+use std::cell::Cell;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+
+use genpdf::elements::{
+    Break, FrameCellDecorator, LinearLayout, OrderedList, PaddedElement, PageBreak, Paragraph,
+    TableLayout, UnorderedList,
+};
+use genpdf::fonts::{FontData, FontFamily};
+use genpdf::style::{Color, Style};
+use image::GenericImageView;
+use genpdf::{render, Alignment, Context, Element, Margins, Mm, Position, RenderResult, Size};
+use pulldown_cmark::{CodeBlockKind, Event, HeadingLevel, Tag, TagEnd};
+use serde::{Deserialize, Serialize};
+
+use super::theme::{self, ThemePalette};
+use crate::diagram::{self, DiagramKind};
+use crate::markdown;
+use crate::math;
+
+/// 默认正文字号（pt）
+const DEFAULT_FONT_SIZE: u8 = 11;
+/// 默认页边距（mm）
+const DEFAULT_MARGIN_MM: f64 = 20.0;
+/// 页眉页脚与正文之间的间距（mm）
+const DECORATION_GAP_MM: f64 = 4.0;
+/// 图片按此 DPI 换算为物理尺寸
+const IMAGE_DPI: f64 = 150.0;
+
+/// 正文字体候选（regular, bold, italic, bold italic）
+const SANS_FONT_CANDIDATES: &[[&str; 4]] = &[
+    [
+        "C:\\Windows\\Fonts\\arial.ttf",
+        "C:\\Windows\\Fonts\\arialbd.ttf",
+        "C:\\Windows\\Fonts\\ariali.ttf",
+        "C:\\Windows\\Fonts\\arialbi.ttf",
+    ],
+    [
+        "/System/Library/Fonts/Supplemental/Arial.ttf",
+        "/System/Library/Fonts/Supplemental/Arial Bold.ttf",
+        "/System/Library/Fonts/Supplemental/Arial Italic.ttf",
+        "/System/Library/Fonts/Supplemental/Arial Bold Italic.ttf",
+    ],
+    [
+        "/Library/Fonts/Arial.ttf",
+        "/Library/Fonts/Arial Bold.ttf",
+        "/Library/Fonts/Arial Italic.ttf",
+        "/Library/Fonts/Arial Bold Italic.ttf",
+    ],
+    [
+        "/usr/share/fonts/truetype/liberation/LiberationSans-Regular.ttf",
+        "/usr/share/fonts/truetype/liberation/LiberationSans-Bold.ttf",
+        "/usr/share/fonts/truetype/liberation/LiberationSans-Italic.ttf",
+        "/usr/share/fonts/truetype/liberation/LiberationSans-BoldItalic.ttf",
+    ],
+    [
+        "/usr/share/fonts/truetype/dejavu/DejaVuSans.ttf",
+        "/usr/share/fonts/truetype/dejavu/DejaVuSans-Bold.ttf",
+        "/usr/share/fonts/truetype/dejavu/DejaVuSans-Oblique.ttf",
+        "/usr/share/fonts/truetype/dejavu/DejaVuSans-BoldOblique.ttf",
+    ],
+];
+
+/// 等宽字体候选（用于代码）
+const MONO_FONT_CANDIDATES: &[[&str; 4]] = &[
+    [
+        "C:\\Windows\\Fonts\\consola.ttf",
+        "C:\\Windows\\Fonts\\consolab.ttf",
+        "C:\\Windows\\Fonts\\consolai.ttf",
+        "C:\\Windows\\Fonts\\consolaz.ttf",
+    ],
+    [
+        "/System/Library/Fonts/Supplemental/Courier New.ttf",
+        "/System/Library/Fonts/Supplemental/Courier New Bold.ttf",
+        "/System/Library/Fonts/Supplemental/Courier New Italic.ttf",
+        "/System/Library/Fonts/Supplemental/Courier New Bold Italic.ttf",
+    ],
+    [
+        "/usr/share/fonts/truetype/liberation/LiberationMono-Regular.ttf",
+        "/usr/share/fonts/truetype/liberation/LiberationMono-Bold.ttf",
+        "/usr/share/fonts/truetype/liberation/LiberationMono-Italic.ttf",
+        "/usr/share/fonts/truetype/liberation/LiberationMono-BoldItalic.ttf",
+    ],
+    [
+        "/usr/share/fonts/truetype/dejavu/DejaVuSansMono.ttf",
+        "/usr/share/fonts/truetype/dejavu/DejaVuSansMono-Bold.ttf",
+        "/usr/share/fonts/truetype/dejavu/DejaVuSansMono-Oblique.ttf",
+        "/usr/share/fonts/truetype/dejavu/DejaVuSansMono-BoldOblique.ttf",
+    ],
+];
+
+/// CJK 字体候选
+///
+/// 只列出 `.ttf` 文件：`.ttc` 字体集合无法直接作为 TrueType 嵌入 PDF。
+const CJK_FONT_CANDIDATES: &[&str] = &[
+    "C:\\Windows\\Fonts\\simhei.ttf",
+    "C:\\Windows\\Fonts\\simkai.ttf",
+    "/System/Library/Fonts/Supplemental/Arial Unicode.ttf",
+    "/Library/Fonts/Arial Unicode.ttf",
+    "/System/Library/Fonts/Supplemental/Songti.ttf",
+    "/usr/share/fonts/truetype/droid/DroidSansFallbackFull.ttf",
+    "/usr/share/fonts/truetype/arphic-gkai00mp/gkai00mp.ttf",
+    "/usr/share/fonts/truetype/arphic-gbsn00lp/gbsn00lp.ttf",
+    "/usr/share/fonts/opentype/noto/NotoSansSC-Regular.ttf",
+];
+
+/// PDF 页边距（mm）
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct PdfMargins {
+    pub top: f64,
+    pub right: f64,
+    pub bottom: f64,
+    pub left: f64,
+}
+
+/// PDF 导出选项
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PdfExportOptions {
+    /// 纸张大小：A3 / A4 / A5 / Letter / Legal，默认 A4
+    pub page_size: Option<String>,
+    /// 是否横向
+    pub landscape: Option<bool>,
+    /// 页边距，默认四边 20mm
+    pub margins: Option<PdfMargins>,
+    /// 正文字号（pt）
+    pub font_size: Option<u8>,
+    /// 是否显示页眉，默认显示
+    pub show_header: Option<bool>,
+    /// 页眉文字，默认使用文件名
+    pub header_text: Option<String>,
+    /// 是否在页脚显示页码，默认显示
+    pub show_page_numbers: Option<bool>,
+    /// 页脚左侧附加文字
+    pub footer_text: Option<String>,
+    /// 解析相对图片路径的基准目录（通常为源文档所在目录）
+    pub base_dir: Option<String>,
+    /// 自定义正文字体文件（.ttf）
+    pub font_path: Option<String>,
+    /// 自定义 CJK 字体文件（.ttf）
+    pub cjk_font_path: Option<String>,
+    /// 自定义等宽字体文件（.ttf）
+    pub mono_font_path: Option<String>,
+    /// 导出主题 id，PDF 使用主题的文字、标题、链接和代码颜色
+    pub theme: Option<String>,
+    /// 按 `theme` 解析出的配色，由调用方填入；为空时使用默认浅色配色
+    #[serde(skip)]
+    pub palette: Option<ThemePalette>,
+}
+
+impl PdfExportOptions {
+    /// 纸张尺寸（宽, 高），单位 mm
+    fn paper_size(&self) -> (f64, f64) {
+        let (w, h) = match self
+            .page_size
+            .as_deref()
+            .map(|s| s.to_ascii_lowercase())
+            .as_deref()
+        {
+            Some("a3") => (297.0, 420.0),
+            Some("a5") => (148.0, 210.0),
+            Some("letter") => (215.9, 279.4),
+            Some("legal") => (215.9, 355.6),
+            _ => (210.0, 297.0),
+        };
+        if self.landscape.unwrap_or(false) {
+            (h, w)
+        } else {
+            (w, h)
+        }
+    }
+
+    fn margins(&self) -> PdfMargins {
+        self.margins.unwrap_or(PdfMargins {
+            top: DEFAULT_MARGIN_MM,
+            right: DEFAULT_MARGIN_MM,
+            bottom: DEFAULT_MARGIN_MM,
+            left: DEFAULT_MARGIN_MM,
+        })
+    }
+
+    fn font_size(&self) -> u8 {
+        self.font_size.unwrap_or(DEFAULT_FONT_SIZE).clamp(6, 32)
+    }
+
+    /// 排版用的颜色，纸张总是白色，深色主题改用浅色配色
+    fn colors(&self) -> PdfColors {
+        let palette = self
+            .palette
+            .as_ref()
+            .map(ThemePalette::for_paper)
+            .unwrap_or_else(ThemePalette::light);
+        let color = |value: &str, fallback: Color| {
+            theme::parse_color(value).map_or(fallback, |(r, g, b)| Color::Rgb(r, g, b))
+        };
+        PdfColors {
+            text: color(&palette.text, Color::Greyscale(0)),
+            heading: color(&palette.heading, Color::Greyscale(0)),
+            link: color(&palette.link, Color::Rgb(3, 102, 214)),
+            code: color(&palette.code, Color::Rgb(199, 37, 78)),
+            muted: color(&palette.muted, Color::Greyscale(100)),
+        }
+    }
+}
+
+/// 主题配色换算后的 PDF 颜色
+#[derive(Debug, Clone, Copy)]
+struct PdfColors {
+    text: Color,
+    heading: Color,
+    link: Color,
+    code: Color,
+    muted: Color,
+}
+
+/// 导出前解析好的字体数据（两遍渲染共用）
+struct LoadedFonts {
+    body: FontFamily<FontData>,
+    mono: FontFamily<FontData>,
+}
+
+/// 将 Markdown 渲染为 PDF 并写入 `output`，返回总页数
+pub fn export_markdown_to_pdf(
+    content: &str,
+    output: &Path,
+    title: &str,
+    options: &PdfExportOptions,
+) -> Result<usize, String> {
+    let fonts = load_fonts(content, title, options)?;
+
+    // 第一遍渲染只用于统计总页数，以便页脚显示 "第 n / N 页"
+    let page_counter = Rc::new(Cell::new(0));
+    let doc = build_document(content, title, options, &fonts, None, page_counter.clone())?;
+    doc.render(io::sink())
+        .map_err(|e| format!("Failed to layout PDF: {}", e))?;
+    let total_pages = page_counter.get();
+    log::debug!("[export_pdf] Layout pass finished: {} pages", total_pages);
+
+    let doc = build_document(
+        content,
+        title,
+        options,
+        &fonts,
+        Some(total_pages),
+        Rc::new(Cell::new(0)),
+    )?;
+    doc.render_to_file(output)
+        .map_err(|e| format!("Failed to write PDF: {}", e))?;
+
+    Ok(total_pages)
+}
+
+/// 查找并加载正文字体、等宽字体
+fn load_fonts(content: &str, title: &str, options: &PdfExportOptions) -> Result<LoadedFonts, String> {
+    let needs_cjk = markdown::contains_cjk(content)
+        || markdown::contains_cjk(title)
+        || options
+            .header_text
+            .as_deref()
+            .is_some_and(markdown::contains_cjk)
+        || options
+            .footer_text
+            .as_deref()
+            .is_some_and(markdown::contains_cjk);
+
+    let cjk = if needs_cjk {
+        let cjk = match options.cjk_font_path.as_deref() {
+            Some(path) => Some(load_single_font(Path::new(path))?),
+            None => CJK_FONT_CANDIDATES
+                .iter()
+                .map(Path::new)
+                .filter(|p| p.is_file())
+                .find_map(|p| load_single_font(p).ok()),
+        };
+        if cjk.is_none() {
+            log::warn!("[export_pdf] Document contains CJK text but no CJK font was found");
+        }
+        cjk
+    } else {
+        None
+    };
+
+    let sans = match options.font_path.as_deref() {
+        Some(path) => Some(same_font_family(load_single_font(Path::new(path))?)),
+        None => find_font_family(SANS_FONT_CANDIDATES),
+    };
+
+    // CJK 字体同时包含拉丁字形，含中文的文档整体使用 CJK 字体渲染
+    let body = match (cjk.clone(), sans) {
+        (Some(cjk), _) => same_font_family(cjk),
+        (None, Some(sans)) => sans,
+        (None, None) => {
+            return Err(
+                "No usable TrueType font found on this system; set font_path in export options"
+                    .to_string(),
+            )
+        }
+    };
+
+    let mono = match options.mono_font_path.as_deref() {
+        Some(path) => same_font_family(load_single_font(Path::new(path))?),
+        None => match (cjk, find_font_family(MONO_FONT_CANDIDATES)) {
+            // 代码中出现中文注释时等宽字体无法显示，退回 CJK 字体
+            (Some(cjk), _) if code_contains_cjk(content) => same_font_family(cjk),
+            (_, Some(mono)) => mono,
+            _ => body.clone(),
+        },
+    };
+
+    Ok(LoadedFonts { body, mono })
+}
+
+fn load_single_font(path: &Path) -> Result<FontData, String> {
+    log::debug!("[export_pdf] Loading font: {:?}", path);
+    FontData::load(path, None).map_err(|e| format!("Failed to load font {:?}: {}", path, e))
+}
+
+fn same_font_family(font: FontData) -> FontFamily<FontData> {
+    FontFamily {
+        regular: font.clone(),
+        bold: font.clone(),
+        italic: font.clone(),
+        bold_italic: font,
+    }
+}
+
+/// 按候选顺序查找第一个可用字体族，缺失的粗体/斜体变体回退到常规字体
+fn find_font_family(candidates: &[[&str; 4]]) -> Option<FontFamily<FontData>> {
+    candidates.iter().find_map(|[regular, bold, italic, bold_italic]| {
+        let regular = load_single_font(Path::new(regular)).ok()?;
+        let variant = |path: &str| load_single_font(Path::new(path)).unwrap_or(regular.clone());
+        Some(FontFamily {
+            bold: variant(bold),
+            italic: variant(italic),
+            bold_italic: variant(bold_italic),
+            regular,
+        })
+    })
+}
+
+fn code_contains_cjk(content: &str) -> bool {
+    let mut in_code = false;
+    markdown::parser(content).any(|event| match event {
+        Event::Start(Tag::CodeBlock(_)) => {
+            in_code = true;
+            false
+        }
+        Event::End(TagEnd::CodeBlock) => {
+            in_code = false;
+            false
+        }
+        Event::Code(text) => markdown::contains_cjk(&text),
+        Event::Text(text) => in_code && markdown::contains_cjk(&text),
+        _ => false,
+    })
+}
+
+fn build_document(
+    content: &str,
+    title: &str,
+    options: &PdfExportOptions,
+    fonts: &LoadedFonts,
+    total_pages: Option<usize>,
+    page_counter: Rc<Cell<usize>>,
+) -> Result<genpdf::Document, String> {
+    let mut doc = genpdf::Document::new(fonts.body.clone());
+    let mono = doc.add_font_family(fonts.mono.clone());
+    doc.set_title(title);
+    let (paper_width, paper_height) = options.paper_size();
+    doc.set_paper_size(Size::new(paper_width, paper_height));
+    doc.set_font_size(options.font_size());
+    doc.set_line_spacing(1.3);
+
+    let margins = options.margins();
+    let header = if options.show_header.unwrap_or(true) {
+        Some(options.header_text.clone().unwrap_or_else(|| title.to_string()))
+    } else {
+        None
+    };
+    doc.set_page_decorator(PageFrame {
+        margins: Margins::trbl(margins.top, margins.right, margins.bottom, margins.left),
+        header,
+        footer: options.footer_text.clone(),
+        show_page_numbers: options.show_page_numbers.unwrap_or(true),
+        total_pages,
+        page: page_counter,
+    });
+
+    let content_width = paper_width - margins.left - margins.right;
+    let base_dir = options.base_dir.as_ref().map(PathBuf::from);
+
+    let colors = options.colors();
+    let mut builder = PdfBuilder::new(options.font_size(), mono, content_width, base_dir, colors);
+    for event in markdown::parser(content) {
+        builder.handle(event);
+    }
+    doc.push(builder.finish().styled(Style::new().with_color(colors.text)));
+
+    Ok(doc)
+}
+
+/// 页面装饰：页边距、页眉（文件名）和页脚（页码）
+struct PageFrame {
+    margins: Margins,
+    header: Option<String>,
+    footer: Option<String>,
+    show_page_numbers: bool,
+    total_pages: Option<usize>,
+    page: Rc<Cell<usize>>,
+}
+
+impl genpdf::PageDecorator for PageFrame {
+    fn decorate_page<'a>(
+        &mut self,
+        context: &Context,
+        mut area: render::Area<'a>,
+        style: Style,
+    ) -> Result<render::Area<'a>, genpdf::error::Error> {
+        let page = self.page.get() + 1;
+        self.page.set(page);
+        area.add_margins(self.margins);
+
+        let small = style.with_font_size(8).with_color(Color::Greyscale(110));
+        let width = area.size().width;
+
+        if let Some(header) = &self.header {
+            let mut text = Paragraph::new(header.as_str());
+            let result = text.render(context, area.clone(), small)?;
+            let line_y = result.size.height + Mm::from(1.0);
+            area.draw_line(
+                vec![Position::new(0, line_y), Position::new(width, line_y)],
+                small,
+            );
+            area.add_offset(Position::new(0, line_y + Mm::from(DECORATION_GAP_MM)));
+        }
+
+        let page_label = if self.show_page_numbers {
+            Some(match self.total_pages {
+                Some(total) => format!("{} / {}", page, total),
+                None => page.to_string(),
+            })
+        } else {
+            None
+        };
+
+        if self.footer.is_some() || page_label.is_some() {
+            let line_height = small.line_height(&context.font_cache);
+            let mut footer_area = area.clone();
+            footer_area.add_offset(Position::new(0, area.size().height - line_height));
+            let columns = footer_area.split_horizontally(&[1, 1]);
+            if let Some(footer) = &self.footer {
+                Paragraph::new(footer.as_str()).render(context, columns[0].clone(), small)?;
+            }
+            if let Some(label) = page_label {
+                Paragraph::new(label)
+                    .aligned(Alignment::Right)
+                    .render(context, columns[1].clone(), small)?;
+            }
+            let height = area.size().height - line_height - Mm::from(DECORATION_GAP_MM);
+            area.set_height(height);
+        }
+
+        Ok(area)
+    }
+}
+
+/// 水平分割线
+struct HorizontalRule;
+
+impl Element for HorizontalRule {
+    fn render(
+        &mut self,
+        _context: &Context,
+        area: render::Area<'_>,
+        style: Style,
+    ) -> Result<RenderResult, genpdf::error::Error> {
+        let width = area.size().width;
+        area.draw_line(
+            vec![Position::new(0, 2), Position::new(width, 2)],
+            style.with_color(Color::Greyscale(200)),
+        );
+        Ok(RenderResult {
+            size: Size::new(width, 4),
+            has_more: false,
+        })
+    }
+}
+
+/// 块级容器类型
+enum ContainerKind {
+    Root,
+    BlockQuote,
+    List(Option<u64>),
+    Item,
+    FootnoteDefinition(String),
+}
+
+struct Container {
+    kind: ContainerKind,
+    layout: LinearLayout,
+    /// 仅列表使用：已完成的列表项
+    items: Vec<LinearLayout>,
+}
+
+impl Container {
+    fn new(kind: ContainerKind) -> Self {
+        Container {
+            kind,
+            layout: LinearLayout::vertical(),
+            items: Vec::new(),
+        }
+    }
+}
+
+/// 表格收集状态
+#[derive(Default)]
+struct TableState {
+    rows: Vec<Vec<Paragraph>>,
+    row: Vec<Paragraph>,
+    in_head: bool,
+}
+
+/// 将 pulldown-cmark 事件流转换为 genpdf 元素树
+struct PdfBuilder {
+    font_size: u8,
+    mono: FontFamily<genpdf::fonts::Font>,
+    content_width: f64,
+    base_dir: Option<PathBuf>,
+    colors: PdfColors,
+    stack: Vec<Container>,
+    paragraph: Option<Paragraph>,
+    paragraph_style: Style,
+    bold: usize,
+    italic: usize,
+    link: usize,
+    /// 代码块的语言和内容
+    code_block: Option<(String, String)>,
+    table: Option<TableState>,
+    image: Option<(String, String)>,
+}
+
+impl PdfBuilder {
+    fn new(
+        font_size: u8,
+        mono: FontFamily<genpdf::fonts::Font>,
+        content_width: f64,
+        base_dir: Option<PathBuf>,
+        colors: PdfColors,
+    ) -> Self {
+        PdfBuilder {
+            font_size,
+            mono,
+            content_width,
+            base_dir,
+            colors,
+            stack: vec![Container::new(ContainerKind::Root)],
+            paragraph: None,
+            paragraph_style: Style::new(),
+            bold: 0,
+            italic: 0,
+            link: 0,
+            code_block: None,
+            table: None,
+            image: None,
+        }
+    }
+
+    fn finish(mut self) -> LinearLayout {
+        self.flush_paragraph();
+        while self.stack.len() > 1 {
+            self.close_container();
+        }
+        self.stack.pop().map(|c| c.layout).unwrap_or_else(LinearLayout::vertical)
+    }
+
+    fn handle(&mut self, event: Event<'_>) {
+        if let Some((_, code)) = self.code_block.as_mut() {
+            match event {
+                Event::Text(text) => code.push_str(&text),
+                Event::End(TagEnd::CodeBlock) => self.finish_code_block(),
+                _ => {}
+            }
+            return;
+        }
+
+        if let Some((_, alt)) = self.image.as_mut() {
+            match event {
+                Event::Text(text) | Event::Code(text) => alt.push_str(&text),
+                Event::End(TagEnd::Image) => self.finish_image(),
+                _ => {}
+            }
+            return;
+        }
+
+        match event {
+            Event::Start(tag) => self.start_tag(tag),
+            Event::End(tag) => self.end_tag(tag),
+            Event::Text(text) => self.push_text(&text, self.inline_style()),
+            Event::Code(text) => {
+                let style = self.inline_style().with_font_family(self.mono);
+                self.push_text(&text, style.with_color(self.colors.code));
+            }
+            Event::InlineMath(tex) => {
+                let style = self.inline_style().italic();
+                self.push_text(&math::convert_tex(&tex), style);
+            }
+            Event::DisplayMath(tex) => self.push_display_math(&tex),
+            Event::SoftBreak => self.push_text(" ", self.inline_style()),
+            Event::HardBreak => self.flush_paragraph(),
+            Event::Rule => {
+                self.flush_paragraph();
+                self.push_block(HorizontalRule);
+            }
+            Event::TaskListMarker(checked) => {
+                let marker = if checked { "[x] " } else { "[ ] " };
+                let style = self.inline_style().with_font_family(self.mono);
+                self.push_text(marker, style);
+            }
+            Event::FootnoteReference(label) => {
+                let style = self.inline_style().with_color(self.colors.link);
+                self.push_text(&format!("[{}]", label), style);
+            }
+            Event::Html(html) if super::print::is_page_break(&html) => {
+                self.flush_paragraph();
+                self.push_block(PageBreak::new());
+            }
+            Event::Html(_) | Event::InlineHtml(_) => {}
+        }
+    }
+
+    fn start_tag(&mut self, tag: Tag<'_>) {
+        match tag {
+            Tag::Paragraph => self.flush_paragraph(),
+            Tag::Heading { level, .. } => {
+                self.flush_paragraph();
+                let scale = match level {
+                    HeadingLevel::H1 => 2.0,
+                    HeadingLevel::H2 => 1.6,
+                    HeadingLevel::H3 => 1.35,
+                    HeadingLevel::H4 => 1.15,
+                    _ => 1.0,
+                };
+                let size = (f64::from(self.font_size) * scale).round() as u8;
+                self.push_block(Break::new(0.6));
+                self.paragraph_style = Style::new()
+                    .bold()
+                    .with_font_size(size)
+                    .with_color(self.colors.heading);
+            }
+            Tag::BlockQuote(_) => {
+                self.flush_paragraph();
+                self.stack.push(Container::new(ContainerKind::BlockQuote));
+            }
+            Tag::CodeBlock(kind) => {
+                self.flush_paragraph();
+                let lang = match kind {
+                    CodeBlockKind::Fenced(info) => info.to_string(),
+                    CodeBlockKind::Indented => String::new(),
+                };
+                self.code_block = Some((lang, String::new()));
+            }
+            Tag::List(start) => {
+                self.flush_paragraph();
+                self.stack.push(Container::new(ContainerKind::List(start)));
+            }
+            Tag::Item => {
+                self.flush_paragraph();
+                self.stack.push(Container::new(ContainerKind::Item));
+            }
+            Tag::FootnoteDefinition(label) => {
+                self.flush_paragraph();
+                self.stack
+                    .push(Container::new(ContainerKind::FootnoteDefinition(label.to_string())));
+            }
+            Tag::Table(_) => {
+                self.flush_paragraph();
+                self.table = Some(TableState::default());
+            }
+            Tag::TableHead => {
+                if let Some(table) = self.table.as_mut() {
+                    table.in_head = true;
+                }
+            }
+            Tag::TableRow => {}
+            Tag::TableCell => {
+                self.paragraph = Some(Paragraph::new(""));
+                if self.table.as_ref().is_some_and(|t| t.in_head) {
+                    self.paragraph_style = Style::new().bold();
+                }
+            }
+            Tag::Emphasis => self.italic += 1,
+            Tag::Strong => self.bold += 1,
+            Tag::Link { .. } => self.link += 1,
+            Tag::Image { dest_url, .. } => {
+                self.image = Some((dest_url.to_string(), String::new()));
+            }
+            _ => {}
+        }
+    }
+
+    fn end_tag(&mut self, tag: TagEnd) {
+        match tag {
+            TagEnd::Paragraph => {
+                self.flush_paragraph();
+                if matches!(self.top_kind(), ContainerKind::Root | ContainerKind::BlockQuote) {
+                    self.push_block(Break::new(0.5));
+                }
+            }
+            TagEnd::Heading(_) => {
+                self.flush_paragraph();
+                self.push_block(Break::new(0.3));
+                self.paragraph_style = Style::new();
+            }
+            TagEnd::BlockQuote(_)
+            | TagEnd::List(_)
+            | TagEnd::Item
+            | TagEnd::FootnoteDefinition => {
+                self.flush_paragraph();
+                self.close_container();
+            }
+            TagEnd::TableCell => {
+                let cell = self.paragraph.take().unwrap_or_else(|| Paragraph::new(""));
+                if let Some(table) = self.table.as_mut() {
+                    table.row.push(cell);
+                }
+                self.paragraph_style = Style::new();
+            }
+            TagEnd::TableHead | TagEnd::TableRow => {
+                if let Some(table) = self.table.as_mut() {
+                    let row = std::mem::take(&mut table.row);
+                    table.rows.push(row);
+                    table.in_head = false;
+                }
+            }
+            TagEnd::Table => self.finish_table(),
+            TagEnd::Emphasis => self.italic = self.italic.saturating_sub(1),
+            TagEnd::Strong => self.bold = self.bold.saturating_sub(1),
+            TagEnd::Link => self.link = self.link.saturating_sub(1),
+            _ => {}
+        }
+    }
+
+    fn top_kind(&self) -> &ContainerKind {
+        self.stack
+            .last()
+            .map(|c| &c.kind)
+            .unwrap_or(&ContainerKind::Root)
+    }
+
+    fn inline_style(&self) -> Style {
+        let mut style = self.paragraph_style;
+        if self.bold > 0 {
+            style.set_bold();
+        }
+        if self.italic > 0 {
+            style.set_italic();
+        }
+        if self.link > 0 {
+            style.set_color(self.colors.link);
+        }
+        style
+    }
+
+    /// 追加行内文本
+    ///
+    /// genpdf 只在空格处换行，连续的 CJK 字符逐字推入，使其可以在任意字间断行。
+    fn push_text(&mut self, text: &str, style: Style) {
+        let paragraph = self.paragraph.get_or_insert_with(|| Paragraph::new(""));
+        let mut run = String::new();
+        for c in text.chars() {
+            if markdown::is_cjk_char(c) {
+                if !run.is_empty() {
+                    paragraph.push_styled(std::mem::take(&mut run), style);
+                }
+                paragraph.push_styled(c.to_string(), style);
+            } else {
+                run.push(c);
+            }
+        }
+        if !run.is_empty() {
+            paragraph.push_styled(run, style);
+        }
+    }
+
+    fn flush_paragraph(&mut self) {
+        // 表格单元格中的段落由 TableCell 结束事件收集
+        if self.table.is_some() {
+            return;
+        }
+        if let Some(paragraph) = self.paragraph.take() {
+            self.push_block(paragraph);
+        }
+    }
+
+    fn push_block<E: Element + 'static>(&mut self, element: E) {
+        if let Some(container) = self.stack.last_mut() {
+            container.layout.push(element);
+        }
+    }
+
+    fn close_container(&mut self) {
+        let Some(container) = self.stack.pop() else {
+            return;
+        };
+        match container.kind {
+            ContainerKind::Root => self.stack.push(container),
+            ContainerKind::Item => {
+                if let Some(parent) = self.stack.last_mut() {
+                    parent.items.push(container.layout);
+                }
+            }
+            ContainerKind::List(start) => {
+                match start {
+                    Some(start) => {
+                        let mut list = OrderedList::with_start(start as usize);
+                        for item in container.items {
+                            list.push(item);
+                        }
+                        self.push_block(list);
+                    }
+                    None => {
+                        let mut list = UnorderedList::new();
+                        for item in container.items {
+                            list.push(item);
+                        }
+                        self.push_block(list);
+                    }
+                }
+                if matches!(self.top_kind(), ContainerKind::Root) {
+                    self.push_block(Break::new(0.5));
+                }
+            }
+            ContainerKind::BlockQuote => {
+                let quote = PaddedElement::new(container.layout, Margins::trbl(0, 0, 0, 6))
+                    .styled(Style::new().with_color(self.colors.muted));
+                self.push_block(quote);
+            }
+            ContainerKind::FootnoteDefinition(label) => {
+                let mut layout = LinearLayout::vertical();
+                layout.push(Paragraph::new(format!("[{}]", label)));
+                layout.push(container.layout);
+                let size = self.font_size.saturating_sub(2).max(6);
+                self.push_block(layout.styled(Style::new().with_font_size(size)));
+            }
+        }
+    }
+
+    fn finish_code_block(&mut self) {
+        let Some((lang, code)) = self.code_block.take() else {
+            return;
+        };
+        let lang_token = lang.split_whitespace().next().unwrap_or("");
+        if let Some(kind) = DiagramKind::from_lang(lang_token) {
+            match self.diagram_image(kind, &code) {
+                Ok(image) => {
+                    self.push_block(image);
+                    self.push_block(Break::new(0.5));
+                    return;
+                }
+                Err(e) => log::warn!("[export_pdf] Diagram not rendered: {}", e),
+            }
+        }
+        let style = Style::new()
+            .with_font_family(self.mono)
+            .with_font_size(self.font_size.saturating_sub(1).max(6));
+        let mut layout = LinearLayout::vertical();
+        for line in code.trim_end_matches('\n').lines() {
+            let mut paragraph = Paragraph::new("");
+            if line.is_empty() {
+                paragraph.push_styled(" ", style);
+            } else {
+                paragraph.push_styled(line.replace('\t', "    "), style);
+            }
+            layout.push(paragraph);
+        }
+        self.push_block(layout.padded(2).framed());
+        self.push_block(Break::new(0.5));
+    }
+
+    fn finish_table(&mut self) {
+        let Some(table) = self.table.take() else {
+            return;
+        };
+        let columns = table.rows.iter().map(Vec::len).max().unwrap_or(0);
+        if columns == 0 {
+            return;
+        }
+        let mut layout = TableLayout::new(vec![1; columns]);
+        layout.set_cell_decorator(FrameCellDecorator::new(true, true, false));
+        for row in table.rows {
+            let mut cells: Vec<Box<dyn Element>> = row
+                .into_iter()
+                .map(|cell| Box::new(cell.padded(1)) as Box<dyn Element>)
+                .collect();
+            while cells.len() < columns {
+                cells.push(Box::new(Paragraph::new("")));
+            }
+            if let Err(e) = layout.push_row(cells) {
+                log::warn!("[export_pdf] Skipping malformed table row: {}", e);
+            }
+        }
+        self.push_block(layout);
+        self.push_block(Break::new(0.5));
+    }
+
+    fn finish_image(&mut self) {
+        let Some((url, alt)) = self.image.take() else {
+            return;
+        };
+        match self.load_image(&url) {
+            Ok(image) => {
+                let paragraph = self.paragraph.take();
+                if let Some(paragraph) = paragraph {
+                    self.push_block(paragraph);
+                }
+                self.push_block(image);
+            }
+            Err(e) => {
+                log::warn!("[export_pdf] Image not embedded ({}): {}", url, e);
+                let label = if alt.is_empty() { url } else { alt };
+                let style = self.inline_style().italic().with_color(Color::Greyscale(120));
+                self.push_text(&format!("[{}]", label), style);
+            }
+        }
+    }
+
+    /// 块级公式：优先使用 MathJax 生成的 SVG，不可用时居中显示 Unicode 文本
+    fn push_display_math(&mut self, tex: &str) {
+        let style = self.inline_style().italic();
+        // 表格单元格中只能放行内内容
+        if self.table.is_some() {
+            self.push_text(&math::convert_tex(tex), style);
+            return;
+        }
+        self.flush_paragraph();
+        match self.math_image(tex) {
+            Ok(image) => self.push_block(image),
+            Err(e) => {
+                log::debug!("[export_pdf] Formula not rendered as SVG: {}", e);
+                let mut paragraph = Paragraph::new("").aligned(Alignment::Center);
+                paragraph.push_styled(math::convert_tex(tex), style);
+                self.push_block(paragraph);
+            }
+        }
+    }
+
+    /// MathJax 的 SVG 以 `ex` 为单位，按正文字号换算（usvg 中 1ex = 6px）
+    fn math_image(&self, tex: &str) -> Result<genpdf::elements::Image, String> {
+        let svg = math::to_svg(tex, true)?;
+        let ex_px = f64::from(self.font_size) * 96.0 / 72.0 / 2.0;
+        let scale = ex_px / 6.0 * IMAGE_DPI / 96.0;
+        let max_width_px = (self.content_width / 25.4 * IMAGE_DPI) as u32;
+        let image = diagram::rasterize_svg(&svg, max_width_px, scale as f32)?;
+        let element =
+            genpdf::elements::Image::from_dynamic_image(image::DynamicImage::ImageRgb8(image))
+                .map_err(|e| e.to_string())?
+                .with_dpi(IMAGE_DPI)
+                .with_alignment(Alignment::Center);
+        Ok(element)
+    }
+
+    /// 渲染图表并按图片 DPI 栅格化（SVG 以 96 DPI 为基准）
+    fn diagram_image(
+        &self,
+        kind: DiagramKind,
+        code: &str,
+    ) -> Result<genpdf::elements::Image, String> {
+        let svg = diagram::render_svg(kind, code)?;
+        let max_width_px = (self.content_width / 25.4 * IMAGE_DPI) as u32;
+        let image = diagram::rasterize_svg(&svg, max_width_px, (IMAGE_DPI / 96.0) as f32)?;
+        let element =
+            genpdf::elements::Image::from_dynamic_image(image::DynamicImage::ImageRgb8(image))
+                .map_err(|e| e.to_string())?
+                .with_dpi(IMAGE_DPI)
+                .with_alignment(Alignment::Center);
+        Ok(element)
+    }
+
+    fn load_image(&self, url: &str) -> Result<genpdf::elements::Image, String> {
+        if url.starts_with("http://") || url.starts_with("https://") || url.starts_with("data:") {
+            return Err("remote images are not supported".to_string());
+        }
+        let path = crate::render::resolve_local_path(url, self.base_dir.as_deref());
+
+        // genpdf 不支持 alpha 通道，统一转换为 RGB；
+        // 图片以未压缩形式嵌入，超出版心宽度的图片先按目标 DPI 缩小，避免 PDF 体积膨胀
+        let mut image = image::open(&path).map_err(|e| format!("{:?}: {}", path, e))?;
+        let max_width_px = (self.content_width / 25.4 * IMAGE_DPI) as u32;
+        if image.width() > max_width_px && max_width_px > 0 {
+            let height = (f64::from(image.height()) * f64::from(max_width_px)
+                / f64::from(image.width()))
+            .max(1.0) as u32;
+            image = image.resize_exact(max_width_px, height, image::imageops::FilterType::Triangle);
+        }
+        let rgb = image::DynamicImage::ImageRgb8(image.to_rgb8());
+        let element = genpdf::elements::Image::from_dynamic_image(rgb)
+            .map_err(|e| e.to_string())?
+            .with_dpi(IMAGE_DPI)
+            .with_alignment(Alignment::Center);
+        Ok(element)
+    }
+}
@@ -0,0 +1,232 @@
+//! 打印
+//!
+//! 直接打印编辑器界面会带上工具栏、侧边栏，并且滚动容器会截断内容。这里在后端生成专门用于打印的
+//! HTML：固定使用浅色主题，预渲染公式和图表，图片内嵌，并加上避免在标题后、代码块和表格中间分页的
+//! 样式；然后在单独的预览窗口中加载它并调用系统打印对话框。
+//!
+//! 文档中单独一行的 `<!-- pagebreak -->`、`\pagebreak` 或 `\newpage` 会强制分页。
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+
+use serde::Deserialize;
+use tauri::webview::PageLoadEvent;
+use tauri::{AppHandle, Manager, Url, WebviewUrl, WebviewWindowBuilder};
+
+use super::html::first_heading;
+use super::theme::ExportTheme;
+use super::ExportResult;
+use crate::access::{self, AccessKind};
+use crate::error::VividError;
+use crate::{bibliography, markdown, render};
+
+/// 打印预览窗口的标签
+const PRINT_WINDOW_LABEL: &str = "print-preview";
+
+/// 打印参数
+#[derive(Debug, Default, Deserialize)]
+pub struct PrintOptions {
+    /// 编辑器中尚未保存的内容；为空时从 `path` 读取
+    pub content: Option<String>,
+    /// 纸张大小：`A4`、`Letter` 等，默认由打印对话框决定
+    pub page_size: Option<String>,
+    /// 页边距（毫米），默认 15
+    pub margin_mm: Option<f32>,
+    #[serde(default)]
+    pub landscape: bool,
+    /// 在外部链接后打印网址，默认是
+    pub show_link_urls: Option<bool>,
+}
+
+/// 单独一行的分页标记
+pub fn is_page_break(line: &str) -> bool {
+    matches!(
+        line.trim(),
+        "<!-- pagebreak -->" | "<!--pagebreak-->" | "\\pagebreak" | "\\newpage"
+    )
+}
+
+/// 按分页标记拆分文档，代码块中的标记不算
+fn split_pages(content: &str) -> Vec<String> {
+    let mut pages = vec![String::new()];
+    let mut fence: Option<&str> = None;
+    for line in content.split_inclusive('\n') {
+        let trimmed = line.trim_start();
+        match fence {
+            Some(marker) if trimmed.starts_with(marker) => fence = None,
+            Some(_) => {}
+            None if trimmed.starts_with("```") => fence = Some("```"),
+            None if trimmed.starts_with("~~~") => fence = Some("~~~"),
+            None if is_page_break(line) => {
+                pages.push(String::new());
+                continue;
+            }
+            None => {}
+        }
+        if let Some(page) = pages.last_mut() {
+            page.push_str(line);
+        }
+    }
+    pages
+}
+
+fn print_css(options: &PrintOptions) -> String {
+    let size = match (options.page_size.as_deref(), options.landscape) {
+        (Some(size), true) => format!("{} landscape", size),
+        (Some(size), false) => size.to_string(),
+        (None, true) => "landscape".to_string(),
+        (None, false) => "auto".to_string(),
+    };
+    let link_urls = if options.show_link_urls.unwrap_or(true) {
+        r#"a[href^="http"]::after { content: " (" attr(href) ")"; font-size: 0.85em; color: #57606a; word-break: break-all; }"#
+    } else {
+        ""
+    };
+    format!(
+        r#"
+@page {{ size: {}; margin: {}mm; }}
+.page-break {{ break-after: page; page-break-after: always; }}
+@media print {{
+    html, body {{ background: #fff !important; }}
+    .markdown-body {{ max-width: none; margin: 0; padding: 0; }}
+    h1, h2, h3, h4, h5, h6 {{ break-after: avoid; page-break-after: avoid; }}
+    pre, blockquote, table, figure, img, svg, math {{ break-inside: avoid; page-break-inside: avoid; }}
+    tr {{ break-inside: avoid; }}
+    thead {{ display: table-header-group; }}
+    img, svg {{ max-width: 100% !important; height: auto; }}
+    pre {{ white-space: pre-wrap; word-wrap: break-word; }}
+    p, li {{ orphans: 3; widows: 3; }}
+    {}
+}}
+"#,
+        size,
+        options.margin_mm.unwrap_or(15.0).max(0.0),
+        link_urls
+    )
+}
+
+/// 生成打印用 HTML
+pub fn build_print_html(
+    content: &str,
+    title: &str,
+    base_dir: Option<&Path>,
+    options: &PrintOptions,
+) -> String {
+    // 打印总是使用浅色主题，深色背景在纸上既费墨又难以阅读
+    let theme = ExportTheme::builtin(None);
+    let render_options = render::RenderOptions {
+        highlight_theme: Some(theme.highlight_theme().to_string()),
+        render_math: true,
+        embed_images: true,
+        base_dir: base_dir.map(Path::to_path_buf),
+        hard_breaks: true,
+        render_diagrams: true,
+    };
+    let body = split_pages(content)
+        .iter()
+        .map(|page| render::markdown_to_html(page, &render_options))
+        .collect::<Vec<_>>()
+        .join("<div class=\"page-break\"></div>\n");
+
+    format!(
+        r#"<!DOCTYPE html>
+<html>
+<head>
+    <meta charset="UTF-8">
+    <meta name="generator" content="VividMark">
+    <title>{}</title>
+    <style>
+{}
+{}
+    </style>
+</head>
+<body>
+<article class="markdown-body">
+{}
+</article>
+</body>
+</html>
+"#,
+        markdown::escape_html(title),
+        theme.css(),
+        print_css(options),
+        body
+    )
+}
+
+// 生成打印用 HTML 并在预览窗口中打开系统打印对话框
+#[tauri::command]
+pub async fn print_document(
+    app: AppHandle,
+    path: String,
+    options: Option<PrintOptions>,
+) -> Result<ExportResult, VividError> {
+    let start = Instant::now();
+    let source = PathBuf::from(&path);
+    let mut options = options.unwrap_or_default();
+    log::info!("[print_document] Preparing {}", path);
+
+    if options.content.is_none() {
+        access::ensure_access(&app, &source, AccessKind::Read)?;
+    }
+    let content = match options.content.take() {
+        Some(content) => content,
+        None => fs::read_to_string(&source).map_err(|e| {
+            log::error!("[print_document] Failed to read source: {}", e);
+            format!("Failed to read file: {}", e)
+        })?,
+    };
+    let title = first_heading(&content).unwrap_or_else(|| crate::file_name_of(&source));
+    let base_dir = source.parent().map(Path::to_path_buf);
+
+    let window_title = title.clone();
+    let handle = app.clone();
+    let html = tauri::async_runtime::spawn_blocking(move || {
+        let content = bibliography::resolve_citations(&handle, content);
+        build_print_html(&content, &title, base_dir.as_deref(), &options)
+    })
+    .await
+    .map_err(|e| format!("Print task failed: {}", e))?;
+
+    // 页面通过 file:// 加载，内嵌的图片和样式不依赖应用资源
+    let output = app
+        .path()
+        .app_cache_dir()
+        .map_err(|e| format!("Failed to resolve cache directory: {}", e))?
+        .join("print.html");
+    if let Some(parent) = output.parent() {
+        fs::create_dir_all(parent).map_err(|e| format!("Failed to create directory: {}", e))?;
+    }
+    fs::write(&output, &html).map_err(|e| format!("Failed to write file: {}", e))?;
+    let url =
+        Url::from_file_path(&output).map_err(|_| format!("Invalid path: {}", output.display()))?;
+
+    // 同时只保留一个预览窗口
+    if let Some(window) = app.get_webview_window(PRINT_WINDOW_LABEL) {
+        let _ = window.destroy();
+    }
+    WebviewWindowBuilder::new(&app, PRINT_WINDOW_LABEL, WebviewUrl::External(url))
+        .title(format!("{} - Print", window_title))
+        .inner_size(900.0, 1000.0)
+        .on_page_load(|webview, payload| {
+            if payload.event() == PageLoadEvent::Finished {
+                if let Err(e) = webview.print() {
+                    log::error!("[print_document] Failed to open print dialog: {}", e);
+                }
+            }
+        })
+        .build()
+        .map_err(|e| {
+            log::error!("[print_document] {}", e);
+            format!("Failed to create window: {}", e)
+        })?;
+
+    log::info!(
+        "[print_document] ✓ Success: {} ({} bytes) in {:?}",
+        path,
+        html.len(),
+        start.elapsed()
+    );
+    Ok(ExportResult::succeeded(&output))
+}
@@ -0,0 +1,684 @@
+//! 发布到静态站点 / GitHub Pages
+//!
+//! 把单篇笔记或整个文件夹渲染为带主题的 HTML 页面，front matter 中的 `title`、`date`、
+//! `description`、`tags`、`slug`、`draft` 映射为页面元数据。站点根目录下的
+//! `.vividmark-pages.json` 记录已发布的页面（不推送到 GitHub），每次发布后据此重新生成 `index.html`；
+//! 笔记之间的相对链接改写为页面地址，本地图片复制到 `assets/`。
+//!
+//! GitHub Pages 目标在应用数据目录中维护目标分支的工作副本，写入后提交并推送。
+//! 推送使用系统的 Git 凭据；钥匙串中存有 `publish:<目标名称>` 时作为访问令牌使用。
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use std::time::Instant;
+
+use base64::Engine;
+use regex::{Captures, Regex};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tauri::{AppHandle, Manager};
+
+use super::html::first_heading;
+use super::theme::{ExportTheme, ThemeStore};
+use crate::access::{self, AccessKind};
+use crate::assets::content_hash;
+use crate::error::VividError;
+use crate::git::{run_git, run_git_with_env};
+use crate::render::{self, RenderOptions};
+use crate::settings::{PublishDestination, PublishTarget, SettingsStore};
+use crate::{frontmatter, markdown, secrets, storage, workspace};
+
+/// 已发布页面清单
+const MANIFEST_FILE: &str = ".vividmark-pages.json";
+/// 复制本地图片的目录
+const ASSETS_DIR: &str = "assets";
+
+/// 页面头部与索引页的补充样式
+const PAGE_CSS: &str = r#"
+.page-meta { color: #6a737d; font-size: 0.9em; margin-bottom: 2em; }
+.page-meta a { margin-right: 0.5em; }
+.page-tags span { margin-right: 0.5em; }
+.page-list { list-style: none; padding-left: 0; }
+.page-list li { margin-bottom: 1em; }
+.page-list time { color: #6a737d; font-size: 0.9em; margin-left: 0.5em; }
+.page-list p { margin: 0.25em 0 0; }
+"#;
+
+/// 已发布页面的元数据
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct PageMeta {
+    /// 源文件路径
+    source: String,
+    /// 页面相对站点根目录的地址
+    url: String,
+    title: String,
+    date: Option<String>,
+    description: Option<String>,
+    tags: Vec<String>,
+}
+
+/// 待发布的页面
+struct Page {
+    meta: PageMeta,
+    source_dir: PathBuf,
+    body: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PublishResult {
+    pub target: String,
+    /// 站点输出目录（GitHub Pages 目标为本地工作副本）
+    pub output: String,
+    /// 本次发布的页面地址（相对站点根目录）
+    pub pages: Vec<String>,
+    /// 因 `draft: true` 跳过的源文件
+    pub skipped: Vec<String>,
+    /// GitHub Pages 目标的新提交，内容没有变化时为空
+    pub commit: Option<String>,
+}
+
+/// front matter 中的字符串字段，按顺序取第一个非空值
+fn meta_string(data: &Value, keys: &[&str]) -> Option<String> {
+    keys.iter().find_map(|key| match data.get(*key)? {
+        Value::String(s) if !s.trim().is_empty() => Some(s.trim().to_string()),
+        Value::Number(n) => Some(n.to_string()),
+        _ => None,
+    })
+}
+
+/// `tags` 字段，支持数组和逗号分隔的字符串
+fn meta_tags(data: &Value) -> Vec<String> {
+    match data.get("tags") {
+        Some(Value::Array(items)) => items
+            .iter()
+            .filter_map(|v| v.as_str())
+            .map(|s| s.trim().to_string())
+            .filter(|s| !s.is_empty())
+            .collect(),
+        Some(Value::String(s)) => s
+            .split(',')
+            .map(|t| t.trim().to_string())
+            .filter(|t| !t.is_empty())
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+fn is_draft(data: &Value) -> bool {
+    data.get("draft").and_then(Value::as_bool) == Some(true)
+        || data.get("published").and_then(Value::as_bool) == Some(false)
+}
+
+/// 读取笔记并生成页面元数据，草稿返回 `None`
+fn load_page(path: &Path, rel_dir: &str) -> Result<Option<Page>, String> {
+    let content = fs::read_to_string(path).map_err(|e| format!("Failed to read file: {}", e))?;
+    let (data, body) = match frontmatter::find_front_matter(&content) {
+        Some(block) => (
+            frontmatter::parse_block(&block)?,
+            content[block.body_start..].to_string(),
+        ),
+        None => (Value::Null, content.clone()),
+    };
+    if is_draft(&data) {
+        return Ok(None);
+    }
+
+    let stem = path
+        .file_stem()
+        .and_then(|n| n.to_str())
+        .unwrap_or("untitled")
+        .to_string();
+    let slug = meta_string(&data, &["slug"])
+        .map(|s| markdown::slugify(&s))
+        .filter(|s| !s.is_empty())
+        .unwrap_or_else(|| {
+            let slug = markdown::slugify(&stem);
+            if slug.is_empty() {
+                stem.clone()
+            } else {
+                slug
+            }
+        });
+    let url = if rel_dir.is_empty() {
+        format!("{}.html", slug)
+    } else {
+        format!("{}/{}.html", rel_dir, slug)
+    };
+    let title = meta_string(&data, &["title"])
+        .or_else(|| first_heading(&body))
+        .unwrap_or(stem);
+
+    Ok(Some(Page {
+        meta: PageMeta {
+            source: path.to_string_lossy().to_string(),
+            url,
+            title,
+            date: meta_string(&data, &["date"]),
+            description: meta_string(&data, &["description", "summary", "excerpt"]),
+            tags: meta_tags(&data),
+        },
+        source_dir: path.parent().map(Path::to_path_buf).unwrap_or_default(),
+        body,
+    }))
+}
+
+/// 从一个页面指向另一个站内地址的相对链接
+fn relative_url(from_page: &str, to: &str) -> String {
+    format!("{}{}", "../".repeat(from_page.matches('/').count()), to)
+}
+
+fn url_attribute_regex() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| Regex::new(r#"\b(href|src)="([^"]*)""#).expect("valid regex"))
+}
+
+fn is_external(url: &str) -> bool {
+    url.is_empty()
+        || url.starts_with('#')
+        || url.starts_with("data:")
+        || url.contains("://")
+        || url.starts_with("mailto:")
+}
+
+/// 规范化路径用于匹配链接目标，文件不存在时原样返回
+fn canonical(path: &Path) -> PathBuf {
+    path.canonicalize().unwrap_or_else(|_| path.to_path_buf())
+}
+
+/// 复制页面引用的本地图片到 `assets/`，以内容哈希命名避免重名
+fn copy_asset(path: &Path, site: &Path) -> Option<String> {
+    let data = fs::read(path).ok()?;
+    let ext = path
+        .extension()
+        .and_then(|e| e.to_str())
+        .map(|e| format!(".{}", e.to_ascii_lowercase()))
+        .unwrap_or_default();
+    let name = format!("{}/{}{}", ASSETS_DIR, &content_hash(&data)[..16], ext);
+    let target = site.join(&name);
+    if !target.exists() {
+        fs::create_dir_all(site.join(ASSETS_DIR)).ok()?;
+        fs::write(&target, &data).ok()?;
+    }
+    Some(name)
+}
+
+/// 改写页面中的链接：指向已发布笔记的链接改为页面地址，本地图片复制到站点中
+fn rewrite_urls(html: &str, page: &Page, pages: &HashMap<PathBuf, String>, site: &Path) -> String {
+    url_attribute_regex()
+        .replace_all(html, |caps: &Captures<'_>| {
+            let attr = &caps[1];
+            let url = caps[2].replace("&amp;", "&");
+            if is_external(&url) {
+                return caps[0].to_string();
+            }
+            let (path_part, fragment) = match url.find('#') {
+                Some(i) => (&url[..i], &url[i..]),
+                None => (url.as_str(), ""),
+            };
+            let path = render::resolve_local_path(path_part, Some(&page.source_dir));
+            let rewritten = if attr == "href" {
+                pages
+                    .get(&canonical(&path))
+                    .map(|target| format!("{}{}", relative_url(&page.meta.url, target), fragment))
+            } else if path.is_file() {
+                copy_asset(&path, site).map(|name| relative_url(&page.meta.url, &name))
+            } else {
+                None
+            };
+            match rewritten {
+                Some(url) => format!("{}=\"{}\"", attr, markdown::escape_html(&url)),
+                None => caps[0].to_string(),
+            }
+        })
+        .into_owned()
+}
+
+/// 页面外壳：主题样式、元数据和返回索引页的链接
+fn page_shell(
+    title: &str,
+    meta: Option<&PageMeta>,
+    site_title: &str,
+    index_href: Option<&str>,
+    theme: &ExportTheme,
+    body: &str,
+) -> String {
+    let mut head = String::new();
+    let mut header = String::new();
+    if let Some(meta) = meta {
+        if let Some(description) = &meta.description {
+            head.push_str(&format!(
+                "    <meta name=\"description\" content=\"{}\">\n    <meta property=\"og:description\" content=\"{}\">\n",
+                markdown::escape_html(description),
+                markdown::escape_html(description)
+            ));
+        }
+        if !meta.tags.is_empty() {
+            head.push_str(&format!(
+                "    <meta name=\"keywords\" content=\"{}\">\n",
+                markdown::escape_html(&meta.tags.join(", "))
+            ));
+        }
+        head.push_str(&format!(
+            "    <meta property=\"og:title\" content=\"{}\">\n",
+            markdown::escape_html(&meta.title)
+        ));
+
+        header.push_str("<header class=\"page-meta\">");
+        if let Some(href) = index_href {
+            header.push_str(&format!(
+                "<a href=\"{}\">{}</a>",
+                markdown::escape_html(href),
+                markdown::escape_html(site_title)
+            ));
+        }
+        if let Some(date) = &meta.date {
+            header.push_str(&format!(
+                "<time datetime=\"{}\">{}</time>",
+                markdown::escape_html(date),
+                markdown::escape_html(date)
+            ));
+        }
+        if !meta.tags.is_empty() {
+            header.push_str("<div class=\"page-tags\">");
+            for tag in &meta.tags {
+                header.push_str(&format!("<span>#{}</span>", markdown::escape_html(tag)));
+            }
+            header.push_str("</div>");
+        }
+        header.push_str("</header>\n");
+    }
+
+    format!(
+        r#"<!DOCTYPE html>
+<html>
+<head>
+    <meta charset="UTF-8">
+    <meta name="viewport" content="width=device-width, initial-scale=1">
+    <meta name="generator" content="VividMark">
+    <title>{}</title>
+{}    <style>
+{}
+{}
+    </style>
+</head>
+<body>
+<article class="markdown-body">
+{}{}
+</article>
+</body>
+</html>
+"#,
+        markdown::escape_html(title),
+        head,
+        theme.css(),
+        PAGE_CSS,
+        header,
+        body
+    )
+}
+
+/// 索引页：按日期倒序列出所有已发布页面
+fn index_html(manifest: &[PageMeta], site_title: &str, theme: &ExportTheme) -> String {
+    let mut pages: Vec<&PageMeta> = manifest.iter().collect();
+    pages.sort_by(|a, b| b.date.cmp(&a.date).then_with(|| a.title.cmp(&b.title)));
+    let mut body = format!(
+        "<h1>{}</h1>\n<ul class=\"page-list\">\n",
+        markdown::escape_html(site_title)
+    );
+    for page in pages {
+        body.push_str(&format!(
+            "<li><a href=\"{}\">{}</a>",
+            markdown::escape_html(&page.url),
+            markdown::escape_html(&page.title)
+        ));
+        if let Some(date) = &page.date {
+            body.push_str(&format!("<time>{}</time>", markdown::escape_html(date)));
+        }
+        if let Some(description) = &page.description {
+            body.push_str(&format!("<p>{}</p>", markdown::escape_html(description)));
+        }
+        body.push_str("</li>\n");
+    }
+    body.push_str("</ul>");
+    page_shell(site_title, None, site_title, None, theme, &body)
+}
+
+/// 要发布的笔记及其在站点中的子目录
+fn collect_sources(path: &Path) -> Vec<(PathBuf, String)> {
+    if !path.is_dir() {
+        return vec![(path.to_path_buf(), String::new())];
+    }
+    workspace::markdown_files(path)
+        .into_iter()
+        .map(|file| {
+            let rel_dir = file
+                .parent()
+                .and_then(|dir| dir.strip_prefix(path).ok())
+                .map(|dir| {
+                    dir.components()
+                        .map(|c| markdown::slugify(&c.as_os_str().to_string_lossy()))
+                        .filter(|c| !c.is_empty())
+                        .collect::<Vec<_>>()
+                        .join("/")
+                })
+                .unwrap_or_default();
+            (file, rel_dir)
+        })
+        .collect()
+}
+
+/// 渲染页面并写入站点目录，返回本次发布的页面地址和跳过的草稿
+fn build_site(
+    path: &Path,
+    site: &Path,
+    site_title: &str,
+    theme: &ExportTheme,
+) -> Result<(Vec<String>, Vec<String>), String> {
+    fs::create_dir_all(site).map_err(|e| format!("Failed to create directory: {}", e))?;
+    let manifest_path = site.join(MANIFEST_FILE);
+    let previous: Vec<PageMeta> = storage::load_json(&manifest_path);
+
+    let mut pages = Vec::new();
+    let mut skipped = Vec::new();
+    for (file, rel_dir) in collect_sources(path) {
+        match load_page(&file, &rel_dir)? {
+            Some(page) => pages.push(page),
+            None => skipped.push(file.to_string_lossy().to_string()),
+        }
+    }
+    if pages.is_empty() {
+        return Err(if skipped.is_empty() {
+            "No notes to publish".to_string()
+        } else {
+            "All notes are marked as draft".to_string()
+        });
+    }
+
+    // 本次发布的页面覆盖清单中的同名条目；源文件已删除或改为草稿的旧页面一并移除
+    let published: Vec<String> = pages.iter().map(|p| p.meta.source.clone()).collect();
+    let mut manifest: Vec<PageMeta> = Vec::new();
+    for old in previous {
+        let stale = !Path::new(&old.source).exists() || skipped.contains(&old.source);
+        if stale || published.contains(&old.source) {
+            let replaced = pages
+                .iter()
+                .any(|p| p.meta.source == old.source && p.meta.url == old.url);
+            if !replaced {
+                let _ = fs::remove_file(site.join(&old.url));
+            }
+            continue;
+        }
+        manifest.push(old);
+    }
+    manifest.extend(pages.iter().map(|p| p.meta.clone()));
+
+    let links: HashMap<PathBuf, String> = manifest
+        .iter()
+        .map(|m| (canonical(Path::new(&m.source)), m.url.clone()))
+        .collect();
+    let has_index_page = manifest.iter().any(|m| m.url == "index.html");
+
+    for page in &pages {
+        let options = RenderOptions {
+            highlight_theme: Some(theme.highlight_theme().to_string()),
+            render_math: true,
+            embed_images: false,
+            base_dir: Some(page.source_dir.clone()),
+            hard_breaks: true,
+            render_diagrams: true,
+        };
+        let body = render::markdown_to_html(&page.body, &options);
+        let body = rewrite_urls(&body, page, &links, site);
+        let index_href = (!has_index_page).then(|| relative_url(&page.meta.url, "index.html"));
+        let html = page_shell(
+            &page.meta.title,
+            Some(&page.meta),
+            site_title,
+            index_href.as_deref(),
+            theme,
+            &body,
+        );
+        let target = site.join(&page.meta.url);
+        if let Some(parent) = target.parent() {
+            fs::create_dir_all(parent).map_err(|e| format!("Failed to create directory: {}", e))?;
+        }
+        fs::write(&target, html).map_err(|e| format!("Failed to write file: {}", e))?;
+    }
+
+    if !has_index_page {
+        fs::write(
+            site.join("index.html"),
+            index_html(&manifest, site_title, theme),
+        )
+        .map_err(|e| format!("Failed to write file: {}", e))?;
+    }
+    storage::save_json(&manifest_path, &manifest)?;
+    Ok((pages.into_iter().map(|p| p.meta.url).collect(), skipped))
+}
+
+/// 推送令牌对应的 Git 配置环境变量（不出现在命令行参数中）
+fn token_env(token: Option<&str>) -> Vec<(String, String)> {
+    let Some(token) = token else {
+        return Vec::new();
+    };
+    let basic =
+        base64::engine::general_purpose::STANDARD.encode(format!("x-access-token:{}", token));
+    vec![
+        ("GIT_CONFIG_COUNT".to_string(), "1".to_string()),
+        (
+            "GIT_CONFIG_KEY_0".to_string(),
+            "http.extraHeader".to_string(),
+        ),
+        (
+            "GIT_CONFIG_VALUE_0".to_string(),
+            format!("Authorization: Basic {}", basic),
+        ),
+    ]
+}
+
+/// 准备目标分支的工作副本：首次克隆（分支不存在时新建孤立分支），之后同步到远端最新状态
+fn prepare_checkout(
+    dir: &Path,
+    repository: &str,
+    branch: &str,
+    env: &[(&str, &str)],
+) -> Result<(), String> {
+    let parent = dir.parent().unwrap_or(Path::new("."));
+    fs::create_dir_all(parent).map_err(|e| format!("Failed to create directory: {}", e))?;
+    let heads = run_git_with_env(parent, &["ls-remote", "--heads", repository, branch], env)?;
+    let remote_exists = !heads.trim().is_empty();
+
+    if !dir.join(".git").exists() {
+        let dir_arg = dir.to_string_lossy().to_string();
+        if remote_exists {
+            run_git_with_env(
+                parent,
+                &[
+                    "clone",
+                    "--depth",
+                    "1",
+                    "--branch",
+                    branch,
+                    "--single-branch",
+                    repository,
+                    &dir_arg,
+                ],
+                env,
+            )?;
+        } else {
+            fs::create_dir_all(dir).map_err(|e| format!("Failed to create directory: {}", e))?;
+            run_git(dir, &["init"])?;
+            run_git(dir, &["checkout", "--orphan", branch])?;
+            run_git(dir, &["remote", "add", "origin", repository])?;
+        }
+        return Ok(());
+    }
+
+    run_git(dir, &["remote", "set-url", "origin", repository])?;
+    if remote_exists {
+        run_git_with_env(dir, &["fetch", "--depth", "1", "origin", branch], env)?;
+        run_git(dir, &["checkout", "-B", branch, "FETCH_HEAD"])?;
+        run_git(dir, &["reset", "--hard", "FETCH_HEAD"])?;
+    }
+    Ok(())
+}
+
+/// 提交工作副本中的改动并推送，没有改动时返回 `None`
+fn commit_and_push(
+    dir: &Path,
+    branch: &str,
+    message: &str,
+    env: &[(&str, &str)],
+) -> Result<Option<String>, String> {
+    // GitHub Pages 默认用 Jekyll 处理，跳过它以免下划线开头的目录被忽略
+    let nojekyll = dir.join(".nojekyll");
+    if !nojekyll.exists() {
+        fs::write(&nojekyll, "").map_err(|e| format!("Failed to write file: {}", e))?;
+    }
+    // 清单中含有本机源文件路径，只保留在工作副本中，不推送到公开仓库
+    let gitignore = dir.join(".gitignore");
+    if !gitignore.exists() {
+        fs::write(&gitignore, format!("{}\n", MANIFEST_FILE))
+            .map_err(|e| format!("Failed to write file: {}", e))?;
+    }
+    run_git(dir, &["add", "-A"])?;
+    if run_git(dir, &["status", "--porcelain"])?.trim().is_empty() {
+        return Ok(None);
+    }
+
+    // 没有配置提交者时使用应用名，避免提交失败
+    let mut commit_args = Vec::new();
+    if run_git(dir, &["config", "user.email"])
+        .map(|v| v.trim().is_empty())
+        .unwrap_or(true)
+    {
+        commit_args.extend([
+            "-c",
+            "user.name=VividMark",
+            "-c",
+            "user.email=vividmark@localhost",
+        ]);
+    }
+    commit_args.extend(["commit", "-m", message]);
+    run_git(dir, &commit_args)?;
+    run_git_with_env(
+        dir,
+        &["push", "origin", &format!("HEAD:refs/heads/{}", branch)],
+        env,
+    )?;
+    Ok(Some(
+        run_git(dir, &["rev-parse", "HEAD"])?.trim().to_string(),
+    ))
+}
+
+fn publish_to(
+    app: &AppHandle,
+    path: &Path,
+    target: &PublishTarget,
+    default_theme: &str,
+) -> Result<PublishResult, String> {
+    let theme = app
+        .state::<ThemeStore>()
+        .get(Some(target.theme.as_deref().unwrap_or(default_theme)));
+    let site_title = target
+        .site_title
+        .clone()
+        .unwrap_or_else(|| target.name.clone());
+
+    match &target.destination {
+        PublishDestination::Directory { output_dir } => {
+            let site = PathBuf::from(output_dir);
+            let (pages, skipped) = build_site(path, &site, &site_title, &theme)?;
+            Ok(PublishResult {
+                target: target.name.clone(),
+                output: site.to_string_lossy().to_string(),
+                pages,
+                skipped,
+                commit: None,
+            })
+        }
+        PublishDestination::GithubPages { repository, branch } => {
+            let key = content_hash(format!("{}#{}", repository, branch).as_bytes());
+            let dir = app
+                .path()
+                .app_data_dir()
+                .map_err(|e| format!("Failed to resolve data directory: {}", e))?
+                .join("publish")
+                .join(&key[..16]);
+            let token = secrets::get(&format!("publish:{}", target.name)).unwrap_or_else(|e| {
+                log::warn!("[publish] {}", e);
+                None
+            });
+            let env = token_env(token.as_deref());
+            let env: Vec<(&str, &str)> =
+                env.iter().map(|(k, v)| (k.as_str(), v.as_str())).collect();
+
+            prepare_checkout(&dir, repository, branch, &env)?;
+            let (pages, skipped) = build_site(path, &dir, &site_title, &theme)?;
+            let name = path
+                .file_name()
+                .map(|n| n.to_string_lossy().to_string())
+                .unwrap_or_default();
+            let commit = commit_and_push(
+                &dir,
+                branch,
+                &format!("Publish {} from VividMark", name),
+                &env,
+            )?;
+            Ok(PublishResult {
+                target: target.name.clone(),
+                output: dir.to_string_lossy().to_string(),
+                pages,
+                skipped,
+                commit,
+            })
+        }
+    }
+}
+
+// 发布笔记或文件夹到设置中名为 `target` 的发布目标
+#[tauri::command]
+pub async fn publish(
+    app: AppHandle,
+    path: String,
+    target: String,
+) -> Result<PublishResult, VividError> {
+    let start = Instant::now();
+    let settings = app.state::<SettingsStore>().get();
+    let publish_target = settings
+        .publish
+        .targets
+        .iter()
+        .find(|t| t.name == target)
+        .cloned()
+        .ok_or_else(|| format!("Publish target not found: {}", target))?;
+    let source = PathBuf::from(&path);
+    access::ensure_access(&app, &source, AccessKind::Read)?;
+    if !source.exists() {
+        return Err(VividError::not_found(&path));
+    }
+    log::info!("[publish] Publishing {} to {}", path, target);
+
+    let default_theme = settings.export.html_theme.clone();
+    let handle = app.clone();
+    let result = tauri::async_runtime::spawn_blocking(move || {
+        publish_to(&handle, &source, &publish_target, &default_theme)
+    })
+    .await
+    .map_err(|e| format!("Publish task failed: {}", e))?
+    .map_err(|e| {
+        log::error!("[publish] {}", e);
+        e
+    })?;
+
+    log::info!(
+        "[publish] ✓ Success: {} page(s) to {} ({} draft(s) skipped) in {:?}",
+        result.pages.len(),
+        result.output,
+        result.skipped.len(),
+        start.elapsed()
+    );
+    Ok(result)
+}
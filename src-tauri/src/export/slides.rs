@@ -0,0 +1,526 @@
+//! 导出为演示文稿
+//!
+//! 按 Marp / reveal.js 的习惯拆分幻灯片：单独一行的 `---` 是幻灯片分隔符；文档中没有分隔符时，
+//! 每个一级、二级标题开始一张新幻灯片。幻灯片中 `Note:` 之后的内容作为演讲者备注。
+//!
+//! 生成的 HTML 使用 reveal.js 的结构，从 CDN 加载 reveal.js；离线打开时由内联的简易脚本
+//! 提供翻页，因此文件仍然可以单独演示。样式、代码高亮和本地图片都内嵌在文件中。
+//! 主题可以是 reveal.js 自带的主题，也可以是导出主题（见 `theme`）：后者在浅色或深色的基础主题上
+//! 按导出主题的配色覆盖背景、文字、标题和链接颜色。需要 PDF 时，每张幻灯片在横向页面上单独成页。
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tauri::{AppHandle, Manager};
+
+use super::html::first_heading;
+use super::pdf::{self, PdfExportOptions, PdfMargins};
+use super::theme::{ThemePalette, ThemeStore};
+use crate::access::{self, AccessKind};
+use crate::error::VividError;
+use crate::render::{self, RenderOptions};
+use crate::{bibliography, frontmatter, highlight, markdown};
+
+/// reveal.js 的 CDN 地址
+const REVEAL_CDN: &str = "https://cdn.jsdelivr.net/npm/reveal.js@5.1.0/dist";
+/// reveal.js 自带的主题，前者为浅色
+const LIGHT_THEMES: &[&str] = &["white", "beige", "serif", "simple", "sky", "solarized"];
+const DARK_THEMES: &[&str] = &["black", "league", "night", "moon", "blood", "dracula"];
+/// 幻灯片间的过渡效果
+const TRANSITIONS: &[&str] = &["none", "fade", "slide", "convex", "concave", "zoom"];
+
+/// 离线时代替 reveal.js 的样式和翻页脚本
+const FALLBACK_CSS: &str = r#"
+html.no-reveal, html.no-reveal body { margin: 0; height: 100%; overflow: hidden; }
+html.no-reveal .reveal .slides > section { display: none; box-sizing: border-box; height: 100vh; padding: 6vh 8vw; overflow: auto; }
+html.no-reveal .reveal .slides > section.present { display: block; }
+html.no-reveal .reveal aside.notes { display: none; }
+"#;
+const FALLBACK_JS: &str = r#"
+(function () {
+    if (window.Reveal) { return; }
+    document.documentElement.classList.add('no-reveal');
+    var slides = document.querySelectorAll('.reveal .slides > section');
+    var index = Math.max(0, Math.min(slides.length - 1, parseInt(location.hash.slice(2), 10) || 0));
+    function show(i) {
+        index = Math.max(0, Math.min(slides.length - 1, i));
+        slides.forEach(function (s, n) { s.classList.toggle('present', n === index); });
+        history.replaceState(null, '', '#/' + index);
+    }
+    document.addEventListener('keydown', function (e) {
+        if (['ArrowRight', 'ArrowDown', 'PageDown', ' '].indexOf(e.key) >= 0) { show(index + 1); }
+        else if (['ArrowLeft', 'ArrowUp', 'PageUp'].indexOf(e.key) >= 0) { show(index - 1); }
+        else if (e.key === 'Home') { show(0); }
+        else if (e.key === 'End') { show(slides.length - 1); }
+    });
+    document.addEventListener('click', function (e) {
+        if (!e.target.closest('a')) { show(index + (e.clientX < window.innerWidth / 3 ? -1 : 1)); }
+    });
+    show(index);
+})();
+"#;
+
+/// 幻灯片导出参数
+#[derive(Debug, Deserialize)]
+pub struct ExportSlidesParams {
+    /// 源 Markdown 文件路径，用于解析相对图片路径和默认输出位置
+    pub path: String,
+    /// 编辑器中尚未保存的内容；为空时从 `path` 读取
+    pub content: Option<String>,
+    /// 输出文件路径，默认与源文件同目录的 `<文件名>.slides.html`
+    pub output: Option<String>,
+    /// reveal.js 主题（如 `white`、`black`）或导出主题 id；默认使用 front matter 中的 `theme`，
+    /// 否则为 `white`
+    pub theme: Option<String>,
+    /// 过渡效果，默认 `slide`
+    pub transition: Option<String>,
+    /// 同时导出 PDF（与 HTML 同名的 `.pdf`）
+    #[serde(default)]
+    pub pdf: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ExportSlidesResult {
+    pub success: bool,
+    /// HTML 文件路径
+    pub path: Option<String>,
+    /// PDF 文件路径，未导出 PDF 时为空
+    pub pdf_path: Option<String>,
+    pub slides: usize,
+    pub error: Option<String>,
+}
+
+/// 一张幻灯片的 Markdown
+#[derive(Debug, Default, PartialEq)]
+pub struct Slide {
+    pub content: String,
+    /// 演讲者备注
+    pub notes: String,
+}
+
+fn is_separator(line: &str) -> bool {
+    line.trim_end() == "---"
+}
+
+fn is_split_heading(line: &str) -> bool {
+    let hashes = line.bytes().take_while(|&b| b == b'#').count();
+    (1..=2).contains(&hashes)
+        && matches!(
+            line.as_bytes().get(hashes),
+            Some(b' ' | b'\t' | b'\r' | b'\n') | None
+        )
+}
+
+fn notes_start(line: &str) -> Option<&str> {
+    let trimmed = line.trim_start();
+    ["Note:", "Notes:"]
+        .iter()
+        .find_map(|prefix| trimmed.strip_prefix(prefix))
+}
+
+/// 拆分幻灯片，代码块中的分隔符和标题不算
+pub fn split_slides(content: &str) -> Vec<Slide> {
+    let body = match frontmatter::find_front_matter(content) {
+        Some(block) => &content[block.body_start..],
+        None => content,
+    };
+    let lines: Vec<(&str, bool)> = {
+        let mut fence: Option<&str> = None;
+        body.split_inclusive('\n')
+            .map(|line| {
+                let trimmed = line.trim_start();
+                let in_code = match fence {
+                    Some(marker) => {
+                        if trimmed.starts_with(marker) {
+                            fence = None;
+                        }
+                        true
+                    }
+                    None if trimmed.starts_with("```") => {
+                        fence = Some("```");
+                        true
+                    }
+                    None if trimmed.starts_with("~~~") => {
+                        fence = Some("~~~");
+                        true
+                    }
+                    None => false,
+                };
+                (line, in_code)
+            })
+            .collect()
+    };
+    let by_rules = lines
+        .iter()
+        .any(|&(line, in_code)| !in_code && is_separator(line));
+
+    let mut slides = vec![Slide::default()];
+    let mut in_notes = false;
+    for (line, in_code) in lines {
+        if !in_code {
+            let starts_slide = if by_rules {
+                is_separator(line)
+            } else {
+                is_split_heading(line)
+            };
+            if starts_slide {
+                slides.push(Slide::default());
+                in_notes = false;
+                if by_rules {
+                    continue;
+                }
+            } else if let Some(rest) = notes_start(line).filter(|_| !in_notes) {
+                in_notes = true;
+                if let Some(slide) = slides.last_mut() {
+                    slide.notes.push_str(rest.trim_start());
+                }
+                continue;
+            }
+        }
+        if let Some(slide) = slides.last_mut() {
+            if in_notes {
+                slide.notes.push_str(line);
+            } else {
+                slide.content.push_str(line);
+            }
+        }
+    }
+    slides.retain(|slide| !slide.content.trim().is_empty() || !slide.notes.trim().is_empty());
+    slides
+}
+
+/// 幻灯片主题
+#[derive(Debug, Clone)]
+pub struct SlidesTheme {
+    /// reveal.js 基础主题
+    reveal: &'static str,
+    /// 导出主题的配色，覆盖基础主题的颜色
+    palette: Option<ThemePalette>,
+}
+
+impl SlidesTheme {
+    /// 解析主题：reveal.js 自带主题优先，其次是导出主题，都不认识时使用 `white`
+    pub fn resolve(store: &ThemeStore, theme: Option<&str>) -> Self {
+        let theme = theme
+            .map(|t| t.trim().to_ascii_lowercase())
+            .unwrap_or_default();
+        if let Some(reveal) = LIGHT_THEMES
+            .iter()
+            .chain(DARK_THEMES)
+            .find(|&&name| name == theme)
+        {
+            return SlidesTheme {
+                reveal,
+                palette: None,
+            };
+        }
+        match store.find(&theme).filter(|_| !theme.is_empty()) {
+            Some(export_theme) => SlidesTheme {
+                reveal: if export_theme.is_dark() {
+                    "black"
+                } else {
+                    "white"
+                },
+                palette: Some(export_theme.palette().clone()),
+            },
+            None => SlidesTheme {
+                reveal: "white",
+                palette: None,
+            },
+        }
+    }
+
+    fn is_dark(&self) -> bool {
+        DARK_THEMES.contains(&self.reveal)
+    }
+
+    /// 用 reveal.js 的 CSS 变量覆盖基础主题的颜色
+    fn palette_css(&self) -> String {
+        let Some(palette) = &self.palette else {
+            return String::new();
+        };
+        format!(
+            r#":root {{ --r-background-color: {bg}; --r-main-color: {text}; --r-heading-color: {heading}; --r-link-color: {link}; --r-link-color-hover: {link}; }}
+.reveal-viewport, html.no-reveal body {{ background: {bg}; color: {text}; }}
+.reveal code {{ color: {code}; }}
+.reveal blockquote {{ color: {muted}; }}"#,
+            bg = palette.background,
+            text = palette.text,
+            heading = palette.heading,
+            link = palette.link,
+            code = palette.code,
+            muted = palette.muted,
+        )
+    }
+}
+
+fn transition_name(transition: Option<&str>) -> &'static str {
+    let transition = transition
+        .map(|t| t.trim().to_ascii_lowercase())
+        .unwrap_or_default();
+    TRANSITIONS
+        .iter()
+        .find(|&&name| name == transition)
+        .copied()
+        .unwrap_or("slide")
+}
+
+/// 生成 reveal.js 幻灯片 HTML
+pub fn build_slides_html(
+    slides: &[Slide],
+    title: &str,
+    theme: &SlidesTheme,
+    transition: &str,
+    base_dir: Option<&Path>,
+) -> String {
+    let highlight_theme = if theme.is_dark() {
+        highlight::DARK_THEME
+    } else {
+        highlight::LIGHT_THEME
+    };
+    let options = RenderOptions {
+        highlight_theme: Some(highlight_theme.to_string()),
+        render_math: true,
+        embed_images: true,
+        base_dir: base_dir.map(Path::to_path_buf),
+        hard_breaks: false,
+        render_diagrams: true,
+    };
+    let sections = slides
+        .iter()
+        .map(|slide| {
+            let mut section = format!(
+                "<section>\n{}",
+                render::markdown_to_html(&slide.content, &options)
+            );
+            if !slide.notes.trim().is_empty() {
+                section.push_str(&format!(
+                    "<aside class=\"notes\">\n{}</aside>\n",
+                    render::markdown_to_html(&slide.notes, &options)
+                ));
+            }
+            section.push_str("</section>");
+            section
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    format!(
+        r#"<!DOCTYPE html>
+<html>
+<head>
+    <meta charset="UTF-8">
+    <meta name="viewport" content="width=device-width, initial-scale=1">
+    <meta name="generator" content="VividMark">
+    <title>{title}</title>
+    <link rel="stylesheet" href="{cdn}/reveal.css">
+    <link rel="stylesheet" href="{cdn}/theme/{theme}.css">
+    <style>
+.reveal pre {{ box-shadow: none; }}
+.reveal pre code {{ max-height: 60vh; padding: 0.5em; }}
+.reveal img, .reveal svg {{ max-width: 100%; max-height: 60vh; }}
+.reveal table {{ border-collapse: collapse; }}
+{palette}
+{fallback}
+    </style>
+</head>
+<body>
+<div class="reveal">
+<div class="slides">
+{sections}
+</div>
+</div>
+<script src="{cdn}/reveal.js"></script>
+<script>
+if (window.Reveal) {{
+    Reveal.initialize({{ hash: true, slideNumber: true, transition: '{transition}' }});
+}}
+{fallback_js}
+</script>
+</body>
+</html>
+"#,
+        title = markdown::escape_html(title),
+        cdn = REVEAL_CDN,
+        theme = theme.reveal,
+        palette = theme.palette_css(),
+        fallback = FALLBACK_CSS,
+        sections = sections,
+        transition = transition,
+        fallback_js = FALLBACK_JS,
+    )
+}
+
+/// 导出幻灯片 PDF：横向页面，每张幻灯片一页
+fn export_slides_pdf(
+    slides: &[Slide],
+    output: &Path,
+    title: &str,
+    base_dir: Option<&Path>,
+    palette: Option<ThemePalette>,
+) -> Result<usize, String> {
+    let content = slides
+        .iter()
+        .map(|slide| slide.content.trim())
+        .collect::<Vec<_>>()
+        .join("\n\n<!-- pagebreak -->\n\n");
+    let options = PdfExportOptions {
+        landscape: Some(true),
+        margins: Some(PdfMargins {
+            top: 15.0,
+            right: 20.0,
+            bottom: 15.0,
+            left: 20.0,
+        }),
+        font_size: Some(16),
+        show_header: Some(false),
+        base_dir: base_dir.map(|dir| dir.to_string_lossy().to_string()),
+        palette,
+        ..Default::default()
+    };
+    pdf::export_markdown_to_pdf(&content, output, title, &options)
+}
+
+fn failed(error: impl Into<String>) -> ExportSlidesResult {
+    ExportSlidesResult {
+        success: false,
+        path: None,
+        pdf_path: None,
+        slides: 0,
+        error: Some(error.into()),
+    }
+}
+
+// 导出 reveal.js 幻灯片，可同时导出 PDF
+#[tauri::command]
+pub async fn export_slides(
+    app: AppHandle,
+    params: ExportSlidesParams,
+) -> Result<ExportSlidesResult, VividError> {
+    let start = Instant::now();
+    let source = PathBuf::from(&params.path);
+    log::info!("[export_slides] Starting slides export operation");
+    log::debug!("[export_slides] Source: {}", params.path);
+
+    if params.content.is_none() {
+        access::ensure_access(&app, &source, AccessKind::Read)?;
+    }
+    let content = match params.content {
+        Some(content) => content,
+        None => fs::read_to_string(&source).map_err(|e| {
+            log::error!("[export_slides] Failed to read source: {}", e);
+            format!("Failed to read file: {}", e)
+        })?,
+    };
+
+    let front_matter = frontmatter::parse_front_matter(&content)
+        .ok()
+        .flatten()
+        .map(|(_, data)| data)
+        .unwrap_or(Value::Null);
+    let field = |key: &str| {
+        front_matter
+            .get(key)
+            .and_then(Value::as_str)
+            .map(str::to_string)
+    };
+    let theme = SlidesTheme::resolve(
+        &app.state::<ThemeStore>(),
+        params.theme.or_else(|| field("theme")).as_deref(),
+    );
+    let transition = transition_name(params.transition.or_else(|| field("transition")).as_deref());
+    let title = field("title")
+        .or_else(|| first_heading(&content))
+        .unwrap_or_else(|| {
+            source
+                .file_stem()
+                .and_then(|n| n.to_str())
+                .unwrap_or("VividMark Slides")
+                .to_string()
+        });
+    log::debug!(
+        "[export_slides] Theme: {:?}, transition: {}",
+        theme,
+        transition
+    );
+
+    let output = params.output.map(PathBuf::from).unwrap_or_else(|| {
+        let stem = source
+            .file_stem()
+            .map(|s| s.to_string_lossy().to_string())
+            .unwrap_or_else(|| "slides".to_string());
+        source.with_file_name(format!("{}.slides.html", stem))
+    });
+    access::ensure_access(&app, &output, AccessKind::Write)?;
+    let pdf_output = params.pdf.then(|| output.with_extension("pdf"));
+    let base_dir = source.parent().map(Path::to_path_buf);
+
+    let html_title = title.clone();
+    let render_pdf = pdf_output.clone();
+    let result = tauri::async_runtime::spawn_blocking(move || {
+        let content = bibliography::resolve_citations(&app, content);
+        let slides = split_slides(&content);
+        let html = build_slides_html(
+            &slides,
+            &html_title,
+            &theme,
+            transition,
+            base_dir.as_deref(),
+        );
+        let pages = match &render_pdf {
+            Some(pdf) => Some(export_slides_pdf(
+                &slides,
+                pdf,
+                &html_title,
+                base_dir.as_deref(),
+                theme.palette.clone(),
+            )),
+            None => None,
+        };
+        (slides.len(), html, pages)
+    })
+    .await
+    .map_err(|e| format!("Slides export task failed: {}", e))?;
+    let (slides, html, pages) = result;
+
+    if slides == 0 {
+        log::warn!("[export_slides] Document has no slides");
+        return Ok(failed("Document has no content to present"));
+    }
+    if let Err(e) = fs::write(&output, &html) {
+        log::error!("[export_slides] Failed to write output {:?}: {}", output, e);
+        return Ok(failed(format!("Failed to write file: {}", e)));
+    }
+    if let Some(Err(e)) = &pages {
+        log::error!("[export_slides] PDF export failed: {}", e);
+        return Ok(ExportSlidesResult {
+            success: false,
+            path: Some(output.to_string_lossy().to_string()),
+            pdf_path: None,
+            slides,
+            error: Some(e.clone()),
+        });
+    }
+
+    log::info!(
+        "[export_slides] ✓ Success: {:?} ({} slides, {} bytes{}) in {:?}",
+        output,
+        slides,
+        html.len(),
+        pdf_output
+            .as_ref()
+            .map(|pdf| format!(", PDF {:?}", pdf))
+            .unwrap_or_default(),
+        start.elapsed()
+    );
+    Ok(ExportSlidesResult {
+        success: true,
+        path: Some(output.to_string_lossy().to_string()),
+        pdf_path: pdf_output.map(|pdf| pdf.to_string_lossy().to_string()),
+        slides,
+        error: None,
+    })
+}
@@ -0,0 +1,385 @@
+//! 导出主题
+//!
+//! HTML、PDF、幻灯片和站点发布共用的主题。内置主题随程序打包；用户主题是主题目录
+//! （应用数据目录下的 `themes/`）中的 `.css` 文件，文件名（不含扩展名）即主题 id，
+//! 与内置主题同 id 时覆盖内置主题。
+//!
+//! 主题在 `:root` 中用 CSS 变量声明配色，PDF 和幻灯片这类不能直接使用主题样式的导出按配色排版：
+//!
+//! ```css
+//! :root {
+//!     --vm-text: #333333;
+//!     --vm-background: #ffffff;
+//!     --vm-link: #0366d6;
+//!     --vm-code: #c7254e;
+//!     --vm-heading: #24292e;
+//!     --vm-muted: #6a737d;
+//! }
+//! ```
+//!
+//! 未声明的颜色按背景亮度使用浅色或深色默认值。
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use tauri::State;
+
+use super::html;
+use crate::error::VividError;
+use crate::highlight;
+
+pub const DEFAULT_THEME: &str = "github";
+
+/// 内置主题：`(id, 名称, 样式)`
+const BUILTIN_THEMES: &[(&str, &str, &str)] = &[
+    ("github", "GitHub", include_str!("themes/github.css")),
+    ("dark", "Dark", include_str!("themes/dark.css")),
+    ("academic", "Academic", include_str!("themes/academic.css")),
+    ("sepia", "Sepia", include_str!("themes/sepia.css")),
+];
+
+/// 预览主题时默认使用的示例文档
+const SAMPLE_MARKDOWN: &str = r#"# Theme Preview
+
+A paragraph with **bold**, *italic*, `inline code` and a [link](https://example.com).
+
+## Lists and Quotes
+
+- First item
+- Second item
+  1. Nested ordered item
+- [x] Completed task
+
+> A blockquote with a short remark.
+
+## Code
+
+```rust
+fn main() {
+    println!("Hello, VividMark!");
+}
+```
+
+## Table
+
+| Name | Value |
+| ---- | ----: |
+| Alpha | 1 |
+| Beta | 2 |
+
+---
+
+Inline math $E = mc^2$ and a footnote.[^1]
+
+[^1]: The footnote text.
+"#;
+
+/// 主题配色（`#rrggbb`）
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ThemePalette {
+    pub text: String,
+    pub background: String,
+    pub link: String,
+    pub code: String,
+    pub heading: String,
+    /// 引用、脚注等次要文字
+    pub muted: String,
+}
+
+impl ThemePalette {
+    pub fn light() -> Self {
+        ThemePalette {
+            text: "#333333".to_string(),
+            background: "#ffffff".to_string(),
+            link: "#0366d6".to_string(),
+            code: "#c7254e".to_string(),
+            heading: "#24292e".to_string(),
+            muted: "#6a737d".to_string(),
+        }
+    }
+
+    pub fn dark() -> Self {
+        ThemePalette {
+            text: "#c9d1d9".to_string(),
+            background: "#0d1117".to_string(),
+            link: "#58a6ff".to_string(),
+            code: "#ff7b72".to_string(),
+            heading: "#e6edf3".to_string(),
+            muted: "#8b949e".to_string(),
+        }
+    }
+
+    /// 打印到白纸上使用的配色：深色主题的文字颜色在白底上看不清，改用浅色默认值
+    pub fn for_paper(&self) -> ThemePalette {
+        if is_dark_color(&self.background) {
+            ThemePalette::light()
+        } else {
+            self.clone()
+        }
+    }
+}
+
+/// 解析 `#rgb` / `#rrggbb` 颜色
+pub fn parse_color(color: &str) -> Option<(u8, u8, u8)> {
+    let hex = color.trim().strip_prefix('#')?;
+    let channel = |s: &str| u8::from_str_radix(s, 16).ok();
+    match hex.len() {
+        3 => {
+            let mut digits = hex.chars().map(|c| c.to_string().repeat(2));
+            Some((
+                channel(&digits.next()?)?,
+                channel(&digits.next()?)?,
+                channel(&digits.next()?)?,
+            ))
+        }
+        6 => Some((
+            channel(hex.get(0..2)?)?,
+            channel(hex.get(2..4)?)?,
+            channel(hex.get(4..6)?)?,
+        )),
+        _ => None,
+    }
+}
+
+/// 按相对亮度判断是否为深色
+fn is_dark_color(color: &str) -> bool {
+    parse_color(color).is_some_and(|(r, g, b)| {
+        0.2126 * f64::from(r) + 0.7152 * f64::from(g) + 0.0722 * f64::from(b) < 128.0
+    })
+}
+
+/// 从样式中读取 `--vm-*` 配色变量
+fn palette_from_css(css: &str) -> ThemePalette {
+    let variable = Regex::new(r"--vm-([a-z]+)\s*:\s*(#[0-9a-fA-F]{3,6})\b").ok();
+    let declared: Vec<(String, String)> = variable
+        .iter()
+        .flat_map(|re| re.captures_iter(css))
+        .map(|caps| (caps[1].to_string(), caps[2].to_string()))
+        .collect();
+    let get = |name: &str| {
+        declared
+            .iter()
+            .find(|(key, value)| key == name && parse_color(value).is_some())
+            .map(|(_, value)| value.clone())
+    };
+
+    let mut palette = match get("background") {
+        Some(background) if is_dark_color(&background) => ThemePalette::dark(),
+        _ => ThemePalette::light(),
+    };
+    for (name, slot) in [
+        ("text", &mut palette.text),
+        ("background", &mut palette.background),
+        ("link", &mut palette.link),
+        ("code", &mut palette.code),
+        ("heading", &mut palette.heading),
+        ("muted", &mut palette.muted),
+    ] {
+        if let Some(value) = get(name) {
+            *slot = value;
+        }
+    }
+    palette
+}
+
+/// 主题信息
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ThemeInfo {
+    pub id: String,
+    pub name: String,
+    pub builtin: bool,
+    /// 用户主题的样式文件
+    pub path: Option<String>,
+    pub dark: bool,
+    pub palette: ThemePalette,
+}
+
+/// 导出使用的主题
+#[derive(Debug, Clone)]
+pub struct ExportTheme {
+    pub info: ThemeInfo,
+    css: String,
+}
+
+impl ExportTheme {
+    fn new(id: &str, name: String, css: String, path: Option<&Path>) -> Self {
+        let palette = palette_from_css(&css);
+        ExportTheme {
+            info: ThemeInfo {
+                id: id.to_string(),
+                name,
+                builtin: path.is_none(),
+                path: path.map(|p| p.to_string_lossy().to_string()),
+                dark: is_dark_color(&palette.background),
+                palette,
+            },
+            css,
+        }
+    }
+
+    /// 按 id 查找内置主题（不区分大小写）
+    pub fn find_builtin(id: &str) -> Option<Self> {
+        let id = id.trim().to_ascii_lowercase();
+        BUILTIN_THEMES
+            .iter()
+            .find(|(builtin, _, _)| *builtin == id)
+            .map(|(id, name, css)| ExportTheme::new(id, name.to_string(), css.to_string(), None))
+    }
+
+    /// 内置主题，不认识的主题使用默认主题（列表中的第一个）
+    pub fn builtin(id: Option<&str>) -> Self {
+        id.and_then(ExportTheme::find_builtin).unwrap_or_else(|| {
+            let (id, name, css) = BUILTIN_THEMES[0];
+            ExportTheme::new(id, name.to_string(), css.to_string(), None)
+        })
+    }
+
+    pub fn id(&self) -> &str {
+        &self.info.id
+    }
+
+    pub fn css(&self) -> &str {
+        &self.css
+    }
+
+    pub fn palette(&self) -> &ThemePalette {
+        &self.info.palette
+    }
+
+    pub fn is_dark(&self) -> bool {
+        self.info.dark
+    }
+
+    pub fn highlight_theme(&self) -> &'static str {
+        if self.info.dark {
+            highlight::DARK_THEME
+        } else {
+            highlight::LIGHT_THEME
+        }
+    }
+}
+
+/// `solarized-light` → `Solarized Light`
+fn display_name(id: &str) -> String {
+    id.split(['-', '_', ' '])
+        .filter(|word| !word.is_empty())
+        .map(|word| {
+            let mut chars = word.chars();
+            match chars.next() {
+                Some(first) => first.to_uppercase().chain(chars).collect(),
+                None => String::new(),
+            }
+        })
+        .collect::<Vec<String>>()
+        .join(" ")
+}
+
+/// 用户主题目录
+pub struct ThemeStore {
+    dir: PathBuf,
+}
+
+impl ThemeStore {
+    pub fn new(dir: PathBuf) -> Self {
+        ThemeStore { dir }
+    }
+
+    /// 主题目录中的用户主题
+    fn user_themes(&self) -> Vec<ExportTheme> {
+        let Ok(entries) = fs::read_dir(&self.dir) else {
+            return Vec::new();
+        };
+        entries
+            .flatten()
+            .map(|entry| entry.path())
+            .filter(|path| {
+                path.extension()
+                    .is_some_and(|ext| ext.eq_ignore_ascii_case("css"))
+            })
+            .filter_map(|path| {
+                let id = path.file_stem()?.to_string_lossy().to_lowercase();
+                match fs::read_to_string(&path) {
+                    Ok(css) => Some(ExportTheme::new(&id, display_name(&id), css, Some(&path))),
+                    Err(e) => {
+                        log::warn!("[themes] Failed to read {:?}: {}", path, e);
+                        None
+                    }
+                }
+            })
+            .collect()
+    }
+
+    /// 列出所有主题，用户主题覆盖同 id 的内置主题
+    pub fn list(&self) -> Vec<ThemeInfo> {
+        if let Err(e) = fs::create_dir_all(&self.dir) {
+            log::warn!("[themes] Failed to create {:?}: {}", self.dir, e);
+        }
+        let user = self.user_themes();
+        let mut themes: Vec<ThemeInfo> = BUILTIN_THEMES
+            .iter()
+            .filter(|(id, _, _)| !user.iter().any(|theme| theme.id() == *id))
+            .map(|(id, _, _)| ExportTheme::builtin(Some(id)).info)
+            .collect();
+        let mut user: Vec<ThemeInfo> = user.into_iter().map(|theme| theme.info).collect();
+        user.sort_by(|a, b| a.id.cmp(&b.id));
+        themes.extend(user);
+        themes
+    }
+
+    /// 按 id 查找主题，用户主题优先
+    pub fn find(&self, id: &str) -> Option<ExportTheme> {
+        let id = id.trim().to_ascii_lowercase();
+        self.user_themes()
+            .into_iter()
+            .find(|theme| theme.id() == id)
+            .or_else(|| ExportTheme::find_builtin(&id))
+    }
+
+    /// 按 id 查找主题，找不到时使用默认主题
+    pub fn get(&self, id: Option<&str>) -> ExportTheme {
+        let Some(id) = id.filter(|id| !id.trim().is_empty()) else {
+            return ExportTheme::builtin(None);
+        };
+        self.find(id).unwrap_or_else(|| {
+            log::warn!("[themes] Unknown theme {:?}, using {}", id, DEFAULT_THEME);
+            ExportTheme::builtin(None)
+        })
+    }
+}
+
+// 列出导出主题（内置主题和主题目录中的用户主题）
+#[tauri::command]
+pub fn list_export_themes(store: State<'_, ThemeStore>) -> Vec<ThemeInfo> {
+    let themes = store.list();
+    log::debug!("[list_export_themes] {} theme(s)", themes.len());
+    themes
+}
+
+// 用指定主题渲染示例文档，返回完整的 HTML 供前端预览
+#[tauri::command]
+pub fn preview_theme(
+    store: State<'_, ThemeStore>,
+    theme: String,
+    sample_md: Option<String>,
+) -> Result<String, VividError> {
+    let Some(export_theme) = store.find(&theme) else {
+        log::error!("[preview_theme] Unknown theme: {}", theme);
+        return Err(VividError::invalid_input(format!(
+            "Unknown theme: {}",
+            theme
+        )));
+    };
+    let sample = sample_md
+        .filter(|sample| !sample.trim().is_empty())
+        .unwrap_or_else(|| SAMPLE_MARKDOWN.to_string());
+    let title = html::first_heading(&sample).unwrap_or_else(|| export_theme.info.name.clone());
+    Ok(html::build_standalone_html(
+        &sample,
+        &title,
+        &export_theme,
+        true,
+        None,
+    ))
+}
@@ -0,0 +1,403 @@
+//! 导出整个工作区
+//!
+//! 把工作区中的每个 Markdown 文件转换为 HTML 或 PDF，按原目录结构写入输出目录。笔记之间的
+//! 链接（`[text](note.md)` 和 `[[note]]`）改写为指向导出后文件的相对链接；HTML 内嵌图片，
+//! 每个文件都可以单独打开。
+//!
+//! 导出过程中逐个文件发送 `export-progress` 事件，`cancel_workspace_export` 在当前文件完成后
+//! 停止导出，已写入的文件保留。也可以用 `start_job("export_workspace", ...)` 在后台任务队列中
+//! 导出（见 `jobs`）。
+
+use std::collections::HashSet;
+use std::fs;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Instant;
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter, Manager, State};
+
+use super::html::{build_standalone_html, first_heading};
+use super::pdf::{export_markdown_to_pdf, PdfExportOptions};
+use super::theme::{ExportTheme, ThemeStore};
+use crate::access::{self, AccessKind};
+use crate::assets::{path_to_link, relative_path};
+use crate::error::VividError;
+use crate::links::{self, LinkKind, Resolver};
+use crate::{markdown, workspace};
+
+/// 导出进度事件
+pub const EXPORT_PROGRESS_EVENT: &str = "export-progress";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WorkspaceExportFormat {
+    Html,
+    Pdf,
+}
+
+impl WorkspaceExportFormat {
+    fn extension(self) -> &'static str {
+        match self {
+            WorkspaceExportFormat::Html => "html",
+            WorkspaceExportFormat::Pdf => "pdf",
+        }
+    }
+}
+
+/// 工作区导出选项
+#[derive(Debug, Default, Deserialize)]
+pub struct ExportWorkspaceOptions {
+    /// 输出目录，默认为工作区旁的 `<工作区名称>-export`
+    pub output: Option<String>,
+    /// 导出主题 id，默认 `github`；PDF 使用主题的配色
+    pub theme: Option<String>,
+    /// PDF 页面设置
+    pub pdf: Option<PdfExportOptions>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExportProgress {
+    pub root: String,
+    pub done: usize,
+    pub total: usize,
+    /// 正在导出的文件（相对工作区根目录），完成时为空
+    pub path: Option<String>,
+}
+
+/// 导出失败的文件
+#[derive(Debug, Serialize, Deserialize)]
+pub struct FailedExport {
+    pub path: String,
+    pub error: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct WorkspaceExportResult {
+    pub output: String,
+    /// 导出的文件
+    pub exported: Vec<String>,
+    pub failed: Vec<FailedExport>,
+    /// 是否被取消，取消前已导出的文件保留
+    pub cancelled: bool,
+}
+
+/// 正在进行的工作区导出，同时只允许一个
+#[derive(Default)]
+pub struct WorkspaceExport {
+    running: AtomicBool,
+    cancelled: AtomicBool,
+}
+
+/// 导出期间持有，结束时清除运行标记
+pub(crate) struct RunningGuard<'a>(&'a WorkspaceExport);
+
+impl Drop for RunningGuard<'_> {
+    fn drop(&mut self) {
+        self.0.running.store(false, Ordering::SeqCst);
+    }
+}
+
+impl WorkspaceExport {
+    pub(crate) fn begin(&self) -> Result<RunningGuard<'_>, String> {
+        if self.running.swap(true, Ordering::SeqCst) {
+            return Err("A workspace export is already running".to_string());
+        }
+        self.cancelled.store(false, Ordering::SeqCst);
+        Ok(RunningGuard(self))
+    }
+
+    /// 是否已请求取消（`cancel_workspace_export`）
+    pub(crate) fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::SeqCst)
+    }
+}
+
+/// 导出目录：选项中的目录，默认为工作区旁的 `<工作区名称>-export`
+pub(crate) fn output_dir(
+    root: &Path,
+    options: &ExportWorkspaceOptions,
+) -> Result<PathBuf, VividError> {
+    let output = match &options.output {
+        Some(output) => PathBuf::from(output),
+        None => {
+            let name = root
+                .file_name()
+                .map(|n| n.to_string_lossy().to_string())
+                .unwrap_or_else(|| "workspace".to_string());
+            root.with_file_name(format!("{}-export", name))
+        }
+    };
+    if output == root {
+        return Err(VividError::invalid_input(
+            "Output directory must differ from the workspace",
+        ));
+    }
+    Ok(output)
+}
+
+/// 笔记在导出目录中对应的文件
+fn exported_path(root: &Path, output: &Path, source: &Path, ext: &str) -> Option<PathBuf> {
+    Some(
+        output
+            .join(source.strip_prefix(root).ok()?)
+            .with_extension(ext),
+    )
+}
+
+/// 从 `source` 指向导出后的 `target` 的相对链接
+fn exported_link(source: &Path, target: &Path, heading: Option<&str>, ext: &str) -> String {
+    let target = target.with_extension(ext);
+    let dir = source.parent().unwrap_or(Path::new(""));
+    let mut link = relative_path(dir, &target)
+        .map(|rel| path_to_link(&rel))
+        .unwrap_or_else(|| path_to_link(&target));
+    if let Some(heading) = heading.filter(|h| !h.is_empty()) {
+        link.push('#');
+        link.push_str(&markdown::slugify(heading));
+    }
+    link
+}
+
+/// `[text](url "title")` 中 URL 的位置
+fn link_url_range(content: &str, span: &Range<usize>) -> Option<Range<usize>> {
+    let text = &content[span.clone()];
+    let open = text.rfind("](")? + 2;
+    let rest = &text[open..];
+    let (offset, len) = match rest.strip_prefix('<') {
+        Some(inner) => (1, inner.find('>')?),
+        None => (
+            0,
+            rest.find(|c: char| c.is_whitespace() || c == ')')
+                .unwrap_or(rest.len()),
+        ),
+    };
+    let start = span.start + open + offset;
+    Some(start..start + len)
+}
+
+/// 把指向工作区内笔记的链接改写为导出后文件的链接
+fn rewrite_note_links(
+    content: &str,
+    source: &Path,
+    resolver: &Resolver<'_>,
+    exported: &HashSet<PathBuf>,
+    ext: &str,
+) -> String {
+    let mut edits: Vec<(Range<usize>, String)> = Vec::new();
+    for link in links::extract_links(content) {
+        let Some(target) = resolver
+            .resolve_exact(source, &link)
+            .filter(|target| exported.contains(target))
+        else {
+            continue;
+        };
+        let span = link.start..link.end;
+        match link.kind {
+            LinkKind::Markdown => {
+                if let Some(range) = link_url_range(content, &span) {
+                    edits.push((
+                        range,
+                        exported_link(source, &target, link.heading.as_deref(), ext),
+                    ));
+                }
+            }
+            // 渲染时不识别 `[[...]]`，改写为普通链接，显示别名或原始文字
+            LinkKind::Wiki => {
+                let inner = content[span.clone()]
+                    .trim_start_matches("[[")
+                    .trim_end_matches("]]");
+                let label = inner
+                    .split_once('|')
+                    .map_or(inner, |(_, alias)| alias)
+                    .trim();
+                edits.push((
+                    span,
+                    format!(
+                        "[{}]({})",
+                        label.replace('[', "\\[").replace(']', "\\]"),
+                        exported_link(source, &target, link.heading.as_deref(), ext)
+                    ),
+                ));
+            }
+            LinkKind::Embed => {}
+        }
+    }
+
+    edits.sort_by_key(|(range, _)| range.start);
+    let mut out = String::with_capacity(content.len());
+    let mut last = 0;
+    for (range, text) in edits {
+        if range.start < last {
+            continue;
+        }
+        out.push_str(&content[last..range.start]);
+        out.push_str(&text);
+        last = range.end;
+    }
+    out.push_str(&content[last..]);
+    out
+}
+
+/// 导出单个文件
+fn export_file(
+    source: &Path,
+    content: &str,
+    target: &Path,
+    format: WorkspaceExportFormat,
+    options: &ExportWorkspaceOptions,
+    theme: &ExportTheme,
+) -> Result<(), String> {
+    let title = first_heading(content).unwrap_or_else(|| crate::file_name_of(source));
+    if let Some(parent) = target.parent() {
+        fs::create_dir_all(parent).map_err(|e| format!("Failed to create directory: {}", e))?;
+    }
+    let base_dir = source.parent();
+    match format {
+        WorkspaceExportFormat::Html => {
+            let html = build_standalone_html(content, &title, theme, true, base_dir);
+            fs::write(target, html).map_err(|e| format!("Failed to write file: {}", e))
+        }
+        WorkspaceExportFormat::Pdf => {
+            let mut pdf = options.pdf.clone().unwrap_or_default();
+            pdf.base_dir = base_dir.map(|dir| dir.to_string_lossy().to_string());
+            pdf.palette = Some(theme.palette().clone());
+            export_markdown_to_pdf(content, target, &title, &pdf).map(|_| ())
+        }
+    }
+}
+
+/// 导出工作区；`theme` 为按 `options.theme` 解析出的主题，`progress` 在每个文件开始前和
+/// 全部完成后调用，`cancelled` 返回 `true` 时在当前文件完成后停止
+pub(crate) fn run(
+    root: &Path,
+    output: &Path,
+    format: WorkspaceExportFormat,
+    options: &ExportWorkspaceOptions,
+    theme: &ExportTheme,
+    progress: &dyn Fn(ExportProgress),
+    cancelled: &dyn Fn() -> bool,
+) -> WorkspaceExportResult {
+    let files = workspace::markdown_files(root);
+    let exported: HashSet<PathBuf> = files.iter().cloned().collect();
+    let linked = links::scan_files(&files);
+    let resolver = Resolver::new(Some(root), &linked);
+    let ext = format.extension();
+    let root_name = root.to_string_lossy().to_string();
+    let progress = |done: usize, path: Option<String>| {
+        progress(ExportProgress {
+            root: root_name.clone(),
+            done,
+            total: files.len(),
+            path,
+        })
+    };
+
+    let mut result = WorkspaceExportResult {
+        output: output.to_string_lossy().to_string(),
+        exported: Vec::new(),
+        failed: Vec::new(),
+        cancelled: false,
+    };
+    for (done, source) in files.iter().enumerate() {
+        if cancelled() {
+            log::info!("[export_workspace] Cancelled after {} file(s)", done);
+            result.cancelled = true;
+            break;
+        }
+        let rel = source
+            .strip_prefix(root)
+            .unwrap_or(source)
+            .to_string_lossy()
+            .to_string();
+        progress(done, Some(rel.clone()));
+
+        let Some(target) = exported_path(root, output, source, ext) else {
+            continue;
+        };
+        let exported_file = fs::read_to_string(source)
+            .map_err(|e| format!("Failed to read file: {}", e))
+            .and_then(|content| {
+                let content = rewrite_note_links(&content, source, &resolver, &exported, ext);
+                export_file(source, &content, &target, format, options, theme)
+            });
+        match exported_file {
+            Ok(()) => result.exported.push(target.to_string_lossy().to_string()),
+            Err(error) => {
+                log::warn!("[export_workspace] Failed to export {}: {}", rel, error);
+                result.failed.push(FailedExport { path: rel, error });
+            }
+        }
+    }
+    progress(result.exported.len() + result.failed.len(), None);
+    result
+}
+
+// 把工作区中的所有 Markdown 文件导出为 HTML 或 PDF，保留目录结构
+#[tauri::command]
+pub async fn export_workspace(
+    app: AppHandle,
+    root: String,
+    format: WorkspaceExportFormat,
+    options: Option<ExportWorkspaceOptions>,
+) -> Result<WorkspaceExportResult, VividError> {
+    let start = Instant::now();
+    let root = PathBuf::from(&root);
+    let options = options.unwrap_or_default();
+    access::ensure_access(&app, &root, AccessKind::Read)?;
+    if !root.is_dir() {
+        log::error!("[export_workspace] Not a directory: {}", root.display());
+        return Err(VividError::dir_not_found(&root));
+    }
+    let output = output_dir(&root, &options)?;
+    access::ensure_access(&app, &output, AccessKind::Write)?;
+    log::info!(
+        "[export_workspace] Exporting {} as {:?} to {}",
+        root.display(),
+        format,
+        output.display()
+    );
+
+    let theme = app.state::<ThemeStore>().get(options.theme.as_deref());
+    let handle = app.clone();
+    let result = tauri::async_runtime::spawn_blocking(move || {
+        let jobs = handle.state::<WorkspaceExport>();
+        let _guard = jobs.begin()?;
+        let progress = |payload: ExportProgress| {
+            if let Err(e) = handle.emit(EXPORT_PROGRESS_EVENT, payload) {
+                log::warn!("[export_workspace] Failed to emit progress: {}", e);
+            }
+        };
+        let cancelled = || jobs.is_cancelled();
+        Ok::<_, String>(run(
+            &root, &output, format, &options, &theme, &progress, &cancelled,
+        ))
+    })
+    .await
+    .map_err(|e| format!("Export task failed: {}", e))?
+    .map_err(|e| {
+        log::error!("[export_workspace] {}", e);
+        e
+    })?;
+
+    log::info!(
+        "[export_workspace] ✓ Success: {} exported, {} failed{} in {:?}",
+        result.exported.len(),
+        result.failed.len(),
+        if result.cancelled { " (cancelled)" } else { "" },
+        start.elapsed()
+    );
+    Ok(result)
+}
+
+// 取消正在进行的工作区导出，返回是否有导出在进行
+#[tauri::command]
+pub fn cancel_workspace_export(jobs: State<'_, WorkspaceExport>) -> bool {
+    let running = jobs.running.load(Ordering::SeqCst);
+    if running {
+        log::info!("[cancel_workspace_export] Cancelling workspace export");
+        jobs.cancelled.store(true, Ordering::SeqCst);
+    }
+    running
+}
@@ -0,0 +1,173 @@
+//! 附件文本提取
+//!
+//! 从工作区中的 PDF 和 Word（.docx）附件提取纯文本：`extract_text` 供预览面板显示，
+//! 全文索引（见 `search`）也用它索引附件，使附件和 Markdown 文档一起被搜索到。
+//!
+//! PDF 由 `pdf-extract` 按页提取，只能取出文本层，扫描件没有文本；Word 文档读取
+//! `word/document.xml`，每个段落一行，表格的单元格用制表符分隔。
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+
+use serde::{Deserialize, Serialize};
+use tauri::AppHandle;
+
+use crate::access::{self, AccessKind};
+use crate::error::VividError;
+use crate::import::docx;
+
+/// 提取文本的文件大小上限，更大的附件不预览也不索引
+pub const MAX_EXTRACT_BYTES: u64 = 50 * 1024 * 1024;
+/// 返回给预览面板的最大字符数
+const MAX_PREVIEW_CHARS: usize = 200_000;
+
+/// 支持提取文本的附件格式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DocumentKind {
+    Pdf,
+    Docx,
+}
+
+impl DocumentKind {
+    pub fn of(path: &Path) -> Option<Self> {
+        let ext = path.extension()?.to_str()?;
+        if ext.eq_ignore_ascii_case("pdf") {
+            Some(DocumentKind::Pdf)
+        } else if ext.eq_ignore_ascii_case("docx") {
+            Some(DocumentKind::Docx)
+        } else {
+            None
+        }
+    }
+}
+
+/// 是否可以提取该文件的文本
+pub fn is_extractable(path: &Path) -> bool {
+    DocumentKind::of(path).is_some()
+}
+
+/// 提取结果
+pub struct Extracted {
+    pub kind: DocumentKind,
+    pub text: String,
+    /// PDF 的页数
+    pub pages: Option<usize>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ExtractedText {
+    pub path: String,
+    pub kind: DocumentKind,
+    pub text: String,
+    pub pages: Option<usize>,
+    /// 文本是否因过长被截断
+    pub truncated: bool,
+}
+
+/// 去掉行尾空白，把连续的空行合并为一行
+fn tidy(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut blank = true;
+    for line in text.lines().map(str::trim_end) {
+        if line.trim().is_empty() {
+            if !blank {
+                out.push('\n');
+            }
+            blank = true;
+            continue;
+        }
+        out.push_str(line);
+        out.push('\n');
+        blank = false;
+    }
+    out.truncate(out.trim_end().len());
+    out
+}
+
+fn pdf_pages(bytes: &[u8]) -> Result<Vec<String>, String> {
+    // pdf-extract 遇到不支持的字体或编码时会直接 panic，不能让它中断索引
+    std::panic::catch_unwind(|| pdf_extract::extract_text_from_mem_by_pages(bytes))
+        .map_err(|_| "Unsupported PDF content".to_string())?
+        .map_err(|e| format!("Invalid PDF: {}", e))
+}
+
+/// 读取附件并提取纯文本
+pub fn extract(path: &Path) -> Result<Extracted, String> {
+    let kind = DocumentKind::of(path).ok_or("Unsupported file type")?;
+    let size = fs::metadata(path)
+        .map_err(|e| format!("Failed to read file: {}", e))?
+        .len();
+    if size > MAX_EXTRACT_BYTES {
+        return Err(format!(
+            "File is larger than {} MB",
+            MAX_EXTRACT_BYTES / 1024 / 1024
+        ));
+    }
+    let bytes = fs::read(path).map_err(|e| format!("Failed to read file: {}", e))?;
+    let (text, pages) = match kind {
+        DocumentKind::Pdf => {
+            let pages = pdf_pages(&bytes)?;
+            let count = pages.len();
+            let text = pages
+                .iter()
+                .map(|page| tidy(page))
+                .filter(|page| !page.is_empty())
+                .collect::<Vec<_>>()
+                .join("\n\n");
+            (text, Some(count))
+        }
+        DocumentKind::Docx => (tidy(&docx::to_text(&bytes)?), None),
+    };
+    Ok(Extracted { kind, text, pages })
+}
+
+// 提取 PDF 或 Word 附件的纯文本，用于预览
+#[tauri::command]
+pub async fn extract_text(app: AppHandle, path: String) -> Result<ExtractedText, VividError> {
+    let start = Instant::now();
+    let path_buf = PathBuf::from(&path);
+    log::info!("[extract_text] Extracting {}", path);
+    if !path_buf.is_file() {
+        log::error!("[extract_text] File does not exist: {}", path);
+        return Err(VividError::not_found(&path_buf));
+    }
+    if !is_extractable(&path_buf) {
+        return Err(VividError::invalid_input(
+            "Only PDF and Word (.docx) files are supported",
+        ));
+    }
+    access::ensure_access(&app, &path_buf, AccessKind::Read)?;
+
+    let extracted = tauri::async_runtime::spawn_blocking(move || extract(&path_buf))
+        .await
+        .map_err(|e| format!("Extract task failed: {}", e))?
+        .map_err(|e| {
+            log::error!("[extract_text] {}", e);
+            VividError::from(e)
+        })?;
+
+    let mut text = extracted.text;
+    let truncated = match text.char_indices().nth(MAX_PREVIEW_CHARS) {
+        Some((end, _)) => {
+            text.truncate(end);
+            true
+        }
+        None => false,
+    };
+    log::info!(
+        "[extract_text] ✓ Success: {:?}, {} char(s){} in {:?}",
+        extracted.kind,
+        text.chars().count(),
+        if truncated { " (truncated)" } else { "" },
+        start.elapsed()
+    );
+    Ok(ExtractedText {
+        path,
+        kind: extracted.kind,
+        text,
+        pages: extracted.pages,
+        truncated,
+    })
+}
@@ -0,0 +1,126 @@
+//! 文件属性
+//!
+//! `get_file_info` 返回文档属性面板需要的信息：大小、创建 / 修改 / 访问时间（ISO 8601，
+//! 本地时区）、权限、只读标记，以及文本文件的行数、字数和字符数。
+//! 字数与状态栏一致（见 `stats`）；大文件和加密文件不统计内容。
+
+use std::fs::{self, Metadata};
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+use tauri::AppHandle;
+
+use crate::access::{self, AccessKind};
+use crate::error::VividError;
+use crate::largefile::LARGE_FILE_THRESHOLD;
+use crate::{encryption, readonly, stats, workspace};
+
+/// 文件属性
+#[derive(Debug, Serialize, Deserialize)]
+pub struct FileProperties {
+    pub path: String,
+    pub name: String,
+    pub size: u64,
+    /// 创建时间，文件系统不支持时为空
+    pub created: Option<String>,
+    pub modified: Option<String>,
+    pub accessed: Option<String>,
+    /// Unix 权限（如 `644`），其它平台为空
+    pub permissions: Option<String>,
+    pub read_only: bool,
+    pub is_dir: bool,
+    pub is_symlink: bool,
+    pub is_markdown: bool,
+    pub encrypted: bool,
+    /// 文本内容统计，目录、二进制文件、大文件和加密文件为空
+    pub lines: Option<usize>,
+    pub words: Option<usize>,
+    pub characters: Option<usize>,
+}
+
+/// 时间格式化为带时区的 ISO 8601（如 `2024-05-01T09:30:00+08:00`）
+pub fn iso_time(time: SystemTime) -> String {
+    DateTime::<Local>::from(time)
+        .format("%Y-%m-%dT%H:%M:%S%:z")
+        .to_string()
+}
+
+#[cfg(unix)]
+fn permissions(metadata: &Metadata) -> Option<String> {
+    use std::os::unix::fs::PermissionsExt;
+    Some(format!("{:o}", metadata.permissions().mode() & 0o777))
+}
+
+#[cfg(not(unix))]
+fn permissions(_metadata: &Metadata) -> Option<String> {
+    None
+}
+
+fn file_properties(path: &Path) -> Result<FileProperties, VividError> {
+    let metadata =
+        fs::metadata(path).map_err(|e| VividError::io("Failed to read file", path, &e))?;
+    let is_symlink = fs::symlink_metadata(path)
+        .map(|m| m.file_type().is_symlink())
+        .unwrap_or(false);
+    let encrypted = metadata.is_file() && encryption::is_encrypted_file(path);
+
+    let text = if metadata.is_file() && !encrypted && metadata.len() <= LARGE_FILE_THRESHOLD {
+        // 不是 UTF-8 的文件视为二进制，不统计
+        fs::read(path)
+            .ok()
+            .and_then(|bytes| String::from_utf8(bytes).ok())
+    } else {
+        None
+    };
+    let counts = text.as_deref().map(|text| {
+        let words = if workspace::is_markdown(path) {
+            stats::document_stats(text).words
+        } else {
+            let (cjk, latin) = stats::count_words(text);
+            cjk + latin
+        };
+        (text.lines().count(), words, text.chars().count())
+    });
+
+    Ok(FileProperties {
+        path: path.to_string_lossy().to_string(),
+        name: crate::file_name_of(path),
+        size: metadata.len(),
+        created: metadata.created().ok().map(iso_time),
+        modified: metadata.modified().ok().map(iso_time),
+        accessed: metadata.accessed().ok().map(iso_time),
+        permissions: permissions(&metadata),
+        read_only: readonly::is_read_only(path) || metadata.permissions().readonly(),
+        is_dir: metadata.is_dir(),
+        is_symlink,
+        is_markdown: workspace::is_markdown(path),
+        encrypted,
+        lines: counts.map(|(lines, _, _)| lines),
+        words: counts.map(|(_, words, _)| words),
+        characters: counts.map(|(_, _, characters)| characters),
+    })
+}
+
+// 读取文件属性，供文档属性面板使用
+#[tauri::command]
+pub async fn get_file_info(app: AppHandle, path: String) -> Result<FileProperties, VividError> {
+    let path_buf = PathBuf::from(&path);
+    if !path_buf.exists() {
+        log::error!("[get_file_info] File does not exist: {}", path);
+        return Err(VividError::not_found(&path_buf));
+    }
+    access::ensure_access(&app, &path_buf, AccessKind::Read)?;
+    let properties = tauri::async_runtime::spawn_blocking(move || file_properties(&path_buf))
+        .await
+        .map_err(|e| format!("File info task failed: {}", e))??;
+    log::debug!(
+        "[get_file_info] {}: {} bytes, modified {:?}, {:?} words",
+        path,
+        properties.size,
+        properties.modified,
+        properties.words
+    );
+    Ok(properties)
+}
@@ -0,0 +1,421 @@
+//! Front matter 解析与修改
+//!
+//! 支持 YAML（`---` 包围）与 TOML（`+++` 包围）两种格式。读取时统一转换为 JSON；
+//! 修改时只替换被修改的顶层字段，其余行（包括注释和字段顺序）保持原样。
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use tauri::AppHandle;
+
+use crate::access::{self, AccessKind};
+use crate::error::VividError;
+
+/// Front matter 格式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FrontMatterFormat {
+    Yaml,
+    Toml,
+}
+
+/// 文档中的 front matter 区块
+#[derive(Debug, Clone)]
+pub struct FrontMatterBlock<'a> {
+    pub format: FrontMatterFormat,
+    /// 分隔线之间的原始内容
+    pub raw: &'a str,
+    /// `raw` 在文档中的起始字节偏移
+    pub raw_start: usize,
+    /// 正文（结束分隔线之后）的起始字节偏移
+    pub body_start: usize,
+}
+
+/// 读取结果
+#[derive(Debug, Serialize, Deserialize)]
+pub struct FrontMatterResult {
+    /// 文档没有 front matter 时为空
+    pub format: Option<FrontMatterFormat>,
+    /// 解析后的字段，没有 front matter 时为空对象
+    pub data: Value,
+    /// 正文的起始字节偏移
+    pub body_start: usize,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct UpdateFrontMatterParams {
+    pub path: String,
+    /// 要修改的字段，值为 `null` 表示删除该字段
+    pub patch: Map<String, Value>,
+}
+
+/// 修改结果
+#[derive(Debug, Serialize, Deserialize)]
+pub struct UpdateFrontMatterResult {
+    /// 写回后的完整文档内容
+    pub content: String,
+    pub data: Value,
+}
+
+/// 查找文档开头的 front matter
+pub fn find_front_matter(content: &str) -> Option<FrontMatterBlock<'_>> {
+    let offset = if content.starts_with('\u{feff}') {
+        3
+    } else {
+        0
+    };
+    let text = &content[offset..];
+    let first_line_end = text.find('\n')?;
+    let (format, closers): (_, &[&str]) = match text[..first_line_end].trim_end() {
+        "---" => (FrontMatterFormat::Yaml, &["---", "..."]),
+        "+++" => (FrontMatterFormat::Toml, &["+++"]),
+        _ => return None,
+    };
+
+    let raw_start = offset + first_line_end + 1;
+    let mut pos = raw_start;
+    for line in content[raw_start..].split_inclusive('\n') {
+        if closers.contains(&line.trim_end()) {
+            return Some(FrontMatterBlock {
+                format,
+                raw: &content[raw_start..pos],
+                raw_start,
+                body_start: pos + line.len(),
+            });
+        }
+        pos += line.len();
+    }
+    None
+}
+
+/// 将 front matter 解析为 JSON 对象
+pub fn parse_block(block: &FrontMatterBlock<'_>) -> Result<Value, String> {
+    if block.raw.trim().is_empty() {
+        return Ok(Value::Object(Map::new()));
+    }
+    let value = match block.format {
+        FrontMatterFormat::Yaml => serde_yaml::from_str::<Value>(block.raw)
+            .map_err(|e| format!("Invalid YAML front matter: {}", e))?,
+        FrontMatterFormat::Toml => {
+            let table = block
+                .raw
+                .parse::<toml_edit::DocumentMut>()
+                .map_err(|e| format!("Invalid TOML front matter: {}", e))?;
+            toml_table_to_json(table.as_table())
+        }
+    };
+    match value {
+        Value::Object(_) => Ok(value),
+        Value::Null => Ok(Value::Object(Map::new())),
+        _ => Err("Front matter must be a key-value mapping".to_string()),
+    }
+}
+
+/// 解析文档的 front matter，没有时返回 `None`
+pub fn parse_front_matter(content: &str) -> Result<Option<(FrontMatterFormat, Value)>, String> {
+    match find_front_matter(content) {
+        Some(block) => Ok(Some((block.format, parse_block(&block)?))),
+        None => Ok(None),
+    }
+}
+
+fn toml_value_to_json(value: &toml_edit::Value) -> Value {
+    match value {
+        toml_edit::Value::String(s) => Value::from(s.value().as_str()),
+        toml_edit::Value::Integer(i) => Value::from(*i.value()),
+        toml_edit::Value::Float(f) => serde_json::Number::from_f64(*f.value())
+            .map(Value::Number)
+            .unwrap_or(Value::Null),
+        toml_edit::Value::Boolean(b) => Value::from(*b.value()),
+        // 日期时间统一转为 RFC 3339 字符串
+        toml_edit::Value::Datetime(d) => Value::from(d.value().to_string()),
+        toml_edit::Value::Array(array) => {
+            Value::Array(array.iter().map(toml_value_to_json).collect())
+        }
+        toml_edit::Value::InlineTable(table) => Value::Object(
+            table
+                .iter()
+                .map(|(k, v)| (k.to_string(), toml_value_to_json(v)))
+                .collect(),
+        ),
+    }
+}
+
+fn toml_item_to_json(item: &toml_edit::Item) -> Value {
+    match item {
+        toml_edit::Item::None => Value::Null,
+        toml_edit::Item::Value(value) => toml_value_to_json(value),
+        toml_edit::Item::Table(table) => toml_table_to_json(table),
+        toml_edit::Item::ArrayOfTables(array) => {
+            Value::Array(array.iter().map(toml_table_to_json).collect())
+        }
+    }
+}
+
+pub fn toml_table_to_json(table: &toml_edit::Table) -> Value {
+    Value::Object(
+        table
+            .iter()
+            .map(|(k, v)| (k.to_string(), toml_item_to_json(v)))
+            .collect(),
+    )
+}
+
+fn json_to_toml_value(value: &Value) -> Option<toml_edit::Value> {
+    Some(match value {
+        Value::Null => return None,
+        Value::Bool(b) => (*b).into(),
+        Value::Number(n) => match n.as_i64() {
+            Some(i) => i.into(),
+            None => n.as_f64()?.into(),
+        },
+        // 形如日期时间的字符串写回为 TOML 日期时间类型
+        Value::String(s) => match s.parse::<toml_edit::Datetime>() {
+            Ok(datetime) => datetime.into(),
+            Err(_) => s.as_str().into(),
+        },
+        Value::Array(items) => {
+            toml_edit::Value::Array(items.iter().filter_map(json_to_toml_value).collect())
+        }
+        Value::Object(map) => {
+            let mut table = toml_edit::InlineTable::new();
+            for (k, v) in map {
+                if let Some(v) = json_to_toml_value(v) {
+                    table.insert(k, v);
+                }
+            }
+            toml_edit::Value::InlineTable(table)
+        }
+    })
+}
+
+/// 修改 TOML front matter；toml_edit 会保留未修改部分的格式与注释
+fn update_toml(raw: &str, patch: &Map<String, Value>) -> Result<String, String> {
+    let mut document = raw
+        .parse::<toml_edit::DocumentMut>()
+        .map_err(|e| format!("Invalid TOML front matter: {}", e))?;
+    for (key, value) in patch {
+        match json_to_toml_value(value) {
+            Some(value) => {
+                // 保留原字段的前后缀（行尾注释等）
+                match document.get_mut(key).and_then(|item| item.as_value_mut()) {
+                    Some(existing) => {
+                        let decor = existing.decor().clone();
+                        *existing = value;
+                        *existing.decor_mut() = decor;
+                    }
+                    None => {
+                        document.insert(key, toml_edit::Item::Value(value));
+                    }
+                }
+            }
+            None => {
+                document.remove(key);
+            }
+        }
+    }
+    Ok(document.to_string())
+}
+
+/// 解析 YAML 顶层字段名，非字段行返回 `None`
+fn yaml_top_level_key(line: &str) -> Option<String> {
+    if line.starts_with(|c: char| c.is_whitespace() || c == '#' || c == '-') {
+        return None;
+    }
+    let (key, rest) = if let Some(quoted) = line.strip_prefix('"') {
+        let end = quoted.find('"')?;
+        (&quoted[..end], &quoted[end + 1..])
+    } else if let Some(quoted) = line.strip_prefix('\'') {
+        let end = quoted.find('\'')?;
+        (&quoted[..end], &quoted[end + 1..])
+    } else {
+        let colon = line.find(':')?;
+        (line[..colon].trim_end(), &line[colon..])
+    };
+    let rest = rest.trim_start();
+    let after_colon = rest.strip_prefix(':')?;
+    if after_colon.is_empty() || after_colon.starts_with(char::is_whitespace) {
+        Some(key.to_string())
+    } else {
+        None
+    }
+}
+
+/// 判断一行是否属于上一个字段的值（缩进行或顶格的列表项）
+fn is_yaml_continuation(line: &str) -> bool {
+    line.starts_with(char::is_whitespace) && !line.trim().is_empty()
+        || line.starts_with("- ")
+        || line.trim_end() == "-"
+}
+
+/// 序列化单个 YAML 字段；`flow` 为 true 且值为标量数组时使用 `[a, b]` 行内格式
+fn yaml_entry(key: &str, value: &Value, flow: bool) -> Result<String, String> {
+    let scalars = value
+        .as_array()
+        .filter(|items| items.iter().all(|v| !v.is_array() && !v.is_object()));
+    if let (true, Some(items)) = (flow, scalars) {
+        let items = items
+            .iter()
+            .map(|v| serde_yaml::to_string(v).map(|s| s.trim_end().to_string()))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| e.to_string())?;
+        let key = serde_yaml::to_string(key).map_err(|e| e.to_string())?;
+        return Ok(format!("{}: [{}]\n", key.trim_end(), items.join(", ")));
+    }
+
+    let mut entry = Map::new();
+    entry.insert(key.to_string(), value.clone());
+    serde_yaml::to_string(&entry).map_err(|e| format!("Failed to serialize {}: {}", key, e))
+}
+
+/// 修改 YAML front matter：逐行定位顶层字段，只替换被修改字段的行
+fn update_yaml(raw: &str, patch: &Map<String, Value>) -> Result<String, String> {
+    let lines: Vec<&str> = raw.split_inclusive('\n').collect();
+    let mut out = String::with_capacity(raw.len());
+    let mut handled: Vec<&str> = Vec::new();
+    let mut i = 0;
+
+    while i < lines.len() {
+        let line = lines[i];
+        let key = yaml_top_level_key(line);
+        let Some((key, value)) = key.and_then(|k| patch.get_key_value(&k)) else {
+            out.push_str(line);
+            i += 1;
+            continue;
+        };
+
+        // 跳过该字段原有的值（包括多行的列表 / 映射 / 块字符串）
+        let flow = line.contains('[');
+        let mut end = i + 1;
+        while end < lines.len() {
+            if is_yaml_continuation(lines[end]) {
+                end += 1;
+                continue;
+            }
+            // 空行之后仍是缩进内容时（块字符串中的空行）视为同一个值
+            let next = lines[end..].iter().position(|l| !l.trim().is_empty());
+            match next {
+                Some(offset) if offset > 0 && is_yaml_continuation(lines[end + offset]) => {
+                    end += offset;
+                }
+                _ => break,
+            }
+        }
+
+        if !value.is_null() {
+            out.push_str(&yaml_entry(key, value, flow)?);
+        }
+        handled.push(key.as_str());
+        i = end;
+    }
+
+    for (key, value) in patch {
+        if value.is_null() || handled.contains(&key.as_str()) {
+            continue;
+        }
+        if !out.is_empty() && !out.ends_with('\n') {
+            out.push('\n');
+        }
+        out.push_str(&yaml_entry(key, value, false)?);
+    }
+    Ok(out)
+}
+
+/// 将补丁应用到文档的 front matter，返回新的文档内容；没有 front matter 时新建 YAML 区块
+pub fn apply_front_matter_patch(
+    content: &str,
+    patch: &Map<String, Value>,
+) -> Result<String, String> {
+    let Some(block) = find_front_matter(content) else {
+        let raw = update_yaml("", patch)?;
+        if raw.is_empty() {
+            return Ok(content.to_string());
+        }
+        return Ok(format!("---\n{}---\n\n{}", raw, content));
+    };
+
+    // 先确认原内容可以解析，避免在损坏的 front matter 上继续修改
+    parse_block(&block)?;
+    let raw = match block.format {
+        FrontMatterFormat::Yaml => update_yaml(block.raw, patch)?,
+        FrontMatterFormat::Toml => update_toml(block.raw, patch)?,
+    };
+    let closing_start = block.raw_start + block.raw.len();
+    let mut raw = raw;
+    if !raw.is_empty() && !raw.ends_with('\n') {
+        raw.push('\n');
+    }
+
+    Ok(format!(
+        "{}{}{}",
+        &content[..block.raw_start],
+        raw,
+        &content[closing_start..]
+    ))
+}
+
+// 读取文档的 front matter
+#[tauri::command]
+pub fn read_front_matter(app: AppHandle, path: String) -> Result<FrontMatterResult, VividError> {
+    access::ensure_access(&app, Path::new(&path), AccessKind::Read)?;
+    let content = fs::read_to_string(&path).map_err(|e| {
+        log::error!("[read_front_matter] Failed to read {}: {}", path, e);
+        format!("Failed to read file: {}", e)
+    })?;
+
+    let result = match find_front_matter(&content) {
+        Some(block) => FrontMatterResult {
+            format: Some(block.format),
+            data: parse_block(&block)?,
+            body_start: block.body_start,
+        },
+        None => FrontMatterResult {
+            format: None,
+            data: Value::Object(Map::new()),
+            body_start: 0,
+        },
+    };
+    log::debug!("[read_front_matter] {} -> {:?}", path, result.format);
+    Ok(result)
+}
+
+// 修改文档的 front matter 并写回文件
+#[tauri::command]
+pub fn update_front_matter(
+    app: AppHandle,
+    params: UpdateFrontMatterParams,
+) -> Result<UpdateFrontMatterResult, VividError> {
+    let path = PathBuf::from(&params.path);
+    access::ensure_access(&app, &path, AccessKind::Write)?;
+    log::info!("[update_front_matter] Updating {}", params.path);
+    log::debug!(
+        "[update_front_matter] Keys: {:?}",
+        params.patch.keys().collect::<Vec<_>>()
+    );
+
+    let content = fs::read_to_string(&path).map_err(|e| {
+        log::error!("[update_front_matter] Failed to read: {}", e);
+        format!("Failed to read file: {}", e)
+    })?;
+    let updated = apply_front_matter_patch(&content, &params.patch).map_err(|e| {
+        log::error!("[update_front_matter] {}", e);
+        e
+    })?;
+    let data = parse_front_matter(&updated)?
+        .map(|(_, data)| data)
+        .unwrap_or_else(|| Value::Object(Map::new()));
+
+    if updated != content {
+        fs::write(&path, &updated).map_err(|e| {
+            log::error!("[update_front_matter] Failed to write: {}", e);
+            format!("Failed to save file: {}", e)
+        })?;
+    }
+    log::info!("[update_front_matter] ✓ Success: {}", params.path);
+    Ok(UpdateFrontMatterResult {
+        content: updated,
+        data,
+    })
+}
@@ -0,0 +1,468 @@
+//! Git 集成
+//!
+//! 通过调用系统中的 `git` 命令实现，不引入 libgit2，
+//! 行为与用户在终端中看到的保持一致（包括 `.gitignore`、凭据与钩子配置）。
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::time::Instant;
+
+use serde::{Deserialize, Serialize};
+use tauri::AppHandle;
+
+use crate::access::{self, AccessKind};
+use crate::error::VividError;
+use crate::paths;
+
+/// 文件的 Git 状态
+#[derive(Debug, Serialize, Deserialize)]
+pub struct GitFileStatus {
+    /// 文件绝对路径
+    pub path: String,
+    /// 重命名前的路径
+    pub original_path: Option<String>,
+    /// `modified` / `added` / `deleted` / `renamed` / `copied` / `untracked` / `conflicted` / `ignored`
+    pub status: String,
+    /// 是否已暂存
+    pub staged: bool,
+}
+
+/// 仓库状态
+#[derive(Debug, Serialize, Deserialize)]
+pub struct GitStatus {
+    pub root: String,
+    pub branch: Option<String>,
+    pub upstream: Option<String>,
+    pub ahead: u32,
+    pub behind: u32,
+    pub files: Vec<GitFileStatus>,
+}
+
+/// 行级变更，用于编辑器行号旁的标记
+#[derive(Debug, Serialize, Deserialize)]
+pub struct GitLineChange {
+    /// `added` / `modified` / `deleted`
+    pub kind: String,
+    /// 起始行（从 1 开始）；删除时为被删内容之后的行
+    pub start_line: usize,
+    pub line_count: usize,
+}
+
+/// 单个文件相对 HEAD 的差异
+#[derive(Debug, Serialize, Deserialize)]
+pub struct GitDiff {
+    pub path: String,
+    /// 统一格式的 diff 文本
+    pub diff: String,
+    pub changes: Vec<GitLineChange>,
+    /// 文件未被 Git 跟踪
+    pub untracked: bool,
+}
+
+/// 提交记录
+#[derive(Debug, Serialize, Deserialize)]
+pub struct GitCommit {
+    pub hash: String,
+    pub short_hash: String,
+    pub author: String,
+    pub email: String,
+    /// 提交时间（Unix 秒）
+    pub timestamp: i64,
+    pub subject: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct GitCommitParams {
+    pub paths: Vec<String>,
+    pub message: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct GitLogParams {
+    pub path: String,
+    /// 返回的最大条数，默认 50
+    pub limit: Option<usize>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct GitCheckoutVersionParams {
+    pub path: String,
+    /// 提交哈希、分支名等任意 Git 版本标识
+    pub rev: String,
+}
+
+/// 在指定目录执行 git 命令，返回标准输出
+pub(crate) fn run_git(dir: &Path, args: &[&str]) -> Result<String, String> {
+    run_git_with_env(dir, args, &[])
+}
+
+/// 带额外环境变量执行 git 命令（如通过 `GIT_CONFIG_*` 传入不应出现在命令行中的凭据）
+pub(crate) fn run_git_with_env(
+    dir: &Path,
+    args: &[&str],
+    env: &[(&str, &str)],
+) -> Result<String, String> {
+    let mut command = Command::new("git");
+    command.arg("-C").arg(dir).args(args);
+    command.envs(env.iter().copied());
+    // 避免 Windows 上弹出控制台窗口
+    #[cfg(windows)]
+    {
+        use std::os::windows::process::CommandExt;
+        const CREATE_NO_WINDOW: u32 = 0x0800_0000;
+        command.creation_flags(CREATE_NO_WINDOW);
+    }
+
+    log::debug!("[git] git -C {:?} {}", dir, args.join(" "));
+    let output = command.output().map_err(|e| {
+        if e.kind() == std::io::ErrorKind::NotFound {
+            "Git is not installed or not in PATH".to_string()
+        } else {
+            format!("Failed to run git: {}", e)
+        }
+    })?;
+
+    if output.status.success() {
+        Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+    } else {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        Err(format!("git {} failed: {}", args[0], stderr.trim()))
+    }
+}
+
+/// 文件或目录所在仓库的根目录
+fn repo_root(path: &Path) -> Result<PathBuf, String> {
+    let dir = if path.is_dir() {
+        path
+    } else {
+        path.parent().unwrap_or(Path::new("."))
+    };
+    let root = run_git(dir, &["rev-parse", "--show-toplevel"])?;
+    Ok(PathBuf::from(root.trim()))
+}
+
+/// 文件相对仓库根目录的路径（Git 使用 `/` 分隔）
+fn repo_relative(root: &Path, path: &Path) -> Result<String, String> {
+    let root = paths::canonicalize(root);
+    // 已删除的文件也能规范化（按其上级目录）
+    let absolute = paths::canonicalize(path);
+    let relative = absolute
+        .strip_prefix(&root)
+        .map_err(|_| format!("{:?} is not inside repository {:?}", path, root))?;
+    Ok(relative.to_string_lossy().replace('\\', "/"))
+}
+
+fn status_name(code: char) -> &'static str {
+    match code {
+        'M' | 'T' => "modified",
+        'A' => "added",
+        'D' => "deleted",
+        'R' => "renamed",
+        'C' => "copied",
+        'U' => "conflicted",
+        '?' => "untracked",
+        '!' => "ignored",
+        _ => "modified",
+    }
+}
+
+/// 解析 `## main...origin/main [ahead 1, behind 2]`
+fn parse_branch_line(line: &str, status: &mut GitStatus) {
+    let line = line.trim_start_matches("## ");
+    let (refs, counts) = match line.split_once(" [") {
+        Some((refs, counts)) => (refs, Some(counts.trim_end_matches(']'))),
+        None => (line, None),
+    };
+    let (branch, upstream) = match refs.split_once("...") {
+        Some((branch, upstream)) => (branch, Some(upstream)),
+        None => (refs, None),
+    };
+    let branch = branch.strip_prefix("No commits yet on ").unwrap_or(branch);
+    if !branch.starts_with("HEAD (no branch)") {
+        status.branch = Some(branch.to_string());
+    }
+    status.upstream = upstream.map(str::to_string);
+
+    for part in counts.unwrap_or("").split(", ") {
+        if let Some(n) = part.strip_prefix("ahead ") {
+            status.ahead = n.parse().unwrap_or(0);
+        } else if let Some(n) = part.strip_prefix("behind ") {
+            status.behind = n.parse().unwrap_or(0);
+        }
+    }
+}
+
+/// 解析 `git status --porcelain=v1 -z --branch` 的输出
+fn parse_status(root: &Path, output: &str) -> GitStatus {
+    let mut status = GitStatus {
+        root: root.to_string_lossy().to_string(),
+        branch: None,
+        upstream: None,
+        ahead: 0,
+        behind: 0,
+        files: Vec::new(),
+    };
+
+    let mut entries = output.split('\0').filter(|e| !e.is_empty());
+    while let Some(entry) = entries.next() {
+        if entry.starts_with("## ") {
+            parse_branch_line(entry, &mut status);
+            continue;
+        }
+        if entry.len() < 4 {
+            continue;
+        }
+        let mut codes = entry.chars();
+        let index = codes.next().unwrap_or(' ');
+        let worktree = codes.next().unwrap_or(' ');
+        let path = &entry[3..];
+        // 重命名 / 复制的条目后面紧跟原路径
+        let original_path = if matches!(index, 'R' | 'C') {
+            entries
+                .next()
+                .map(|p| root.join(p).to_string_lossy().to_string())
+        } else {
+            None
+        };
+
+        let conflicted = index == 'U'
+            || worktree == 'U'
+            || (index == 'A' && worktree == 'A')
+            || (index == 'D' && worktree == 'D');
+        let (code, staged) = if conflicted {
+            ('U', false)
+        } else if worktree != ' ' {
+            (worktree, index != ' ' && index != '?' && index != '!')
+        } else {
+            (index, true)
+        };
+
+        status.files.push(GitFileStatus {
+            path: root.join(path).to_string_lossy().to_string(),
+            original_path,
+            status: status_name(code).to_string(),
+            staged,
+        });
+    }
+    status
+}
+
+/// 从 `-U0` 格式的 diff 中提取行级变更
+fn parse_line_changes(diff: &str) -> Vec<GitLineChange> {
+    fn parse_range(range: &str) -> (usize, usize) {
+        let range = &range[1..];
+        match range.split_once(',') {
+            Some((start, count)) => (start.parse().unwrap_or(0), count.parse().unwrap_or(0)),
+            None => (range.parse().unwrap_or(0), 1),
+        }
+    }
+
+    diff.lines()
+        .filter_map(|line| line.strip_prefix("@@ "))
+        .filter_map(|header| {
+            let mut parts = header.split_whitespace();
+            let (_, old_count) = parse_range(parts.next()?);
+            let (new_start, new_count) = parse_range(parts.next()?);
+            Some(if old_count == 0 {
+                GitLineChange {
+                    kind: "added".to_string(),
+                    start_line: new_start,
+                    line_count: new_count,
+                }
+            } else if new_count == 0 {
+                GitLineChange {
+                    kind: "deleted".to_string(),
+                    start_line: new_start + 1,
+                    line_count: old_count,
+                }
+            } else {
+                GitLineChange {
+                    kind: "modified".to_string(),
+                    start_line: new_start,
+                    line_count: new_count,
+                }
+            })
+        })
+        .collect()
+}
+
+// 获取仓库状态
+#[tauri::command]
+pub fn git_status(app: AppHandle, root: String) -> Result<GitStatus, VividError> {
+    let start = Instant::now();
+    access::ensure_access(&app, Path::new(&root), AccessKind::Read)?;
+    let root = repo_root(Path::new(&root))?;
+    let output = run_git(&root, &["status", "--porcelain=v1", "-z", "--branch"])?;
+    let status = parse_status(&root, &output);
+    log::info!(
+        "[git_status] ✓ Success: {:?} on {:?}, {} changed file(s) in {:?}",
+        root,
+        status.branch,
+        status.files.len(),
+        start.elapsed()
+    );
+    Ok(status)
+}
+
+// 获取单个文件相对 HEAD 的差异（含未暂存修改）
+#[tauri::command]
+pub fn git_diff(app: AppHandle, path: String) -> Result<GitDiff, VividError> {
+    let file = PathBuf::from(&path);
+    access::ensure_access(&app, &file, AccessKind::Read)?;
+    let root = repo_root(&file)?;
+    let relative = repo_relative(&root, &file)?;
+
+    let tracked = run_git(&root, &["ls-files", "--error-unmatch", "--", &relative]).is_ok();
+    if !tracked {
+        // 未跟踪的文件整体视为新增
+        let lines = fs::read_to_string(&file)
+            .map(|c| c.lines().count())
+            .unwrap_or(0);
+        return Ok(GitDiff {
+            path,
+            diff: String::new(),
+            changes: vec![GitLineChange {
+                kind: "added".to_string(),
+                start_line: 1,
+                line_count: lines,
+            }],
+            untracked: true,
+        });
+    }
+
+    // 新仓库还没有 HEAD 时与空树比较
+    let base = if run_git(&root, &["rev-parse", "--verify", "--quiet", "HEAD"]).is_ok() {
+        "HEAD"
+    } else {
+        "4b825dc642cb6eb9a060e54bf8d69288fbee4904"
+    };
+    let diff = run_git(&root, &["diff", "--no-color", base, "--", &relative])?;
+    let compact = run_git(&root, &["diff", "--no-color", "-U0", base, "--", &relative])?;
+
+    Ok(GitDiff {
+        path,
+        changes: parse_line_changes(&compact),
+        diff,
+        untracked: false,
+    })
+}
+
+// 提交指定文件，返回新提交的哈希
+#[tauri::command]
+pub fn git_commit(app: AppHandle, params: GitCommitParams) -> Result<String, VividError> {
+    let start = Instant::now();
+    let message = params.message.trim();
+    if message.is_empty() {
+        return Err(VividError::invalid_input("Commit message cannot be empty"));
+    }
+    let first = params
+        .paths
+        .first()
+        .ok_or_else(|| "No files to commit".to_string())?;
+    for path in &params.paths {
+        access::ensure_access(&app, Path::new(path), AccessKind::Write)?;
+    }
+    let root = repo_root(Path::new(first))?;
+
+    let relative: Vec<String> = params
+        .paths
+        .iter()
+        .map(|p| repo_relative(&root, Path::new(p)))
+        .collect::<Result<_, _>>()?;
+    let mut add_args = vec!["add", "-A", "--"];
+    add_args.extend(relative.iter().map(String::as_str));
+    run_git(&root, &add_args)?;
+
+    // 只提交这些路径，不带上用户在别处暂存的改动
+    let mut commit_args = vec!["commit", "-m", message, "--only", "--"];
+    commit_args.extend(relative.iter().map(String::as_str));
+    run_git(&root, &commit_args).map_err(|e| {
+        log::error!("[git_commit] {}", e);
+        e
+    })?;
+
+    let hash = run_git(&root, &["rev-parse", "HEAD"])?.trim().to_string();
+    log::info!(
+        "[git_commit] ✓ Success: {} ({} file(s)) in {:?}",
+        hash,
+        relative.len(),
+        start.elapsed()
+    );
+    Ok(hash)
+}
+
+// 获取文件的提交历史（跟踪重命名）
+#[tauri::command]
+pub fn git_log(app: AppHandle, params: GitLogParams) -> Result<Vec<GitCommit>, VividError> {
+    let file = PathBuf::from(&params.path);
+    access::ensure_access(&app, &file, AccessKind::Read)?;
+    let root = repo_root(&file)?;
+    let relative = repo_relative(&root, &file)?;
+    let limit = params.limit.unwrap_or(50).max(1).to_string();
+
+    let output = run_git(
+        &root,
+        &[
+            "log",
+            "--follow",
+            "-n",
+            &limit,
+            "--format=%H%x1f%h%x1f%an%x1f%ae%x1f%at%x1f%s%x1e",
+            "--",
+            &relative,
+        ],
+    )?;
+
+    let commits: Vec<GitCommit> = output
+        .split('\x1e')
+        .filter_map(|record| {
+            let fields: Vec<&str> = record.trim().split('\x1f').collect();
+            if fields.len() < 6 {
+                return None;
+            }
+            Some(GitCommit {
+                hash: fields[0].to_string(),
+                short_hash: fields[1].to_string(),
+                author: fields[2].to_string(),
+                email: fields[3].to_string(),
+                timestamp: fields[4].parse().unwrap_or(0),
+                subject: fields[5].to_string(),
+            })
+        })
+        .collect();
+    log::debug!("[git_log] {} commit(s) for {}", commits.len(), params.path);
+    Ok(commits)
+}
+
+// 将文件恢复为指定版本的内容（写入工作区，不修改暂存区），返回该版本内容
+#[tauri::command]
+pub fn git_checkout_version(
+    app: AppHandle,
+    params: GitCheckoutVersionParams,
+) -> Result<String, VividError> {
+    let file = PathBuf::from(&params.path);
+    access::ensure_access(&app, &file, AccessKind::Write)?;
+    let root = repo_root(&file)?;
+    let relative = repo_relative(&root, &file)?;
+    if params.rev.starts_with('-') {
+        return Err(VividError::invalid_input(format!(
+            "Invalid revision: {}",
+            params.rev
+        )));
+    }
+
+    let spec = format!("{}:{}", params.rev, relative);
+    let content = run_git(&root, &["show", &spec])?;
+    fs::write(&file, &content).map_err(|e| {
+        log::error!("[git_checkout_version] Failed to write {:?}: {}", file, e);
+        format!("Failed to write file: {}", e)
+    })?;
+
+    log::info!(
+        "[git_checkout_version] ✓ Success: {} restored to {}",
+        params.path,
+        params.rev
+    );
+    Ok(content)
+}
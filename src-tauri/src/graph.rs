@@ -0,0 +1,277 @@
+//! 链接关系图
+//!
+//! 基于链接索引生成整个工作区的关系图（节点为文件，边为链接），
+//! 供前端绘制图谱视图，也可以导出为 JSON 或 GraphML 供其它工具使用。
+
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager};
+
+use crate::access::{self, AccessKind};
+use crate::error::VividError;
+use crate::export::ExportResult;
+use crate::links::{self, FileLinks, LinkIndex, LinkKind, Resolver};
+use crate::markdown::escape_html;
+use crate::workspace::{self, Workspace};
+
+/// 图中的节点
+#[derive(Debug, Serialize, Deserialize)]
+pub struct GraphNode {
+    /// 相对于根目录的路径（使用 `/` 分隔）；未解析的链接目标为链接文本
+    pub id: String,
+    pub name: String,
+    /// 文件的完整路径，未解析的链接目标为空
+    pub path: Option<String>,
+    /// 指向尚不存在的笔记
+    pub unresolved: bool,
+    /// 出链数量
+    pub outgoing: usize,
+    /// 入链数量
+    pub incoming: usize,
+}
+
+/// 图中的边，同一对节点之间的多条链接合并为一条
+#[derive(Debug, Serialize, Deserialize)]
+pub struct GraphEdge {
+    pub source: String,
+    pub target: String,
+    /// 链接次数
+    pub count: usize,
+}
+
+/// 链接关系图
+#[derive(Debug, Serialize, Deserialize)]
+pub struct LinkGraph {
+    pub nodes: Vec<GraphNode>,
+    pub edges: Vec<GraphEdge>,
+}
+
+/// 导出格式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum GraphFormat {
+    #[default]
+    Json,
+    Graphml,
+}
+
+/// 导出参数
+#[derive(Debug, Deserialize)]
+pub struct ExportLinkGraphParams {
+    pub root: String,
+    /// 默认 JSON
+    pub format: Option<GraphFormat>,
+    /// 输出文件路径，默认写入根目录下的 `link-graph.json` / `link-graph.graphml`
+    pub output: Option<String>,
+}
+
+fn node_id(root: &Path, path: &Path) -> String {
+    path.strip_prefix(root)
+        .unwrap_or(path)
+        .to_string_lossy()
+        .replace('\\', "/")
+}
+
+fn file_name(path: &Path) -> String {
+    path.file_stem()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_default()
+}
+
+/// 根据链接索引生成关系图，只统计指向 Markdown 文件或未解析 Wiki 链接的边
+pub fn build_graph(root: &Path, files: &HashMap<PathBuf, FileLinks>) -> LinkGraph {
+    let resolver = Resolver::new(Some(root), files);
+    let mut nodes: BTreeMap<String, GraphNode> = BTreeMap::new();
+    let mut edges: BTreeMap<(String, String), usize> = BTreeMap::new();
+
+    for path in files.keys().filter(|p| p.starts_with(root)) {
+        let id = node_id(root, path);
+        nodes.insert(
+            id.clone(),
+            GraphNode {
+                name: file_name(path),
+                path: Some(path.to_string_lossy().to_string()),
+                id,
+                unresolved: false,
+                outgoing: 0,
+                incoming: 0,
+            },
+        );
+    }
+
+    for (source, file) in files.iter().filter(|(p, _)| p.starts_with(root)) {
+        let source_id = node_id(root, source);
+        for link in &file.links {
+            let target_id = match resolver.resolve_exact(source, link) {
+                Some(target) if target == *source => continue,
+                Some(target) if files.contains_key(&target) => node_id(root, &target),
+                // 未解析的 Wiki 链接显示为占位节点；普通链接和图片等附件不计入
+                None if link.kind == LinkKind::Wiki && !link.target.is_empty() => {
+                    let id = link.target.clone();
+                    nodes.entry(id.clone()).or_insert_with(|| GraphNode {
+                        name: id.rsplit('/').next().unwrap_or(&id).to_string(),
+                        id: id.clone(),
+                        path: None,
+                        unresolved: true,
+                        outgoing: 0,
+                        incoming: 0,
+                    });
+                    id
+                }
+                _ => continue,
+            };
+            *edges.entry((source_id.clone(), target_id)).or_insert(0) += 1;
+        }
+    }
+
+    for ((source, target), count) in &edges {
+        if let Some(node) = nodes.get_mut(source) {
+            node.outgoing += count;
+        }
+        if let Some(node) = nodes.get_mut(target) {
+            node.incoming += count;
+        }
+    }
+
+    LinkGraph {
+        nodes: nodes.into_values().collect(),
+        edges: edges
+            .into_iter()
+            .map(|((source, target), count)| GraphEdge {
+                source,
+                target,
+                count,
+            })
+            .collect(),
+    }
+}
+
+/// 生成 GraphML 文档
+pub fn to_graphml(graph: &LinkGraph) -> String {
+    let mut out = String::from(
+        r#"<?xml version="1.0" encoding="UTF-8"?>
+<graphml xmlns="http://graphml.graphdrawing.org/xmlns">
+  <key id="name" for="node" attr.name="name" attr.type="string"/>
+  <key id="path" for="node" attr.name="path" attr.type="string"/>
+  <key id="unresolved" for="node" attr.name="unresolved" attr.type="boolean"/>
+  <key id="count" for="edge" attr.name="count" attr.type="int"/>
+  <graph id="links" edgedefault="directed">
+"#,
+    );
+    for node in &graph.nodes {
+        out.push_str(&format!(
+            "    <node id=\"{}\">\n      <data key=\"name\">{}</data>\n",
+            escape_html(&node.id),
+            escape_html(&node.name)
+        ));
+        if let Some(path) = &node.path {
+            out.push_str(&format!(
+                "      <data key=\"path\">{}</data>\n",
+                escape_html(path)
+            ));
+        }
+        out.push_str(&format!(
+            "      <data key=\"unresolved\">{}</data>\n    </node>\n",
+            node.unresolved
+        ));
+    }
+    for (i, edge) in graph.edges.iter().enumerate() {
+        out.push_str(&format!(
+            "    <edge id=\"e{}\" source=\"{}\" target=\"{}\">\n      <data key=\"count\">{}</data>\n    </edge>\n",
+            i,
+            escape_html(&edge.source),
+            escape_html(&edge.target),
+            edge.count
+        ));
+    }
+    out.push_str("  </graph>\n</graphml>\n");
+    out
+}
+
+/// 生成关系图：根目录为当前工作区时直接使用索引，否则临时扫描
+fn graph_for_root(app: &AppHandle, root: &Path) -> Result<LinkGraph, String> {
+    if !root.is_dir() {
+        return Err(format!("Directory does not exist: {}", root.display()));
+    }
+    let workspace_root = app.state::<Workspace>().root();
+    let files = if workspace_root.as_deref() == Some(root) {
+        app.state::<LinkIndex>().snapshot()?
+    } else {
+        links::scan_files(&workspace::markdown_files(root))
+    };
+    Ok(build_graph(root, &files))
+}
+
+// 获取目录下所有笔记的链接关系图
+#[tauri::command]
+pub async fn get_link_graph(app: AppHandle, root: String) -> Result<LinkGraph, VividError> {
+    let start = Instant::now();
+    let root = PathBuf::from(root);
+    access::ensure_access(&app, &root, AccessKind::Read)?;
+    let graph = tauri::async_runtime::spawn_blocking(move || graph_for_root(&app, &root))
+        .await
+        .map_err(|e| format!("Link graph task failed: {}", e))?
+        .map_err(|e| {
+            log::error!("[get_link_graph] {}", e);
+            e
+        })?;
+    log::info!(
+        "[get_link_graph] ✓ Success: {} node(s), {} edge(s) in {:?}",
+        graph.nodes.len(),
+        graph.edges.len(),
+        start.elapsed()
+    );
+    Ok(graph)
+}
+
+// 导出链接关系图为 JSON 或 GraphML
+#[tauri::command]
+pub async fn export_link_graph(
+    app: AppHandle,
+    params: ExportLinkGraphParams,
+) -> Result<ExportResult, VividError> {
+    let root = PathBuf::from(&params.root);
+    let format = params.format.unwrap_or_default();
+    log::info!("[export_link_graph] Exporting {:?} as {:?}", root, format);
+
+    let output = params.output.map(PathBuf::from).unwrap_or_else(|| {
+        root.join(match format {
+            GraphFormat::Json => "link-graph.json",
+            GraphFormat::Graphml => "link-graph.graphml",
+        })
+    });
+
+    access::ensure_access(&app, &root, AccessKind::Read)?;
+    access::ensure_access(&app, &output, AccessKind::Write)?;
+    let graph_root = root.clone();
+    let graph = tauri::async_runtime::spawn_blocking(move || graph_for_root(&app, &graph_root))
+        .await
+        .map_err(|e| format!("Link graph task failed: {}", e))?;
+    let graph = match graph {
+        Ok(graph) => graph,
+        Err(e) => {
+            log::error!("[export_link_graph] {}", e);
+            return Ok(ExportResult::failed(e));
+        }
+    };
+
+    let data = match format {
+        GraphFormat::Json => serde_json::to_string_pretty(&graph).map_err(|e| e.to_string())?,
+        GraphFormat::Graphml => to_graphml(&graph),
+    };
+    if let Err(e) = fs::write(&output, data) {
+        log::error!(
+            "[export_link_graph] Failed to write output {:?}: {}",
+            output,
+            e
+        );
+        return Ok(ExportResult::failed(format!("Failed to write file: {}", e)));
+    }
+
+    log::info!("[export_link_graph] ✓ Success: {:?}", output);
+    Ok(ExportResult::succeeded(&output))
+}
@@ -0,0 +1,45 @@
+//! 代码块语法高亮
+//!
+//! 基于 syntect 生成带内联样式的 HTML，导出后的文件无需额外的高亮脚本或样式表。
+
+use std::sync::OnceLock;
+
+use syntect::highlighting::ThemeSet;
+use syntect::html::highlighted_html_for_string;
+use syntect::parsing::SyntaxSet;
+
+/// 浅色主题默认使用的高亮配色
+pub const LIGHT_THEME: &str = "InspiredGitHub";
+/// 深色主题默认使用的高亮配色
+pub const DARK_THEME: &str = "base16-ocean.dark";
+
+fn syntax_set() -> &'static SyntaxSet {
+    static SYNTAXES: OnceLock<SyntaxSet> = OnceLock::new();
+    SYNTAXES.get_or_init(SyntaxSet::load_defaults_newlines)
+}
+
+fn theme_set() -> &'static ThemeSet {
+    static THEMES: OnceLock<ThemeSet> = OnceLock::new();
+    THEMES.get_or_init(ThemeSet::load_defaults)
+}
+
+/// 将代码高亮为 `<pre>` HTML 片段
+///
+/// 语言或主题无法识别时返回 `None`，由调用方回退为普通代码块。
+pub fn highlight_code(code: &str, lang: &str, theme: &str) -> Option<String> {
+    let lang = lang.split_whitespace().next().unwrap_or("");
+    if lang.is_empty() {
+        return None;
+    }
+    let syntaxes = syntax_set();
+    let syntax = syntaxes.find_syntax_by_token(lang)?;
+    let theme = theme_set().themes.get(theme)?;
+
+    match highlighted_html_for_string(code, syntaxes, syntax, theme) {
+        Ok(html) => Some(html),
+        Err(e) => {
+            log::warn!("[highlight] Failed to highlight {} block: {}", lang, e);
+            None
+        }
+    }
+}
@@ -0,0 +1,268 @@
+//! 后端消息本地化
+//!
+//! 错误消息在代码中统一用英文书写（日志也使用英文），返回给前端时再按当前语言翻译。
+//! 消息目录是 `locales/` 下的 JSON 文件：`messages` 以英文短语为源文本，错误消息以某个短语
+//! 开头时翻译该短语并保留冒号后的细节（路径、系统错误等）；没有匹配的短语时在原消息前加上
+//! 错误类别的译名。新增语言只需要添加一个目录文件并在 `Locale` 中登记。
+//!
+//! 当前语言由设置中的 `language` 决定，为空或 `auto` 时按操作系统的首选语言协商，
+//! 都不支持时使用英文。
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU8, Ordering};
+use std::sync::OnceLock;
+
+use serde::{Deserialize, Serialize};
+
+const EN_US_CATALOG: &str = include_str!("locales/en-US.json");
+const ZH_CN_CATALOG: &str = include_str!("locales/zh-CN.json");
+
+/// 支持的语言
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Locale {
+    #[serde(rename = "en-US")]
+    EnUs,
+    #[serde(rename = "zh-CN")]
+    ZhCn,
+}
+
+impl Locale {
+    pub const ALL: [Locale; 2] = [Locale::EnUs, Locale::ZhCn];
+
+    /// 解析 `zh`、`zh-CN`、`zh_Hans_CN.UTF-8`、`en-GB` 等写法，只看语言部分
+    pub fn parse(tag: &str) -> Option<Locale> {
+        let tag = tag.trim().to_ascii_lowercase().replace('_', "-");
+        let language = tag.split(['-', '.', '@']).next().unwrap_or("");
+        match language {
+            "zh" => Some(Locale::ZhCn),
+            "en" => Some(Locale::EnUs),
+            _ => None,
+        }
+    }
+
+    pub fn tag(self) -> &'static str {
+        match self {
+            Locale::EnUs => "en-US",
+            Locale::ZhCn => "zh-CN",
+        }
+    }
+
+    fn catalog(self) -> &'static Catalog {
+        let (index, source) = match self {
+            Locale::EnUs => (0, EN_US_CATALOG),
+            Locale::ZhCn => (1, ZH_CN_CATALOG),
+        };
+        CATALOGS[index].get_or_init(|| {
+            serde_json::from_str(source).unwrap_or_else(|e| {
+                log::error!("[i18n] Invalid {} catalog: {}", self.tag(), e);
+                Catalog::default()
+            })
+        })
+    }
+}
+
+/// 消息目录
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct Catalog {
+    /// 短语与细节之间的分隔符
+    separator: String,
+    /// 错误类别（`VividError` 的 `code`）的译名
+    codes: HashMap<String, String>,
+    /// 消息 id → 短语
+    messages: HashMap<String, String>,
+}
+
+static CATALOGS: [OnceLock<Catalog>; 2] = [OnceLock::new(), OnceLock::new()];
+static CURRENT: AtomicU8 = AtomicU8::new(0);
+
+/// 当前语言
+pub fn current() -> Locale {
+    match CURRENT.load(Ordering::Relaxed) {
+        1 => Locale::ZhCn,
+        _ => Locale::EnUs,
+    }
+}
+
+fn set_current(locale: Locale) {
+    let value = match locale {
+        Locale::EnUs => 0,
+        Locale::ZhCn => 1,
+    };
+    if CURRENT.swap(value, Ordering::Relaxed) != value {
+        log::info!("[i18n] Locale set to {}", locale.tag());
+    }
+}
+
+/// 操作系统的首选语言（按优先级排列）
+pub fn system_locales() -> Vec<String> {
+    sys_locale::get_locales().collect()
+}
+
+/// 协商语言：设置中的语言优先，其次是系统首选语言中第一个受支持的
+pub fn negotiate(setting: &str) -> Locale {
+    let setting = setting.trim();
+    if !setting.is_empty() && !setting.eq_ignore_ascii_case("auto") {
+        if let Some(locale) = Locale::parse(setting) {
+            return locale;
+        }
+        log::warn!("[i18n] Unsupported language {:?}", setting);
+    }
+    system_locales()
+        .iter()
+        .find_map(|tag| Locale::parse(tag))
+        .unwrap_or(Locale::EnUs)
+}
+
+/// 按设置中的 `language` 切换当前语言
+pub fn apply(setting: &str) {
+    set_current(negotiate(setting));
+}
+
+/// 翻译错误消息：`code` 为错误类别，`message` 为英文消息
+pub fn localize(locale: Locale, code: &str, message: &str) -> String {
+    let source = Locale::EnUs.catalog();
+    let target = locale.catalog();
+    // 最长的匹配短语，避免 `Failed to read file` 抢先匹配更具体的短语
+    let matched = source
+        .messages
+        .iter()
+        .filter(|(_, phrase)| {
+            message == phrase.as_str()
+                || message
+                    .strip_prefix(phrase.as_str())
+                    .is_some_and(|rest| rest.starts_with(": "))
+        })
+        .max_by_key(|(_, phrase)| phrase.len());
+
+    match matched {
+        Some((id, phrase)) => {
+            let translated = target.messages.get(id).unwrap_or(phrase);
+            match message.get(phrase.len() + 2..) {
+                Some(detail) if !detail.is_empty() => {
+                    format!("{}{}{}", translated, target.separator, detail)
+                }
+                _ => translated.clone(),
+            }
+        }
+        None if locale == Locale::EnUs => message.to_string(),
+        None => match target.codes.get(code) {
+            Some(label) => format!("{}{}{}", label, target.separator, message),
+            None => message.to_string(),
+        },
+    }
+}
+
+/// 语言信息
+#[derive(Debug, Serialize, Deserialize)]
+pub struct LocaleInfo {
+    pub locale: Locale,
+    pub available: Vec<Locale>,
+    /// 操作系统的首选语言
+    pub system: Vec<String>,
+}
+
+// 获取后端消息使用的语言
+#[tauri::command]
+pub fn get_locale() -> LocaleInfo {
+    LocaleInfo {
+        locale: current(),
+        available: Locale::ALL.to_vec(),
+        system: system_locales(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_locale_tags() {
+        assert_eq!(Locale::parse("zh"), Some(Locale::ZhCn));
+        assert_eq!(Locale::parse("zh_Hans_CN.UTF-8"), Some(Locale::ZhCn));
+        assert_eq!(Locale::parse("en-GB"), Some(Locale::EnUs));
+        assert_eq!(Locale::parse(" EN "), Some(Locale::EnUs));
+        assert_eq!(Locale::parse("fr-FR"), None);
+        assert_eq!(Locale::parse(""), None);
+    }
+
+    #[test]
+    fn explicit_language_wins_over_system() {
+        assert_eq!(negotiate("zh-CN"), Locale::ZhCn);
+        assert_eq!(negotiate("en"), Locale::EnUs);
+    }
+
+    #[test]
+    fn catalogs_define_the_same_messages() {
+        let source = Locale::EnUs.catalog();
+        for locale in Locale::ALL {
+            let catalog = locale.catalog();
+            assert!(!catalog.separator.is_empty(), "{}", locale.tag());
+            for id in source.messages.keys() {
+                assert!(
+                    catalog.messages.contains_key(id),
+                    "{}: {}",
+                    locale.tag(),
+                    id
+                );
+            }
+            for code in source.codes.keys() {
+                assert!(
+                    catalog.codes.contains_key(code),
+                    "{}: {}",
+                    locale.tag(),
+                    code
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn translates_phrase_and_keeps_detail() {
+        assert_eq!(
+            localize(Locale::ZhCn, "io", "Failed to save file: disk full"),
+            "保存文件失败：disk full"
+        );
+        assert_eq!(
+            localize(Locale::ZhCn, "not_found", "File does not exist"),
+            "文件不存在"
+        );
+        assert_eq!(
+            localize(Locale::EnUs, "io", "Failed to save file: disk full"),
+            "Failed to save file: disk full"
+        );
+    }
+
+    #[test]
+    fn phrase_must_end_at_a_separator() {
+        // `File is read-only` 之后不是 `: `，不能当作该短语翻译
+        assert_eq!(
+            localize(Locale::ZhCn, "permission_denied", "File is read-onlyish"),
+            "没有权限：File is read-onlyish"
+        );
+    }
+
+    #[test]
+    fn prefers_the_longest_matching_phrase() {
+        assert_eq!(
+            localize(Locale::ZhCn, "io", "Failed to move file to trash: busy"),
+            "移到废纸篓失败：busy"
+        );
+    }
+
+    #[test]
+    fn unknown_messages_get_the_category_label() {
+        assert_eq!(
+            localize(Locale::ZhCn, "not_found", "Something vanished"),
+            "未找到：Something vanished"
+        );
+        assert_eq!(
+            localize(Locale::ZhCn, "unknown_code", "Something vanished"),
+            "Something vanished"
+        );
+        assert_eq!(
+            localize(Locale::EnUs, "not_found", "Something vanished"),
+            "Something vanished"
+        );
+    }
+}
@@ -0,0 +1,663 @@
+//! 导入 Word 文档
+//!
+//! 直接读取 `word/document.xml`：按段落样式识别标题、引用和代码块（兼容导出时使用的
+//! `SourceCode` / `VerbatimChar` 样式），按编号定义还原有序和无序列表，图片保存到资源目录。
+//! 先生成简单的 HTML，再由 [`super::html`] 转换为 Markdown。
+
+use std::collections::{HashMap, HashSet};
+use std::io::{Cursor, Read};
+
+use roxmltree::{Document, Node};
+
+use super::{html, AssetWriter};
+use crate::markdown::escape_html;
+
+const DOCUMENT_XML: &str = "word/document.xml";
+const STYLES_XML: &str = "word/styles.xml";
+const NUMBERING_XML: &str = "word/numbering.xml";
+const RELS_XML: &str = "word/_rels/document.xml.rels";
+/// 识别为行内代码的等宽字体
+const MONOSPACE_FONTS: &[&str] = &["courier", "consolas", "menlo", "monaco", "mono", "code"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ParagraphKind {
+    Normal,
+    Heading(u8),
+    Quote,
+    Code,
+}
+
+/// 样式表中与转换有关的部分
+#[derive(Default)]
+struct Styles {
+    paragraphs: HashMap<String, ParagraphKind>,
+    code_chars: HashSet<String>,
+}
+
+impl Styles {
+    fn paragraph_kind(&self, id: &str) -> ParagraphKind {
+        self.paragraphs
+            .get(id)
+            .copied()
+            .unwrap_or_else(|| kind_from_name(id, id))
+    }
+}
+
+/// 编号定义：`numId` → 各级是否为有序列表
+type Numbering = HashMap<String, HashMap<String, bool>>;
+
+struct Relationship {
+    target: String,
+    external: bool,
+}
+
+fn local<'a>(node: &Node<'a, '_>) -> &'a str {
+    node.tag_name().name()
+}
+
+/// 按本地名称取属性（忽略命名空间前缀）
+fn attr<'a>(node: Node<'a, '_>, name: &str) -> Option<&'a str> {
+    node.attributes()
+        .find(|a| a.name() == name)
+        .map(|a| a.value())
+}
+
+fn elements<'a, 'i>(node: Node<'a, 'i>) -> impl Iterator<Item = Node<'a, 'i>> {
+    node.children().filter(Node::is_element)
+}
+
+fn find_child<'a, 'i>(node: Node<'a, 'i>, name: &str) -> Option<Node<'a, 'i>> {
+    elements(node).find(|n| local(n) == name)
+}
+
+fn child_val<'a>(node: Node<'a, '_>, name: &str) -> Option<&'a str> {
+    find_child(node, name).and_then(|n| attr(n, "val"))
+}
+
+fn read_entry(
+    archive: &mut zip::ZipArchive<Cursor<&[u8]>>,
+    name: &str,
+) -> Result<Option<Vec<u8>>, String> {
+    let mut entry = match archive.by_name(name) {
+        Ok(entry) => entry,
+        Err(zip::result::ZipError::FileNotFound) => return Ok(None),
+        Err(e) => return Err(format!("Failed to read {}: {}", name, e)),
+    };
+    let mut data = Vec::new();
+    entry
+        .read_to_end(&mut data)
+        .map_err(|e| format!("Failed to read {}: {}", name, e))?;
+    Ok(Some(data))
+}
+
+fn read_xml(
+    archive: &mut zip::ZipArchive<Cursor<&[u8]>>,
+    name: &str,
+) -> Result<Option<String>, String> {
+    Ok(read_entry(archive, name)?.map(|data| String::from_utf8_lossy(&data).into_owned()))
+}
+
+/// 根据样式名（`heading 1`、`Quote`、`Source Code` 等）判断段落类型
+fn kind_from_name(id: &str, name: &str) -> ParagraphKind {
+    let id = id.to_ascii_lowercase();
+    let name = name.to_ascii_lowercase();
+    for candidate in [&name, &id] {
+        if let Some(level) = candidate
+            .strip_prefix("heading")
+            .and_then(|n| n.trim().parse::<u8>().ok())
+        {
+            return ParagraphKind::Heading(level.clamp(1, 6));
+        }
+    }
+    if name == "title" || id == "title" {
+        ParagraphKind::Heading(1)
+    } else if name.contains("quote") || id.contains("quote") || name == "block text" {
+        ParagraphKind::Quote
+    } else if id == "sourcecode" || name.contains("code") || name == "html preformatted" {
+        ParagraphKind::Code
+    } else {
+        ParagraphKind::Normal
+    }
+}
+
+fn parse_styles(xml: &str) -> Styles {
+    let mut styles = Styles::default();
+    let Ok(doc) = Document::parse(xml) else {
+        return styles;
+    };
+    // 样式 id → (名称, 基于的样式)
+    let mut paragraph_styles: HashMap<&str, (&str, Option<&str>)> = HashMap::new();
+    for style in elements(doc.root_element()).filter(|n| local(n) == "style") {
+        let Some(id) = attr(style, "styleId") else {
+            continue;
+        };
+        let name = child_val(style, "name").unwrap_or(id);
+        match attr(style, "type") {
+            Some("character") => {
+                let lower = name.to_ascii_lowercase();
+                if id == "VerbatimChar" || lower.contains("code") || lower.contains("verbatim") {
+                    styles.code_chars.insert(id.to_string());
+                }
+            }
+            Some("paragraph") | None => {
+                paragraph_styles.insert(id, (name, child_val(style, "basedOn")));
+            }
+            _ => {}
+        }
+    }
+    for &id in paragraph_styles.keys() {
+        // 沿 basedOn 向上查找，自定义样式通常基于内置的标题或引用样式
+        let mut current = Some(id);
+        let mut kind = ParagraphKind::Normal;
+        for _ in 0..8 {
+            let Some((name, based_on)) = current.and_then(|c| paragraph_styles.get(c)) else {
+                break;
+            };
+            kind = kind_from_name(current.unwrap_or(id), name);
+            if kind != ParagraphKind::Normal {
+                break;
+            }
+            current = *based_on;
+        }
+        styles.paragraphs.insert(id.to_string(), kind);
+    }
+    styles
+}
+
+fn parse_numbering(xml: &str) -> Numbering {
+    let Ok(doc) = Document::parse(xml) else {
+        return Numbering::new();
+    };
+    let root = doc.root_element();
+    let mut abstracts: HashMap<&str, HashMap<String, bool>> = HashMap::new();
+    for abstract_num in elements(root).filter(|n| local(n) == "abstractNum") {
+        let Some(id) = attr(abstract_num, "abstractNumId") else {
+            continue;
+        };
+        let levels = elements(abstract_num)
+            .filter(|n| local(n) == "lvl")
+            .filter_map(|lvl| {
+                let format = child_val(lvl, "numFmt").unwrap_or("bullet");
+                Some((
+                    attr(lvl, "ilvl")?.to_string(),
+                    !matches!(format, "bullet" | "none"),
+                ))
+            })
+            .collect();
+        abstracts.insert(id, levels);
+    }
+    elements(root)
+        .filter(|n| local(n) == "num")
+        .filter_map(|num| {
+            let levels = abstracts.get(child_val(num, "abstractNumId")?)?;
+            Some((attr(num, "numId")?.to_string(), levels.clone()))
+        })
+        .collect()
+}
+
+fn parse_relationships(xml: &str) -> HashMap<String, Relationship> {
+    let Ok(doc) = Document::parse(xml) else {
+        return HashMap::new();
+    };
+    elements(doc.root_element())
+        .filter_map(|rel| {
+            Some((
+                attr(rel, "Id")?.to_string(),
+                Relationship {
+                    target: attr(rel, "Target")?.to_string(),
+                    external: attr(rel, "TargetMode") == Some("External"),
+                },
+            ))
+        })
+        .collect()
+}
+
+/// 关系目标（相对于 `word/`）在压缩包中的路径
+fn part_path(target: &str) -> String {
+    if let Some(absolute) = target.strip_prefix('/') {
+        return absolute.to_string();
+    }
+    let mut parts = vec!["word"];
+    for segment in target.split('/') {
+        match segment {
+            ".." => {
+                parts.pop();
+            }
+            "." | "" => {}
+            segment => parts.push(segment),
+        }
+    }
+    parts.join("/")
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+struct Format {
+    bold: bool,
+    italic: bool,
+    strike: bool,
+    code: bool,
+}
+
+/// 合并格式相同的相邻片段后输出，避免 `**a****b**`
+fn render_segments(segments: Vec<(Format, String)>) -> String {
+    let mut merged: Vec<(Format, String)> = Vec::new();
+    for (format, html) in segments {
+        match merged.last_mut() {
+            Some((last, text)) if *last == format => text.push_str(&html),
+            _ => merged.push((format, html)),
+        }
+    }
+    let mut out = String::new();
+    for (format, mut html) in merged {
+        if format.code {
+            html = format!("<code>{}</code>", html);
+        }
+        if format.strike {
+            html = format!("<del>{}</del>", html);
+        }
+        if format.italic {
+            html = format!("<em>{}</em>", html);
+        }
+        if format.bold {
+            html = format!("<strong>{}</strong>", html);
+        }
+        out.push_str(&html);
+    }
+    out
+}
+
+/// 代码段落的纯文本
+fn plain_text(node: Node<'_, '_>, out: &mut String) {
+    for child in elements(node) {
+        match local(&child) {
+            "t" => out.push_str(child.text().unwrap_or_default()),
+            "tab" => out.push('\t'),
+            "br" | "cr" => out.push('\n'),
+            // 修订中删除的内容和兼容性备用内容不输出
+            "del" | "Fallback" | "pPr" | "rPr" => {}
+            _ => plain_text(child, out),
+        }
+    }
+}
+
+struct Converter<'d, 'w> {
+    archive: zip::ZipArchive<Cursor<&'d [u8]>>,
+    styles: Styles,
+    numbering: Numbering,
+    rels: HashMap<String, Relationship>,
+    assets: &'w mut AssetWriter,
+    /// 已保存的图片：关系 id → 链接
+    images: HashMap<String, String>,
+    html: String,
+    /// 打开的列表：(标签, numId)
+    lists: Vec<(&'static str, String)>,
+    code: Option<String>,
+}
+
+impl Converter<'_, '_> {
+    fn blocks(&mut self, node: Node<'_, '_>) {
+        for child in elements(node) {
+            match local(&child) {
+                "p" => self.paragraph(child),
+                "tbl" => {
+                    self.flush_code();
+                    self.close_lists();
+                    self.table(child);
+                }
+                "sdt" => {
+                    if let Some(content) = find_child(child, "sdtContent") {
+                        self.blocks(content);
+                    }
+                }
+                "customXml" => self.blocks(child),
+                _ => {}
+            }
+        }
+    }
+
+    fn paragraph(&mut self, p: Node<'_, '_>) {
+        let props = find_child(p, "pPr");
+        let mut kind = props
+            .and_then(|pr| child_val(pr, "pStyle"))
+            .map_or(ParagraphKind::Normal, |id| self.styles.paragraph_kind(id));
+        if let Some(level) = props
+            .and_then(|pr| child_val(pr, "outlineLvl"))
+            .and_then(|l| l.parse::<u8>().ok())
+            .filter(|l| *l < 6)
+        {
+            kind = ParagraphKind::Heading(level + 1);
+        }
+
+        // 连续的代码段落合并为一个代码块
+        if kind == ParagraphKind::Code {
+            self.close_lists();
+            let code = self.code.get_or_insert_with(String::new);
+            plain_text(p, code);
+            code.push('\n');
+            return;
+        }
+        self.flush_code();
+
+        let content = self.inline(p);
+        let numbering = props
+            .and_then(|pr| find_child(pr, "numPr"))
+            .and_then(|num| {
+                Some((
+                    child_val(num, "numId")?,
+                    child_val(num, "ilvl").unwrap_or("0"),
+                ))
+            })
+            .filter(|(id, _)| *id != "0");
+        if let (Some((num_id, level)), ParagraphKind::Normal) = (numbering, kind) {
+            self.list_item(num_id, level, &content);
+            return;
+        }
+
+        self.close_lists();
+        if content.trim().is_empty() {
+            return;
+        }
+        match kind {
+            ParagraphKind::Heading(level) => {
+                self.html
+                    .push_str(&format!("<h{0}>{1}</h{0}>", level, content));
+            }
+            ParagraphKind::Quote => {
+                self.html
+                    .push_str(&format!("<blockquote><p>{}</p></blockquote>", content));
+            }
+            _ => self.html.push_str(&format!("<p>{}</p>", content)),
+        }
+    }
+
+    fn list_item(&mut self, num_id: &str, level: &str, content: &str) {
+        let ordered = self
+            .numbering
+            .get(num_id)
+            .and_then(|levels| levels.get(level))
+            .copied()
+            .unwrap_or(false);
+        let tag = if ordered { "ol" } else { "ul" };
+        let depth = level.parse::<usize>().unwrap_or(0).min(8) + 1;
+
+        while self.lists.len() > depth {
+            self.close_list();
+        }
+        if self.lists.len() == depth {
+            let (open_tag, open_id) = &self.lists[depth - 1];
+            // 编号不同的有序列表重新开始计数
+            if *open_tag != tag || (ordered && open_id != num_id) {
+                self.close_list();
+            } else {
+                self.html.push_str("</li>");
+            }
+        }
+        while self.lists.len() < depth {
+            self.html.push_str(&format!("<{}>", tag));
+            self.lists.push((tag, num_id.to_string()));
+            if self.lists.len() < depth {
+                self.html.push_str("<li>");
+            }
+        }
+        self.html.push_str("<li>");
+        self.html.push_str(content);
+    }
+
+    fn close_list(&mut self) {
+        if let Some((tag, _)) = self.lists.pop() {
+            self.html.push_str(&format!("</li></{}>", tag));
+        }
+    }
+
+    fn close_lists(&mut self) {
+        while !self.lists.is_empty() {
+            self.close_list();
+        }
+    }
+
+    fn flush_code(&mut self) {
+        if let Some(code) = self.code.take() {
+            self.html.push_str(&format!(
+                "<pre><code>{}</code></pre>",
+                escape_html(code.trim_end_matches('\n'))
+            ));
+        }
+    }
+
+    fn table(&mut self, table: Node<'_, '_>) {
+        self.html.push_str("<table>");
+        for row in elements(table).filter(|n| local(n) == "tr") {
+            self.html.push_str("<tr>");
+            for cell in elements(row).filter(|n| local(n) == "tc") {
+                let props = find_child(cell, "tcPr");
+                let span = props
+                    .and_then(|pr| child_val(pr, "gridSpan"))
+                    .and_then(|s| s.parse::<usize>().ok())
+                    .unwrap_or(1);
+                // 纵向合并的后续单元格留空
+                let merged = props
+                    .and_then(|pr| find_child(pr, "vMerge"))
+                    .is_some_and(|m| attr(m, "val") != Some("restart"));
+                let content = if merged {
+                    String::new()
+                } else {
+                    elements(cell)
+                        .filter(|n| local(n) == "p")
+                        .map(|p| self.inline(p))
+                        .filter(|p| !p.trim().is_empty())
+                        .collect::<Vec<String>>()
+                        .join("<br>")
+                };
+                self.html
+                    .push_str(&format!("<td colspan=\"{}\">{}</td>", span, content));
+            }
+            self.html.push_str("</tr>");
+        }
+        self.html.push_str("</table>");
+    }
+
+    fn inline(&mut self, node: Node<'_, '_>) -> String {
+        let mut segments = Vec::new();
+        self.collect_runs(node, &mut segments);
+        render_segments(segments)
+    }
+
+    fn collect_runs(&mut self, node: Node<'_, '_>, segments: &mut Vec<(Format, String)>) {
+        for child in elements(node) {
+            match local(&child) {
+                "r" => self.run(child, segments),
+                "hyperlink" => {
+                    let href = attr(child, "id")
+                        .and_then(|id| self.rels.get(id))
+                        .map(|rel| rel.target.clone())
+                        .or_else(|| attr(child, "anchor").map(|a| format!("#{}", a)));
+                    let inner = self.inline(child);
+                    let html = match href {
+                        Some(href) => format!("<a href=\"{}\">{}</a>", escape_html(&href), inner),
+                        None => inner,
+                    };
+                    segments.push((Format::default(), html));
+                }
+                "ins" | "smartTag" | "fldSimple" | "customXml" | "sdtContent" => {
+                    self.collect_runs(child, segments)
+                }
+                "sdt" => {
+                    if let Some(content) = find_child(child, "sdtContent") {
+                        self.collect_runs(content, segments);
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn run_format(&self, props: Option<Node<'_, '_>>) -> Format {
+        let Some(props) = props else {
+            return Format::default();
+        };
+        let on = |name: &str| {
+            find_child(props, name)
+                .is_some_and(|n| !matches!(attr(n, "val"), Some("0" | "false" | "off" | "none")))
+        };
+        let monospace = find_child(props, "rFonts")
+            .and_then(|fonts| attr(fonts, "ascii"))
+            .map(str::to_ascii_lowercase)
+            .is_some_and(|font| MONOSPACE_FONTS.iter().any(|m| font.contains(m)));
+        Format {
+            bold: on("b"),
+            italic: on("i"),
+            strike: on("strike") || on("dstrike"),
+            code: monospace
+                || child_val(props, "rStyle").is_some_and(|s| self.styles.code_chars.contains(s)),
+        }
+    }
+
+    fn run(&mut self, run: Node<'_, '_>, segments: &mut Vec<(Format, String)>) {
+        let format = self.run_format(find_child(run, "rPr"));
+        let mut html = String::new();
+        for child in elements(run) {
+            match local(&child) {
+                "t" => html.push_str(&escape_html(child.text().unwrap_or_default())),
+                "tab" => html.push(' '),
+                "noBreakHyphen" => html.push('-'),
+                // 分页符、分栏符不输出
+                "br" if matches!(attr(child, "type"), None | Some("textWrapping")) => {
+                    html.push_str("<br>")
+                }
+                "cr" => html.push_str("<br>"),
+                "drawing" | "pict" | "object" => {
+                    if let Some(image) = self.image(child) {
+                        html.push_str(&image);
+                    }
+                }
+                _ => {}
+            }
+        }
+        if !html.is_empty() {
+            segments.push((format, html));
+        }
+    }
+
+    /// 保存嵌入的图片，返回 `<img>` 标签
+    fn image(&mut self, node: Node<'_, '_>) -> Option<String> {
+        let rel_id = node.descendants().find_map(|n| match local(&n) {
+            "blip" => attr(n, "embed").or_else(|| attr(n, "link")),
+            "imagedata" => attr(n, "id"),
+            _ => None,
+        })?;
+        let alt = node
+            .descendants()
+            .find(|n| local(n) == "docPr")
+            .and_then(|n| {
+                attr(n, "descr")
+                    .filter(|d| !d.is_empty())
+                    .or_else(|| attr(n, "title"))
+            })
+            .unwrap_or_default()
+            .to_string();
+
+        let link = match self.images.get(rel_id) {
+            Some(link) => link.clone(),
+            None => {
+                let rel = self.rels.get(rel_id)?;
+                let link = if rel.external {
+                    rel.target.clone()
+                } else {
+                    let path = part_path(&rel.target);
+                    let data = read_entry(&mut self.archive, &path).ok().flatten()?;
+                    let name = path.rsplit('/').next().map(str::to_string);
+                    self.assets.store(&data, name.as_deref())?
+                };
+                self.images.insert(rel_id.to_string(), link.clone());
+                link
+            }
+        };
+        Some(format!(
+            "<img src=\"{}\" alt=\"{}\">",
+            escape_html(&link),
+            escape_html(&alt)
+        ))
+    }
+}
+
+/// 把 Word 文档转换为 Markdown，图片保存到资源目录
+pub fn to_markdown(bytes: &[u8], assets: &mut AssetWriter) -> Result<String, String> {
+    let mut archive =
+        zip::ZipArchive::new(Cursor::new(bytes)).map_err(|e| format!("Invalid docx: {}", e))?;
+    let document = read_xml(&mut archive, DOCUMENT_XML)?
+        .ok_or_else(|| format!("Invalid docx: missing {}", DOCUMENT_XML))?;
+    let styles = read_xml(&mut archive, STYLES_XML)?
+        .map(|xml| parse_styles(&xml))
+        .unwrap_or_default();
+    let numbering = read_xml(&mut archive, NUMBERING_XML)?
+        .map(|xml| parse_numbering(&xml))
+        .unwrap_or_default();
+    let rels = read_xml(&mut archive, RELS_XML)?
+        .map(|xml| parse_relationships(&xml))
+        .unwrap_or_default();
+
+    let doc = Document::parse(&document).map_err(|e| format!("Invalid docx: {}", e))?;
+    let body = find_child(doc.root_element(), "body")
+        .ok_or_else(|| "Invalid docx: missing document body".to_string())?;
+
+    let mut converter = Converter {
+        archive,
+        styles,
+        numbering,
+        rels,
+        assets,
+        images: HashMap::new(),
+        html: String::new(),
+        lists: Vec::new(),
+        code: None,
+    };
+    converter.blocks(body);
+    converter.flush_code();
+    converter.close_lists();
+    Ok(html::html_to_markdown(&converter.html, &mut |_| None))
+}
+
+/// 表格按行输出，单元格之间用制表符分隔
+fn table_text(table: Node<'_, '_>, out: &mut String) {
+    for row in elements(table).filter(|n| local(n) == "tr") {
+        let cells: Vec<String> = elements(row)
+            .filter(|n| local(n) == "tc")
+            .map(|cell| {
+                let mut text = String::new();
+                block_text(cell, &mut text);
+                text.split_whitespace().collect::<Vec<_>>().join(" ")
+            })
+            .collect();
+        out.push_str(&cells.join("\t"));
+        out.push('\n');
+    }
+}
+
+fn block_text(node: Node<'_, '_>, out: &mut String) {
+    for child in elements(node) {
+        match local(&child) {
+            "p" => {
+                plain_text(child, out);
+                out.push('\n');
+            }
+            "tbl" => table_text(child, out),
+            "sectPr" => {}
+            // 内容控件等容器
+            _ => block_text(child, out),
+        }
+    }
+}
+
+/// 提取 Word 文档的纯文本，每个段落一行，用于预览和全文搜索
+pub fn to_text(bytes: &[u8]) -> Result<String, String> {
+    let mut archive =
+        zip::ZipArchive::new(Cursor::new(bytes)).map_err(|e| format!("Invalid docx: {}", e))?;
+    let document = read_xml(&mut archive, DOCUMENT_XML)?
+        .ok_or_else(|| format!("Invalid docx: missing {}", DOCUMENT_XML))?;
+    let doc = Document::parse(&document).map_err(|e| format!("Invalid docx: {}", e))?;
+    let body = find_child(doc.root_element(), "body")
+        .ok_or_else(|| "Invalid docx: missing document body".to_string())?;
+    let mut text = String::new();
+    block_text(body, &mut text);
+    Ok(text)
+}
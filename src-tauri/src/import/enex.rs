@@ -0,0 +1,242 @@
+//! 导入 Evernote 导出文件（.enex）
+//!
+//! 一个 .enex 文件包含多条笔记。笔记正文是 ENML（XHTML 的子集），附件以 base64 内嵌，
+//! 正文中用 `<en-media hash="…">` 按附件内容的 MD5 引用。创建时间、标签和来源网址写入 front matter。
+
+use std::collections::HashMap;
+use std::sync::OnceLock;
+
+use base64::Engine;
+use chrono::NaiveDateTime;
+use md5::{Digest, Md5};
+use regex::{Captures, Regex};
+use roxmltree::{Document, Node, ParsingOptions};
+
+use super::{html, AssetWriter};
+use crate::markdown::escape_html;
+
+/// 一条笔记
+pub struct Note {
+    pub title: String,
+    /// ENML 正文
+    content: String,
+    created: Option<String>,
+    updated: Option<String>,
+    tags: Vec<String>,
+    source_url: Option<String>,
+    resources: Vec<Resource>,
+}
+
+/// 笔记附件
+struct Resource {
+    data: Vec<u8>,
+    mime: String,
+    file_name: Option<String>,
+}
+
+fn child_text(node: Node<'_, '_>, name: &str) -> Option<String> {
+    node.children()
+        .find(|n| n.tag_name().name() == name)
+        .map(|n| n.text().unwrap_or_default().trim().to_string())
+        .filter(|text| !text.is_empty())
+}
+
+/// `20230115T083000Z` → `2023-01-15T08:30:00Z`
+fn format_time(value: &str) -> String {
+    NaiveDateTime::parse_from_str(value, "%Y%m%dT%H%M%SZ")
+        .map(|time| time.format("%Y-%m-%dT%H:%M:%SZ").to_string())
+        .unwrap_or_else(|_| value.to_string())
+}
+
+fn parse_resource(node: Node<'_, '_>) -> Option<Resource> {
+    let data: String = child_text(node, "data")?
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .collect();
+    let data = base64::engine::general_purpose::STANDARD
+        .decode(data)
+        .ok()?;
+    let file_name = node
+        .children()
+        .find(|n| n.tag_name().name() == "resource-attributes")
+        .and_then(|attrs| child_text(attrs, "file-name"));
+    Some(Resource {
+        data,
+        mime: child_text(node, "mime").unwrap_or_default(),
+        file_name,
+    })
+}
+
+/// 解析 .enex 文件中的所有笔记
+pub fn parse_enex(xml: &str) -> Result<Vec<Note>, String> {
+    let options = ParsingOptions {
+        allow_dtd: true,
+        ..ParsingOptions::default()
+    };
+    let doc = Document::parse_with_options(xml, options)
+        .map_err(|e| format!("Invalid ENEX file: {}", e))?;
+    let notes = doc
+        .root_element()
+        .children()
+        .filter(|n| n.tag_name().name() == "note")
+        .map(|note| {
+            let source_url = note
+                .children()
+                .find(|n| n.tag_name().name() == "note-attributes")
+                .and_then(|attrs| child_text(attrs, "source-url"));
+            Note {
+                title: child_text(note, "title").unwrap_or_default(),
+                content: child_text(note, "content").unwrap_or_default(),
+                created: child_text(note, "created").map(|t| format_time(&t)),
+                updated: child_text(note, "updated").map(|t| format_time(&t)),
+                tags: note
+                    .children()
+                    .filter(|n| n.tag_name().name() == "tag")
+                    .filter_map(|n| n.text().map(|t| t.trim().to_string()))
+                    .filter(|tag| !tag.is_empty())
+                    .collect(),
+                source_url,
+                resources: note
+                    .children()
+                    .filter(|n| n.tag_name().name() == "resource")
+                    .filter_map(parse_resource)
+                    .collect(),
+            }
+        })
+        .collect();
+    Ok(notes)
+}
+
+fn media_regex() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| Regex::new(r"(?is)<en-media\b([^>]*?)/?>").expect("valid regex"))
+}
+
+fn todo_regex() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| Regex::new(r"(?is)<en-todo\b([^>]*?)/?>").expect("valid regex"))
+}
+
+fn closing_regex() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| Regex::new(r"(?i)</en-(?:media|todo)\s*>").expect("valid regex"))
+}
+
+fn crypt_regex() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| Regex::new(r"(?is)<en-crypt\b.*?</en-crypt\s*>").expect("valid regex"))
+}
+
+/// 标签属性中某个属性的值
+fn attribute<'a>(attrs: &'a str, name: &str) -> Option<&'a str> {
+    let pattern = format!("{}=\"", name);
+    let mut search = 0;
+    while let Some(i) = attrs[search..].find(&pattern) {
+        let start = search + i;
+        let value_start = start + pattern.len();
+        if attrs[..start].ends_with(char::is_whitespace) {
+            let len = attrs[value_start..].find('"')?;
+            return Some(&attrs[value_start..value_start + len]);
+        }
+        search = value_start;
+    }
+    None
+}
+
+fn extension_for(mime: &str) -> &'static str {
+    match mime {
+        "image/png" => "png",
+        "image/jpeg" => "jpg",
+        "image/gif" => "gif",
+        "image/svg+xml" => "svg",
+        "image/webp" => "webp",
+        "application/pdf" => "pdf",
+        "audio/mpeg" => "mp3",
+        "audio/wav" => "wav",
+        "text/plain" => "txt",
+        _ => "bin",
+    }
+}
+
+/// 把笔记转换为 Markdown（带 front matter），附件保存到资源目录
+pub fn note_to_markdown(note: &Note, assets: &mut AssetWriter) -> String {
+    let resources: HashMap<String, &Resource> = note
+        .resources
+        .iter()
+        .map(|r| {
+            let hash: String = Md5::digest(&r.data)
+                .iter()
+                .map(|b| format!("{:02x}", b))
+                .collect();
+            (hash, r)
+        })
+        .collect();
+    let mut links: HashMap<String, String> = HashMap::new();
+
+    // ENML 专有标签先换成普通 HTML：附件换成图片或链接，待办换成复选框
+    let content = media_regex().replace_all(&note.content, |caps: &Captures<'_>| {
+        let Some(hash) = attribute(&caps[1], "hash").map(str::to_ascii_lowercase) else {
+            return String::new();
+        };
+        let Some(resource) = resources.get(&hash) else {
+            return String::new();
+        };
+        let name = resource
+            .file_name
+            .clone()
+            .unwrap_or_else(|| format!("attachment.{}", extension_for(&resource.mime)));
+        let link = match links.get(&hash) {
+            Some(link) => link.clone(),
+            None => {
+                let Some(link) = assets.store(&resource.data, Some(&name)) else {
+                    return String::new();
+                };
+                links.insert(hash, link.clone());
+                link
+            }
+        };
+        if resource.mime.starts_with("image/") {
+            format!(
+                "<img src=\"{}\" alt=\"{}\">",
+                escape_html(&link),
+                escape_html(&name)
+            )
+        } else {
+            format!(
+                "<a href=\"{}\">{}</a>",
+                escape_html(&link),
+                escape_html(&name)
+            )
+        }
+    });
+    let content = todo_regex().replace_all(&content, |caps: &Captures<'_>| {
+        if attribute(&caps[1], "checked") == Some("true") {
+            "<input type=\"checkbox\" checked>"
+        } else {
+            "<input type=\"checkbox\">"
+        }
+    });
+    let content = closing_regex().replace_all(&content, "");
+    let content = crypt_regex().replace_all(&content, "<p><em>(encrypted content)</em></p>");
+    let body = html::html_to_markdown(&content, &mut |_| None);
+
+    let mut front_matter = serde_yaml::Mapping::new();
+    front_matter.insert("title".into(), note.title.clone().into());
+    if let Some(created) = &note.created {
+        front_matter.insert("created".into(), created.clone().into());
+    }
+    if let Some(updated) = &note.updated {
+        front_matter.insert("updated".into(), updated.clone().into());
+    }
+    if !note.tags.is_empty() {
+        let tags = note.tags.iter().cloned().map(Into::into).collect();
+        front_matter.insert("tags".into(), serde_yaml::Value::Sequence(tags));
+    }
+    if let Some(source) = &note.source_url {
+        front_matter.insert("source".into(), source.clone().into());
+    }
+    match serde_yaml::to_string(&front_matter) {
+        Ok(yaml) => format!("---\n{}---\n\n{}", yaml, body),
+        Err(_) => body,
+    }
+}
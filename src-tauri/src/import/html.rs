@@ -0,0 +1,831 @@
+//! HTML 转 Markdown
+//!
+//! 导入 HTML / Word / Evernote 文件时共用：支持标题、段落、强调、链接、图片、列表（含任务列表）、
+//! 引用、代码块和表格，其它标签只保留文字内容。图片地址由调用方改写（如保存到资源目录）。
+
+use kuchikiki::traits::*;
+use kuchikiki::{ElementData, NodeData, NodeRef};
+
+/// 图片地址改写：返回写入 Markdown 的链接，返回 `None` 时保留原地址
+pub type ImageResolver<'a> = dyn FnMut(&str) -> Option<String> + 'a;
+
+/// 块级标签：前后断开段落
+const BLOCK_TAGS: &[&str] = &[
+    "address",
+    "article",
+    "aside",
+    "blockquote",
+    "body",
+    "center",
+    "dd",
+    "details",
+    "dialog",
+    "div",
+    "dl",
+    "dt",
+    "en-note",
+    "fieldset",
+    "figcaption",
+    "figure",
+    "footer",
+    "form",
+    "h1",
+    "h2",
+    "h3",
+    "h4",
+    "h5",
+    "h6",
+    "header",
+    "hgroup",
+    "hr",
+    "html",
+    "li",
+    "main",
+    "nav",
+    "ol",
+    "p",
+    "pre",
+    "section",
+    "summary",
+    "table",
+    "tbody",
+    "td",
+    "tfoot",
+    "th",
+    "thead",
+    "tr",
+    "ul",
+];
+/// 不输出任何内容的标签
+const SKIPPED_TAGS: &[&str] = &[
+    "button", "canvas", "head", "iframe", "link", "meta", "noscript", "object", "script", "select",
+    "style", "svg", "template", "textarea", "title",
+];
+
+fn is_block(tag: &str) -> bool {
+    BLOCK_TAGS.contains(&tag)
+}
+
+fn tag_name(element: &ElementData) -> &str {
+    &element.name.local
+}
+
+fn attr(element: &ElementData, name: &str) -> Option<String> {
+    element.attributes.borrow().get(name).map(str::to_string)
+}
+
+/// 行内样式中某个属性的值（小写）
+fn style_value(element: &ElementData, property: &str) -> Option<String> {
+    let style = attr(element, "style")?.to_ascii_lowercase();
+    style.split(';').find_map(|decl| {
+        let (name, value) = decl.split_once(':')?;
+        (name.trim() == property).then(|| value.trim().to_string())
+    })
+}
+
+/// 连续空白（包括换行和不换行空格）合并为一个空格
+fn collapse_whitespace(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut space = false;
+    for c in text.chars() {
+        if c.is_whitespace() {
+            if !space {
+                out.push(' ');
+            }
+            space = true;
+        } else {
+            out.push(c);
+            space = false;
+        }
+    }
+    out
+}
+
+/// 转义文本中会被当作 Markdown 语法的字符
+fn escape_text(text: &str) -> String {
+    let chars: Vec<char> = text.chars().collect();
+    let mut out = String::with_capacity(text.len());
+    for (i, &c) in chars.iter().enumerate() {
+        let prev = i.checked_sub(1).map(|i| chars[i]);
+        let next = chars.get(i + 1).copied();
+        let escape = match c {
+            '\\' | '`' | '*' | '[' | ']' | '<' => true,
+            // 单词内部的下划线不会被当作强调
+            '_' => {
+                !(prev.is_some_and(char::is_alphanumeric)
+                    && next.is_some_and(char::is_alphanumeric))
+            }
+            '~' => prev == Some('~') || next == Some('~'),
+            _ => false,
+        };
+        if escape {
+            out.push('\\');
+        }
+        out.push(c);
+    }
+    out
+}
+
+/// 转义行首会被当作块级语法的文本（标题、引用、列表、分隔线）
+fn escape_line_start(line: &str) -> String {
+    let first = line.chars().next();
+    let is_rule = line.len() >= 3 && line.chars().all(|c| c == '-' || c == '=');
+    match first {
+        Some('#' | '>') => format!("\\{}", line),
+        Some('-' | '+') if is_rule || line[1..].starts_with(' ') => format!("\\{}", line),
+        Some('=') if is_rule => format!("\\{}", line),
+        Some(c) if c.is_ascii_digit() => {
+            let digits = line.chars().take_while(char::is_ascii_digit).count();
+            let rest = &line[digits..];
+            if (rest.starts_with(". ") || rest.starts_with(") ")) || rest == "." || rest == ")" {
+                format!("{}\\{}", &line[..digits], rest)
+            } else {
+                line.to_string()
+            }
+        }
+        _ => line.to_string(),
+    }
+}
+
+/// 追加行内内容，避免连续空格
+fn append_inline(buffer: &mut String, piece: &str) {
+    if buffer.ends_with([' ', '\n']) {
+        buffer.push_str(piece.trim_start_matches(' '));
+    } else {
+        buffer.push_str(piece);
+    }
+}
+
+/// 整理一段行内内容：去掉每行首尾空白、多余的硬换行，并转义行首
+fn normalize_paragraph(inline: &str) -> String {
+    let lines: Vec<String> = inline
+        .split('\n')
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && *line != "\\")
+        .map(escape_line_start)
+        .collect();
+    let mut text = lines.join("\n");
+    // 段落末尾的硬换行没有意义
+    while text.ends_with('\\') && !text.ends_with("\\\\") {
+        text.pop();
+        text.truncate(text.trim_end().len());
+    }
+    text
+}
+
+/// 用强调标记包住内容，首尾空白留在标记外面
+fn wrap(content: &str, marker: &str) -> String {
+    let start = content.len() - content.trim_start().len();
+    let end = content.trim_end().len();
+    if start >= end {
+        return content.to_string();
+    }
+    let (mut core, mut trailing) = (&content[start..end], content[end..].to_string());
+    // 末尾的硬换行（`\` + 换行）放到标记外面
+    if trailing.starts_with('\n') && core.ends_with('\\') {
+        core = &core[..core.len() - 1];
+        trailing.insert(0, '\\');
+    }
+    if core.trim().is_empty() {
+        return content.to_string();
+    }
+    format!(
+        "{}{}{}{}{}",
+        &content[..start],
+        marker,
+        core.trim_end(),
+        marker,
+        trailing
+    )
+}
+
+/// 行内代码，内容含反引号时使用更长的定界符
+fn code_span(text: &str) -> String {
+    let text = collapse_whitespace(text);
+    if text.trim().is_empty() {
+        return text;
+    }
+    let fence = "`".repeat(longest_run(&text, '`') + 1);
+    if text.starts_with('`') || text.ends_with('`') {
+        format!("{} {} {}", fence, text, fence)
+    } else {
+        format!("{}{}{}", fence, text, fence)
+    }
+}
+
+fn longest_run(text: &str, c: char) -> usize {
+    let mut longest = 0;
+    let mut current = 0;
+    for ch in text.chars() {
+        if ch == c {
+            current += 1;
+            longest = longest.max(current);
+        } else {
+            current = 0;
+        }
+    }
+    longest
+}
+
+/// 链接地址，含空格或括号时用尖括号包住
+fn link_destination(url: &str) -> String {
+    if url.contains([' ', '(', ')']) {
+        format!("<{}>", url.replace('<', "%3C").replace('>', "%3E"))
+    } else {
+        url.to_string()
+    }
+}
+
+fn title_suffix(title: Option<String>) -> String {
+    match title.map(|t| collapse_whitespace(&t)) {
+        Some(title) if !title.trim().is_empty() => {
+            format!(" \"{}\"", title.trim().replace('"', "\\\""))
+        }
+        _ => String::new(),
+    }
+}
+
+fn quote(body: &str) -> String {
+    body.lines()
+        .map(|line| {
+            if line.is_empty() {
+                ">".to_string()
+            } else {
+                format!("> {}", line)
+            }
+        })
+        .collect::<Vec<String>>()
+        .join("\n")
+}
+
+fn has_block_descendant(node: &NodeRef) -> bool {
+    node.descendants()
+        .any(|d| d.as_element().is_some_and(|e| is_block(tag_name(e))))
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum BlockKind {
+    Paragraph,
+    /// 单独出现的列表项（Evernote 的待办、Word 的列表段落），相邻的合并为一个紧凑列表
+    ListItem,
+    List,
+    Table,
+    Other,
+}
+
+/// 收集块级内容
+struct Blocks {
+    blocks: Vec<(BlockKind, String)>,
+    inline: String,
+    /// 以复选框开头的段落是否转为任务列表项
+    promote_tasks: bool,
+}
+
+impl Blocks {
+    fn new(promote_tasks: bool) -> Self {
+        Blocks {
+            blocks: Vec::new(),
+            inline: String::new(),
+            promote_tasks,
+        }
+    }
+
+    fn push_inline(&mut self, piece: &str) {
+        append_inline(&mut self.inline, piece);
+    }
+
+    fn flush(&mut self) {
+        let text = normalize_paragraph(&self.inline);
+        self.inline.clear();
+        if text.is_empty() {
+            return;
+        }
+        let is_task = text.starts_with("[ ]") || text.starts_with("[x]");
+        if is_task && self.promote_tasks {
+            self.blocks
+                .push((BlockKind::ListItem, format!("- {}", text)));
+        } else {
+            self.blocks.push((BlockKind::Paragraph, text));
+        }
+    }
+
+    fn push(&mut self, kind: BlockKind, block: String) {
+        self.flush();
+        if !block.trim().is_empty() {
+            self.blocks.push((kind, block));
+        }
+    }
+
+    /// 拼接所有块；列表项中只有一段文字时块之间不空行（紧凑列表）
+    fn finish(mut self, list_item: bool) -> String {
+        self.flush();
+        let paragraphs = self
+            .blocks
+            .iter()
+            .filter(|(kind, _)| matches!(kind, BlockKind::Paragraph | BlockKind::ListItem))
+            .count();
+        let has_table = self
+            .blocks
+            .iter()
+            .any(|(kind, _)| *kind == BlockKind::Table);
+        let tight = list_item && paragraphs <= 1 && !has_table;
+
+        let mut out = String::new();
+        let mut prev = None;
+        for (kind, block) in &self.blocks {
+            if let Some(prev) = prev {
+                let consecutive_items = prev == BlockKind::ListItem && *kind == BlockKind::ListItem;
+                out.push_str(if tight || consecutive_items {
+                    "\n"
+                } else {
+                    "\n\n"
+                });
+            }
+            out.push_str(block);
+            prev = Some(*kind);
+        }
+        out
+    }
+}
+
+struct Converter<'r, 'a> {
+    resolve_image: &'r mut ImageResolver<'a>,
+}
+
+impl Converter<'_, '_> {
+    fn blocks(&mut self, node: &NodeRef, out: &mut Blocks) {
+        for child in node.children() {
+            self.block_node(&child, out);
+        }
+    }
+
+    fn block_node(&mut self, node: &NodeRef, out: &mut Blocks) {
+        let element = match node.data() {
+            NodeData::Text(text) => {
+                out.push_inline(&escape_text(&collapse_whitespace(&text.borrow())));
+                return;
+            }
+            NodeData::Element(element) => element,
+            _ => return,
+        };
+        let tag = tag_name(element);
+        if SKIPPED_TAGS.contains(&tag) {
+            return;
+        }
+        match tag {
+            "h1" | "h2" | "h3" | "h4" | "h5" | "h6" => {
+                let level = usize::from(tag.as_bytes()[1] - b'0');
+                let text = normalize_paragraph(&self.inline_children(node).replace("\\\n", " "))
+                    .replace('\n', " ");
+                if !text.is_empty() {
+                    out.push(BlockKind::Other, format!("{} {}", "#".repeat(level), text));
+                }
+            }
+            "br" => out.push_inline("\\\n"),
+            "hr" => out.push(BlockKind::Other, "---".to_string()),
+            "pre" => out.push(BlockKind::Other, code_block(node)),
+            "ul" | "ol" => {
+                let list = self.list(node, element, tag == "ol");
+                out.push(BlockKind::List, list);
+            }
+            "li" => {
+                let item = self.list_item("- ", node);
+                out.push(BlockKind::List, item);
+            }
+            // Word 的列表是带 `mso-list` 样式的普通段落
+            "p" if style_value(element, "mso-list").is_some_and(|v| v.contains("level")) => {
+                let item = self.word_list_item(node, element);
+                out.push(BlockKind::ListItem, item);
+            }
+            "blockquote" => {
+                let body = self.container(node, false);
+                out.push(BlockKind::Other, quote(&body));
+            }
+            "table" => {
+                let table = self.table(node);
+                out.push(BlockKind::Table, table);
+            }
+            "dt" => {
+                let text = normalize_paragraph(&self.inline_children(node));
+                out.push(BlockKind::Paragraph, wrap(&text, "**"));
+            }
+            _ if is_block(tag) => {
+                out.flush();
+                self.blocks(node, out);
+                out.flush();
+            }
+            // 行内标签里包着块级内容时不保留格式，只保留结构
+            _ if has_block_descendant(node) => self.blocks(node, out),
+            _ => {
+                let inline = self.inline_element(node, element);
+                out.push_inline(&inline);
+            }
+        }
+    }
+
+    /// 把子节点转换为一段独立的 Markdown（用于列表项、引用）
+    fn container(&mut self, node: &NodeRef, list_item: bool) -> String {
+        let mut blocks = Blocks::new(!list_item);
+        self.blocks(node, &mut blocks);
+        blocks.finish(list_item)
+    }
+
+    fn inline_children(&mut self, node: &NodeRef) -> String {
+        let mut out = String::new();
+        for child in node.children() {
+            let piece = match child.data() {
+                NodeData::Text(text) => escape_text(&collapse_whitespace(&text.borrow())),
+                NodeData::Element(element) if !SKIPPED_TAGS.contains(&tag_name(element)) => {
+                    self.inline_element(&child, element)
+                }
+                _ => continue,
+            };
+            append_inline(&mut out, &piece);
+        }
+        out
+    }
+
+    fn inline_element(&mut self, node: &NodeRef, element: &ElementData) -> String {
+        let tag = tag_name(element);
+        match tag {
+            "br" => "\\\n".to_string(),
+            "strong" | "b" => {
+                let inner = self.inline_children(node);
+                // Google 文档把整段内容包在 `<b style="font-weight:normal">` 里
+                let normal = style_value(element, "font-weight")
+                    .is_some_and(|w| w == "normal" || w == "400");
+                if normal {
+                    inner
+                } else {
+                    wrap(&inner, "**")
+                }
+            }
+            "em" | "i" | "cite" | "dfn" => wrap(&self.inline_children(node), "*"),
+            "del" | "s" | "strike" => wrap(&self.inline_children(node), "~~"),
+            "code" | "kbd" | "samp" | "tt" => code_span(&node.text_contents()),
+            "a" => self.link(node, element),
+            "img" => self.image(element),
+            "input" => {
+                let is_checkbox =
+                    attr(element, "type").is_some_and(|t| t.eq_ignore_ascii_case("checkbox"));
+                match (is_checkbox, attr(element, "checked").is_some()) {
+                    (true, true) => "[x] ".to_string(),
+                    (true, false) => "[ ] ".to_string(),
+                    _ => String::new(),
+                }
+            }
+            // Word 列表段落中的编号，已在 `word_list_item` 中处理
+            "span" if style_value(element, "mso-list").as_deref() == Some("ignore") => {
+                String::new()
+            }
+            "span" | "font" => {
+                let mut inner = self.inline_children(node);
+                let bold = style_value(element, "font-weight").is_some_and(|w| {
+                    w == "bold" || w == "bolder" || w.parse::<u32>().is_ok_and(|w| w >= 600)
+                });
+                let italic = style_value(element, "font-style")
+                    .is_some_and(|s| s == "italic" || s == "oblique");
+                let strike = style_value(element, "text-decoration")
+                    .is_some_and(|d| d.contains("line-through"));
+                if strike {
+                    inner = wrap(&inner, "~~");
+                }
+                if italic {
+                    inner = wrap(&inner, "*");
+                }
+                if bold {
+                    inner = wrap(&inner, "**");
+                }
+                inner
+            }
+            // 出现在行内的块级标签只保留文字
+            _ if is_block(tag) => format!(" {} ", self.inline_children(node)),
+            _ => self.inline_children(node),
+        }
+    }
+
+    fn link(&mut self, node: &NodeRef, element: &ElementData) -> String {
+        let text = self.inline_children(node);
+        let href = attr(element, "href").unwrap_or_default();
+        let href = href.trim();
+        if href.is_empty() || href.starts_with("javascript:") {
+            return text;
+        }
+        let start = text.len() - text.trim_start().len();
+        let end = text.trim_end().len();
+        if start >= end {
+            return text;
+        }
+        let label = &text[start..end];
+        let has_scheme = href.starts_with("http://")
+            || href.starts_with("https://")
+            || href.starts_with("mailto:");
+        let link = if has_scheme && label == escape_text(href) && !href.contains([' ', '<', '>']) {
+            format!("<{}>", href)
+        } else {
+            format!(
+                "[{}]({}{})",
+                label,
+                link_destination(href),
+                title_suffix(attr(element, "title"))
+            )
+        };
+        format!("{}{}{}", &text[..start], link, &text[end..])
+    }
+
+    fn image(&mut self, element: &ElementData) -> String {
+        let src = attr(element, "src").unwrap_or_default();
+        let src = src.trim();
+        if src.is_empty() {
+            return String::new();
+        }
+        let link = (self.resolve_image)(src).unwrap_or_else(|| src.to_string());
+        let alt =
+            escape_text(collapse_whitespace(&attr(element, "alt").unwrap_or_default()).trim());
+        format!(
+            "![{}]({}{})",
+            alt,
+            link_destination(&link),
+            title_suffix(attr(element, "title"))
+        )
+    }
+
+    fn list(&mut self, node: &NodeRef, element: &ElementData, ordered: bool) -> String {
+        let mut number = attr(element, "start")
+            .and_then(|s| s.trim().parse::<u64>().ok())
+            .unwrap_or(1);
+        let mut items: Vec<String> = Vec::new();
+        for child in node.children() {
+            let Some(child_element) = child.as_element() else {
+                // 列表中直接出现的文字当作单独的列表项
+                if child
+                    .as_text()
+                    .is_some_and(|t| !t.borrow().trim().is_empty())
+                {
+                    items.push(format!("- {}", escape_text(child.text_contents().trim())));
+                }
+                continue;
+            };
+            match tag_name(child_element) {
+                // 不规范的嵌套：子列表直接放在列表中，归到上一项下面
+                tag @ ("ul" | "ol") => {
+                    let nested = self.list(&child, child_element, tag == "ol");
+                    match items.last_mut() {
+                        Some(last) => {
+                            let indent = " ".repeat(last.find(' ').map_or(2, |i| i + 1));
+                            for line in nested.lines() {
+                                last.push('\n');
+                                if !line.is_empty() {
+                                    last.push_str(&indent);
+                                    last.push_str(line);
+                                }
+                            }
+                        }
+                        None => items.push(nested),
+                    }
+                }
+                tag if SKIPPED_TAGS.contains(&tag) => {}
+                _ => {
+                    let marker = if ordered {
+                        format!("{}. ", number)
+                    } else {
+                        "- ".to_string()
+                    };
+                    number += 1;
+                    items.push(self.list_item(&marker, &child));
+                }
+            }
+        }
+        items.join("\n")
+    }
+
+    fn list_item(&mut self, marker: &str, node: &NodeRef) -> String {
+        let body = self.container(node, true);
+        if body.is_empty() {
+            return marker.trim_end().to_string();
+        }
+        let indent = " ".repeat(marker.len());
+        let mut out = String::new();
+        for (i, line) in body.lines().enumerate() {
+            if i == 0 {
+                out.push_str(marker);
+            } else {
+                out.push('\n');
+                if !line.is_empty() {
+                    out.push_str(&indent);
+                }
+            }
+            out.push_str(line);
+        }
+        out
+    }
+
+    fn word_list_item(&mut self, node: &NodeRef, element: &ElementData) -> String {
+        let level = style_value(element, "mso-list")
+            .and_then(|v| {
+                v.split_whitespace()
+                    .find_map(|token| token.strip_prefix("level")?.parse::<usize>().ok())
+            })
+            .unwrap_or(1)
+            .clamp(1, 9);
+        // 编号文字在 `mso-list:Ignore` 的 span 中，数字开头的是有序列表
+        let numbering = node
+            .descendants()
+            .find(|n| {
+                n.as_element()
+                    .is_some_and(|e| style_value(e, "mso-list").as_deref() == Some("ignore"))
+            })
+            .map(|n| n.text_contents())
+            .unwrap_or_default();
+        let number: String = numbering
+            .trim()
+            .chars()
+            .take_while(char::is_ascii_digit)
+            .collect();
+        let marker = if number.is_empty() {
+            "- ".to_string()
+        } else {
+            format!("{}. ", number)
+        };
+        let text = normalize_paragraph(&self.inline_children(node));
+        format!("{}{}{}", "    ".repeat(level - 1), marker, text)
+    }
+
+    fn table(&mut self, node: &NodeRef) -> String {
+        let mut rows: Vec<Vec<(String, Option<String>)>> = Vec::new();
+        let mut caption = None;
+        self.collect_rows(node, &mut rows, &mut caption);
+
+        let columns = rows.iter().map(Vec::len).max().unwrap_or(0);
+        if columns == 0 {
+            return caption.unwrap_or_default();
+        }
+        let mut lines = Vec::with_capacity(rows.len() + 1);
+        for (i, row) in rows.iter().enumerate() {
+            let mut cells: Vec<&str> = row.iter().map(|(text, _)| text.as_str()).collect();
+            cells.resize(columns, "");
+            lines.push(format!("| {} |", cells.join(" | ")));
+            if i == 0 {
+                let separators: Vec<&str> = (0..columns)
+                    .map(
+                        |c| match row.get(c).and_then(|(_, align)| align.as_deref()) {
+                            Some("center") => ":---:",
+                            Some("right") => "---:",
+                            Some("left") => ":---",
+                            _ => "---",
+                        },
+                    )
+                    .collect();
+                lines.push(format!("| {} |", separators.join(" | ")));
+            }
+        }
+        let table = lines.join("\n");
+        match caption {
+            Some(caption) if !caption.is_empty() => format!("{}\n\n{}", caption, table),
+            _ => table,
+        }
+    }
+
+    fn collect_rows(
+        &mut self,
+        node: &NodeRef,
+        rows: &mut Vec<Vec<(String, Option<String>)>>,
+        caption: &mut Option<String>,
+    ) {
+        for child in node.children() {
+            let Some(element) = child.as_element() else {
+                continue;
+            };
+            match tag_name(element) {
+                "thead" | "tbody" | "tfoot" => self.collect_rows(&child, rows, caption),
+                "caption" => *caption = Some(normalize_paragraph(&self.inline_children(&child))),
+                "tr" => {
+                    let mut row = Vec::new();
+                    for cell in child.children() {
+                        let Some(cell_element) = cell.as_element() else {
+                            continue;
+                        };
+                        if !matches!(tag_name(cell_element), "td" | "th") {
+                            continue;
+                        }
+                        let align = attr(cell_element, "align")
+                            .or_else(|| style_value(cell_element, "text-align"))
+                            .map(|a| a.to_ascii_lowercase());
+                        let span = attr(cell_element, "colspan")
+                            .and_then(|s| s.trim().parse::<usize>().ok())
+                            .unwrap_or(1)
+                            .clamp(1, 64);
+                        row.push((self.cell(&cell), align));
+                        for _ in 1..span {
+                            row.push((String::new(), None));
+                        }
+                    }
+                    rows.push(row);
+                }
+                _ => {}
+            }
+        }
+    }
+
+    /// 单元格只能包含行内内容：多个段落之间用 `<br>` 分隔
+    fn cell(&mut self, node: &NodeRef) -> String {
+        let body = self.container(node, false);
+        body.lines()
+            .map(|line| line.trim().trim_end_matches('\\').trim_end())
+            .filter(|line| !line.is_empty())
+            .collect::<Vec<&str>>()
+            .join("<br>")
+            .replace('|', "\\|")
+    }
+}
+
+/// 代码块内容：保留原样文本，`<br>` 和按行包装的块级标签转为换行
+fn preformatted_text(node: &NodeRef, out: &mut String) {
+    for child in node.children() {
+        match child.data() {
+            NodeData::Text(text) => out.push_str(&text.borrow()),
+            NodeData::Element(element) => {
+                let tag = tag_name(element);
+                if tag == "br" {
+                    out.push('\n');
+                    continue;
+                }
+                preformatted_text(&child, out);
+                if matches!(tag, "div" | "p" | "li") && !out.ends_with('\n') {
+                    out.push('\n');
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// 从 `language-xxx` / `lang-xxx` 类名中取代码语言
+fn code_language(node: &NodeRef) -> Option<String> {
+    node.inclusive_descendants()
+        .filter_map(|n| n.as_element().and_then(|e| attr(e, "class")))
+        .flat_map(|class| {
+            class
+                .split_whitespace()
+                .filter_map(|token| {
+                    token
+                        .strip_prefix("language-")
+                        .or_else(|| token.strip_prefix("lang-"))
+                        .map(str::to_ascii_lowercase)
+                })
+                .collect::<Vec<String>>()
+        })
+        .find(|lang| {
+            !lang.is_empty()
+                && lang
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '+' | '-' | '#' | '.'))
+        })
+}
+
+fn code_block(node: &NodeRef) -> String {
+    let mut code = String::new();
+    preformatted_text(node, &mut code);
+    let code = code.trim_end_matches(['\n', '\r']).replace("\r\n", "\n");
+    let fence = "`".repeat(longest_run(&code, '`').max(2) + 1);
+    format!(
+        "{}{}\n{}\n{}",
+        fence,
+        code_language(node).unwrap_or_default(),
+        code,
+        fence
+    )
+}
+
+/// 解析 HTML 文档（也接受片段）
+pub fn parse(html: &str) -> NodeRef {
+    kuchikiki::parse_html().one(html).document_node
+}
+
+/// 文档标题：`<title>`，没有时取第一个一级标题
+pub fn title(document: &NodeRef) -> Option<String> {
+    ["title", "h1"].iter().find_map(|tag| {
+        document
+            .descendants()
+            .find(|n| n.as_element().is_some_and(|e| tag_name(e) == *tag))
+            .map(|n| collapse_whitespace(&n.text_contents()).trim().to_string())
+            .filter(|title| !title.is_empty())
+    })
+}
+
+/// 把解析后的 HTML 转换为 Markdown
+pub fn to_markdown(node: &NodeRef, resolve_image: &mut ImageResolver<'_>) -> String {
+    let mut converter = Converter { resolve_image };
+    let mut blocks = Blocks::new(true);
+    converter.blocks(node, &mut blocks);
+    let mut markdown = blocks.finish(false);
+    if !markdown.is_empty() {
+        markdown.push('\n');
+    }
+    markdown
+}
+
+/// 把 HTML 文本转换为 Markdown
+pub fn html_to_markdown(html: &str, resolve_image: &mut ImageResolver<'_>) -> String {
+    to_markdown(&parse(html), resolve_image)
+}
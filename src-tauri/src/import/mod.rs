@@ -0,0 +1,328 @@
+//! 文档导入
+//!
+//! 把其它应用导出的 HTML、Word（.docx）和 Evernote（.enex）文件转换为 Markdown 文档，
+//! 图片和附件保存到新文档旁的资源目录。各格式的解析放在独立的子模块中。
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+
+use base64::Engine;
+use serde::{Deserialize, Serialize};
+use tauri::AppHandle;
+
+use crate::access::{self, AccessKind};
+use crate::assets;
+use crate::error::VividError;
+use crate::render;
+
+pub mod docx;
+pub mod enex;
+pub mod html;
+
+/// 文件名中保留的标题长度（字符）
+const MAX_TITLE_LEN: usize = 80;
+
+/// 导入格式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ImportFormat {
+    Html,
+    Docx,
+    Enex,
+}
+
+impl ImportFormat {
+    /// 按扩展名识别格式
+    pub fn from_path(path: &Path) -> Option<Self> {
+        let ext = path.extension()?.to_str()?.to_ascii_lowercase();
+        match ext.as_str() {
+            "html" | "htm" | "xhtml" => Some(ImportFormat::Html),
+            "docx" => Some(ImportFormat::Docx),
+            "enex" => Some(ImportFormat::Enex),
+            _ => None,
+        }
+    }
+}
+
+/// 导入参数
+#[derive(Debug, Deserialize)]
+pub struct ImportDocumentParams {
+    /// 要导入的文件
+    pub path: String,
+    /// 文件格式，默认按扩展名识别
+    pub format: Option<ImportFormat>,
+    /// 新文档的保存目录，默认与源文件同目录（.enex 的笔记放在以文件名命名的子目录中）
+    pub target_dir: Option<String>,
+    /// 资源目录，规则同 `save_asset`
+    pub assets_dir: Option<String>,
+}
+
+/// 导入生成的文档
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ImportedDocument {
+    pub path: String,
+    pub title: String,
+    /// 保存到资源目录的图片和附件数量
+    pub assets: usize,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ImportResult {
+    pub documents: Vec<ImportedDocument>,
+    /// 未能导入的图片等非致命问题
+    pub warnings: Vec<String>,
+}
+
+/// 把图片和附件保存到某个文档的资源目录，返回相对于文档的链接
+pub struct AssetWriter {
+    dir: PathBuf,
+    document_dir: PathBuf,
+    stored: usize,
+    warnings: Vec<String>,
+}
+
+impl AssetWriter {
+    pub fn new(document: &Path, assets_dir: Option<&str>) -> Self {
+        AssetWriter {
+            dir: assets::resolve_assets_dir(document, assets_dir),
+            document_dir: document.parent().unwrap_or(Path::new(".")).to_path_buf(),
+            stored: 0,
+            warnings: Vec::new(),
+        }
+    }
+
+    /// 保存内容，失败时记录警告并返回 `None`
+    pub fn store(&mut self, bytes: &[u8], name: Option<&str>) -> Option<String> {
+        match assets::store_asset(&self.dir, bytes, name) {
+            Ok((path, _)) => {
+                self.stored += 1;
+                Some(
+                    assets::relative_path(&self.document_dir, &path)
+                        .map(|rel| assets::path_to_link(&rel))
+                        .unwrap_or_else(|| assets::path_to_link(&path)),
+                )
+            }
+            Err(e) => {
+                self.warnings
+                    .push(format!("{}: {}", name.unwrap_or("attachment"), e));
+                None
+            }
+        }
+    }
+
+    /// 保存失败的图片和附件
+    pub fn warnings(&self) -> &[String] {
+        &self.warnings
+    }
+
+    /// 保存 HTML 中引用的图片：data URI 解码，本地文件复制，远程地址保持不变
+    pub fn import_source(&mut self, src: &str, base_dir: &Path) -> Option<String> {
+        if let Some(data) = src.strip_prefix("data:") {
+            let (meta, payload) = data.split_once(',')?;
+            let bytes = if meta.ends_with(";base64") {
+                base64::engine::general_purpose::STANDARD
+                    .decode(payload.trim())
+                    .ok()?
+            } else {
+                render::percent_decode(payload).into_bytes()
+            };
+            return self.store(&bytes, None);
+        }
+        let is_remote = ["http://", "https://", "//", "mailto:"]
+            .iter()
+            .any(|prefix| src.starts_with(prefix));
+        if is_remote {
+            return None;
+        }
+
+        let mut local = src.strip_prefix("file://").unwrap_or(src);
+        // `file:///C:/...` 的盘符前多一个 `/`
+        if local.starts_with('/') && local.get(2..3) == Some(":") {
+            local = &local[1..];
+        }
+        let local = render::percent_decode(local.split(['?', '#']).next().unwrap_or(local));
+        let path = base_dir.join(&local);
+        match fs::read(&path) {
+            Ok(bytes) => {
+                let name = path.file_name().map(|n| n.to_string_lossy().to_string());
+                self.store(&bytes, name.as_deref())
+            }
+            Err(e) => {
+                self.warnings.push(format!("{}: {}", src, e));
+                None
+            }
+        }
+    }
+}
+
+/// 由标题生成文件名：去掉文件系统不允许的字符
+fn file_stem_for(title: &str) -> String {
+    let cleaned: String = title
+        .chars()
+        .map(|c| match c {
+            '/' | '\\' | ':' | '*' | '?' | '"' | '<' | '>' | '|' => ' ',
+            c if c.is_control() => ' ',
+            c => c,
+        })
+        .collect();
+    let stem: String = cleaned
+        .split_whitespace()
+        .collect::<Vec<&str>>()
+        .join(" ")
+        .chars()
+        .take(MAX_TITLE_LEN)
+        .collect();
+    let stem = stem.trim_matches(['.', ' ']);
+    if stem.is_empty() {
+        "Untitled".to_string()
+    } else {
+        stem.to_string()
+    }
+}
+
+/// 目录中尚未使用的文档路径，重名时追加序号
+fn unique_document_path(dir: &Path, title: &str) -> PathBuf {
+    let stem = file_stem_for(title);
+    let mut path = dir.join(format!("{}.md", stem));
+    let mut n = 2;
+    while path.exists() {
+        path = dir.join(format!("{} {}.md", stem, n));
+        n += 1;
+    }
+    path
+}
+
+/// 导入进度：已生成的文档和警告
+struct Importer {
+    target_dir: PathBuf,
+    assets_dir: Option<String>,
+    result: ImportResult,
+}
+
+impl Importer {
+    /// 为一篇新文档分配路径，用 `convert` 生成内容后写入
+    fn add(
+        &mut self,
+        title: &str,
+        convert: impl FnOnce(&mut AssetWriter) -> Result<String, String>,
+    ) -> Result<(), String> {
+        fs::create_dir_all(&self.target_dir)
+            .map_err(|e| format!("Failed to create directory: {}", e))?;
+        let path = unique_document_path(&self.target_dir, title);
+        let mut assets = AssetWriter::new(&path, self.assets_dir.as_deref());
+        let markdown = convert(&mut assets)?;
+        fs::write(&path, markdown).map_err(|e| format!("Failed to write file: {}", e))?;
+
+        log::debug!("[import_document] Wrote {:?}", path);
+        self.result.warnings.append(&mut assets.warnings);
+        self.result.documents.push(ImportedDocument {
+            path: path.to_string_lossy().to_string(),
+            title: title.to_string(),
+            assets: assets.stored,
+        });
+        Ok(())
+    }
+}
+
+fn import_file(
+    source: &Path,
+    format: ImportFormat,
+    target_dir: PathBuf,
+    assets_dir: Option<String>,
+) -> Result<ImportResult, String> {
+    let bytes = fs::read(source).map_err(|e| format!("Failed to read file: {}", e))?;
+    let stem = source
+        .file_stem()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let mut importer = Importer {
+        target_dir,
+        assets_dir,
+        result: ImportResult {
+            documents: Vec::new(),
+            warnings: Vec::new(),
+        },
+    };
+
+    match format {
+        ImportFormat::Html => {
+            let document = html::parse(&String::from_utf8_lossy(&bytes));
+            let title = html::title(&document).unwrap_or(stem);
+            let base_dir = source.parent().unwrap_or(Path::new(".")).to_path_buf();
+            importer.add(&title, |assets| {
+                Ok(html::to_markdown(&document, &mut |src| {
+                    assets.import_source(src, &base_dir)
+                }))
+            })?;
+        }
+        ImportFormat::Docx => {
+            importer.add(&stem, |assets| docx::to_markdown(&bytes, assets))?;
+        }
+        ImportFormat::Enex => {
+            let notes = enex::parse_enex(&String::from_utf8_lossy(&bytes))?;
+            importer.target_dir = importer.target_dir.join(file_stem_for(&stem));
+            for note in &notes {
+                let title = if note.title.trim().is_empty() {
+                    "Untitled"
+                } else {
+                    note.title.as_str()
+                };
+                importer.add(title, |assets| Ok(enex::note_to_markdown(note, assets)))?;
+            }
+        }
+    }
+    Ok(importer.result)
+}
+
+// 导入 HTML / Word / Evernote 文件，转换为 Markdown 文档
+#[tauri::command]
+pub async fn import_document(
+    app: AppHandle,
+    params: ImportDocumentParams,
+) -> Result<ImportResult, VividError> {
+    let start = Instant::now();
+    let source = PathBuf::from(&params.path);
+
+    log::info!("[import_document] Starting import operation");
+    log::debug!(
+        "[import_document] Source: {}, format: {:?}",
+        params.path,
+        params.format
+    );
+
+    let format = params
+        .format
+        .or_else(|| ImportFormat::from_path(&source))
+        .ok_or_else(|| format!("Unsupported import format: {}", params.path))?;
+    let target_dir = match params.target_dir {
+        Some(dir) => PathBuf::from(dir),
+        None => source
+            .parent()
+            .map(Path::to_path_buf)
+            .ok_or_else(|| format!("Invalid path: {}", params.path))?,
+    };
+    access::ensure_access(&app, &source, AccessKind::Read)?;
+    access::ensure_access(&app, &target_dir, AccessKind::Write)?;
+    let assets_dir = params.assets_dir;
+
+    let result = tauri::async_runtime::spawn_blocking(move || {
+        import_file(&source, format, target_dir, assets_dir)
+    })
+    .await
+    .map_err(|e| format!("Import task failed: {}", e))?
+    .map_err(|e| {
+        log::error!("[import_document] {}", e);
+        e
+    })?;
+
+    for warning in &result.warnings {
+        log::warn!("[import_document] {}", warning);
+    }
+    log::info!(
+        "[import_document] ✓ Success: {} document(s) in {:?}",
+        result.documents.len(),
+        start.elapsed()
+    );
+    Ok(result)
+}
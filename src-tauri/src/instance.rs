@@ -0,0 +1,174 @@
+//! 单实例与启动文件
+//!
+//! 应用已经在运行时，再次启动（例如在文件管理器中双击另一个 `.md` 文件）不会打开第二个进程，
+//! 而是把命令行中的文件参数转发给正在运行的实例，由前端在新标签页中打开。
+//!
+//! 首次启动时通过命令行参数（Windows / Linux 的文件关联）或 macOS 的 `RunEvent::Opened`
+//! 传入的文件，在前端调用 `frontend_ready` 之前先缓存起来，之后逐个以 `open-file` 事件发送；
+//! `vividmark://` 链接（见 `deeplink`）同样经过这里排队。
+
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter, Manager, State};
+
+use crate::access::AccessControl;
+use crate::deeplink::{self, DeepLinkTarget};
+use crate::error::VividError;
+use crate::{encryption, workspace};
+
+/// 第二个实例请求打开文件时发送的事件
+pub const OPEN_FILE_REQUEST_EVENT: &str = "open-file-request";
+
+/// 启动时传入的文件已读取完毕，载荷为 `FileInfo`
+pub const OPEN_FILE_EVENT: &str = "open-file";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OpenFileRequest {
+    pub path: String,
+}
+
+/// 需要等前端就绪后才能处理的打开请求
+#[derive(Debug, Clone, PartialEq)]
+pub enum Launch {
+    File(PathBuf),
+    Navigate(DeepLinkTarget),
+}
+
+#[derive(Default)]
+struct LaunchState {
+    /// 前端是否已注册事件监听
+    ready: bool,
+    /// 前端就绪前收到的请求
+    pending: Vec<Launch>,
+}
+
+/// 启动文件队列
+#[derive(Default)]
+pub struct LaunchFiles {
+    state: Mutex<LaunchState>,
+}
+
+/// 可以打开的文件：Markdown 文件或加密文档
+pub(crate) fn is_openable(path: &Path) -> bool {
+    path.is_file() && (workspace::is_markdown(path) || encryption::is_encrypted_file(path))
+}
+
+/// 从命令行参数中取出要打开的文件：跳过程序名和选项，相对路径按启动目录解析，只保留可以打开的文件
+pub fn file_args(args: &[String], cwd: &Path) -> Vec<PathBuf> {
+    args.iter()
+        .skip(1)
+        .filter(|arg| !arg.starts_with('-'))
+        .map(|arg| {
+            let path = match arg.strip_prefix("file://") {
+                Some(url) => PathBuf::from(crate::render::percent_decode(url)),
+                None => PathBuf::from(arg),
+            };
+            if path.is_relative() {
+                cwd.join(path)
+            } else {
+                path
+            }
+        })
+        .filter(|path| is_openable(path))
+        .collect()
+}
+
+/// 单实例插件回调：唤起主窗口并转发文件参数
+pub fn on_second_instance(app: &AppHandle, args: Vec<String>, cwd: String) {
+    log::info!("[single_instance] Second instance started with {:?}", args);
+
+    if let Some(window) = app.get_webview_window("main") {
+        let _ = window.unminimize();
+        let _ = window.show();
+        let _ = window.set_focus();
+    }
+
+    for path in file_args(&args, Path::new(&cwd)) {
+        // 前端收到请求后调用 `read_file` 打开
+        app.state::<AccessControl>().grant_session(&path);
+        let path = path.to_string_lossy().to_string();
+        log::info!("[single_instance] Forwarding file: {}", path);
+        if let Err(e) = app.emit(OPEN_FILE_REQUEST_EVENT, &OpenFileRequest { path }) {
+            log::warn!("[single_instance] Failed to emit open-file-request: {}", e);
+        }
+    }
+}
+
+/// 读取文件并发送 `open-file` 事件
+fn emit_open_file(app: &AppHandle, path: &Path) {
+    app.state::<AccessControl>().grant_session(path);
+    let result = crate::load_file(app.state(), app.state(), path.to_string_lossy().to_string());
+    match result {
+        Ok(info) => {
+            if let Err(e) = app.emit(OPEN_FILE_EVENT, &info) {
+                log::warn!("[open_file] Failed to emit open-file: {}", e);
+            }
+        }
+        Err(e) => log::error!("[open_file] {}: {}", path.display(), e),
+    }
+}
+
+fn deliver(app: &AppHandle, launch: &Launch) {
+    match launch {
+        Launch::File(path) => emit_open_file(app, path),
+        Launch::Navigate(target) => {
+            if let Err(e) = app.emit(deeplink::DEEP_LINK_EVENT, target) {
+                log::warn!("[deep_link] Failed to emit deep-link-navigate: {}", e);
+            }
+        }
+    }
+}
+
+/// 处理打开请求；前端尚未就绪时先缓存
+pub fn dispatch(app: &AppHandle, launch: Launch) {
+    let queue = app.state::<LaunchFiles>();
+    {
+        let mut state = match queue.state.lock() {
+            Ok(state) => state,
+            Err(e) => {
+                log::error!("[open_file] Launch state poisoned: {}", e);
+                return;
+            }
+        };
+        if !state.ready {
+            log::info!("[open_file] Queued until frontend is ready: {:?}", launch);
+            if !state.pending.contains(&launch) {
+                state.pending.push(launch);
+            }
+            return;
+        }
+    }
+    deliver(app, &launch);
+}
+
+/// 打开启动时传入的文件
+pub fn open_file(app: &AppHandle, path: PathBuf) {
+    if !is_openable(&path) {
+        log::warn!("[open_file] Ignoring unsupported path: {}", path.display());
+        return;
+    }
+    dispatch(app, Launch::File(path));
+}
+
+// 前端注册好事件监听后调用，发送启动前缓存的文件和链接
+#[tauri::command]
+pub fn frontend_ready(app: AppHandle, launch: State<'_, LaunchFiles>) -> Result<usize, VividError> {
+    let pending = {
+        let mut state = launch
+            .state
+            .lock()
+            .map_err(|e| format!("Launch state poisoned: {}", e))?;
+        state.ready = true;
+        std::mem::take(&mut state.pending)
+    };
+    log::info!(
+        "[frontend_ready] Delivering {} launch request(s)",
+        pending.len()
+    );
+    for launch in &pending {
+        deliver(&app, launch);
+    }
+    Ok(pending.len())
+}
@@ -0,0 +1,364 @@
+//! 文件校验与工作区完整性检查
+//!
+//! 同步工具出错时可能悄悄改写或截断文件，而修改时间保持不变，编辑器无从察觉。
+//! `verify_workspace_integrity` 计算工作区内每个文件的 BLAKE3 哈希，与上次检查时保存的索引
+//! （应用数据目录 `integrity/<根目录哈希>.json`）比较：
+//!
+//! - 修改时间变新、内容变化的文件视为正常编辑；
+//! - 内容变化但修改时间没有变新的文件视为被意外修改；
+//! - 无法读取、Markdown 不再是有效的 UTF-8 文本、含有 NUL 字节或被截断为空的文件视为损坏。
+//!
+//! 检查后正常编辑、新增和删除的文件会更新到索引中；有问题的文件保留原记录，直到以
+//! `accept: true` 重新检查，确认当前内容无误。
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{Instant, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use tauri::{AppHandle, Manager};
+
+use crate::access::{self, AccessKind};
+use crate::error::VividError;
+use crate::{encryption, storage, workspace};
+
+/// 哈希算法
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum HashAlgorithm {
+    #[default]
+    Blake3,
+    Sha256,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct FileHash {
+    pub path: String,
+    pub algo: HashAlgorithm,
+    /// 十六进制摘要
+    pub hash: String,
+    pub size: u64,
+}
+
+/// 索引中一个文件的记录
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct IndexEntry {
+    /// BLAKE3 哈希
+    hash: String,
+    size: u64,
+    /// 修改时间（Unix 毫秒）
+    modified: u64,
+}
+
+/// 一个工作区上次检查时的状态，键为相对根目录的路径
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct IntegrityIndex {
+    root: String,
+    checked_at: u64,
+    files: BTreeMap<String, IndexEntry>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum IssueKind {
+    /// 内容变化但修改时间没有变新
+    UnexpectedlyModified,
+    Corrupted,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct IntegrityIssue {
+    /// 相对根目录的路径
+    pub path: String,
+    pub kind: IssueKind,
+    pub message: String,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct IntegrityReport {
+    pub root: String,
+    /// 是否已有上次检查的索引；第一次检查只建立索引
+    pub has_baseline: bool,
+    /// 上次检查时间（Unix 毫秒）
+    pub last_checked: Option<u64>,
+    pub checked: usize,
+    pub issues: Vec<IntegrityIssue>,
+    /// 正常编辑过的文件
+    pub changed: Vec<String>,
+    pub added: Vec<String>,
+    pub removed: Vec<String>,
+}
+
+/// 应用数据目录中的完整性索引
+pub struct IntegrityStore {
+    dir: PathBuf,
+    /// 同一时间只运行一次检查，避免并发写入同一个索引
+    lock: Mutex<()>,
+}
+
+/// 流式计算文件摘要
+fn digest_file(path: &Path, algo: HashAlgorithm) -> io::Result<(String, u64)> {
+    let mut file = fs::File::open(path)?;
+    match algo {
+        HashAlgorithm::Blake3 => {
+            let mut hasher = blake3::Hasher::new();
+            let size = io::copy(&mut file, &mut hasher)?;
+            Ok((hasher.finalize().to_hex().to_string(), size))
+        }
+        HashAlgorithm::Sha256 => {
+            let mut hasher = Sha256::new();
+            let size = io::copy(&mut file, &mut hasher)?;
+            let hash = hasher
+                .finalize()
+                .iter()
+                .map(|b| format!("{:02x}", b))
+                .collect();
+            Ok((hash, size))
+        }
+    }
+}
+
+fn modified_millis(metadata: &fs::Metadata) -> u64 {
+    metadata
+        .modified()
+        .ok()
+        .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+        .map_or(0, |d| d.as_millis() as u64)
+}
+
+/// 递归列出根目录下的所有文件（跳过与文件树相同的隐藏和依赖目录）
+fn workspace_files(root: &Path) -> Vec<PathBuf> {
+    let mut files = Vec::new();
+    let mut pending = vec![root.to_path_buf()];
+    while let Some(dir) = pending.pop() {
+        let Ok(entries) = fs::read_dir(&dir) else {
+            log::warn!("[integrity] Failed to read directory: {}", dir.display());
+            continue;
+        };
+        for entry in entries.flatten() {
+            if workspace::is_ignored(&entry.file_name().to_string_lossy()) {
+                continue;
+            }
+            match entry.file_type() {
+                Ok(t) if t.is_dir() => pending.push(entry.path()),
+                Ok(t) if t.is_file() => files.push(entry.path()),
+                _ => {}
+            }
+        }
+    }
+    files.sort();
+    files
+}
+
+fn relative_key(root: &Path, path: &Path) -> String {
+    path.strip_prefix(root)
+        .unwrap_or(path)
+        .to_string_lossy()
+        .replace('\\', "/")
+}
+
+/// 读取并检查文件，返回索引记录或损坏原因
+fn inspect(path: &Path, previous: Option<&IndexEntry>) -> Result<IndexEntry, String> {
+    let metadata = fs::metadata(path).map_err(|e| format!("Failed to read metadata: {}", e))?;
+    let modified = modified_millis(&metadata);
+    let (hash, size) = if workspace::is_markdown(path) {
+        let data = fs::read(path).map_err(|e| format!("Failed to read file: {}", e))?;
+        // 加密文件是二进制内容，不检查编码
+        if !encryption::is_encrypted(&data) {
+            if data.contains(&0) {
+                return Err("File contains NUL bytes".to_string());
+            }
+            if let Err(e) = std::str::from_utf8(&data) {
+                return Err(format!("File is not valid UTF-8 text: {}", e));
+            }
+        }
+        (blake3::hash(&data).to_hex().to_string(), data.len() as u64)
+    } else {
+        digest_file(path, HashAlgorithm::Blake3)
+            .map_err(|e| format!("Failed to read file: {}", e))?
+    };
+    if size == 0 && previous.is_some_and(|p| p.size > 0) {
+        return Err("File was truncated to 0 bytes".to_string());
+    }
+    Ok(IndexEntry {
+        hash,
+        size,
+        modified,
+    })
+}
+
+impl IntegrityStore {
+    pub fn new(dir: PathBuf) -> Self {
+        IntegrityStore {
+            dir,
+            lock: Mutex::new(()),
+        }
+    }
+
+    /// 工作区的索引文件，按根目录的哈希命名
+    fn index_path(&self, root: &Path) -> PathBuf {
+        let hash = blake3::hash(root.to_string_lossy().as_bytes()).to_hex();
+        self.dir.join(format!("{}.json", &hash[..16]))
+    }
+
+    /// 检查工作区并更新索引；`accept` 为真时把有问题的文件也按当前内容记入索引
+    pub fn verify(&self, root: &Path, accept: bool) -> Result<IntegrityReport, String> {
+        let _guard = self
+            .lock
+            .lock()
+            .map_err(|_| "Integrity index lock poisoned".to_string())?;
+        let index_path = self.index_path(root);
+        let mut previous: IntegrityIndex = storage::load_json(&index_path);
+        let has_baseline = previous.root == root.to_string_lossy();
+
+        let mut report = IntegrityReport {
+            root: root.to_string_lossy().to_string(),
+            has_baseline,
+            last_checked: has_baseline.then_some(previous.checked_at),
+            ..IntegrityReport::default()
+        };
+        if !has_baseline {
+            previous.files.clear();
+        }
+        let mut files = BTreeMap::new();
+
+        for path in workspace_files(root) {
+            let key = relative_key(root, &path);
+            let old = previous.files.remove(&key);
+            report.checked += 1;
+            let entry = match inspect(&path, old.as_ref()) {
+                Ok(entry) => entry,
+                Err(message) => {
+                    report.issues.push(IntegrityIssue {
+                        path: key.clone(),
+                        kind: IssueKind::Corrupted,
+                        message,
+                    });
+                    // 保留原记录，下次检查仍会报告；确认后移除原记录，修复后按新增文件记入
+                    if let (Some(old), false) = (old, accept) {
+                        files.insert(key, old);
+                    }
+                    continue;
+                }
+            };
+            match old {
+                None => {
+                    if has_baseline {
+                        report.added.push(key.clone());
+                    }
+                }
+                Some(old) if old.hash == entry.hash => {}
+                Some(old) if entry.modified > old.modified => report.changed.push(key.clone()),
+                Some(old) => {
+                    report.issues.push(IntegrityIssue {
+                        path: key.clone(),
+                        kind: IssueKind::UnexpectedlyModified,
+                        message: format!(
+                            "Content changed ({} -> {} bytes) but modification time did not advance",
+                            old.size, entry.size
+                        ),
+                    });
+                    if !accept {
+                        files.insert(key, old);
+                        continue;
+                    }
+                }
+            }
+            files.insert(key, entry);
+        }
+        report.removed = previous.files.into_keys().collect();
+
+        let index = IntegrityIndex {
+            root: report.root.clone(),
+            checked_at: storage::now_millis(),
+            files,
+        };
+        storage::save_json(&index_path, &index)?;
+        Ok(report)
+    }
+}
+
+// 计算文件的 BLAKE3（默认）或 SHA-256 摘要
+#[tauri::command]
+pub async fn hash_file(
+    app: AppHandle,
+    path: String,
+    algo: Option<HashAlgorithm>,
+) -> Result<FileHash, VividError> {
+    let algo = algo.unwrap_or_default();
+    let path_buf = PathBuf::from(&path);
+    access::ensure_access(&app, &path_buf, AccessKind::Read)?;
+    if !path_buf.is_file() {
+        log::error!("[hash_file] File does not exist: {}", path);
+        return Err(VividError::not_found(&path_buf));
+    }
+    let target = path_buf.clone();
+    let (hash, size) = tauri::async_runtime::spawn_blocking(move || digest_file(&target, algo))
+        .await
+        .map_err(|e| format!("Hash task failed: {}", e))?
+        .map_err(|e| {
+            log::error!("[hash_file] Failed to read file: {}", e);
+            VividError::io("Failed to read file", &path_buf, &e)
+        })?;
+    log::info!("[hash_file] {:?} {}: {}", algo, path, hash);
+    Ok(FileHash {
+        path,
+        algo,
+        hash,
+        size,
+    })
+}
+
+// 与上次检查的索引比较，报告被意外修改或损坏的文件
+#[tauri::command]
+pub async fn verify_workspace_integrity(
+    app: AppHandle,
+    root: String,
+    accept: Option<bool>,
+) -> Result<IntegrityReport, VividError> {
+    let start = Instant::now();
+    let root_path = PathBuf::from(&root);
+    access::ensure_access(&app, &root_path, AccessKind::Read)?;
+    log::info!("[verify_workspace_integrity] Checking {}", root);
+    if !root_path.is_dir() {
+        log::error!(
+            "[verify_workspace_integrity] Directory does not exist: {}",
+            root
+        );
+        return Err(VividError::dir_not_found(&root_path));
+    }
+    let root_path = root_path.canonicalize().unwrap_or(root_path);
+    let accept = accept.unwrap_or(false);
+
+    let report = tauri::async_runtime::spawn_blocking(move || {
+        app.state::<IntegrityStore>().verify(&root_path, accept)
+    })
+    .await
+    .map_err(|e| format!("Integrity task failed: {}", e))?
+    .map_err(|e| {
+        log::error!("[verify_workspace_integrity] {}", e);
+        VividError::from(e)
+    })?;
+    for issue in &report.issues {
+        log::warn!(
+            "[verify_workspace_integrity] {:?}: {} ({})",
+            issue.kind,
+            issue.path,
+            issue.message
+        );
+    }
+    log::info!(
+        "[verify_workspace_integrity] ✓ Success: {} file(s), {} issue(s), {} changed, {} added, {} removed in {:?}",
+        report.checked,
+        report.issues.len(),
+        report.changed.len(),
+        report.added.len(),
+        report.removed.len(),
+        start.elapsed()
+    );
+    Ok(report)
+}
@@ -0,0 +1,420 @@
+//! 后台任务队列
+//!
+//! 导出和索引等耗时操作可以通过 `start_job(kind, params)` 放到后台执行：命令立即返回任务 id，
+//! 任务状态和进度通过 `job-progress` 事件发送，`cancel_job` 取消排队中或正在运行的任务。
+//!
+//! 同时运行的任务数有上限，超出的任务按提交顺序排队。工作区导出和索引在处理完当前文件
+//! （或当前索引）后响应取消；单个文档的导出无法中途停止，只能在开始前取消。
+
+use std::collections::VecDeque;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tauri::{AppHandle, Emitter, Manager, State};
+
+use crate::access::{self, AccessKind};
+use crate::error::VividError;
+use crate::export::docx::{self, ExportDocxParams};
+use crate::export::html::{self, ExportHtmlParams};
+use crate::export::pdf::PdfExportOptions;
+use crate::export::theme::ThemeStore;
+use crate::export::workspace::{self as workspace_export, ExportWorkspaceOptions};
+use crate::export::workspace::{WorkspaceExport, WorkspaceExportFormat};
+use crate::workspace::{self, Workspace};
+
+/// 任务状态事件
+pub const JOB_PROGRESS_EVENT: &str = "job-progress";
+/// 同时运行的任务数
+const MAX_WORKERS: usize = 2;
+/// 保留的已结束任务数，供 `list_jobs` 查询
+const MAX_FINISHED_JOBS: usize = 50;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum JobStatus {
+    Queued,
+    Running,
+    Completed,
+    Failed,
+    Cancelled,
+}
+
+impl JobStatus {
+    fn is_finished(self) -> bool {
+        matches!(
+            self,
+            JobStatus::Completed | JobStatus::Failed | JobStatus::Cancelled
+        )
+    }
+}
+
+/// 工作区导出任务的参数
+#[derive(Debug, Deserialize)]
+pub struct ExportWorkspaceJob {
+    pub root: String,
+    pub format: WorkspaceExportFormat,
+    #[serde(default)]
+    pub options: ExportWorkspaceOptions,
+}
+
+/// 在后端渲染 PDF 的参数，与 `export_pdf` 设置 `path` 时相同
+#[derive(Debug, Deserialize)]
+pub struct ExportPdfJob {
+    pub path: String,
+    pub content: String,
+    pub title: Option<String>,
+    pub options: Option<PdfExportOptions>,
+}
+
+/// 任务类型及其参数
+#[derive(Debug, Deserialize)]
+#[serde(tag = "kind", content = "params", rename_all = "snake_case")]
+enum JobRequest {
+    ExportWorkspace(ExportWorkspaceJob),
+    /// 重建当前工作区的所有索引，不需要参数
+    IndexWorkspace {},
+    ExportHtml(ExportHtmlParams),
+    ExportDocx(ExportDocxParams),
+    ExportPdf(ExportPdfJob),
+}
+
+/// 任务的当前状态，也是 `job-progress` 事件的内容
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JobInfo {
+    pub id: String,
+    pub kind: String,
+    pub status: JobStatus,
+    pub done: usize,
+    pub total: usize,
+    /// 当前处理的内容，例如正在导出的文件
+    pub message: Option<String>,
+    /// 完成时的结果，与对应命令的返回值相同
+    pub result: Option<Value>,
+    pub error: Option<String>,
+}
+
+struct Job {
+    info: JobInfo,
+    /// 排队中的任务参数，开始运行时取出
+    request: Option<JobRequest>,
+    cancelled: Arc<AtomicBool>,
+}
+
+#[derive(Default)]
+struct QueueState {
+    jobs: VecDeque<Job>,
+    running: usize,
+}
+
+impl QueueState {
+    /// 只保留最近的若干个已结束任务
+    fn prune_finished(&mut self) {
+        let finished = self
+            .jobs
+            .iter()
+            .filter(|job| job.info.status.is_finished())
+            .count();
+        let mut excess = finished.saturating_sub(MAX_FINISHED_JOBS);
+        self.jobs.retain(|job| {
+            if excess > 0 && job.info.status.is_finished() {
+                excess -= 1;
+                return false;
+            }
+            true
+        });
+    }
+}
+
+/// 后台任务队列
+#[derive(Default)]
+pub struct JobQueue {
+    state: Mutex<QueueState>,
+    next_id: AtomicU64,
+}
+
+impl JobQueue {
+    /// 修改任务状态并发送事件
+    fn update(&self, app: &AppHandle, id: &str, change: impl FnOnce(&mut JobInfo)) {
+        let info = {
+            let Ok(mut state) = self.state.lock() else {
+                return;
+            };
+            let Some(job) = state.jobs.iter_mut().find(|job| job.info.id == id) else {
+                return;
+            };
+            change(&mut job.info);
+            job.info.clone()
+        };
+        emit(app, &info);
+    }
+
+    /// 取出下一个排队的任务并标记为运行中
+    fn next(&self) -> Option<(JobInfo, JobRequest, Arc<AtomicBool>)> {
+        let mut state = self.state.lock().ok()?;
+        if state.running >= MAX_WORKERS {
+            return None;
+        }
+        let job = state
+            .jobs
+            .iter_mut()
+            .find(|job| job.info.status == JobStatus::Queued)?;
+        let request = job.request.take()?;
+        job.info.status = JobStatus::Running;
+        let started = (job.info.clone(), request, job.cancelled.clone());
+        state.running += 1;
+        Some(started)
+    }
+
+    /// 任务结束：释放工作线程并清理已结束的任务
+    fn finish(&self) {
+        let Ok(mut state) = self.state.lock() else {
+            return;
+        };
+        state.running = state.running.saturating_sub(1);
+        state.prune_finished();
+    }
+}
+
+fn emit(app: &AppHandle, info: &JobInfo) {
+    if let Err(e) = app.emit(JOB_PROGRESS_EVENT, info) {
+        log::warn!("[jobs] Failed to emit {}: {}", JOB_PROGRESS_EVENT, e);
+    }
+}
+
+/// 任务运行时的进度和取消状态
+struct JobContext {
+    app: AppHandle,
+    id: String,
+    cancelled: Arc<AtomicBool>,
+}
+
+impl JobContext {
+    fn progress(&self, done: usize, total: usize, message: Option<String>) {
+        self.app
+            .state::<JobQueue>()
+            .update(&self.app, &self.id, |info| {
+                info.done = done;
+                info.total = total;
+                info.message = message;
+            });
+    }
+
+    fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::SeqCst)
+    }
+}
+
+fn to_value(result: impl Serialize) -> Result<Value, String> {
+    serde_json::to_value(result).map_err(|e| format!("Failed to serialize job result: {}", e))
+}
+
+/// 执行任务，返回结果和是否被取消
+async fn run(ctx: Arc<JobContext>, request: JobRequest) -> Result<(Value, bool), String> {
+    let app = ctx.app.clone();
+    match request {
+        JobRequest::ExportWorkspace(job) => {
+            let root = PathBuf::from(&job.root);
+            if !root.is_dir() {
+                return Err(format!("Directory does not exist: {}", root.display()));
+            }
+            let output =
+                workspace_export::output_dir(&root, &job.options).map_err(|e| e.to_string())?;
+            access::ensure_access(&app, &root, AccessKind::Read).map_err(|e| e.to_string())?;
+            access::ensure_access(&app, &output, AccessKind::Write).map_err(|e| e.to_string())?;
+            tauri::async_runtime::spawn_blocking(move || {
+                let exports = app.state::<WorkspaceExport>();
+                let _guard = exports.begin()?;
+                let progress = |payload: workspace_export::ExportProgress| {
+                    ctx.progress(payload.done, payload.total, payload.path)
+                };
+                // `cancel_workspace_export` 也能取消后台的工作区导出
+                let cancelled = || ctx.is_cancelled() || exports.is_cancelled();
+                let theme = app.state::<ThemeStore>().get(job.options.theme.as_deref());
+                let result = workspace_export::run(
+                    &root,
+                    &output,
+                    job.format,
+                    &job.options,
+                    &theme,
+                    &progress,
+                    &cancelled,
+                );
+                let cancelled = result.cancelled;
+                Ok((to_value(result)?, cancelled))
+            })
+            .await
+            .map_err(|e| format!("Export task failed: {}", e))?
+        }
+        JobRequest::IndexWorkspace {} => {
+            let root = app
+                .state::<Workspace>()
+                .root()
+                .ok_or("No workspace is open")?;
+            tauri::async_runtime::spawn_blocking(move || {
+                let progress = |done, total| ctx.progress(done, total, None);
+                let completed = workspace::reindex(&app, &root, &progress, &|| ctx.is_cancelled());
+                Ok((Value::Null, !completed))
+            })
+            .await
+            .map_err(|e| format!("Index task failed: {}", e))?
+        }
+        JobRequest::ExportHtml(params) => {
+            ctx.progress(0, 1, Some(params.path.clone()));
+            let result = html::export_html(app, params)
+                .await
+                .map_err(|e| e.to_string())?;
+            Ok((to_value(result)?, false))
+        }
+        JobRequest::ExportDocx(params) => {
+            ctx.progress(0, 1, Some(params.path.clone()));
+            let result = docx::export_docx(app, params)
+                .await
+                .map_err(|e| e.to_string())?;
+            Ok((to_value(result)?, false))
+        }
+        JobRequest::ExportPdf(job) => {
+            ctx.progress(0, 1, Some(job.path.clone()));
+            access::ensure_access(&app, Path::new(&job.path), AccessKind::Write)
+                .map_err(|e| e.to_string())?;
+            let result =
+                crate::export_pdf_native(app, job.path, job.content, job.title, job.options)
+                    .await
+                    .map_err(|e| e.to_string())?;
+            Ok((to_value(result)?, false))
+        }
+    }
+}
+
+/// 在空闲的工作线程上启动排队的任务
+fn pump(app: &AppHandle) {
+    let queue = app.state::<JobQueue>();
+    while let Some((info, request, cancelled)) = queue.next() {
+        emit(app, &info);
+        let ctx = Arc::new(JobContext {
+            app: app.clone(),
+            id: info.id.clone(),
+            cancelled,
+        });
+        let app = app.clone();
+        tauri::async_runtime::spawn(async move {
+            let start = Instant::now();
+            log::info!("[jobs] Running {} ({})", info.id, info.kind);
+            let outcome = run(ctx.clone(), request).await;
+            let queue = app.state::<JobQueue>();
+            queue.update(&app, &info.id, |job| match outcome {
+                Ok((result, cancelled)) => {
+                    job.status = if cancelled {
+                        JobStatus::Cancelled
+                    } else {
+                        JobStatus::Completed
+                    };
+                    job.result = Some(result);
+                    job.message = None;
+                }
+                Err(e) => {
+                    log::error!("[jobs] {} failed: {}", job.id, e);
+                    job.status = JobStatus::Failed;
+                    job.error = Some(e);
+                }
+            });
+            log::info!("[jobs] ✓ Finished {} in {:?}", info.id, start.elapsed());
+            queue.finish();
+            pump(&app);
+        });
+    }
+}
+
+// 提交后台任务，返回任务 id；进度通过 `job-progress` 事件发送
+#[tauri::command]
+pub fn start_job(
+    app: AppHandle,
+    queue: State<'_, JobQueue>,
+    kind: String,
+    params: Option<Value>,
+) -> Result<String, VividError> {
+    let params = params
+        .filter(|params| !params.is_null())
+        .unwrap_or_else(|| Value::Object(Default::default()));
+    let request: JobRequest = serde_json::from_value(
+        serde_json::json!({ "kind": kind, "params": params }),
+    )
+    .map_err(|e| {
+        log::warn!("[start_job] Invalid {} job: {}", kind, e);
+        VividError::invalid_input(format!("Invalid {} job: {}", kind, e))
+    })?;
+
+    let id = format!("job-{}", queue.next_id.fetch_add(1, Ordering::SeqCst) + 1);
+    let info = JobInfo {
+        id: id.clone(),
+        kind,
+        status: JobStatus::Queued,
+        done: 0,
+        total: 0,
+        message: None,
+        result: None,
+        error: None,
+    };
+    queue
+        .state
+        .lock()
+        .map_err(|e| e.to_string())?
+        .jobs
+        .push_back(Job {
+            info: info.clone(),
+            request: Some(request),
+            cancelled: Arc::new(AtomicBool::new(false)),
+        });
+    log::info!("[start_job] Queued {} ({})", id, info.kind);
+    emit(&app, &info);
+    pump(&app);
+    Ok(id)
+}
+
+// 取消任务：排队中的任务直接取消，运行中的任务在下一个检查点停止
+//
+// 返回是否发出了取消：排队中和运行中的任务返回 `true`，已结束的任务返回 `false`。
+// 运行中的任务可能在响应取消前完成，最终状态以 `job-progress` 事件为准。
+#[tauri::command]
+pub fn cancel_job(
+    app: AppHandle,
+    queue: State<'_, JobQueue>,
+    id: String,
+) -> Result<bool, VividError> {
+    let cancelled = {
+        let mut state = queue.state.lock().map_err(|e| e.to_string())?;
+        let Some(job) = state.jobs.iter_mut().find(|job| job.info.id == id) else {
+            return Err(VividError::invalid_input(format!("Job not found: {}", id)));
+        };
+        match job.info.status {
+            JobStatus::Queued => {
+                job.request = None;
+                job.info.status = JobStatus::Cancelled;
+                let info = job.info.clone();
+                state.prune_finished();
+                Some(info)
+            }
+            JobStatus::Running => {
+                job.cancelled.store(true, Ordering::SeqCst);
+                None
+            }
+            _ => return Ok(false),
+        }
+    };
+    log::info!("[cancel_job] Cancelling {}", id);
+    if let Some(info) = cancelled {
+        emit(&app, &info);
+    }
+    pump(&app);
+    Ok(true)
+}
+
+// 列出排队中、运行中和最近结束的任务
+#[tauri::command]
+pub fn list_jobs(queue: State<'_, JobQueue>) -> Result<Vec<JobInfo>, VividError> {
+    let state = queue.state.lock().map_err(|e| e.to_string())?;
+    Ok(state.jobs.iter().map(|job| job.info.clone()).collect())
+}
@@ -0,0 +1,251 @@
+//! 大文件分块读取
+//!
+//! 超过阈值的文件不再通过一次 IPC 整体返回：`open_large_file` 扫描一遍文件建立行索引，
+//! 返回句柄和总行数，前端滚动时再用 `read_chunk` 按行读取可见区域。
+
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::io::{BufReader, Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::Instant;
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use tauri::{AppHandle, State};
+
+use crate::access::{self, AccessKind};
+use crate::error::VividError;
+use crate::revision;
+
+/// 超过该大小（字节）的文件由 `read_file` 自动切换为分块读取
+pub const LARGE_FILE_THRESHOLD: u64 = 8 * 1024 * 1024;
+/// 单次 `read_chunk` 最多返回的字节数
+const MAX_CHUNK_BYTES: u64 = 4 * 1024 * 1024;
+
+/// 已打开的大文件
+#[derive(Debug, Serialize, Deserialize)]
+pub struct LargeFileInfo {
+    pub handle: String,
+    pub path: String,
+    pub name: String,
+    pub size: u64,
+    pub line_count: usize,
+    pub revision: String,
+}
+
+/// 一段按行读取的内容
+#[derive(Debug, Serialize, Deserialize)]
+pub struct FileChunk {
+    /// 起始行（从 0 开始）
+    pub offset: usize,
+    /// 实际返回的行数
+    pub len: usize,
+    pub content: String,
+    /// 是否已读到文件末尾
+    pub eof: bool,
+}
+
+struct LargeFile {
+    path: PathBuf,
+    size: u64,
+    /// 每行起始的字节偏移
+    line_starts: Vec<u64>,
+}
+
+impl LargeFile {
+    fn line_count(&self) -> usize {
+        self.line_starts.len()
+    }
+
+    /// 第 `line` 行起始的字节偏移，超出范围时返回文件末尾
+    fn line_start(&self, line: usize) -> u64 {
+        self.line_starts.get(line).copied().unwrap_or(self.size)
+    }
+}
+
+/// 已打开的大文件句柄
+#[derive(Default)]
+pub struct LargeFileStore {
+    files: Mutex<HashMap<String, LargeFile>>,
+    next_handle: AtomicU64,
+}
+
+/// 扫描文件：计算行起始偏移和内容哈希
+fn scan(path: &Path) -> std::io::Result<(Vec<u64>, String, u64)> {
+    let mut reader = BufReader::new(File::open(path)?);
+    let mut buf = vec![0u8; 64 * 1024];
+    let mut hasher = Sha256::new();
+    let mut line_starts = vec![0u64];
+    let mut pos = 0u64;
+
+    loop {
+        let n = reader.read(&mut buf)?;
+        if n == 0 {
+            break;
+        }
+        hasher.update(&buf[..n]);
+        for (i, b) in buf[..n].iter().enumerate() {
+            if *b == b'\n' {
+                line_starts.push(pos + i as u64 + 1);
+            }
+        }
+        pos += n as u64;
+    }
+    // 以换行结尾时最后一个“行首”就是文件末尾，不算作新的一行
+    if line_starts.len() > 1 && line_starts.last() == Some(&pos) {
+        line_starts.pop();
+    }
+
+    let hash: String = hasher
+        .finalize()
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect();
+    Ok((line_starts, hash, pos))
+}
+
+impl LargeFileStore {
+    /// 打开文件并登记句柄
+    pub fn open(&self, path: &Path) -> Result<LargeFileInfo, String> {
+        let (line_starts, hash, size) = scan(path).map_err(|e| {
+            log::error!("[open_large_file] Failed to read {}: {}", path.display(), e);
+            format!("Failed to read file: {}", e)
+        })?;
+        let revision = revision::revision_with_hash(&hash, fs::metadata(path).ok().as_ref());
+        let handle = format!(
+            "large-{}",
+            self.next_handle.fetch_add(1, Ordering::Relaxed) + 1
+        );
+        let info = LargeFileInfo {
+            handle: handle.clone(),
+            path: path.to_string_lossy().to_string(),
+            name: path
+                .file_name()
+                .and_then(|n| n.to_str())
+                .unwrap_or("Untitled.md")
+                .to_string(),
+            size,
+            line_count: line_starts.len(),
+            revision,
+        };
+        self.files.lock().map_err(|e| e.to_string())?.insert(
+            handle,
+            LargeFile {
+                path: path.to_path_buf(),
+                size,
+                line_starts,
+            },
+        );
+        Ok(info)
+    }
+
+    /// 读取从第 `offset` 行开始的 `len` 行
+    pub fn read(&self, handle: &str, offset: usize, len: usize) -> Result<FileChunk, String> {
+        let files = self.files.lock().map_err(|e| e.to_string())?;
+        let file = files
+            .get(handle)
+            .ok_or_else(|| format!("Unknown file handle: {}", handle))?;
+
+        let offset = offset.min(file.line_count());
+        let start = file.line_start(offset);
+        // 按行截取，但不超过单次读取上限（至少返回一行）
+        let mut end_line = offset.saturating_add(len).min(file.line_count());
+        while end_line > offset + 1 && file.line_start(end_line) - start > MAX_CHUNK_BYTES {
+            end_line = offset + (end_line - offset) / 2;
+        }
+        let end = file.line_start(end_line);
+
+        let mut reader =
+            File::open(&file.path).map_err(|e| format!("Failed to read file: {}", e))?;
+        reader
+            .seek(SeekFrom::Start(start))
+            .map_err(|e| format!("Failed to read file: {}", e))?;
+        let mut bytes = vec![0u8; (end - start) as usize];
+        reader
+            .read_exact(&mut bytes)
+            .map_err(|e| format!("Failed to read file: {}", e))?;
+
+        Ok(FileChunk {
+            offset,
+            len: end_line - offset,
+            content: String::from_utf8_lossy(&bytes).into_owned(),
+            eof: end_line >= file.line_count(),
+        })
+    }
+
+    /// 句柄对应的文件
+    pub fn path(&self, handle: &str) -> Option<PathBuf> {
+        Some(self.files.lock().ok()?.get(handle)?.path.clone())
+    }
+
+    pub fn close(&self, handle: &str) -> Result<bool, String> {
+        Ok(self
+            .files
+            .lock()
+            .map_err(|e| e.to_string())?
+            .remove(handle)
+            .is_some())
+    }
+}
+
+// 以分块模式打开大文件，返回句柄与总行数
+#[tauri::command]
+pub fn open_large_file(
+    app: AppHandle,
+    store: State<'_, LargeFileStore>,
+    path: String,
+) -> Result<LargeFileInfo, VividError> {
+    let start = Instant::now();
+    access::ensure_access(&app, Path::new(&path), AccessKind::Read)?;
+    log::info!("[open_large_file] Opening {}", path);
+    let info = store.open(Path::new(&path))?;
+    log::info!(
+        "[open_large_file] ✓ Success: {} ({} bytes, {} lines) in {:?}",
+        info.handle,
+        info.size,
+        info.line_count,
+        start.elapsed()
+    );
+    Ok(info)
+}
+
+// 读取大文件中从 `offset` 行开始的 `len` 行
+#[tauri::command]
+pub fn read_chunk(
+    app: AppHandle,
+    store: State<'_, LargeFileStore>,
+    handle: String,
+    offset: usize,
+    len: usize,
+) -> Result<FileChunk, VividError> {
+    // 打开后访问范围可能已经收紧（例如切换了工作区）
+    if let Some(path) = store.path(&handle) {
+        access::ensure_access(&app, &path, AccessKind::Read)?;
+    }
+    let chunk = store.read(&handle, offset, len).map_err(|e| {
+        log::error!("[read_chunk] {}", e);
+        e
+    })?;
+    log::debug!(
+        "[read_chunk] {} lines {}..{} ({} bytes)",
+        handle,
+        chunk.offset,
+        chunk.offset + chunk.len,
+        chunk.content.len()
+    );
+    Ok(chunk)
+}
+
+// 关闭大文件句柄
+#[tauri::command]
+pub fn close_large_file(
+    store: State<'_, LargeFileStore>,
+    handle: String,
+) -> Result<(), VividError> {
+    if store.close(&handle)? {
+        log::debug!("[close_large_file] Closed {}", handle);
+    }
+    Ok(())
+}
@@ -1,13 +1,89 @@
 use serde::{Deserialize, Serialize};
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::time::Instant;
-use tauri::{Manager, WebviewWindow};
+use tauri::{AppHandle, Manager, WebviewWindow};
+
+use error::VividError;
 
 #[cfg(unix)]
 use std::os::unix::fs::PermissionsExt;
 
+mod access;
+mod archive;
+mod assets;
+mod attachments;
+mod backup;
+mod bibliography;
+mod calendar;
+mod cli;
+mod clipboard;
+mod daily;
+mod deeplink;
+mod diagram;
+mod docstate;
+mod dropped;
+mod duplicates;
+mod encryption;
+mod error;
+mod export;
+mod extract;
+mod fileinfo;
+mod frontmatter;
+mod git;
+mod graph;
+mod highlight;
+mod i18n;
+mod import;
+mod instance;
+mod integrity;
+mod jobs;
+mod largefile;
+mod linkcheck;
+mod links;
+mod lint;
+mod markdown;
+mod math;
+mod merge;
+mod metadata;
+mod ocr;
+mod parse;
+mod patch;
+mod paths;
+mod perf;
+mod plugins;
+mod quickopen;
+mod readonly;
+mod recovery;
+mod references;
+mod rename;
+mod render;
+mod replace;
+mod retry;
+mod revision;
+mod search;
+mod secrets;
+mod sections;
+mod session;
+mod settings;
+mod snapshots;
+mod snippets;
+mod sourcemap;
+mod spellcheck;
+mod stats;
+mod storage;
+mod sync;
+mod tables;
+mod tags;
+mod tasks;
+mod templates;
+mod tools;
+mod windows;
+mod workspace;
+mod workspace_config;
+mod writing_sessions;
 
+use export::pdf::PdfExportOptions;
 
 /// 文件树项
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -31,12 +107,114 @@ pub struct FileInfo {
     pub path: String,
     pub content: String,
     pub name: String,
+    /// 修订标记（修改时间 + 内容哈希），保存时作为 `expected_revision` 传回
+    pub revision: String,
+    /// 文件超过大小阈值时为分块读取句柄，此时 `content` 为空，需通过 `read_chunk` 读取
+    pub large: Option<largefile::LargeFileInfo>,
+    /// 文件以加密格式存储；未解锁时 `content` 为空，需通过 `decrypt_file` 输入口令
+    pub encrypted: bool,
+    /// 文件只读或没有写权限，编辑器应提示而不是等到保存失败
+    pub read_only: bool,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct SaveResult {
     pub success: bool,
     pub error: Option<String>,
+    /// 保存后的修订标记
+    pub revision: Option<String>,
+    /// 文件在打开后被其它程序修改时返回磁盘上的版本，此时不会写入
+    pub conflict: Option<SaveConflict>,
+    /// 失败类别，成功时为空
+    pub error_kind: Option<SaveErrorKind>,
+    /// 稍后重试是否可能成功
+    pub retryable: bool,
+    /// 实际写入尝试次数，未开始写入时为 0
+    pub attempts: u32,
+    /// 保存前被格式化工具修改过时为写入的内容，前端用它更新编辑器
+    pub formatted_content: Option<String>,
+}
+
+/// 保存失败的类别，前端据此决定提示稍后重试还是直接报错
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SaveErrorKind {
+    /// 文件在打开后被其它程序修改或删除
+    Conflict,
+    /// 文件被占用，重试多次后仍然失败
+    Transient,
+    PermissionDenied,
+    NotFound,
+    /// 文件只读，可以解除只读后重试（`unlock` 参数或 `set_read_only`）
+    ReadOnly,
+    /// 文件被其它程序独占打开，重试多次后仍然失败
+    Locked,
+    /// 磁盘已满、只读文件系统等无法通过重试解决的错误
+    Fatal,
+}
+
+impl SaveErrorKind {
+    /// 对应的错误类别（`VividError` 的 `code`），用于翻译错误消息
+    fn code(self) -> &'static str {
+        match self {
+            SaveErrorKind::Conflict => "conflict",
+            SaveErrorKind::PermissionDenied | SaveErrorKind::ReadOnly => "permission_denied",
+            SaveErrorKind::NotFound => "not_found",
+            SaveErrorKind::Transient | SaveErrorKind::Locked | SaveErrorKind::Fatal => "io",
+        }
+    }
+}
+
+impl SaveResult {
+    // 错误消息按当前语言翻译后返回给前端，与 `VividError` 一致
+    fn conflict(error: &str, conflict: SaveConflict) -> Self {
+        SaveResult {
+            success: false,
+            error: Some(i18n::localize(i18n::current(), SaveErrorKind::Conflict.code(), error)),
+            revision: None,
+            conflict: Some(conflict),
+            error_kind: Some(SaveErrorKind::Conflict),
+            retryable: false,
+            attempts: 0,
+            formatted_content: None,
+        }
+    }
+
+    fn failure(error: String, kind: SaveErrorKind, attempts: u32) -> Self {
+        SaveResult {
+            success: false,
+            error: Some(i18n::localize(i18n::current(), kind.code(), &error)),
+            revision: None,
+            conflict: None,
+            error_kind: Some(kind),
+            retryable: matches!(kind, SaveErrorKind::Transient | SaveErrorKind::Locked),
+            attempts,
+            formatted_content: None,
+        }
+    }
+
+    fn io_failure(message: String, e: &std::io::Error, attempts: u32) -> Self {
+        let kind = if readonly::is_lock_error(e) {
+            SaveErrorKind::Locked
+        } else if retry::is_transient(e) {
+            SaveErrorKind::Transient
+        } else {
+            match e.kind() {
+                std::io::ErrorKind::PermissionDenied => SaveErrorKind::PermissionDenied,
+                std::io::ErrorKind::NotFound => SaveErrorKind::NotFound,
+                _ => SaveErrorKind::Fatal,
+            }
+        };
+        SaveResult::failure(message, kind, attempts)
+    }
+}
+
+/// 保存冲突信息，供界面提供合并 / 覆盖 / 重新加载
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SaveConflict {
+    /// 磁盘上的当前内容，文件已被删除时为空
+    pub disk_content: Option<String>,
+    pub disk_revision: Option<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -46,10 +224,18 @@ pub struct ExportPdfResult {
 }
 
 /// PDF 导出参数
+///
+/// 设置 `path` 时在后端将 `content`（Markdown）直接渲染为 PDF 写入该路径；
+/// 否则沿用旧的浏览器打印方式，使用前端渲染好的 `html_content`。
 #[derive(Debug, Deserialize)]
 pub struct ExportPdfParams {
-    pub html_content: String,
+    pub html_content: Option<String>,
     pub title: Option<String>,
+    pub path: Option<String>,
+    pub content: Option<String>,
+    pub options: Option<PdfExportOptions>,
+    /// 浏览器打印方式使用的导出主题；后端渲染使用 `options.theme`
+    pub theme: Option<String>,
 }
 
 /// 文件元数据信息，用于诊断
@@ -73,10 +259,7 @@ fn get_file_metadata(path: &PathBuf) -> Option<FileMetadata> {
         FileMetadata {
             size: metadata.len(),
             permissions: Some(perm_str),
-            modified: metadata
-                .modified()
-                .ok()
-                .and_then(|t| t.elapsed().ok().map(|d| format!("{:?} ago", d))),
+            modified: metadata.modified().ok().map(fileinfo::iso_time),
             is_file: metadata.is_file(),
         }
     })
@@ -102,9 +285,24 @@ fn format_error_with_context(operation: &str, path: &str, error: &std::io::Error
     )
 }
 
-// 读取文件
+// 读取文件（只能读取允许访问的路径，见 `access`）
 #[tauri::command]
-fn read_file(path: String) -> Result<FileInfo, String> {
+fn read_file(
+    app: AppHandle,
+    large_files: tauri::State<'_, largefile::LargeFileStore>,
+    keys: tauri::State<'_, encryption::EncryptionKeys>,
+    path: String,
+) -> Result<FileInfo, VividError> {
+    access::ensure_access(&app, Path::new(&path), access::AccessKind::Read)?;
+    load_file(large_files, keys, path)
+}
+
+/// 读取文件，不检查访问范围（供后端自行确定路径的功能使用）
+pub(crate) fn load_file(
+    large_files: tauri::State<'_, largefile::LargeFileStore>,
+    keys: tauri::State<'_, encryption::EncryptionKeys>,
+    path: String,
+) -> Result<FileInfo, VividError> {
     let start = Instant::now();
     let path_buf = PathBuf::from(&path);
 
@@ -126,6 +324,48 @@ fn read_file(path: String) -> Result<FileInfo, String> {
         log::warn!("[read_file] Unable to retrieve metadata before reading");
     }
 
+    // 加密文件：已缓存密钥时直接解密，否则由前端提示输入口令后调用 decrypt_file
+    if encryption::is_encrypted_file(&path_buf) {
+        let bytes = fs::read(&path_buf)
+            .map_err(|e| VividError::io("Failed to read file", &path_buf, &e))?;
+        let revision = revision::revision_for(&bytes, fs::metadata(&path_buf).ok().as_ref());
+        let content = keys.unlock_cached(&path_buf, &bytes).unwrap_or_default();
+        perf::record(perf::Operation::Read, &path, start.elapsed(), bytes.len() as u64);
+        log::info!(
+            "[read_file] ✓ Success: {} (encrypted, {})",
+            path,
+            if content.is_empty() { "locked" } else { "unlocked" }
+        );
+        return Ok(FileInfo {
+            path,
+            content,
+            name: file_name_of(&path_buf),
+            revision,
+            large: None,
+            encrypted: true,
+            read_only: readonly::is_read_only(&path_buf),
+        });
+    }
+
+    // 超过阈值的文件改为分块读取，避免一次性通过 IPC 传输
+    let size = fs::metadata(&path_buf).map(|m| m.len()).unwrap_or(0);
+    if size > largefile::LARGE_FILE_THRESHOLD {
+        log::info!(
+            "[read_file] File exceeds {} bytes, switching to chunked mode",
+            largefile::LARGE_FILE_THRESHOLD
+        );
+        let info = large_files.open(&path_buf)?;
+        return Ok(FileInfo {
+            path,
+            content: String::new(),
+            name: info.name.clone(),
+            revision: info.revision.clone(),
+            large: Some(info),
+            encrypted: false,
+            read_only: readonly::is_read_only(&path_buf),
+        });
+    }
+
     let content = fs::read_to_string(&path_buf).map_err(|e| {
         let error_msg = format_error_with_context("read_file", &path, &e);
         log::error!("[read_file] Operation failed: {}", error_msg);
@@ -139,17 +379,14 @@ fn read_file(path: String) -> Result<FileInfo, String> {
             }
         }
         
-        format!("Failed to read file: {}", e)
+        VividError::io("Failed to read file", &path_buf, &e)
     })?;
 
-    let name = path_buf
-        .file_name()
-        .and_then(|n| n.to_str())
-        .unwrap_or("Untitled.md")
-        .to_string();
+    let name = file_name_of(&path_buf);
 
     let size = content.len();
     let elapsed = start.elapsed();
+    perf::record(perf::Operation::Read, &path, elapsed, size as u64);
     
     log::info!(
         "[read_file] ✓ Success: {} ({} bytes, {} chars) in {:?} (~{:.2} MB/s)",
@@ -164,13 +401,39 @@ fn read_file(path: String) -> Result<FileInfo, String> {
         }
     );
 
-    Ok(FileInfo { path, content, name })
+    let revision = revision::revision_for(content.as_bytes(), fs::metadata(&path_buf).ok().as_ref());
+
+    Ok(FileInfo {
+        path,
+        content,
+        name,
+        revision,
+        large: None,
+        encrypted: false,
+        read_only: readonly::is_read_only(&path_buf),
+    })
+}
+
+pub(crate) fn file_name_of(path: &std::path::Path) -> String {
+    path.file_name()
+        .and_then(|n| n.to_str())
+        .unwrap_or("Untitled.md")
+        .to_string()
 }
 
 // 保存文件
 #[tauri::command]
-fn save_file(path: String, content: String) -> Result<SaveResult, String> {
+async fn save_file(
+    window: WebviewWindow,
+    path: String,
+    content: String,
+    expected_revision: Option<String>,
+    encryption: Option<encryption::EncryptOptions>,
+    unlock: Option<bool>,
+) -> Result<SaveResult, VividError> {
     let start = Instant::now();
+    let settings = window.state::<settings::SettingsStore>();
+    let keys = window.state::<encryption::EncryptionKeys>();
     let path_buf = PathBuf::from(&path);
     let content_size = content.len();
     let content_chars = content.chars().count();
@@ -179,6 +442,7 @@ fn save_file(path: String, content: String) -> Result<SaveResult, String> {
     log::debug!("[save_file] Target path: {}", path);
     log::debug!("[save_file] Content size: {} bytes, {} characters", content_size, content_chars);
     log::debug!("[save_file] Path absolute: {:?}", path_buf.canonicalize().ok());
+    access::ensure_access(window.app_handle(), &path_buf, access::AccessKind::Write)?;
 
     // 检查父目录
     if let Some(parent) = path_buf.parent() {
@@ -186,12 +450,54 @@ fn save_file(path: String, content: String) -> Result<SaveResult, String> {
             log::warn!("[save_file] Parent directory does not exist, will attempt to create: {:?}", parent);
             if let Err(e) = fs::create_dir_all(parent) {
                 log::error!("[save_file] Failed to create parent directories: {}", e);
-                return Err(format!("Failed to create directory: {}", e));
+                return Ok(SaveResult::io_failure(
+                    format!("Failed to create directory: {}", e),
+                    &e,
+                    0,
+                ));
             }
             log::info!("[save_file] Created parent directories: {:?}", parent);
         }
     }
 
+    // 检查文件在打开后是否被其它程序修改
+    if let Some(expected) = expected_revision.as_deref() {
+        match revision::current_revision(&path_buf) {
+            Ok(Some((bytes, disk_revision))) if !revision::same_content(expected, &disk_revision) => {
+                log::warn!(
+                    "[save_file] Conflict detected: expected revision {}, found {}",
+                    expected,
+                    disk_revision
+                );
+                return Ok(SaveResult::conflict(
+                    "File was modified by another program",
+                    SaveConflict {
+                        disk_content: if encryption::is_encrypted(&bytes) {
+                            keys.unlock_cached(&path_buf, &bytes)
+                        } else {
+                            Some(String::from_utf8_lossy(&bytes).into_owned())
+                        },
+                        disk_revision: Some(disk_revision),
+                    },
+                ));
+            }
+            Ok(None) => {
+                log::warn!("[save_file] Conflict detected: file was deleted since it was opened");
+                return Ok(SaveResult::conflict(
+                    "File was deleted by another program",
+                    SaveConflict {
+                        disk_content: None,
+                        disk_revision: None,
+                    },
+                ));
+            }
+            Ok(Some(_)) => {}
+            Err(e) => {
+                log::warn!("[save_file] Unable to check revision, saving anyway: {}", e);
+            }
+        }
+    }
+
     // 如果文件已存在，记录原文件元数据
     if path_buf.exists() {
         if let Some(meta) = get_file_metadata(&path_buf) {
@@ -206,21 +512,50 @@ fn save_file(path: String, content: String) -> Result<SaveResult, String> {
         log::debug!("[save_file] Creating new file");
     }
 
-    let write_start = Instant::now();
-    fs::write(&path, &content).map_err(|e| {
-        let error_msg = format_error_with_context("save_file", &path, &e);
-        log::error!("[save_file] Write operation failed: {}", error_msg);
-        
-        // 诊断磁盘空间
-        if e.kind() == std::io::ErrorKind::Other {
-            log::error!("[save_file] Possible causes: insufficient disk space or filesystem error");
-        }
-        
-        format!("Failed to save file: {}", e)
+    // 按设置用外部工具格式化，加密文档的明文不交给外部程序
+    let formats_on_save = settings
+        .get()
+        .external_tools
+        .tools
+        .iter()
+        .any(|tool| tool.format_on_save);
+    let formatted_content = if formats_on_save && !keys.will_encrypt(&path_buf, encryption.as_ref()) {
+        let app = window.app_handle().clone();
+        let (format_path, original) = (path_buf.clone(), content.clone());
+        tauri::async_runtime::spawn_blocking(move || tools::format_on_save(&app, &format_path, &original))
+            .await
+            .unwrap_or_else(|e| {
+                log::warn!("[save_file] Format task failed: {}", e);
+                None
+            })
+    } else {
+        None
+    };
+    let content = formatted_content.clone().unwrap_or(content);
+
+    // 加密文件保持加密存储
+    let data = keys.prepare_save(&path_buf, &content, encryption.as_ref()).map_err(|e| {
+        log::warn!("[save_file] {}", e);
+        e
     })?;
-    
+
+    let write_start = Instant::now();
+    let written = write_document(
+        &window,
+        "save_file",
+        &path_buf,
+        &content,
+        &data,
+        unlock.unwrap_or(false),
+    );
+    let (revision, attempts) = match written {
+        Ok(written) => written,
+        Err(failure) => return Ok(failure),
+    };
+
     let write_elapsed = write_start.elapsed();
     let total_elapsed = start.elapsed();
+    perf::record(perf::Operation::Save, &path, total_elapsed, data.len() as u64);
 
     // 验证写入后的文件
     if let Some(meta) = get_file_metadata(&path_buf) {
@@ -230,10 +565,10 @@ fn save_file(path: String, content: String) -> Result<SaveResult, String> {
             meta.permissions
         );
         
-        if meta.size as usize != content_size {
+        if meta.size as usize != data.len() {
             log::warn!(
                 "[save_file] Size mismatch! Expected {} bytes, found {} bytes",
-                content_size,
+                data.len(),
                 meta.size
             );
         }
@@ -255,9 +590,116 @@ fn save_file(path: String, content: String) -> Result<SaveResult, String> {
     Ok(SaveResult {
         success: true,
         error: None,
+        revision: Some(revision),
+        conflict: None,
+        error_kind: None,
+        retryable: false,
+        attempts,
+        formatted_content,
     })
 }
 
+/// 保存的写入阶段，`save_file` 和 `apply_patch`（增量保存）共用
+///
+/// 检查只读（`unlock` 时先解除）、按设置备份原文件、原子写入（文件被占用时退避重试），
+/// 写入后清除崩溃恢复草稿、通知其它窗口、记录写作统计并交给插件。`content` 为文档明文，
+/// `data` 为实际写入的字节（加密文档为密文）。成功时返回新的修订标记和写入尝试次数，
+/// 失败时返回交给前端的 `SaveResult`。
+pub(crate) fn write_document(
+    window: &WebviewWindow,
+    command: &str,
+    path_buf: &Path,
+    content: &str,
+    data: &[u8],
+    unlock: bool,
+) -> Result<(String, u32), SaveResult> {
+    let path = path_buf.to_string_lossy();
+
+    // 只读文件提前返回，用户确认后以 `unlock` 重新保存时先解除只读
+    match readonly::write_access(path_buf) {
+        readonly::WriteAccess::ReadOnly if unlock => {
+            if let Err(e) = readonly::set_permission(path_buf, false) {
+                log::error!("[{}] Failed to clear read-only flag: {}", command, e);
+                return Err(SaveResult::io_failure(
+                    format!("Failed to clear read-only flag: {}", e),
+                    &e,
+                    0,
+                ));
+            }
+            log::info!("[{}] Cleared read-only flag: {:?}", command, path_buf);
+        }
+        readonly::WriteAccess::ReadOnly => {
+            log::warn!("[{}] File is read-only: {}", command, path);
+            return Err(SaveResult::failure(
+                format!("File is read-only: {}", path),
+                SaveErrorKind::ReadOnly,
+                0,
+            ));
+        }
+        readonly::WriteAccess::Locked => {
+            log::warn!("[{}] File is locked by another program, will retry: {}", command, path);
+        }
+        readonly::WriteAccess::Writable => {}
+    }
+
+    // 覆盖前读取原内容，用于写作统计
+    let previous = if encryption::is_encrypted(data) {
+        None
+    } else {
+        writing_sessions::read_previous(path_buf)
+    };
+
+    // 覆盖前按设置备份原文件
+    let current_settings = window.state::<settings::SettingsStore>().get();
+    window
+        .state::<backup::BackupStore>()
+        .before_save(&current_settings.backup, path_buf);
+
+    // 网络盘、同步盘上的文件可能短暂被占用，按设置退避重试；
+    // 先写临时文件再替换，写入中断时不会截断原文件
+    let (written, attempts) = retry::with_retry(&current_settings.save_retry, command, || {
+        storage::replace_atomic(path_buf, data)
+    });
+    if let Err(e) = written {
+        let error_msg = format_error_with_context(command, &path, &e);
+        log::error!(
+            "[{}] Write operation failed after {} attempt(s): {}",
+            command,
+            attempts,
+            error_msg
+        );
+
+        // 诊断磁盘空间
+        if e.kind() == std::io::ErrorKind::Other {
+            log::error!("[{}] Possible causes: insufficient disk space or filesystem error", command);
+        }
+
+        return Err(SaveResult::io_failure(
+            format!("Failed to save file: {}", e),
+            &e,
+            attempts,
+        ));
+    }
+
+    // 已保存的内容不再需要崩溃恢复
+    window.state::<recovery::RecoveryStore>().discard(path_buf);
+
+    let revision = revision::revision_for(data, fs::metadata(path_buf).ok().as_ref());
+    // 通知其它打开了同一文件的窗口
+    window
+        .state::<windows::WindowManager>()
+        .note_saved(window.app_handle(), window.label(), path_buf, &revision);
+    if let Some(previous) = previous {
+        let app = window.app_handle();
+        writing_sessions::WritingSessions::note_saved(app, path_buf, previous, content);
+    }
+    // 加密文档的明文不交给插件
+    if !encryption::is_encrypted(data) {
+        plugins::PluginHost::after_save(window.app_handle(), path_buf, content);
+    }
+    Ok((revision, attempts))
+}
+
 // 检查文件是否存在
 #[tauri::command]
 fn file_exists(path: String) -> bool {
@@ -277,9 +719,10 @@ fn file_exists(path: String) -> bool {
 
 /// 读取目录内容
 #[tauri::command]
-fn read_directory(params: ReadDirectoryParams) -> Result<Vec<FileTreeItem>, String> {
+fn read_directory(app: AppHandle, params: ReadDirectoryParams) -> Result<Vec<FileTreeItem>, VividError> {
     let start = Instant::now();
     let path_buf = PathBuf::from(&params.path);
+    access::ensure_access(&app, &path_buf, access::AccessKind::Read)?;
     let recursive = params.recursive.unwrap_or(false);
 
     log::info!("[read_directory] Starting directory read operation");
@@ -288,12 +731,15 @@ fn read_directory(params: ReadDirectoryParams) -> Result<Vec<FileTreeItem>, Stri
 
     if !path_buf.exists() {
         log::error!("[read_directory] Directory does not exist: {}", params.path);
-        return Err(format!("Directory does not exist: {}", params.path));
+        return Err(VividError::dir_not_found(&params.path));
     }
 
     if !path_buf.is_dir() {
         log::error!("[read_directory] Path is not a directory: {}", params.path);
-        return Err(format!("Path is not a directory: {}", params.path));
+        return Err(VividError::invalid_input(format!(
+            "Path is not a directory: {}",
+            params.path
+        )));
     }
 
     let entries = read_directory_recursive(&path_buf, recursive, 0)
@@ -309,16 +755,177 @@ fn read_directory(params: ReadDirectoryParams) -> Result<Vec<FileTreeItem>, Stri
     Ok(entries)
 }
 
-// 导出 PDF - 使用系统打印对话框
+/// 重命名参数
+#[derive(Debug, Deserialize)]
+pub struct RenameFileParams {
+    pub path: String,
+    /// 新文件名（不含目录），在原目录内重命名
+    pub new_name: String,
+}
+
+// 删除文件或目录：默认移入系统回收站，`permanent` 为 true 时直接删除
+#[tauri::command]
+fn delete_file(app: AppHandle, path: String, permanent: Option<bool>) -> Result<(), VividError> {
+    let start = Instant::now();
+    let path_buf = PathBuf::from(&path);
+    let permanent = permanent.unwrap_or(false);
+
+    log::info!("[delete_file] Starting delete operation");
+    log::debug!("[delete_file] Target path: {}, permanent: {}", path, permanent);
+    access::ensure_access(&app, &path_buf, access::AccessKind::Delete)?;
+
+    if !path_buf.exists() {
+        log::error!("[delete_file] Path does not exist: {}", path);
+        return Err(VividError::not_found(&path));
+    }
+
+    let result = if permanent {
+        if path_buf.is_dir() {
+            fs::remove_dir_all(&path_buf)
+        } else {
+            fs::remove_file(&path_buf)
+        }
+        .map_err(|e| {
+            let error_msg = format_error_with_context("delete_file", &path, &e);
+            log::error!("[delete_file] Delete failed: {}", error_msg);
+            VividError::io("Failed to delete file", &path_buf, &e)
+        })
+    } else {
+        trash::delete(&path_buf).map_err(|e| {
+            log::error!("[delete_file] Move to trash failed: {}", e);
+            VividError::from(format!("Failed to move file to trash: {}", e))
+        })
+    };
+    result?;
+
+    log::info!(
+        "[delete_file] ✓ Success: {} ({}) in {:?}",
+        path,
+        if permanent { "deleted" } else { "moved to trash" },
+        start.elapsed()
+    );
+    Ok(())
+}
+
+// 在原目录内重命名文件或目录，返回新路径
+#[tauri::command]
+fn rename_file(app: AppHandle, params: RenameFileParams) -> Result<String, VividError> {
+    let path_buf = PathBuf::from(&params.path);
+    let new_name = params.new_name.trim();
+    access::ensure_access(&app, &path_buf, access::AccessKind::Write)?;
+
+    log::info!("[rename_file] Starting rename operation");
+    log::debug!("[rename_file] {} -> {}", params.path, new_name);
+
+    if new_name.is_empty() || new_name == "." || new_name == ".." || new_name.contains(['/', '\\']) {
+        log::error!("[rename_file] Invalid file name: {:?}", new_name);
+        return Err(VividError::invalid_input(format!(
+            "Invalid file name: {}",
+            new_name
+        )));
+    }
+    if !path_buf.exists() {
+        log::error!("[rename_file] Path does not exist: {}", params.path);
+        return Err(VividError::not_found(&params.path));
+    }
+
+    let target = path_buf.with_file_name(new_name);
+    // 大小写不敏感的文件系统上，仅修改大小写时目标路径“已存在”
+    if target.exists() && !paths::same_file(&target, &path_buf) {
+        log::error!("[rename_file] Target already exists: {:?}", target);
+        return Err(VividError::conflict(
+            &target,
+            format!("A file named {} already exists", new_name),
+        ));
+    }
+
+    fs::rename(&path_buf, &target).map_err(|e| {
+        let error_msg = format_error_with_context("rename_file", &params.path, &e);
+        log::error!("[rename_file] Rename failed: {}", error_msg);
+        VividError::io("Failed to rename file", &path_buf, &e)
+    })?;
+
+    let new_path = target.to_string_lossy().to_string();
+    log::info!("[rename_file] ✓ Success: {} -> {}", params.path, new_path);
+    Ok(new_path)
+}
+
+/// 生成副本路径：`name copy.md`、`name copy 2.md` ...
+fn duplicate_path(path: &std::path::Path) -> PathBuf {
+    let stem = path
+        .file_stem()
+        .and_then(|s| s.to_str())
+        .unwrap_or("Untitled");
+    let ext = path
+        .extension()
+        .and_then(|e| e.to_str())
+        .map(|e| format!(".{}", e))
+        .unwrap_or_default();
+
+    let mut index = 1;
+    loop {
+        let name = if index == 1 {
+            format!("{} copy{}", stem, ext)
+        } else {
+            format!("{} copy {}{}", stem, index, ext)
+        };
+        let candidate = path.with_file_name(name);
+        if !candidate.exists() {
+            return candidate;
+        }
+        index += 1;
+    }
+}
+
+// 在同一目录下创建文件副本，返回副本路径
+#[tauri::command]
+fn duplicate_file(app: AppHandle, path: String) -> Result<String, VividError> {
+    let path_buf = PathBuf::from(&path);
+    access::ensure_access(&app, &path_buf, access::AccessKind::Read)?;
+
+    log::info!("[duplicate_file] Starting duplicate operation");
+    log::debug!("[duplicate_file] Source path: {}", path);
+
+    if !path_buf.is_file() {
+        log::error!("[duplicate_file] Not a file: {}", path);
+        return Err(VividError::not_found(&path));
+    }
+
+    let target = duplicate_path(&path_buf);
+    access::ensure_access(&app, &target, access::AccessKind::Write)?;
+    fs::copy(&path_buf, &target).map_err(|e| {
+        let error_msg = format_error_with_context("duplicate_file", &path, &e);
+        log::error!("[duplicate_file] Copy failed: {}", error_msg);
+        VividError::io("Failed to duplicate file", &path_buf, &e)
+    })?;
+
+    let new_path = target.to_string_lossy().to_string();
+    log::info!("[duplicate_file] ✓ Success: {} -> {}", path, new_path);
+    Ok(new_path)
+}
+
+// 导出 PDF
 #[tauri::command]
 async fn export_pdf(
-    _window: tauri::Window,
+    window: tauri::Window,
     params: ExportPdfParams,
-) -> Result<ExportPdfResult, String> {
+) -> Result<ExportPdfResult, VividError> {
     let start = Instant::now();
     log::info!("[export_pdf] Starting PDF export operation");
     log::debug!("[export_pdf] Title: {:?}", params.title);
-    log::debug!("[export_pdf] HTML content size: {} bytes", params.html_content.len());
+
+    if let Some(path) = params.path {
+        let app = window.app_handle().clone();
+        access::ensure_access(&app, Path::new(&path), access::AccessKind::Write)?;
+        let content = params.content.unwrap_or_default();
+        return export_pdf_native(app, path, content, params.title, params.options).await;
+    }
+
+    let html_content = params.html_content.unwrap_or_default();
+    log::debug!("[export_pdf] HTML content size: {} bytes", html_content.len());
+    let theme = window
+        .state::<export::theme::ThemeStore>()
+        .get(params.theme.as_deref());
 
     // 创建临时 HTML 文件
     let temp_dir = std::env::temp_dir();
@@ -334,101 +941,16 @@ async fn export_pdf(
     <meta charset="UTF-8">
     <title>{}</title>
     <style>
-        body {{
-            font-family: -apple-system, BlinkMacSystemFont, "Segoe UI", Roboto, "Helvetica Neue", Arial, sans-serif;
-            line-height: 1.6;
-            color: #333;
-            max-width: 900px;
-            margin: 0 auto;
-            padding: 40px;
-        }}
-        h1, h2, h3, h4, h5, h6 {{
-            margin-top: 24px;
-            margin-bottom: 16px;
-            font-weight: 600;
-            line-height: 1.25;
-        }}
-        h1 {{ font-size: 2em; border-bottom: 1px solid #eaecef; padding-bottom: 0.3em; }}
-        h2 {{ font-size: 1.5em; border-bottom: 1px solid #eaecef; padding-bottom: 0.3em; }}
-        h3 {{ font-size: 1.25em; }}
-        p {{ margin-bottom: 16px; }}
-        code {{
-            background-color: #f6f8fa;
-            padding: 0.2em 0.4em;
-            border-radius: 3px;
-            font-family: "SFMono-Regular", Consolas, "Liberation Mono", Menlo, Courier, monospace;
-            font-size: 85%;
-        }}
-        pre {{
-            background-color: #f6f8fa;
-            padding: 16px;
-            border-radius: 6px;
-            overflow: auto;
-            font-size: 85%;
-            line-height: 1.45;
-        }}
-        pre code {{
-            background-color: transparent;
-            padding: 0;
-        }}
-        blockquote {{
-            margin: 0;
-            padding: 0 1em;
-            color: #6a737d;
-            border-left: 0.25em solid #dfe2e5;
-        }}
-        ul, ol {{
-            margin-bottom: 16px;
-            padding-left: 2em;
-        }}
-        li + li {{
-            margin-top: 0.25em;
-        }}
-        table {{
-            border-collapse: collapse;
-            width: 100%;
-            margin-bottom: 16px;
-        }}
-        th, td {{
-            padding: 6px 13px;
-            border: 1px solid #dfe2e5;
-        }}
-        th {{
-            background-color: #f6f8fa;
-            font-weight: 600;
-        }}
-        tr:nth-child(2n) {{
-            background-color: #f6f8fa;
-        }}
-        img {{
-            max-width: 100%;
-            height: auto;
-        }}
-        .task-list-item {{
-            list-style-type: none;
-        }}
-        .admonition {{
-            margin: 16px 0;
-            padding: 12px 16px;
-            border-left: 4px solid;
-            border-radius: 4px;
-        }}
-        .admonition.tip {{ border-color: #28a745; background-color: #f8fff8; }}
-        .admonition.warning {{ border-color: #ffc107; background-color: #fffbf0; }}
-        .admonition.info {{ border-color: #17a2b8; background-color: #f0f9fb; }}
-        .admonition.note {{ border-color: #6c757d; background-color: #f8f9fa; }}
-        .admonition.danger {{ border-color: #dc3545; background-color: #fff5f5; }}
-        .admonition-title {{
-            font-weight: 600;
-            margin-bottom: 8px;
-        }}
+{}
     </style>
 </head>
 <body>
     {}
 </body>
 </html>"#,
-        title, params.html_content
+        title,
+        theme.css(),
+        html_content
     );
 
     // 写入临时文件
@@ -478,9 +1000,82 @@ async fn export_pdf(
     }
 }
 
+/// 在后端将 Markdown 渲染为 PDF（不依赖 WebView 打印）
+pub(crate) async fn export_pdf_native(
+    app: AppHandle,
+    path: String,
+    content: String,
+    title: Option<String>,
+    options: Option<PdfExportOptions>,
+) -> Result<ExportPdfResult, VividError> {
+    let start = Instant::now();
+    let output = PathBuf::from(&path);
+    let mut options =
+        options.unwrap_or_else(|| app.state::<settings::SettingsStore>().get().export.pdf);
+    if options.palette.is_none() {
+        let theme = app.state::<export::theme::ThemeStore>().get(options.theme.as_deref());
+        options.palette = Some(theme.palette().clone());
+    }
+    let title = title.unwrap_or_else(|| {
+        output
+            .file_stem()
+            .and_then(|n| n.to_str())
+            .unwrap_or("VividMark Export")
+            .to_string()
+    });
+
+    log::debug!("[export_pdf] Native export target: {}", path);
+    log::debug!("[export_pdf] Markdown size: {} bytes, options: {:?}", content.len(), options);
+
+    if let Some(parent) = output.parent() {
+        if !parent.as_os_str().is_empty() && !parent.exists() {
+            if let Err(e) = fs::create_dir_all(parent) {
+                log::error!("[export_pdf] Failed to create output directory: {}", e);
+                return Ok(ExportPdfResult {
+                    success: false,
+                    error: Some(format!("Failed to create directory: {}", e)),
+                });
+            }
+        }
+    }
+
+    let render_path = output.clone();
+    let result = tauri::async_runtime::spawn_blocking(move || {
+        let content = bibliography::resolve_citations(&app, content);
+        export::pdf::export_markdown_to_pdf(&content, &render_path, &title, &options)
+    })
+    .await
+    .map_err(|e| format!("PDF export task failed: {}", e))?;
+
+    match result {
+        Ok(pages) => {
+            log::info!(
+                "[export_pdf] ✓ Success: {} ({} pages) in {:?}",
+                path,
+                pages,
+                start.elapsed()
+            );
+            Ok(ExportPdfResult {
+                success: true,
+                error: None,
+            })
+        }
+        Err(e) => {
+            log::error!("[export_pdf] Native export failed: {}", e);
+            Ok(ExportPdfResult {
+                success: false,
+                error: Some(e),
+            })
+        }
+    }
+}
+
 /// 使用 WebView 原生打印功能导出 PDF（应用内打印对话框）
 #[tauri::command]
-async fn print_pdf(window: WebviewWindow, file_name: String) -> Result<ExportPdfResult, String> {
+async fn print_pdf(
+    window: WebviewWindow,
+    file_name: String,
+) -> Result<ExportPdfResult, VividError> {
     log::info!("[print_pdf] Opening native print dialog for: {}", file_name);
     
     // 注入 CSS 进行打印准备
@@ -739,12 +1334,31 @@ fn log_system_info() {
     log::info!("[System] ============================================");
 }
 
+/// 控制台程序 `vividmark-cli` 的入口，返回退出码（见 `cli`）
+pub fn run_cli() -> i32 {
+    let args: Vec<String> = std::env::args().collect();
+    cli::run_console(&args)
+}
+
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
+    // 带子命令启动时在终端执行后退出，不创建窗口
+    let args: Vec<String> = std::env::args().collect();
+    if let Some(code) = cli::run(&args) {
+        std::process::exit(code);
+    }
+
     tauri::Builder::default()
+        // 单实例插件需要最先注册，第二个实例在加载其他插件前就会退出
+        .plugin(tauri_plugin_single_instance::init(instance::on_second_instance))
+        .plugin(tauri_plugin_deep_link::init())
         .plugin(tauri_plugin_dialog::init())
         .plugin(tauri_plugin_fs::init())
         .plugin(tauri_plugin_shell::init())
+        .manage(instance::LaunchFiles::default())
+        .manage(windows::WindowManager::default())
+        .manage(export::workspace::WorkspaceExport::default())
+        .manage(jobs::JobQueue::default())
         .setup(|app| {
             // Configure logging for both debug and release builds
             let log_builder = tauri_plugin_log::Builder::default()
@@ -778,10 +1392,226 @@ pub fn run() {
                 }
             }
 
+            // 最近文件与会话状态保存在应用数据目录
+            let data_dir = app.path().app_data_dir()?;
+            log::info!("[System] Data directory: {:?}", data_dir);
+            app.manage(spellcheck::SpellChecker::load(data_dir.clone()));
+            app.manage(templates::TemplateStore::new(data_dir.join("templates")));
+            app.manage(export::theme::ThemeStore::new(data_dir.join("themes")));
+            app.manage(backup::BackupStore::new(data_dir.clone()));
+            app.manage(snapshots::SnapshotStore::new(data_dir.join("snapshots")));
+            app.manage(recovery::RecoveryStore::new(data_dir.join("recovery")));
+            app.manage(plugins::PluginHost::new(data_dir.join("plugins")));
+            app.manage(integrity::IntegrityStore::new(data_dir.join("integrity")));
+            let bibliography_config = data_dir.join("bibliography.json");
+            app.manage(bibliography::BibliographyStore::new(bibliography_config));
+            app.manage(search::SearchIndex::new(data_dir.join("search")));
+            let metadata_dir = data_dir.join("metadata");
+            app.manage(metadata::MetadataCache::new(app.handle().clone(), metadata_dir));
+            app.manage(sync::SyncStore::load(data_dir.join("sync")));
+            app.manage(writing_sessions::WritingSessions::new(data_dir.clone()));
+            app.manage(docstate::DocStateStore::load(data_dir.clone()));
+            app.manage(snippets::SnippetStore::load(data_dir.clone()));
+            app.manage(session::SessionStore::new(data_dir));
+            app.manage(settings::SettingsStore::load(app.path().app_config_dir()?));
+            app.manage(workspace::Workspace::default());
+            app.manage(access::AccessControl::default());
+            app.manage(dropped::DroppedPaths::default());
+            app.manage(tags::TagIndex::default());
+            app.manage(links::LinkIndex::default());
+            app.manage(quickopen::QuickOpenCache::default());
+            app.manage(encryption::EncryptionKeys::default());
+            app.manage(largefile::LargeFileStore::default());
+
+            // 通过文件关联启动时，命令行参数中带有要打开的文件
+            let args: Vec<String> = std::env::args().collect();
+            let cwd = std::env::current_dir().unwrap_or_default();
+            for path in instance::file_args(&args, &cwd) {
+                instance::open_file(app.handle(), path);
+            }
+            deeplink::init(app.handle());
+
             log::info!("[VividMark] Application started successfully");
             Ok(())
         })
-        .invoke_handler(tauri::generate_handler![read_file, save_file, file_exists, read_directory, export_pdf, print_pdf])
-        .run(tauri::generate_context!())
-        .expect("error while running tauri application");
+        .on_window_event(|window, event| match event {
+            tauri::WindowEvent::Destroyed => {
+                window
+                    .state::<windows::WindowManager>()
+                    .window_closed(window.app_handle(), window.label());
+            }
+            // 记下拖入的路径，`handle_dropped_paths` 只处理这些路径
+            tauri::WindowEvent::DragDrop(tauri::DragDropEvent::Drop { paths, .. }) => {
+                window.state::<dropped::DroppedPaths>().record(paths);
+            }
+            _ => {}
+        })
+        .invoke_handler(tauri::generate_handler![
+            read_file,
+            save_file,
+            file_exists,
+            read_directory,
+            delete_file,
+            rename_file,
+            rename::rename_with_link_update,
+            duplicate_file,
+            export_pdf,
+            print_pdf,
+            export::html::export_html,
+            export::docx::export_docx,
+            assets::save_asset,
+            assets::localize_remote_images,
+            session::add_recent_file,
+            session::get_recent_files,
+            session::pin_file,
+            session::remove_recent_file,
+            session::save_session,
+            session::restore_session,
+            settings::get_settings,
+            settings::update_settings,
+            merge::merge_contents,
+            git::git_status,
+            git::git_diff,
+            git::git_commit,
+            git::git_log,
+            git::git_checkout_version,
+            parse::parse_markdown,
+            render::render_html,
+            frontmatter::read_front_matter,
+            frontmatter::update_front_matter,
+            workspace::open_workspace,
+            workspace::close_workspace,
+            tags::get_all_tags,
+            tags::get_files_by_tag,
+            tags::rename_tag,
+            links::resolve_link,
+            links::get_backlinks,
+            graph::get_link_graph,
+            graph::export_link_graph,
+            linkcheck::check_links,
+            largefile::open_large_file,
+            largefile::read_chunk,
+            largefile::close_large_file,
+            patch::apply_patch,
+            stats::get_document_stats,
+            spellcheck::check_text,
+            spellcheck::add_to_dictionary,
+            diagram::render_diagram,
+            math::render_math,
+            templates::list_templates,
+            templates::create_from_template,
+            daily::open_daily_note,
+            backup::list_backups,
+            backup::restore_backup,
+            replace::replace_in_workspace,
+            import::import_document,
+            clipboard::convert_html_to_markdown,
+            clipboard::read_clipboard_markdown,
+            sections::move_section,
+            sections::promote_heading,
+            sections::demote_heading,
+            sections::extract_section_to_file,
+            tables::format_tables,
+            tables::table_edit,
+            lint::lint_markdown,
+            lint::fix_markdown,
+            search::query_index,
+            quickopen::quick_open,
+            encryption::encrypt_file,
+            encryption::decrypt_file,
+            encryption::lock_encrypted_file,
+            secrets::store_secret,
+            secrets::get_secret,
+            secrets::delete_secret,
+            sync::configure_sync,
+            sync::get_sync_config,
+            sync::sync_workspace,
+            export::publish::publish,
+            instance::frontend_ready,
+            windows::open_in_new_window,
+            windows::register_document,
+            windows::unregister_document,
+            windows::window_documents,
+            export::print::print_document,
+            readonly::set_read_only,
+            metadata::get_file_summaries,
+            assets::list_assets,
+            assets::find_orphaned_assets,
+            assets::move_asset,
+            export::workspace::export_workspace,
+            export::workspace::cancel_workspace_export,
+            export::pandoc::get_pandoc_status,
+            export::pandoc::convert_via_pandoc,
+            export::epub::export_epub,
+            export::slides::export_slides,
+            export::theme::list_export_themes,
+            export::theme::preview_theme,
+            writing_sessions::get_writing_stats,
+            snapshots::create_snapshot,
+            snapshots::list_snapshots,
+            snapshots::read_snapshot,
+            snapshots::restore_snapshot,
+            snapshots::delete_snapshot,
+            snapshots::collect_snapshot_garbage,
+            integrity::hash_file,
+            integrity::verify_workspace_integrity,
+            access::request_access,
+            dropped::handle_dropped_paths,
+            extract::extract_text,
+            bibliography::load_bibliography,
+            bibliography::get_bibliography,
+            bibliography::search_citations,
+            bibliography::list_citation_styles,
+            references::renumber_footnotes,
+            references::convert_inline_to_reference_links,
+            references::collect_undefined_references,
+            tasks::collect_tasks,
+            tasks::toggle_task,
+            calendar::query_by_date,
+            jobs::start_job,
+            jobs::cancel_job,
+            jobs::list_jobs,
+            recovery::stash_unsaved,
+            recovery::list_recoverable_documents,
+            recovery::recover,
+            recovery::discard_unsaved,
+            plugins::list_plugins,
+            plugins::enable_plugin,
+            plugins::invoke_plugin,
+            tools::run_tool,
+            ocr::ocr_image,
+            attachments::save_attachment,
+            attachments::get_attachment_metadata,
+            attachments::transcribe_attachment,
+            workspace_config::get_effective_config,
+            fileinfo::get_file_info,
+            duplicates::find_duplicates,
+            i18n::get_locale,
+            perf::get_perf_metrics,
+            perf::trace_operation,
+            archive::backup_workspace_to_zip,
+            archive::restore_workspace_from_zip,
+            docstate::save_doc_state,
+            docstate::get_doc_state,
+            sourcemap::compute_source_map,
+            snippets::list_snippets,
+            snippets::save_snippet,
+            snippets::delete_snippet,
+            snippets::expand_snippet,
+            snippets::export_snippets,
+            snippets::import_snippets
+        ])
+        .build(tauri::generate_context!())
+        .expect("error while building tauri application")
+        .run(|_app, _event| {
+            // macOS 双击或拖到程序坞图标打开的文件通过 Apple Event 传入，而不是命令行参数
+            #[cfg(target_os = "macos")]
+            if let tauri::RunEvent::Opened { urls } = _event {
+                for url in urls {
+                    if let Ok(path) = url.to_file_path() {
+                        instance::open_file(_app, path);
+                    }
+                }
+            }
+        });
 }
@@ -0,0 +1,439 @@
+//! 失效链接检查
+//!
+//! 检查文档中的相对文件链接、图片路径、标题锚点和 Wiki 链接，可选地对 http(s)
+//! 链接发送 HEAD 请求。结果带有行列位置，前端据此给失效链接加下划线。
+
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use futures_util::StreamExt;
+use pulldown_cmark::{Event, Tag};
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager};
+
+use crate::access::{self, AccessKind};
+use crate::error::VividError;
+use crate::frontmatter;
+use crate::links::{self, FileLinks, LinkIndex, LinkKind, Resolver};
+use crate::markdown;
+use crate::parse::{self, utf16_offset};
+use crate::render;
+use crate::workspace::{self, Workspace};
+
+/// HTTP 检查的默认超时
+const DEFAULT_HTTP_TIMEOUT_SECS: u64 = 10;
+/// HTTP 检查的默认并发数
+const DEFAULT_HTTP_CONCURRENCY: usize = 8;
+
+/// 检查参数：`path` 与 `root` 二选一
+#[derive(Debug, Deserialize)]
+pub struct CheckLinksParams {
+    /// 检查单个文件
+    pub path: Option<String>,
+    /// 检查目录下的所有 Markdown 文件
+    pub root: Option<String>,
+    /// 单个文件尚未保存的内容
+    pub content: Option<String>,
+    /// 是否检查 http(s) 链接，默认否
+    pub check_http: Option<bool>,
+    pub timeout_secs: Option<u64>,
+    pub concurrency: Option<usize>,
+}
+
+/// 问题类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DiagnosticKind {
+    MissingFile,
+    MissingImage,
+    MissingAnchor,
+    UnresolvedWikiLink,
+    HttpError,
+}
+
+/// 一条失效链接
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LinkDiagnostic {
+    pub path: String,
+    pub url: String,
+    pub kind: DiagnosticKind,
+    pub message: String,
+    /// 所在行（从 0 开始）
+    pub line_index: usize,
+    /// 行内列号（UTF-16，从 0 开始）
+    pub column: usize,
+    /// 链接源码的 UTF-16 区间
+    pub char_index: usize,
+    pub char_end: usize,
+    /// HTTP 状态码
+    pub status: Option<u16>,
+}
+
+/// 检查结果
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CheckLinksResult {
+    pub checked_files: usize,
+    pub checked_links: usize,
+    pub diagnostics: Vec<LinkDiagnostic>,
+}
+
+/// 待检查的链接
+struct Candidate {
+    url: String,
+    image: bool,
+    range: Range<usize>,
+}
+
+/// 收集文档中的普通链接与图片（Wiki 链接由链接索引提取）
+fn collect_links(content: &str) -> Vec<Candidate> {
+    let body_start = frontmatter::find_front_matter(content)
+        .map(|block| block.body_start)
+        .unwrap_or(0);
+    markdown::parser(&content[body_start..])
+        .into_offset_iter()
+        .filter_map(|(event, range)| {
+            let (url, image) = match event {
+                Event::Start(Tag::Link { dest_url, .. }) => (dest_url, false),
+                Event::Start(Tag::Image { dest_url, .. }) => (dest_url, true),
+                _ => return None,
+            };
+            Some(Candidate {
+                url: url.to_string(),
+                image,
+                range: body_start + range.start..body_start + range.end,
+            })
+        })
+        .collect()
+}
+
+fn is_http(url: &str) -> bool {
+    url.starts_with("http://") || url.starts_with("https://")
+}
+
+/// 文档中标题锚点的缓存
+#[derive(Default)]
+struct AnchorCache {
+    files: HashMap<PathBuf, Option<HashSet<String>>>,
+}
+
+impl AnchorCache {
+    fn anchors_of(content: &str) -> HashSet<String> {
+        parse::outline(content)
+            .into_iter()
+            .flat_map(|h| [h.slug, markdown::slugify(&h.text)])
+            .collect()
+    }
+
+    /// 文件中的锚点，无法读取时返回 `None`
+    fn get(&mut self, path: &Path) -> Option<&HashSet<String>> {
+        self.files
+            .entry(path.to_path_buf())
+            .or_insert_with(|| fs::read_to_string(path).ok().map(|c| Self::anchors_of(&c)))
+            .as_ref()
+    }
+}
+
+/// 锚点是否存在：与 slug 比较，Wiki 链接中的标题文本先转换为 slug
+fn has_anchor(anchors: &HashSet<String>, anchor: &str) -> bool {
+    // `^id` 为块引用，不做检查
+    anchor.starts_with('^')
+        || anchors.contains(anchor)
+        || anchors.contains(&anchor.to_lowercase())
+        || anchors.contains(&markdown::slugify(anchor))
+}
+
+/// 检查单个文件中的本地链接，返回问题列表、检查的链接数以及其中的 http 链接
+fn check_file(
+    path: &Path,
+    content: &str,
+    resolver: &Resolver<'_>,
+    anchors: &mut AnchorCache,
+) -> (Vec<LinkDiagnostic>, usize, Vec<(String, LinkDiagnostic)>) {
+    let base_dir = path.parent();
+    let own_anchors = AnchorCache::anchors_of(content);
+    let diagnostic = |url: &str, kind, message: String, range: &Range<usize>| {
+        let line_start = content[..range.start].rfind('\n').map_or(0, |i| i + 1);
+        let char_index = utf16_offset(content, range.start);
+        LinkDiagnostic {
+            path: path.to_string_lossy().to_string(),
+            url: url.to_string(),
+            kind,
+            message,
+            line_index: content[..range.start].matches('\n').count(),
+            column: char_index - utf16_offset(content, line_start),
+            char_index,
+            char_end: utf16_offset(content, range.end),
+            status: None,
+        }
+    };
+
+    let mut diagnostics = Vec::new();
+    let mut http = Vec::new();
+    let candidates = collect_links(content);
+    let mut checked = candidates.len();
+
+    for link in &candidates {
+        if link.url.is_empty() {
+            continue;
+        }
+        if is_http(&link.url) {
+            let pending = diagnostic(
+                &link.url,
+                DiagnosticKind::HttpError,
+                String::new(),
+                &link.range,
+            );
+            http.push((link.url.clone(), pending));
+            continue;
+        }
+        if let Some(anchor) = link.url.strip_prefix('#') {
+            let anchor = render::percent_decode(anchor);
+            if !has_anchor(&own_anchors, &anchor) {
+                diagnostics.push(diagnostic(
+                    &link.url,
+                    DiagnosticKind::MissingAnchor,
+                    format!("Heading not found: #{}", anchor),
+                    &link.range,
+                ));
+            }
+            continue;
+        }
+        if links::is_external(&link.url) {
+            continue;
+        }
+
+        let target = links::normalize_path(&render::resolve_local_path(&link.url, base_dir));
+        if !target.exists() {
+            let (kind, what) = if link.image {
+                (DiagnosticKind::MissingImage, "Image")
+            } else {
+                (DiagnosticKind::MissingFile, "File")
+            };
+            diagnostics.push(diagnostic(
+                &link.url,
+                kind,
+                format!("{} not found: {}", what, target.display()),
+                &link.range,
+            ));
+            continue;
+        }
+        // 目标文件无法读取时不报锚点错误
+        let anchor = link
+            .url
+            .split_once('#')
+            .map(|(_, a)| render::percent_decode(a));
+        if let Some(anchor) = anchor.filter(|a| !a.is_empty()) {
+            if !workspace::is_markdown(&target) {
+                continue;
+            }
+            let found = anchors
+                .get(&target)
+                .map_or(true, |set| has_anchor(set, &anchor));
+            if !found {
+                diagnostics.push(diagnostic(
+                    &link.url,
+                    DiagnosticKind::MissingAnchor,
+                    format!("Heading not found in {}: #{}", target.display(), anchor),
+                    &link.range,
+                ));
+            }
+        }
+    }
+
+    for link in links::extract_links(content)
+        .into_iter()
+        .filter(|l| l.kind != LinkKind::Markdown)
+    {
+        checked += 1;
+        let range = link.start..link.end;
+        let url = &content[range.clone()];
+        let Some(target) = resolver.resolve_exact(path, &link) else {
+            diagnostics.push(diagnostic(
+                url,
+                DiagnosticKind::UnresolvedWikiLink,
+                format!("No note matches \"{}\"", link.target),
+                &range,
+            ));
+            continue;
+        };
+        let Some(heading) = &link.heading else {
+            continue;
+        };
+        let found = if target == path {
+            has_anchor(&own_anchors, heading)
+        } else {
+            anchors
+                .get(&target)
+                .map_or(true, |set| has_anchor(set, heading))
+        };
+        if !found {
+            diagnostics.push(diagnostic(
+                url,
+                DiagnosticKind::MissingAnchor,
+                format!("Heading not found: #{}", heading),
+                &range,
+            ));
+        }
+    }
+
+    (diagnostics, checked, http)
+}
+
+/// HTTP 检查失败：状态码（请求未完成时为空）与错误信息
+type HttpFailure = (Option<u16>, String);
+
+/// 检查一个 http(s) 链接：先发 HEAD，服务器不支持时改用 GET
+async fn check_http_link(client: &reqwest::Client, url: &str) -> Result<(), HttpFailure> {
+    let head = client.head(url).send().await;
+    let response = match head {
+        Ok(r) if !matches!(r.status().as_u16(), 403 | 405 | 501) => r,
+        _ => client
+            .get(url)
+            .send()
+            .await
+            .map_err(|e| (None, format!("Request failed: {}", e)))?,
+    };
+    let status = response.status();
+    if status.is_client_error() || status.is_server_error() {
+        return Err((Some(status.as_u16()), format!("HTTP {}", status)));
+    }
+    Ok(())
+}
+
+/// 本地检查部分（在阻塞线程中执行）
+fn check_local(
+    app: &AppHandle,
+    params: &CheckLinksParams,
+) -> Result<(CheckLinksResult, Vec<(String, LinkDiagnostic)>), String> {
+    let workspace_root = app.state::<Workspace>().root();
+    let (files, root): (Vec<PathBuf>, Option<PathBuf>) = match (&params.path, &params.root) {
+        (Some(path), _) => (vec![PathBuf::from(path)], workspace_root.clone()),
+        (None, Some(root)) => {
+            let root = PathBuf::from(root);
+            if !root.is_dir() {
+                return Err(format!("Directory does not exist: {}", root.display()));
+            }
+            (workspace::markdown_files(&root), Some(root))
+        }
+        (None, None) => return Err("Either path or root is required".to_string()),
+    };
+
+    // 解析 Wiki 链接用的文件列表：优先使用工作区索引
+    let indexed: HashMap<PathBuf, FileLinks> = match &root {
+        Some(root) if workspace_root.as_deref() == Some(root.as_path()) => {
+            app.state::<LinkIndex>().snapshot()?
+        }
+        Some(root) => links::scan_files(&workspace::markdown_files(root)),
+        None => HashMap::new(),
+    };
+    let resolver = Resolver::new(root.as_deref(), &indexed);
+    let mut anchors = AnchorCache::default();
+
+    let mut result = CheckLinksResult {
+        checked_files: 0,
+        checked_links: 0,
+        diagnostics: Vec::new(),
+    };
+    let mut http = Vec::new();
+    for file in &files {
+        let content = match (&params.content, &params.path) {
+            (Some(content), Some(_)) => content.clone(),
+            _ => fs::read_to_string(file).map_err(|e| {
+                log::error!("[check_links] Failed to read {}: {}", file.display(), e);
+                format!("Failed to read file: {}", e)
+            })?,
+        };
+        let (diagnostics, checked, pending) = check_file(file, &content, &resolver, &mut anchors);
+        result.checked_files += 1;
+        result.checked_links += checked;
+        result.diagnostics.extend(diagnostics);
+        http.extend(pending);
+    }
+    Ok((result, http))
+}
+
+// 检查文档或目录中的失效链接
+#[tauri::command]
+pub async fn check_links(
+    app: AppHandle,
+    params: CheckLinksParams,
+) -> Result<CheckLinksResult, VividError> {
+    let start = Instant::now();
+    log::info!(
+        "[check_links] Checking {:?}",
+        params.path.as_ref().or(params.root.as_ref())
+    );
+
+    for path in params.path.iter().chain(&params.root) {
+        access::ensure_access(&app, Path::new(path), AccessKind::Read)?;
+    }
+    let check_http = params.check_http.unwrap_or(false);
+    let timeout = Duration::from_secs(
+        params
+            .timeout_secs
+            .unwrap_or(DEFAULT_HTTP_TIMEOUT_SECS)
+            .max(1),
+    );
+    let concurrency = params
+        .concurrency
+        .unwrap_or(DEFAULT_HTTP_CONCURRENCY)
+        .clamp(1, 32);
+
+    let (mut result, http) =
+        tauri::async_runtime::spawn_blocking(move || check_local(&app, &params))
+            .await
+            .map_err(|e| format!("Link check task failed: {}", e))?
+            .map_err(|e| {
+                log::error!("[check_links] {}", e);
+                e
+            })?;
+
+    if check_http && !http.is_empty() {
+        let client = reqwest::Client::builder()
+            .timeout(timeout)
+            .user_agent(concat!("VividMark/", env!("CARGO_PKG_VERSION")))
+            .build()
+            .map_err(|e| format!("Failed to create HTTP client: {}", e))?;
+
+        let urls: HashSet<String> = http.iter().map(|(url, _)| url.clone()).collect();
+        log::debug!("[check_links] Checking {} HTTP link(s)", urls.len());
+        let checks: Vec<(String, Result<(), HttpFailure>)> = futures_util::stream::iter(urls)
+            .map(|url| {
+                let client = &client;
+                async move {
+                    let result = check_http_link(client, &url).await;
+                    (url, result)
+                }
+            })
+            .buffer_unordered(concurrency)
+            .collect()
+            .await;
+        let failures: HashMap<String, HttpFailure> = checks
+            .into_iter()
+            .filter_map(|(url, result)| result.err().map(|e| (url, e)))
+            .collect();
+
+        for (url, mut diagnostic) in http {
+            if let Some((status, message)) = failures.get(&url) {
+                diagnostic.status = *status;
+                diagnostic.message = message.clone();
+                result.diagnostics.push(diagnostic);
+            }
+        }
+    }
+
+    result
+        .diagnostics
+        .sort_by(|a, b| (&a.path, a.char_index).cmp(&(&b.path, b.char_index)));
+    log::info!(
+        "[check_links] ✓ Success: {} link(s) in {} file(s), {} problem(s) in {:?}",
+        result.checked_links,
+        result.checked_files,
+        result.diagnostics.len(),
+        start.elapsed()
+    );
+    Ok(result)
+}
@@ -0,0 +1,590 @@
+//! 链接索引与 Wiki 链接解析
+//!
+//! 索引工作区中每个文件的出链：`[[Wiki 链接]]`、`![[嵌入]]` 以及指向本地文件的
+//! 普通 Markdown 链接。Wiki 链接按路径、文件名、front matter 别名逐级匹配，
+//! 都找不到时再做模糊匹配。
+
+use std::collections::HashMap;
+use std::fs;
+use std::ops::Range;
+use std::path::{Component, Path, PathBuf};
+use std::sync::Mutex;
+
+use pulldown_cmark::{Event, Tag, TagEnd};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tauri::State;
+
+use crate::error::VividError;
+use crate::frontmatter;
+use crate::markdown;
+use crate::parse::{utf16_offset, LineIndex};
+use crate::render;
+use crate::workspace::{FileIndex, Workspace};
+
+/// 链接类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LinkKind {
+    /// `[[note]]`
+    Wiki,
+    /// `![[note]]`
+    Embed,
+    /// `[text](note.md)` / `![alt](image.png)`
+    Markdown,
+}
+
+/// 文件中的一条出链
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OutLink {
+    pub kind: LinkKind,
+    /// 链接目标（不含 `#标题` 和 `|别名`）
+    pub target: String,
+    /// `#` 之后的标题或块引用
+    pub heading: Option<String>,
+    /// 链接源码的字节区间
+    pub start: usize,
+    pub end: usize,
+    /// 所在行（从 0 开始）
+    pub line_index: usize,
+    /// 起始位置的 UTF-16 偏移
+    pub char_index: usize,
+}
+
+/// 单个文件的链接信息
+#[derive(Debug, Clone, Default)]
+pub struct FileLinks {
+    pub links: Vec<OutLink>,
+    /// front matter 中的 `aliases` / `alias`
+    pub aliases: Vec<String>,
+}
+
+/// 链接的匹配方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MatchKind {
+    /// 相对路径或工作区路径完全匹配
+    Path,
+    /// 文件名匹配
+    Name,
+    /// front matter 别名匹配
+    Alias,
+    /// 忽略大小写、空格和连字符后的近似匹配
+    Fuzzy,
+}
+
+/// 链接解析结果
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ResolvedLink {
+    /// 解析到的文件，找不到时为空
+    pub path: Option<String>,
+    pub heading: Option<String>,
+    pub matched_by: Option<MatchKind>,
+    /// 其它可能的目标（同名文件或模糊匹配的候选）
+    pub candidates: Vec<String>,
+}
+
+/// 反向链接
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Backlink {
+    /// 链接所在的文件
+    pub path: String,
+    pub name: String,
+    pub kind: LinkKind,
+    pub heading: Option<String>,
+    pub line_index: usize,
+    pub char_index: usize,
+    /// 链接所在行的文本
+    pub context: String,
+}
+
+/// 链接是否指向外部（带协议头或纯锚点）
+pub fn is_external(url: &str) -> bool {
+    url.starts_with('#')
+        || url.starts_with("//")
+        || url.split_once(':').is_some_and(|(scheme, _)| {
+            scheme.len() > 1
+                && scheme
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || c == '+')
+        })
+}
+
+/// 按路径组件消去 `.` 和 `..`（不访问文件系统）
+pub fn normalize_path(path: &Path) -> PathBuf {
+    let mut out = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                if !out.pop() {
+                    out.push("..");
+                }
+            }
+            other => out.push(other),
+        }
+    }
+    out
+}
+
+/// 代码、公式和 HTML 所占的区间，这些区域中的 `[[...]]` 不算链接
+pub(crate) fn literal_ranges(body: &str) -> Vec<Range<usize>> {
+    let mut ranges = Vec::new();
+    let mut code_start = None;
+    for (event, range) in markdown::parser(body).into_offset_iter() {
+        match event {
+            Event::Start(Tag::CodeBlock(_)) => code_start = Some(range.start),
+            Event::End(TagEnd::CodeBlock) => {
+                if let Some(start) = code_start.take() {
+                    ranges.push(start..range.end);
+                }
+            }
+            Event::Code(_)
+            | Event::InlineMath(_)
+            | Event::DisplayMath(_)
+            | Event::Html(_)
+            | Event::InlineHtml(_) => ranges.push(range),
+            _ => {}
+        }
+    }
+    ranges
+}
+
+/// 拆分 `target#heading|alias`
+fn split_wiki_target(inner: &str) -> (String, Option<String>) {
+    let target = inner.split('|').next().unwrap_or(inner);
+    match target.split_once('#') {
+        Some((file, heading)) => (
+            file.trim().to_string(),
+            Some(heading.trim().to_string()).filter(|h| !h.is_empty()),
+        ),
+        None => (target.trim().to_string(), None),
+    }
+}
+
+/// 提取文档中的所有出链
+pub fn extract_links(content: &str) -> Vec<OutLink> {
+    let body_start = frontmatter::find_front_matter(content)
+        .map(|block| block.body_start)
+        .unwrap_or(0);
+    let body = &content[body_start..];
+    let lines = LineIndex::new(content);
+    let make = |kind, target: String, heading, range: Range<usize>| OutLink {
+        kind,
+        target,
+        heading,
+        line_index: lines.line_of(range.start),
+        char_index: utf16_offset(content, range.start),
+        start: range.start,
+        end: range.end,
+    };
+
+    let mut links = Vec::new();
+    for (event, range) in markdown::parser(body).into_offset_iter() {
+        let (Event::Start(Tag::Link { dest_url, .. }) | Event::Start(Tag::Image { dest_url, .. })) =
+            event
+        else {
+            continue;
+        };
+        if dest_url.is_empty() || is_external(&dest_url) {
+            continue;
+        }
+        let (file, heading) = match dest_url.split_once('#') {
+            Some((file, heading)) => (file, Some(render::percent_decode(heading))),
+            None => (dest_url.as_ref(), None),
+        };
+        let range = body_start + range.start..body_start + range.end;
+        links.push(make(
+            LinkKind::Markdown,
+            render::percent_decode(file),
+            heading,
+            range,
+        ));
+    }
+
+    let literals = literal_ranges(body);
+    let mut search = 0;
+    while let Some(open) = body[search..].find("[[").map(|i| search + i) {
+        let Some(close) = body[open + 2..].find("]]").map(|i| open + 2 + i) else {
+            break;
+        };
+        let inner = &body[open + 2..close];
+        search = close + 2;
+        if inner.contains('\n') || inner.contains("[[") {
+            search = open + 2;
+            continue;
+        }
+        if literals.iter().any(|r| r.contains(&open)) {
+            continue;
+        }
+        let embed = body[..open].ends_with('!');
+        let start = if embed { open - 1 } else { open };
+        let (target, heading) = split_wiki_target(inner);
+        if target.is_empty() && heading.is_none() {
+            continue;
+        }
+        let kind = if embed {
+            LinkKind::Embed
+        } else {
+            LinkKind::Wiki
+        };
+        links.push(make(
+            kind,
+            target,
+            heading,
+            body_start + start..body_start + close + 2,
+        ));
+    }
+
+    links.sort_by_key(|link| link.start);
+    links
+}
+
+/// front matter 中的别名
+fn aliases(content: &str) -> Vec<String> {
+    let Ok(Some((_, data))) = frontmatter::parse_front_matter(content) else {
+        return Vec::new();
+    };
+    match data.get("aliases").or_else(|| data.get("alias")) {
+        Some(Value::Array(items)) => items
+            .iter()
+            .filter_map(|item| item.as_str().map(str::to_string))
+            .collect(),
+        Some(Value::String(alias)) => alias
+            .split(',')
+            .map(|a| a.trim().to_string())
+            .filter(|a| !a.is_empty())
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+fn file_links(content: &str) -> FileLinks {
+    FileLinks {
+        links: extract_links(content),
+        aliases: aliases(content),
+    }
+}
+
+/// 模糊匹配用的规范形式：小写，去掉空白、`-` 和 `_`
+fn fuzzy_key(text: &str) -> String {
+    text.chars()
+        .filter(|c| !c.is_whitespace() && !matches!(c, '-' | '_'))
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+/// 编辑距离（按字符）
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut prev = row[0];
+        row[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let current = row[j + 1];
+            row[j + 1] = if ca == *cb {
+                prev
+            } else {
+                1 + prev.min(row[j]).min(row[j + 1])
+            };
+            prev = current;
+        }
+    }
+    row[b.len()]
+}
+
+fn file_stem(path: &Path) -> String {
+    path.file_stem()
+        .map(|s| s.to_string_lossy().to_lowercase())
+        .unwrap_or_default()
+}
+
+/// 在工作区文件中解析链接
+pub struct Resolver<'a> {
+    root: Option<&'a Path>,
+    files: &'a HashMap<PathBuf, FileLinks>,
+}
+
+impl<'a> Resolver<'a> {
+    pub fn new(root: Option<&'a Path>, files: &'a HashMap<PathBuf, FileLinks>) -> Self {
+        Resolver { root, files }
+    }
+
+    fn known(&self, path: &Path) -> bool {
+        self.files.contains_key(path) || path.is_file()
+    }
+
+    /// 按路径解析：先相对于当前文件，再相对于工作区根目录；没有扩展名时补 `.md`
+    fn by_path(&self, source: &Path, target: &str) -> Option<PathBuf> {
+        let bases = source.parent().into_iter().chain(self.root);
+        for base in bases {
+            let path = normalize_path(&base.join(target));
+            if path.extension().is_some() && self.known(&path) {
+                return Some(path);
+            }
+            let with_ext =
+                path.with_file_name(format!("{}.md", path.file_name()?.to_string_lossy()));
+            if self.known(&with_ext) {
+                return Some(with_ext);
+            }
+        }
+        None
+    }
+
+    /// 同名候选按与当前文件的距离排序：同目录优先，其次路径更短的
+    fn rank(&self, source: &Path, mut paths: Vec<PathBuf>) -> Vec<PathBuf> {
+        let dir = source.parent();
+        paths.sort_by_key(|path| {
+            (
+                path.parent() != dir,
+                path.components().count(),
+                path.clone(),
+            )
+        });
+        paths
+    }
+
+    /// 解析链接目标
+    pub fn resolve(
+        &self,
+        source: &Path,
+        target: &str,
+    ) -> (Option<PathBuf>, Option<MatchKind>, Vec<PathBuf>) {
+        let target = target.trim();
+        if target.is_empty() {
+            return (
+                Some(source.to_path_buf()),
+                Some(MatchKind::Path),
+                Vec::new(),
+            );
+        }
+        if let Some(path) = self.by_path(source, target) {
+            return (Some(path), Some(MatchKind::Path), Vec::new());
+        }
+
+        // 文件名 / 路径后缀匹配：`note`、`folder/note`、`note.md`
+        let wanted = target.replace('\\', "/").to_lowercase();
+        let wanted = wanted
+            .strip_suffix(".md")
+            .unwrap_or(&wanted)
+            .trim_start_matches("./")
+            .to_string();
+        let by_name: Vec<PathBuf> = self
+            .files
+            .keys()
+            .filter(|path| {
+                let full = path.with_extension("").to_string_lossy().replace('\\', "/");
+                let full = full.to_lowercase();
+                full == wanted || full.ends_with(&format!("/{}", wanted))
+            })
+            .cloned()
+            .collect();
+        if !by_name.is_empty() {
+            let mut ranked = self.rank(source, by_name);
+            let best = ranked.remove(0);
+            return (Some(best), Some(MatchKind::Name), ranked);
+        }
+
+        let by_alias: Vec<PathBuf> = self
+            .files
+            .iter()
+            .filter(|(_, links)| links.aliases.iter().any(|a| a.to_lowercase() == wanted))
+            .map(|(path, _)| path.clone())
+            .collect();
+        if !by_alias.is_empty() {
+            let mut ranked = self.rank(source, by_alias);
+            let best = ranked.remove(0);
+            return (Some(best), Some(MatchKind::Alias), ranked);
+        }
+
+        // 模糊匹配：规范化后相同，或编辑距离不超过长度的四分之一
+        let key = fuzzy_key(wanted.rsplit('/').next().unwrap_or(&wanted));
+        let threshold = (key.chars().count() / 4).max(1);
+        let mut fuzzy: Vec<(usize, PathBuf)> = self
+            .files
+            .iter()
+            .filter_map(|(path, links)| {
+                std::iter::once(file_stem(path))
+                    .chain(links.aliases.iter().cloned())
+                    .map(|name| edit_distance(&key, &fuzzy_key(&name)))
+                    .min()
+                    .filter(|distance| *distance <= threshold)
+                    .map(|distance| (distance, path.clone()))
+            })
+            .collect();
+        fuzzy.sort();
+        let mut candidates: Vec<PathBuf> = fuzzy.into_iter().map(|(_, path)| path).collect();
+        if candidates.is_empty() {
+            return (None, None, Vec::new());
+        }
+        let best = candidates.remove(0);
+        (Some(best), Some(MatchKind::Fuzzy), candidates)
+    }
+
+    /// 解析一条出链，只接受精确匹配（路径 / 文件名 / 别名）
+    pub fn resolve_exact(&self, source: &Path, link: &OutLink) -> Option<PathBuf> {
+        match link.kind {
+            LinkKind::Markdown if link.target.is_empty() => Some(source.to_path_buf()),
+            LinkKind::Markdown => {
+                let base = source.parent()?;
+                let path = normalize_path(&render::resolve_local_path(&link.target, Some(base)));
+                // `[text](note)` 省略扩展名时按 `note.md` 处理
+                let with_ext = path.with_extension("md");
+                if path.extension().is_none() && self.known(&with_ext) {
+                    Some(with_ext)
+                } else {
+                    Some(path)
+                }
+            }
+            LinkKind::Wiki | LinkKind::Embed => match self.resolve(source, &link.target) {
+                (Some(path), Some(kind), _) if kind != MatchKind::Fuzzy => Some(path),
+                _ => None,
+            },
+        }
+    }
+}
+
+/// 读取并提取一批文件的链接，跳过无法读取的文件
+pub fn scan_files(paths: &[PathBuf]) -> HashMap<PathBuf, FileLinks> {
+    paths
+        .iter()
+        .filter_map(|path| {
+            let content = fs::read_to_string(path).ok()?;
+            Some((path.clone(), file_links(&content)))
+        })
+        .collect()
+}
+
+/// 工作区链接索引
+#[derive(Default)]
+pub struct LinkIndex {
+    files: Mutex<HashMap<PathBuf, FileLinks>>,
+}
+
+impl LinkIndex {
+    /// 复制当前索引，避免在解析链接时长时间持有锁
+    pub fn snapshot(&self) -> Result<HashMap<PathBuf, FileLinks>, String> {
+        Ok(self.files.lock().map_err(|e| e.to_string())?.clone())
+    }
+}
+
+impl FileIndex for LinkIndex {
+    fn rebuild(&self, paths: &[PathBuf]) {
+        let files = scan_files(paths);
+        if let Ok(mut guard) = self.files.lock() {
+            *guard = files;
+        }
+    }
+
+    fn refresh(&self, path: &Path) {
+        let links = fs::read_to_string(path).ok().map(|c| file_links(&c));
+        if let Ok(mut files) = self.files.lock() {
+            match links {
+                Some(links) => {
+                    files.insert(path.to_path_buf(), links);
+                }
+                None => {
+                    files.remove(path);
+                }
+            }
+        }
+    }
+
+    fn remove_under(&self, path: &Path) {
+        if let Ok(mut files) = self.files.lock() {
+            files.retain(|file, _| !file.starts_with(path));
+        }
+    }
+
+    fn clear(&self) {
+        if let Ok(mut files) = self.files.lock() {
+            files.clear();
+        }
+    }
+}
+
+fn path_string(path: &Path) -> String {
+    path.to_string_lossy().to_string()
+}
+
+// 解析 Wiki 链接目标，返回对应的文件
+#[tauri::command]
+pub fn resolve_link(
+    workspace: State<'_, Workspace>,
+    index: State<'_, LinkIndex>,
+    source_path: String,
+    target: String,
+) -> Result<ResolvedLink, VividError> {
+    let files = index.snapshot()?;
+    let root = workspace.root();
+    let resolver = Resolver::new(root.as_deref(), &files);
+    let source = normalize_path(Path::new(&source_path));
+
+    let target = target.trim();
+    let target = target
+        .strip_prefix("[[")
+        .and_then(|t| t.strip_suffix("]]"))
+        .unwrap_or(target);
+    let (file, heading) = split_wiki_target(target);
+    let (path, matched_by, candidates) = resolver.resolve(&source, &file);
+
+    log::debug!("[resolve_link] {} -> {:?} ({:?})", target, path, matched_by);
+    Ok(ResolvedLink {
+        path: path.as_deref().map(path_string),
+        heading,
+        matched_by,
+        candidates: candidates.iter().map(|p| path_string(p)).collect(),
+    })
+}
+
+// 获取链接到指定文件的所有位置
+#[tauri::command]
+pub fn get_backlinks(
+    workspace: State<'_, Workspace>,
+    index: State<'_, LinkIndex>,
+    path: String,
+) -> Result<Vec<Backlink>, VividError> {
+    let files = index.snapshot()?;
+    let root = workspace.root();
+    let resolver = Resolver::new(root.as_deref(), &files);
+    let target = normalize_path(Path::new(&path));
+
+    let mut sources: Vec<&PathBuf> = files.keys().filter(|p| **p != target).collect();
+    sources.sort();
+
+    let mut backlinks = Vec::new();
+    for source in sources {
+        let matches: Vec<&OutLink> = files[source]
+            .links
+            .iter()
+            .filter(|link| resolver.resolve_exact(source, link).as_deref() == Some(&*target))
+            .collect();
+        if matches.is_empty() {
+            continue;
+        }
+        // 只在有匹配时读取文件内容，用于返回上下文
+        let content = fs::read_to_string(source).unwrap_or_default();
+        let lines: Vec<&str> = content.lines().collect();
+        for link in matches {
+            backlinks.push(Backlink {
+                path: path_string(source),
+                name: source
+                    .file_name()
+                    .map(|n| n.to_string_lossy().to_string())
+                    .unwrap_or_default(),
+                kind: link.kind,
+                heading: link.heading.clone(),
+                line_index: link.line_index,
+                char_index: link.char_index,
+                context: lines
+                    .get(link.line_index)
+                    .map(|l| l.trim().to_string())
+                    .unwrap_or_default(),
+            });
+        }
+    }
+
+    log::debug!("[get_backlinks] {} -> {} link(s)", path, backlinks.len());
+    Ok(backlinks)
+}
@@ -0,0 +1,450 @@
+//! Markdown 检查与自动修复
+//!
+//! 规则基于 pulldown-cmark 的解析结果和逐行扫描，代码块、HTML 块和 front matter 不参与检查。
+//! 只有不改变渲染结果（或只统一写法）的问题提供自动修复，标题跳级、过长的行需手动处理。
+//! 关闭的规则、行长度上限和列表标记在设置的 `lint` 中配置，工作区配置可以覆盖（见 `workspace_config`）。
+
+use std::ops::Range;
+use std::path::Path;
+use std::sync::OnceLock;
+
+use pulldown_cmark::{Event, Tag, TagEnd};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use tauri::State;
+
+use crate::error::VividError;
+use crate::frontmatter;
+use crate::markdown;
+use crate::parse::{utf16_offset, LineIndex};
+use crate::settings::{LintSettings, SettingsStore};
+use crate::workspace_config;
+
+/// 检查规则
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LintRule {
+    /// 标题级别一次增加超过一级
+    HeadingIncrement,
+    /// 行尾空白（两个空格的硬换行除外）
+    TrailingSpaces,
+    /// 未写成链接的网址
+    BareUrls,
+    /// 无序列表混用 `-` / `*` / `+`
+    ListMarkerStyle,
+    /// 行过长
+    LineLength,
+}
+
+impl LintRule {
+    pub const ALL: [LintRule; 5] = [
+        LintRule::HeadingIncrement,
+        LintRule::TrailingSpaces,
+        LintRule::BareUrls,
+        LintRule::ListMarkerStyle,
+        LintRule::LineLength,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            LintRule::HeadingIncrement => "heading_increment",
+            LintRule::TrailingSpaces => "trailing_spaces",
+            LintRule::BareUrls => "bare_urls",
+            LintRule::ListMarkerStyle => "list_marker_style",
+            LintRule::LineLength => "line_length",
+        }
+    }
+
+    fn from_name(name: &str) -> Option<Self> {
+        LintRule::ALL.into_iter().find(|rule| rule.name() == name)
+    }
+
+    fn severity(self) -> Severity {
+        match self {
+            LintRule::HeadingIncrement | LintRule::BareUrls | LintRule::ListMarkerStyle => {
+                Severity::Warning
+            }
+            LintRule::TrailingSpaces | LintRule::LineLength => Severity::Info,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Error,
+    Warning,
+    Info,
+}
+
+/// 一条检查结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LintDiagnostic {
+    pub rule: LintRule,
+    pub severity: Severity,
+    pub message: String,
+    /// 所在行（从 0 开始）
+    pub line_index: usize,
+    /// 起止位置的 UTF-16 偏移
+    pub char_index: usize,
+    pub char_end: usize,
+    /// 是否可以用 `fix_markdown` 自动修复
+    pub fixable: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct FixMarkdownResult {
+    pub content: String,
+    /// 修复的问题数量
+    pub fixed: usize,
+    /// 修复后仍然存在的问题
+    pub remaining: Vec<LintDiagnostic>,
+}
+
+/// 检查中发现的问题，带字节区间和可选的修复
+struct Finding {
+    rule: LintRule,
+    range: Range<usize>,
+    message: String,
+    fix: Option<String>,
+}
+
+/// 解析一遍文档得到的结构信息
+#[derive(Default)]
+struct Scan {
+    /// 不做逐行检查的区间：代码块、HTML 块、front matter
+    skipped: Vec<Range<usize>>,
+    tables: Vec<Range<usize>>,
+    headings: Vec<(u8, Range<usize>)>,
+    /// 无序列表项标记的字节偏移
+    markers: Vec<usize>,
+    /// 链接和代码之外的连续文本
+    texts: Vec<Range<usize>>,
+}
+
+fn url_regex() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| Regex::new(r"(?i)\b(?:https?://|www\.)[^\s<>]+").expect("valid regex"))
+}
+
+fn scan(content: &str) -> Scan {
+    let mut result = Scan::default();
+    let body_start = frontmatter::find_front_matter(content).map_or(0, |block| block.body_start);
+    if body_start > 0 {
+        result.skipped.push(0..body_start);
+    }
+
+    let mut lists: Vec<bool> = Vec::new();
+    let mut in_link = 0usize;
+    let mut in_code = false;
+    let mut heading: Option<(u8, Range<usize>)> = None;
+    for (event, range) in markdown::parser(content).into_offset_iter() {
+        if range.start < body_start {
+            continue;
+        }
+        match event {
+            Event::Start(Tag::CodeBlock(_)) => {
+                in_code = true;
+                result.skipped.push(range);
+            }
+            Event::End(TagEnd::CodeBlock) => in_code = false,
+            Event::Start(Tag::HtmlBlock) => result.skipped.push(range),
+            Event::Start(Tag::Table(_)) => result.tables.push(range),
+            Event::Start(Tag::Heading { level, .. }) => heading = Some((level as u8, range)),
+            Event::End(TagEnd::Heading(_)) => result.headings.extend(heading.take()),
+            Event::Start(Tag::List(start)) => lists.push(start.is_none()),
+            Event::End(TagEnd::List(_)) => {
+                lists.pop();
+            }
+            Event::Start(Tag::Item) if lists.last() == Some(&true) => {
+                let offset = range.start + content[range.start..].len()
+                    - content[range.start..].trim_start().len();
+                if content[offset..].starts_with(['-', '*', '+']) {
+                    result.markers.push(offset);
+                }
+            }
+            Event::Start(Tag::Link { .. }) | Event::Start(Tag::Image { .. }) => in_link += 1,
+            Event::End(TagEnd::Link) | Event::End(TagEnd::Image) => {
+                in_link = in_link.saturating_sub(1)
+            }
+            Event::Text(_) if in_link == 0 && !in_code => match result.texts.last_mut() {
+                // 解析器会在 `&`、`[` 等字符处拆开文本，相邻的片段合并后再查找网址
+                Some(last) if last.end == range.start => last.end = range.end,
+                _ => result.texts.push(range),
+            },
+            _ => {}
+        }
+    }
+    result
+}
+
+fn check_headings(scan: &Scan, findings: &mut Vec<Finding>) {
+    let mut previous: Option<u8> = None;
+    for (level, range) in &scan.headings {
+        if let Some(prev) = previous {
+            if *level > prev + 1 {
+                findings.push(Finding {
+                    rule: LintRule::HeadingIncrement,
+                    range: range.clone(),
+                    message: format!("Heading level jumps from H{} to H{}", prev, level),
+                    fix: None,
+                });
+            }
+        }
+        previous = Some(*level);
+    }
+}
+
+fn check_bare_urls(content: &str, scan: &Scan, findings: &mut Vec<Finding>) {
+    for text in &scan.texts {
+        for m in url_regex().find_iter(&content[text.clone()]) {
+            // 与 GFM 自动链接一致：去掉末尾的标点和不成对的右括号
+            let mut url = m.as_str();
+            loop {
+                let trimmed = url.trim_end_matches(['.', ',', ':', ';', '!', '?', '"', '\'']);
+                let trimmed = if trimmed.ends_with(')')
+                    && trimmed.matches(')').count() > trimmed.matches('(').count()
+                {
+                    &trimmed[..trimmed.len() - 1]
+                } else {
+                    trimmed
+                };
+                if trimmed.len() == url.len() {
+                    break;
+                }
+                url = trimmed;
+            }
+            let start = text.start + m.start();
+            let fix = if url.starts_with(['w', 'W']) {
+                format!("[{}](https://{})", url, url)
+            } else {
+                format!("<{}>", url)
+            };
+            findings.push(Finding {
+                rule: LintRule::BareUrls,
+                range: start..start + url.len(),
+                message: format!("Bare URL: {}", url),
+                fix: Some(fix),
+            });
+        }
+    }
+}
+
+fn check_list_markers(
+    content: &str,
+    scan: &Scan,
+    settings: &LintSettings,
+    findings: &mut Vec<Finding>,
+) {
+    let Some(expected) = settings.list_marker.or_else(|| {
+        scan.markers
+            .first()
+            .and_then(|&i| content[i..].chars().next())
+    }) else {
+        return;
+    };
+    for &offset in &scan.markers {
+        let marker = content[offset..].chars().next().unwrap_or(expected);
+        if marker != expected {
+            findings.push(Finding {
+                rule: LintRule::ListMarkerStyle,
+                range: offset..offset + 1,
+                message: format!("Expected list marker '{}', found '{}'", expected, marker),
+                fix: Some(expected.to_string()),
+            });
+        }
+    }
+}
+
+fn check_lines(
+    content: &str,
+    scan: &Scan,
+    rules: &[LintRule],
+    settings: &LintSettings,
+    findings: &mut Vec<Finding>,
+) {
+    let in_any =
+        |ranges: &[Range<usize>], offset: usize| ranges.iter().any(|r| r.contains(&offset));
+    let lines: Vec<(usize, &str)> = content
+        .split_inclusive('\n')
+        .scan(0, |offset, line| {
+            let start = *offset;
+            *offset += line.len();
+            Some((start, line.trim_end_matches(['\n', '\r'])))
+        })
+        .collect();
+
+    for (i, &(start, line)) in lines.iter().enumerate() {
+        if in_any(&scan.skipped, start) {
+            continue;
+        }
+
+        if rules.contains(&LintRule::TrailingSpaces) {
+            let trimmed = line.trim_end_matches([' ', '\t']);
+            let trailing = &line[trimmed.len()..];
+            let next_has_text = lines
+                .get(i + 1)
+                .is_some_and(|(_, next)| !next.trim().is_empty());
+            // 非空行末尾恰好两个空格且下一行仍有文字时是硬换行
+            let hard_break = trailing == "  " && !trimmed.is_empty() && next_has_text;
+            if !trailing.is_empty() && !hard_break {
+                findings.push(Finding {
+                    rule: LintRule::TrailingSpaces,
+                    range: start + trimmed.len()..start + line.len(),
+                    message: "Trailing whitespace".to_string(),
+                    fix: Some(String::new()),
+                });
+            }
+        }
+
+        let max = settings.max_line_length;
+        if rules.contains(&LintRule::LineLength) && max > 0 && !in_any(&scan.tables, start) {
+            let length = line.chars().count();
+            let over = line.char_indices().nth(max).map(|(i, _)| i);
+            // 超出部分没有空白（如长网址）时无法折行，不报告
+            if let Some(over) = over.filter(|&i| line[i..].contains(char::is_whitespace)) {
+                findings.push(Finding {
+                    rule: LintRule::LineLength,
+                    range: start + over..start + line.len(),
+                    message: format!("Line is {} characters long (max {})", length, max),
+                    fix: None,
+                });
+            }
+        }
+    }
+}
+
+/// 设置中启用的规则，`requested` 不为空时只使用其中列出的规则
+fn enabled_rules(settings: &LintSettings, requested: Option<&[String]>) -> Vec<LintRule> {
+    match requested {
+        Some(names) => names
+            .iter()
+            .filter_map(|name| {
+                let rule = LintRule::from_name(name);
+                if rule.is_none() {
+                    log::warn!("[lint] Unknown rule: {}", name);
+                }
+                rule
+            })
+            .collect(),
+        None => LintRule::ALL
+            .into_iter()
+            .filter(|rule| !settings.disabled_rules.iter().any(|r| r == rule.name()))
+            .collect(),
+    }
+}
+
+fn lint(content: &str, rules: &[LintRule], settings: &LintSettings) -> Vec<Finding> {
+    let scan = scan(content);
+    let mut findings = Vec::new();
+    if rules.contains(&LintRule::HeadingIncrement) {
+        check_headings(&scan, &mut findings);
+    }
+    if rules.contains(&LintRule::BareUrls) {
+        check_bare_urls(content, &scan, &mut findings);
+    }
+    if rules.contains(&LintRule::ListMarkerStyle) {
+        check_list_markers(content, &scan, settings, &mut findings);
+    }
+    check_lines(content, &scan, rules, settings, &mut findings);
+    findings.sort_by_key(|finding| (finding.range.start, finding.range.end));
+    findings
+}
+
+fn to_diagnostics(content: &str, findings: &[Finding]) -> Vec<LintDiagnostic> {
+    let lines = LineIndex::new(content);
+    findings
+        .iter()
+        .map(|finding| LintDiagnostic {
+            rule: finding.rule,
+            severity: finding.rule.severity(),
+            message: finding.message.clone(),
+            line_index: lines.line_of(finding.range.start),
+            char_index: utf16_offset(content, finding.range.start),
+            char_end: utf16_offset(content, finding.range.end),
+            fixable: finding.fix.is_some(),
+        })
+        .collect()
+}
+
+/// 检查文档
+pub fn lint_content(
+    content: &str,
+    settings: &LintSettings,
+    rules: Option<&[String]>,
+) -> Vec<LintDiagnostic> {
+    let rules = enabled_rules(settings, rules);
+    to_diagnostics(content, &lint(content, &rules, settings))
+}
+
+/// 应用所有可自动修复的问题，返回修复后的内容和修复数量
+pub fn fix_content(
+    content: &str,
+    settings: &LintSettings,
+    rules: Option<&[String]>,
+) -> (String, usize) {
+    let rules = enabled_rules(settings, rules);
+    let mut out = String::with_capacity(content.len());
+    let mut last = 0;
+    let mut fixed = 0;
+    for finding in lint(content, &rules, settings) {
+        let Some(fix) = finding.fix else {
+            continue;
+        };
+        if finding.range.start < last {
+            continue;
+        }
+        out.push_str(&content[last..finding.range.start]);
+        out.push_str(&fix);
+        last = finding.range.end;
+        fixed += 1;
+    }
+    out.push_str(&content[last..]);
+    (out, fixed)
+}
+
+/// 文档的检查设置，文档属于带配置的工作区时使用工作区配置
+fn lint_settings(store: &SettingsStore, path: Option<&str>) -> LintSettings {
+    let settings = store.get();
+    match path.filter(|path| !path.trim().is_empty()) {
+        Some(path) => workspace_config::resolve(&settings, Path::new(path)).lint,
+        None => settings.lint,
+    }
+}
+
+// 检查 Markdown 文档，`ruleset` 为空时使用设置中启用的全部规则
+#[tauri::command]
+pub fn lint_markdown(
+    settings: State<'_, SettingsStore>,
+    content: String,
+    ruleset: Option<Vec<String>>,
+    path: Option<String>,
+) -> Result<Vec<LintDiagnostic>, VividError> {
+    let settings = lint_settings(&settings, path.as_deref());
+    let diagnostics = lint_content(&content, &settings, ruleset.as_deref());
+    log::debug!("[lint_markdown] {} diagnostic(s)", diagnostics.len());
+    Ok(diagnostics)
+}
+
+// 自动修复可安全修复的问题（行尾空白、裸网址、列表标记）
+#[tauri::command]
+pub fn fix_markdown(
+    settings: State<'_, SettingsStore>,
+    content: String,
+    rules: Option<Vec<String>>,
+    path: Option<String>,
+) -> Result<FixMarkdownResult, VividError> {
+    let settings = lint_settings(&settings, path.as_deref());
+    let (fixed_content, fixed) = fix_content(&content, &settings, rules.as_deref());
+    let remaining = lint_content(&fixed_content, &settings, rules.as_deref());
+    log::info!(
+        "[fix_markdown] ✓ Success: {} fix(es), {} remaining",
+        fixed,
+        remaining.len()
+    );
+    Ok(FixMarkdownResult {
+        content: fixed_content,
+        fixed,
+        remaining,
+    })
+}
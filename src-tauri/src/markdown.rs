@@ -0,0 +1,209 @@
+//! Markdown 解析公共工具
+//!
+//! 后端所有需要理解 Markdown 结构的功能（导出、大纲、统计等）都通过这里获取解析器，
+//! 保证各处启用的 GFM 扩展保持一致。
+
+use std::ops::Range;
+
+use pulldown_cmark::{Options, Parser};
+
+/// VividMark 默认启用的 Markdown 扩展（与前端编辑器保持一致的 GFM 子集）
+pub fn parser_options() -> Options {
+    Options::ENABLE_TABLES
+        | Options::ENABLE_FOOTNOTES
+        | Options::ENABLE_STRIKETHROUGH
+        | Options::ENABLE_TASKLISTS
+        | Options::ENABLE_SMART_PUNCTUATION
+        | Options::ENABLE_HEADING_ATTRIBUTES
+        | Options::ENABLE_MATH
+}
+
+/// 使用默认扩展创建解析器
+pub fn parser(content: &str) -> Parser<'_> {
+    Parser::new_ext(content, parser_options())
+}
+
+/// 编辑器预览支持的 Admonition 类型（`::: tip 标题`）
+pub const ADMONITION_TYPES: &[&str] = &[
+    "tip", "warning", "info", "note", "danger", "success", "hint", "important", "caution",
+];
+
+/// 将 `:::` Admonition 容器展开为 HTML 块
+///
+/// pulldown-cmark 不支持 markdown-it-container 语法，这里在解析前把容器替换为与前端相同的
+/// `<div class="admonition ...">` 结构，前后留空行以便容器内部仍按 Markdown 解析。
+pub fn expand_admonitions(content: &str) -> String {
+    expand_admonitions_with_lines(content).0
+}
+
+/// 追加文本，并为其中新开始的每一行记录来源行号
+fn push_mapped(out: &mut String, lines: &mut Vec<usize>, text: &str, origin: usize) {
+    for (i, c) in text.char_indices() {
+        if i == 0 && (out.is_empty() || out.ends_with('\n')) {
+            lines.push(origin);
+        }
+        if c == '\n' && i + 1 < text.len() {
+            lines.push(origin);
+        }
+    }
+    out.push_str(text);
+}
+
+/// 同 `expand_admonitions`，另外返回展开后每一行对应的原文行号（从 0 开始），
+/// 容器标记展开出的几行都对应 `:::` 所在的行
+pub fn expand_admonitions_with_lines(content: &str) -> (String, Vec<usize>) {
+    let mut out = String::with_capacity(content.len());
+    let mut lines = Vec::new();
+    let mut fence: Option<&str> = None;
+    let mut depth = 0usize;
+    let mut last = 0;
+
+    for (origin, line) in content.split_inclusive('\n').enumerate() {
+        let trimmed = line.trim();
+        last = origin;
+
+        if let Some(marker) = fence {
+            if trimmed.starts_with(marker) {
+                fence = None;
+            }
+            push_mapped(&mut out, &mut lines, line, origin);
+            continue;
+        }
+        if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            fence = Some(&trimmed[..3]);
+            push_mapped(&mut out, &mut lines, line, origin);
+            continue;
+        }
+
+        if let Some(info) = trimmed.strip_prefix(":::") {
+            let info = info.trim();
+            if info.is_empty() && depth > 0 {
+                depth -= 1;
+                push_mapped(&mut out, &mut lines, "\n</div></div>\n\n", origin);
+                continue;
+            }
+            let kind = info.split_whitespace().next().unwrap_or("");
+            if let Some(kind) = ADMONITION_TYPES.iter().find(|t| **t == kind) {
+                let title = info[kind.len()..].trim();
+                let title = if title.is_empty() {
+                    let mut chars = kind.chars();
+                    chars
+                        .next()
+                        .map(|c| c.to_uppercase().chain(chars).collect::<String>())
+                        .unwrap_or_default()
+                } else {
+                    title.to_string()
+                };
+                depth += 1;
+                push_mapped(
+                    &mut out,
+                    &mut lines,
+                    &format!(
+                        "\n<div class=\"admonition {}\">\n<div class=\"admonition-title\">{}</div>\n<div class=\"admonition-content\">\n\n",
+                        kind,
+                        escape_html(&title)
+                    ),
+                    origin,
+                );
+                continue;
+            }
+        }
+
+        push_mapped(&mut out, &mut lines, line, origin);
+    }
+
+    for _ in 0..depth {
+        push_mapped(&mut out, &mut lines, "\n</div></div>\n", last);
+    }
+    // 以换行结尾时最后还有一个空行
+    if out.ends_with('\n') || out.is_empty() {
+        lines.push(last + usize::from(content.ends_with('\n')));
+    }
+    (out, lines)
+}
+
+/// 转义 HTML 特殊字符
+pub fn escape_html(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&#39;"),
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+/// 判断字符是否为 CJK（中日韩）字符，包括全角标点
+pub fn is_cjk_char(c: char) -> bool {
+    matches!(
+        c as u32,
+        0x1100..=0x11FF     // 韩文字母
+            | 0x2E80..=0x2FDF   // CJK 部首
+            | 0x3000..=0x303F   // CJK 标点
+            | 0x3040..=0x30FF   // 平假名、片假名
+            | 0x3100..=0x31BF   // 注音
+            | 0x3400..=0x4DBF   // 扩展 A
+            | 0x4E00..=0x9FFF   // 基本汉字
+            | 0xAC00..=0xD7AF   // 韩文音节
+            | 0xF900..=0xFAFF   // 兼容汉字
+            | 0xFF00..=0xFFEF   // 全角字符
+            | 0x20000..=0x2FA1F // 扩展 B-F 及兼容补充
+    )
+}
+
+/// 判断文本中是否包含 CJK 字符
+pub fn contains_cjk(text: &str) -> bool {
+    text.chars().any(is_cjk_char)
+}
+
+/// 生成 GitHub 风格的标题锚点：小写，去掉标点，空白替换为 `-`，保留 CJK 等非 ASCII 字母
+pub fn slugify(text: &str) -> String {
+    let mut slug = String::with_capacity(text.len());
+    for c in text.trim().chars() {
+        if c.is_alphanumeric() || c == '-' || c == '_' {
+            slug.extend(c.to_lowercase());
+        } else if c.is_whitespace() {
+            slug.push('-');
+        }
+    }
+    slug
+}
+
+/// 对源码的一处替换：字节区间和替换文本
+pub type Replacement = (Range<usize>, String);
+
+/// 偏移所在行的起始位置
+pub fn line_start(content: &str, offset: usize) -> usize {
+    content[..offset].rfind('\n').map_or(0, |i| i + 1)
+}
+
+/// 按字节区间替换文本
+///
+/// `edits` 会按起始位置排序，与前一处重叠的替换被丢弃，调用后只剩实际应用的替换。
+pub fn apply_edits(content: &str, edits: &mut Vec<Replacement>) -> String {
+    edits.sort_by_key(|(range, _)| range.start);
+    let mut last = 0;
+    edits.retain(|(range, _)| {
+        let keep = range.start >= last;
+        if keep {
+            last = range.end;
+        }
+        keep
+    });
+
+    let inserted: usize = edits.iter().map(|(_, text)| text.len()).sum();
+    let mut out = String::with_capacity(content.len() + inserted);
+    let mut last = 0;
+    for (range, text) in edits.iter() {
+        out.push_str(&content[last..range.start]);
+        out.push_str(text);
+        last = range.end;
+    }
+    out.push_str(&content[last..]);
+    out
+}
@@ -0,0 +1,542 @@
+//! 公式预渲染
+//!
+//! 将 `$...$` / `$$...$$` 中的 LaTeX 公式在后端转换为 MathML、SVG 或 Unicode 文本，
+//! 导出的 HTML / PDF 在查看时无需加载 KaTeX。
+//! MathML 由 latex2mathml 生成；SVG 调用系统中安装的 MathJax 命令行工具 `tex2svg`，
+//! 结果按公式缓存；Unicode 文本用于不支持 MathML 的导出格式（PDF 行内公式等）。
+
+use std::collections::HashMap;
+use std::iter::Peekable;
+use std::str::Chars;
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::Instant;
+
+use serde::{Deserialize, Serialize};
+
+use crate::diagram;
+use crate::error::VividError;
+
+/// 内存缓存的最大条目数，超出后整体清空
+const MAX_CACHE_ENTRIES: usize = 1024;
+
+/// 输出格式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MathFormat {
+    #[default]
+    Mathml,
+    Svg,
+    /// Unicode 纯文本近似
+    Text,
+}
+
+/// 待渲染的公式
+#[derive(Debug, Clone, Deserialize)]
+pub struct MathExpression {
+    pub tex: String,
+    /// 块级公式（`$$...$$`），默认为行内公式
+    #[serde(default)]
+    pub display: bool,
+}
+
+/// 渲染结果，与请求中的公式一一对应
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RenderedMath {
+    /// 渲染失败时为空
+    pub output: String,
+    pub error: Option<String>,
+}
+
+/// LaTeX → MathML
+pub fn to_mathml(tex: &str, display: bool) -> Result<String, String> {
+    let style = if display {
+        latex2mathml::DisplayStyle::Block
+    } else {
+        latex2mathml::DisplayStyle::Inline
+    };
+    latex2mathml::latex_to_mathml(tex, style).map_err(|e| e.to_string())
+}
+
+type SvgCache = Mutex<HashMap<(bool, String), Arc<str>>>;
+
+fn svg_cache() -> &'static SvgCache {
+    static CACHE: OnceLock<SvgCache> = OnceLock::new();
+    CACHE.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+/// LaTeX → SVG（MathJax 的 `tex2svg`），相同的公式直接返回缓存
+pub fn to_svg(tex: &str, display: bool) -> Result<Arc<str>, String> {
+    let key = (display, tex.to_string());
+    if let Some(svg) = svg_cache().lock().map_err(|e| e.to_string())?.get(&key) {
+        return Ok(svg.clone());
+    }
+
+    let mut args = Vec::new();
+    if !display {
+        args.push("--inline");
+    }
+    args.push(tex);
+    let output = diagram::run_tool("tex2svg", &args, "")?;
+    let svg = String::from_utf8(output).map_err(|_| "tex2svg output is not valid UTF-8")?;
+    let svg = match svg.find("<svg") {
+        Some(start) => svg[start..].trim_end().to_string(),
+        None => return Err("tex2svg did not produce SVG".to_string()),
+    };
+
+    let svg: Arc<str> = svg.into();
+    let mut cache = svg_cache().lock().map_err(|e| e.to_string())?;
+    if cache.len() >= MAX_CACHE_ENTRIES {
+        cache.clear();
+    }
+    cache.insert(key, svg.clone());
+    Ok(svg)
+}
+
+/// 常用命令对应的 Unicode 符号
+fn symbol(name: &str) -> Option<&'static str> {
+    Some(match name {
+        "alpha" => "α",
+        "beta" => "β",
+        "gamma" => "γ",
+        "delta" => "δ",
+        "epsilon" => "ϵ",
+        "varepsilon" => "ε",
+        "zeta" => "ζ",
+        "eta" => "η",
+        "theta" => "θ",
+        "vartheta" => "ϑ",
+        "iota" => "ι",
+        "kappa" => "κ",
+        "lambda" => "λ",
+        "mu" => "μ",
+        "nu" => "ν",
+        "xi" => "ξ",
+        "pi" => "π",
+        "varpi" => "ϖ",
+        "rho" => "ρ",
+        "varrho" => "ϱ",
+        "sigma" => "σ",
+        "varsigma" => "ς",
+        "tau" => "τ",
+        "upsilon" => "υ",
+        "phi" => "ϕ",
+        "varphi" => "φ",
+        "chi" => "χ",
+        "psi" => "ψ",
+        "omega" => "ω",
+        "Gamma" => "Γ",
+        "Delta" => "Δ",
+        "Theta" => "Θ",
+        "Lambda" => "Λ",
+        "Xi" => "Ξ",
+        "Pi" => "Π",
+        "Sigma" => "Σ",
+        "Upsilon" => "Υ",
+        "Phi" => "Φ",
+        "Psi" => "Ψ",
+        "Omega" => "Ω",
+        "times" => "×",
+        "cdot" => "·",
+        "ast" => "∗",
+        "star" => "⋆",
+        "circ" => "∘",
+        "bullet" => "•",
+        "pm" => "±",
+        "mp" => "∓",
+        "div" => "÷",
+        "leq" | "le" => "≤",
+        "geq" | "ge" => "≥",
+        "neq" | "ne" => "≠",
+        "ll" => "≪",
+        "gg" => "≫",
+        "approx" => "≈",
+        "equiv" => "≡",
+        "sim" => "∼",
+        "simeq" => "≃",
+        "cong" => "≅",
+        "propto" => "∝",
+        "infty" => "∞",
+        "partial" => "∂",
+        "nabla" => "∇",
+        "sum" => "∑",
+        "prod" => "∏",
+        "coprod" => "∐",
+        "int" => "∫",
+        "iint" => "∬",
+        "iiint" => "∭",
+        "oint" => "∮",
+        "in" => "∈",
+        "notin" => "∉",
+        "ni" => "∋",
+        "subset" => "⊂",
+        "subseteq" => "⊆",
+        "supset" => "⊃",
+        "supseteq" => "⊇",
+        "cup" => "∪",
+        "cap" => "∩",
+        "setminus" => "∖",
+        "emptyset" | "varnothing" => "∅",
+        "forall" => "∀",
+        "exists" => "∃",
+        "nexists" => "∄",
+        "neg" | "lnot" => "¬",
+        "land" | "wedge" => "∧",
+        "lor" | "vee" => "∨",
+        "oplus" => "⊕",
+        "otimes" => "⊗",
+        "perp" => "⊥",
+        "parallel" => "∥",
+        "mid" => "∣",
+        "to" | "rightarrow" => "→",
+        "leftarrow" | "gets" => "←",
+        "leftrightarrow" => "↔",
+        "Rightarrow" | "implies" => "⇒",
+        "Leftarrow" => "⇐",
+        "Leftrightarrow" | "iff" => "⇔",
+        "mapsto" => "↦",
+        "uparrow" => "↑",
+        "downarrow" => "↓",
+        "angle" => "∠",
+        "triangle" => "△",
+        "degree" => "°",
+        "prime" => "′",
+        "hbar" => "ℏ",
+        "ell" => "ℓ",
+        "Re" => "ℜ",
+        "Im" => "ℑ",
+        "aleph" => "ℵ",
+        "langle" => "⟨",
+        "rangle" => "⟩",
+        "lfloor" => "⌊",
+        "rfloor" => "⌋",
+        "lceil" => "⌈",
+        "rceil" => "⌉",
+        "vert" => "|",
+        "Vert" => "‖",
+        "ldots" | "dots" => "…",
+        "cdots" => "⋯",
+        "vdots" => "⋮",
+        "ddots" => "⋱",
+        "quad" => "  ",
+        "qquad" => "    ",
+        "lbrace" => "{",
+        "rbrace" => "}",
+        _ => return None,
+    })
+}
+
+/// 按函数名原样输出的命令（`\sin x` → `sin x`）
+const FUNCTIONS: &[&str] = &[
+    "sin", "cos", "tan", "cot", "sec", "csc", "arcsin", "arccos", "arctan", "sinh", "cosh", "tanh",
+    "log", "ln", "lg", "exp", "lim", "liminf", "limsup", "max", "min", "sup", "inf", "det", "dim",
+    "ker", "deg", "gcd", "arg", "mod", "Pr",
+];
+
+/// 只影响排版、没有对应文字的命令
+const IGNORED: &[&str] = &[
+    "left",
+    "right",
+    "big",
+    "Big",
+    "bigg",
+    "Bigg",
+    "bigl",
+    "bigr",
+    "Bigl",
+    "Bigr",
+    "displaystyle",
+    "textstyle",
+    "scriptstyle",
+    "limits",
+    "nolimits",
+];
+
+fn superscript(c: char) -> Option<char> {
+    Some(match c {
+        '0' => '⁰',
+        '1' => '¹',
+        '2' => '²',
+        '3' => '³',
+        '4' => '⁴',
+        '5' => '⁵',
+        '6' => '⁶',
+        '7' => '⁷',
+        '8' => '⁸',
+        '9' => '⁹',
+        '+' => '⁺',
+        '-' | '−' => '⁻',
+        '=' => '⁼',
+        '(' => '⁽',
+        ')' => '⁾',
+        'n' => 'ⁿ',
+        'i' => 'ⁱ',
+        '′' => '′',
+        _ => return None,
+    })
+}
+
+fn subscript(c: char) -> Option<char> {
+    Some(match c {
+        '0' => '₀',
+        '1' => '₁',
+        '2' => '₂',
+        '3' => '₃',
+        '4' => '₄',
+        '5' => '₅',
+        '6' => '₆',
+        '7' => '₇',
+        '8' => '₈',
+        '9' => '₉',
+        '+' => '₊',
+        '-' | '−' => '₋',
+        '=' => '₌',
+        '(' => '₍',
+        ')' => '₎',
+        'a' => 'ₐ',
+        'e' => 'ₑ',
+        'o' => 'ₒ',
+        'x' => 'ₓ',
+        'i' => 'ᵢ',
+        'j' => 'ⱼ',
+        'k' => 'ₖ',
+        'm' => 'ₘ',
+        'n' => 'ₙ',
+        _ => return None,
+    })
+}
+
+/// 上下标：全部字符都有对应的上下标字符时直接转换，否则写作 `^(...)` / `_(...)`
+fn script(text: &str, marker: char, map: fn(char) -> Option<char>) -> String {
+    let text = text.trim();
+    match text.chars().map(map).collect::<Option<String>>() {
+        Some(mapped) if !mapped.is_empty() => mapped,
+        _ if text.chars().count() == 1 => format!("{}{}", marker, text),
+        _ => format!("{}({})", marker, text),
+    }
+}
+
+/// 多个字符的分子、分母等加上括号
+fn wrap(text: &str) -> String {
+    let text = text.trim();
+    if text.chars().count() > 1 && !text.chars().all(char::is_alphanumeric) {
+        format!("({})", text)
+    } else {
+        text.to_string()
+    }
+}
+
+/// 双线体字母（`\mathbb{R}` → `ℝ`）
+fn double_struck(text: &str) -> String {
+    text.chars()
+        .map(|c| match c {
+            'C' => 'ℂ',
+            'H' => 'ℍ',
+            'N' => 'ℕ',
+            'P' => 'ℙ',
+            'Q' => 'ℚ',
+            'R' => 'ℝ',
+            'Z' => 'ℤ',
+            other => other,
+        })
+        .collect()
+}
+
+/// LaTeX 公式到 Unicode 文本的简单转换器
+struct TexToText<'a> {
+    chars: Peekable<Chars<'a>>,
+}
+
+impl TexToText<'_> {
+    /// 转换到 `}` 或结尾为止
+    fn sequence(&mut self, in_group: bool) -> String {
+        let mut out = String::new();
+        while let Some(c) = self.chars.next() {
+            match c {
+                '{' => out.push_str(&self.sequence(true)),
+                '}' if in_group => break,
+                '}' => {}
+                '\\' => out.push_str(&self.command()),
+                '^' => {
+                    let arg = self.argument();
+                    out.push_str(&script(&arg, '^', superscript));
+                }
+                '_' => {
+                    let arg = self.argument();
+                    out.push_str(&script(&arg, '_', subscript));
+                }
+                '&' | '~' => out.push(' '),
+                '\'' => out.push('′'),
+                '-' => out.push('−'),
+                c if c.is_whitespace() => {
+                    if !out.ends_with(' ') && !out.is_empty() {
+                        out.push(' ');
+                    }
+                }
+                c => out.push(c),
+            }
+        }
+        out
+    }
+
+    /// 命令或上下标的参数：`{...}`、一个命令或一个字符
+    fn argument(&mut self) -> String {
+        while self.chars.next_if(|c| c.is_whitespace()).is_some() {}
+        match self.chars.next() {
+            Some('{') => self.sequence(true),
+            Some('\\') => self.command(),
+            Some(c) => c.to_string(),
+            None => String::new(),
+        }
+    }
+
+    /// `[...]` 可选参数
+    fn optional_argument(&mut self) -> Option<String> {
+        self.chars.next_if_eq(&'[')?;
+        let mut inner = String::new();
+        for c in self.chars.by_ref() {
+            if c == ']' {
+                break;
+            }
+            inner.push(c);
+        }
+        Some(convert_tex(&inner))
+    }
+
+    fn command(&mut self) -> String {
+        let mut name = String::new();
+        while let Some(c) = self.chars.next_if(|c| c.is_ascii_alphabetic()) {
+            name.push(c);
+        }
+        if name.is_empty() {
+            return match self.chars.next() {
+                Some(',' | ':' | ';' | '>' | ' ') => " ".to_string(),
+                Some('!') | None => String::new(),
+                Some('\\') => "; ".to_string(),
+                Some('|') => "‖".to_string(),
+                Some(c) => c.to_string(),
+            };
+        }
+
+        if let Some(symbol) = symbol(&name) {
+            return symbol.to_string();
+        }
+        if FUNCTIONS.contains(&name.as_str()) {
+            return name;
+        }
+        if IGNORED.contains(&name.as_str()) {
+            // `\left.` 表示不显示的定界符
+            if matches!(name.as_str(), "left" | "right") {
+                self.chars.next_if_eq(&'.');
+            }
+            return String::new();
+        }
+        let accent = |arg: String, mark: char| {
+            if arg.chars().count() == 1 {
+                format!("{}{}", arg, mark)
+            } else {
+                arg
+            }
+        };
+        match name.as_str() {
+            "frac" | "dfrac" | "tfrac" | "cfrac" => {
+                let numerator = self.argument();
+                let denominator = self.argument();
+                format!("{}/{}", wrap(&numerator), wrap(&denominator))
+            }
+            "binom" => {
+                let n = self.argument();
+                let k = self.argument();
+                format!("C({}, {})", n.trim(), k.trim())
+            }
+            "sqrt" => {
+                let index = self.optional_argument();
+                let radicand = self.argument();
+                let root = match index.as_deref().map(str::trim) {
+                    Some("3") => "∛".to_string(),
+                    Some("4") => "∜".to_string(),
+                    Some(index) => format!("{}√", script(index, '^', superscript)),
+                    None => "√".to_string(),
+                };
+                format!("{}{}", root, wrap(&radicand))
+            }
+            "mathbb" => double_struck(&self.argument()),
+            "hat" | "widehat" => accent(self.argument(), '\u{0302}'),
+            "bar" | "overline" => accent(self.argument(), '\u{0304}'),
+            "vec" => accent(self.argument(), '\u{20D7}'),
+            "dot" => accent(self.argument(), '\u{0307}'),
+            "ddot" => accent(self.argument(), '\u{0308}'),
+            "tilde" | "widetilde" => accent(self.argument(), '\u{0303}'),
+            // 环境名本身不输出，矩阵等环境的 `&`、`\\` 转换为空格和分号
+            "begin" | "end" => {
+                self.argument();
+                String::new()
+            }
+            // 字体、文本等命令只保留内容
+            _ => {
+                if self.chars.peek() == Some(&'{') {
+                    self.chars.next();
+                    self.sequence(true)
+                } else {
+                    name
+                }
+            }
+        }
+    }
+}
+
+/// LaTeX → Unicode 纯文本近似（`\frac{a}{b}` → `a/b`, `x^2` → `x²`）
+pub fn convert_tex(tex: &str) -> String {
+    let mut converter = TexToText {
+        chars: tex.chars().peekable(),
+    };
+    converter.sequence(false).trim().to_string()
+}
+
+fn render_expression(expression: &MathExpression, format: MathFormat) -> RenderedMath {
+    let result = match format {
+        MathFormat::Mathml => to_mathml(&expression.tex, expression.display),
+        MathFormat::Svg => to_svg(&expression.tex, expression.display).map(|svg| svg.to_string()),
+        MathFormat::Text => Ok(convert_tex(&expression.tex)),
+    };
+    match result {
+        Ok(output) => RenderedMath {
+            output,
+            error: None,
+        },
+        Err(e) => RenderedMath {
+            output: String::new(),
+            error: Some(e),
+        },
+    }
+}
+
+// 批量渲染公式，默认输出 MathML
+#[tauri::command]
+pub async fn render_math(
+    expressions: Vec<MathExpression>,
+    format: Option<MathFormat>,
+) -> Result<Vec<RenderedMath>, VividError> {
+    let start = Instant::now();
+    let format = format.unwrap_or_default();
+    let count = expressions.len();
+    let results = tauri::async_runtime::spawn_blocking(move || {
+        expressions
+            .iter()
+            .map(|expression| render_expression(expression, format))
+            .collect::<Vec<_>>()
+    })
+    .await
+    .map_err(|e| format!("Math render task failed: {}", e))?;
+
+    let failed = results.iter().filter(|r| r.error.is_some()).count();
+    if failed > 0 {
+        log::warn!("[render_math] {} of {} formula(s) failed", failed, count);
+    }
+    log::debug!(
+        "[render_math] Rendered {} formula(s) as {:?} in {:?}",
+        count,
+        format,
+        start.elapsed()
+    );
+    Ok(results)
+}
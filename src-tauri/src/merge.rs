@@ -0,0 +1,310 @@
+//! 三方合并（diff3）
+//!
+//! 保存冲突时，以打开文件时的内容为共同祖先，合并编辑器中的修改与磁盘上的修改。
+//! 双方改动互不重叠时自动合并；重叠且不同的部分输出冲突标记，并返回每个冲突块的详情。
+
+use serde::{Deserialize, Serialize};
+use similar::{capture_diff_slices, Algorithm, DiffTag};
+
+const MARKER_MINE: &str = "<<<<<<< mine";
+const MARKER_BASE: &str = "||||||| base";
+const MARKER_SEPARATOR: &str = "=======";
+const MARKER_THEIRS: &str = ">>>>>>> theirs";
+
+/// 合并结果
+#[derive(Debug, Serialize, Deserialize)]
+pub struct MergeResult {
+    /// 合并后的内容，冲突处包含 diff3 风格的冲突标记
+    pub merged: String,
+    /// 是否没有冲突
+    pub clean: bool,
+    pub conflicts: Vec<MergeConflict>,
+}
+
+/// 冲突块
+#[derive(Debug, Serialize, Deserialize)]
+pub struct MergeConflict {
+    /// 冲突标记在合并结果中的起止行（从 0 开始，不含 `end_line`）
+    pub start_line: usize,
+    pub end_line: usize,
+    /// 冲突区域在共同祖先中的起始行
+    pub base_line: usize,
+    pub base: String,
+    pub mine: String,
+    pub theirs: String,
+}
+
+/// 某一方相对祖先的一处改动：祖先行区间 → 该方行区间
+#[derive(Debug, Clone, Copy)]
+struct Hunk {
+    base_start: usize,
+    base_end: usize,
+    side_start: usize,
+    side_end: usize,
+}
+
+fn split_lines(text: &str) -> Vec<&str> {
+    text.split_inclusive('\n').collect()
+}
+
+/// 计算一方相对祖先的改动块（相邻的删除 / 插入合并为一块）
+fn hunks(base: &[&str], side: &[&str]) -> Vec<Hunk> {
+    let mut hunks: Vec<Hunk> = Vec::new();
+    for op in capture_diff_slices(Algorithm::Myers, base, side) {
+        let (tag, old, new) = op.as_tag_tuple();
+        if tag == DiffTag::Equal {
+            continue;
+        }
+        match hunks.last_mut() {
+            Some(last) if last.base_end == old.start && last.side_end == new.start => {
+                last.base_end = old.end;
+                last.side_end = new.end;
+            }
+            _ => hunks.push(Hunk {
+                base_start: old.start,
+                base_end: old.end,
+                side_start: new.start,
+                side_end: new.end,
+            }),
+        }
+    }
+    hunks
+}
+
+/// 一个合并区域：祖先行区间以及落在其中的双方改动
+struct Region {
+    base_start: usize,
+    base_end: usize,
+    mine: Vec<Hunk>,
+    theirs: Vec<Hunk>,
+}
+
+/// 将双方改动按祖先位置合并为区域；重叠或首尾相接的改动归入同一区域
+fn regions(mine: &[Hunk], theirs: &[Hunk]) -> Vec<Region> {
+    let mut tagged: Vec<(Hunk, bool)> = mine
+        .iter()
+        .map(|h| (*h, true))
+        .chain(theirs.iter().map(|h| (*h, false)))
+        .collect();
+    tagged.sort_by_key(|(h, _)| (h.base_start, h.base_end));
+
+    let mut regions: Vec<Region> = Vec::new();
+    for (hunk, is_mine) in tagged {
+        let extend = regions
+            .last()
+            .is_some_and(|r| hunk.base_start <= r.base_end);
+        if !extend {
+            regions.push(Region {
+                base_start: hunk.base_start,
+                base_end: hunk.base_end,
+                mine: Vec::new(),
+                theirs: Vec::new(),
+            });
+        }
+        let region = regions.last_mut().expect("region exists");
+        region.base_end = region.base_end.max(hunk.base_end);
+        if is_mine {
+            region.mine.push(hunk);
+        } else {
+            region.theirs.push(hunk);
+        }
+    }
+    regions
+}
+
+/// 区域在某一方中对应的行区间；区域内未改动的祖先行与该方一一对应
+fn side_range(region: &Region, hunks: &[Hunk]) -> (usize, usize) {
+    let first = hunks.first().expect("side has hunks");
+    let last = hunks.last().expect("side has hunks");
+    (
+        first.side_start - (first.base_start - region.base_start),
+        last.side_end + (region.base_end - last.base_end),
+    )
+}
+
+/// 追加若干行，确保结尾有换行，以免冲突标记粘在内容行尾
+fn push_block(out: &mut Vec<String>, lines: &[&str]) {
+    for line in lines {
+        if line.ends_with('\n') {
+            out.push((*line).to_string());
+        } else {
+            out.push(format!("{}\n", line));
+        }
+    }
+}
+
+/// 执行三方合并
+pub fn merge(base: &str, mine: &str, theirs: &str) -> MergeResult {
+    let base_lines = split_lines(base);
+    let mine_lines = split_lines(mine);
+    let theirs_lines = split_lines(theirs);
+
+    let mine_hunks = hunks(&base_lines, &mine_lines);
+    let theirs_hunks = hunks(&base_lines, &theirs_lines);
+
+    let mut out: Vec<String> = Vec::new();
+    let mut conflicts = Vec::new();
+    let mut base_pos = 0;
+
+    for region in regions(&mine_hunks, &theirs_hunks) {
+        out.extend(
+            base_lines[base_pos..region.base_start]
+                .iter()
+                .map(|l| l.to_string()),
+        );
+        base_pos = region.base_end;
+
+        let base_part = &base_lines[region.base_start..region.base_end];
+        let mine_part = if region.mine.is_empty() {
+            base_part
+        } else {
+            let (start, end) = side_range(&region, &region.mine);
+            &mine_lines[start..end]
+        };
+        let theirs_part = if region.theirs.is_empty() {
+            base_part
+        } else {
+            let (start, end) = side_range(&region, &region.theirs);
+            &theirs_lines[start..end]
+        };
+
+        if region.theirs.is_empty() || mine_part == theirs_part {
+            out.extend(mine_part.iter().map(|l| l.to_string()));
+            continue;
+        }
+        if region.mine.is_empty() {
+            out.extend(theirs_part.iter().map(|l| l.to_string()));
+            continue;
+        }
+
+        // 双方都修改了同一区域且结果不同：输出冲突标记
+        let start_line = out.len();
+        out.push(format!("{}\n", MARKER_MINE));
+        push_block(&mut out, mine_part);
+        out.push(format!("{}\n", MARKER_BASE));
+        push_block(&mut out, base_part);
+        out.push(format!("{}\n", MARKER_SEPARATOR));
+        push_block(&mut out, theirs_part);
+        out.push(format!("{}\n", MARKER_THEIRS));
+
+        conflicts.push(MergeConflict {
+            start_line,
+            end_line: out.len(),
+            base_line: region.base_start,
+            base: base_part.concat(),
+            mine: mine_part.concat(),
+            theirs: theirs_part.concat(),
+        });
+    }
+    out.extend(base_lines[base_pos..].iter().map(|l| l.to_string()));
+
+    MergeResult {
+        merged: out.concat(),
+        clean: conflicts.is_empty(),
+        conflicts,
+    }
+}
+
+// 三方合并文本
+#[tauri::command]
+pub fn merge_contents(base: String, mine: String, theirs: String) -> MergeResult {
+    let result = merge(&base, &mine, &theirs);
+    log::info!(
+        "[merge_contents] Merged {} bytes with {} conflict(s)",
+        result.merged.len(),
+        result.conflicts.len()
+    );
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn merges_non_overlapping_changes() {
+        let base = "一\n二\n三\n四\n";
+        let mine = "一（改）\n二\n三\n四\n";
+        let theirs = "一\n二\n三\n四（改）\n";
+        let result = merge(base, mine, theirs);
+        assert!(result.clean);
+        assert_eq!(result.merged, "一（改）\n二\n三\n四（改）\n");
+    }
+
+    #[test]
+    fn identical_changes_do_not_conflict() {
+        let base = "a\nb\nc\n";
+        let changed = "a\nB\nc\n";
+        let result = merge(base, changed, changed);
+        assert!(result.clean);
+        assert_eq!(result.merged, changed);
+    }
+
+    #[test]
+    fn overlapping_changes_produce_conflict_markers() {
+        let base = "a\nb\nc\n";
+        let result = merge(base, "a\nmine\nc\n", "a\ntheirs\nc\n");
+        assert!(!result.clean);
+        assert_eq!(
+            result.merged,
+            "a\n<<<<<<< mine\nmine\n||||||| base\nb\n=======\ntheirs\n>>>>>>> theirs\nc\n"
+        );
+        let conflict = &result.conflicts[0];
+        assert_eq!((conflict.start_line, conflict.end_line), (1, 8));
+        assert_eq!(conflict.base_line, 1);
+        assert_eq!(conflict.base, "b\n");
+        assert_eq!(conflict.mine, "mine\n");
+        assert_eq!(conflict.theirs, "theirs\n");
+    }
+
+    #[test]
+    fn conflict_markers_start_on_their_own_line() {
+        let result = merge("a\nb", "a\nmine", "a\ntheirs");
+        assert!(!result.clean);
+        assert!(result
+            .merged
+            .contains("mine\n||||||| base\nb\n=======\ntheirs\n>>>>>>>"));
+    }
+
+    #[test]
+    fn empty_base_takes_the_only_side_that_changed() {
+        let result = merge("", "新内容\n", "");
+        assert!(result.clean);
+        assert_eq!(result.merged, "新内容\n");
+
+        let result = merge("", "", "theirs\n");
+        assert!(result.clean);
+        assert_eq!(result.merged, "theirs\n");
+    }
+
+    #[test]
+    fn empty_base_with_different_additions_conflicts() {
+        let result = merge("", "mine\n", "theirs\n");
+        assert!(!result.clean);
+        assert_eq!(result.conflicts.len(), 1);
+        assert_eq!(result.conflicts[0].base, "");
+        assert_eq!(result.conflicts[0].base_line, 0);
+        assert_eq!(
+            result.merged,
+            "<<<<<<< mine\nmine\n||||||| base\n=======\ntheirs\n>>>>>>> theirs\n"
+        );
+    }
+
+    #[test]
+    fn all_empty_is_clean() {
+        let result = merge("", "", "");
+        assert!(result.clean);
+        assert_eq!(result.merged, "");
+    }
+
+    #[test]
+    fn adjacent_changes_are_one_region() {
+        // 首尾相接的改动归入同一区域，双方结果不同则冲突
+        let base = "a\nb\nc\n";
+        let result = merge(base, "A\nb\nc\n", "a\nB\nc\n");
+        assert!(!result.clean);
+        assert_eq!(result.conflicts[0].base, "a\nb\n");
+        assert_eq!(result.conflicts[0].mine, "A\nb\n");
+        assert_eq!(result.conflicts[0].theirs, "a\nB\n");
+    }
+}
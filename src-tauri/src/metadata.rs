@@ -0,0 +1,387 @@
+//! 文件元数据缓存
+//!
+//! 侧边栏的提示和排序需要每个文件的标题、字数、修改时间、标签和出链。打开工作区后在后台
+//! 预先计算这些信息并保存在应用数据目录 `metadata/` 下的 SQLite 数据库中（每个工作区一个
+//! 文件），`get_file_summaries` 直接读取缓存而不访问文件系统。
+//!
+//! 重新打开工作区时只重新计算修改时间或大小发生变化的文件，之后随文件监听增量更新；
+//! 后台预热完成后发送 `file-summaries-ready` 事件，前端据此刷新。
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::Instant;
+
+use rusqlite::{params, Connection, Transaction};
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::access::{self, AccessKind};
+use crate::error::VividError;
+use crate::largefile::LARGE_FILE_THRESHOLD;
+use crate::links;
+use crate::search::file_stamp;
+use crate::workspace::FileIndex;
+use crate::{assets, frontmatter, parse, stats, tags};
+
+/// 后台预热完成后发送的事件
+pub const SUMMARIES_READY_EVENT: &str = "file-summaries-ready";
+
+/// 数据库结构版本，不一致时丢弃旧缓存重建
+const SCHEMA_VERSION: i64 = 1;
+/// 预热时每批写入的文件数，批与批之间释放数据库锁，读取不会被长时间阻塞
+const BATCH_SIZE: usize = 100;
+
+/// 单个文件的元数据
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FileSummary {
+    pub path: String,
+    /// 第一个标题，没有标题时为文件名
+    pub title: String,
+    pub words: usize,
+    /// 修改时间（Unix 毫秒）
+    pub modified: i64,
+    pub size: i64,
+    pub tags: Vec<String>,
+    /// 出链目标（不含 `#标题`），按出现顺序去重
+    pub links: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct FileSummaries {
+    /// 后台预热是否已完成；未完成时 `files` 只包含已计算的文件
+    pub ready: bool,
+    pub files: Vec<FileSummary>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SummariesReady {
+    pub root: String,
+    pub files: usize,
+}
+
+struct OpenCache {
+    root: PathBuf,
+    conn: Connection,
+}
+
+/// 工作区文件元数据缓存
+pub struct MetadataCache {
+    app: AppHandle,
+    dir: PathBuf,
+    db: Mutex<Option<OpenCache>>,
+    /// 每次重建或关闭时递增，过期的预热任务据此提前结束
+    generation: AtomicU64,
+    ready: AtomicBool,
+}
+
+fn init_schema(conn: &Connection) -> rusqlite::Result<()> {
+    let version: i64 = conn.query_row("PRAGMA user_version", [], |row| row.get(0))?;
+    if version != SCHEMA_VERSION {
+        conn.execute_batch("DROP TABLE IF EXISTS files;")?;
+    }
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS files (
+            path TEXT PRIMARY KEY,
+            modified INTEGER NOT NULL,
+            size INTEGER NOT NULL,
+            title TEXT NOT NULL,
+            words INTEGER NOT NULL,
+            tags TEXT NOT NULL,
+            links TEXT NOT NULL
+        );",
+    )?;
+    conn.pragma_update(None, "user_version", SCHEMA_VERSION)
+}
+
+/// 读取并计算单个文件的元数据，文件无法读取时返回 `None`
+fn summarize(path: &Path) -> Option<FileSummary> {
+    let (modified, size) = file_stamp(path)?;
+    let name = path
+        .file_stem()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let mut summary = FileSummary {
+        path: path.to_string_lossy().to_string(),
+        title: name,
+        words: 0,
+        modified,
+        size,
+        tags: Vec::new(),
+        links: Vec::new(),
+    };
+    // 大文件只记录文件信息，不读取内容
+    if size as u64 > LARGE_FILE_THRESHOLD {
+        return Some(summary);
+    }
+    let content = fs::read_to_string(path).ok()?;
+
+    let body_start = frontmatter::find_front_matter(&content).map_or(0, |block| block.body_start);
+    if let Some(heading) = parse::outline(&content[body_start..]).into_iter().next() {
+        summary.title = heading.text;
+    }
+    summary.words = stats::document_stats(&content).words;
+    summary.tags = tags::file_tags(&content)
+        .into_values()
+        .map(|(name, _)| name)
+        .collect();
+    for link in links::extract_links(&content) {
+        if !link.target.is_empty() && !summary.links.contains(&link.target) {
+            summary.links.push(link.target);
+        }
+    }
+    Some(summary)
+}
+
+fn upsert(tx: &Transaction<'_>, summary: &FileSummary) -> rusqlite::Result<()> {
+    tx.execute(
+        "INSERT OR REPLACE INTO files (path, modified, size, title, words, tags, links)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+        params![
+            summary.path,
+            summary.modified,
+            summary.size,
+            summary.title,
+            summary.words as i64,
+            serde_json::to_string(&summary.tags).unwrap_or_default(),
+            serde_json::to_string(&summary.links).unwrap_or_default(),
+        ],
+    )?;
+    Ok(())
+}
+
+fn remove_file(tx: &Transaction<'_>, path: &str) -> rusqlite::Result<()> {
+    tx.execute("DELETE FROM files WHERE path = ?1", [path])?;
+    Ok(())
+}
+
+impl MetadataCache {
+    pub fn new(app: AppHandle, dir: PathBuf) -> Self {
+        MetadataCache {
+            app,
+            dir,
+            db: Mutex::new(None),
+            generation: AtomicU64::new(0),
+            ready: AtomicBool::new(false),
+        }
+    }
+
+    /// 打开（必要时创建）工作区对应的缓存数据库
+    pub fn open(&self, root: &Path) -> Result<(), String> {
+        let hash = assets::content_hash(root.to_string_lossy().as_bytes());
+        fs::create_dir_all(&self.dir).map_err(|e| format!("Failed to create directory: {}", e))?;
+        let path = self.dir.join(format!("{}.sqlite", &hash[..16]));
+        let conn = Connection::open(&path)
+            .and_then(|conn| init_schema(&conn).map(|_| conn))
+            .map_err(|e| format!("Failed to open metadata cache: {}", e))?;
+        log::debug!("[metadata] Opened cache {:?} for {}", path, root.display());
+        *self.db.lock().map_err(|e| e.to_string())? = Some(OpenCache {
+            root: root.to_path_buf(),
+            conn,
+        });
+        Ok(())
+    }
+
+    /// 在事务中修改缓存，未打开工作区时忽略，出错时记录日志
+    fn update(&self, f: impl FnOnce(&Transaction<'_>) -> rusqlite::Result<()>) {
+        let Ok(mut db) = self.db.lock() else {
+            return;
+        };
+        let Some(cache) = db.as_mut() else {
+            return;
+        };
+        let result = cache.conn.transaction().and_then(|tx| {
+            f(&tx)?;
+            tx.commit()
+        });
+        if let Err(e) = result {
+            log::warn!("[metadata] Failed to update cache: {}", e);
+        }
+    }
+
+    /// 缓存中的修改时间和大小
+    fn stamps(&self) -> Result<HashMap<String, (i64, i64)>, String> {
+        let db = self.db.lock().map_err(|e| e.to_string())?;
+        let cache = db.as_ref().ok_or("No workspace is open")?;
+        let mut stmt = cache
+            .conn
+            .prepare("SELECT path, modified, size FROM files")
+            .map_err(|e| e.to_string())?;
+        let rows = stmt
+            .query_map([], |row| {
+                Ok((row.get::<_, String>(0)?, (row.get(1)?, row.get(2)?)))
+            })
+            .map_err(|e| e.to_string())?;
+        rows.collect::<rusqlite::Result<_>>()
+            .map_err(|e| e.to_string())
+    }
+
+    /// 后台预热：重新计算有变化的文件，移除已删除的文件
+    fn warm_up(&self, generation: u64, paths: &[PathBuf]) {
+        let start = Instant::now();
+        let mut cached = match self.stamps() {
+            Ok(cached) => cached,
+            Err(e) => {
+                log::warn!("[metadata] Skipping warm-up: {}", e);
+                return;
+            }
+        };
+        let stale: Vec<&PathBuf> = paths
+            .iter()
+            .filter(|path| {
+                let stamp = cached.remove(path.to_string_lossy().as_ref());
+                stamp.is_none() || stamp != file_stamp(path)
+            })
+            .collect();
+        // 剩下的是已删除的文件
+        self.update(|tx| {
+            for path in cached.keys() {
+                remove_file(tx, path)?;
+            }
+            Ok(())
+        });
+
+        for batch in stale.chunks(BATCH_SIZE) {
+            if self.generation.load(Ordering::SeqCst) != generation {
+                log::debug!("[metadata] Warm-up superseded, stopping");
+                return;
+            }
+            // 在锁外读取和解析文件
+            let summaries: Vec<FileSummary> = batch.iter().filter_map(|p| summarize(p)).collect();
+            self.update(|tx| summaries.iter().try_for_each(|summary| upsert(tx, summary)));
+        }
+        if self.generation.load(Ordering::SeqCst) != generation {
+            return;
+        }
+        self.ready.store(true, Ordering::SeqCst);
+
+        log::info!(
+            "[metadata] ✓ Warmed {} of {} file(s), removed {} in {:?}",
+            stale.len(),
+            paths.len(),
+            cached.len(),
+            start.elapsed()
+        );
+        let root = self
+            .db
+            .lock()
+            .ok()
+            .and_then(|db| db.as_ref().map(|cache| cache.root.clone()));
+        if let Some(root) = root {
+            let payload = SummariesReady {
+                root: root.to_string_lossy().to_string(),
+                files: paths.len(),
+            };
+            if let Err(e) = self.app.emit(SUMMARIES_READY_EVENT, payload) {
+                log::warn!("[metadata] Failed to emit event: {}", e);
+            }
+        }
+    }
+
+    fn summaries(&self, root: &Path) -> Result<Vec<FileSummary>, String> {
+        let db = self.db.lock().map_err(|e| e.to_string())?;
+        let cache = db.as_ref().ok_or("No workspace is open")?;
+        let mut stmt = cache
+            .conn
+            .prepare_cached(
+                "SELECT path, modified, size, title, words, tags, links FROM files ORDER BY path",
+            )
+            .map_err(|e| format!("Failed to read metadata cache: {}", e))?;
+        let rows = stmt
+            .query_map([], |row| {
+                Ok(FileSummary {
+                    path: row.get(0)?,
+                    modified: row.get(1)?,
+                    size: row.get(2)?,
+                    title: row.get(3)?,
+                    words: row.get::<_, i64>(4)? as usize,
+                    tags: serde_json::from_str(&row.get::<_, String>(5)?).unwrap_or_default(),
+                    links: serde_json::from_str(&row.get::<_, String>(6)?).unwrap_or_default(),
+                })
+            })
+            .map_err(|e| format!("Failed to read metadata cache: {}", e))?;
+
+        let mut files = Vec::new();
+        for row in rows {
+            let summary = row.map_err(|e| format!("Failed to read metadata cache: {}", e))?;
+            if Path::new(&summary.path).starts_with(root) {
+                files.push(summary);
+            }
+        }
+        Ok(files)
+    }
+}
+
+impl FileIndex for MetadataCache {
+    /// 在后台重建，不阻塞打开工作区
+    fn rebuild(&self, paths: &[PathBuf]) {
+        let generation = self.generation.fetch_add(1, Ordering::SeqCst) + 1;
+        self.ready.store(false, Ordering::SeqCst);
+        let app = self.app.clone();
+        let paths = paths.to_vec();
+        tauri::async_runtime::spawn_blocking(move || {
+            app.state::<MetadataCache>().warm_up(generation, &paths)
+        });
+    }
+
+    fn refresh(&self, path: &Path) {
+        let summary = summarize(path);
+        self.update(|tx| match &summary {
+            Some(summary) => upsert(tx, summary),
+            None => remove_file(tx, &path.to_string_lossy()),
+        });
+    }
+
+    fn remove_under(&self, path: &Path) {
+        self.update(|tx| {
+            let paths: Vec<String> = {
+                let mut stmt = tx.prepare("SELECT path FROM files")?;
+                let rows = stmt.query_map([], |row| row.get::<_, String>(0))?;
+                rows.collect::<rusqlite::Result<Vec<String>>>()?
+            };
+            for file in paths.iter().filter(|p| Path::new(p).starts_with(path)) {
+                remove_file(tx, file)?;
+            }
+            Ok(())
+        });
+    }
+
+    /// 关闭数据库并停止预热，缓存文件保留供下次打开时复用
+    fn clear(&self) {
+        self.generation.fetch_add(1, Ordering::SeqCst);
+        self.ready.store(false, Ordering::SeqCst);
+        if let Ok(mut db) = self.db.lock() {
+            *db = None;
+        }
+    }
+}
+
+// 读取工作区（或其中某个目录）下所有文件的缓存元数据
+#[tauri::command]
+pub async fn get_file_summaries(app: AppHandle, root: String) -> Result<FileSummaries, VividError> {
+    let start = Instant::now();
+    access::ensure_access(&app, Path::new(&root), AccessKind::Read)?;
+    let summaries = tauri::async_runtime::spawn_blocking(move || {
+        let cache = app.state::<MetadataCache>();
+        // 先读取状态：读取期间预热完成时前端还会收到事件
+        let ready = cache.ready.load(Ordering::SeqCst);
+        cache
+            .summaries(Path::new(&root))
+            .map(|files| FileSummaries { ready, files })
+    })
+    .await
+    .map_err(|e| format!("Metadata task failed: {}", e))?
+    .map_err(|e| {
+        log::error!("[get_file_summaries] {}", e);
+        e
+    })?;
+    log::debug!(
+        "[get_file_summaries] {} file(s) (ready: {}) in {:?}",
+        summaries.files.len(),
+        summaries.ready,
+        start.elapsed()
+    );
+    Ok(summaries)
+}
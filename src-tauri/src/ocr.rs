@@ -0,0 +1,275 @@
+//! 图片文字识别
+//!
+//! 与 pandoc 一样调用外部命令：`ocr_image` 把图片交给 tesseract（设置的 `ocr.tesseract_path`
+//! 或 PATH 中的 `tesseract`），解析 TSV 输出得到逐行文字和置信度。粘贴的截图以字节传入，
+//! 写入临时文件后识别，识别结束后删除。
+//!
+//! 返回的 `markdown` 按段落分行并转义 Markdown 语法字符，可以直接插入文档。
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager};
+
+use crate::access::{self, AccessKind};
+use crate::bibliography::escape_markdown;
+use crate::diagram::run_tool_with_timeout;
+use crate::error::VividError;
+use crate::settings::SettingsStore;
+use crate::storage;
+
+/// 单张图片的识别超时
+const OCR_TIMEOUT: Duration = Duration::from_secs(120);
+/// 图片大小上限
+const MAX_IMAGE_BYTES: usize = 50 * 1024 * 1024;
+
+/// 要识别的图片：文件路径或图片内容（如剪贴板中的截图）
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+pub enum ImageSource {
+    Path(String),
+    Bytes(Vec<u8>),
+}
+
+/// 识别出的一行文字
+#[derive(Debug, Serialize, Deserialize)]
+pub struct OcrLine {
+    pub text: String,
+    /// 平均置信度（0 到 1）
+    pub confidence: f32,
+    /// 所在段落（从 0 开始）
+    pub paragraph: usize,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct OcrResult {
+    /// 识别出的纯文本，段落之间空一行
+    pub text: String,
+    /// 转义后可直接插入文档的 Markdown
+    pub markdown: String,
+    /// 所有文字的平均置信度（0 到 1），没有识别出文字时为 0
+    pub confidence: f32,
+    pub language: String,
+    pub lines: Vec<OcrLine>,
+    pub word_count: usize,
+}
+
+/// 识别期间使用的临时图片文件，结束后删除
+struct TempImage(PathBuf);
+
+impl Drop for TempImage {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.0);
+    }
+}
+
+/// tesseract 语言包名只包含字母、数字和下划线，多个语言用 `+` 连接
+fn is_valid_language(language: &str) -> bool {
+    !language.is_empty()
+        && language.split('+').all(|part| {
+            !part.is_empty() && part.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+        })
+}
+
+/// 解析 tesseract 的 TSV 输出：按 (块, 段落, 行) 汇总单词
+fn parse_tsv(tsv: &str) -> Vec<OcrLine> {
+    let mut lines: Vec<OcrLine> = Vec::new();
+    let mut current_key = None;
+    let mut current_paragraph = None;
+    let mut paragraph = 0;
+    let mut confidences: Vec<f32> = Vec::new();
+
+    // 结束当前行，记录平均置信度
+    fn finish(lines: &mut [OcrLine], confidences: &mut Vec<f32>) {
+        if let Some(line) = lines.last_mut() {
+            if !confidences.is_empty() {
+                line.confidence =
+                    confidences.iter().sum::<f32>() / confidences.len() as f32 / 100.0;
+            }
+        }
+        confidences.clear();
+    }
+
+    for row in tsv.lines().skip(1) {
+        let fields: Vec<&str> = row.splitn(12, '\t').collect();
+        if fields.len() < 12 || fields[0] != "5" {
+            continue;
+        }
+        let text = fields[11].trim();
+        let Ok(confidence) = fields[10].trim().parse::<f32>() else {
+            continue;
+        };
+        if text.is_empty() || confidence < 0.0 {
+            continue;
+        }
+        let paragraph_key = (fields[1], fields[2], fields[3]);
+        let line_key = (paragraph_key, fields[4]);
+        if current_key != Some(line_key) {
+            finish(&mut lines, &mut confidences);
+            if current_paragraph.is_some_and(|key| key != paragraph_key) {
+                paragraph += 1;
+            }
+            current_paragraph = Some(paragraph_key);
+            current_key = Some(line_key);
+            lines.push(OcrLine {
+                text: String::new(),
+                confidence: 0.0,
+                paragraph,
+            });
+        }
+        if let Some(line) = lines.last_mut() {
+            if !line.text.is_empty() {
+                line.text.push(' ');
+            }
+            line.text.push_str(text);
+        }
+        confidences.push(confidence.min(100.0));
+    }
+    finish(&mut lines, &mut confidences);
+    lines
+}
+
+/// 行首会被解析为标题、引用、列表等块语法的字符前加反斜杠
+fn escape_line_start(line: &str) -> String {
+    let mut out = String::with_capacity(line.len() + 8);
+    let digits = line.chars().take_while(char::is_ascii_digit).count();
+    let rest = &line[digits..];
+    if line.starts_with(['#', '-', '+', '=', '|']) {
+        out.push('\\');
+    } else if digits > 0 && (rest.starts_with(". ") || rest.starts_with(") ")) {
+        out.push_str(&line[..digits]);
+        out.push('\\');
+        escape_markdown(rest, &mut out);
+        return out;
+    }
+    escape_markdown(line, &mut out);
+    out
+}
+
+fn build_result(lines: Vec<OcrLine>, language: String) -> OcrResult {
+    let mut text = String::new();
+    let mut markdown = String::new();
+    let mut previous = None;
+    for line in &lines {
+        if let Some(previous) = previous {
+            let separator = if previous == line.paragraph {
+                "\n"
+            } else {
+                "\n\n"
+            };
+            text.push_str(separator);
+            markdown.push_str(separator);
+        }
+        text.push_str(&line.text);
+        markdown.push_str(&escape_line_start(&line.text));
+        previous = Some(line.paragraph);
+    }
+    let words: Vec<(usize, f32)> = lines
+        .iter()
+        .map(|line| (line.text.split_whitespace().count(), line.confidence))
+        .collect();
+    let word_count: usize = words.iter().map(|(count, _)| count).sum();
+    let confidence = if word_count == 0 {
+        0.0
+    } else {
+        words
+            .iter()
+            .map(|(count, confidence)| *count as f32 * confidence)
+            .sum::<f32>()
+            / word_count as f32
+    };
+    OcrResult {
+        text,
+        markdown,
+        confidence,
+        language,
+        lines,
+        word_count,
+    }
+}
+
+fn recognize(program: &str, image: &Path, language: String) -> Result<OcrResult, String> {
+    let image = image.to_string_lossy();
+    let output = run_tool_with_timeout(
+        program,
+        &[&image, "stdout", "-l", &language, "tsv"],
+        "",
+        OCR_TIMEOUT,
+    )?;
+    let tsv = String::from_utf8_lossy(&output);
+    Ok(build_result(parse_tsv(&tsv), language))
+}
+
+// 识别图片中的文字；`image` 为图片路径或图片内容，`language` 默认使用设置中的识别语言
+#[tauri::command]
+pub async fn ocr_image(
+    app: AppHandle,
+    image: ImageSource,
+    language: Option<String>,
+) -> Result<OcrResult, VividError> {
+    let start = Instant::now();
+    let settings = app.state::<SettingsStore>().get().ocr;
+    let language = language
+        .map(|l| l.trim().to_string())
+        .filter(|l| !l.is_empty())
+        .unwrap_or(settings.language);
+    if !is_valid_language(&language) {
+        return Err(VividError::invalid_input(format!(
+            "Invalid OCR language: {}",
+            language
+        )));
+    }
+    let program = settings
+        .tesseract_path
+        .map(|p| p.trim().to_string())
+        .filter(|p| !p.is_empty())
+        .unwrap_or_else(|| "tesseract".to_string());
+
+    let (path, _temp) = match image {
+        ImageSource::Path(path) => {
+            let path = PathBuf::from(path);
+            if !path.is_file() {
+                log::error!("[ocr_image] File does not exist: {}", path.display());
+                return Err(VividError::not_found(&path));
+            }
+            access::ensure_access(&app, &path, AccessKind::Read)?;
+            (path, None)
+        }
+        ImageSource::Bytes(bytes) => {
+            if bytes.is_empty() {
+                return Err(VividError::invalid_input("Image is empty"));
+            }
+            if bytes.len() > MAX_IMAGE_BYTES {
+                return Err(VividError::invalid_input(format!(
+                    "Image is larger than {} MB",
+                    MAX_IMAGE_BYTES / 1024 / 1024
+                )));
+            }
+            let path = std::env::temp_dir().join(format!(
+                "vividmark-ocr-{}-{}.img",
+                std::process::id(),
+                storage::now_millis()
+            ));
+            fs::write(&path, &bytes)?;
+            (path.clone(), Some(TempImage(path)))
+        }
+    };
+    log::info!("[ocr_image] {} ({})", path.display(), language);
+
+    let result = tauri::async_runtime::spawn_blocking(move || recognize(&program, &path, language))
+        .await
+        .map_err(|e| format!("OCR task failed: {}", e))?
+        .map_err(|e| {
+            log::error!("[ocr_image] {}", e);
+            VividError::from(e)
+        })?;
+    log::info!(
+        "[ocr_image] ✓ Success: {} word(s), confidence {:.2} in {:?}",
+        result.word_count,
+        result.confidence,
+        start.elapsed()
+    );
+    Ok(result)
+}
@@ -0,0 +1,322 @@
+//! Markdown 结构解析
+//!
+//! 为前端提供大纲（目录面板、标题跳转）和可选的完整语法树，
+//! 避免在 JS 中再维护一套解析器。所有位置同时给出字节区间和 UTF-16 偏移，
+//! 后者可直接用于 textarea 的 `selectionStart`。
+
+use std::collections::HashMap;
+use std::ops::Range;
+
+use pulldown_cmark::{CodeBlockKind, Event, HeadingLevel, Tag, TagEnd};
+use serde::{Deserialize, Serialize};
+
+use crate::markdown;
+
+/// 大纲中的一个标题
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Heading {
+    pub level: u8,
+    pub text: String,
+    /// 锚点，优先使用 `{#id}` 属性，否则由标题文本生成（重复时追加 `-1`、`-2`）
+    pub slug: String,
+    /// 标题在文档中的序号（用于预览模式定位）
+    pub index: usize,
+    /// 所在行（从 0 开始）
+    pub line_index: usize,
+    /// 起始位置的 UTF-16 偏移
+    pub char_index: usize,
+    /// 标题源码的字节区间
+    pub start: usize,
+    pub end: usize,
+}
+
+/// 语法树节点
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AstNode {
+    #[serde(rename = "type")]
+    pub kind: String,
+    pub start: usize,
+    pub end: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub level: Option<u8>,
+    /// 代码块语言
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub lang: Option<String>,
+    /// 链接 / 图片地址
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    /// 文本、代码、HTML、公式等叶子节点的内容
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub value: Option<String>,
+    /// 任务列表项的勾选状态
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub checked: Option<bool>,
+    /// 有序列表的起始序号
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub start_number: Option<u64>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub children: Vec<AstNode>,
+}
+
+impl AstNode {
+    fn new(kind: &str, range: Range<usize>) -> Self {
+        AstNode {
+            kind: kind.to_string(),
+            start: range.start,
+            end: range.end,
+            level: None,
+            lang: None,
+            url: None,
+            title: None,
+            value: None,
+            checked: None,
+            start_number: None,
+            children: Vec::new(),
+        }
+    }
+
+    fn leaf(kind: &str, range: Range<usize>, value: &str) -> Self {
+        let mut node = AstNode::new(kind, range);
+        node.value = Some(value.to_string());
+        node
+    }
+}
+
+/// 解析结果
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ParsedMarkdown {
+    pub outline: Vec<Heading>,
+    /// 仅在请求时返回
+    pub ast: Option<AstNode>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ParseMarkdownParams {
+    pub content: String,
+    /// 是否返回完整语法树，默认只返回大纲
+    pub include_ast: Option<bool>,
+}
+
+/// 字节偏移 → 行号 / UTF-16 偏移的换算
+pub struct LineIndex {
+    /// 每行起始的字节偏移
+    line_starts: Vec<usize>,
+}
+
+impl LineIndex {
+    pub fn new(content: &str) -> Self {
+        let mut line_starts = vec![0];
+        line_starts.extend(content.match_indices('\n').map(|(i, _)| i + 1));
+        LineIndex { line_starts }
+    }
+
+    /// 字节偏移所在的行（从 0 开始）
+    pub fn line_of(&self, offset: usize) -> usize {
+        match self.line_starts.binary_search(&offset) {
+            Ok(line) => line,
+            Err(next) => next - 1,
+        }
+    }
+}
+
+/// 字节偏移对应的 UTF-16 偏移
+pub fn utf16_offset(content: &str, byte_offset: usize) -> usize {
+    content[..byte_offset.min(content.len())]
+        .chars()
+        .map(char::len_utf16)
+        .sum()
+}
+
+fn heading_level(level: HeadingLevel) -> u8 {
+    match level {
+        HeadingLevel::H1 => 1,
+        HeadingLevel::H2 => 2,
+        HeadingLevel::H3 => 3,
+        HeadingLevel::H4 => 4,
+        HeadingLevel::H5 => 5,
+        HeadingLevel::H6 => 6,
+    }
+}
+
+/// 提取文档大纲
+pub fn outline(content: &str) -> Vec<Heading> {
+    let lines = LineIndex::new(content);
+    let mut headings = Vec::new();
+    let mut slug_counts: HashMap<String, usize> = HashMap::new();
+    let mut current: Option<(u8, Option<String>, Range<usize>, String)> = None;
+
+    for (event, range) in markdown::parser(content).into_offset_iter() {
+        match event {
+            Event::Start(Tag::Heading { level, id, .. }) => {
+                current = Some((
+                    heading_level(level),
+                    id.map(|id| id.to_string()),
+                    range,
+                    String::new(),
+                ));
+            }
+            Event::End(TagEnd::Heading(_)) => {
+                let Some((level, id, range, text)) = current.take() else {
+                    continue;
+                };
+                let text = text.trim().to_string();
+                let slug = id.unwrap_or_else(|| {
+                    let base = markdown::slugify(&text);
+                    let count = slug_counts.entry(base.clone()).or_insert(0);
+                    let slug = if *count == 0 {
+                        base.clone()
+                    } else {
+                        format!("{}-{}", base, count)
+                    };
+                    *count += 1;
+                    slug
+                });
+                headings.push(Heading {
+                    level,
+                    text,
+                    slug,
+                    index: headings.len(),
+                    line_index: lines.line_of(range.start),
+                    char_index: utf16_offset(content, range.start),
+                    start: range.start,
+                    end: range.end,
+                });
+            }
+            Event::Text(text) | Event::Code(text) | Event::InlineMath(text) => {
+                if let Some((_, _, _, buf)) = current.as_mut() {
+                    buf.push_str(&text);
+                }
+            }
+            _ => {}
+        }
+    }
+    headings
+}
+
+fn tag_node(tag: Tag<'_>, range: Range<usize>) -> AstNode {
+    match tag {
+        Tag::Paragraph => AstNode::new("paragraph", range),
+        Tag::Heading { level, .. } => {
+            let mut node = AstNode::new("heading", range);
+            node.level = Some(heading_level(level));
+            node
+        }
+        Tag::BlockQuote(_) => AstNode::new("blockquote", range),
+        Tag::CodeBlock(kind) => {
+            let mut node = AstNode::new("code_block", range);
+            if let CodeBlockKind::Fenced(info) = kind {
+                let lang = info.split_whitespace().next().unwrap_or("");
+                if !lang.is_empty() {
+                    node.lang = Some(lang.to_string());
+                }
+            }
+            node
+        }
+        Tag::HtmlBlock => AstNode::new("html_block", range),
+        Tag::List(start) => {
+            let mut node = AstNode::new("list", range);
+            node.start_number = start;
+            node
+        }
+        Tag::Item => AstNode::new("list_item", range),
+        Tag::FootnoteDefinition(label) => {
+            let mut node = AstNode::new("footnote_definition", range);
+            node.value = Some(label.to_string());
+            node
+        }
+        Tag::Table(_) => AstNode::new("table", range),
+        Tag::TableHead => AstNode::new("table_head", range),
+        Tag::TableRow => AstNode::new("table_row", range),
+        Tag::TableCell => AstNode::new("table_cell", range),
+        Tag::Emphasis => AstNode::new("emphasis", range),
+        Tag::Strong => AstNode::new("strong", range),
+        Tag::Strikethrough => AstNode::new("strikethrough", range),
+        Tag::Link {
+            dest_url, title, ..
+        } => {
+            let mut node = AstNode::new("link", range);
+            node.url = Some(dest_url.to_string());
+            node.title = (!title.is_empty()).then(|| title.to_string());
+            node
+        }
+        Tag::Image {
+            dest_url, title, ..
+        } => {
+            let mut node = AstNode::new("image", range);
+            node.url = Some(dest_url.to_string());
+            node.title = (!title.is_empty()).then(|| title.to_string());
+            node
+        }
+        other => AstNode::new(&format!("{:?}", other).to_lowercase(), range),
+    }
+}
+
+/// 构建完整语法树，根节点类型为 `document`
+pub fn ast(content: &str) -> AstNode {
+    let mut stack = vec![AstNode::new("document", 0..content.len())];
+
+    for (event, range) in markdown::parser(content).into_offset_iter() {
+        let leaf = match event {
+            Event::Start(tag) => {
+                stack.push(tag_node(tag, range));
+                continue;
+            }
+            Event::End(_) => {
+                if stack.len() > 1 {
+                    let node = stack.pop().expect("stack is not empty");
+                    stack.last_mut().expect("root exists").children.push(node);
+                }
+                continue;
+            }
+            Event::TaskListMarker(checked) => {
+                if let Some(item) = stack.last_mut() {
+                    item.checked = Some(checked);
+                }
+                continue;
+            }
+            Event::Text(text) => {
+                // 代码块内的文本直接作为代码块的值
+                if let Some(block) = stack.last_mut().filter(|n| n.kind == "code_block") {
+                    block.value.get_or_insert_with(String::new).push_str(&text);
+                    continue;
+                }
+                AstNode::leaf("text", range, &text)
+            }
+            Event::Code(code) => AstNode::leaf("inline_code", range, &code),
+            Event::InlineMath(tex) => AstNode::leaf("inline_math", range, &tex),
+            Event::DisplayMath(tex) => AstNode::leaf("display_math", range, &tex),
+            Event::Html(html) => AstNode::leaf("html", range, &html),
+            Event::InlineHtml(html) => AstNode::leaf("inline_html", range, &html),
+            Event::FootnoteReference(label) => AstNode::leaf("footnote_reference", range, &label),
+            Event::SoftBreak => AstNode::new("soft_break", range),
+            Event::HardBreak => AstNode::new("hard_break", range),
+            Event::Rule => AstNode::new("thematic_break", range),
+        };
+        stack.last_mut().expect("root exists").children.push(leaf);
+    }
+
+    // 正常情况下事件成对出现，这里兜底收拢未闭合的节点
+    while stack.len() > 1 {
+        let node = stack.pop().expect("stack is not empty");
+        stack.last_mut().expect("root exists").children.push(node);
+    }
+    stack.pop().expect("root exists")
+}
+
+// 解析 Markdown，返回大纲（可选语法树）
+#[tauri::command]
+pub fn parse_markdown(params: ParseMarkdownParams) -> ParsedMarkdown {
+    let outline = outline(&params.content);
+    let ast = params
+        .include_ast
+        .unwrap_or(false)
+        .then(|| ast(&params.content));
+    log::debug!(
+        "[parse_markdown] {} bytes, {} heading(s)",
+        params.content.len(),
+        outline.len()
+    );
+    ParsedMarkdown { outline, ast }
+}
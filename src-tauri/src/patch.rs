@@ -0,0 +1,277 @@
+//! 增量保存
+//!
+//! 大文档保存时前端只发送改动的区间，由后端在磁盘内容上应用，写入与 `save_file` 相同
+//! （见 `write_document`：备份、原子写入、通知其它窗口等）。
+//! 磁盘内容的修订标记与 `base_revision` 不一致、文件只读或写入失败时不做任何修改，
+//! 返回 `requires_full_save`，由前端改用 `save_file` 发送完整内容（并处理解除只读、重试等）。
+
+use std::path::PathBuf;
+use std::time::Instant;
+
+use serde::{Deserialize, Serialize};
+use tauri::{Manager, WebviewWindow};
+
+use crate::access::{self, AccessKind};
+use crate::error::VividError;
+use crate::{markdown, perf, revision};
+
+/// 一处区间替换，偏移为基准内容中的 UTF-16 偏移（与 textarea 的 `selectionStart` 一致）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TextEdit {
+    pub start: usize,
+    pub end: usize,
+    pub text: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ApplyPatchResult {
+    pub success: bool,
+    pub error: Option<String>,
+    /// 保存后的修订标记
+    pub revision: Option<String>,
+    /// 无法增量保存（修订不一致或改动无效），需要改用完整内容保存
+    pub requires_full_save: bool,
+}
+
+impl ApplyPatchResult {
+    fn fallback(error: impl Into<String>) -> Self {
+        ApplyPatchResult {
+            success: false,
+            error: Some(error.into()),
+            revision: None,
+            requires_full_save: true,
+        }
+    }
+}
+
+/// 将 UTF-16 偏移转换为字节偏移，`offsets` 需已升序排列；超出范围时返回 `None`
+fn byte_offsets(content: &str, offsets: &[usize]) -> Option<Vec<usize>> {
+    let mut result = Vec::with_capacity(offsets.len());
+    let mut pending = offsets.iter().peekable();
+    let mut utf16 = 0;
+    for (byte, c) in content.char_indices() {
+        while let Some(&&offset) = pending.peek() {
+            if offset > utf16 {
+                break;
+            }
+            // 偏移落在代理对中间时视为无效
+            if offset < utf16 {
+                return None;
+            }
+            result.push(byte);
+            pending.next();
+        }
+        utf16 += c.len_utf16();
+    }
+    for &offset in pending {
+        if offset != utf16 {
+            return None;
+        }
+        result.push(content.len());
+    }
+    Some(result)
+}
+
+/// 在内容上应用改动
+pub fn apply_edits(content: &str, edits: &[TextEdit]) -> Result<String, String> {
+    let mut edits: Vec<&TextEdit> = edits.iter().collect();
+    edits.sort_by_key(|edit| (edit.start, edit.end));
+    for pair in edits.windows(2) {
+        if pair[1].start < pair[0].end {
+            return Err("Edits overlap".to_string());
+        }
+    }
+    if edits.iter().any(|edit| edit.end < edit.start) {
+        return Err("Invalid edit range".to_string());
+    }
+
+    let offsets: Vec<usize> = edits.iter().flat_map(|e| [e.start, e.end]).collect();
+    let bytes = byte_offsets(content, &offsets).ok_or("Edit range is out of bounds")?;
+    let mut replacements: Vec<markdown::Replacement> = edits
+        .iter()
+        .zip(bytes.chunks(2))
+        .map(|(edit, range)| (range[0]..range[1], edit.text.clone()))
+        .collect();
+    Ok(markdown::apply_edits(content, &mut replacements))
+}
+
+// 在磁盘文件上应用区间改动（增量保存）
+//
+// `base_revision` 为前端内容所基于的修订标记（上次读取或保存时返回），
+// `edits` 为相对该内容的改动，区间不能重叠。
+#[tauri::command]
+pub fn apply_patch(
+    window: WebviewWindow,
+    path: String,
+    base_revision: String,
+    edits: Vec<TextEdit>,
+) -> Result<ApplyPatchResult, VividError> {
+    let start = Instant::now();
+    let path_buf = PathBuf::from(&path);
+    access::ensure_access(window.app_handle(), &path_buf, AccessKind::Write)?;
+    log::info!("[apply_patch] Starting incremental save operation");
+    log::debug!(
+        "[apply_patch] Target path: {}, {} edit(s)",
+        path,
+        edits.len()
+    );
+
+    let (bytes, disk_revision) = match revision::current_revision(&path_buf) {
+        Ok(Some(current)) => current,
+        Ok(None) => {
+            log::warn!("[apply_patch] File no longer exists, full save required");
+            return Ok(ApplyPatchResult::fallback("File does not exist"));
+        }
+        Err(e) => {
+            log::error!("[apply_patch] Failed to read file: {}", e);
+            return Ok(ApplyPatchResult::fallback(format!(
+                "Failed to read file: {}",
+                e
+            )));
+        }
+    };
+    if !revision::same_content(&base_revision, &disk_revision) {
+        log::warn!(
+            "[apply_patch] Revision mismatch: expected {}, found {}",
+            base_revision,
+            disk_revision
+        );
+        return Ok(ApplyPatchResult::fallback("Revision mismatch"));
+    }
+
+    let Ok(content) = String::from_utf8(bytes) else {
+        return Ok(ApplyPatchResult::fallback("File is not valid UTF-8"));
+    };
+    let updated = match apply_edits(&content, &edits) {
+        Ok(updated) => updated,
+        Err(e) => {
+            log::warn!("[apply_patch] {}", e);
+            return Ok(ApplyPatchResult::fallback(e));
+        }
+    };
+
+    let written = crate::write_document(
+        &window,
+        "apply_patch",
+        &path_buf,
+        &updated,
+        updated.as_bytes(),
+        false,
+    );
+    let revision = match written {
+        Ok((revision, _)) => revision,
+        Err(failure) => {
+            let error = failure.error.unwrap_or_default();
+            log::warn!("[apply_patch] {}, full save required", error);
+            return Ok(ApplyPatchResult::fallback(error));
+        }
+    };
+    perf::record(
+        perf::Operation::Save,
+        &path,
+        start.elapsed(),
+        updated.len() as u64,
+    );
+
+    log::info!(
+        "[apply_patch] ✓ Success: {} ({} bytes) in {:?}",
+        path,
+        updated.len(),
+        start.elapsed()
+    );
+    Ok(ApplyPatchResult {
+        success: true,
+        error: None,
+        revision: Some(revision),
+        requires_full_save: false,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn edit(start: usize, end: usize, text: &str) -> TextEdit {
+        TextEdit {
+            start,
+            end,
+            text: text.to_string(),
+        }
+    }
+
+    #[test]
+    fn byte_offsets_count_utf16_units() {
+        // `中` 占 3 字节、1 个 UTF-16 单元；`😀` 占 4 字节、2 个 UTF-16 单元
+        let content = "a中😀b";
+        assert_eq!(
+            byte_offsets(content, &[0, 1, 2, 4, 5]),
+            Some(vec![0, 1, 4, 8, 9])
+        );
+    }
+
+    #[test]
+    fn byte_offsets_reject_surrogate_pair_middle() {
+        assert_eq!(byte_offsets("😀", &[1]), None);
+        assert_eq!(byte_offsets("a😀", &[0, 2]), None);
+    }
+
+    #[test]
+    fn byte_offsets_reject_out_of_bounds() {
+        assert_eq!(byte_offsets("ab", &[3]), None);
+        assert_eq!(byte_offsets("", &[0, 0]), Some(vec![0, 0]));
+    }
+
+    #[test]
+    fn applies_edits_after_non_ascii_text() {
+        let content = "标题😀\n正文";
+        // 把 `正文` 替换为 `内容`：`标题😀\n` 共 5 个 UTF-16 单元
+        assert_eq!(
+            apply_edits(content, &[edit(5, 7, "内容")]).unwrap(),
+            "标题😀\n内容"
+        );
+        assert_eq!(
+            apply_edits(content, &[edit(2, 4, "")]).unwrap(),
+            "标题\n正文"
+        );
+    }
+
+    #[test]
+    fn rejects_edit_inside_surrogate_pair() {
+        assert!(apply_edits("😀", &[edit(1, 2, "x")]).is_err());
+    }
+
+    #[test]
+    fn applies_out_of_order_edits() {
+        let edits = [edit(4, 5, "E"), edit(0, 1, "A"), edit(2, 2, "+")];
+        assert_eq!(apply_edits("abcde", &edits).unwrap(), "Ab+cdE");
+    }
+
+    #[test]
+    fn adjacent_edits_are_allowed() {
+        let edits = [edit(1, 2, "B"), edit(0, 1, "A"), edit(2, 2, "!")];
+        assert_eq!(apply_edits("abc", &edits).unwrap(), "AB!c");
+    }
+
+    #[test]
+    fn rejects_overlapping_edits() {
+        let edits = [edit(3, 5, "x"), edit(0, 4, "y")];
+        assert_eq!(apply_edits("abcdef", &edits).unwrap_err(), "Edits overlap");
+    }
+
+    #[test]
+    fn rejects_invalid_ranges() {
+        assert_eq!(
+            apply_edits("abc", &[edit(2, 1, "x")]).unwrap_err(),
+            "Invalid edit range"
+        );
+        assert_eq!(
+            apply_edits("abc", &[edit(2, 4, "x")]).unwrap_err(),
+            "Edit range is out of bounds"
+        );
+    }
+
+    #[test]
+    fn no_edits_returns_content() {
+        assert_eq!(apply_edits("abc", &[]).unwrap(), "abc");
+    }
+}
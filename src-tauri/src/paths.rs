@@ -0,0 +1,233 @@
+//! 路径规范化
+//!
+//! 同一个文件可以通过多种路径访问：符号链接、`..`、大小写不同的写法（macOS 和 Windows 默认
+//! 不区分大小写），以及 macOS 上 NFC / NFD 两种 Unicode 形式的文件名。文件监听、最近文件和
+//! 多窗口冲突检测都以路径作为键，必须先换成同一种写法，否则会把同一个文件当成两篇文档。
+//!
+//! - `canonicalize` 解析符号链接并换成磁盘上的实际写法；文件不存在时规范化最近的已存在
+//!   上级目录，其余部分按字面拼接；macOS 上再统一为 NFC；
+//! - `same_file` 除比较规范化路径外还比较 inode，硬链接也视为同一个文件。
+
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::links::normalize_path;
+
+/// 文件的唯一标识（设备号与 inode），只在 Unix 上可用
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct FileId {
+    device: u64,
+    inode: u64,
+}
+
+#[cfg(unix)]
+pub fn file_id(path: &Path) -> Option<FileId> {
+    use std::os::unix::fs::MetadataExt;
+    let metadata = fs::metadata(path).ok()?;
+    Some(FileId {
+        device: metadata.dev(),
+        inode: metadata.ino(),
+    })
+}
+
+#[cfg(not(unix))]
+pub fn file_id(_path: &Path) -> Option<FileId> {
+    None
+}
+
+/// 去掉 Windows `canonicalize` 返回的 `\\?\` 前缀，与用户看到的路径保持一致
+#[cfg(windows)]
+fn strip_verbatim(path: PathBuf) -> PathBuf {
+    let text = path.to_string_lossy();
+    if let Some(rest) = text.strip_prefix(r"\\?\UNC\") {
+        PathBuf::from(format!(r"\\{}", rest))
+    } else if let Some(rest) = text.strip_prefix(r"\\?\") {
+        PathBuf::from(rest)
+    } else {
+        path
+    }
+}
+
+#[cfg(not(windows))]
+fn strip_verbatim(path: PathBuf) -> PathBuf {
+    path
+}
+
+/// macOS 的文件系统不区分 Unicode 形式，统一为 NFC 后再比较
+#[cfg(target_os = "macos")]
+fn normalize_unicode(path: PathBuf) -> PathBuf {
+    use unicode_normalization::{is_nfc, UnicodeNormalization};
+    match path.to_str() {
+        Some(text) if !is_nfc(text) => PathBuf::from(text.nfc().collect::<String>()),
+        _ => path,
+    }
+}
+
+#[cfg(not(target_os = "macos"))]
+fn normalize_unicode(path: PathBuf) -> PathBuf {
+    path
+}
+
+/// 规范化路径：转为绝对路径、解析符号链接并统一写法
+pub fn canonicalize(path: &Path) -> PathBuf {
+    let absolute = if path.is_absolute() {
+        path.to_path_buf()
+    } else {
+        std::env::current_dir()
+            .map(|cwd| cwd.join(path))
+            .unwrap_or_else(|_| path.to_path_buf())
+    };
+
+    // 已删除或尚未创建的文件：规范化最近的已存在上级目录，再拼上其余部分
+    let mut existing = absolute.as_path();
+    let mut rest = Vec::new();
+    let resolved = loop {
+        if let Ok(resolved) = fs::canonicalize(existing) {
+            break Some(resolved);
+        }
+        // 用最后一个组件而不是 `file_name`，`..` 也要保留下来
+        match (existing.parent(), existing.components().next_back()) {
+            (Some(parent), Some(name)) => {
+                rest.push(name.as_os_str().to_os_string());
+                existing = parent;
+            }
+            _ => break None,
+        }
+    };
+    let Some(mut resolved) = resolved.map(strip_verbatim) else {
+        return normalize_unicode(normalize_path(&absolute));
+    };
+    if rest.is_empty() {
+        return normalize_unicode(resolved);
+    }
+    for name in rest.iter().rev() {
+        resolved.push(name);
+    }
+    // 不存在的部分无法解析符号链接，只能按字面处理 `..`
+    normalize_unicode(normalize_path(&resolved))
+}
+
+/// 两个路径是否指向同一个文件
+pub fn same_file(a: &Path, b: &Path) -> bool {
+    if canonicalize(a) == canonicalize(b) {
+        return true;
+    }
+    matches!((file_id(a), file_id(b)), (Some(a), Some(b)) if a == b)
+}
+
+/// 去掉指向同一个文件的重复路径，保留第一次出现的写法和原有顺序
+pub fn dedupe(paths: impl IntoIterator<Item = PathBuf>) -> Vec<PathBuf> {
+    let mut seen_paths = HashSet::new();
+    let mut seen_ids = HashSet::new();
+    let mut unique = Vec::new();
+    for path in paths {
+        if !seen_paths.insert(canonicalize(&path)) {
+            continue;
+        }
+        if file_id(&path).is_some_and(|id| !seen_ids.insert(id)) {
+            continue;
+        }
+        unique.push(path);
+    }
+    unique
+}
+
+/// 把 `canonical_root` 下的规范化路径换回 `root` 的写法（如文件监听报告的路径）
+pub fn rebase(path: &Path, canonical_root: &Path, root: &Path) -> PathBuf {
+    match path.strip_prefix(canonical_root) {
+        Ok(relative) if canonical_root != root => root.join(relative),
+        _ => path.to_path_buf(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir =
+            std::env::temp_dir().join(format!("vividmark-paths-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        fs::canonicalize(dir).unwrap()
+    }
+
+    #[test]
+    fn relative_paths_become_absolute() {
+        let path = canonicalize(Path::new("vividmark-missing-dir/note.md"));
+        assert!(path.is_absolute());
+        assert!(path.ends_with("vividmark-missing-dir/note.md"));
+    }
+
+    #[test]
+    fn missing_file_keeps_its_name() {
+        let dir = temp_dir("missing");
+        assert_eq!(
+            canonicalize(&dir.join("new/note.md")),
+            dir.join("new").join("note.md")
+        );
+    }
+
+    #[test]
+    fn parent_dirs_are_resolved() {
+        let dir = temp_dir("parent");
+        fs::create_dir(dir.join("a")).unwrap();
+        assert_eq!(canonicalize(&dir.join("a/../b.md")), dir.join("b.md"));
+        assert_eq!(
+            canonicalize(&dir.join("missing/../../b.md")),
+            dir.parent().unwrap().join("b.md")
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn symlinks_are_resolved() {
+        let dir = temp_dir("symlink");
+        fs::create_dir(dir.join("real")).unwrap();
+        fs::write(dir.join("real/note.md"), "").unwrap();
+        std::os::unix::fs::symlink(dir.join("real"), dir.join("link")).unwrap();
+
+        assert_eq!(
+            canonicalize(&dir.join("link/note.md")),
+            dir.join("real/note.md")
+        );
+        assert_eq!(
+            canonicalize(&dir.join("link/new.md")),
+            dir.join("real/new.md")
+        );
+        assert!(same_file(
+            &dir.join("link/note.md"),
+            &dir.join("real/note.md")
+        ));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn hard_links_are_the_same_file() {
+        let dir = temp_dir("hardlink");
+        fs::write(dir.join("a.md"), "").unwrap();
+        fs::hard_link(dir.join("a.md"), dir.join("b.md")).unwrap();
+        fs::write(dir.join("c.md"), "").unwrap();
+
+        assert!(same_file(&dir.join("a.md"), &dir.join("b.md")));
+        assert!(!same_file(&dir.join("a.md"), &dir.join("c.md")));
+        assert_eq!(
+            dedupe([dir.join("a.md"), dir.join("c.md"), dir.join("b.md")]),
+            vec![dir.join("a.md"), dir.join("c.md")]
+        );
+    }
+
+    #[test]
+    fn rebase_restores_the_original_root() {
+        let path = Path::new("/private/tmp/notes/a.md");
+        assert_eq!(
+            rebase(path, Path::new("/private/tmp"), Path::new("/tmp")),
+            PathBuf::from("/tmp/notes/a.md")
+        );
+        assert_eq!(
+            rebase(path, Path::new("/other"), Path::new("/tmp")),
+            path.to_path_buf()
+        );
+    }
+}
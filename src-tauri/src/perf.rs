@@ -0,0 +1,353 @@
+//! 性能统计
+//!
+//! 读取、保存、搜索和建立索引在完成时调用 `record`，按操作类别累计次数、耗时和字节数，
+//! 保留最近的耗时样本计算 p50 / p95，`get_perf_metrics` 返回给前端的诊断面板。
+//! 超过阈值的慢操作记录警告日志。
+//!
+//! 诊断大型工作区的性能问题时可以用 `trace_operation` 打开跟踪：之后每次操作都作为一行
+//! JSON（开始时间、类别、对象、耗时、字节数、线程）追加到应用数据目录下 `traces/` 中的文件。
+//! 统计和跟踪都只保存在本机，不会上传。
+
+use std::collections::{HashMap, VecDeque};
+use std::fs::{self, File};
+use std::io::{BufWriter, Write};
+use std::path::PathBuf;
+use std::sync::{Mutex, OnceLock};
+use std::thread;
+use std::time::{Duration, SystemTime};
+
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager};
+
+use crate::error::VividError;
+use crate::fileinfo::iso_time;
+
+/// 每类操作保留的耗时样本数
+const MAX_SAMPLES: usize = 1000;
+
+/// 统计的操作类别
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Operation {
+    Read,
+    Save,
+    Search,
+    /// 重建工作区索引
+    Index,
+}
+
+impl Operation {
+    pub const ALL: [Operation; 4] = [
+        Operation::Read,
+        Operation::Save,
+        Operation::Search,
+        Operation::Index,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Operation::Read => "read",
+            Operation::Save => "save",
+            Operation::Search => "search",
+            Operation::Index => "index",
+        }
+    }
+
+    /// 超过该耗时记录为慢操作
+    fn slow_threshold(self) -> Duration {
+        match self {
+            Operation::Read => Duration::from_millis(200),
+            Operation::Save => Duration::from_millis(500),
+            Operation::Search => Duration::from_millis(300),
+            Operation::Index => Duration::from_secs(10),
+        }
+    }
+}
+
+/// 一类操作的累计数据
+#[derive(Default)]
+struct Stats {
+    count: u64,
+    bytes: u64,
+    total: Duration,
+    max: Duration,
+    slow: u64,
+    samples: VecDeque<Duration>,
+}
+
+impl Stats {
+    fn add(&mut self, elapsed: Duration, bytes: u64) {
+        self.count += 1;
+        self.bytes += bytes;
+        self.total += elapsed;
+        self.max = self.max.max(elapsed);
+        if self.samples.len() == MAX_SAMPLES {
+            self.samples.pop_front();
+        }
+        self.samples.push_back(elapsed);
+    }
+}
+
+/// 正在写入的跟踪文件
+struct Trace {
+    path: PathBuf,
+    writer: BufWriter<File>,
+    spans: u64,
+}
+
+/// 一次操作的跟踪记录，对应跟踪文件中的一行
+#[derive(Debug, Serialize)]
+struct Span<'a> {
+    start: String,
+    operation: Operation,
+    target: &'a str,
+    duration_ms: f64,
+    bytes: u64,
+    thread: String,
+}
+
+struct Registry {
+    since: Mutex<SystemTime>,
+    stats: Mutex<HashMap<Operation, Stats>>,
+    trace: Mutex<Option<Trace>>,
+}
+
+fn registry() -> &'static Registry {
+    static REGISTRY: OnceLock<Registry> = OnceLock::new();
+    REGISTRY.get_or_init(|| Registry {
+        since: Mutex::new(SystemTime::now()),
+        stats: Mutex::new(HashMap::new()),
+        trace: Mutex::new(None),
+    })
+}
+
+fn millis(duration: Duration) -> f64 {
+    duration.as_secs_f64() * 1000.0
+}
+
+/// 记录一次完成的操作：`target` 为文件路径或查询等便于定位的描述，`bytes` 为读写的字节数
+pub fn record(operation: Operation, target: &str, elapsed: Duration, bytes: u64) {
+    let registry = registry();
+    let slow = elapsed >= operation.slow_threshold();
+    if let Ok(mut stats) = registry.stats.lock() {
+        let entry = stats.entry(operation).or_default();
+        entry.add(elapsed, bytes);
+        if slow {
+            entry.slow += 1;
+        }
+    }
+    if slow {
+        log::warn!(
+            "[perf] Slow {}: {} took {:?} ({} bytes)",
+            operation.name(),
+            target,
+            elapsed,
+            bytes
+        );
+    }
+
+    let Ok(mut trace) = registry.trace.lock() else {
+        return;
+    };
+    let Some(current) = trace.as_mut() else {
+        return;
+    };
+    let span = Span {
+        start: iso_time(SystemTime::now() - elapsed),
+        operation,
+        target,
+        duration_ms: millis(elapsed),
+        bytes,
+        thread: thread::current()
+            .name()
+            .map(str::to_string)
+            .unwrap_or_else(|| format!("{:?}", thread::current().id())),
+    };
+    let written = serde_json::to_string(&span)
+        .map_err(|e| e.to_string())
+        .and_then(|line| {
+            writeln!(current.writer, "{}", line)
+                .and_then(|_| current.writer.flush())
+                .map_err(|e| e.to_string())
+        });
+    match written {
+        Ok(()) => current.spans += 1,
+        Err(e) => {
+            log::warn!(
+                "[perf] Failed to write trace {:?}, tracing stopped: {}",
+                current.path,
+                e
+            );
+            *trace = None;
+        }
+    }
+}
+
+/// 按 nearest-rank 计算百分位（毫秒）
+fn percentile(sorted: &[Duration], p: f64) -> f64 {
+    if sorted.is_empty() {
+        return 0.0;
+    }
+    let rank = (p * sorted.len() as f64).ceil() as usize;
+    millis(sorted[rank.clamp(1, sorted.len()) - 1])
+}
+
+/// 一类操作的统计
+#[derive(Debug, Serialize, Deserialize)]
+pub struct OperationMetrics {
+    pub operation: Operation,
+    pub count: u64,
+    pub bytes: u64,
+    pub total_ms: f64,
+    pub mean_ms: f64,
+    /// p50 / p95 按最近的样本计算
+    pub p50_ms: f64,
+    pub p95_ms: f64,
+    pub max_ms: f64,
+    /// 超过慢操作阈值的次数
+    pub slow: u64,
+    pub slow_threshold_ms: f64,
+}
+
+/// 跟踪状态
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TraceStatus {
+    pub enabled: bool,
+    /// 跟踪文件，关闭跟踪后仍返回刚写完的文件
+    pub path: Option<String>,
+    pub spans: u64,
+}
+
+/// 性能统计
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PerfMetrics {
+    /// 开始统计的时间（启动或上次重置）
+    pub since: String,
+    pub operations: Vec<OperationMetrics>,
+    pub trace: TraceStatus,
+}
+
+fn trace_status(trace: Option<&Trace>) -> TraceStatus {
+    TraceStatus {
+        enabled: trace.is_some(),
+        path: trace.map(|t| t.path.to_string_lossy().to_string()),
+        spans: trace.map_or(0, |t| t.spans),
+    }
+}
+
+fn snapshot(reset: bool) -> PerfMetrics {
+    let registry = registry();
+    let mut stats = registry
+        .stats
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+    let operations = Operation::ALL
+        .iter()
+        .map(|&operation| {
+            let entry = stats.get(&operation);
+            let mut sorted: Vec<Duration> = entry
+                .map(|s| s.samples.iter().copied().collect())
+                .unwrap_or_default();
+            sorted.sort();
+            let count = entry.map_or(0, |s| s.count);
+            let total = entry.map_or(Duration::ZERO, |s| s.total);
+            OperationMetrics {
+                operation,
+                count,
+                bytes: entry.map_or(0, |s| s.bytes),
+                total_ms: millis(total),
+                mean_ms: if count > 0 {
+                    millis(total) / count as f64
+                } else {
+                    0.0
+                },
+                p50_ms: percentile(&sorted, 0.5),
+                p95_ms: percentile(&sorted, 0.95),
+                max_ms: entry.map_or(0.0, |s| millis(s.max)),
+                slow: entry.map_or(0, |s| s.slow),
+                slow_threshold_ms: millis(operation.slow_threshold()),
+            }
+        })
+        .collect();
+
+    let mut since = registry
+        .since
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+    let trace = registry
+        .trace
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+    let metrics = PerfMetrics {
+        since: iso_time(*since),
+        operations,
+        trace: trace_status(trace.as_ref()),
+    };
+    if reset {
+        stats.clear();
+        *since = SystemTime::now();
+    }
+    metrics
+}
+
+// 获取读取、保存、搜索和索引的性能统计，`reset` 为 true 时返回后清零
+#[tauri::command]
+pub fn get_perf_metrics(reset: Option<bool>) -> PerfMetrics {
+    let reset = reset.unwrap_or(false);
+    let metrics = snapshot(reset);
+    log::debug!(
+        "[get_perf_metrics] {} operation(s) since {}{}",
+        metrics.operations.iter().map(|m| m.count).sum::<u64>(),
+        metrics.since,
+        if reset { ", reset" } else { "" }
+    );
+    metrics
+}
+
+// 打开或关闭操作跟踪，打开时在应用数据目录的 traces/ 下新建跟踪文件
+#[tauri::command]
+pub fn trace_operation(app: AppHandle, enabled: bool) -> Result<TraceStatus, VividError> {
+    let mut trace = registry()
+        .trace
+        .lock()
+        .map_err(|e| format!("Trace lock poisoned: {}", e))?;
+    if !enabled {
+        let status = trace_status(trace.as_ref());
+        if let Some(current) = trace.take() {
+            log::info!(
+                "[trace_operation] ✓ Tracing stopped: {} span(s) in {:?}",
+                current.spans,
+                current.path
+            );
+        }
+        return Ok(TraceStatus {
+            enabled: false,
+            ..status
+        });
+    }
+    if trace.is_some() {
+        return Ok(trace_status(trace.as_ref()));
+    }
+
+    let dir = app
+        .path()
+        .app_data_dir()
+        .map_err(|e| format!("Failed to resolve app data directory: {}", e))?
+        .join("traces");
+    fs::create_dir_all(&dir).map_err(|e| VividError::io("Failed to create directory", &dir, &e))?;
+    let stamp = DateTime::<Local>::from(SystemTime::now()).format("%Y%m%d-%H%M%S");
+    let path = dir.join(format!("trace-{}.jsonl", stamp));
+    let file = File::options()
+        .create(true)
+        .append(true)
+        .open(&path)
+        .map_err(|e| VividError::io("Failed to create file", &path, &e))?;
+    log::info!("[trace_operation] ✓ Tracing to {:?}", path);
+    *trace = Some(Trace {
+        path,
+        writer: BufWriter::new(file),
+        spans: 0,
+    });
+    Ok(trace_status(trace.as_ref()))
+}
@@ -0,0 +1,575 @@
+//! 用户插件
+//!
+//! 插件是应用数据目录 `plugins/<目录>/` 下的 WebAssembly 模块，由 `plugin.json` 描述：
+//!
+//! ```json
+//! {
+//!   "id": "word-goal",
+//!   "name": "Word Goal",
+//!   "version": "1.0.0",
+//!   "description": "...",
+//!   "main": "plugin.wasm",
+//!   "hooks": ["on_save", "on_render"],
+//!   "commands": [{ "id": "progress", "title": "Show word goal progress" }]
+//! }
+//! ```
+//!
+//! 插件在 wasmi 解释器中运行，只能导入 `vividmark.log(ptr, len)`，不能访问文件、网络或环境变量；
+//! 每次调用使用新的实例，限制执行步数（fuel）和内存。模块需要导出：
+//!
+//! - `memory` 和 `alloc(len: i32) -> i32`，宿主通过它写入输入；
+//! - 声明的钩子 `on_save` / `on_render`，以及声明了命令时的 `on_command`，
+//!   签名都是 `(ptr: i32, len: i32) -> i64`：输入为 UTF-8 JSON，返回 `(ptr << 32) | len`
+//!   指向输出的 JSON，返回 0 表示没有输出。
+//!
+//! 钩子的输入输出：
+//!
+//! - `on_save`：保存成功后调用，输入 `{ "path", "content" }`，输出被忽略；加密文档不调用；
+//! - `on_render`：后端渲染预览前调用，输入 `{ "content" }`，输出 `{ "content" }` 时替换要渲染的 Markdown；
+//! - `on_command`：命令面板执行插件命令时由 `invoke_plugin` 调用，输入输出由插件自行约定，
+//!   一般为 `{ "command": "<命令 id>", ... }`。
+//!
+//! 启用的插件 id 保存在 `plugins/enabled.json`，插件默认不启用。
+
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::{Instant, SystemTime};
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tauri::{AppHandle, Manager, State};
+use wasmi::{
+    Caller, Config, Engine, Extern, Linker, Module, Store, StoreLimits, StoreLimitsBuilder,
+};
+
+use crate::error::VividError;
+use crate::storage;
+
+const MANIFEST_FILE: &str = "plugin.json";
+const ENABLED_FILE: &str = "enabled.json";
+/// 单次调用的执行步数上限，防止死循环卡住后台线程
+const MAX_FUEL: u64 = 500_000_000;
+/// 插件线性内存上限
+const MAX_MEMORY_BYTES: usize = 64 * 1024 * 1024;
+/// 插件模块文件大小上限
+const MAX_MODULE_BYTES: u64 = 32 * 1024 * 1024;
+/// 插件输出大小上限
+const MAX_OUTPUT_BYTES: usize = 32 * 1024 * 1024;
+/// 单条插件日志的长度上限
+const MAX_LOG_BYTES: usize = 4096;
+
+/// 生命周期钩子
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PluginHook {
+    OnSave,
+    OnRender,
+}
+
+impl PluginHook {
+    fn export_name(self) -> &'static str {
+        match self {
+            PluginHook::OnSave => "on_save",
+            PluginHook::OnRender => "on_render",
+        }
+    }
+}
+
+/// 插件在命令面板中注册的命令
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PluginCommand {
+    pub id: String,
+    pub title: String,
+}
+
+/// `plugin.json`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PluginManifest {
+    pub id: String,
+    pub name: String,
+    #[serde(default)]
+    pub version: String,
+    #[serde(default)]
+    pub description: String,
+    /// 模块文件，相对插件目录，默认 `plugin.wasm`
+    #[serde(default = "default_main")]
+    pub main: String,
+    #[serde(default)]
+    pub hooks: Vec<PluginHook>,
+    #[serde(default)]
+    pub commands: Vec<PluginCommand>,
+}
+
+fn default_main() -> String {
+    "plugin.wasm".to_string()
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PluginInfo {
+    /// manifest 无效时为目录名
+    pub id: String,
+    pub name: String,
+    pub version: String,
+    pub description: String,
+    pub hooks: Vec<PluginHook>,
+    pub commands: Vec<PluginCommand>,
+    pub enabled: bool,
+    /// 插件目录
+    pub path: String,
+    /// manifest 或模块无效的原因，此时插件无法启用
+    pub error: Option<String>,
+}
+
+/// 已安装的插件
+struct Plugin {
+    manifest: PluginManifest,
+    dir: PathBuf,
+    /// 扫描时模块文件的修改时间，变化后重新编译
+    modified: Option<SystemTime>,
+    /// 编译后的模块，首次调用时加载
+    module: Arc<OnceLock<Result<Module, String>>>,
+}
+
+#[derive(Default, Serialize, Deserialize)]
+struct EnabledPlugins {
+    enabled: Vec<String>,
+}
+
+/// 单次调用的运行状态
+struct RuntimeState {
+    plugin: String,
+    limits: StoreLimits,
+}
+
+/// 插件目录和已启用的插件
+pub struct PluginHost {
+    dir: PathBuf,
+    engine: Engine,
+    plugins: Mutex<Vec<Arc<Plugin>>>,
+    enabled: Mutex<HashSet<String>>,
+}
+
+fn valid_id(id: &str) -> bool {
+    !id.is_empty()
+        && id
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'))
+}
+
+/// 解析并检查插件目录中的 manifest
+fn read_manifest(dir: &Path) -> Result<PluginManifest, String> {
+    let text = fs::read_to_string(dir.join(MANIFEST_FILE))
+        .map_err(|e| format!("Failed to read {}: {}", MANIFEST_FILE, e))?;
+    let manifest: PluginManifest =
+        serde_json::from_str(&text).map_err(|e| format!("Invalid {}: {}", MANIFEST_FILE, e))?;
+    if !valid_id(&manifest.id) {
+        return Err(format!("Invalid plugin id: {:?}", manifest.id));
+    }
+    let main = Path::new(&manifest.main);
+    if !main
+        .components()
+        .all(|component| matches!(component, Component::Normal(_)))
+    {
+        return Err(format!(
+            "Module path must stay inside the plugin folder: {}",
+            manifest.main
+        ));
+    }
+    if !dir.join(main).is_file() {
+        return Err(format!("Module not found: {}", manifest.main));
+    }
+    Ok(manifest)
+}
+
+/// 输出指针和长度打包在一个 i64 中
+fn unpack(packed: i64) -> (usize, usize) {
+    let packed = packed as u64;
+    ((packed >> 32) as usize, (packed & 0xffff_ffff) as usize)
+}
+
+impl Plugin {
+    fn module(&self, engine: &Engine) -> Result<&Module, String> {
+        self.module
+            .get_or_init(|| {
+                let path = self.dir.join(&self.manifest.main);
+                let size = fs::metadata(&path).map_err(|e| e.to_string())?.len();
+                if size > MAX_MODULE_BYTES {
+                    return Err(format!(
+                        "Module is larger than {} MB",
+                        MAX_MODULE_BYTES / 1024 / 1024
+                    ));
+                }
+                let bytes = fs::read(&path).map_err(|e| e.to_string())?;
+                Module::new(engine, &bytes[..]).map_err(|e| format!("Invalid module: {}", e))
+            })
+            .as_ref()
+            .map_err(Clone::clone)
+    }
+
+    fn info(&self, enabled: bool) -> PluginInfo {
+        let manifest = &self.manifest;
+        PluginInfo {
+            id: manifest.id.clone(),
+            name: manifest.name.clone(),
+            version: manifest.version.clone(),
+            description: manifest.description.clone(),
+            hooks: manifest.hooks.clone(),
+            commands: manifest.commands.clone(),
+            enabled,
+            path: self.dir.to_string_lossy().to_string(),
+            error: None,
+        }
+    }
+}
+
+/// 插件的 `vividmark.log`：读取插件内存中的一段文字写入日志
+fn plugin_log(caller: Caller<'_, RuntimeState>, ptr: i32, len: i32) {
+    let Some(memory) = caller.get_export("memory").and_then(Extern::into_memory) else {
+        return;
+    };
+    let data = memory.data(&caller);
+    let start = ptr as u32 as usize;
+    let end = start.saturating_add((len as u32 as usize).min(MAX_LOG_BYTES));
+    if let Some(bytes) = data.get(start..end) {
+        log::info!(
+            "[plugin:{}] {}",
+            caller.data().plugin,
+            String::from_utf8_lossy(bytes)
+        );
+    }
+}
+
+impl PluginHost {
+    pub fn new(dir: PathBuf) -> Self {
+        let mut config = Config::default();
+        config.consume_fuel(true);
+        let enabled: EnabledPlugins = storage::load_json(&dir.join(ENABLED_FILE));
+        PluginHost {
+            dir,
+            engine: Engine::new(&config),
+            plugins: Mutex::new(Vec::new()),
+            enabled: Mutex::new(enabled.enabled.into_iter().collect()),
+        }
+    }
+
+    /// 重新扫描插件目录，返回所有插件（包括无效的）的信息
+    fn rescan(&self) -> Vec<PluginInfo> {
+        let enabled = self.enabled.lock().map(|e| e.clone()).unwrap_or_default();
+        let mut dirs: Vec<PathBuf> = fs::read_dir(&self.dir)
+            .map(|entries| {
+                entries
+                    .flatten()
+                    .map(|entry| entry.path())
+                    .filter(|path| path.join(MANIFEST_FILE).is_file())
+                    .collect()
+            })
+            .unwrap_or_default();
+        dirs.sort();
+
+        let previous = self.plugins.lock().map(|p| p.clone()).unwrap_or_default();
+        let mut plugins: Vec<Arc<Plugin>> = Vec::new();
+        let mut infos = Vec::new();
+        for dir in dirs {
+            let manifest = read_manifest(&dir).and_then(|manifest| {
+                if plugins.iter().any(|p| p.manifest.id == manifest.id) {
+                    Err(format!("Duplicate plugin id: {}", manifest.id))
+                } else {
+                    Ok(manifest)
+                }
+            });
+            let manifest = match manifest {
+                Ok(manifest) => manifest,
+                Err(error) => {
+                    log::warn!("[plugins] Ignoring {:?}: {}", dir, error);
+                    let name = dir
+                        .file_name()
+                        .map(|n| n.to_string_lossy().to_string())
+                        .unwrap_or_default();
+                    infos.push(PluginInfo {
+                        id: name.clone(),
+                        name,
+                        version: String::new(),
+                        description: String::new(),
+                        hooks: Vec::new(),
+                        commands: Vec::new(),
+                        enabled: false,
+                        path: dir.to_string_lossy().to_string(),
+                        error: Some(error),
+                    });
+                    continue;
+                }
+            };
+            // 模块文件没有变化时沿用已编译的结果
+            let modified = fs::metadata(dir.join(&manifest.main))
+                .and_then(|m| m.modified())
+                .ok();
+            let module = previous
+                .iter()
+                .find(|p| {
+                    p.dir == dir
+                        && p.manifest.main == manifest.main
+                        && p.modified.is_some()
+                        && p.modified == modified
+                })
+                .map(|p| p.module.clone())
+                .unwrap_or_default();
+            let plugin = Arc::new(Plugin {
+                manifest,
+                dir,
+                modified,
+                module,
+            });
+            let mut info = plugin.info(enabled.contains(&plugin.manifest.id));
+            if let Some(Err(error)) = plugin.module.get() {
+                info.error = Some(error.clone());
+            }
+            infos.push(info);
+            plugins.push(plugin);
+        }
+        if let Ok(mut current) = self.plugins.lock() {
+            *current = plugins;
+        }
+        infos
+    }
+
+    fn find(&self, id: &str) -> Option<Arc<Plugin>> {
+        let find = |plugins: &[Arc<Plugin>]| plugins.iter().find(|p| p.manifest.id == id).cloned();
+        if let Some(plugin) = self.plugins.lock().ok().and_then(|p| find(&p)) {
+            return Some(plugin);
+        }
+        self.rescan();
+        self.plugins.lock().ok().and_then(|p| find(&p))
+    }
+
+    fn is_enabled(&self, id: &str) -> bool {
+        self.enabled
+            .lock()
+            .is_ok_and(|enabled| enabled.contains(id))
+    }
+
+    fn set_enabled(&self, id: &str, enabled: bool) -> Result<(), String> {
+        let mut current = self.enabled.lock().map_err(|e| e.to_string())?;
+        if enabled {
+            current.insert(id.to_string());
+        } else {
+            current.remove(id);
+        }
+        let mut ids: Vec<String> = current.iter().cloned().collect();
+        ids.sort();
+        storage::save_json(
+            &self.dir.join(ENABLED_FILE),
+            &EnabledPlugins { enabled: ids },
+        )
+    }
+
+    /// 已启用、声明了该钩子的插件
+    fn with_hook(&self, hook: PluginHook) -> Vec<Arc<Plugin>> {
+        let enabled = self.enabled.lock().map(|e| e.clone()).unwrap_or_default();
+        if enabled.is_empty() {
+            return Vec::new();
+        }
+        let loaded = self.plugins.lock().map(|p| !p.is_empty()).unwrap_or(false);
+        if !loaded {
+            self.rescan();
+        }
+        self.plugins
+            .lock()
+            .map(|plugins| {
+                plugins
+                    .iter()
+                    .filter(|p| {
+                        enabled.contains(&p.manifest.id) && p.manifest.hooks.contains(&hook)
+                    })
+                    .cloned()
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// 在新的沙箱实例中调用插件的导出函数
+    fn call(&self, plugin: &Plugin, export: &str, input: &Value) -> Result<Option<Value>, String> {
+        let module = plugin.module(&self.engine)?;
+        let input = serde_json::to_vec(input).map_err(|e| e.to_string())?;
+        let input_len = i32::try_from(input.len()).map_err(|_| "Input is too large")?;
+
+        let mut store = Store::new(
+            &self.engine,
+            RuntimeState {
+                plugin: plugin.manifest.id.clone(),
+                limits: StoreLimitsBuilder::new()
+                    .memory_size(MAX_MEMORY_BYTES)
+                    .instances(1)
+                    .build(),
+            },
+        );
+        store.limiter(|state| &mut state.limits);
+        store.set_fuel(MAX_FUEL).map_err(|e| e.to_string())?;
+
+        let mut linker = Linker::<RuntimeState>::new(&self.engine);
+        linker
+            .func_wrap("vividmark", "log", plugin_log)
+            .map_err(|e| e.to_string())?;
+        let instance = linker
+            .instantiate(&mut store, module)
+            .and_then(|pre| pre.start(&mut store))
+            .map_err(|e| format!("Failed to instantiate plugin: {}", e))?;
+
+        let memory = instance
+            .get_memory(&store, "memory")
+            .ok_or("Plugin does not export `memory`")?;
+        let alloc = instance
+            .get_typed_func::<i32, i32>(&store, "alloc")
+            .map_err(|e| format!("Plugin does not export `alloc`: {}", e))?;
+        let function = instance
+            .get_typed_func::<(i32, i32), i64>(&store, export)
+            .map_err(|e| format!("Plugin does not export `{}`: {}", export, e))?;
+
+        let ptr = alloc
+            .call(&mut store, input_len)
+            .map_err(|e| format!("`alloc` failed: {}", e))?;
+        memory
+            .write(&mut store, ptr as u32 as usize, &input)
+            .map_err(|e| format!("Failed to pass input to plugin: {}", e))?;
+        let packed = function
+            .call(&mut store, (ptr, input_len))
+            .map_err(|e| format!("`{}` failed: {}", export, e))?;
+        if packed == 0 {
+            return Ok(None);
+        }
+
+        let (out_ptr, out_len) = unpack(packed);
+        if out_len > MAX_OUTPUT_BYTES {
+            return Err(format!(
+                "Plugin output is larger than {} MB",
+                MAX_OUTPUT_BYTES / 1024 / 1024
+            ));
+        }
+        let output = memory
+            .data(&store)
+            .get(out_ptr..out_ptr.saturating_add(out_len))
+            .ok_or("Plugin returned an output outside its memory")?;
+        if output.is_empty() {
+            return Ok(None);
+        }
+        serde_json::from_slice(output)
+            .map(Some)
+            .map_err(|e| format!("Plugin returned invalid JSON: {}", e))
+    }
+
+    /// 依次调用启用的 `on_render` 插件处理要渲染的 Markdown，插件出错时跳过
+    pub fn render_hooks(&self, mut content: String) -> String {
+        for plugin in self.with_hook(PluginHook::OnRender) {
+            let input = serde_json::json!({ "content": content });
+            match self.call(&plugin, PluginHook::OnRender.export_name(), &input) {
+                Ok(Some(output)) => {
+                    if let Some(updated) = output.get("content").and_then(Value::as_str) {
+                        content = updated.to_string();
+                    }
+                }
+                Ok(None) => {}
+                Err(e) => log::warn!("[plugins] {} on_render: {}", plugin.manifest.id, e),
+            }
+        }
+        content
+    }
+
+    /// 保存成功后在后台通知启用的 `on_save` 插件
+    pub fn after_save(app: &AppHandle, path: &Path, content: &str) {
+        let host = app.state::<PluginHost>();
+        let plugins = host.with_hook(PluginHook::OnSave);
+        if plugins.is_empty() {
+            return;
+        }
+        let app = app.clone();
+        let input = serde_json::json!({
+            "path": path.to_string_lossy(),
+            "content": content,
+        });
+        tauri::async_runtime::spawn_blocking(move || {
+            let host = app.state::<PluginHost>();
+            for plugin in plugins {
+                if let Err(e) = host.call(&plugin, PluginHook::OnSave.export_name(), &input) {
+                    log::warn!("[plugins] {} on_save: {}", plugin.manifest.id, e);
+                }
+            }
+        });
+    }
+}
+
+// 列出插件目录中的插件，插件目录可在此之后安装或更新
+#[tauri::command]
+pub fn list_plugins(host: State<'_, PluginHost>) -> Vec<PluginInfo> {
+    let plugins = host.rescan();
+    log::info!(
+        "[list_plugins] {} plugin(s) in {:?}",
+        plugins.len(),
+        host.dir
+    );
+    plugins
+}
+
+// 启用或停用插件（`enabled` 默认为 true），启用前检查模块能否加载
+#[tauri::command]
+pub fn enable_plugin(
+    host: State<'_, PluginHost>,
+    id: String,
+    enabled: Option<bool>,
+) -> Result<PluginInfo, VividError> {
+    let enabled = enabled.unwrap_or(true);
+    let plugin = host.find(&id).ok_or_else(|| {
+        log::warn!("[enable_plugin] Plugin not found: {}", id);
+        VividError::invalid_input(format!("Plugin not found: {}", id))
+    })?;
+    if enabled {
+        plugin.module(&host.engine).map_err(|e| {
+            log::error!("[enable_plugin] {}: {}", id, e);
+            VividError::invalid_input(format!("Plugin {} cannot be loaded: {}", id, e))
+        })?;
+    }
+    host.set_enabled(&id, enabled)?;
+    log::info!(
+        "[enable_plugin] ✓ {} {}",
+        if enabled { "Enabled" } else { "Disabled" },
+        id
+    );
+    Ok(plugin.info(enabled))
+}
+
+// 执行插件命令：把 `payload` 交给插件的 `on_command`，返回插件的输出
+#[tauri::command]
+pub async fn invoke_plugin(
+    app: AppHandle,
+    id: String,
+    payload: Option<Value>,
+) -> Result<Value, VividError> {
+    let start = Instant::now();
+    let host = app.state::<PluginHost>();
+    let plugin = host
+        .find(&id)
+        .ok_or_else(|| VividError::invalid_input(format!("Plugin not found: {}", id)))?;
+    if !host.is_enabled(&id) {
+        return Err(VividError::invalid_input(format!(
+            "Plugin is not enabled: {}",
+            id
+        )));
+    }
+    log::info!("[invoke_plugin] {}", id);
+
+    let payload = payload.unwrap_or(Value::Null);
+    let handle = app.clone();
+    let output = tauri::async_runtime::spawn_blocking(move || {
+        handle
+            .state::<PluginHost>()
+            .call(&plugin, "on_command", &payload)
+    })
+    .await
+    .map_err(|e| format!("Plugin task failed: {}", e))?
+    .map_err(|e| {
+        log::error!("[invoke_plugin] {}: {}", id, e);
+        VividError::from(format!("Plugin {} failed: {}", id, e))
+    })?;
+    log::info!("[invoke_plugin] ✓ Success: {} in {:?}", id, start.elapsed());
+    Ok(output.unwrap_or(Value::Null))
+}
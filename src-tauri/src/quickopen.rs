@@ -0,0 +1,369 @@
+//! 快速打开（Ctrl+P）
+//!
+//! 对工作区文件的相对路径做模糊匹配，打分方式参照 fzf：连续匹配、单词边界和路径分隔符后的匹配得分更高，
+//! 匹配之间的间隔扣分，落在文件名部分的匹配额外加分。当前工作区的文件列表由文件监听维护，
+//! 不用每次按键都重新扫描目录；其它目录的扫描结果短暂缓存。
+
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager};
+
+use crate::access::{self, AccessKind};
+use crate::error::VividError;
+use crate::workspace::{self, FileIndex, Workspace};
+
+const DEFAULT_LIMIT: usize = 50;
+const MAX_LIMIT: usize = 1000;
+/// 非工作区目录的文件列表缓存时间
+const SCAN_CACHE_TTL: Duration = Duration::from_secs(10);
+
+const SCORE_MATCH: i32 = 16;
+const GAP_START: i32 = 3;
+const GAP_EXTENSION: i32 = 1;
+/// 空白之后
+const BONUS_BOUNDARY_WHITE: i32 = 10;
+/// `/` 等分隔符之后
+const BONUS_BOUNDARY_DELIMITER: i32 = 9;
+/// 其它非单词字符之后
+const BONUS_BOUNDARY: i32 = 8;
+/// 驼峰或数字开头
+const BONUS_CAMEL: i32 = 7;
+const BONUS_CONSECUTIVE: i32 = 4;
+/// 查询第一个字符的边界加分倍数
+const FIRST_CHAR_MULTIPLIER: i32 = 2;
+/// 每个落在文件名中的匹配字符
+const BONUS_FILE_NAME: i32 = 2;
+
+/// 一个匹配结果
+#[derive(Debug, Serialize, Deserialize)]
+pub struct QuickOpenMatch {
+    pub path: String,
+    /// 相对根目录的路径（`/` 分隔）
+    pub relative_path: String,
+    pub score: i32,
+    /// 匹配字符在 `relative_path` 中的 UTF-16 偏移
+    pub positions: Vec<usize>,
+}
+
+/// 快速打开使用的文件列表
+#[derive(Default)]
+pub struct QuickOpenCache {
+    /// 当前工作区的文件，随文件监听更新
+    files: Mutex<BTreeSet<PathBuf>>,
+    /// 最近一次扫描的其它目录
+    scanned: Mutex<Option<(PathBuf, Instant, Vec<PathBuf>)>>,
+}
+
+impl FileIndex for QuickOpenCache {
+    fn rebuild(&self, paths: &[PathBuf]) {
+        if let Ok(mut files) = self.files.lock() {
+            *files = paths.iter().cloned().collect();
+        }
+    }
+
+    fn refresh(&self, path: &Path) {
+        if let Ok(mut files) = self.files.lock() {
+            if path.is_file() {
+                files.insert(path.to_path_buf());
+            } else {
+                files.remove(path);
+            }
+        }
+    }
+
+    fn remove_under(&self, path: &Path) {
+        if let Ok(mut files) = self.files.lock() {
+            files.retain(|file| !file.starts_with(path));
+        }
+    }
+
+    fn clear(&self) {
+        if let Ok(mut files) = self.files.lock() {
+            files.clear();
+        }
+    }
+}
+
+impl QuickOpenCache {
+    /// `root` 下的文件：当前工作区直接使用监听维护的列表，其它目录扫描后缓存
+    fn files(&self, root: &Path, workspace_root: Option<&Path>) -> Vec<PathBuf> {
+        if workspace_root == Some(root) {
+            if let Ok(files) = self.files.lock() {
+                return files.iter().cloned().collect();
+            }
+        }
+        if let Ok(scanned) = self.scanned.lock() {
+            if let Some((dir, at, files)) = scanned.as_ref() {
+                if dir == root && at.elapsed() < SCAN_CACHE_TTL {
+                    return files.clone();
+                }
+            }
+        }
+        let files = workspace::markdown_files(root);
+        if let Ok(mut scanned) = self.scanned.lock() {
+            *scanned = Some((root.to_path_buf(), Instant::now(), files.clone()));
+        }
+        files
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum CharClass {
+    White,
+    Delimiter,
+    NonWord,
+    Lower,
+    Upper,
+    Number,
+}
+
+fn char_class(c: char) -> CharClass {
+    if c.is_whitespace() {
+        CharClass::White
+    } else if matches!(c, '/' | '\\' | ',' | ':' | ';' | '|') {
+        CharClass::Delimiter
+    } else if c.is_lowercase() {
+        CharClass::Lower
+    } else if c.is_uppercase() {
+        CharClass::Upper
+    } else if c.is_numeric() {
+        CharClass::Number
+    } else if c.is_alphabetic() {
+        // 没有大小写的文字（如汉字）
+        CharClass::Lower
+    } else {
+        CharClass::NonWord
+    }
+}
+
+/// 字符位于 `prev` 之后时的边界加分
+fn bonus_for(prev: CharClass, class: CharClass) -> i32 {
+    let word = matches!(
+        class,
+        CharClass::Lower | CharClass::Upper | CharClass::Number
+    );
+    match prev {
+        CharClass::White if word => BONUS_BOUNDARY_WHITE,
+        CharClass::Delimiter if word => BONUS_BOUNDARY_DELIMITER,
+        CharClass::NonWord if word => BONUS_BOUNDARY,
+        CharClass::Lower if class == CharClass::Upper => BONUS_CAMEL,
+        p if p != CharClass::Number && class == CharClass::Number => BONUS_CAMEL,
+        _ if class == CharClass::NonWord || class == CharClass::Delimiter => BONUS_BOUNDARY,
+        _ => 0,
+    }
+}
+
+/// 可复用的打分缓冲区
+#[derive(Default)]
+struct Matcher {
+    /// 第 i 个查询字符恰好匹配在第 j 个字符时的最高分
+    matched: Vec<i32>,
+    /// 第 i 个查询字符已匹配在 j 之前、第 j 个字符属于间隔时的最高分
+    gap: Vec<i32>,
+    /// 匹配在 (i, j) 时所得的边界加分，连续匹配沿用片段开头的加分
+    chunk_bonus: Vec<i32>,
+    /// (i, j) 的最高分是否来自与上一个查询字符的连续匹配
+    consecutive: Vec<bool>,
+}
+
+const NONE: i32 = i32::MIN / 2;
+
+impl Matcher {
+    /// 对文本打分，返回分数和匹配字符的下标；不是子序列时返回 `None`
+    fn score(
+        &mut self,
+        text: &[char],
+        pattern: &[char],
+        case_sensitive: bool,
+    ) -> Option<(i32, Vec<usize>)> {
+        let (n, m) = (text.len(), pattern.len());
+        let fold = |c: char| {
+            if case_sensitive {
+                c
+            } else {
+                c.to_lowercase().next().unwrap_or(c)
+            }
+        };
+        let folded: Vec<char> = text.iter().map(|&c| fold(c)).collect();
+
+        // 先确认是子序列，大部分候选在这里就被排除
+        let mut rest = pattern.iter().peekable();
+        for &c in &folded {
+            if rest.peek() == Some(&&c) {
+                rest.next();
+            }
+        }
+        if rest.peek().is_some() {
+            return None;
+        }
+
+        let mut bonus = Vec::with_capacity(n);
+        let mut prev = CharClass::Delimiter;
+        for &c in text {
+            let class = char_class(c);
+            bonus.push(bonus_for(prev, class));
+            prev = class;
+        }
+
+        for buffer in [&mut self.matched, &mut self.gap, &mut self.chunk_bonus] {
+            buffer.clear();
+            buffer.resize(n * m, NONE);
+        }
+        self.consecutive.clear();
+        self.consecutive.resize(n * m, false);
+        let at = |i: usize, j: usize| i * n + j;
+
+        for (i, &p) in pattern.iter().enumerate() {
+            for j in 0..n {
+                if folded[j] == p {
+                    if i == 0 {
+                        self.matched[at(i, j)] = SCORE_MATCH + bonus[j] * FIRST_CHAR_MULTIPLIER;
+                        self.chunk_bonus[at(i, j)] = bonus[j];
+                    } else if j > 0 {
+                        // 与 fzf 相同：连续匹配的每个字符都获得片段开头的边界加分
+                        let chunk = self.chunk_bonus[at(i - 1, j - 1)]
+                            .max(BONUS_CONSECUTIVE)
+                            .max(bonus[j]);
+                        let consecutive = self.matched[at(i - 1, j - 1)] + SCORE_MATCH + chunk;
+                        let after_gap = self.gap[at(i - 1, j - 1)] + SCORE_MATCH + bonus[j];
+                        if consecutive >= after_gap {
+                            self.matched[at(i, j)] = consecutive.max(NONE);
+                            self.chunk_bonus[at(i, j)] = chunk;
+                            self.consecutive[at(i, j)] = true;
+                        } else {
+                            self.matched[at(i, j)] = after_gap.max(NONE);
+                            self.chunk_bonus[at(i, j)] = bonus[j];
+                        }
+                    }
+                }
+                if j > 0 {
+                    let start = self.matched[at(i, j - 1)] - GAP_START;
+                    let extend = self.gap[at(i, j - 1)] - GAP_EXTENSION;
+                    self.gap[at(i, j)] = start.max(extend).max(NONE);
+                }
+            }
+        }
+
+        // 取最后一个查询字符得分最高的位置，再向前回溯
+        let (mut j, best) = (0..n)
+            .map(|j| (j, self.matched[at(m - 1, j)]))
+            .max_by_key(|&(j, score)| (score, std::cmp::Reverse(j)))?;
+        if best <= NONE / 2 {
+            return None;
+        }
+        let mut positions = vec![j];
+        for i in (1..m).rev() {
+            if self.consecutive[at(i, j)] {
+                j -= 1;
+            } else {
+                // 沿间隔向前找到上一个匹配位置
+                let mut k = j - 1;
+                while k > 0 && self.gap[at(i - 1, k)] != self.matched[at(i - 1, k - 1)] - GAP_START
+                {
+                    k -= 1;
+                }
+                j = k.saturating_sub(1);
+            }
+            positions.push(j);
+        }
+        positions.reverse();
+        Some((best, positions))
+    }
+}
+
+/// 在文件列表中模糊匹配，按分数从高到低排序
+fn search(root: &Path, files: &[PathBuf], query: &str, limit: usize) -> Vec<QuickOpenMatch> {
+    let pattern: Vec<char> = query.chars().filter(|c| !c.is_whitespace()).collect();
+    // 智能大小写：查询中有大写字母时区分大小写
+    let case_sensitive = pattern.iter().any(|c| c.is_uppercase());
+    let pattern: Vec<char> = if case_sensitive {
+        pattern
+    } else {
+        pattern
+            .iter()
+            .map(|c| c.to_lowercase().next().unwrap_or(*c))
+            .collect()
+    };
+
+    let mut matcher = Matcher::default();
+    let mut results = Vec::new();
+    for file in files {
+        let relative = file
+            .strip_prefix(root)
+            .unwrap_or(file)
+            .to_string_lossy()
+            .replace('\\', "/");
+        let chars: Vec<char> = relative.chars().collect();
+        let (score, positions) = if pattern.is_empty() {
+            (0, Vec::new())
+        } else {
+            let Some((score, positions)) = matcher.score(&chars, &pattern, case_sensitive) else {
+                continue;
+            };
+            let name_start = chars.iter().rposition(|&c| c == '/').map_or(0, |i| i + 1);
+            let in_name = positions.iter().filter(|&&p| p >= name_start).count() as i32;
+            (score + in_name * BONUS_FILE_NAME, positions)
+        };
+
+        let mut utf16 = Vec::with_capacity(chars.len());
+        let mut offset = 0;
+        for c in &chars {
+            utf16.push(offset);
+            offset += c.len_utf16();
+        }
+        results.push(QuickOpenMatch {
+            path: file.to_string_lossy().to_string(),
+            positions: positions.iter().map(|&p| utf16[p]).collect(),
+            score,
+            relative_path: relative,
+        });
+    }
+
+    results.sort_by(|a, b| {
+        b.score
+            .cmp(&a.score)
+            .then(a.relative_path.len().cmp(&b.relative_path.len()))
+            .then_with(|| a.relative_path.cmp(&b.relative_path))
+    });
+    results.truncate(limit);
+    results
+}
+
+// 按模糊匹配查找 `root` 下的文件
+#[tauri::command]
+pub async fn quick_open(
+    app: AppHandle,
+    root: String,
+    query: String,
+    limit: Option<usize>,
+) -> Result<Vec<QuickOpenMatch>, VividError> {
+    let start = Instant::now();
+    let root = PathBuf::from(root);
+    access::ensure_access(&app, &root, AccessKind::Read)?;
+    if !root.is_dir() {
+        return Err(VividError::dir_not_found(&root));
+    }
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).clamp(1, MAX_LIMIT);
+
+    let (results, total) = tauri::async_runtime::spawn_blocking(move || {
+        let workspace_root = app.state::<Workspace>().root();
+        let files = app
+            .state::<QuickOpenCache>()
+            .files(&root, workspace_root.as_deref());
+        (search(&root, &files, &query, limit), files.len())
+    })
+    .await
+    .map_err(|e| format!("Quick open task failed: {}", e))?;
+
+    log::debug!(
+        "[quick_open] {} of {} file(s) in {:?}",
+        results.len(),
+        total,
+        start.elapsed()
+    );
+    Ok(results)
+}
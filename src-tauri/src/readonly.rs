@@ -0,0 +1,105 @@
+//! 只读与被占用的文件
+//!
+//! 打开文件时在 `FileInfo.read_only` 中标记只读文件，编辑器据此提示；保存前先检查目标文件
+//! 能否写入，只读文件直接返回 `read_only` 类别的失败，而不是写入后才报“权限被拒绝”。
+//! 前端可以让用户选择“解除只读并重试”：调用 `set_read_only(path, false)` 或以 `unlock`
+//! 参数再次保存。
+
+use std::fs::{self, OpenOptions};
+use std::io;
+use std::path::{Path, PathBuf};
+
+use tauri::AppHandle;
+
+use crate::access::{self, AccessKind};
+use crate::error::VividError;
+
+/// 文件能否写入
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WriteAccess {
+    Writable,
+    /// 设置了只读属性，或当前用户没有写权限
+    ReadOnly,
+    /// 被其它程序独占打开或加锁
+    Locked,
+}
+
+/// 检查文件能否写入；文件不存在时视为可写，由实际写入报告目录的问题
+pub fn write_access(path: &Path) -> WriteAccess {
+    let Ok(metadata) = fs::metadata(path) else {
+        return WriteAccess::Writable;
+    };
+    if !metadata.is_file() {
+        return WriteAccess::Writable;
+    }
+    if metadata.permissions().readonly() {
+        return WriteAccess::ReadOnly;
+    }
+    // 以写方式打开但不截断，不会修改文件内容
+    match OpenOptions::new().write(true).open(path) {
+        Err(e) if is_lock_error(&e) => WriteAccess::Locked,
+        // Windows 上杀毒软件扫描时也会拒绝访问，交给保存时的重试处理
+        Err(e) if cfg!(unix) && e.kind() == io::ErrorKind::PermissionDenied => {
+            WriteAccess::ReadOnly
+        }
+        _ => WriteAccess::Writable,
+    }
+}
+
+/// 文件被其它程序独占打开或加锁导致的错误
+pub fn is_lock_error(e: &io::Error) -> bool {
+    // Windows：ERROR_SHARING_VIOLATION、ERROR_LOCK_VIOLATION；Unix：EBUSY、ETXTBSY
+    if cfg!(windows) {
+        matches!(e.raw_os_error(), Some(32 | 33))
+    } else if cfg!(unix) {
+        matches!(e.raw_os_error(), Some(16 | 26))
+    } else {
+        false
+    }
+}
+
+pub fn is_read_only(path: &Path) -> bool {
+    write_access(path) == WriteAccess::ReadOnly
+}
+
+/// 设置或清除只读：Windows 上修改只读属性，Unix 上修改所有者的写权限
+pub fn set_permission(path: &Path, read_only: bool) -> io::Result<()> {
+    let mut permissions = fs::metadata(path)?.permissions();
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let mode = permissions.mode();
+        // 清除只读时只放开所有者的写权限，不放开组和其他用户
+        permissions.set_mode(if read_only {
+            mode & !0o222
+        } else {
+            mode | 0o200
+        });
+    }
+    #[cfg(not(unix))]
+    permissions.set_readonly(read_only);
+    fs::set_permissions(path, permissions)
+}
+
+// 设置或清除文件的只读状态，返回设置后文件是否只读
+#[tauri::command]
+pub fn set_read_only(app: AppHandle, path: String, read_only: bool) -> Result<bool, VividError> {
+    let path_buf = PathBuf::from(&path);
+    access::ensure_access(&app, &path_buf, AccessKind::Write)?;
+    log::info!("[set_read_only] {} -> {}", path, read_only);
+    if !path_buf.is_file() {
+        log::error!("[set_read_only] File does not exist: {}", path);
+        return Err(VividError::not_found(&path));
+    }
+    set_permission(&path_buf, read_only).map_err(|e| {
+        log::error!("[set_read_only] {}", e);
+        VividError::io("Failed to change permissions", &path_buf, &e)
+    })?;
+    let read_only = is_read_only(&path_buf);
+    log::info!(
+        "[set_read_only] ✓ Success: {} (read-only: {})",
+        path,
+        read_only
+    );
+    Ok(read_only)
+}
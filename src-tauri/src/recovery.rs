@@ -0,0 +1,287 @@
+//! 未保存内容的崩溃恢复
+//!
+//! 编辑器每隔几秒把有未保存修改的文档交给 `stash_unsaved`，内容写入应用数据目录
+//! `recovery/<路径哈希>.json`（类似 Vim 的 swap 文件）。文档保存后或用户放弃修改时删除。
+//!
+//! 应用崩溃或被强制退出后，之前运行留下的暂存内容由 `list_recoverable_documents` 列出，
+//! `recover` 取回内容，前端作为未保存的修改打开。本次运行写入的暂存内容不算可恢复。
+//! 尚未保存过的新文档可以用任意非绝对路径的标识（如 `untitled:1`）暂存。
+//!
+//! 加密文档的明文不会写入磁盘，这类文档不做暂存。
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::UNIX_EPOCH;
+
+use serde::{Deserialize, Serialize};
+use tauri::State;
+
+use crate::error::VividError;
+use crate::{encryption, paths, storage};
+
+/// 单个文档暂存的大小上限
+const MAX_STASH_BYTES: usize = 50 * 1024 * 1024;
+/// 超过该时间的暂存内容不再提供恢复，列出时删除
+const MAX_AGE_MS: u64 = 30 * 24 * 60 * 60 * 1000;
+/// 列表中内容预览的字符数
+const PREVIEW_CHARS: usize = 200;
+
+/// 一个暂存文件
+#[derive(Debug, Serialize, Deserialize)]
+struct SwapFile {
+    /// 文档路径或新文档的标识
+    path: String,
+    content: String,
+    /// 暂存时间（Unix 毫秒）
+    saved_at: u64,
+    /// 写入该暂存的运行
+    session: String,
+}
+
+/// 可恢复的文档
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RecoverableDocument {
+    pub path: String,
+    pub saved_at: u64,
+    pub size: usize,
+    /// 内容开头的一段，便于用户辨认
+    pub preview: String,
+    /// 是否是尚未保存过的新文档
+    pub untitled: bool,
+    /// 文件是否仍然存在
+    pub exists: bool,
+    /// 暂存之后文件在磁盘上又被修改过，恢复的内容可能比磁盘上的旧
+    pub modified_since: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RecoveredDocument {
+    pub path: String,
+    pub content: String,
+    pub saved_at: u64,
+    pub modified_since: bool,
+}
+
+/// 应用数据目录中的暂存内容
+pub struct RecoveryStore {
+    dir: PathBuf,
+    /// 本次运行的标识，用于区分之前运行留下的暂存
+    session: String,
+    /// 每个文档最近一次暂存的内容哈希，内容没有变化时不重复写入
+    stashed: Mutex<HashMap<String, String>>,
+}
+
+fn is_untitled(path: &str) -> bool {
+    !Path::new(path).is_absolute()
+}
+
+/// 统一路径写法，同一个文件总是对应同一个暂存
+fn document_key(path: &str) -> String {
+    if is_untitled(path) {
+        path.to_string()
+    } else {
+        paths::canonicalize(Path::new(path))
+            .to_string_lossy()
+            .to_string()
+    }
+}
+
+/// 文件的修改时间（Unix 毫秒）
+fn modified_millis(path: &Path) -> Option<u64> {
+    let modified = fs::metadata(path).ok()?.modified().ok()?;
+    Some(modified.duration_since(UNIX_EPOCH).ok()?.as_millis() as u64)
+}
+
+fn preview(content: &str) -> String {
+    let text = content.trim_start();
+    match text.char_indices().nth(PREVIEW_CHARS) {
+        Some((end, _)) => text[..end].to_string(),
+        None => text.to_string(),
+    }
+}
+
+impl SwapFile {
+    fn modified_since(&self) -> bool {
+        !is_untitled(&self.path)
+            && modified_millis(Path::new(&self.path)).is_some_and(|m| m > self.saved_at)
+    }
+}
+
+impl RecoveryStore {
+    pub fn new(dir: PathBuf) -> Self {
+        RecoveryStore {
+            dir,
+            session: format!("{}-{}", std::process::id(), storage::now_millis()),
+            stashed: Mutex::new(HashMap::new()),
+        }
+    }
+
+    fn swap_path(&self, key: &str) -> PathBuf {
+        let hash = blake3::hash(key.as_bytes()).to_hex().to_string();
+        self.dir.join(format!("{}.json", &hash[..16]))
+    }
+
+    fn load(&self, key: &str) -> Option<SwapFile> {
+        let text = fs::read_to_string(self.swap_path(key)).ok()?;
+        serde_json::from_str(&text).ok()
+    }
+
+    /// 暂存文档内容，内容与上次暂存相同时跳过，返回是否写入
+    pub fn stash(&self, path: &str, content: String) -> Result<bool, String> {
+        if content.len() > MAX_STASH_BYTES {
+            return Err(format!(
+                "Document is larger than {} MB",
+                MAX_STASH_BYTES / 1024 / 1024
+            ));
+        }
+        let key = document_key(path);
+        let hash = blake3::hash(content.as_bytes()).to_hex().to_string();
+        let mut stashed = self.stashed.lock().map_err(|e| e.to_string())?;
+        if stashed.get(&key) == Some(&hash) {
+            return Ok(false);
+        }
+        let swap = SwapFile {
+            path: key.clone(),
+            content,
+            saved_at: storage::now_millis(),
+            session: self.session.clone(),
+        };
+        storage::save_json(&self.swap_path(&key), &swap)?;
+        stashed.insert(key, hash);
+        Ok(true)
+    }
+
+    /// 删除文档的暂存内容（保存或放弃修改后），返回是否存在暂存
+    pub fn discard(&self, path: &Path) -> bool {
+        self.discard_key(&document_key(&path.to_string_lossy()))
+    }
+
+    fn discard_key(&self, key: &str) -> bool {
+        if let Ok(mut stashed) = self.stashed.lock() {
+            stashed.remove(key);
+        }
+        match fs::remove_file(self.swap_path(key)) {
+            Ok(()) => true,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => false,
+            Err(e) => {
+                log::warn!("[recovery] Failed to remove swap file for {}: {}", key, e);
+                false
+            }
+        }
+    }
+
+    /// 之前运行留下的暂存内容，过期的暂存顺便删除
+    fn recoverable(&self) -> Vec<SwapFile> {
+        let Ok(entries) = fs::read_dir(&self.dir) else {
+            return Vec::new();
+        };
+        let now = storage::now_millis();
+        let mut swaps = Vec::new();
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.extension().and_then(|ext| ext.to_str()) != Some("json") {
+                continue;
+            }
+            let swap = fs::read_to_string(&path)
+                .ok()
+                .and_then(|text| serde_json::from_str::<SwapFile>(&text).ok());
+            let Some(swap) = swap else {
+                log::warn!("[recovery] Ignoring unreadable swap file {:?}", path);
+                continue;
+            };
+            if now.saturating_sub(swap.saved_at) > MAX_AGE_MS {
+                log::info!("[recovery] Removing expired swap file for {}", swap.path);
+                let _ = fs::remove_file(&path);
+                continue;
+            }
+            if swap.session != self.session {
+                swaps.push(swap);
+            }
+        }
+        swaps.sort_by_key(|swap| std::cmp::Reverse(swap.saved_at));
+        swaps
+    }
+}
+
+// 暂存有未保存修改的文档，供崩溃后恢复；加密文档不暂存，返回 false
+#[tauri::command]
+pub fn stash_unsaved(
+    store: State<'_, RecoveryStore>,
+    path: String,
+    content: String,
+) -> Result<bool, VividError> {
+    if path.trim().is_empty() {
+        return Err(VividError::invalid_input("Document path is empty"));
+    }
+    if !is_untitled(&path) && encryption::is_encrypted_file(Path::new(&path)) {
+        log::debug!("[stash_unsaved] Skipping encrypted document: {}", path);
+        return Ok(false);
+    }
+    let written = store.stash(&path, content).map_err(|e| {
+        log::warn!("[stash_unsaved] {}: {}", path, e);
+        VividError::from(e)
+    })?;
+    if written {
+        log::debug!("[stash_unsaved] Stashed {}", path);
+    }
+    Ok(written)
+}
+
+// 列出之前运行中未保存、可以恢复的文档，最近的在前
+#[tauri::command]
+pub fn list_recoverable_documents(
+    store: State<'_, RecoveryStore>,
+) -> Result<Vec<RecoverableDocument>, VividError> {
+    let documents: Vec<RecoverableDocument> = store
+        .recoverable()
+        .into_iter()
+        .map(|swap| RecoverableDocument {
+            untitled: is_untitled(&swap.path),
+            exists: !is_untitled(&swap.path) && Path::new(&swap.path).is_file(),
+            modified_since: swap.modified_since(),
+            size: swap.content.len(),
+            preview: preview(&swap.content),
+            saved_at: swap.saved_at,
+            path: swap.path,
+        })
+        .collect();
+    log::info!(
+        "[list_recoverable_documents] {} recoverable document(s)",
+        documents.len()
+    );
+    Ok(documents)
+}
+
+// 取回文档的暂存内容；暂存保留到文档保存或调用 `discard_unsaved`
+#[tauri::command]
+pub fn recover(
+    store: State<'_, RecoveryStore>,
+    path: String,
+) -> Result<RecoveredDocument, VividError> {
+    let swap = store.load(&document_key(&path)).ok_or_else(|| {
+        log::warn!("[recover] No recovery data for {}", path);
+        VividError::invalid_input(format!("No recovery data for {}", path))
+    })?;
+    log::info!(
+        "[recover] ✓ Recovered {} ({} bytes, saved at {})",
+        swap.path,
+        swap.content.len(),
+        swap.saved_at
+    );
+    Ok(RecoveredDocument {
+        modified_since: swap.modified_since(),
+        path: swap.path,
+        content: swap.content,
+        saved_at: swap.saved_at,
+    })
+}
+
+// 放弃文档的暂存内容，返回是否存在暂存
+#[tauri::command]
+pub fn discard_unsaved(store: State<'_, RecoveryStore>, path: String) -> bool {
+    let discarded = store.discard_key(&document_key(&path));
+    log::info!("[discard_unsaved] {}: {}", path, discarded);
+    discarded
+}
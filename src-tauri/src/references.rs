@@ -0,0 +1,666 @@
+//! 脚注与引用式链接整理
+//!
+//! 长篇笔记里的脚注和链接经过多次增删后很容易变乱，这里提供三个整理命令：
+//!
+//! - `renumber_footnotes`：按首次引用的顺序把脚注重新编号为 `[^1]`、`[^2]`……，
+//!   顶层的脚注定义按新编号集中到文末；
+//! - `convert_inline_to_reference_links`：把 `[文字](url)` 改写为 `[文字][n]`，
+//!   目标相同的链接共用一个定义，已有的引用定义会被复用；
+//! - `collect_undefined_references`：列出没有定义的脚注和引用链接，以及没有被引用的定义。
+//!
+//! 脚注、链接和定义的位置都由 pulldown-cmark 识别，代码块和公式中的内容不受影响；
+//! front matter 不参与解析。脚注和链接标签不区分大小写，与 CommonMark 一致。
+
+use std::collections::{HashMap, HashSet};
+use std::ops::Range;
+use std::sync::OnceLock;
+
+use pulldown_cmark::{BrokenLink, Event, LinkType, Parser, Tag, TagEnd};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+use crate::error::VividError;
+use crate::parse::LineIndex;
+use crate::{frontmatter, links, markdown};
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RenumberFootnotesResult {
+    /// 修改后的完整文档内容
+    pub content: String,
+    /// 脚注定义的数量
+    pub footnotes: usize,
+    pub changed: bool,
+    /// 没有被引用的脚注（原标签），编号排在被引用的脚注之后
+    pub unreferenced: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ReferenceLinksResult {
+    /// 修改后的完整文档内容
+    pub content: String,
+    /// 改写的链接数量
+    pub converted: usize,
+    /// 新增的引用定义数量
+    pub definitions: usize,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ReferenceKind {
+    Footnote,
+    Link,
+}
+
+/// 没有定义的引用
+#[derive(Debug, Serialize, Deserialize)]
+pub struct UndefinedReference {
+    pub kind: ReferenceKind,
+    pub label: String,
+    pub line_index: usize,
+    pub start: usize,
+    pub end: usize,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ReferenceReport {
+    pub undefined: Vec<UndefinedReference>,
+    /// 没有被引用的脚注定义
+    pub unused_footnotes: Vec<String>,
+    /// 没有被引用的链接定义
+    pub unused_links: Vec<String>,
+}
+
+/// 正文（front matter 之后）的起始偏移
+fn body_start(content: &str) -> usize {
+    frontmatter::find_front_matter(content)
+        .map(|block| block.body_start)
+        .unwrap_or(0)
+}
+
+fn label_key(label: &str) -> String {
+    label.trim().to_lowercase()
+}
+
+/// 跳过 `end` 之后的空行（含 `end` 所在行的剩余部分）
+fn skip_blank_lines(content: &str, mut end: usize) -> usize {
+    loop {
+        match content[end..].find('\n') {
+            Some(newline) if content[end..end + newline].trim().is_empty() => end += newline + 1,
+            Some(_) => return end,
+            None if content[end..].trim().is_empty() => return content.len(),
+            None => return end,
+        }
+    }
+}
+
+fn footnote_ref_regex() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| Regex::new(r"\[\^([^\]\s]+)\](:)?").expect("valid regex"))
+}
+
+/// 文档中的一个脚注定义
+struct FootnoteDef {
+    key: String,
+    label: String,
+    range: Range<usize>,
+    /// 顶层定义可以移动到文末，容器（列表、引用块）中的定义只在原处改编号
+    movable: bool,
+}
+
+/// 脚注引用和定义，区间相对正文
+fn scan_footnotes(body: &str) -> (Vec<(String, Range<usize>)>, Vec<FootnoteDef>) {
+    let mut refs = Vec::new();
+    let mut defs: Vec<FootnoteDef> = Vec::new();
+    for (event, range) in markdown::parser(body).into_offset_iter() {
+        match event {
+            Event::FootnoteReference(label) => refs.push((label_key(&label), range)),
+            Event::Start(Tag::FootnoteDefinition(label)) => {
+                let movable = markdown::line_start(body, range.start) == range.start;
+                defs.push(FootnoteDef {
+                    key: label_key(&label),
+                    label: label.to_string(),
+                    range,
+                    movable,
+                });
+            }
+            _ => {}
+        }
+    }
+    (refs, defs)
+}
+
+/// 重写定义开头的 `[^label]:` 和其中嵌套的引用
+fn rewrite_definition(
+    body: &str,
+    def: &FootnoteDef,
+    refs: &[(String, Range<usize>)],
+    numbers: &HashMap<String, usize>,
+) -> String {
+    let range = &def.range;
+    let mut edits = Vec::new();
+    if let Some(close) = body[range.clone()].find("]:") {
+        edits.push((0..close + 2, format!("[^{}]:", numbers[&def.key])));
+    }
+    for (key, r) in refs {
+        if r.start >= range.start && r.end <= range.end {
+            if let Some(n) = numbers.get(key) {
+                edits.push((
+                    r.start - range.start..r.end - range.start,
+                    format!("[^{}]", n),
+                ));
+            }
+        }
+    }
+    markdown::apply_edits(&body[range.clone()], &mut edits)
+}
+
+fn renumber(body: &str) -> (String, usize, Vec<String>) {
+    let (refs, defs) = scan_footnotes(body);
+    if defs.is_empty() {
+        return (body.to_string(), 0, Vec::new());
+    }
+    let defined: HashMap<&str, &FootnoteDef> =
+        defs.iter().map(|def| (def.key.as_str(), def)).collect();
+    let inside = |range: &Range<usize>| {
+        defs.iter()
+            .find(|def| def.range.start <= range.start && range.end <= def.range.end)
+    };
+
+    // 先按正文中首次引用的顺序编号，再处理只在其它脚注中被引用的脚注
+    fn push<'a>(order: &mut Vec<&'a str>, defined: &HashMap<&str, &'a FootnoteDef>, key: &str) {
+        if let Some(def) = defined.get(key) {
+            if !order.contains(&def.key.as_str()) {
+                order.push(def.key.as_str());
+            }
+        }
+    }
+    let mut order: Vec<&str> = Vec::new();
+    for (key, range) in &refs {
+        if inside(range).is_none() {
+            push(&mut order, &defined, key);
+        }
+    }
+    let mut next = 0;
+    while next < order.len() {
+        let def = defined[order[next]];
+        for (key, range) in &refs {
+            if inside(range).is_some_and(|outer| outer.key == def.key) {
+                push(&mut order, &defined, key);
+            }
+        }
+        next += 1;
+    }
+    let referenced = order.len();
+    let mut unreferenced = Vec::new();
+    for def in &defs {
+        if !order.contains(&def.key.as_str()) {
+            order.push(def.key.as_str());
+            unreferenced.push(def.label.clone());
+        }
+    }
+    let numbers: HashMap<String, usize> = order
+        .iter()
+        .enumerate()
+        .map(|(i, key)| (key.to_string(), i + 1))
+        .collect();
+
+    let mut edits = Vec::new();
+    let mut moved: Vec<(usize, String)> = Vec::new();
+    for def in &defs {
+        let text = rewrite_definition(body, def, &refs, &numbers);
+        if !def.movable {
+            edits.push((def.range.clone(), text));
+            continue;
+        }
+        let removal = def.range.start..skip_blank_lines(body, def.range.end);
+        edits.push((removal, String::new()));
+        moved.push((numbers[&def.key], text.trim_end().to_string()));
+    }
+    for (key, range) in &refs {
+        if let (None, Some(n)) = (inside(range), numbers.get(key)) {
+            edits.push((range.clone(), format!("[^{}]", n)));
+        }
+    }
+
+    let mut out = markdown::apply_edits(body, &mut edits);
+    if !moved.is_empty() {
+        moved.sort_by_key(|(n, _)| *n);
+        out.truncate(out.trim_end().len());
+        if !out.is_empty() {
+            out.push_str("\n\n");
+        }
+        let mut previous_multiline = false;
+        for (i, (_, text)) in moved.iter().enumerate() {
+            // 多段落的脚注前后留空行，单行脚注紧挨着排列
+            let multiline = text.contains("\n\n");
+            if i > 0 {
+                out.push_str(if multiline || previous_multiline {
+                    "\n\n"
+                } else {
+                    "\n"
+                });
+            }
+            out.push_str(text);
+            previous_multiline = multiline;
+        }
+        out.push('\n');
+    }
+    log::debug!(
+        "[renumber_footnotes] {} referenced, {} unreferenced",
+        referenced,
+        unreferenced.len()
+    );
+    (out, defs.len(), unreferenced)
+}
+
+/// 把引用定义的目标写成 `[label]: url "title"`
+fn definition_line(label: &str, dest: &str, title: &str) -> String {
+    let needs_brackets = dest.is_empty()
+        || dest.contains(char::is_whitespace)
+        || dest.contains(['<', '>'])
+        || dest.matches('(').count() != dest.matches(')').count();
+    let dest = if needs_brackets {
+        format!("<{}>", dest.replace('<', "\\<").replace('>', "\\>"))
+    } else {
+        dest.to_string()
+    };
+    if title.is_empty() {
+        format!("[{}]: {}", label, dest)
+    } else {
+        format!(
+            "[{}]: {} \"{}\"",
+            label,
+            dest,
+            title.replace('\\', "\\\\").replace('"', "\\\"")
+        )
+    }
+}
+
+/// 文档中的一个行内链接
+struct InlineLink {
+    range: Range<usize>,
+    /// 方括号中的链接文字
+    text: Range<usize>,
+    dest: String,
+    title: String,
+    /// 原文中圆括号内的目标和标题
+    raw: String,
+}
+
+fn convert_links(body: &str) -> (String, usize, usize) {
+    let mut parser = markdown::parser(body).into_offset_iter();
+    let mut links: Vec<InlineLink> = Vec::new();
+    let mut open: Option<(Range<usize>, String, String, usize)> = None;
+    for (event, range) in parser.by_ref() {
+        match event {
+            Event::Start(Tag::Link {
+                link_type: LinkType::Inline,
+                dest_url,
+                title,
+                ..
+            }) if !dest_url.is_empty() => {
+                open = Some((
+                    range.clone(),
+                    dest_url.to_string(),
+                    title.to_string(),
+                    range.start + 1,
+                ));
+            }
+            Event::End(TagEnd::Link) => {
+                let Some((link, dest, title, inner_end)) = open.take() else {
+                    continue;
+                };
+                let Some(close) = body[inner_end..link.end].find("](") else {
+                    continue;
+                };
+                let text = link.start + 1..inner_end + close;
+                let raw = body[text.end + 2..link.end - 1].trim().to_string();
+                links.push(InlineLink {
+                    range: link,
+                    text,
+                    dest,
+                    title,
+                    raw,
+                });
+            }
+            _ => {
+                if let Some((_, _, _, inner_end)) = open.as_mut() {
+                    *inner_end = (*inner_end).max(range.end);
+                }
+            }
+        }
+    }
+    if links.is_empty() {
+        return (body.to_string(), 0, 0);
+    }
+
+    let existing = parser.reference_definitions();
+    let mut used: HashSet<String> = existing.iter().map(|(label, _)| label_key(label)).collect();
+    let mut by_target: HashMap<(String, String), String> = HashMap::new();
+    let mut last_definition_end = 0;
+    for (label, def) in existing.iter() {
+        let title = def.title.as_deref().unwrap_or_default().to_string();
+        by_target
+            .entry((def.dest.to_string(), title))
+            .or_insert_with(|| label.to_string());
+        last_definition_end = last_definition_end.max(def.span.end);
+    }
+    let mut next_number = used
+        .iter()
+        .filter_map(|label| label.parse::<usize>().ok())
+        .max()
+        .unwrap_or(0);
+
+    let mut edits = Vec::new();
+    let mut definitions = Vec::new();
+    let converted = links.len();
+    for InlineLink {
+        range,
+        text,
+        dest,
+        title,
+        raw,
+    } in links
+    {
+        let label = match by_target.get(&(dest.clone(), title.clone())) {
+            Some(label) => label.clone(),
+            None => {
+                let label = loop {
+                    next_number += 1;
+                    let label = next_number.to_string();
+                    if used.insert(label.clone()) {
+                        break label;
+                    }
+                };
+                // 原文中的写法已经按 Markdown 转义过，多行的标题重新生成
+                let line = if raw.contains('\n') || raw.is_empty() {
+                    definition_line(&label, &dest, &title)
+                } else {
+                    format!("[{}]: {}", label, raw)
+                };
+                definitions.push(line);
+                by_target.insert((dest, title), label.clone());
+                label
+            }
+        };
+        edits.push((range, format!("[{}][{}]", &body[text], label)));
+    }
+
+    let mut out = markdown::apply_edits(body, &mut edits);
+    let added = definitions.len();
+    if added > 0 {
+        let trimmed = out.trim_end().len();
+        out.truncate(trimmed);
+        // 文末已经是引用定义时接在后面，否则另起一段
+        let after_definitions = last_definition_end > 0
+            && body[last_definition_end.min(body.len())..]
+                .trim()
+                .is_empty();
+        if !out.is_empty() {
+            out.push_str(if after_definitions { "\n" } else { "\n\n" });
+        }
+        out.push_str(&definitions.join("\n"));
+        out.push('\n');
+    }
+    (out, converted, added)
+}
+
+fn collect_report(content: &str) -> ReferenceReport {
+    let start = body_start(content);
+    let body = &content[start..];
+    let lines = LineIndex::new(content);
+    let mut broken: Vec<(String, Range<usize>)> = Vec::new();
+    let mut footnote_refs = HashSet::new();
+    let mut footnote_defs: Vec<String> = Vec::new();
+    let mut link_refs = HashSet::new();
+    let mut link_defs: Vec<(usize, String)> = Vec::new();
+    {
+        // 简写形式 `[text]` 与普通方括号无法区分，只检查 `[text][label]` 和 `[label][]`
+        let callback = |link: BrokenLink<'_>| {
+            if matches!(link.link_type, LinkType::Reference | LinkType::Collapsed) {
+                broken.push((link.reference.to_string(), link.span));
+            }
+            None
+        };
+        let mut parser =
+            Parser::new_with_broken_link_callback(body, markdown::parser_options(), Some(callback))
+                .into_offset_iter();
+        for (event, _) in parser.by_ref() {
+            match event {
+                Event::FootnoteReference(label) => {
+                    footnote_refs.insert(label_key(&label));
+                }
+                Event::Start(Tag::FootnoteDefinition(label)) => {
+                    footnote_defs.push(label.to_string());
+                }
+                Event::Start(Tag::Link { link_type, id, .. })
+                | Event::Start(Tag::Image { link_type, id, .. })
+                    if matches!(
+                        link_type,
+                        LinkType::Reference | LinkType::Collapsed | LinkType::Shortcut
+                    ) =>
+                {
+                    link_refs.insert(label_key(&id));
+                }
+                _ => {}
+            }
+        }
+        for (label, def) in parser.reference_definitions().iter() {
+            link_defs.push((def.span.start, label.to_string()));
+        }
+    }
+
+    let mut undefined: Vec<UndefinedReference> = broken
+        .into_iter()
+        .map(|(label, range)| UndefinedReference {
+            kind: ReferenceKind::Link,
+            label,
+            line_index: lines.line_of(start + range.start),
+            start: start + range.start,
+            end: start + range.end,
+        })
+        .collect();
+
+    // 没有定义的脚注引用被当作普通文本，只能按文本查找
+    let defined: HashSet<String> = footnote_defs.iter().map(|l| label_key(l)).collect();
+    let literals = links::literal_ranges(body);
+    for caps in footnote_ref_regex().captures_iter(body) {
+        let m = caps.get(0).expect("whole match");
+        if caps.get(2).is_some() || defined.contains(&label_key(&caps[1])) {
+            continue;
+        }
+        if literals
+            .iter()
+            .any(|r| r.start <= m.start() && m.end() <= r.end)
+        {
+            continue;
+        }
+        undefined.push(UndefinedReference {
+            kind: ReferenceKind::Footnote,
+            label: caps[1].to_string(),
+            line_index: lines.line_of(start + m.start()),
+            start: start + m.start(),
+            end: start + m.end(),
+        });
+    }
+    undefined.sort_by_key(|r| r.start);
+
+    link_defs.sort();
+    ReferenceReport {
+        undefined,
+        unused_footnotes: footnote_defs
+            .into_iter()
+            .filter(|label| !footnote_refs.contains(&label_key(label)))
+            .collect(),
+        unused_links: link_defs
+            .into_iter()
+            .map(|(_, label)| label)
+            .filter(|label| !link_refs.contains(&label_key(label)))
+            .collect(),
+    }
+}
+
+// 按首次引用的顺序重新编号脚注，并把脚注定义集中到文末
+#[tauri::command]
+pub fn renumber_footnotes(content: String) -> Result<RenumberFootnotesResult, VividError> {
+    let start = body_start(&content);
+    let (body, footnotes, unreferenced) = renumber(&content[start..]);
+    let renumbered = format!("{}{}", &content[..start], body);
+    let changed = renumbered != content;
+    log::debug!(
+        "[renumber_footnotes] {} footnote(s), changed: {}",
+        footnotes,
+        changed
+    );
+    Ok(RenumberFootnotesResult {
+        content: renumbered,
+        footnotes,
+        changed,
+        unreferenced,
+    })
+}
+
+// 把行内链接改写为引用式链接，相同目标共用一个定义
+#[tauri::command]
+pub fn convert_inline_to_reference_links(
+    content: String,
+) -> Result<ReferenceLinksResult, VividError> {
+    let start = body_start(&content);
+    let (body, converted, definitions) = convert_links(&content[start..]);
+    log::debug!(
+        "[convert_inline_to_reference_links] {} link(s), {} new definition(s)",
+        converted,
+        definitions
+    );
+    Ok(ReferenceLinksResult {
+        content: format!("{}{}", &content[..start], body),
+        converted,
+        definitions,
+    })
+}
+
+// 列出未定义的脚注和引用链接，以及未被引用的定义
+#[tauri::command]
+pub fn collect_undefined_references(content: String) -> Result<ReferenceReport, VividError> {
+    let report = collect_report(&content);
+    log::debug!(
+        "[collect_undefined_references] {} undefined, {} unused footnote(s), {} unused link(s)",
+        report.undefined.len(),
+        report.unused_footnotes.len(),
+        report.unused_links.len()
+    );
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn renumbered(content: &str) -> RenumberFootnotesResult {
+        renumber_footnotes(content.to_string()).unwrap()
+    }
+
+    #[test]
+    fn renumbers_in_order_of_first_reference() {
+        let result = renumbered("乙[^b]，甲[^a]，再次[^b]。\n\n[^a]: 甲注\n\n[^b]: 乙注\n");
+        assert_eq!(
+            result.content,
+            "乙[^1]，甲[^2]，再次[^1]。\n\n[^1]: 乙注\n[^2]: 甲注\n"
+        );
+        assert_eq!(result.footnotes, 2);
+        assert!(result.changed);
+        assert!(result.unreferenced.is_empty());
+    }
+
+    #[test]
+    fn labels_are_case_insensitive() {
+        let result = renumbered("x[^Note]\n\n[^note]: n\n");
+        assert_eq!(result.content, "x[^1]\n\n[^1]: n\n");
+    }
+
+    #[test]
+    fn definitions_without_references_are_numbered_last() {
+        let result = renumbered("正文[^used]\n\n[^orphan]: 孤立\n[^used]: 已引用\n");
+        assert_eq!(result.content, "正文[^1]\n\n[^1]: 已引用\n[^2]: 孤立\n");
+        assert_eq!(result.unreferenced, vec!["orphan"]);
+    }
+
+    #[test]
+    fn only_unreferenced_definitions() {
+        let result = renumbered("正文\n\n[^x]: 注\n");
+        assert_eq!(result.content, "正文\n\n[^1]: 注\n");
+        assert_eq!(result.footnotes, 1);
+        assert_eq!(result.unreferenced, vec!["x"]);
+    }
+
+    #[test]
+    fn already_numbered_document_is_unchanged() {
+        let content = "a[^1] b[^2]\n\n[^1]: one\n[^2]: two\n";
+        let result = renumbered(content);
+        assert_eq!(result.content, content);
+        assert!(!result.changed);
+    }
+
+    #[test]
+    fn renumbering_keeps_front_matter_and_code() {
+        let content = "---\ntitle: \"[^a]\"\n---\n`[^a]` 与[^a]\n\n[^a]: 注\n";
+        let result = renumbered(content);
+        assert_eq!(
+            result.content,
+            "---\ntitle: \"[^a]\"\n---\n`[^a]` 与[^1]\n\n[^1]: 注\n"
+        );
+    }
+
+    #[test]
+    fn nested_references_are_numbered_after_their_parent() {
+        let result = renumbered("a[^p]\n\n[^c]: child\n[^p]: parent[^c]\n");
+        assert_eq!(result.content, "a[^1]\n\n[^1]: parent[^2]\n[^2]: child\n");
+        assert!(result.unreferenced.is_empty());
+    }
+
+    #[test]
+    fn converts_links_sharing_a_target() {
+        let content = "[甲](https://a.example) 和 [乙](https://a.example)，[丙](b.md \"标题\")\n";
+        let result = convert_inline_to_reference_links(content.to_string()).unwrap();
+        assert_eq!(
+            result.content,
+            "[甲][1] 和 [乙][1]，[丙][2]\n\n[1]: https://a.example\n[2]: b.md \"标题\"\n"
+        );
+        assert_eq!(result.converted, 3);
+        assert_eq!(result.definitions, 2);
+    }
+
+    #[test]
+    fn reuses_existing_definitions() {
+        let content =
+            "[a](https://x.example) [b][1]\n\n[1]: https://y.example\n[x]: https://x.example\n";
+        let result = convert_inline_to_reference_links(content.to_string()).unwrap();
+        assert_eq!(
+            result.content,
+            "[a][x] [b][1]\n\n[1]: https://y.example\n[x]: https://x.example\n"
+        );
+        assert_eq!(result.definitions, 0);
+    }
+
+    #[test]
+    fn reports_undefined_and_unused_references() {
+        let content = "第一行\n引用[^missing]和[链接][nowhere]，`[^code]`\n\n[^spare]: 没有引用\n\n[unused]: https://example.com\n";
+        let report = collect_undefined_references(content.to_string()).unwrap();
+        let undefined: Vec<(ReferenceKind, &str, usize)> = report
+            .undefined
+            .iter()
+            .map(|r| (r.kind, r.label.as_str(), r.line_index))
+            .collect();
+        assert_eq!(
+            undefined,
+            vec![
+                (ReferenceKind::Footnote, "missing", 1),
+                (ReferenceKind::Link, "nowhere", 1),
+            ]
+        );
+        let footnote = &report.undefined[0];
+        assert_eq!(&content[footnote.start..footnote.end], "[^missing]");
+        assert_eq!(report.unused_footnotes, vec!["spare"]);
+        assert_eq!(report.unused_links, vec!["unused"]);
+    }
+}
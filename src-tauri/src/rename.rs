@@ -0,0 +1,366 @@
+//! 重命名并更新链接
+//!
+//! `rename_with_link_update` 移动文件或目录，并改写工作区中指向它们的链接：
+//!
+//! - 普通 Markdown 链接、图片和 HTML `src` / `href` 按文档所在目录重新计算相对路径，
+//!   保留 `#标题`，原链接省略 `.md` 时新链接同样省略；
+//! - `[[Wiki 链接]]` 和 `![[嵌入]]` 只在移动后解析不到原文件时改写目标部分，
+//!   保留 `#标题`、`|别名`；原来按文件名链接的改为新文件名，按路径链接的改为新的工作区路径。
+//!
+//! 被移动的文档中的相对链接同样按新位置改写。链接在移动前解析，移动后再计算新写法。
+
+use std::collections::HashMap;
+use std::fs;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager};
+
+use crate::access::{self, AccessKind};
+use crate::assets::{self, LinkEdit};
+use crate::backup::BackupStore;
+use crate::docstate::DocStateStore;
+use crate::error::VividError;
+use crate::links::{self, FileLinks, LinkIndex, LinkKind, MatchKind, Resolver};
+use crate::settings::SettingsStore;
+use crate::workspace::{self, FileIndex, Workspace};
+use crate::{markdown, paths, render, storage};
+
+/// 改写了链接的文档
+#[derive(Debug, Serialize, Deserialize)]
+pub struct UpdatedFile {
+    /// 文档当前的路径（被移动的文档为新路径）
+    pub path: String,
+    pub links: usize,
+}
+
+/// 未能改写的文档
+#[derive(Debug, Serialize, Deserialize)]
+pub struct FailedFile {
+    pub path: String,
+    pub error: String,
+}
+
+/// 重命名结果
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RenameReport {
+    pub old_path: String,
+    pub new_path: String,
+    pub updated: Vec<UpdatedFile>,
+    pub failed: Vec<FailedFile>,
+}
+
+/// 移动前解析好的一处链接
+enum PendingLink {
+    /// 普通链接：目标文件、是否省略了 `.md`、是否为绝对路径
+    Markdown {
+        target: PathBuf,
+        omit_ext: bool,
+        absolute: bool,
+    },
+    /// Wiki 链接：目标文件和原来的写法
+    Wiki { target: PathBuf, raw: String },
+}
+
+/// 一篇需要检查的文档
+struct PendingFile {
+    path: PathBuf,
+    content: String,
+    links: Vec<(Range<usize>, PendingLink)>,
+}
+
+/// 路径移动后的位置：`old` 本身或其下的文件
+fn remap(path: &Path, old: &Path, new: &Path) -> Option<PathBuf> {
+    if path == old {
+        return Some(new.to_path_buf());
+    }
+    path.strip_prefix(old).ok().map(|rel| new.join(rel))
+}
+
+fn moved_or_same(path: &Path, old: &Path, new: &Path) -> PathBuf {
+    remap(path, old, new).unwrap_or_else(|| path.to_path_buf())
+}
+
+/// Wiki 链接中目标文本的区间（`[[` 之后、`#` 和 `|` 之前）
+fn wiki_target_range(content: &str, link: &links::OutLink) -> Option<Range<usize>> {
+    let open = link.start + content[link.start..link.end].find("[[")? + 2;
+    let offset = content[open..link.end].find(&link.target)?;
+    Some(open + offset..open + offset + link.target.len())
+}
+
+/// 找出文档中指向移动对象的链接；文档本身被移动时还包括它的所有相对链接
+fn pending_links(
+    path: &Path,
+    content: &str,
+    file_links: Option<&FileLinks>,
+    resolver: &Resolver,
+    old: &Path,
+) -> Vec<(Range<usize>, PendingLink)> {
+    let doc_moved = path.starts_with(old);
+    let dir = path.parent().unwrap_or(Path::new("."));
+    let mut pending = Vec::new();
+
+    for reference in assets::collect_asset_refs(content) {
+        let Some(range) = reference.range.filter(|_| !reference.wiki) else {
+            continue;
+        };
+        let absolute = Path::new(&reference.target).is_absolute();
+        let target =
+            links::normalize_path(&render::resolve_local_path(&reference.target, Some(dir)));
+        let with_ext = target.with_extension("md");
+        let omit_ext = target.extension().is_none() && with_ext.is_file();
+        let target = if omit_ext { with_ext } else { target };
+        // 绝对路径不受文档位置影响，只在目标被移动时改写
+        let affected = target.starts_with(old) || (doc_moved && !absolute);
+        if !affected || !target.exists() {
+            continue;
+        }
+        pending.push((
+            range,
+            PendingLink::Markdown {
+                target,
+                omit_ext,
+                absolute,
+            },
+        ));
+    }
+
+    for link in file_links.map_or(&[][..], |f| f.links.as_slice()) {
+        if link.kind == LinkKind::Markdown || link.target.is_empty() {
+            continue;
+        }
+        let Some(target) = resolver.resolve_exact(path, link) else {
+            continue;
+        };
+        if !(doc_moved || target.starts_with(old)) {
+            continue;
+        }
+        if let Some(range) = wiki_target_range(content, link) {
+            pending.push((
+                range,
+                PendingLink::Wiki {
+                    target,
+                    raw: link.target.clone(),
+                },
+            ));
+        }
+    }
+    pending
+}
+
+/// 普通链接的新写法
+fn markdown_link(document: &Path, target: &Path, omit_ext: bool, absolute: bool) -> String {
+    let rel = document
+        .parent()
+        .filter(|_| !absolute)
+        .and_then(|dir| assets::relative_path(dir, target));
+    let link = assets::path_to_link(rel.as_deref().unwrap_or(target));
+    match link.strip_suffix(".md") {
+        Some(stem) if omit_ext => stem.to_string(),
+        _ => link,
+    }
+}
+
+/// Wiki 链接的新写法，移动后原写法仍能解析到目标时返回 `None`
+fn wiki_link(
+    resolver: &Resolver,
+    root: &Path,
+    document: &Path,
+    target: &Path,
+    raw: &str,
+) -> Option<String> {
+    let resolves = |text: &str| {
+        matches!(
+            resolver.resolve(document, text),
+            (Some(path), Some(kind), _) if kind != MatchKind::Fuzzy && path == target
+        )
+    };
+    if resolves(raw) {
+        return None;
+    }
+    let keep_ext = Path::new(raw).extension().is_some();
+    let name = if keep_ext {
+        target.file_name()
+    } else {
+        target.file_stem()
+    }
+    .map(|name| name.to_string_lossy().to_string())?;
+    // 原来按文件名链接且新文件名不会解析到其它文件时继续用文件名
+    if !raw.contains(['/', '\\']) && resolves(&name) {
+        return Some(name);
+    }
+    let rel = assets::relative_path(root, target)?;
+    let rel = if keep_ext {
+        rel
+    } else {
+        rel.with_extension("")
+    };
+    Some(rel.to_string_lossy().replace('\\', "/"))
+}
+
+/// 移动文件或目录并改写链接
+fn rename_and_relink(
+    app: &AppHandle,
+    root: &Path,
+    old: &Path,
+    new: &Path,
+) -> Result<RenameReport, VividError> {
+    // 移动前解析所有链接，移动后旧路径就不存在了
+    let files = workspace::markdown_files(root);
+    let index = links::scan_files(&files);
+    let resolver = Resolver::new(Some(root), &index);
+    let mut pending = Vec::new();
+    for path in &files {
+        let Ok(content) = fs::read_to_string(path) else {
+            continue;
+        };
+        let links = pending_links(path, &content, index.get(path), &resolver, old);
+        if !links.is_empty() {
+            pending.push(PendingFile {
+                path: path.clone(),
+                content,
+                links,
+            });
+        }
+    }
+
+    if let Some(parent) = new.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|e| VividError::io("Failed to create directory", parent, &e))?;
+    }
+    fs::rename(old, new).map_err(|e| VividError::io("Failed to rename file", old, &e))?;
+    app.state::<DocStateStore>().move_path(old, new);
+
+    let moved_index: HashMap<PathBuf, FileLinks> = index
+        .iter()
+        .map(|(path, links)| (moved_or_same(path, old, new), links.clone()))
+        .collect();
+    let resolver = Resolver::new(Some(root), &moved_index);
+    let settings = app.state::<SettingsStore>().get();
+    let backups = app.state::<BackupStore>();
+    let link_index = app.state::<LinkIndex>();
+    link_index.remove_under(old);
+
+    let mut report = RenameReport {
+        old_path: old.to_string_lossy().to_string(),
+        new_path: new.to_string_lossy().to_string(),
+        updated: Vec::new(),
+        failed: Vec::new(),
+    };
+    for file in pending {
+        let document = moved_or_same(&file.path, old, new);
+        let mut edits: Vec<LinkEdit> = file
+            .links
+            .into_iter()
+            .filter_map(|(range, link)| {
+                let text = match link {
+                    PendingLink::Markdown {
+                        target,
+                        omit_ext,
+                        absolute,
+                    } => markdown_link(
+                        &document,
+                        &moved_or_same(&target, old, new),
+                        omit_ext,
+                        absolute,
+                    ),
+                    PendingLink::Wiki { target, raw } => wiki_link(
+                        &resolver,
+                        root,
+                        &document,
+                        &moved_or_same(&target, old, new),
+                        &raw,
+                    )?,
+                };
+                (file.content[range.clone()] != text).then_some((range, text))
+            })
+            .collect();
+        if edits.is_empty() {
+            continue;
+        }
+
+        let out = markdown::apply_edits(&file.content, &mut edits);
+        backups.before_save(&settings.backup, &document);
+        match storage::write_atomic(&document, out.as_bytes()) {
+            Ok(()) => {
+                link_index.refresh(&document);
+                report.updated.push(UpdatedFile {
+                    path: document.to_string_lossy().to_string(),
+                    links: edits.len(),
+                });
+            }
+            Err(e) => {
+                log::error!(
+                    "[rename_with_link_update] Failed to update {}: {}",
+                    document.display(),
+                    e
+                );
+                report.failed.push(FailedFile {
+                    path: document.to_string_lossy().to_string(),
+                    error: e,
+                });
+            }
+        }
+    }
+    Ok(report)
+}
+
+// 移动或重命名文件 / 目录，并改写工作区中所有指向它的链接
+#[tauri::command]
+pub async fn rename_with_link_update(
+    app: AppHandle,
+    old_path: String,
+    new_path: String,
+) -> Result<RenameReport, VividError> {
+    let start = Instant::now();
+    let old = links::normalize_path(Path::new(&old_path));
+    let new = links::normalize_path(Path::new(&new_path));
+    log::info!("[rename_with_link_update] {} -> {}", old_path, new_path);
+
+    if !old.exists() {
+        log::error!(
+            "[rename_with_link_update] Path does not exist: {}",
+            old_path
+        );
+        return Err(VividError::not_found(&old));
+    }
+    if old.is_dir() && new.starts_with(&old) {
+        log::error!(
+            "[rename_with_link_update] Cannot move a directory into itself: {}",
+            new_path
+        );
+        return Err(VividError::invalid_input(format!(
+            "Cannot move a directory into itself: {}",
+            new_path
+        )));
+    }
+    // 只改大小写时目标与原文件是同一个文件
+    if new.exists() && !paths::same_file(&old, &new) {
+        log::error!(
+            "[rename_with_link_update] Target already exists: {}",
+            new_path
+        );
+        return Err(VividError::conflict(
+            &new,
+            format!("File already exists: {}", new_path),
+        ));
+    }
+    access::ensure_access(&app, &old, AccessKind::Write)?;
+    access::ensure_access(&app, &new, AccessKind::Write)?;
+    let root = assets::root_or_parent(app.state::<Workspace>().root(), &old);
+
+    let handle = app.clone();
+    let report =
+        tauri::async_runtime::spawn_blocking(move || rename_and_relink(&handle, &root, &old, &new))
+            .await
+            .map_err(|e| format!("Rename task failed: {}", e))??;
+    log::info!(
+        "[rename_with_link_update] ✓ Success: {} file(s) updated, {} failed in {:?}",
+        report.updated.len(),
+        report.failed.len(),
+        start.elapsed()
+    );
+    Ok(report)
+}
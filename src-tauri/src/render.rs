@@ -0,0 +1,434 @@
+//! Markdown → HTML 渲染
+//!
+//! 预览与导出（HTML / 打印等）共用的渲染管线：在 pulldown-cmark 事件流上替换代码块、公式和图片，
+//! 再交给 `pulldown_cmark::html` 输出。预览时的结果经过 ammonia 白名单过滤，
+//! 避免文档中的任意 HTML 在 webview 中执行脚本。
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+
+use base64::Engine;
+use pulldown_cmark::{CodeBlockKind, CowStr, Event, Tag, TagEnd};
+use serde::Deserialize;
+use tauri::{AppHandle, Manager};
+
+use crate::bibliography;
+use crate::diagram::{self, DiagramKind};
+use crate::error::VividError;
+use crate::highlight;
+use crate::markdown;
+use crate::math;
+use crate::plugins::PluginHost;
+
+/// 渲染选项
+#[derive(Debug, Clone, Default)]
+pub struct RenderOptions {
+    /// 代码高亮使用的 syntect 主题，`None` 表示不高亮
+    pub highlight_theme: Option<String>,
+    /// 是否将 `$...$` / `$$...$$` 预渲染为 MathML
+    pub render_math: bool,
+    /// 是否将本地图片以 data URI 内嵌
+    pub embed_images: bool,
+    /// 解析相对图片路径的基准目录
+    pub base_dir: Option<PathBuf>,
+    /// 单个换行渲染为 `<br>`（与编辑器预览的 `breaks: true` 一致）
+    pub hard_breaks: bool,
+    /// 是否调用外部工具将 Mermaid / PlantUML / Graphviz 代码块渲染为 SVG
+    pub render_diagrams: bool,
+}
+
+/// 将 Markdown 渲染为 HTML 片段（不含 `<html>` 外壳）
+pub fn markdown_to_html(content: &str, options: &RenderOptions) -> String {
+    let source = markdown::expand_admonitions(content);
+    let mut events: Vec<Event<'_>> = Vec::new();
+    let mut code_block: Option<(String, String)> = None;
+
+    for event in markdown::parser(&source) {
+        if let Some((lang, code)) = code_block.as_mut() {
+            match event {
+                Event::Text(text) => code.push_str(&text),
+                Event::End(TagEnd::CodeBlock) => {
+                    let html = render_code_block(lang, code, options);
+                    events.push(Event::Html(html.into()));
+                    code_block = None;
+                }
+                _ => {}
+            }
+            continue;
+        }
+
+        match event {
+            Event::Start(Tag::CodeBlock(kind)) => {
+                let lang = match kind {
+                    CodeBlockKind::Fenced(info) => info.to_string(),
+                    CodeBlockKind::Indented => String::new(),
+                };
+                code_block = Some((lang, String::new()));
+            }
+            Event::InlineMath(tex) if options.render_math => {
+                events.push(Event::InlineHtml(render_math(&tex, false).into()));
+            }
+            Event::DisplayMath(tex) if options.render_math => {
+                events.push(Event::Html(render_math(&tex, true).into()));
+            }
+            Event::SoftBreak if options.hard_breaks => events.push(Event::HardBreak),
+            Event::Start(Tag::Image {
+                link_type,
+                dest_url,
+                title,
+                id,
+            }) if options.embed_images => {
+                let dest_url = embed_image(&dest_url, options.base_dir.as_deref())
+                    .map(CowStr::from)
+                    .unwrap_or(dest_url);
+                events.push(Event::Start(Tag::Image {
+                    link_type,
+                    dest_url,
+                    title,
+                    id,
+                }));
+            }
+            other => events.push(other),
+        }
+    }
+
+    let mut html = String::with_capacity(source.len() * 3 / 2);
+    pulldown_cmark::html::push_html(&mut html, events.into_iter());
+    html
+}
+
+/// 渲染代码块：高亮成功时返回带内联样式的 `<pre>`，否则输出转义后的源码
+fn render_code_block(lang: &str, code: &str, options: &RenderOptions) -> String {
+    let lang_token = lang.split_whitespace().next().unwrap_or("");
+
+    if let Some(kind) = DiagramKind::from_lang(lang_token) {
+        if options.render_diagrams {
+            match diagram::render_svg(kind, code) {
+                Ok(svg) => {
+                    return format!(
+                        "<div class=\"{}-diagram\"><img src=\"{}\" alt=\"{} diagram\"></div>\n",
+                        kind.name(),
+                        diagram::svg_data_uri(&svg),
+                        kind.name()
+                    );
+                }
+                Err(e) => log::warn!("[render] Diagram not rendered: {}", e),
+            }
+        }
+        // 未渲染的图表保留源码并标记类型，供查看端脚本处理
+        return format!(
+            "<pre class=\"diagram {}\">{}</pre>\n",
+            lang_token,
+            markdown::escape_html(code)
+        );
+    }
+
+    if let Some(theme) = options.highlight_theme.as_deref() {
+        if let Some(html) = highlight::highlight_code(code, lang_token, theme) {
+            return html;
+        }
+    }
+
+    let class = if lang_token.is_empty() {
+        String::new()
+    } else {
+        format!(" class=\"language-{}\"", markdown::escape_html(lang_token))
+    };
+    format!(
+        "<pre><code{}>{}</code></pre>\n",
+        class,
+        markdown::escape_html(code)
+    )
+}
+
+/// 将 LaTeX 公式转换为 MathML，失败时保留原始公式文本
+pub fn render_math(tex: &str, display: bool) -> String {
+    match math::to_mathml(tex, display) {
+        Ok(mathml) => mathml,
+        Err(e) => {
+            log::warn!("[render] Failed to convert formula to MathML: {}", e);
+            let (open, close) = if display { ("$$", "$$") } else { ("$", "$") };
+            format!(
+                "<code class=\"math-error\">{}{}{}</code>",
+                open,
+                markdown::escape_html(tex),
+                close
+            )
+        }
+    }
+}
+
+/// 读取本地图片并编码为 data URI，远程或无法读取的图片返回 `None`
+pub fn embed_image(url: &str, base_dir: Option<&Path>) -> Option<String> {
+    if url.starts_with("http://") || url.starts_with("https://") || url.starts_with("data:") {
+        return None;
+    }
+
+    let path = resolve_local_path(url, base_dir);
+    let mime = image_mime_type(&path)?;
+    match fs::read(&path) {
+        Ok(bytes) => Some(format!(
+            "data:{};base64,{}",
+            mime,
+            base64::engine::general_purpose::STANDARD.encode(bytes)
+        )),
+        Err(e) => {
+            log::warn!("[render] Failed to embed image {:?}: {}", path, e);
+            None
+        }
+    }
+}
+
+/// 将 Markdown 中的本地链接解析为文件路径（支持 `file://` 前缀与百分号转义）
+pub fn resolve_local_path(url: &str, base_dir: Option<&Path>) -> PathBuf {
+    let raw = url.strip_prefix("file://").unwrap_or(url);
+    let raw = raw.split(['#', '?']).next().unwrap_or(raw);
+    let path = PathBuf::from(percent_decode(raw));
+    match base_dir {
+        Some(base) if path.is_relative() => base.join(path),
+        _ => path,
+    }
+}
+
+/// 根据扩展名推断图片 MIME 类型
+pub fn image_mime_type(path: &Path) -> Option<&'static str> {
+    let ext = path.extension()?.to_str()?.to_ascii_lowercase();
+    Some(match ext.as_str() {
+        "png" => "image/png",
+        "jpg" | "jpeg" => "image/jpeg",
+        "gif" => "image/gif",
+        "svg" => "image/svg+xml",
+        "webp" => "image/webp",
+        "bmp" => "image/bmp",
+        "ico" => "image/x-icon",
+        "avif" => "image/avif",
+        _ => return None,
+    })
+}
+
+/// 解码链接中的 `%20` 等转义
+pub fn percent_decode(input: &str) -> String {
+    fn hex(b: u8) -> Option<u8> {
+        (b as char).to_digit(16).map(|d| d as u8)
+    }
+
+    let bytes = input.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%' && i + 2 < bytes.len() {
+            if let (Some(hi), Some(lo)) = (hex(bytes[i + 1]), hex(bytes[i + 2])) {
+                out.push(hi << 4 | lo);
+                i += 3;
+                continue;
+            }
+        }
+        out.push(bytes[i]);
+        i += 1;
+    }
+    String::from_utf8_lossy(&out).into_owned()
+}
+
+/// MathML 元素（latex2mathml 的输出）
+const MATHML_TAGS: &[&str] = &[
+    "math",
+    "semantics",
+    "annotation",
+    "mrow",
+    "mi",
+    "mo",
+    "mn",
+    "ms",
+    "mtext",
+    "mspace",
+    "msup",
+    "msub",
+    "msubsup",
+    "mfrac",
+    "msqrt",
+    "mroot",
+    "munder",
+    "mover",
+    "munderover",
+    "mtable",
+    "mtr",
+    "mtd",
+    "mstyle",
+    "menclose",
+    "mpadded",
+    "mphantom",
+    "merror",
+];
+/// MathML 元素允许的属性
+const MATHML_ATTRIBUTES: &[&str] = &[
+    "display",
+    "mathvariant",
+    "stretchy",
+    "fence",
+    "separator",
+    "lspace",
+    "rspace",
+    "accent",
+    "accentunder",
+    "linethickness",
+    "columnalign",
+    "rowalign",
+    "encoding",
+    "xmlns",
+    "width",
+    "height",
+    "depth",
+    "notation",
+    "movablelimits",
+    "largeop",
+    "symmetric",
+    "displaystyle",
+    "scriptlevel",
+];
+/// 代码高亮的内联样式只保留这些属性
+const ALLOWED_STYLE_PROPERTIES: &[&str] = &[
+    "color",
+    "background-color",
+    "font-weight",
+    "font-style",
+    "text-decoration",
+];
+
+/// HTML 过滤白名单的追加项
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct SanitizeOptions {
+    /// 额外允许的标签
+    pub allowed_tags: Option<Vec<String>>,
+    /// 额外允许的属性：标签 → 属性列表，`*` 表示所有标签
+    pub allowed_attributes: Option<HashMap<String, Vec<String>>>,
+    /// 额外允许的链接协议（默认已包含 http、https、mailto 等常见协议）
+    pub allowed_url_schemes: Option<Vec<String>>,
+}
+
+/// 使用 ammonia 过滤 HTML
+///
+/// 在 ammonia 默认白名单的基础上放行渲染管线自身会输出的内容：MathML、代码高亮的内联颜色、
+/// Admonition 与脚注的 class / id、任务列表的复选框，以及内嵌图片的 `data:` 地址。
+pub fn sanitize_html(html: &str, options: &SanitizeOptions) -> String {
+    let mut builder = ammonia::Builder::default();
+    builder
+        .add_tags(MATHML_TAGS)
+        .add_tags(["input", "details", "summary", "mark", "kbd"])
+        .add_generic_attributes(["class", "id", "align"])
+        .add_tag_attributes("input", ["type", "checked", "disabled"])
+        .set_tag_attribute_value("input", "disabled", "")
+        .add_tag_attributes("span", ["style"])
+        .add_tag_attributes("pre", ["style"])
+        .add_tag_attributes("td", ["style"])
+        .add_tag_attributes("th", ["style"])
+        .add_tag_attributes("details", ["open"])
+        .filter_style_properties(ALLOWED_STYLE_PROPERTIES.iter().copied().collect())
+        .add_url_schemes(["data"])
+        .link_rel(Some("noopener noreferrer"))
+        .attribute_filter(|element, attribute, value| {
+            // data: 地址只允许用于图片
+            let is_data_url = value.trim_start().to_ascii_lowercase().starts_with("data:");
+            if is_data_url && !(element == "img" && attribute == "src") {
+                return None;
+            }
+            Some(value.into())
+        });
+    for tag in MATHML_TAGS {
+        builder.add_tag_attributes(tag, MATHML_ATTRIBUTES);
+    }
+
+    if let Some(tags) = &options.allowed_tags {
+        builder.add_tags(tags.iter().map(String::as_str));
+    }
+    if let Some(attributes) = &options.allowed_attributes {
+        for (tag, attrs) in attributes {
+            let attrs = attrs.iter().map(String::as_str);
+            if tag == "*" {
+                builder.add_generic_attributes(attrs);
+            } else {
+                builder.add_tag_attributes(tag.as_str(), attrs);
+            }
+        }
+    }
+    if let Some(schemes) = &options.allowed_url_schemes {
+        builder.add_url_schemes(schemes.iter().map(String::as_str));
+    }
+
+    builder.clean(html).to_string()
+}
+
+/// 预览渲染选项（均可省略）
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct RenderHtmlOptions {
+    /// 代码高亮主题：`light` / `dark` 或 syntect 主题名，默认 `light`
+    pub theme: Option<String>,
+    /// 是否高亮代码块，默认是
+    pub highlight: Option<bool>,
+    /// 是否将公式渲染为 MathML，默认是
+    pub render_math: Option<bool>,
+    /// 单个换行渲染为 `<br>`，默认是（与编辑器一致）
+    pub hard_breaks: Option<bool>,
+    /// 是否内嵌本地图片，默认否
+    pub embed_images: Option<bool>,
+    /// 是否在后端渲染图表，默认否（首次渲染需要启动外部工具，较慢）
+    pub render_diagrams: Option<bool>,
+    /// 解析相对图片路径的基准目录
+    pub base_dir: Option<String>,
+    /// 是否过滤 HTML，默认是
+    pub sanitize: Option<bool>,
+    #[serde(flatten)]
+    pub sanitize_options: SanitizeOptions,
+}
+
+impl RenderHtmlOptions {
+    fn render_options(&self) -> RenderOptions {
+        let theme = match self.theme.as_deref() {
+            None | Some("light") => highlight::LIGHT_THEME,
+            Some("dark") => highlight::DARK_THEME,
+            Some(name) => name,
+        };
+        RenderOptions {
+            highlight_theme: self.highlight.unwrap_or(true).then(|| theme.to_string()),
+            render_math: self.render_math.unwrap_or(true),
+            embed_images: self.embed_images.unwrap_or(false),
+            base_dir: self.base_dir.as_ref().map(PathBuf::from),
+            hard_breaks: self.hard_breaks.unwrap_or(true),
+            render_diagrams: self.render_diagrams.unwrap_or(false),
+        }
+    }
+}
+
+// 在后端渲染 Markdown 为（过滤后的）HTML 片段
+#[tauri::command]
+pub async fn render_html(
+    app: AppHandle,
+    content: String,
+    options: Option<RenderHtmlOptions>,
+) -> Result<String, VividError> {
+    let start = Instant::now();
+    let options = options.unwrap_or_default();
+    let size = content.len();
+
+    let html = tauri::async_runtime::spawn_blocking(move || {
+        let content = bibliography::resolve_citations(&app, content);
+        let content = app.state::<PluginHost>().render_hooks(content);
+        let html = markdown_to_html(&content, &options.render_options());
+        if options.sanitize.unwrap_or(true) {
+            sanitize_html(&html, &options.sanitize_options)
+        } else {
+            html
+        }
+    })
+    .await
+    .map_err(|e| format!("Render task failed: {}", e))?;
+
+    log::debug!(
+        "[render_html] Rendered {} bytes -> {} bytes in {:?}",
+        size,
+        html.len(),
+        start.elapsed()
+    );
+    Ok(html)
+}
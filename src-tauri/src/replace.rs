@@ -0,0 +1,356 @@
+//! 工作区查找替换
+//!
+//! `replace_in_workspace` 分两步使用：不带 `accept` 调用时只返回预览（每个文件的匹配与替换结果），
+//! 用户勾选后带上 `accept` 再调用一次，只替换选中的匹配。
+//! 应用时按文件校验修订标记，预览之后被修改过的文件会跳过；每个文件先写临时文件再重命名，
+//! 保证要么全部替换、要么保持原样。正则模式下替换文本支持 `$1` / `${name}` 捕获组。
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+
+use regex::{Captures, Regex, RegexBuilder};
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager};
+
+use crate::access::{self, AccessKind};
+use crate::backup::BackupStore;
+use crate::error::VividError;
+use crate::largefile::LARGE_FILE_THRESHOLD;
+use crate::parse::{utf16_offset, LineIndex};
+use crate::settings::SettingsStore;
+use crate::{revision, storage, workspace};
+
+/// 预览最多返回的匹配数，超出后不再继续扫描
+const MAX_PREVIEW_MATCHES: usize = 10_000;
+/// 预览中每行上下文的最大字符数
+const MAX_CONTEXT_CHARS: usize = 200;
+
+/// 用户确认要替换的匹配
+#[derive(Debug, Clone, Deserialize)]
+pub struct AcceptedFile {
+    pub path: String,
+    /// 预览时返回的修订标记
+    pub revision: String,
+    /// 要替换的匹配序号（预览中的 `index`）
+    pub matches: Vec<usize>,
+}
+
+/// 查找替换选项
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct ReplaceOptions {
+    /// 将 `query` 作为正则表达式
+    pub regex: bool,
+    pub case_sensitive: bool,
+    pub whole_word: bool,
+    /// 为空时只返回预览，否则替换其中列出的匹配
+    pub accept: Option<Vec<AcceptedFile>>,
+}
+
+/// 一处匹配
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ReplaceMatch {
+    /// 在文件中的序号，应用替换时用来指定匹配
+    pub index: usize,
+    /// 所在行（从 0 开始）
+    pub line_index: usize,
+    /// 起止位置的 UTF-16 偏移
+    pub char_index: usize,
+    pub char_end: usize,
+    pub matched: String,
+    pub replacement: String,
+    /// 匹配所在行的原文
+    pub line: String,
+    /// 该行替换后的样子
+    pub preview: String,
+}
+
+/// 单个文件的预览或替换结果
+#[derive(Debug, Serialize, Deserialize)]
+pub struct FileReplacement {
+    pub path: String,
+    /// 文件当前（替换后）的修订标记
+    pub revision: String,
+    pub matches: Vec<ReplaceMatch>,
+    /// 实际替换的数量，预览时为 0
+    pub replaced: usize,
+    /// 文件被跳过的原因
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ReplaceResult {
+    /// 是否已写入文件（否则为预览）
+    pub applied: bool,
+    pub files: Vec<FileReplacement>,
+    pub total_matches: usize,
+    /// 匹配过多，预览被截断
+    pub truncated: bool,
+}
+
+fn build_regex(query: &str, options: &ReplaceOptions) -> Result<Regex, String> {
+    if query.is_empty() {
+        return Err("Search query is empty".to_string());
+    }
+    let mut pattern = if options.regex {
+        query.to_string()
+    } else {
+        regex::escape(query)
+    };
+    if options.whole_word {
+        pattern = format!(r"\b(?:{})\b", pattern);
+    }
+    RegexBuilder::new(&pattern)
+        .case_insensitive(!options.case_sensitive)
+        .multi_line(true)
+        .build()
+        .map_err(|e| format!("Invalid regular expression: {}", e))
+}
+
+/// 单个匹配的替换文本：正则模式展开捕获组，普通模式原样使用
+fn expand(caps: &Captures<'_>, replacement: &str, regex_mode: bool) -> String {
+    if regex_mode {
+        let mut out = String::new();
+        caps.expand(replacement, &mut out);
+        out
+    } else {
+        replacement.to_string()
+    }
+}
+
+/// 截取过长的上下文
+fn clip(line: &str) -> String {
+    match line.char_indices().nth(MAX_CONTEXT_CHARS) {
+        Some((i, _)) => format!("{}…", &line[..i]),
+        None => line.to_string(),
+    }
+}
+
+/// 查找文件中的所有匹配
+fn find_matches(
+    content: &str,
+    regex: &Regex,
+    replacement: &str,
+    regex_mode: bool,
+) -> Vec<(std::ops::Range<usize>, String)> {
+    regex
+        .captures_iter(content)
+        .filter_map(|caps| {
+            let m = caps.get(0)?;
+            // 空匹配（如 `^`）没有可替换的内容
+            (!m.is_empty()).then(|| (m.range(), expand(&caps, replacement, regex_mode)))
+        })
+        .collect()
+}
+
+/// 在内容上替换指定的匹配
+fn apply_matches(content: &str, matches: &[(std::ops::Range<usize>, String)]) -> String {
+    let mut out = String::with_capacity(content.len());
+    let mut last = 0;
+    for (range, replacement) in matches {
+        out.push_str(&content[last..range.start]);
+        out.push_str(replacement);
+        last = range.end;
+    }
+    out.push_str(&content[last..]);
+    out
+}
+
+fn preview_file(
+    path: &Path,
+    content: &str,
+    revision: String,
+    matches: Vec<(std::ops::Range<usize>, String)>,
+) -> FileReplacement {
+    let lines = LineIndex::new(content);
+    let preview_matches = matches
+        .into_iter()
+        .enumerate()
+        .map(|(index, (range, replacement))| {
+            let line_start = content[..range.start].rfind('\n').map_or(0, |i| i + 1);
+            let line_end = content[range.start..]
+                .find('\n')
+                .map_or(content.len(), |i| range.start + i);
+            let line = &content[line_start..line_end];
+            let preview = if range.end <= line_end {
+                format!(
+                    "{}{}{}",
+                    &content[line_start..range.start],
+                    replacement,
+                    &content[range.end..line_end]
+                )
+            } else {
+                format!("{}{}", &content[line_start..range.start], replacement)
+            };
+            ReplaceMatch {
+                index,
+                line_index: lines.line_of(range.start),
+                char_index: utf16_offset(content, range.start),
+                char_end: utf16_offset(content, range.end),
+                matched: content[range.clone()].to_string(),
+                replacement,
+                line: clip(line),
+                preview: clip(&preview),
+            }
+        })
+        .collect();
+    FileReplacement {
+        path: path.to_string_lossy().to_string(),
+        revision,
+        matches: preview_matches,
+        replaced: 0,
+        error: None,
+    }
+}
+
+/// 读取文本文件及其修订标记，大文件和非 UTF-8 文件跳过
+fn read_text(path: &Path) -> Result<(String, String), String> {
+    let (bytes, revision) = revision::current_revision(path)
+        .map_err(|e| format!("Failed to read file: {}", e))?
+        .ok_or("File does not exist")?;
+    if bytes.len() as u64 > LARGE_FILE_THRESHOLD {
+        return Err("File is too large".to_string());
+    }
+    let content = String::from_utf8(bytes).map_err(|_| "File is not valid UTF-8")?;
+    Ok((content, revision))
+}
+
+fn preview(root: &Path, regex: &Regex, replacement: &str, regex_mode: bool) -> ReplaceResult {
+    let mut result = ReplaceResult {
+        applied: false,
+        files: Vec::new(),
+        total_matches: 0,
+        truncated: false,
+    };
+    for path in workspace::markdown_files(root) {
+        if result.total_matches >= MAX_PREVIEW_MATCHES {
+            result.truncated = true;
+            break;
+        }
+        let Ok((content, revision)) = read_text(&path) else {
+            continue;
+        };
+        let mut matches = find_matches(&content, regex, replacement, regex_mode);
+        if matches.is_empty() {
+            continue;
+        }
+        let remaining = MAX_PREVIEW_MATCHES - result.total_matches;
+        if matches.len() > remaining {
+            matches.truncate(remaining);
+            result.truncated = true;
+        }
+        result.total_matches += matches.len();
+        result
+            .files
+            .push(preview_file(&path, &content, revision, matches));
+    }
+    result
+}
+
+/// 替换单个文件中选中的匹配
+fn apply_file(
+    app: &AppHandle,
+    accepted: &AcceptedFile,
+    regex: &Regex,
+    replacement: &str,
+    regex_mode: bool,
+) -> FileReplacement {
+    let path = PathBuf::from(&accepted.path);
+    let mut result = FileReplacement {
+        path: accepted.path.clone(),
+        revision: accepted.revision.clone(),
+        matches: Vec::new(),
+        replaced: 0,
+        error: None,
+    };
+
+    let (content, disk_revision) = match read_text(&path) {
+        Ok(read) => read,
+        Err(e) => {
+            result.error = Some(e);
+            return result;
+        }
+    };
+    if !revision::same_content(&accepted.revision, &disk_revision) {
+        result.revision = disk_revision;
+        result.error = Some("File was modified since the preview".to_string());
+        return result;
+    }
+
+    let selected: Vec<_> = find_matches(&content, regex, replacement, regex_mode)
+        .into_iter()
+        .enumerate()
+        .filter(|(index, _)| accepted.matches.contains(index))
+        .map(|(_, m)| m)
+        .collect();
+    if selected.is_empty() {
+        return result;
+    }
+    let updated = apply_matches(&content, &selected);
+
+    let settings = app.state::<SettingsStore>().get();
+    app.state::<BackupStore>()
+        .before_save(&settings.backup, &path);
+    if let Err(e) = storage::write_atomic(&path, updated.as_bytes()) {
+        log::error!("[replace_in_workspace] {}", e);
+        result.error = Some(e);
+        return result;
+    }
+    result.replaced = selected.len();
+    result.revision = revision::revision_for(updated.as_bytes(), fs::metadata(&path).ok().as_ref());
+    result
+}
+
+// 在工作区中查找替换：不带 `options.accept` 时返回预览，否则替换选中的匹配
+#[tauri::command]
+pub async fn replace_in_workspace(
+    app: AppHandle,
+    root: String,
+    query: String,
+    replacement: String,
+    options: Option<ReplaceOptions>,
+) -> Result<ReplaceResult, VividError> {
+    let start = Instant::now();
+    let options = options.unwrap_or_default();
+    let regex = build_regex(&query, &options)?;
+    let root = PathBuf::from(root);
+    let kind = if options.accept.is_some() {
+        AccessKind::Write
+    } else {
+        AccessKind::Read
+    };
+    access::ensure_access(&app, &root, kind)?;
+    if !root.is_dir() {
+        return Err(VividError::dir_not_found(&root));
+    }
+
+    let result = tauri::async_runtime::spawn_blocking(move || match &options.accept {
+        None => preview(&root, &regex, &replacement, options.regex),
+        Some(accepted) => {
+            let files: Vec<FileReplacement> = accepted
+                .iter()
+                // 只允许替换工作区内的文件
+                .filter(|file| Path::new(&file.path).starts_with(&root))
+                .map(|file| apply_file(&app, file, &regex, &replacement, options.regex))
+                .collect();
+            ReplaceResult {
+                applied: true,
+                total_matches: files.iter().map(|f| f.replaced).sum(),
+                files,
+                truncated: false,
+            }
+        }
+    })
+    .await
+    .map_err(|e| format!("Replace task failed: {}", e))?;
+
+    log::info!(
+        "[replace_in_workspace] ✓ {}: {} match(es) in {} file(s) in {:?}",
+        if result.applied { "Applied" } else { "Preview" },
+        result.total_matches,
+        result.files.len(),
+        start.elapsed()
+    );
+    Ok(result)
+}
@@ -0,0 +1,75 @@
+//! 瞬时 IO 错误重试
+//!
+//! 网络盘和 OneDrive 等同步目录中的文件可能短暂被其它进程占用（Windows 共享冲突、
+//! 杀毒软件扫描时的拒绝访问），过一会儿再写就能成功。这类错误按指数退避重试，
+//! 其它错误（磁盘已满、路径不存在、只读文件系统等）立即返回。
+
+use std::io;
+use std::thread;
+use std::time::Duration;
+
+use crate::settings::SaveRetrySettings;
+
+/// 是否为值得重试的瞬时错误
+pub fn is_transient(e: &io::Error) -> bool {
+    if matches!(
+        e.kind(),
+        io::ErrorKind::Interrupted | io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut
+    ) {
+        return true;
+    }
+    #[cfg(windows)]
+    {
+        // ERROR_ACCESS_DENIED（文件正被扫描或替换）、ERROR_SHARING_VIOLATION、ERROR_LOCK_VIOLATION
+        if matches!(e.raw_os_error(), Some(5 | 32 | 33)) {
+            return true;
+        }
+    }
+    #[cfg(unix)]
+    {
+        // EBUSY、ETXTBSY
+        if matches!(e.raw_os_error(), Some(16 | 26)) {
+            return true;
+        }
+    }
+    false
+}
+
+/// 第 `attempt` 次失败（从 1 开始）后的等待时间
+pub fn backoff_delay(policy: &SaveRetrySettings, attempt: u32) -> Duration {
+    let factor = 1u64 << attempt.saturating_sub(1).min(16);
+    Duration::from_millis(
+        policy
+            .initial_delay_ms
+            .saturating_mul(factor)
+            .min(policy.max_delay_ms),
+    )
+}
+
+/// 执行 `op`，遇到瞬时错误时按退避策略重试，返回最终结果和尝试次数
+pub fn with_retry<T, F>(policy: &SaveRetrySettings, label: &str, mut op: F) -> (io::Result<T>, u32)
+where
+    F: FnMut() -> io::Result<T>,
+{
+    let max_attempts = policy.max_attempts.max(1);
+    let mut attempt = 1;
+    loop {
+        match op() {
+            Ok(value) => return (Ok(value), attempt),
+            Err(e) if attempt < max_attempts && is_transient(&e) => {
+                let delay = backoff_delay(policy, attempt);
+                log::warn!(
+                    "[{}] Transient error on attempt {}/{}: {}, retrying in {:?}",
+                    label,
+                    attempt,
+                    max_attempts,
+                    e,
+                    delay
+                );
+                thread::sleep(delay);
+                attempt += 1;
+            }
+            Err(e) => return (Err(e), attempt),
+        }
+    }
+}
@@ -0,0 +1,51 @@
+//! 文件修订标记
+//!
+//! 读取文件时返回 `revision`（修改时间 + 内容哈希），保存时由前端带回，
+//! 用于发现文件在打开之后被其它程序修改。
+
+use std::fs;
+use std::path::Path;
+use std::time::UNIX_EPOCH;
+
+use crate::assets::content_hash;
+
+/// 修订标记中保留的哈希长度
+const HASH_LEN: usize = 16;
+
+/// 根据内容与修改时间生成修订标记：`<mtime 纳秒>-<内容哈希>`
+pub fn revision_for(content: &[u8], metadata: Option<&fs::Metadata>) -> String {
+    revision_with_hash(&content_hash(content), metadata)
+}
+
+/// 根据已计算好的 SHA-256 十六进制摘要生成修订标记（用于分块读取的大文件）
+pub fn revision_with_hash(hash: &str, metadata: Option<&fs::Metadata>) -> String {
+    let mtime = metadata
+        .and_then(|m| m.modified().ok())
+        .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+        .map(|d| d.as_nanos())
+        .unwrap_or(0);
+    format!("{:x}-{}", mtime, &hash[..HASH_LEN.min(hash.len())])
+}
+
+/// 读取磁盘上的当前内容与修订标记，文件不存在时返回 `None`
+pub fn current_revision(path: &Path) -> std::io::Result<Option<(Vec<u8>, String)>> {
+    match fs::read(path) {
+        Ok(bytes) => {
+            let metadata = fs::metadata(path).ok();
+            let revision = revision_for(&bytes, metadata.as_ref());
+            Ok(Some((bytes, revision)))
+        }
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(e),
+    }
+}
+
+/// 判断两个修订标记是否对应相同的内容
+///
+/// 只比较内容哈希：文件被 `touch` 或被其它程序以相同内容重写时不算冲突。
+pub fn same_content(a: &str, b: &str) -> bool {
+    fn hash_part(revision: &str) -> &str {
+        revision.rsplit('-').next().unwrap_or(revision)
+    }
+    hash_part(a) == hash_part(b)
+}
@@ -0,0 +1,443 @@
+//! 工作区全文搜索
+//!
+//! 索引保存在应用数据目录 `search/` 下的 SQLite 数据库中（FTS5），每个工作区一个文件。
+//! 重新打开工作区时只重新索引修改时间或大小发生变化的文件，之后随文件监听增量更新。
+//! 除 Markdown 文档外，PDF 和 Word 附件也按提取出的文本（见 `extract`）建立索引。
+//!
+//! FTS5 的 unicode61 分词器会把连续的 CJK 字符当作一个词，写入和查询前把每个 CJK 字符
+//! 拆成单独的词，查询时按短语匹配，从而支持任意长度的中文子串搜索。
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{Instant, UNIX_EPOCH};
+
+use pulldown_cmark::{Event, TagEnd};
+use regex::{Regex, RegexBuilder};
+use rusqlite::{params, Connection, OptionalExtension, Transaction};
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager};
+
+use crate::assets;
+use crate::error::VividError;
+use crate::extract;
+use crate::frontmatter;
+use crate::largefile::LARGE_FILE_THRESHOLD;
+use crate::markdown::{self, is_cjk_char};
+use crate::parse;
+use crate::perf;
+use crate::workspace::{self, FileIndex};
+
+/// 数据库结构版本，不一致时丢弃旧索引重建
+const SCHEMA_VERSION: i64 = 1;
+const DEFAULT_LIMIT: usize = 50;
+const MAX_LIMIT: usize = 500;
+/// 摘要中匹配前后保留的字符数
+const SNIPPET_CONTEXT: usize = 60;
+
+/// 一条搜索结果
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SearchHit {
+    pub path: String,
+    pub title: String,
+    /// 相关度，越大越相关
+    pub score: f64,
+    /// 第一处匹配附近的文本
+    pub snippet: String,
+    /// 匹配在 `snippet` 中的 UTF-16 区间
+    pub highlights: Vec<[usize; 2]>,
+}
+
+/// 工作区全文索引
+pub struct SearchIndex {
+    dir: PathBuf,
+    db: Mutex<Option<Connection>>,
+}
+
+/// 在每个 CJK 字符两侧加空格，使分词器把它们拆成单独的词
+fn segment(text: &str) -> String {
+    let mut out = String::with_capacity(text.len() + text.len() / 2);
+    for c in text.chars() {
+        if is_cjk_char(c) {
+            out.push(' ');
+            out.push(c);
+            out.push(' ');
+        } else {
+            out.push(c);
+        }
+    }
+    out
+}
+
+/// 文档正文的纯文本（去掉 front matter 和 Markdown 标记），块之间换行
+pub(crate) fn plain_text(body: &str) -> String {
+    let mut text = String::with_capacity(body.len());
+    for event in markdown::parser(body) {
+        match event {
+            Event::Text(t) | Event::Code(t) | Event::InlineMath(t) | Event::DisplayMath(t) => {
+                text.push_str(&t)
+            }
+            Event::SoftBreak => text.push(' '),
+            Event::HardBreak => text.push('\n'),
+            Event::End(
+                TagEnd::Paragraph
+                | TagEnd::Heading(_)
+                | TagEnd::Item
+                | TagEnd::CodeBlock
+                | TagEnd::TableCell
+                | TagEnd::TableRow,
+            ) if !text.ends_with('\n') => text.push('\n'),
+            _ => {}
+        }
+    }
+    text
+}
+
+/// 文件修改时间（毫秒）和大小，用于判断是否需要重新索引
+pub(crate) fn file_stamp(path: &Path) -> Option<(i64, i64)> {
+    let meta = fs::metadata(path).ok()?;
+    let modified = meta
+        .modified()
+        .ok()
+        .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+        .map_or(0, |d| d.as_millis() as i64);
+    Some((modified, meta.len() as i64))
+}
+
+fn init_schema(conn: &Connection) -> rusqlite::Result<()> {
+    let version: i64 = conn.query_row("PRAGMA user_version", [], |row| row.get(0))?;
+    if version != SCHEMA_VERSION {
+        conn.execute_batch("DROP TABLE IF EXISTS files; DROP TABLE IF EXISTS files_fts;")?;
+    }
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS files (
+            id INTEGER PRIMARY KEY,
+            path TEXT NOT NULL UNIQUE,
+            modified INTEGER NOT NULL,
+            size INTEGER NOT NULL,
+            title TEXT NOT NULL,
+            body TEXT NOT NULL
+        );
+        CREATE VIRTUAL TABLE IF NOT EXISTS files_fts
+            USING fts5(name, title, body, tokenize = 'unicode61 remove_diacritics 2');",
+    )?;
+    conn.pragma_update(None, "user_version", SCHEMA_VERSION)
+}
+
+fn remove_file(tx: &Transaction<'_>, path: &str) -> rusqlite::Result<()> {
+    let id: Option<i64> = tx
+        .query_row("SELECT id FROM files WHERE path = ?1", [path], |row| {
+            row.get(0)
+        })
+        .optional()?;
+    if let Some(id) = id {
+        tx.execute("DELETE FROM files_fts WHERE rowid = ?1", [id])?;
+        tx.execute("DELETE FROM files WHERE id = ?1", [id])?;
+    }
+    Ok(())
+}
+
+/// 重新索引单个文件（Markdown 或可提取文本的附件），无法读取或过大时从索引中移除
+fn index_file(tx: &Transaction<'_>, path: &Path) -> rusqlite::Result<()> {
+    let key = path.to_string_lossy();
+    remove_file(tx, &key)?;
+    let Some((modified, size)) = file_stamp(path) else {
+        return Ok(());
+    };
+    let name = path
+        .file_stem()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_default();
+
+    let (title, text) = if extract::is_extractable(path) {
+        // 附件以文件名（含扩展名）为标题，便于和同名文档区分
+        match extract::extract(path) {
+            Ok(extracted) => (crate::file_name_of(path), extracted.text),
+            Err(e) => {
+                log::debug!("[search] Skipping {}: {}", path.display(), e);
+                return Ok(());
+            }
+        }
+    } else {
+        if size as u64 > LARGE_FILE_THRESHOLD {
+            return Ok(());
+        }
+        let Ok(content) = fs::read_to_string(path) else {
+            return Ok(());
+        };
+        let body_start =
+            frontmatter::find_front_matter(&content).map_or(0, |block| block.body_start);
+        let body = &content[body_start..];
+        let title = parse::outline(body)
+            .into_iter()
+            .find(|heading| heading.level == 1)
+            .map(|heading| heading.text)
+            .unwrap_or_else(|| name.clone());
+        (title, plain_text(body))
+    };
+
+    tx.execute(
+        "INSERT INTO files (path, modified, size, title, body) VALUES (?1, ?2, ?3, ?4, ?5)",
+        params![key, modified, size, title, text],
+    )?;
+    tx.execute(
+        "INSERT INTO files_fts (rowid, name, title, body) VALUES (?1, ?2, ?3, ?4)",
+        params![
+            tx.last_insert_rowid(),
+            segment(&name),
+            segment(&title),
+            segment(&text)
+        ],
+    )?;
+    Ok(())
+}
+
+/// 把用户输入转换为 FTS5 查询：每个词按短语匹配（词之间为“与”），非 CJK 词按前缀匹配
+fn fts_query(query: &str) -> Option<String> {
+    let terms: Vec<String> = query
+        .split_whitespace()
+        .filter(|term| term.chars().any(char::is_alphanumeric))
+        .map(|term| {
+            let phrase = format!("\"{}\"", segment(term).trim().replace('"', "\"\""));
+            if markdown::contains_cjk(term) {
+                phrase
+            } else {
+                format!("{}*", phrase)
+            }
+        })
+        .collect();
+    (!terms.is_empty()).then(|| terms.join(" "))
+}
+
+/// 匹配任一查询词（忽略大小写）的正则，用于生成摘要
+fn terms_regex(query: &str) -> Option<Regex> {
+    let alternatives: Vec<String> = query.split_whitespace().map(regex::escape).collect();
+    if alternatives.is_empty() {
+        return None;
+    }
+    RegexBuilder::new(&alternatives.join("|"))
+        .case_insensitive(true)
+        .build()
+        .ok()
+}
+
+/// 截取第一处匹配前后的文本作为摘要，没有匹配时取正文开头
+fn snippet(body: &str, terms: Option<&Regex>) -> (String, Vec<[usize; 2]>) {
+    let first = terms.and_then(|re| re.find(body));
+    let (start, end) = match first {
+        Some(m) => {
+            let start = body[..m.start()]
+                .char_indices()
+                .rev()
+                .nth(SNIPPET_CONTEXT - 1)
+                .map_or(0, |(i, _)| i);
+            let end = body[m.end()..]
+                .char_indices()
+                .nth(SNIPPET_CONTEXT)
+                .map_or(body.len(), |(i, _)| m.end() + i);
+            (start, end)
+        }
+        None => (
+            0,
+            body.char_indices()
+                .nth(SNIPPET_CONTEXT * 2)
+                .map_or(body.len(), |(i, _)| i),
+        ),
+    };
+
+    let prefix = if start > 0 { "…" } else { "" };
+    let suffix = if end < body.len() { "…" } else { "" };
+    let window = &body[start..end];
+    let offset = prefix.encode_utf16().count();
+    let highlights = terms
+        .map(|re| {
+            re.find_iter(window)
+                .map(|m| {
+                    [
+                        offset + parse::utf16_offset(window, m.start()),
+                        offset + parse::utf16_offset(window, m.end()),
+                    ]
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+    // 换行替换为空格，长度不变
+    let text = format!("{}{}{}", prefix, window.replace('\n', " "), suffix);
+    (text, highlights)
+}
+
+impl SearchIndex {
+    pub fn new(dir: PathBuf) -> Self {
+        SearchIndex {
+            dir,
+            db: Mutex::new(None),
+        }
+    }
+
+    /// 打开（必要时创建）工作区对应的索引数据库
+    pub fn open(&self, root: &Path) -> Result<(), String> {
+        let hash = assets::content_hash(root.to_string_lossy().as_bytes());
+        fs::create_dir_all(&self.dir).map_err(|e| format!("Failed to create directory: {}", e))?;
+        let path = self.dir.join(format!("{}.sqlite", &hash[..16]));
+        let conn = Connection::open(&path)
+            .and_then(|conn| init_schema(&conn).map(|_| conn))
+            .map_err(|e| format!("Failed to open search index: {}", e))?;
+        log::debug!("[search] Opened index {:?} for {}", path, root.display());
+        *self.db.lock().map_err(|e| e.to_string())? = Some(conn);
+        Ok(())
+    }
+
+    /// 在事务中修改索引，未打开工作区时忽略，出错时记录日志
+    fn update(&self, f: impl FnOnce(&Transaction<'_>) -> rusqlite::Result<()>) {
+        let Ok(mut db) = self.db.lock() else {
+            return;
+        };
+        let Some(conn) = db.as_mut() else {
+            return;
+        };
+        let result = conn.transaction().and_then(|tx| {
+            f(&tx)?;
+            tx.commit()
+        });
+        if let Err(e) = result {
+            log::warn!("[search] Failed to update index: {}", e);
+        }
+    }
+
+    fn query(&self, query: &str, limit: usize) -> Result<Vec<SearchHit>, String> {
+        let Some(fts) = fts_query(query) else {
+            return Ok(Vec::new());
+        };
+        let terms = terms_regex(query);
+        let db = self.db.lock().map_err(|e| e.to_string())?;
+        let conn = db.as_ref().ok_or("No workspace is open")?;
+
+        let mut stmt = conn
+            .prepare_cached(
+                "SELECT f.path, f.title, f.body, bm25(files_fts, 10.0, 5.0, 1.0) AS rank
+                 FROM files_fts JOIN files f ON f.id = files_fts.rowid
+                 WHERE files_fts MATCH ?1
+                 ORDER BY rank
+                 LIMIT ?2",
+            )
+            .map_err(|e| format!("Search failed: {}", e))?;
+        let rows = stmt
+            .query_map(params![fts, limit as i64], |row| {
+                Ok((
+                    row.get::<_, String>(0)?,
+                    row.get::<_, String>(1)?,
+                    row.get::<_, String>(2)?,
+                    row.get::<_, f64>(3)?,
+                ))
+            })
+            .map_err(|e| format!("Search failed: {}", e))?;
+
+        let mut hits = Vec::new();
+        for row in rows {
+            let (path, title, body, rank) = row.map_err(|e| format!("Search failed: {}", e))?;
+            let (snippet, highlights) = snippet(&body, terms.as_ref());
+            hits.push(SearchHit {
+                path,
+                title,
+                // bm25 越小越相关
+                score: -rank,
+                snippet,
+                highlights,
+            });
+        }
+        Ok(hits)
+    }
+}
+
+impl FileIndex for SearchIndex {
+    fn accepts(&self, path: &Path) -> bool {
+        workspace::is_markdown(path) || extract::is_extractable(path)
+    }
+
+    fn rebuild(&self, paths: &[PathBuf]) {
+        self.update(|tx| {
+            let mut indexed: HashMap<String, (i64, i64)> = HashMap::new();
+            {
+                let mut stmt = tx.prepare("SELECT path, modified, size FROM files")?;
+                let rows = stmt.query_map([], |row| {
+                    Ok((row.get::<_, String>(0)?, (row.get(1)?, row.get(2)?)))
+                })?;
+                for row in rows {
+                    let (path, stamp) = row?;
+                    indexed.insert(path, stamp);
+                }
+            }
+
+            let mut updated = 0;
+            for path in paths {
+                let stamp = indexed.remove(path.to_string_lossy().as_ref());
+                if stamp.is_none() || stamp != file_stamp(path) {
+                    index_file(tx, path)?;
+                    updated += 1;
+                }
+            }
+            // 剩下的是已删除的文件
+            for path in indexed.keys() {
+                remove_file(tx, path)?;
+            }
+            log::debug!(
+                "[search] Re-indexed {} of {} file(s), removed {}",
+                updated,
+                paths.len(),
+                indexed.len()
+            );
+            Ok(())
+        });
+    }
+
+    fn refresh(&self, path: &Path) {
+        self.update(|tx| index_file(tx, path));
+    }
+
+    fn remove_under(&self, path: &Path) {
+        self.update(|tx| {
+            let paths: Vec<String> = {
+                let mut stmt = tx.prepare("SELECT path FROM files")?;
+                let rows = stmt.query_map([], |row| row.get::<_, String>(0))?;
+                rows.collect::<rusqlite::Result<Vec<String>>>()?
+            };
+            for file in paths.iter().filter(|p| Path::new(p).starts_with(path)) {
+                remove_file(tx, file)?;
+            }
+            Ok(())
+        });
+    }
+
+    /// 关闭数据库，索引文件保留供下次打开时复用
+    fn clear(&self) {
+        if let Ok(mut db) = self.db.lock() {
+            *db = None;
+        }
+    }
+}
+
+// 在工作区全文索引中搜索，按相关度排序
+#[tauri::command]
+pub async fn query_index(
+    app: AppHandle,
+    query: String,
+    limit: Option<usize>,
+) -> Result<Vec<SearchHit>, VividError> {
+    let start = Instant::now();
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).clamp(1, MAX_LIMIT);
+    let target = query.clone();
+    let hits = tauri::async_runtime::spawn_blocking(move || {
+        app.state::<SearchIndex>().query(&query, limit)
+    })
+    .await
+    .map_err(|e| format!("Search task failed: {}", e))?
+    .map_err(|e| {
+        log::error!("[query_index] {}", e);
+        e
+    })?;
+    let elapsed = start.elapsed();
+    perf::record(perf::Operation::Search, &target, elapsed, 0);
+    log::debug!("[query_index] {} result(s) in {:?}", hits.len(), elapsed);
+    Ok(hits)
+}
@@ -0,0 +1,75 @@
+//! 系统钥匙串
+//!
+//! 加密口令、同步令牌、发布 API 密钥等敏感信息保存在系统钥匙串中
+//! （macOS Keychain / Windows 凭据管理器 / Linux Secret Service），不写入明文设置文件。
+//! 所有条目使用同一个服务名，`key` 作为账户名区分。
+
+use keyring::{Entry, Error};
+
+use crate::error::VividError;
+
+/// 钥匙串中的服务名
+const SERVICE: &str = "com.vividmark.app";
+
+fn entry(key: &str) -> Result<Entry, String> {
+    if key.trim().is_empty() {
+        return Err("Secret key must not be empty".to_string());
+    }
+    Entry::new(SERVICE, key).map_err(|e| format!("Failed to access keychain: {}", e))
+}
+
+/// 写入或覆盖一项密钥
+pub fn store(key: &str, value: &str) -> Result<(), String> {
+    entry(key)?
+        .set_password(value)
+        .map_err(|e| format!("Failed to store secret: {}", e))
+}
+
+/// 读取一项密钥，不存在时返回 `None`
+pub fn get(key: &str) -> Result<Option<String>, String> {
+    match entry(key)?.get_password() {
+        Ok(value) => Ok(Some(value)),
+        Err(Error::NoEntry) => Ok(None),
+        Err(e) => Err(format!("Failed to read secret: {}", e)),
+    }
+}
+
+/// 删除一项密钥，返回是否存在
+pub fn delete(key: &str) -> Result<bool, String> {
+    match entry(key)?.delete_credential() {
+        Ok(()) => Ok(true),
+        Err(Error::NoEntry) => Ok(false),
+        Err(e) => Err(format!("Failed to delete secret: {}", e)),
+    }
+}
+
+// 保存密钥到系统钥匙串
+#[tauri::command]
+pub fn store_secret(key: String, value: String) -> Result<(), VividError> {
+    store(&key, &value).map_err(|e| {
+        log::error!("[store_secret] {}: {}", key, e);
+        e
+    })?;
+    log::info!("[store_secret] ✓ Success: {}", key);
+    Ok(())
+}
+
+// 从系统钥匙串读取密钥
+#[tauri::command]
+pub fn get_secret(key: String) -> Result<Option<String>, VividError> {
+    get(&key).map_err(|e| {
+        log::error!("[get_secret] {}: {}", key, e);
+        VividError::from(e)
+    })
+}
+
+// 从系统钥匙串删除密钥
+#[tauri::command]
+pub fn delete_secret(key: String) -> Result<bool, VividError> {
+    let existed = delete(&key).map_err(|e| {
+        log::error!("[delete_secret] {}: {}", key, e);
+        e
+    })?;
+    log::info!("[delete_secret] {} (existed: {})", key, existed);
+    Ok(existed)
+}
@@ -0,0 +1,425 @@
+//! 按大纲重组文档
+//!
+//! 一个章节是从某个标题开始、到下一个同级或更高级标题之前的全部内容。
+//! 这里提供移动章节、调整标题级别、把章节拆分为新文件等操作，直接在磁盘文件上修改，
+//! 返回修改后的完整内容和新的大纲供前端刷新编辑器。
+
+use std::fs;
+use std::ops::{Range, RangeInclusive};
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, State};
+
+use crate::access::{self, AccessKind};
+use crate::assets;
+use crate::backup::BackupStore;
+use crate::error::VividError;
+use crate::frontmatter;
+use crate::markdown::{apply_edits, line_start, Replacement};
+use crate::parse::{self, Heading};
+use crate::revision;
+use crate::settings::SettingsStore;
+use crate::storage;
+
+/// 重组操作的结果
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SectionEditResult {
+    /// 修改后的完整文档内容
+    pub content: String,
+    /// 写回后的修订标记
+    pub revision: String,
+    /// 修改后的大纲
+    pub outline: Vec<Heading>,
+    /// `extract_section_to_file` 新建的文件
+    pub created: Option<String>,
+}
+
+/// 读取文档；提供 `base_revision` 时要求磁盘内容未被修改
+fn load(path: &Path, base_revision: Option<&str>) -> Result<String, String> {
+    let (bytes, disk_revision) = revision::current_revision(path)
+        .map_err(|e| format!("Failed to read file: {}", e))?
+        .ok_or_else(|| format!("File does not exist: {}", path.display()))?;
+    if let Some(base) = base_revision {
+        if !revision::same_content(base, &disk_revision) {
+            return Err("File was modified since it was opened".to_string());
+        }
+    }
+    String::from_utf8(bytes).map_err(|_| "File is not valid UTF-8".to_string())
+}
+
+/// 正文中的标题（front matter 的分隔线可能被误识别为 Setext 标题）
+fn body_headings(content: &str) -> Vec<Heading> {
+    let body_start = frontmatter::find_front_matter(content).map_or(0, |block| block.body_start);
+    parse::outline(content)
+        .into_iter()
+        .filter(|heading| heading.start >= body_start)
+        .collect()
+}
+
+fn find_heading<'a>(headings: &'a [Heading], slug: &str) -> Result<&'a Heading, String> {
+    headings
+        .iter()
+        .find(|heading| heading.slug == slug)
+        .ok_or_else(|| format!("Heading not found: {}", slug))
+}
+
+/// 标题所在章节的字节区间（从标题行开始，到下一个同级或更高级标题所在行之前）
+fn section_range(content: &str, headings: &[Heading], heading: &Heading) -> Range<usize> {
+    let end = headings
+        .iter()
+        .filter(|h| h.start > heading.start && h.level <= heading.level)
+        .map(|h| line_start(content, h.start))
+        .next()
+        .unwrap_or(content.len());
+    line_start(content, heading.start)..end
+}
+
+/// 把标题改为指定级别，返回需要替换的区间和新文本；Setext 标题改写为 ATX 形式
+fn relevel(content: &str, heading: &Heading, level: u8) -> Option<Replacement> {
+    let level = level.clamp(1, 6);
+    if level == heading.level {
+        return None;
+    }
+    let source = content[heading.start..heading.end].trim_end_matches(['\n', '\r']);
+    let indented = source.trim_start_matches(' ');
+    if indented.starts_with('#') {
+        let hashes_start = heading.start + (source.len() - indented.len());
+        let hashes = indented.len() - indented.trim_start_matches('#').len();
+        return Some((
+            hashes_start..hashes_start + hashes,
+            "#".repeat(level as usize),
+        ));
+    }
+
+    // Setext：最后一行是 `===` / `---` 下划线，前面的行合并为标题文本
+    let lines: Vec<&str> = source.lines().collect();
+    let text = lines[..lines.len().saturating_sub(1)]
+        .iter()
+        .map(|line| line.trim())
+        .collect::<Vec<&str>>()
+        .join(" ");
+    Some((
+        heading.start..heading.start + source.len(),
+        format!("{} {}", "#".repeat(level as usize), text),
+    ))
+}
+
+/// 按设置备份后写回文件
+fn save(
+    backups: &BackupStore,
+    settings: &SettingsStore,
+    path: &Path,
+    content: &str,
+) -> Result<String, String> {
+    backups.before_save(&settings.get().backup, path);
+    storage::write_atomic(path, content.as_bytes())?;
+    Ok(revision::revision_for(
+        content.as_bytes(),
+        fs::metadata(path).ok().as_ref(),
+    ))
+}
+
+fn finish(
+    backups: &BackupStore,
+    settings: &SettingsStore,
+    path: &Path,
+    original: &str,
+    content: String,
+    created: Option<String>,
+) -> Result<SectionEditResult, String> {
+    let revision = if content == original {
+        revision::revision_for(content.as_bytes(), fs::metadata(path).ok().as_ref())
+    } else {
+        save(backups, settings, path, &content)?
+    };
+    Ok(SectionEditResult {
+        outline: parse::outline(&content),
+        content,
+        revision,
+        created,
+    })
+}
+
+/// 把章节移动到 `target` 偏移处（必须是行首）
+fn move_range(content: &str, section: Range<usize>, target: usize) -> Result<String, String> {
+    if target == section.start || target == section.end {
+        return Ok(content.to_string());
+    }
+    if section.contains(&target) {
+        return Err("Cannot move a section into itself".to_string());
+    }
+
+    let mut text = content[section.clone()].to_string();
+    if !text.ends_with('\n') {
+        text.push('\n');
+    }
+    if !text.ends_with("\n\n") {
+        text.push('\n');
+    }
+    let mut rest = String::with_capacity(content.len());
+    rest.push_str(&content[..section.start]);
+    rest.push_str(&content[section.end..]);
+    let target = if target > section.end {
+        target - section.len()
+    } else {
+        target
+    };
+
+    if target >= rest.len() {
+        // 移到文末：与前文之间保留一个空行，文件以单个换行结尾
+        let mut out = rest.trim_end_matches('\n').to_string();
+        if !out.is_empty() {
+            out.push_str("\n\n");
+        }
+        out.push_str(text.trim_end_matches('\n'));
+        out.push('\n');
+        return Ok(out);
+    }
+    let mut out = String::with_capacity(content.len() + 2);
+    out.push_str(&rest[..target]);
+    out.push_str(&text);
+    out.push_str(&rest[target..]);
+    Ok(out)
+}
+
+/// 提升或降低行区间内所有标题的级别
+fn shift_headings(
+    content: &str,
+    lines: RangeInclusive<usize>,
+    delta: i8,
+) -> Result<String, String> {
+    if lines.is_empty() {
+        return Err("Invalid line range".to_string());
+    }
+    let mut edits: Vec<Replacement> = body_headings(content)
+        .iter()
+        .filter(|heading| lines.contains(&heading.line_index))
+        .filter_map(|heading| {
+            let level = (heading.level as i8 + delta).clamp(1, 6) as u8;
+            relevel(content, heading, level)
+        })
+        .collect();
+    Ok(apply_edits(content, &mut edits))
+}
+
+fn shift_command(
+    app: &AppHandle,
+    command: &str,
+    backups: &BackupStore,
+    settings: &SettingsStore,
+    path: String,
+    lines: RangeInclusive<usize>,
+    base_revision: Option<String>,
+    delta: i8,
+) -> Result<SectionEditResult, VividError> {
+    let start = Instant::now();
+    let path_buf = PathBuf::from(&path);
+    access::ensure_access(app, &path_buf, AccessKind::Write)?;
+    log::info!("[{}] {} lines {:?}", command, path, lines);
+
+    let content = load(&path_buf, base_revision.as_deref())?;
+    let updated = shift_headings(&content, lines, delta).map_err(|e| {
+        log::error!("[{}] {}", command, e);
+        e
+    })?;
+    let result = finish(backups, settings, &path_buf, &content, updated, None)?;
+    log::info!("[{}] ✓ Success: {} in {:?}", command, path, start.elapsed());
+    Ok(result)
+}
+
+// 移动章节（含子章节）
+//
+// `target_position` 为大纲中标题的序号（`Heading.index`），章节移动到该标题之前；
+// 不小于标题总数时移到文末。
+#[tauri::command]
+pub fn move_section(
+    app: AppHandle,
+    backups: State<'_, BackupStore>,
+    settings: State<'_, SettingsStore>,
+    path: String,
+    heading_slug: String,
+    target_position: usize,
+    base_revision: Option<String>,
+) -> Result<SectionEditResult, VividError> {
+    let start = Instant::now();
+    let path_buf = PathBuf::from(&path);
+    access::ensure_access(&app, &path_buf, AccessKind::Write)?;
+    log::info!(
+        "[move_section] {} #{} -> {}",
+        path,
+        heading_slug,
+        target_position
+    );
+
+    let content = load(&path_buf, base_revision.as_deref())?;
+    let headings = body_headings(&content);
+    let heading = find_heading(&headings, &heading_slug)?;
+    let section = section_range(&content, &headings, heading);
+
+    let target = match headings.iter().find(|h| h.index == target_position) {
+        Some(target) => line_start(&content, target.start),
+        None if headings.iter().all(|h| h.index < target_position) => content.len(),
+        None => {
+            return Err(VividError::invalid_input(format!(
+                "Invalid target position: {}",
+                target_position
+            )))
+        }
+    };
+    let updated = move_range(&content, section, target).map_err(|e| {
+        log::error!("[move_section] {}", e);
+        e
+    })?;
+
+    let result = finish(&backups, &settings, &path_buf, &content, updated, None)?;
+    log::info!(
+        "[move_section] ✓ Success: {} in {:?}",
+        path,
+        start.elapsed()
+    );
+    Ok(result)
+}
+
+// 提升行区间（从 0 开始，含两端）内标题的级别，一级标题保持不变
+#[tauri::command]
+pub fn promote_heading(
+    app: AppHandle,
+    backups: State<'_, BackupStore>,
+    settings: State<'_, SettingsStore>,
+    path: String,
+    start_line: usize,
+    end_line: usize,
+    base_revision: Option<String>,
+) -> Result<SectionEditResult, VividError> {
+    shift_command(
+        &app,
+        "promote_heading",
+        &backups,
+        &settings,
+        path,
+        start_line..=end_line,
+        base_revision,
+        -1,
+    )
+}
+
+// 降低行区间（从 0 开始，含两端）内标题的级别，六级标题保持不变
+#[tauri::command]
+pub fn demote_heading(
+    app: AppHandle,
+    backups: State<'_, BackupStore>,
+    settings: State<'_, SettingsStore>,
+    path: String,
+    start_line: usize,
+    end_line: usize,
+    base_revision: Option<String>,
+) -> Result<SectionEditResult, VividError> {
+    shift_command(
+        &app,
+        "demote_heading",
+        &backups,
+        &settings,
+        path,
+        start_line..=end_line,
+        base_revision,
+        1,
+    )
+}
+
+// 把章节拆分为新文件，原位置替换为指向新文件的链接
+//
+// 新文件中章节标题调整为一级，子标题随之调整；`new_path` 为相对路径时相对于原文档所在目录。
+// 章节内的相对链接保持原样，新文件放在其它目录时需要自行调整。
+#[tauri::command]
+pub fn extract_section_to_file(
+    app: AppHandle,
+    backups: State<'_, BackupStore>,
+    settings: State<'_, SettingsStore>,
+    path: String,
+    heading_slug: String,
+    new_path: String,
+    base_revision: Option<String>,
+) -> Result<SectionEditResult, VividError> {
+    let start = Instant::now();
+    let path_buf = PathBuf::from(&path);
+    let base_dir = path_buf.parent().unwrap_or(Path::new(".")).to_path_buf();
+    let mut target = base_dir.join(&new_path);
+    if target.extension().is_none() {
+        target.set_extension("md");
+    }
+    access::ensure_access(&app, &path_buf, AccessKind::Write)?;
+    access::ensure_access(&app, &target, AccessKind::Write)?;
+    log::info!(
+        "[extract_section_to_file] {} #{} -> {}",
+        path,
+        heading_slug,
+        target.display()
+    );
+    if target.exists() {
+        log::error!("[extract_section_to_file] Target already exists");
+        return Err(VividError::conflict(
+            &target,
+            format!("File already exists: {}", target.display()),
+        ));
+    }
+
+    let content = load(&path_buf, base_revision.as_deref())?;
+    let headings = body_headings(&content);
+    let heading = find_heading(&headings, &heading_slug)?;
+    let section = section_range(&content, &headings, heading);
+
+    // 新文件：整体调整标题级别，使章节标题成为一级标题
+    let delta = 1 - heading.level as i8;
+    let mut edits: Vec<Replacement> = headings
+        .iter()
+        .filter(|h| section.contains(&h.start))
+        .filter_map(|h| {
+            let level = (h.level as i8 + delta).clamp(1, 6) as u8;
+            relevel(&content, h, level)
+                .map(|(range, text)| (range.start - section.start..range.end - section.start, text))
+        })
+        .collect();
+    let mut extracted = apply_edits(&content[section.clone()], &mut edits)
+        .trim_end()
+        .to_string();
+    extracted.push('\n');
+
+    if let Some(parent) = target.parent() {
+        fs::create_dir_all(parent).map_err(|e| format!("Failed to create directory: {}", e))?;
+    }
+    storage::write_atomic(&target, extracted.as_bytes()).map_err(|e| {
+        log::error!("[extract_section_to_file] {}", e);
+        e
+    })?;
+
+    let link = assets::relative_path(&base_dir, &target)
+        .map(|rel| assets::path_to_link(&rel))
+        .unwrap_or_else(|| assets::path_to_link(&target));
+    let text = heading.text.replace('[', "\\[").replace(']', "\\]");
+    let mut updated = String::with_capacity(content.len());
+    updated.push_str(&content[..section.start]);
+    updated.push_str(&format!("[{}]({})\n", text, link));
+    if section.end < content.len() {
+        updated.push('\n');
+    }
+    updated.push_str(&content[section.end..]);
+
+    let created = target.to_string_lossy().to_string();
+    let result = finish(
+        &backups,
+        &settings,
+        &path_buf,
+        &content,
+        updated,
+        Some(created),
+    )?;
+    log::info!(
+        "[extract_section_to_file] ✓ Success: {} ({} bytes) in {:?}",
+        path,
+        extracted.len(),
+        start.elapsed()
+    );
+    Ok(result)
+}
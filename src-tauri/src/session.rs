@@ -0,0 +1,315 @@
+//! 最近文件与会话恢复
+//!
+//! 最近文件列表和上次打开的标签页保存在应用数据目录下，
+//! 启动时由前端调用 `restore_session` 恢复。
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use serde::{Deserialize, Serialize};
+use tauri::State;
+
+use crate::error::VividError;
+use crate::{paths, storage};
+
+const RECENT_FILES_FILE: &str = "recent_files.json";
+const SESSION_FILE: &str = "session.json";
+const RECENT_WORKSPACES_FILE: &str = "recent_workspaces.json";
+/// 未固定的最近文件最多保留条数（固定项不计入）
+const MAX_RECENT_FILES: usize = 20;
+/// 最近打开的工作区最多保留条数
+const MAX_RECENT_WORKSPACES: usize = 20;
+
+/// 最近打开的文件
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecentFile {
+    pub path: String,
+    pub name: String,
+    /// 最后打开时间（Unix 毫秒）
+    pub opened_at: u64,
+    pub pinned: bool,
+    /// 文件当前是否仍然存在（仅在返回给前端时计算）
+    #[serde(default, skip_deserializing)]
+    pub exists: bool,
+}
+
+/// 标签页中的光标与滚动位置
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CursorPosition {
+    /// 光标在内容中的偏移（字符）
+    pub offset: usize,
+    /// 选区结束位置，无选区时为空
+    pub selection_end: Option<usize>,
+    pub scroll_top: Option<f64>,
+}
+
+/// 上次关闭时的会话
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Session {
+    pub open_tabs: Vec<String>,
+    pub active_tab: Option<String>,
+    pub cursor_positions: HashMap<String, CursorPosition>,
+    /// 保存时间（Unix 毫秒）
+    pub saved_at: u64,
+}
+
+/// 保存会话参数
+#[derive(Debug, Deserialize)]
+pub struct SaveSessionParams {
+    pub open_tabs: Vec<String>,
+    pub active_tab: Option<String>,
+    #[serde(default)]
+    pub cursor_positions: HashMap<String, CursorPosition>,
+}
+
+/// 固定 / 取消固定参数
+#[derive(Debug, Deserialize)]
+pub struct PinFileParams {
+    pub path: String,
+    pub pinned: bool,
+}
+
+/// 会话状态的存储位置，读写时加锁避免并发命令互相覆盖
+pub struct SessionStore {
+    dir: PathBuf,
+    lock: Mutex<()>,
+}
+
+impl SessionStore {
+    pub fn new(dir: PathBuf) -> Self {
+        SessionStore {
+            dir,
+            lock: Mutex::new(()),
+        }
+    }
+
+    fn recent_path(&self) -> PathBuf {
+        self.dir.join(RECENT_FILES_FILE)
+    }
+
+    fn session_path(&self) -> PathBuf {
+        self.dir.join(SESSION_FILE)
+    }
+
+    fn workspaces_path(&self) -> PathBuf {
+        self.dir.join(RECENT_WORKSPACES_FILE)
+    }
+
+    /// 记录打开过的工作区，最近的在前
+    pub fn add_recent_workspace(&self, root: &Path) -> Result<(), String> {
+        let _guard = self.lock.lock().map_err(|e| e.to_string())?;
+        let path = self.workspaces_path();
+        let roots: Vec<PathBuf> = storage::load_json(&path);
+        // 同一目录的不同写法（符号链接等）只保留最新的一条
+        let mut roots = paths::dedupe(std::iter::once(root.to_path_buf()).chain(roots));
+        roots.truncate(MAX_RECENT_WORKSPACES);
+        storage::save_json(&path, &roots)
+    }
+
+    /// 打开过且仍然存在的工作区
+    pub fn recent_workspaces(&self) -> Vec<PathBuf> {
+        let Ok(_guard) = self.lock.lock() else {
+            return Vec::new();
+        };
+        let roots: Vec<PathBuf> = storage::load_json(&self.workspaces_path());
+        roots.into_iter().filter(|r| r.is_dir()).collect()
+    }
+
+    /// 在锁内读取、修改并写回最近文件列表
+    fn update_recent<F>(&self, update: F) -> Result<Vec<RecentFile>, String>
+    where
+        F: FnOnce(&mut Vec<RecentFile>),
+    {
+        let _guard = self.lock.lock().map_err(|e| e.to_string())?;
+        let path = self.recent_path();
+        let mut files: Vec<RecentFile> = storage::load_json(&path);
+        update(&mut files);
+        normalize_recent(&mut files);
+        storage::save_json(&path, &files)?;
+        Ok(files)
+    }
+}
+
+/// 固定项在前，其余按打开时间倒序，去掉指向同一个文件的重复项，并裁剪超出上限的未固定项
+fn normalize_recent(files: &mut Vec<RecentFile>) {
+    files.sort_by(|a, b| {
+        b.pinned
+            .cmp(&a.pinned)
+            .then_with(|| b.opened_at.cmp(&a.opened_at))
+    });
+    // `dedupe` 按原顺序保留第一次出现的条目，结果是原列表的子序列
+    let mut unique = paths::dedupe(files.iter().map(|f| PathBuf::from(&f.path)))
+        .into_iter()
+        .peekable();
+    files.retain(|f| {
+        unique
+            .next_if(|p| p.as_path() == Path::new(&f.path))
+            .is_some()
+    });
+    let mut unpinned = 0;
+    files.retain(|f| {
+        if f.pinned {
+            return true;
+        }
+        unpinned += 1;
+        unpinned <= MAX_RECENT_FILES
+    });
+}
+
+/// 指向同一个文件的最近文件条目
+fn find_recent<'a>(files: &'a mut [RecentFile], path: &str) -> Option<&'a mut RecentFile> {
+    files
+        .iter_mut()
+        .find(|f| f.path == path || paths::same_file(Path::new(&f.path), Path::new(path)))
+}
+
+fn with_exists(mut files: Vec<RecentFile>) -> Vec<RecentFile> {
+    for file in &mut files {
+        file.exists = Path::new(&file.path).exists();
+    }
+    files
+}
+
+fn file_name(path: &str) -> String {
+    Path::new(path)
+        .file_name()
+        .and_then(|n| n.to_str())
+        .unwrap_or(path)
+        .to_string()
+}
+
+// 记录最近打开的文件
+#[tauri::command]
+pub fn add_recent_file(
+    store: State<'_, SessionStore>,
+    path: String,
+) -> Result<Vec<RecentFile>, VividError> {
+    log::debug!("[add_recent_file] {}", path);
+    let now = storage::now_millis();
+    store
+        .update_recent(|files| match find_recent(files, &path) {
+            Some(file) => {
+                // 记录最近一次打开时使用的写法
+                file.path = path.clone();
+                file.name = file_name(&path);
+                file.opened_at = now;
+            }
+            None => files.push(RecentFile {
+                name: file_name(&path),
+                path: path.clone(),
+                opened_at: now,
+                pinned: false,
+                exists: true,
+            }),
+        })
+        .map(with_exists)
+        .map_err(|e| {
+            log::error!("[add_recent_file] {}", e);
+            VividError::from(e)
+        })
+}
+
+// 获取最近文件列表
+#[tauri::command]
+pub fn get_recent_files(store: State<'_, SessionStore>) -> Result<Vec<RecentFile>, VividError> {
+    let _guard = store.lock.lock().map_err(|e| e.to_string())?;
+    let files: Vec<RecentFile> = storage::load_json(&store.recent_path());
+    Ok(with_exists(files))
+}
+
+// 固定 / 取消固定最近文件
+#[tauri::command]
+pub fn pin_file(
+    store: State<'_, SessionStore>,
+    params: PinFileParams,
+) -> Result<Vec<RecentFile>, VividError> {
+    log::debug!("[pin_file] {} pinned={}", params.path, params.pinned);
+    let now = storage::now_millis();
+    store
+        .update_recent(|files| match find_recent(files, &params.path) {
+            Some(file) => file.pinned = params.pinned,
+            None if params.pinned => files.push(RecentFile {
+                name: file_name(&params.path),
+                path: params.path.clone(),
+                opened_at: now,
+                pinned: true,
+                exists: true,
+            }),
+            None => {}
+        })
+        .map(with_exists)
+        .map_err(|e| {
+            log::error!("[pin_file] {}", e);
+            VividError::from(e)
+        })
+}
+
+// 从最近文件中移除
+#[tauri::command]
+pub fn remove_recent_file(
+    store: State<'_, SessionStore>,
+    path: String,
+) -> Result<Vec<RecentFile>, VividError> {
+    log::debug!("[remove_recent_file] {}", path);
+    store
+        .update_recent(|files| {
+            files.retain(|f| {
+                f.path != path && !paths::same_file(Path::new(&f.path), Path::new(&path))
+            })
+        })
+        .map(with_exists)
+        .map_err(VividError::from)
+}
+
+// 保存当前打开的标签页
+#[tauri::command]
+pub fn save_session(
+    store: State<'_, SessionStore>,
+    params: SaveSessionParams,
+) -> Result<(), VividError> {
+    log::debug!("[save_session] {} tab(s)", params.open_tabs.len());
+    let session = Session {
+        open_tabs: params.open_tabs,
+        active_tab: params.active_tab,
+        cursor_positions: params.cursor_positions,
+        saved_at: storage::now_millis(),
+    };
+    let _guard = store.lock.lock().map_err(|e| e.to_string())?;
+    storage::save_json(&store.session_path(), &session).map_err(|e| {
+        log::error!("[save_session] {}", e);
+        VividError::from(e)
+    })
+}
+
+// 恢复上次的会话，已不存在的文件会被过滤掉
+#[tauri::command]
+pub fn restore_session(store: State<'_, SessionStore>) -> Result<Option<Session>, VividError> {
+    let _guard = store.lock.lock().map_err(|e| e.to_string())?;
+    let path = store.session_path();
+    if !path.exists() {
+        return Ok(None);
+    }
+
+    let mut session: Session = storage::load_json(&path);
+    let before = session.open_tabs.len();
+    session.open_tabs.retain(|tab| Path::new(tab).is_file());
+    let open_tabs = &session.open_tabs;
+    session
+        .cursor_positions
+        .retain(|tab, _| open_tabs.contains(tab));
+    if session
+        .active_tab
+        .as_ref()
+        .is_some_and(|tab| !open_tabs.contains(tab))
+    {
+        session.active_tab = session.open_tabs.first().cloned();
+    }
+
+    log::info!(
+        "[restore_session] Restored {} of {} tab(s)",
+        session.open_tabs.len(),
+        before
+    );
+    Ok(Some(session))
+}
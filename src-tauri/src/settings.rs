@@ -0,0 +1,1015 @@
+//! 应用设置
+//!
+//! 设置以 JSON 保存在应用配置目录的 `settings.json` 中，带有 `version` 字段。
+//! 读取时先按版本逐级迁移原始 JSON，再反序列化为 [`Settings`]；缺失的字段使用默认值。
+//! 个别字段无效时只有这些字段回退为默认值，原文件备份为 `settings.json.bak`。
+//! 每次修改后向所有窗口广播 `settings-changed` 事件。
+
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use tauri::{AppHandle, Emitter, State};
+
+use crate::error::VividError;
+use crate::export::pdf::PdfExportOptions;
+use crate::{i18n, storage};
+
+/// 当前设置结构版本，修改字段含义时递增并在 [`migrate`] 中补充迁移步骤
+pub const SETTINGS_VERSION: u32 = 2;
+pub const SETTINGS_FILE: &str = "settings.json";
+/// 设置变更事件，负载为完整的新设置
+pub const SETTINGS_CHANGED_EVENT: &str = "settings-changed";
+/// 默认的日记路径模式
+pub const DEFAULT_DAILY_NOTE_PATTERN: &str = "journal/YYYY/MM/YYYY-MM-DD.md";
+
+/// 界面主题
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Theme {
+    #[default]
+    Light,
+    Dark,
+    /// 跟随系统
+    System,
+}
+
+/// 插入内部链接的格式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LinkStyle {
+    /// 相对路径的 Markdown 链接 `[标题](notes/a.md)`
+    #[default]
+    Relative,
+    /// `[[Wiki 链接]]`
+    Wiki,
+}
+
+/// 编辑器字体
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FontSettings {
+    /// 正文字体，为空时使用界面默认字体
+    pub family: Option<String>,
+    /// 代码字体
+    pub mono_family: Option<String>,
+    /// 字号（px）
+    pub size: f32,
+    pub line_height: f32,
+}
+
+impl Default for FontSettings {
+    fn default() -> Self {
+        FontSettings {
+            family: None,
+            mono_family: None,
+            size: 16.0,
+            line_height: 1.6,
+        }
+    }
+}
+
+/// 导出默认选项
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ExportSettings {
+    /// PDF 导出的默认选项，`export_pdf` 没有传入选项时使用
+    pub pdf: PdfExportOptions,
+    /// 导出主题 id（内置的 `github` / `dark` / `academic` / `sepia` 或用户主题）
+    pub html_theme: String,
+    /// HTML 导出时是否内嵌本地图片
+    pub embed_assets: bool,
+    /// DOCX 导出的参考模板，`export_docx` 没有指定模板时使用
+    pub docx_template: Option<String>,
+    /// pandoc 可执行文件路径，为空时依次查找随应用附带的 pandoc 和 PATH 中的 pandoc
+    pub pandoc_path: Option<String>,
+}
+
+impl Default for ExportSettings {
+    fn default() -> Self {
+        ExportSettings {
+            pdf: PdfExportOptions::default(),
+            html_theme: "github".to_string(),
+            embed_assets: true,
+            docx_template: None,
+            pandoc_path: None,
+        }
+    }
+}
+
+/// 备份位置
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BackupLocation {
+    /// 文件所在目录下的 `.backups/<文件名>/`
+    #[default]
+    Sibling,
+    /// 应用数据目录下的 `backups/`
+    AppData,
+}
+
+/// 保存前备份与轮换策略
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BackupSettings {
+    /// 覆盖文件前是否先备份原文件
+    pub enabled: bool,
+    pub location: BackupLocation,
+    /// 距上次备份不足该秒数时跳过，避免自动保存产生大量备份
+    pub min_interval_secs: u64,
+    /// 每个文件最多保留的备份数，0 表示不限
+    pub max_count: usize,
+    /// 备份最长保留天数，0 表示不限
+    pub max_age_days: u64,
+    /// 每个文件的备份总大小上限（字节），0 表示不限
+    pub max_total_bytes: u64,
+}
+
+impl Default for BackupSettings {
+    fn default() -> Self {
+        BackupSettings {
+            enabled: false,
+            location: BackupLocation::default(),
+            min_interval_secs: 300,
+            max_count: 20,
+            max_age_days: 30,
+            max_total_bytes: 50 * 1024 * 1024,
+        }
+    }
+}
+
+/// 保存失败时的重试策略
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SaveRetrySettings {
+    /// 最多尝试次数（包括第一次），1 表示不重试
+    pub max_attempts: u32,
+    /// 第一次重试前的等待时间（毫秒），之后每次翻倍
+    pub initial_delay_ms: u64,
+    /// 单次等待时间上限（毫秒）
+    pub max_delay_ms: u64,
+}
+
+impl Default for SaveRetrySettings {
+    fn default() -> Self {
+        SaveRetrySettings {
+            max_attempts: 5,
+            initial_delay_ms: 100,
+            max_delay_ms: 2000,
+        }
+    }
+}
+
+/// 日记设置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DailyNoteSettings {
+    /// 日记根目录，为空时使用当前工作区，没有工作区时使用默认保存目录
+    pub folder: Option<String>,
+    /// 相对根目录的路径模式，`YYYY` / `YY` / `MM` / `DD` 替换为日期
+    pub pattern: String,
+    /// 新建日记使用的模板 id，为空时只写入日期标题
+    pub template: Option<String>,
+}
+
+impl Default for DailyNoteSettings {
+    fn default() -> Self {
+        DailyNoteSettings {
+            folder: None,
+            pattern: DEFAULT_DAILY_NOTE_PATTERN.to_string(),
+            template: Some("daily-note".to_string()),
+        }
+    }
+}
+
+/// Markdown 检查设置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LintSettings {
+    /// 关闭的规则（如 `line_length`）
+    pub disabled_rules: Vec<String>,
+    /// 行长度上限（字符），0 表示不检查
+    pub max_line_length: usize,
+    /// 无序列表统一使用的标记（`-` / `*` / `+`），为空时以文档中第一个列表为准
+    pub list_marker: Option<char>,
+}
+
+impl Default for LintSettings {
+    fn default() -> Self {
+        LintSettings {
+            disabled_rules: Vec::new(),
+            max_line_length: 120,
+            list_marker: None,
+        }
+    }
+}
+
+/// 写作统计设置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WritingSettings {
+    /// 每日目标字数，0 表示不设目标
+    pub daily_goal: usize,
+    /// 同一文档两次保存间隔超过该分钟数时开始新的写作时段
+    pub session_gap_minutes: u64,
+}
+
+impl Default for WritingSettings {
+    fn default() -> Self {
+        WritingSettings {
+            daily_goal: 0,
+            session_gap_minutes: 30,
+        }
+    }
+}
+
+/// 文件访问范围，见 `access` 模块
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AccessSettings {
+    /// 是否限制命令只能访问允许的路径
+    pub enforce: bool,
+    /// 除工作区外允许访问的目录
+    pub roots: Vec<String>,
+    /// 用户通过 `request_access` 确认过的路径
+    pub grants: Vec<String>,
+}
+
+impl AccessSettings {
+    /// 前端请求的修改只保留收紧的部分：可以开启检查、移除目录，不能关闭检查或添加目录
+    fn tightened(&self, requested: AccessSettings) -> AccessSettings {
+        AccessSettings {
+            enforce: self.enforce || requested.enforce,
+            roots: requested
+                .roots
+                .into_iter()
+                .filter(|root| self.roots.contains(root))
+                .collect(),
+            grants: requested
+                .grants
+                .into_iter()
+                .filter(|grant| self.grants.contains(grant))
+                .collect(),
+        }
+    }
+}
+
+impl Default for AccessSettings {
+    fn default() -> Self {
+        AccessSettings {
+            enforce: true,
+            roots: Vec::new(),
+            grants: Vec::new(),
+        }
+    }
+}
+
+fn default_pages_branch() -> String {
+    "gh-pages".to_string()
+}
+
+/// 发布位置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum PublishDestination {
+    /// 写入静态站点的输出目录
+    Directory { output_dir: String },
+    /// 提交并推送到 Git 仓库的分支（GitHub Pages）；`repository` 为 `git clone` 接受的任意地址
+    GithubPages {
+        repository: String,
+        #[serde(default = "default_pages_branch")]
+        branch: String,
+    },
+}
+
+/// 发布目标
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PublishTarget {
+    /// `publish` 命令按名称选择目标
+    pub name: String,
+    #[serde(flatten)]
+    pub destination: PublishDestination,
+    /// 页面主题，为空时沿用 HTML 导出主题
+    #[serde(default)]
+    pub theme: Option<String>,
+    /// 站点标题，显示在索引页和页面顶部
+    #[serde(default)]
+    pub site_title: Option<String>,
+}
+
+/// 发布设置
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PublishSettings {
+    pub targets: Vec<PublishTarget>,
+}
+
+/// 图片文字识别设置，见 `ocr` 模块
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct OcrSettings {
+    /// tesseract 可执行文件路径，为空时使用 PATH 中的 tesseract
+    pub tesseract_path: Option<String>,
+    /// 默认识别语言（tesseract 语言包名，多个用 `+` 连接，如 `eng+chi_sim`）
+    pub language: String,
+}
+
+impl Default for OcrSettings {
+    fn default() -> Self {
+        OcrSettings {
+            tesseract_path: None,
+            language: "eng".to_string(),
+        }
+    }
+}
+
+/// 音频附件的转写设置，见 `attachments` 模块
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TranscriptionSettings {
+    /// 转写命令（如 whisper.cpp 的 `whisper-cli`），为空时不提供转写
+    pub command: Option<String>,
+    /// 参数，`{input}` / `{language}` 替换为音频文件路径和语言；命令需把转写文字写到标准输出
+    pub args: Vec<String>,
+    /// 默认语言，为空时由转写命令自行识别
+    pub language: Option<String>,
+    /// 超时（秒）
+    pub timeout_secs: u64,
+    /// 添加音频附件后自动转写
+    pub auto: bool,
+}
+
+impl Default for TranscriptionSettings {
+    fn default() -> Self {
+        TranscriptionSettings {
+            command: None,
+            args: vec!["{input}".to_string()],
+            language: None,
+            timeout_secs: 600,
+            auto: false,
+        }
+    }
+}
+
+fn default_tool_timeout() -> u64 {
+    30
+}
+
+/// 外部工具的工作目录
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ToolWorkingDir {
+    /// 文档所在目录，新文档使用临时目录
+    #[default]
+    Document,
+    /// 当前工作区根目录，没有工作区时使用临时目录
+    Workspace,
+    /// 每次运行新建、运行后删除的临时目录
+    Temp,
+}
+
+/// 外部工具：通过标准输入接收文档内容，标准输出返回结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExternalTool {
+    /// `run_tool` 按 id 选择工具
+    pub id: String,
+    /// 显示名称，为空时使用 id
+    #[serde(default)]
+    pub name: String,
+    /// 可执行文件
+    pub command: String,
+    /// 参数，`{file}` / `{dir}` / `{name}` 替换为文档路径、所在目录和文件名
+    #[serde(default)]
+    pub args: Vec<String>,
+    #[serde(default)]
+    pub working_dir: ToolWorkingDir,
+    /// 超时（秒）
+    #[serde(default = "default_tool_timeout")]
+    pub timeout_secs: u64,
+    /// 保存前用它格式化文档
+    #[serde(default)]
+    pub format_on_save: bool,
+    /// 保存时格式化的文件扩展名（不含 `.`），为空表示所有 Markdown 文件
+    #[serde(default)]
+    pub extensions: Vec<String>,
+}
+
+/// 外部工具设置，见 `tools` 模块
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ExternalToolSettings {
+    pub tools: Vec<ExternalTool>,
+}
+
+/// 应用设置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    pub version: u32,
+    pub theme: Theme,
+    /// 界面语言（`en` / `zh`），`auto` 表示跟随系统；也决定后端错误消息的语言
+    pub language: String,
+    pub font: FontSettings,
+    /// 自动保存延迟（毫秒），0 表示关闭自动保存
+    pub autosave_interval_ms: u64,
+    /// 新建文档的默认保存目录
+    pub default_save_dir: Option<String>,
+    /// 粘贴图片的资源目录，相对路径基于文档所在目录
+    pub assets_dir: String,
+    pub link_style: LinkStyle,
+    pub export: ExportSettings,
+    pub daily_notes: DailyNoteSettings,
+    pub backup: BackupSettings,
+    pub save_retry: SaveRetrySettings,
+    pub lint: LintSettings,
+    pub publish: PublishSettings,
+    pub external_tools: ExternalToolSettings,
+    pub ocr: OcrSettings,
+    pub transcription: TranscriptionSettings,
+    pub writing: WritingSettings,
+    pub access: AccessSettings,
+    /// 更高版本写入、当前版本不认识的字段，原样保留以免降级使用时丢失
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Settings {
+            version: SETTINGS_VERSION,
+            theme: Theme::default(),
+            language: "auto".to_string(),
+            font: FontSettings::default(),
+            autosave_interval_ms: 2000,
+            default_save_dir: None,
+            assets_dir: crate::assets::DEFAULT_ASSETS_DIR.to_string(),
+            link_style: LinkStyle::default(),
+            export: ExportSettings::default(),
+            daily_notes: DailyNoteSettings::default(),
+            backup: BackupSettings::default(),
+            save_retry: SaveRetrySettings::default(),
+            lint: LintSettings::default(),
+            publish: PublishSettings::default(),
+            external_tools: ExternalToolSettings::default(),
+            ocr: OcrSettings::default(),
+            transcription: TranscriptionSettings::default(),
+            writing: WritingSettings::default(),
+            access: AccessSettings::default(),
+            extra: Map::new(),
+        }
+    }
+}
+
+impl Settings {
+    /// 将取值限制在合理范围内
+    fn normalize(&mut self) {
+        self.font.size = self.font.size.clamp(8.0, 48.0);
+        self.font.line_height = self.font.line_height.clamp(1.0, 3.0);
+        if self.autosave_interval_ms > 0 {
+            self.autosave_interval_ms = self.autosave_interval_ms.clamp(500, 10 * 60 * 1000);
+        }
+        if self.assets_dir.trim().is_empty() {
+            self.assets_dir = crate::assets::DEFAULT_ASSETS_DIR.to_string();
+        }
+        if self.daily_notes.pattern.trim().is_empty() {
+            self.daily_notes.pattern = DEFAULT_DAILY_NOTE_PATTERN.to_string();
+        }
+        self.save_retry.max_attempts = self.save_retry.max_attempts.clamp(1, 10);
+        self.save_retry.initial_delay_ms = self.save_retry.initial_delay_ms.clamp(10, 5000);
+        self.save_retry.max_delay_ms = self
+            .save_retry
+            .max_delay_ms
+            .clamp(self.save_retry.initial_delay_ms, 30_000);
+        self.writing.session_gap_minutes = self.writing.session_gap_minutes.clamp(1, 24 * 60);
+        if self.ocr.language.trim().is_empty() {
+            self.ocr.language = OcrSettings::default().language;
+        }
+        self.transcription.timeout_secs = self.transcription.timeout_secs.clamp(10, 6 * 60 * 60);
+        for tool in &mut self.external_tools.tools {
+            tool.timeout_secs = tool.timeout_secs.clamp(1, 600);
+        }
+        if !matches!(self.lint.list_marker, None | Some('-' | '*' | '+')) {
+            self.lint.list_marker = None;
+        }
+        self.version = self.version.max(SETTINGS_VERSION);
+    }
+
+    /// 恢复会执行外部程序或指向任意路径的字段，返回被恢复的字段名
+    ///
+    /// 这些字段与 `access` 一样只能直接编辑 `settings.json` 修改，否则前端可以借助
+    /// `run_tool`、保存时格式化、OCR、转写、pandoc 导出或日记运行任意程序、写入任意目录。
+    fn keep_protected(&mut self, current: &Settings) -> Vec<&'static str> {
+        fn keep<T: Clone + Serialize>(
+            kept: &mut Vec<&'static str>,
+            name: &'static str,
+            field: &mut T,
+            current: &T,
+        ) {
+            if serde_json::to_value(&*field).ok() != serde_json::to_value(current).ok() {
+                kept.push(name);
+                *field = current.clone();
+            }
+        }
+
+        let mut kept = Vec::new();
+        keep(
+            &mut kept,
+            "external_tools",
+            &mut self.external_tools,
+            &current.external_tools,
+        );
+        keep(
+            &mut kept,
+            "ocr.tesseract_path",
+            &mut self.ocr.tesseract_path,
+            &current.ocr.tesseract_path,
+        );
+        keep(
+            &mut kept,
+            "transcription.command",
+            &mut self.transcription.command,
+            &current.transcription.command,
+        );
+        keep(
+            &mut kept,
+            "transcription.args",
+            &mut self.transcription.args,
+            &current.transcription.args,
+        );
+        keep(
+            &mut kept,
+            "export.pandoc_path",
+            &mut self.export.pandoc_path,
+            &current.export.pandoc_path,
+        );
+        keep(
+            &mut kept,
+            "publish.targets",
+            &mut self.publish.targets,
+            &current.publish.targets,
+        );
+        keep(
+            &mut kept,
+            "daily_notes.folder",
+            &mut self.daily_notes.folder,
+            &current.daily_notes.folder,
+        );
+        kept
+    }
+
+    /// 在当前设置上合并补丁（如工作区配置）得到新的设置，不持久化；访问范围和
+    /// [`keep_protected`](Self::keep_protected) 中的字段不受补丁影响
+    pub fn with_overrides(&self, patch: Value) -> Result<Settings, String> {
+        let mut value = serde_json::to_value(self).map_err(|e| e.to_string())?;
+        merge_json(&mut value, patch);
+        let mut merged: Settings =
+            serde_json::from_value(value).map_err(|e| format!("Invalid settings: {}", e))?;
+        merged.access = self.access.clone();
+        merged.keep_protected(self);
+        merged.normalize();
+        Ok(merged)
+    }
+}
+
+/// 将旧版本的设置 JSON 逐级迁移到当前版本
+///
+/// 版本 0 为未带版本号的早期格式，沿用前端 store 的驼峰字段名（`isDarkMode`、`fontSize` 等）；
+/// 版本 1 的默认语言是 `en`，版本 2 改为 `auto`（跟随系统）。
+fn migrate(mut value: Value) -> Value {
+    let Some(object) = value.as_object_mut() else {
+        return Value::Object(Map::new());
+    };
+    let version = object.get("version").and_then(Value::as_u64).unwrap_or(0);
+
+    if version < 1 {
+        if let Some(dark) = object.remove("isDarkMode").and_then(|v| v.as_bool()) {
+            object
+                .entry("theme")
+                .or_insert_with(|| Value::from(if dark { "dark" } else { "light" }));
+        }
+        if let Some(size) = object.remove("fontSize") {
+            let font = object
+                .entry("font")
+                .or_insert_with(|| Value::Object(Map::new()));
+            if let Some(font) = font.as_object_mut() {
+                font.entry("size").or_insert(size);
+            }
+        }
+        if let Some(dir) = object.remove("defaultSaveDir") {
+            object.entry("default_save_dir").or_insert(dir);
+        }
+        object.insert("version".to_string(), Value::from(1));
+        log::info!("[settings] Migrated settings from version 0 to 1");
+    }
+    if version < 2 {
+        // 版本 1 的默认值是 `en`，无法区分用户是否选择过，统一改为跟随系统
+        if object.get("language").and_then(Value::as_str) == Some("en") {
+            object.insert("language".to_string(), Value::from("auto"));
+        }
+        object.insert("version".to_string(), Value::from(2));
+        log::info!("[settings] Migrated settings from version 1 to 2");
+    }
+
+    value
+}
+
+/// 递归合并 JSON：对象逐字段合并，其余类型直接替换
+fn merge_json(target: &mut Value, patch: Value) {
+    match (target, patch) {
+        (Value::Object(target), Value::Object(patch)) => {
+            for (key, value) in patch {
+                merge_json(target.entry(key).or_insert(Value::Null), value);
+            }
+        }
+        (target, patch) => *target = patch,
+    }
+}
+
+/// 把 `fields` 逐个合并到 `merged` 中 `pointer` 所指的对象上，跳过会使设置无法解析的字段
+///
+/// 无效字段的默认值是对象时继续逐个合并其子字段，只丢弃真正无效的部分；
+/// 跳过的字段以 JSON Pointer 形式记入 `dropped`。
+fn merge_valid(
+    merged: &mut Value,
+    pointer: &str,
+    fields: Map<String, Value>,
+    dropped: &mut Vec<String>,
+) {
+    for (key, value) in fields {
+        let field = format!("{}/{}", pointer, key.replace('~', "~0").replace('/', "~1"));
+        let mut candidate = merged.clone();
+        if let Some(target) = candidate.pointer_mut(pointer) {
+            merge_json(
+                target,
+                Value::Object(Map::from_iter([(key, value.clone())])),
+            );
+        }
+        if serde_json::from_value::<Settings>(candidate.clone()).is_ok() {
+            *merged = candidate;
+            continue;
+        }
+        match value {
+            Value::Object(inner) if merged.pointer(&field).is_some_and(Value::is_object) => {
+                merge_valid(merged, &field, inner, dropped)
+            }
+            _ => dropped.push(field),
+        }
+    }
+}
+
+/// 部分字段无效时在默认设置上保留其余有效字段，返回设置和被丢弃的字段
+fn recover(raw: Value) -> (Settings, Vec<String>) {
+    let mut merged = serde_json::to_value(Settings::default()).unwrap_or_default();
+    let mut dropped = Vec::new();
+    if let Value::Object(fields) = raw {
+        merge_valid(&mut merged, "", fields, &mut dropped);
+    }
+    let settings = serde_json::from_value(merged).unwrap_or_default();
+    (settings, dropped)
+}
+
+/// 设置存储，内存中保留一份当前设置供其它命令读取
+pub struct SettingsStore {
+    path: PathBuf,
+    settings: Mutex<Settings>,
+}
+
+impl SettingsStore {
+    /// 从配置目录加载设置，必要时执行迁移并写回
+    pub fn load(dir: PathBuf) -> Self {
+        let path = dir.join(SETTINGS_FILE);
+        let raw: Value = storage::load_json(&path);
+        let exists = !raw.is_null();
+        let from_version = raw.get("version").and_then(Value::as_u64).unwrap_or(0);
+
+        let mut settings = if exists {
+            let raw = migrate(raw);
+            match serde_json::from_value::<Settings>(raw.clone()) {
+                Ok(settings) => settings,
+                Err(e) => {
+                    // 只丢弃无效字段，访问授权、发布和同步目标等其余设置保持不变；
+                    // 原文件先备份，避免之后的保存覆盖掉被丢弃的内容
+                    let (settings, dropped) = recover(raw);
+                    log::warn!(
+                        "[settings] Invalid settings ({}), using defaults for: {}",
+                        e,
+                        dropped.join(", ")
+                    );
+                    let backup = path.with_extension("json.bak");
+                    if let Err(e) = fs::copy(&path, &backup) {
+                        log::warn!("[settings] Failed to back up settings: {}", e);
+                    } else {
+                        log::info!("[settings] Backed up settings to {:?}", backup);
+                    }
+                    settings
+                }
+            }
+        } else {
+            Settings::default()
+        };
+        settings.normalize();
+
+        if exists && from_version < SETTINGS_VERSION as u64 {
+            if let Err(e) = storage::save_json(&path, &settings) {
+                log::warn!("[settings] Failed to save migrated settings: {}", e);
+            }
+        }
+        log::info!("[settings] Loaded settings from {:?}", path);
+        i18n::apply(&settings.language);
+
+        SettingsStore {
+            path,
+            settings: Mutex::new(settings),
+        }
+    }
+
+    /// 当前设置的副本
+    pub fn get(&self) -> Settings {
+        self.settings.lock().map(|s| s.clone()).unwrap_or_default()
+    }
+
+    /// 在后端直接修改设置并持久化，返回新的设置（调用方负责广播变更事件）
+    pub fn update_with(&self, f: impl FnOnce(&mut Settings)) -> Result<Settings, String> {
+        let mut current = self.settings.lock().map_err(|e| e.to_string())?;
+        let mut updated = current.clone();
+        f(&mut updated);
+        updated.normalize();
+        storage::save_json(&self.path, &updated)?;
+        i18n::apply(&updated.language);
+        *current = updated.clone();
+        Ok(updated)
+    }
+
+    /// 合并补丁并持久化，返回新的设置
+    fn update(&self, patch: Value) -> Result<Settings, String> {
+        let mut current = self.settings.lock().map_err(|e| e.to_string())?;
+        let mut value = serde_json::to_value(&*current).map_err(|e| e.to_string())?;
+        merge_json(&mut value, patch);
+
+        let mut updated: Settings =
+            serde_json::from_value(value).map_err(|e| format!("Invalid settings: {}", e))?;
+        updated.access = current.access.tightened(updated.access);
+        let kept = updated.keep_protected(&current);
+        if !kept.is_empty() {
+            log::warn!(
+                "[settings] Ignoring changes to protected settings: {}",
+                kept.join(", ")
+            );
+        }
+        updated.normalize();
+        storage::save_json(&self.path, &updated)?;
+        i18n::apply(&updated.language);
+        *current = updated.clone();
+        Ok(updated)
+    }
+}
+
+// 获取当前设置
+#[tauri::command]
+pub fn get_settings(store: State<'_, SettingsStore>) -> Settings {
+    store.get()
+}
+
+// 更新设置（传入需要修改的部分字段）
+#[tauri::command]
+pub fn update_settings(
+    app: AppHandle,
+    store: State<'_, SettingsStore>,
+    patch: Value,
+) -> Result<Settings, VividError> {
+    log::debug!("[update_settings] Patch: {}", patch);
+    if !patch.is_object() {
+        return Err(VividError::invalid_input(
+            "Settings patch must be an object",
+        ));
+    }
+
+    let settings = store.update(patch).map_err(|e| {
+        log::error!("[update_settings] {}", e);
+        e
+    })?;
+    if let Err(e) = app.emit(SETTINGS_CHANGED_EVENT, &settings) {
+        log::warn!(
+            "[update_settings] Failed to emit {}: {}",
+            SETTINGS_CHANGED_EVENT,
+            e
+        );
+    }
+    log::info!("[update_settings] ✓ Settings updated");
+    Ok(settings)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!(
+            "vividmark-settings-{}-{}",
+            name,
+            std::process::id()
+        ));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn access_can_only_be_tightened() {
+        let current = AccessSettings {
+            enforce: false,
+            roots: vec!["/a".to_string(), "/b".to_string()],
+            grants: vec!["/g".to_string()],
+        };
+        let requested = AccessSettings {
+            enforce: true,
+            roots: vec!["/b".to_string(), "/etc".to_string()],
+            grants: vec!["/g".to_string(), "/root".to_string()],
+        };
+        let tightened = current.tightened(requested);
+        assert!(tightened.enforce);
+        assert_eq!(tightened.roots, vec!["/b"]);
+        assert_eq!(tightened.grants, vec!["/g"]);
+
+        let relaxed = AccessSettings {
+            enforce: false,
+            ..AccessSettings::default()
+        };
+        assert!(AccessSettings::default().tightened(relaxed).enforce);
+    }
+
+    #[test]
+    fn protected_fields_keep_current_values() {
+        let current = Settings::default();
+        let mut requested: Settings = serde_json::from_value(json!({
+            "theme": "dark",
+            "external_tools": {
+                "tools": [{ "id": "fmt", "command": "/bin/sh", "format_on_save": true }]
+            },
+            "ocr": { "tesseract_path": "/tmp/tesseract", "language": "chi_sim" },
+            "transcription": { "command": "sh", "args": ["-c", "id"], "auto": true },
+            "export": { "pandoc_path": "/tmp/pandoc", "html_theme": "dark" },
+            "publish": {
+                "targets": [{ "name": "site", "type": "directory", "output_dir": "/etc" }]
+            },
+            "daily_notes": { "folder": "/etc", "pattern": "x.md" }
+        }))
+        .unwrap();
+
+        let kept = requested.keep_protected(&current);
+        assert_eq!(
+            kept,
+            vec![
+                "external_tools",
+                "ocr.tesseract_path",
+                "transcription.command",
+                "transcription.args",
+                "export.pandoc_path",
+                "publish.targets",
+                "daily_notes.folder",
+            ]
+        );
+        assert!(requested.external_tools.tools.is_empty());
+        assert_eq!(requested.ocr.tesseract_path, None);
+        assert_eq!(requested.transcription.command, None);
+        assert_eq!(requested.transcription.args, vec!["{input}"]);
+        assert_eq!(requested.export.pandoc_path, None);
+        assert!(requested.publish.targets.is_empty());
+        assert_eq!(requested.daily_notes.folder, None);
+
+        // 其它字段照常修改
+        assert_eq!(requested.theme, Theme::Dark);
+        assert_eq!(requested.ocr.language, "chi_sim");
+        assert!(requested.transcription.auto);
+        assert_eq!(requested.export.html_theme, "dark");
+        assert_eq!(requested.daily_notes.pattern, "x.md");
+    }
+
+    #[test]
+    fn unchanged_protected_fields_are_not_reported() {
+        let current = Settings::default();
+        let mut requested = current.clone();
+        requested.theme = Theme::Dark;
+        assert!(requested.keep_protected(&current).is_empty());
+    }
+
+    #[test]
+    fn update_ignores_protected_fields_and_widened_access() {
+        let dir = temp_dir("update");
+        let store = SettingsStore::load(dir.clone());
+        let updated = store
+            .update(json!({
+                "access": { "enforce": false, "roots": ["/"] },
+                "export": { "pandoc_path": "/tmp/pandoc" },
+                "autosave_interval_ms": 0
+            }))
+            .unwrap();
+        assert!(updated.access.enforce);
+        assert!(updated.access.roots.is_empty());
+        assert_eq!(updated.export.pandoc_path, None);
+        assert_eq!(updated.autosave_interval_ms, 0);
+
+        let reloaded = SettingsStore::load(dir.clone()).get();
+        assert_eq!(reloaded.autosave_interval_ms, 0);
+        assert_eq!(reloaded.export.pandoc_path, None);
+        fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn overrides_cannot_change_protected_fields() {
+        let merged = Settings::default()
+            .with_overrides(json!({
+                "export": { "pandoc_path": "/tmp/pandoc", "html_theme": "dark" },
+                "access": { "enforce": false }
+            }))
+            .unwrap();
+        assert_eq!(merged.export.pandoc_path, None);
+        assert_eq!(merged.export.html_theme, "dark");
+        assert!(merged.access.enforce);
+    }
+
+    #[test]
+    fn recover_keeps_valid_fields() {
+        let (settings, dropped) = recover(json!({
+            "theme": "dark",
+            "autosave_interval_ms": "soon",
+            "access": { "roots": ["/notes"], "enforce": "yes" },
+            "publish": {
+                "targets": [{ "name": "site", "type": "directory", "output_dir": "/site" }]
+            }
+        }));
+        assert_eq!(dropped, vec!["/access/enforce", "/autosave_interval_ms"]);
+        assert_eq!(settings.theme, Theme::Dark);
+        assert_eq!(settings.autosave_interval_ms, 2000);
+        assert_eq!(settings.access.roots, vec!["/notes"]);
+        assert!(settings.access.enforce);
+        assert_eq!(settings.publish.targets[0].name, "site");
+    }
+
+    #[test]
+    fn recover_drops_invalid_list_as_a_whole() {
+        let (settings, dropped) = recover(json!({
+            "access": { "roots": ["/a", 1], "grants": ["/g"] }
+        }));
+        assert_eq!(dropped, vec!["/access/roots"]);
+        assert!(settings.access.roots.is_empty());
+        assert_eq!(settings.access.grants, vec!["/g"]);
+    }
+
+    #[test]
+    fn recover_escapes_pointer_segments() {
+        let mut merged = serde_json::to_value(Settings::default()).unwrap();
+        let mut dropped = Vec::new();
+        let fields = json!({ "a/b": 1, "font": { "size": "big" } });
+        let Value::Object(fields) = fields else {
+            unreachable!()
+        };
+        merge_valid(&mut merged, "", fields, &mut dropped);
+        assert_eq!(dropped, vec!["/font/size"]);
+        // 未知字段原样保留
+        assert_eq!(merged["a/b"], json!(1));
+    }
+
+    #[test]
+    fn load_backs_up_partially_invalid_settings() {
+        let dir = temp_dir("recover");
+        let original = r#"{"version":1,"theme":"dark","font":{"size":"big"}}"#;
+        fs::write(dir.join(SETTINGS_FILE), original).unwrap();
+
+        let settings = SettingsStore::load(dir.clone()).get();
+        assert_eq!(settings.theme, Theme::Dark);
+        assert_eq!(settings.font.size, FontSettings::default().size);
+        assert_eq!(
+            fs::read_to_string(dir.join("settings.json.bak")).unwrap(),
+            original
+        );
+        fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn migrates_version_zero_fields() {
+        let migrated =
+            migrate(json!({ "isDarkMode": true, "fontSize": 18, "defaultSaveDir": "/d" }));
+        assert_eq!(migrated["theme"], json!("dark"));
+        assert_eq!(migrated["font"]["size"], json!(18));
+        assert_eq!(migrated["default_save_dir"], json!("/d"));
+        assert_eq!(migrated["version"], json!(SETTINGS_VERSION));
+        assert!(migrated.get("isDarkMode").is_none());
+    }
+
+    #[test]
+    fn default_language_follows_the_system() {
+        assert_eq!(Settings::default().language, "auto");
+        let migrated = migrate(json!({ "version": 1, "language": "en" }));
+        assert_eq!(migrated["language"], json!("auto"));
+        assert_eq!(migrated["version"], json!(2));
+        let migrated = migrate(json!({ "version": 1, "language": "zh" }));
+        assert_eq!(migrated["language"], json!("zh"));
+        // 版本 2 之后选择的 `en` 保持不变
+        let migrated = migrate(json!({ "version": 2, "language": "en" }));
+        assert_eq!(migrated["language"], json!("en"));
+    }
+}
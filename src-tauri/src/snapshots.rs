@@ -0,0 +1,465 @@
+//! 内容寻址的快照存储
+//!
+//! 版本历史需要为同一篇笔记保存大量版本，逐份复制会让大文件占满磁盘。这里把文件内容按内容
+//! 切分为数 KB 的块（内容定义分块，插入或删除一段文字只影响附近的块），每块以 BLAKE3 哈希
+//! 为键、用 zstd 压缩后保存在应用数据目录 `snapshots/objects/` 下，相同的块只存一份。
+//!
+//! 每个文件的快照列表（块哈希序列）保存在 `snapshots/refs/<路径哈希>.json`。删除快照只修改
+//! 列表，`collect_snapshot_garbage` 之后才清理不再被任何快照引用的块。
+
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::Instant;
+
+use chrono::Local;
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager, State};
+
+use crate::access::{self, AccessKind};
+use crate::error::VividError;
+use crate::largefile::LargeFileStore;
+use crate::{paths, storage, FileInfo};
+
+const OBJECTS_DIR: &str = "objects";
+const REFS_DIR: &str = "refs";
+/// 快照 id 的时间格式，与备份 id 相同
+const ID_FORMAT: &str = "%Y%m%d-%H%M%S-%3f";
+/// zstd 压缩级别
+const COMPRESSION_LEVEL: i32 = 3;
+/// 分块大小：不小于 2 KB、平均约 8 KB、不超过 64 KB
+const MIN_CHUNK: usize = 2 * 1024;
+const MAX_CHUNK: usize = 64 * 1024;
+const CHUNK_MASK: u64 = (1 << 13) - 1;
+
+/// Gear 滚动哈希的随机表（splitmix64 生成，保证每次运行分块边界相同）
+const GEAR: [u64; 256] = gear_table();
+
+const fn gear_table() -> [u64; 256] {
+    let mut table = [0u64; 256];
+    let mut state: u64 = 0x9E37_79B9_7F4A_7C15;
+    let mut i = 0;
+    while i < 256 {
+        state = state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        table[i] = z ^ (z >> 31);
+        i += 1;
+    }
+    table
+}
+
+/// 一个快照
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SnapshotInfo {
+    pub id: String,
+    /// 创建时间（Unix 毫秒）
+    pub created_at: u64,
+    /// 原始内容大小
+    pub size: u64,
+    /// 完整内容的 BLAKE3 哈希
+    pub hash: String,
+    /// 用户填写的说明
+    pub label: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Snapshot {
+    #[serde(flatten)]
+    info: SnapshotInfo,
+    /// 按顺序拼接即为完整内容
+    chunks: Vec<String>,
+}
+
+/// 一个文件的全部快照
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct SnapshotRefs {
+    path: String,
+    /// 最旧的在前
+    snapshots: Vec<Snapshot>,
+}
+
+/// 垃圾回收结果
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SnapshotGcResult {
+    /// 删除的块数
+    pub removed: usize,
+    pub freed_bytes: u64,
+    /// 仍被引用的块数
+    pub kept: usize,
+}
+
+/// 应用数据目录中的快照存储
+pub struct SnapshotStore {
+    dir: PathBuf,
+    /// 写入、删除和垃圾回收互斥，回收时不会删掉正在写入的快照引用的块
+    lock: Mutex<()>,
+}
+
+fn hash_hex(data: &[u8]) -> String {
+    blake3::hash(data).to_hex().to_string()
+}
+
+/// 内容定义分块：在滚动哈希的低位全为 0 处切分
+fn chunk_boundaries(data: &[u8]) -> Vec<&[u8]> {
+    let mut chunks = Vec::new();
+    let mut start = 0;
+    while start < data.len() {
+        let rest = &data[start..];
+        let mut end = rest.len().min(MAX_CHUNK);
+        let mut hash: u64 = 0;
+        for (i, byte) in rest.iter().enumerate().take(end) {
+            hash = (hash << 1).wrapping_add(GEAR[*byte as usize]);
+            if i + 1 >= MIN_CHUNK && hash & CHUNK_MASK == 0 {
+                end = i + 1;
+                break;
+            }
+        }
+        chunks.push(&rest[..end]);
+        start += end;
+    }
+    chunks
+}
+
+impl SnapshotStore {
+    pub fn new(dir: PathBuf) -> Self {
+        SnapshotStore {
+            dir,
+            lock: Mutex::new(()),
+        }
+    }
+
+    fn object_path(&self, hash: &str) -> PathBuf {
+        self.dir
+            .join(OBJECTS_DIR)
+            .join(&hash[..2])
+            .join(format!("{}.zst", &hash[2..]))
+    }
+
+    /// 文件的快照列表，按完整路径的哈希命名
+    fn refs_path(&self, path: &Path) -> PathBuf {
+        let absolute = paths::canonicalize(path);
+        let hash = hash_hex(absolute.to_string_lossy().as_bytes());
+        self.dir
+            .join(REFS_DIR)
+            .join(format!("{}.json", &hash[..16]))
+    }
+
+    fn load_refs(&self, path: &Path) -> SnapshotRefs {
+        storage::load_json(&self.refs_path(path))
+    }
+
+    fn save_refs(&self, path: &Path, refs: &SnapshotRefs) -> Result<(), String> {
+        let refs_path = self.refs_path(path);
+        if refs.snapshots.is_empty() {
+            return match fs::remove_file(&refs_path) {
+                Err(e) if e.kind() != std::io::ErrorKind::NotFound => {
+                    Err(format!("Failed to remove {:?}: {}", refs_path, e))
+                }
+                _ => Ok(()),
+            };
+        }
+        storage::save_json(&refs_path, refs)
+    }
+
+    /// 保存一个块，已存在时跳过
+    fn put_chunk(&self, chunk: &[u8]) -> Result<String, String> {
+        let hash = hash_hex(chunk);
+        let path = self.object_path(&hash);
+        if path.is_file() {
+            return Ok(hash);
+        }
+        let compressed = zstd::bulk::compress(chunk, COMPRESSION_LEVEL)
+            .map_err(|e| format!("Failed to compress chunk: {}", e))?;
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).map_err(|e| format!("Failed to create directory: {}", e))?;
+        }
+        storage::write_atomic(&path, &compressed)?;
+        Ok(hash)
+    }
+
+    /// 读取一个块并校验哈希
+    fn get_chunk(&self, hash: &str) -> Result<Vec<u8>, String> {
+        let path = self.object_path(hash);
+        let compressed =
+            fs::read(&path).map_err(|e| format!("Missing snapshot chunk {}: {}", hash, e))?;
+        let data = zstd::decode_all(compressed.as_slice())
+            .map_err(|e| format!("Corrupt snapshot chunk {}: {}", hash, e))?;
+        if hash_hex(&data) != hash {
+            return Err(format!("Corrupt snapshot chunk {}: hash mismatch", hash));
+        }
+        Ok(data)
+    }
+
+    /// 保存文件内容的快照；与最新快照相同时直接返回最新快照
+    pub fn create(
+        &self,
+        path: &Path,
+        data: &[u8],
+        label: Option<String>,
+    ) -> Result<SnapshotInfo, String> {
+        let _guard = self.lock.lock().map_err(|e| e.to_string())?;
+        let mut refs = self.load_refs(path);
+        let hash = hash_hex(data);
+        if let Some(latest) = refs.snapshots.last() {
+            if latest.info.hash == hash && label.is_none() {
+                return Ok(latest.info.clone());
+            }
+        }
+
+        let chunks = chunk_boundaries(data)
+            .into_iter()
+            .map(|chunk| self.put_chunk(chunk))
+            .collect::<Result<Vec<_>, _>>()?;
+        let now = Local::now();
+        let mut id = now.format(ID_FORMAT).to_string();
+        // 同一毫秒内创建的多个快照
+        let mut n = 1;
+        while refs.snapshots.iter().any(|s| s.info.id == id) {
+            id = format!("{}-{}", now.format(ID_FORMAT), n);
+            n += 1;
+        }
+        let info = SnapshotInfo {
+            id,
+            created_at: u64::try_from(now.timestamp_millis()).unwrap_or(0),
+            size: data.len() as u64,
+            hash,
+            label: label.filter(|l| !l.trim().is_empty()),
+        };
+        refs.path = path.to_string_lossy().to_string();
+        refs.snapshots.push(Snapshot {
+            info: info.clone(),
+            chunks,
+        });
+        self.save_refs(path, &refs)?;
+        Ok(info)
+    }
+
+    /// 文件的所有快照，最新的在前
+    pub fn list(&self, path: &Path) -> Vec<SnapshotInfo> {
+        self.load_refs(path)
+            .snapshots
+            .into_iter()
+            .rev()
+            .map(|snapshot| snapshot.info)
+            .collect()
+    }
+
+    /// 读取快照的完整内容
+    pub fn read(&self, path: &Path, id: &str) -> Result<Vec<u8>, String> {
+        let refs = self.load_refs(path);
+        let snapshot = refs
+            .snapshots
+            .iter()
+            .find(|s| s.info.id == id)
+            .ok_or_else(|| format!("Snapshot not found: {}", id))?;
+        let mut data = Vec::with_capacity(snapshot.info.size as usize);
+        for hash in &snapshot.chunks {
+            data.extend(self.get_chunk(hash)?);
+        }
+        if hash_hex(&data) != snapshot.info.hash {
+            return Err(format!("Corrupt snapshot {}: hash mismatch", id));
+        }
+        Ok(data)
+    }
+
+    /// 删除快照，块在垃圾回收时才清理
+    pub fn delete(&self, path: &Path, id: &str) -> Result<bool, String> {
+        let _guard = self.lock.lock().map_err(|e| e.to_string())?;
+        let mut refs = self.load_refs(path);
+        let before = refs.snapshots.len();
+        refs.snapshots.retain(|s| s.info.id != id);
+        if refs.snapshots.len() == before {
+            return Ok(false);
+        }
+        self.save_refs(path, &refs)?;
+        Ok(true)
+    }
+
+    /// 删除不再被任何快照引用的块
+    pub fn collect_garbage(&self) -> Result<SnapshotGcResult, String> {
+        let _guard = self.lock.lock().map_err(|e| e.to_string())?;
+        let mut live: HashSet<String> = HashSet::new();
+        if let Ok(entries) = fs::read_dir(self.dir.join(REFS_DIR)) {
+            for entry in entries.flatten() {
+                let path = entry.path();
+                if path.extension().and_then(|e| e.to_str()) != Some("json") {
+                    continue;
+                }
+                // 无法读取的快照列表不能确定引用了哪些块，放弃本次回收
+                let content = fs::read_to_string(&path)
+                    .map_err(|e| format!("Failed to read {:?}: {}", path, e))?;
+                let refs: SnapshotRefs = serde_json::from_str(&content)
+                    .map_err(|e| format!("Invalid snapshot list {:?}: {}", path, e))?;
+                live.extend(refs.snapshots.into_iter().flat_map(|s| s.chunks));
+            }
+        }
+
+        let mut result = SnapshotGcResult {
+            removed: 0,
+            freed_bytes: 0,
+            kept: 0,
+        };
+        let Ok(prefixes) = fs::read_dir(self.dir.join(OBJECTS_DIR)) else {
+            return Ok(result);
+        };
+        for prefix in prefixes.flatten() {
+            let prefix_path = prefix.path();
+            let prefix_name = prefix.file_name().to_string_lossy().to_string();
+            let Ok(objects) = fs::read_dir(&prefix_path) else {
+                continue;
+            };
+            for object in objects.flatten() {
+                let name = object.file_name().to_string_lossy().to_string();
+                let Some(rest) = name.strip_suffix(".zst") else {
+                    continue;
+                };
+                if live.contains(&format!("{}{}", prefix_name, rest)) {
+                    result.kept += 1;
+                    continue;
+                }
+                let size = object.metadata().map(|m| m.len()).unwrap_or(0);
+                match fs::remove_file(object.path()) {
+                    Ok(()) => {
+                        result.removed += 1;
+                        result.freed_bytes += size;
+                    }
+                    Err(e) => log::warn!("[snapshots] Failed to remove {}: {}", name, e),
+                }
+            }
+            // 清空的前缀目录一并删除，非空时删除失败即可忽略
+            let _ = fs::remove_dir(&prefix_path);
+        }
+        Ok(result)
+    }
+}
+
+// 为文件当前的磁盘内容创建快照
+#[tauri::command]
+pub async fn create_snapshot(
+    app: AppHandle,
+    path: String,
+    label: Option<String>,
+) -> Result<SnapshotInfo, VividError> {
+    let start = Instant::now();
+    let path_buf = PathBuf::from(&path);
+    access::ensure_access(&app, &path_buf, AccessKind::Read)?;
+    log::info!("[create_snapshot] Creating snapshot of {}", path);
+    if !path_buf.is_file() {
+        log::error!("[create_snapshot] File does not exist: {}", path);
+        return Err(VividError::not_found(&path_buf));
+    }
+    let data = fs::read(&path_buf).map_err(|e| {
+        log::error!("[create_snapshot] Failed to read file: {}", e);
+        VividError::io("Failed to read file", &path_buf, &e)
+    })?;
+
+    let info = tauri::async_runtime::spawn_blocking(move || {
+        app.state::<SnapshotStore>().create(&path_buf, &data, label)
+    })
+    .await
+    .map_err(|e| format!("Snapshot task failed: {}", e))?
+    .map_err(|e| {
+        log::error!("[create_snapshot] {}", e);
+        VividError::from(e)
+    })?;
+    log::info!(
+        "[create_snapshot] ✓ Success: {} ({} bytes) in {:?}",
+        info.id,
+        info.size,
+        start.elapsed()
+    );
+    Ok(info)
+}
+
+// 列出文件的快照（最新的在前）
+#[tauri::command]
+pub fn list_snapshots(
+    app: AppHandle,
+    store: State<'_, SnapshotStore>,
+    path: String,
+) -> Result<Vec<SnapshotInfo>, VividError> {
+    access::ensure_access(&app, Path::new(&path), AccessKind::Read)?;
+    Ok(store.list(Path::new(&path)))
+}
+
+// 读取快照内容（文本）
+#[tauri::command]
+pub fn read_snapshot(
+    app: AppHandle,
+    store: State<'_, SnapshotStore>,
+    path: String,
+    id: String,
+) -> Result<String, VividError> {
+    access::ensure_access(&app, Path::new(&path), AccessKind::Read)?;
+    let data = store.read(Path::new(&path), &id).map_err(|e| {
+        log::error!("[read_snapshot] {}", e);
+        VividError::from(e)
+    })?;
+    String::from_utf8(data).map_err(|_| VividError::Encoding {
+        path: Some(path),
+        message: "Snapshot is not valid UTF-8 text".to_string(),
+    })
+}
+
+// 用快照覆盖文件，覆盖前先为当前内容创建快照以便撤销，返回恢复后的文件信息
+#[tauri::command]
+pub fn restore_snapshot(app: AppHandle, path: String, id: String) -> Result<FileInfo, VividError> {
+    log::info!("[restore_snapshot] Restoring {} from snapshot {}", path, id);
+    let store = app.state::<SnapshotStore>();
+    let path_buf = PathBuf::from(&path);
+    access::ensure_access(&app, &path_buf, AccessKind::Write)?;
+    let data = store.read(&path_buf, &id)?;
+    if let Ok(current) = fs::read(&path_buf) {
+        store.create(&path_buf, &current, None)?;
+    }
+    storage::write_atomic(&path_buf, &data).map_err(|e| {
+        log::error!("[restore_snapshot] Failed to restore: {}", e);
+        VividError::from(e)
+    })?;
+    log::info!("[restore_snapshot] ✓ Success: {}", path);
+    crate::load_file(
+        app.state::<LargeFileStore>(),
+        app.state::<crate::encryption::EncryptionKeys>(),
+        path,
+    )
+}
+
+// 删除快照，返回是否找到该快照
+#[tauri::command]
+pub fn delete_snapshot(
+    app: AppHandle,
+    store: State<'_, SnapshotStore>,
+    path: String,
+    id: String,
+) -> Result<bool, VividError> {
+    access::ensure_access(&app, Path::new(&path), AccessKind::Delete)?;
+    let deleted = store.delete(Path::new(&path), &id)?;
+    log::info!("[delete_snapshot] {} {}: {}", path, id, deleted);
+    Ok(deleted)
+}
+
+// 清理不再被任何快照引用的块
+#[tauri::command]
+pub async fn collect_snapshot_garbage(app: AppHandle) -> Result<SnapshotGcResult, VividError> {
+    let start = Instant::now();
+    let result = tauri::async_runtime::spawn_blocking(move || {
+        app.state::<SnapshotStore>().collect_garbage()
+    })
+    .await
+    .map_err(|e| format!("Snapshot task failed: {}", e))?
+    .map_err(|e| {
+        log::error!("[collect_snapshot_garbage] {}", e);
+        VividError::from(e)
+    })?;
+    log::info!(
+        "[collect_snapshot_garbage] ✓ Success: removed {} chunk(s) ({} bytes), kept {} in {:?}",
+        result.removed,
+        result.freed_bytes,
+        result.kept,
+        start.elapsed()
+    );
+    Ok(result)
+}
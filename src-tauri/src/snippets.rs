@@ -0,0 +1,542 @@
+//! 文本片段
+//!
+//! 片段保存在应用数据目录的 `snippets.json` 中，首次使用时写入几个示例片段。编辑器在输入
+//! 触发词后调用 `expand_snippet`，后端替换变量、解析占位符，返回展开后的文本和各个跳转位置。
+//!
+//! 片段正文的写法：
+//!
+//! - 变量与模板相同（见 `templates`）：`{{date}}`、`{{time}}`、`{{datetime}}`（可带 strftime
+//!   格式，如 `{{date:%Y/%m/%d}}`）、`{{filename}}`、`{{title}}`，另有 `{{clipboard}}`（剪贴板
+//!   中的文本）、`{{selection}}`（编辑器中选中的文本），其它变量由调用方传入；
+//! - 跳转位置与 VS Code 相同：`$1`、`${1:默认文本}`，按序号依次跳转，`$0` 为最后的光标位置；
+//!   `\$` 表示字面的 `$`。
+//!
+//! `export_snippets` / `import_snippets` 以 JSON 文件分享片段集，导入时也接受 VS Code 的
+//! 片段文件（`prefix` / `body` / `description`）。
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use chrono::Local;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tauri::{AppHandle, State};
+
+use crate::access::{self, AccessKind};
+use crate::error::VividError;
+use crate::parse::utf16_offset;
+use crate::{storage, templates};
+
+const SNIPPETS_FILE: &str = "snippets.json";
+/// 片段文件的格式版本
+const SNIPPETS_VERSION: u32 = 1;
+
+/// 首次使用时写入的示例片段：`(触发词, 名称, 正文)`
+const DEFAULT_SNIPPETS: &[(&str, &str, &str)] = &[
+    (";today", "Today", "{{date}}"),
+    (";now", "Current time", "{{datetime}}"),
+    (
+        ";link",
+        "Link",
+        "[${1:{{selection}}}](${2:{{clipboard}}})$0",
+    ),
+    (
+        ";code",
+        "Code block",
+        "```${1:lang}\n${2:{{selection}}}\n```\n$0",
+    ),
+    (
+        ";table",
+        "Table",
+        "| ${1:Column} | ${2:Column} |\n| --- | --- |\n| $3 | $4 |\n$0",
+    ),
+    (";todo", "Task", "- [ ] ${1:Task} ({{date}})$0"),
+];
+
+/// 片段
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Snippet {
+    /// 触发词，不含空白
+    pub trigger: String,
+    #[serde(default)]
+    pub name: String,
+    #[serde(default)]
+    pub description: Option<String>,
+    pub body: String,
+}
+
+/// 片段文件
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct SnippetSet {
+    version: u32,
+    snippets: Vec<Snippet>,
+}
+
+/// 展开时的上下文
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct SnippetContext {
+    /// 当前文档，用于 `{{filename}}` / `{{title}}`
+    pub path: Option<String>,
+    /// 编辑器中选中的文本
+    pub selection: Option<String>,
+    /// 其它变量
+    pub variables: HashMap<String, String>,
+}
+
+/// 一个跳转位置（UTF-16 偏移）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TabStop {
+    pub index: u32,
+    pub start: usize,
+    pub end: usize,
+}
+
+/// 展开结果
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ExpandedSnippet {
+    pub trigger: String,
+    pub text: String,
+    /// 按跳转顺序排列（`$1`、`$2`……），同序号的多个位置需同步编辑
+    pub tab_stops: Vec<TabStop>,
+    /// 展开后的光标位置：第一个跳转位置，没有时为 `$0` 或文本末尾
+    pub cursor: usize,
+}
+
+/// 导入结果
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct ImportSnippetsResult {
+    pub added: usize,
+    pub updated: usize,
+    /// 已存在且未选择覆盖，或格式不正确的片段
+    pub skipped: usize,
+}
+
+fn validate(snippet: &Snippet) -> Result<(), VividError> {
+    let trigger = snippet.trigger.as_str();
+    if trigger.is_empty() || trigger.chars().any(char::is_whitespace) {
+        return Err(VividError::invalid_input(format!(
+            "Invalid snippet trigger: {:?}",
+            trigger
+        )));
+    }
+    if snippet.body.is_empty() {
+        return Err(VividError::invalid_input(format!(
+            "Snippet body must not be empty: {}",
+            trigger
+        )));
+    }
+    Ok(())
+}
+
+/// 变量值中的 `$`、`}` 和 `\` 转义，避免被当作跳转位置
+fn escape_value(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('$', "\\$")
+        .replace('}', "\\}")
+}
+
+/// 解析跳转位置：返回去掉标记后的文本和各位置的字节区间
+fn parse_tab_stops(text: &str) -> (String, Vec<(u32, usize, usize)>) {
+    let mut out = String::with_capacity(text.len());
+    let mut stops = Vec::new();
+    // 未闭合的 `${n:`：序号和默认文本的起始位置
+    let mut open: Vec<(u32, usize)> = Vec::new();
+    let mut chars = text.char_indices().peekable();
+
+    let digits = |start: usize| -> Option<(u32, usize)> {
+        let len = text[start..]
+            .find(|c: char| !c.is_ascii_digit())
+            .unwrap_or(text.len() - start);
+        let index = text[start..start + len].parse().ok()?;
+        Some((index, len))
+    };
+
+    while let Some((i, c)) = chars.next() {
+        match c {
+            '\\' if matches!(chars.peek(), Some((_, '$' | '\\' | '}'))) => {
+                let (_, escaped) = chars.next().expect("peeked");
+                out.push(escaped);
+            }
+            '$' => {
+                let next = i + 1;
+                if let Some((index, len)) = digits(next) {
+                    stops.push((index, out.len(), out.len()));
+                    for _ in 0..len {
+                        chars.next();
+                    }
+                    continue;
+                }
+                if text[next..].starts_with('{') {
+                    if let Some((index, len)) = digits(next + 1) {
+                        match text[next + 1 + len..].chars().next() {
+                            Some('}') => {
+                                stops.push((index, out.len(), out.len()));
+                                for _ in 0..len + 2 {
+                                    chars.next();
+                                }
+                                continue;
+                            }
+                            Some(':') => {
+                                open.push((index, out.len()));
+                                for _ in 0..len + 2 {
+                                    chars.next();
+                                }
+                                continue;
+                            }
+                            _ => {}
+                        }
+                    }
+                }
+                out.push('$');
+            }
+            '}' if !open.is_empty() => {
+                let (index, start) = open.pop().expect("not empty");
+                stops.push((index, start, out.len()));
+            }
+            c => out.push(c),
+        }
+    }
+    // 未闭合的默认文本延续到末尾
+    while let Some((index, start)) = open.pop() {
+        stops.push((index, start, out.len()));
+    }
+    (out, stops)
+}
+
+/// 展开片段正文：先替换变量，再解析跳转位置
+pub fn expand(
+    snippet: &Snippet,
+    context: &SnippetContext,
+    clipboard: Option<String>,
+) -> ExpandedSnippet {
+    let mut variables: HashMap<String, String> = context
+        .variables
+        .iter()
+        .map(|(name, value)| (name.clone(), escape_value(value)))
+        .collect();
+    if let Some(path) = context.path.as_deref().map(Path::new) {
+        let name = path.file_name().map(|n| n.to_string_lossy().to_string());
+        let stem = path.file_stem().map(|s| s.to_string_lossy().to_string());
+        variables.insert(
+            "filename".to_string(),
+            escape_value(&name.unwrap_or_default()),
+        );
+        variables
+            .entry("title".to_string())
+            .or_insert_with(|| escape_value(&stem.unwrap_or_default()));
+    }
+    let selection = context.selection.as_deref().unwrap_or("");
+    variables.insert("selection".to_string(), escape_value(selection));
+    variables.insert(
+        "clipboard".to_string(),
+        escape_value(clipboard.as_deref().unwrap_or("")),
+    );
+
+    let rendered = templates::render_template(&snippet.body, &variables, &Local::now());
+    let (text, mut stops) = parse_tab_stops(&rendered);
+    // `$1`、`$2`……在前，`$0` 最后
+    stops.sort_by_key(|&(index, start, _)| (index == 0, index, start));
+    let tab_stops: Vec<TabStop> = stops
+        .into_iter()
+        .map(|(index, start, end)| TabStop {
+            index,
+            start: utf16_offset(&text, start),
+            end: utf16_offset(&text, end),
+        })
+        .collect();
+    let cursor = tab_stops
+        .first()
+        .map_or_else(|| utf16_offset(&text, text.len()), |stop| stop.start);
+    ExpandedSnippet {
+        trigger: snippet.trigger.clone(),
+        text,
+        tab_stops,
+        cursor,
+    }
+}
+
+/// 读取导入文件：本应用导出的片段集，或 VS Code 片段文件
+fn parse_import(json: &str) -> Result<Vec<Snippet>, String> {
+    let value: Value =
+        serde_json::from_str(json).map_err(|e| format!("Invalid snippets file: {}", e))?;
+    if value.get("snippets").is_some_and(Value::is_array) {
+        let set: SnippetSet =
+            serde_json::from_value(value).map_err(|e| format!("Invalid snippets file: {}", e))?;
+        return Ok(set.snippets);
+    }
+    let Value::Object(entries) = value else {
+        return Err("Invalid snippets file: expected an object".to_string());
+    };
+    // VS Code：{ "名称": { "prefix": "...", "body": ["..."], "description": "..." } }
+    let text = |value: Option<&Value>| match value {
+        Some(Value::String(s)) => Some(s.clone()),
+        Some(Value::Array(items)) => Some(
+            items
+                .iter()
+                .filter_map(Value::as_str)
+                .collect::<Vec<_>>()
+                .join("\n"),
+        ),
+        _ => None,
+    };
+    Ok(entries
+        .iter()
+        .filter_map(|(name, entry)| {
+            let trigger = match entry.get("prefix") {
+                Some(Value::Array(prefixes)) => prefixes.first()?.as_str()?.to_string(),
+                prefix => text(prefix)?,
+            };
+            Some(Snippet {
+                trigger,
+                name: name.clone(),
+                description: text(entry.get("description")),
+                body: text(entry.get("body"))?,
+            })
+        })
+        .collect())
+}
+
+/// 片段存储
+pub struct SnippetStore {
+    path: PathBuf,
+    snippets: Mutex<Vec<Snippet>>,
+}
+
+impl SnippetStore {
+    pub fn load(dir: PathBuf) -> Self {
+        let path = dir.join(SNIPPETS_FILE);
+        let snippets = if path.exists() {
+            storage::load_json::<SnippetSet>(&path).snippets
+        } else {
+            DEFAULT_SNIPPETS
+                .iter()
+                .map(|(trigger, name, body)| Snippet {
+                    trigger: trigger.to_string(),
+                    name: name.to_string(),
+                    description: None,
+                    body: body.to_string(),
+                })
+                .collect()
+        };
+        SnippetStore {
+            path,
+            snippets: Mutex::new(snippets),
+        }
+    }
+
+    pub fn list(&self) -> Vec<Snippet> {
+        self.snippets
+            .lock()
+            .map(|snippets| snippets.clone())
+            .unwrap_or_default()
+    }
+
+    pub fn find(&self, trigger: &str) -> Option<Snippet> {
+        self.snippets
+            .lock()
+            .ok()?
+            .iter()
+            .find(|snippet| snippet.trigger == trigger)
+            .cloned()
+    }
+
+    /// 在锁内修改片段列表并写回，返回修改后的列表
+    fn update<T>(
+        &self,
+        f: impl FnOnce(&mut Vec<Snippet>) -> T,
+    ) -> Result<(T, Vec<Snippet>), String> {
+        let mut snippets = self.snippets.lock().map_err(|e| e.to_string())?;
+        let result = f(&mut snippets);
+        snippets.sort_by(|a, b| a.trigger.cmp(&b.trigger));
+        storage::save_json(
+            &self.path,
+            &SnippetSet {
+                version: SNIPPETS_VERSION,
+                snippets: snippets.clone(),
+            },
+        )?;
+        Ok((result, snippets.clone()))
+    }
+
+    /// 添加片段或替换同触发词的片段，返回是否为新增
+    fn upsert(snippets: &mut Vec<Snippet>, snippet: Snippet) -> bool {
+        match snippets.iter_mut().find(|s| s.trigger == snippet.trigger) {
+            Some(existing) => {
+                *existing = snippet;
+                false
+            }
+            None => {
+                snippets.push(snippet);
+                true
+            }
+        }
+    }
+}
+
+// 列出所有片段
+#[tauri::command]
+pub fn list_snippets(store: State<'_, SnippetStore>) -> Vec<Snippet> {
+    store.list()
+}
+
+// 添加或更新片段（按触发词），返回更新后的列表
+#[tauri::command]
+pub fn save_snippet(
+    store: State<'_, SnippetStore>,
+    snippet: Snippet,
+    previous_trigger: Option<String>,
+) -> Result<Vec<Snippet>, VividError> {
+    validate(&snippet)?;
+    log::info!("[save_snippet] {}", snippet.trigger);
+    let (_, snippets) = store.update(|snippets| {
+        // 修改了触发词时去掉旧的片段
+        if let Some(previous) = previous_trigger.filter(|p| *p != snippet.trigger) {
+            snippets.retain(|s| s.trigger != previous);
+        }
+        SnippetStore::upsert(snippets, snippet)
+    })?;
+    Ok(snippets)
+}
+
+// 删除片段，返回更新后的列表
+#[tauri::command]
+pub fn delete_snippet(
+    store: State<'_, SnippetStore>,
+    trigger: String,
+) -> Result<Vec<Snippet>, VividError> {
+    let (removed, snippets) = store.update(|snippets| {
+        let before = snippets.len();
+        snippets.retain(|s| s.trigger != trigger);
+        before != snippets.len()
+    })?;
+    if !removed {
+        return Err(VividError::invalid_input(format!(
+            "Snippet not found: {}",
+            trigger
+        )));
+    }
+    log::info!("[delete_snippet] ✓ Success: {}", trigger);
+    Ok(snippets)
+}
+
+// 展开触发词对应的片段，没有该片段时返回空
+#[tauri::command]
+pub async fn expand_snippet(
+    store: State<'_, SnippetStore>,
+    trigger: String,
+    context: Option<SnippetContext>,
+) -> Result<Option<ExpandedSnippet>, VividError> {
+    let Some(snippet) = store.find(&trigger) else {
+        log::debug!("[expand_snippet] No snippet for {:?}", trigger);
+        return Ok(None);
+    };
+    let context = context.unwrap_or_default();
+    let uses_clipboard = templates::custom_variables(&snippet.body)
+        .iter()
+        .any(|name| name == "clipboard");
+    // 只在片段用到时读取剪贴板
+    let clipboard = if uses_clipboard {
+        tauri::async_runtime::spawn_blocking(|| {
+            arboard::Clipboard::new()
+                .and_then(|mut clipboard| clipboard.get().text())
+                .map_err(|e| log::warn!("[expand_snippet] Failed to read clipboard: {}", e))
+                .ok()
+        })
+        .await
+        .map_err(|e| format!("Clipboard task failed: {}", e))?
+    } else {
+        None
+    };
+    let expanded = expand(&snippet, &context, clipboard);
+    log::debug!(
+        "[expand_snippet] {} -> {} chars, {} tab stop(s)",
+        trigger,
+        expanded.text.chars().count(),
+        expanded.tab_stops.len()
+    );
+    Ok(Some(expanded))
+}
+
+// 把片段导出为 JSON 文件，`triggers` 为空时导出全部，返回导出的数量
+#[tauri::command]
+pub fn export_snippets(
+    app: AppHandle,
+    store: State<'_, SnippetStore>,
+    path: String,
+    triggers: Option<Vec<String>>,
+) -> Result<usize, VividError> {
+    access::ensure_access(&app, Path::new(&path), AccessKind::Write)?;
+    let snippets: Vec<Snippet> = store
+        .list()
+        .into_iter()
+        .filter(|snippet| {
+            triggers
+                .as_ref()
+                .map_or(true, |triggers| triggers.contains(&snippet.trigger))
+        })
+        .collect();
+    let count = snippets.len();
+    storage::save_json(
+        Path::new(&path),
+        &SnippetSet {
+            version: SNIPPETS_VERSION,
+            snippets,
+        },
+    )
+    .map_err(|e| {
+        log::error!("[export_snippets] {}", e);
+        e
+    })?;
+    log::info!(
+        "[export_snippets] ✓ Success: {} snippet(s) -> {}",
+        count,
+        path
+    );
+    Ok(count)
+}
+
+// 从 JSON 文件导入片段，`overwrite` 为 true 时替换同触发词的现有片段
+#[tauri::command]
+pub fn import_snippets(
+    app: AppHandle,
+    store: State<'_, SnippetStore>,
+    path: String,
+    overwrite: Option<bool>,
+) -> Result<ImportSnippetsResult, VividError> {
+    let path_buf = PathBuf::from(&path);
+    access::ensure_access(&app, &path_buf, AccessKind::Read)?;
+    let json = fs::read_to_string(&path_buf)
+        .map_err(|e| VividError::io("Failed to read file", &path_buf, &e))?;
+    let imported = parse_import(&json).map_err(|e| {
+        log::error!("[import_snippets] {}", e);
+        VividError::invalid_input(e)
+    })?;
+    let overwrite = overwrite.unwrap_or(false);
+
+    let (result, _) = store.update(|snippets| {
+        let mut result = ImportSnippetsResult::default();
+        for snippet in imported {
+            let exists = snippets.iter().any(|s| s.trigger == snippet.trigger);
+            if validate(&snippet).is_err() || (exists && !overwrite) {
+                result.skipped += 1;
+            } else if SnippetStore::upsert(snippets, snippet) {
+                result.added += 1;
+            } else {
+                result.updated += 1;
+            }
+        }
+        result
+    })?;
+    log::info!(
+        "[import_snippets] ✓ Success: {} added, {} updated, {} skipped from {}",
+        result.added,
+        result.updated,
+        result.skipped,
+        path
+    );
+    Ok(result)
+}
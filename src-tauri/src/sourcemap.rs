@@ -0,0 +1,163 @@
+//! 编辑区与预览区的滚动同步
+//!
+//! `compute_source_map` 按后端渲染器（见 `render`）的解析方式把文档切分为顶层块，返回每个块
+//! 对应的源码行范围和在渲染结果中的序号：第 `index` 个块就是渲染出的 HTML 中第 `index` 个
+//! 顶层元素。前端据此在编辑区的行和预览区的元素之间双向定位，而不是按滚动百分比估算。
+//!
+//! `:::` Admonition 容器渲染为一个 `<div>`，整体作为一个块；标题块带有与大纲一致的锚点。
+//! 文献引用和插件的渲染钩子不改变行结构，计算时不执行。
+
+use std::collections::HashMap;
+use std::time::Instant;
+
+use pulldown_cmark::{Event, Tag};
+use serde::{Deserialize, Serialize};
+
+use crate::error::VividError;
+use crate::markdown;
+use crate::parse::{self, LineIndex};
+
+/// 一个顶层块
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SourceBlock {
+    /// 在渲染结果中的序号
+    pub index: usize,
+    /// `paragraph`、`heading`、`code_block`、`list`、`blockquote`、`table`、`html`、
+    /// `thematic_break`、`footnote_definition`、`admonition` 等
+    pub kind: String,
+    /// 源码行范围（从 0 开始，含两端）
+    pub start_line: usize,
+    pub end_line: usize,
+    /// 标题的锚点
+    pub anchor: Option<String>,
+    pub level: Option<u8>,
+}
+
+/// 文档的块映射，按源码顺序排列
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SourceMap {
+    pub blocks: Vec<SourceBlock>,
+    pub line_count: usize,
+}
+
+fn block_kind(tag: &Tag<'_>) -> &'static str {
+    match tag {
+        Tag::Paragraph => "paragraph",
+        Tag::Heading { .. } => "heading",
+        Tag::BlockQuote(_) => "blockquote",
+        Tag::CodeBlock(_) => "code_block",
+        Tag::HtmlBlock => "html",
+        Tag::List(_) => "list",
+        Tag::FootnoteDefinition(_) => "footnote_definition",
+        Tag::Table(_) => "table",
+        _ => "other",
+    }
+}
+
+/// 计算文档的块映射
+pub fn source_map(content: &str) -> SourceMap {
+    let (source, origins) = markdown::expand_admonitions_with_lines(content);
+    let lines = LineIndex::new(&source);
+    // 展开后的行号 → 原文行号
+    let origin = |line: usize| origins.get(line).or(origins.last()).copied().unwrap_or(0);
+    let anchors: HashMap<usize, (String, u8)> = parse::outline(&source)
+        .into_iter()
+        .map(|heading| (heading.start, (heading.slug, heading.level)))
+        .collect();
+
+    let mut blocks: Vec<SourceBlock> = Vec::new();
+    let mut depth = 0usize;
+    // 未闭合的 Admonition 容器的起始行和嵌套层数
+    let mut admonition: Option<(usize, usize)> = None;
+
+    for (event, range) in markdown::parser(&source).into_offset_iter() {
+        let kind = match &event {
+            Event::Start(tag) => {
+                depth += 1;
+                if depth > 1 {
+                    continue;
+                }
+                block_kind(tag)
+            }
+            Event::End(_) => {
+                depth = depth.saturating_sub(1);
+                continue;
+            }
+            _ if depth > 0 => continue,
+            Event::Rule => "thematic_break",
+            Event::Html(_) => "html",
+            _ => "other",
+        };
+        // 块末尾的换行属于该块的最后一行
+        let end = source[..range.end]
+            .trim_end_matches(['\r', '\n'])
+            .len()
+            .max(range.start);
+        let start_line = origin(lines.line_of(range.start));
+        let end_line = origin(lines.line_of(end)).max(start_line);
+
+        if kind == "html" {
+            let html = source[range.clone()].trim_start();
+            if html.starts_with("<div class=\"admonition ") {
+                admonition = match admonition {
+                    Some((start, nested)) => Some((start, nested + 1)),
+                    None => Some((start_line, 1)),
+                };
+                continue;
+            }
+            if let Some((start, nested)) = admonition {
+                if html.starts_with("</div></div>") {
+                    if nested > 1 {
+                        admonition = Some((start, nested - 1));
+                    } else {
+                        admonition = None;
+                        blocks.push(SourceBlock {
+                            index: blocks.len(),
+                            kind: "admonition".to_string(),
+                            start_line: start,
+                            end_line,
+                            anchor: None,
+                            level: None,
+                        });
+                    }
+                    continue;
+                }
+            }
+        }
+        if admonition.is_some() {
+            continue;
+        }
+
+        let heading = anchors.get(&range.start).filter(|_| kind == "heading");
+        blocks.push(SourceBlock {
+            index: blocks.len(),
+            kind: kind.to_string(),
+            start_line,
+            end_line,
+            anchor: heading.map(|(slug, _)| slug.clone()),
+            level: heading.map(|(_, level)| *level),
+        });
+    }
+
+    SourceMap {
+        blocks,
+        line_count: content.split('\n').count(),
+    }
+}
+
+// 计算源码行与渲染结果中顶层块的对应关系，供编辑区和预览区同步滚动
+#[tauri::command]
+pub async fn compute_source_map(content: String) -> Result<SourceMap, VividError> {
+    let start = Instant::now();
+    let size = content.len();
+    let map = tauri::async_runtime::spawn_blocking(move || source_map(&content))
+        .await
+        .map_err(|e| format!("Source map task failed: {}", e))?;
+    log::debug!(
+        "[compute_source_map] {} bytes -> {} block(s) in {:?}",
+        size,
+        map.blocks.len(),
+        start.elapsed()
+    );
+    Ok(map)
+}
@@ -0,0 +1,329 @@
+//! 拼写检查
+//!
+//! 使用 Hunspell 格式的词典（`<语言>.aff` + `<语言>.dic`），依次在应用数据目录的
+//! `dictionaries/` 和系统词典目录中查找。检查时跳过 front matter、代码、数学公式、HTML、
+//! Wiki 链接、网址和标签，中日韩文字不做检查。
+//! 用户词典保存在应用数据目录的 `user_dictionary.json` 中，对所有语言生效。
+
+use std::collections::{BTreeSet, HashMap};
+use std::fs;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+
+use pulldown_cmark::{Event, Tag, TagEnd};
+use serde::{Deserialize, Serialize};
+use spellbook::Dictionary;
+use tauri::{AppHandle, Manager, State};
+
+use crate::error::VividError;
+use crate::links::{self, LinkKind};
+use crate::markdown::{self, is_cjk_char};
+use crate::parse::{utf16_offset, LineIndex};
+use crate::{frontmatter, storage};
+
+const USER_DICTIONARY_FILE: &str = "user_dictionary.json";
+const DICTIONARIES_DIR: &str = "dictionaries";
+/// 系统中常见的 Hunspell 词典目录，不存在的目录会被跳过
+const SYSTEM_DICTIONARY_DIRS: &[&str] = &[
+    "/usr/share/hunspell",
+    "/usr/share/myspell",
+    "/usr/share/myspell/dicts",
+    "/Library/Spelling",
+];
+/// 每个拼写错误最多返回的建议数
+const MAX_SUGGESTIONS: usize = 5;
+
+/// 一处拼写错误
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Misspelling {
+    pub word: String,
+    /// 所在行（从 0 开始）
+    pub line_index: usize,
+    /// 起止位置的 UTF-16 偏移
+    pub char_index: usize,
+    pub char_end: usize,
+    pub suggestions: Vec<String>,
+}
+
+/// 用户词典
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+struct UserDictionary {
+    words: BTreeSet<String>,
+}
+
+/// 已加载的词典与用户词典
+pub struct SpellChecker {
+    dir: PathBuf,
+    dictionaries: Mutex<HashMap<String, Arc<Dictionary>>>,
+    user_words: Mutex<BTreeSet<String>>,
+}
+
+/// `en-US` → `en_US`，与 Hunspell 词典文件名一致
+fn normalize_language(language: &str) -> String {
+    language.trim().replace('-', "_")
+}
+
+/// 在目录中查找词典文件；只给出语言（如 `en`）时取该语言的第一个地区词典
+fn find_in_dir(dir: &Path, language: &str) -> Option<(PathBuf, PathBuf)> {
+    let pair = |stem: &str| {
+        let aff = dir.join(format!("{}.aff", stem));
+        let dic = dir.join(format!("{}.dic", stem));
+        (aff.is_file() && dic.is_file()).then_some((aff, dic))
+    };
+    if let Some(found) = pair(language) {
+        return Some(found);
+    }
+    if language.contains('_') {
+        return None;
+    }
+    let prefix = format!("{}_", language);
+    let mut stems: Vec<String> = fs::read_dir(dir)
+        .ok()?
+        .flatten()
+        .filter_map(|entry| {
+            let name = entry.file_name().to_string_lossy().to_string();
+            name.strip_suffix(".aff")
+                .filter(|stem| stem.starts_with(&prefix))
+                .map(str::to_string)
+        })
+        .collect();
+    stems.sort();
+    stems.iter().find_map(|stem| pair(stem))
+}
+
+/// 网址、邮箱、路径和标签不做拼写检查
+fn is_skipped_chunk(chunk: &str) -> bool {
+    let chunk = chunk.trim_start_matches(['(', '<', '"', '\'']);
+    chunk.contains("://")
+        || chunk.starts_with("www.")
+        || chunk.starts_with("mailto:")
+        || (chunk.contains('@') && chunk.contains('.'))
+        || chunk.contains('/')
+        || chunk.contains('\\')
+        || (chunk.starts_with('#') && chunk.len() > 1)
+}
+
+/// 从一段源码中切出待检查的单词（字节区间相对于 `text`）
+///
+/// 单词由字母组成，中间可以有撇号；含数字的词、单个字母和全大写的缩写都会跳过。
+fn words_in(text: &str) -> Vec<Range<usize>> {
+    let mut words = Vec::new();
+    let mut chunk_start = 0;
+    for chunk in text.split_inclusive(char::is_whitespace) {
+        let offset = chunk_start;
+        chunk_start += chunk.len();
+        if is_skipped_chunk(chunk.trim_end()) {
+            continue;
+        }
+
+        let mut start: Option<usize> = None;
+        let mut has_digit = false;
+        let mut chars = chunk.char_indices().peekable();
+        while let Some((i, c)) = chars.next() {
+            let next_is_letter = chars.peek().is_some_and(|(_, n)| n.is_alphabetic());
+            let in_word = (c.is_alphanumeric() && !is_cjk_char(c))
+                || (start.is_some() && matches!(c, '\'' | '’') && next_is_letter);
+            if in_word {
+                start.get_or_insert(i);
+                has_digit |= c.is_numeric();
+                continue;
+            }
+            if let Some(s) = start.take() {
+                if !has_digit {
+                    words.push(offset + s..offset + i);
+                }
+            }
+            has_digit = false;
+        }
+        if let Some(s) = start {
+            if !has_digit {
+                words.push(offset + s..offset + chunk.len());
+            }
+        }
+    }
+
+    words.retain(|range| {
+        let word = &text[range.clone()];
+        word.chars().count() > 1 && word.chars().any(char::is_lowercase)
+    });
+    words
+}
+
+/// 文档中需要检查的单词（字节区间相对于 `content`）
+pub fn spellable_words(content: &str) -> Vec<Range<usize>> {
+    let body_start = frontmatter::find_front_matter(content)
+        .map(|block| block.body_start)
+        .unwrap_or(0);
+    let body = &content[body_start..];
+    let wiki_links: Vec<Range<usize>> = links::extract_links(content)
+        .into_iter()
+        .filter(|link| link.kind != LinkKind::Markdown)
+        .map(|link| link.start..link.end)
+        .collect();
+
+    // 相邻的文本事件合并后再切词，避免智能标点把 `don't` 拆成多段
+    let mut spans: Vec<Range<usize>> = Vec::new();
+    let mut in_code = false;
+    for (event, range) in markdown::parser(body).into_offset_iter() {
+        match event {
+            Event::Start(Tag::CodeBlock(_)) => in_code = true,
+            Event::End(TagEnd::CodeBlock) => in_code = false,
+            Event::Text(_) if !in_code => {
+                let range = body_start + range.start..body_start + range.end;
+                match spans.last_mut() {
+                    Some(last) if last.end == range.start => last.end = range.end,
+                    _ => spans.push(range),
+                }
+            }
+            _ => {}
+        }
+    }
+
+    spans
+        .into_iter()
+        .flat_map(|span| {
+            words_in(&content[span.clone()])
+                .into_iter()
+                .map(move |word| span.start + word.start..span.start + word.end)
+        })
+        .filter(|word| !wiki_links.iter().any(|link| link.contains(&word.start)))
+        .collect()
+}
+
+impl SpellChecker {
+    /// 从应用数据目录读取用户词典
+    pub fn load(dir: PathBuf) -> Self {
+        let user: UserDictionary = storage::load_json(&dir.join(USER_DICTIONARY_FILE));
+        SpellChecker {
+            dir,
+            dictionaries: Mutex::new(HashMap::new()),
+            user_words: Mutex::new(user.words),
+        }
+    }
+
+    fn search_dirs(&self) -> Vec<PathBuf> {
+        let mut dirs = vec![self.dir.join(DICTIONARIES_DIR)];
+        if let Some(home) = std::env::var_os("HOME") {
+            dirs.push(PathBuf::from(home).join("Library/Spelling"));
+        }
+        dirs.extend(SYSTEM_DICTIONARY_DIRS.iter().map(PathBuf::from));
+        dirs
+    }
+
+    /// 取得语言对应的词典，首次使用时加载并缓存
+    fn dictionary(&self, language: &str) -> Result<Arc<Dictionary>, String> {
+        let language = normalize_language(language);
+        let mut dictionaries = self.dictionaries.lock().map_err(|e| e.to_string())?;
+        if let Some(dictionary) = dictionaries.get(&language) {
+            return Ok(dictionary.clone());
+        }
+
+        let (aff, dic) = self
+            .search_dirs()
+            .iter()
+            .find_map(|dir| find_in_dir(dir, &language))
+            .ok_or_else(|| format!("Dictionary not found for language: {}", language))?;
+        log::info!("[spellcheck] Loading dictionary {:?}", dic);
+        let read = |path: &Path| {
+            fs::read(path)
+                .map(|bytes| String::from_utf8_lossy(&bytes).into_owned())
+                .map_err(|e| format!("Failed to read dictionary {:?}: {}", path, e))
+        };
+        let dictionary = Dictionary::new(&read(&aff)?, &read(&dic)?)
+            .map_err(|e| format!("Failed to parse dictionary {:?}: {}", dic, e))?;
+        let dictionary = Arc::new(dictionary);
+        dictionaries.insert(language, dictionary.clone());
+        Ok(dictionary)
+    }
+
+    /// 检查文档，返回拼写错误及建议
+    pub fn check(&self, content: &str, language: &str) -> Result<Vec<Misspelling>, String> {
+        let dictionary = self.dictionary(language)?;
+        let user_words = self.user_words.lock().map_err(|e| e.to_string())?.clone();
+        let lines = LineIndex::new(content);
+        // 同一个错词在文档中多次出现时只计算一次建议
+        let mut suggestions: HashMap<&str, Vec<String>> = HashMap::new();
+        let mut misspellings = Vec::new();
+
+        for range in spellable_words(content) {
+            let word = &content[range.clone()];
+            if user_words.contains(word)
+                || user_words.contains(&word.to_lowercase())
+                || dictionary.check(word)
+            {
+                continue;
+            }
+            let suggested = suggestions.entry(word).or_insert_with(|| {
+                let mut out = Vec::new();
+                dictionary.suggest(word, &mut out);
+                out.truncate(MAX_SUGGESTIONS);
+                out
+            });
+            misspellings.push(Misspelling {
+                word: word.to_string(),
+                line_index: lines.line_of(range.start),
+                char_index: utf16_offset(content, range.start),
+                char_end: utf16_offset(content, range.end),
+                suggestions: suggested.clone(),
+            });
+        }
+        Ok(misspellings)
+    }
+
+    /// 将单词加入用户词典并保存；已存在时返回 `false`
+    pub fn add_word(&self, word: &str) -> Result<bool, String> {
+        let mut words = self.user_words.lock().map_err(|e| e.to_string())?;
+        if !words.insert(word.to_string()) {
+            return Ok(false);
+        }
+        let user = UserDictionary {
+            words: words.clone(),
+        };
+        storage::save_json(&self.dir.join(USER_DICTIONARY_FILE), &user)?;
+        Ok(true)
+    }
+}
+
+// 检查文本拼写，`language` 为词典名（如 `en_US`、`en-US` 或 `en`）
+#[tauri::command]
+pub async fn check_text(
+    app: AppHandle,
+    content: String,
+    language: String,
+) -> Result<Vec<Misspelling>, VividError> {
+    let start = Instant::now();
+    let misspellings = tauri::async_runtime::spawn_blocking(move || {
+        app.state::<SpellChecker>().check(&content, &language)
+    })
+    .await
+    .map_err(|e| format!("Spell check task failed: {}", e))?
+    .map_err(|e| {
+        log::error!("[check_text] {}", e);
+        e
+    })?;
+    log::debug!(
+        "[check_text] {} misspelling(s) in {:?}",
+        misspellings.len(),
+        start.elapsed()
+    );
+    Ok(misspellings)
+}
+
+// 将单词加入用户词典
+#[tauri::command]
+pub fn add_to_dictionary(checker: State<'_, SpellChecker>, word: String) -> Result<(), VividError> {
+    let word = word.trim();
+    if word.is_empty() || word.contains(char::is_whitespace) {
+        return Err(VividError::invalid_input(format!(
+            "Invalid word: {:?}",
+            word
+        )));
+    }
+    if checker.add_word(word)? {
+        log::info!("[add_to_dictionary] ✓ Success: {}", word);
+    }
+    Ok(())
+}
@@ -0,0 +1,231 @@
+//! 文档统计
+//!
+//! 字数、句子、段落、代码块、阅读时间以及每个标题下的字数，供状态栏和文章统计面板使用。
+//! 中日韩文字按字计数，其它文字按词计数；代码块、front matter 和链接地址不计入字数。
+
+use std::fs;
+use std::path::Path;
+
+use pulldown_cmark::{Event, Tag, TagEnd};
+use serde::{Deserialize, Serialize};
+use tauri::AppHandle;
+
+use crate::access::{self, AccessKind};
+use crate::error::VividError;
+use crate::frontmatter;
+use crate::markdown::{self, is_cjk_char};
+use crate::parse::{self, LineIndex};
+
+/// 英文等按词计数的阅读速度（词 / 分钟）
+const WORDS_PER_MINUTE: f64 = 200.0;
+/// 中日韩文字的阅读速度（字 / 分钟）
+const CJK_CHARS_PER_MINUTE: f64 = 300.0;
+
+#[derive(Debug, Deserialize)]
+pub struct DocumentStatsParams {
+    /// 编辑器中的内容；为空时从 `path` 读取
+    pub content: Option<String>,
+    pub path: Option<String>,
+}
+
+/// 单个标题下的统计
+#[derive(Debug, Serialize, Deserialize)]
+pub struct HeadingStats {
+    pub level: u8,
+    pub text: String,
+    pub slug: String,
+    pub line_index: usize,
+    /// 该标题到下一个标题之间的字数
+    pub words: usize,
+    /// 包含所有子标题在内的字数
+    pub total_words: usize,
+}
+
+/// 文档统计结果
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct DocumentStats {
+    /// 总字数：中日韩文字数 + 其它文字的词数
+    pub words: usize,
+    pub cjk_characters: usize,
+    pub latin_words: usize,
+    /// 不含空白的字符数
+    pub characters: usize,
+    /// 含空白的字符数
+    pub characters_with_spaces: usize,
+    pub sentences: usize,
+    pub paragraphs: usize,
+    pub code_blocks: usize,
+    /// 预计阅读时间（秒）
+    pub reading_time_secs: u64,
+    pub headings: Vec<HeadingStats>,
+}
+
+/// 中日韩标点不算作字
+fn is_cjk_punctuation(c: char) -> bool {
+    matches!(
+        c as u32,
+        0x3000..=0x303F | 0xFF00..=0xFF0F | 0xFF1A..=0xFF20 | 0xFF3B..=0xFF40 | 0xFF5B..=0xFF65
+    )
+}
+
+/// 统计一段文本中的中日韩文字数和其它文字的词数
+pub fn count_words(text: &str) -> (usize, usize) {
+    let mut cjk = 0;
+    let mut latin = 0;
+    let mut in_word = false;
+    for c in text.chars() {
+        if is_cjk_char(c) {
+            if !is_cjk_punctuation(c) {
+                cjk += 1;
+            }
+            in_word = false;
+        } else if c.is_alphanumeric() || (in_word && matches!(c, '\'' | '’' | '-' | '_')) {
+            if !in_word {
+                latin += 1;
+                in_word = true;
+            }
+        } else {
+            in_word = false;
+        }
+    }
+    (cjk, latin)
+}
+
+/// 统计句子数：以句末标点分隔，末尾没有标点的剩余文本也算一句
+///
+/// 英文标点后必须是空白或文本结尾，避免把 `3.14`、`e.g.x` 拆成多句。
+fn count_sentences(text: &str) -> usize {
+    let mut sentences = 0;
+    let mut has_content = false;
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        let ends = match c {
+            '。' | '！' | '？' | '…' => true,
+            '.' | '!' | '?' => chars.peek().map_or(true, |next| next.is_whitespace()),
+            _ => false,
+        };
+        if ends {
+            if has_content {
+                sentences += 1;
+            }
+            has_content = false;
+        } else if c.is_alphanumeric() {
+            has_content = true;
+        }
+    }
+    sentences + usize::from(has_content)
+}
+
+/// 计算文档统计
+pub fn document_stats(content: &str) -> DocumentStats {
+    let body_start = frontmatter::find_front_matter(content)
+        .map(|block| block.body_start)
+        .unwrap_or(0);
+    let body = &content[body_start..];
+    let lines = LineIndex::new(content);
+
+    let mut stats = DocumentStats::default();
+    // 大纲与事件中的标题按顺序一一对应，用来取得标题文本和 slug
+    let mut outline = parse::outline(body).into_iter();
+    let mut in_code = false;
+    let mut in_heading = false;
+    let mut block_text = String::new();
+
+    for event in markdown::parser(body) {
+        match event {
+            Event::Start(Tag::CodeBlock(_)) => {
+                in_code = true;
+                stats.code_blocks += 1;
+            }
+            Event::End(TagEnd::CodeBlock) => in_code = false,
+            Event::Start(Tag::Paragraph) => stats.paragraphs += 1,
+            Event::Start(Tag::Heading { .. }) => {
+                in_heading = true;
+                if let Some(heading) = outline.next() {
+                    stats.headings.push(HeadingStats {
+                        level: heading.level,
+                        text: heading.text,
+                        slug: heading.slug,
+                        line_index: lines.line_of(body_start + heading.start),
+                        words: 0,
+                        total_words: 0,
+                    });
+                }
+            }
+            Event::End(tag) => {
+                // 标题不计入句子数
+                match tag {
+                    TagEnd::Heading(_) => {
+                        in_heading = false;
+                        block_text.clear();
+                    }
+                    TagEnd::Paragraph | TagEnd::Item | TagEnd::TableCell => {
+                        stats.sentences += count_sentences(&block_text);
+                        block_text.clear();
+                    }
+                    _ => {}
+                }
+            }
+            Event::Text(text) | Event::Code(text) if !in_code => {
+                let (cjk, latin) = count_words(&text);
+                stats.cjk_characters += cjk;
+                stats.latin_words += latin;
+                block_text.push_str(&text);
+                // 标题文本本身不计入该标题下的字数
+                if let (false, Some(heading)) = (in_heading, stats.headings.last_mut()) {
+                    heading.words += cjk + latin;
+                }
+            }
+            _ => {}
+        }
+    }
+
+    // 累加子标题的字数：向后直到遇到同级或更高级的标题
+    for i in 0..stats.headings.len() {
+        let level = stats.headings[i].level;
+        stats.headings[i].total_words = stats.headings[i..]
+            .iter()
+            .enumerate()
+            .take_while(|(j, h)| *j == 0 || h.level > level)
+            .map(|(_, h)| h.words)
+            .sum();
+    }
+
+    stats.words = stats.cjk_characters + stats.latin_words;
+    stats.characters = body.chars().filter(|c| !c.is_whitespace()).count();
+    stats.characters_with_spaces = body.chars().count();
+    let minutes = stats.latin_words as f64 / WORDS_PER_MINUTE
+        + stats.cjk_characters as f64 / CJK_CHARS_PER_MINUTE;
+    stats.reading_time_secs = (minutes * 60.0).ceil() as u64;
+    stats
+}
+
+// 获取文档统计信息
+#[tauri::command]
+pub fn get_document_stats(
+    app: AppHandle,
+    params: DocumentStatsParams,
+) -> Result<DocumentStats, VividError> {
+    let content = match (params.content, params.path) {
+        (Some(content), _) => content,
+        (None, Some(path)) => {
+            access::ensure_access(&app, Path::new(&path), AccessKind::Read)?;
+            fs::read_to_string(&path).map_err(|e| {
+                log::error!("[get_document_stats] Failed to read {}: {}", path, e);
+                format!("Failed to read file: {}", e)
+            })?
+        }
+        (None, None) => {
+            return Err(VividError::invalid_input(
+                "Either content or path is required",
+            ))
+        }
+    };
+    let stats = document_stats(&content);
+    log::debug!(
+        "[get_document_stats] {} word(s), {} heading(s)",
+        stats.words,
+        stats.headings.len()
+    );
+    Ok(stats)
+}
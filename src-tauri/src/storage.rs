@@ -0,0 +1,85 @@
+//! 应用数据的 JSON 持久化
+//!
+//! 最近文件、会话、设置等小型状态都以 JSON 文件保存在应用数据目录中。
+//! 写入先落到临时文件再重命名，避免程序崩溃时留下半截文件。
+
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+/// 读取 JSON 文件；文件不存在或内容损坏时返回默认值
+///
+/// 损坏的文件会被重命名为 `*.corrupt`，保留现场便于排查，同时避免下次写入时覆盖。
+pub fn load_json<T: DeserializeOwned + Default>(path: &Path) -> T {
+    let content = match fs::read_to_string(path) {
+        Ok(content) => content,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return T::default(),
+        Err(e) => {
+            log::warn!("[storage] Failed to read {:?}: {}", path, e);
+            return T::default();
+        }
+    };
+
+    match serde_json::from_str(&content) {
+        Ok(value) => value,
+        Err(e) => {
+            log::warn!("[storage] Failed to parse {:?}: {}", path, e);
+            let backup = path.with_extension("json.corrupt");
+            if let Err(e) = fs::rename(path, &backup) {
+                log::warn!("[storage] Failed to move corrupt file aside: {}", e);
+            }
+            T::default()
+        }
+    }
+}
+
+/// 以原子方式写入 JSON 文件
+pub fn save_json<T: Serialize>(path: &Path, value: &T) -> Result<(), String> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| format!("Failed to create directory: {}", e))?;
+    }
+    let json = serde_json::to_string_pretty(value)
+        .map_err(|e| format!("Failed to serialize {:?}: {}", path, e))?;
+    write_atomic(path, json.as_bytes())
+}
+
+/// 先写入同目录下的隐藏临时文件再重命名，替换过程中不会留下半截内容
+pub fn write_atomic(path: &Path, data: &[u8]) -> Result<(), String> {
+    replace_atomic(path, data).map_err(|e| format!("Failed to write {:?}: {}", path, e))
+}
+
+/// 同 `write_atomic`，保留 `io::Error` 供调用方区分只读、被占用等情况
+///
+/// 目标是符号链接时替换链接指向的文件，链接本身保持不变。
+pub fn replace_atomic(path: &Path, data: &[u8]) -> io::Result<()> {
+    let is_link = fs::symlink_metadata(path).is_ok_and(|m| m.file_type().is_symlink());
+    let path = if is_link {
+        fs::canonicalize(path)?
+    } else {
+        path.to_path_buf()
+    };
+    let name = path
+        .file_name()
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "Invalid path"))?;
+    let tmp = path.with_file_name(format!(".{}.tmp", name.to_string_lossy()));
+    fs::write(&tmp, data)?;
+    // 保留原文件的权限
+    if let Ok(metadata) = fs::metadata(&path) {
+        let _ = fs::set_permissions(&tmp, metadata.permissions());
+    }
+    fs::rename(&tmp, &path).inspect_err(|_| {
+        let _ = fs::remove_file(&tmp);
+    })
+}
+
+/// 当前时间（Unix 毫秒）
+pub fn now_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
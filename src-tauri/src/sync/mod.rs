@@ -0,0 +1,885 @@
+//! 工作区同步
+//!
+//! 把工作区与 WebDAV 或 S3 兼容的远端双向同步。每个工作区的同步状态保存在应用数据目录下的
+//! SQLite 数据库中，记录上次同步时每个文件的内容哈希和远端 ETag，据此判断文件在哪一侧被修改：
+//! 只有一侧修改时直接传输；两侧都改过时比较内容哈希，内容不同则保留本地版本，
+//! 远端版本另存为冲突副本。远端删除的文件移到回收站。
+//!
+//! 远端的密码 / 私有访问密钥保存在系统钥匙串中（见 `secrets`），不写入配置文件。
+
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{Duration, Instant, UNIX_EPOCH};
+
+use chrono::{DateTime, Local};
+use rusqlite::{params, Connection};
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter, Manager, State};
+
+use crate::access::{self, AccessKind};
+use crate::assets::content_hash;
+use crate::error::VividError;
+use crate::workspace::{is_ignored, Workspace};
+use crate::{secrets, storage};
+
+pub mod s3;
+pub mod webdav;
+
+/// 同步进度事件
+pub const SYNC_PROGRESS_EVENT: &str = "sync-progress";
+
+/// 远端配置文件
+const REMOTES_FILE: &str = "remotes.json";
+/// 同步状态数据库的结构版本
+const STATE_VERSION: i32 = 1;
+/// 单个请求的超时
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(120);
+
+/// 同步远端
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum RemoteConfig {
+    Webdav(webdav::WebDavConfig),
+    S3(s3::S3Config),
+}
+
+/// 远端文件
+pub struct RemoteEntry {
+    /// 变化标记（ETag，或修改时间 + 大小）
+    pub tag: String,
+}
+
+/// 本地文件
+struct LocalFile {
+    hash: String,
+    modified: i64,
+    size: i64,
+}
+
+/// 上次同步完成时的文件状态
+struct Synced {
+    /// 两侧一致的内容哈希
+    hash: String,
+    /// 远端变化标记，未知时为空
+    remote_tag: String,
+    /// 本地修改时间与大小，未变化时无需重新计算哈希
+    modified: i64,
+    size: i64,
+}
+
+#[derive(Debug, Clone, Copy, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SyncPhase {
+    Scanning,
+    Transferring,
+    Done,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SyncProgress {
+    pub root: String,
+    pub phase: SyncPhase,
+    pub done: usize,
+    pub total: usize,
+    /// 正在处理的文件（相对路径）
+    pub path: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SyncError {
+    pub path: String,
+    pub message: String,
+}
+
+/// 同步结果，路径均相对于工作区根目录
+#[derive(Debug, Default, Serialize)]
+pub struct SyncSummary {
+    pub uploaded: Vec<String>,
+    pub downloaded: Vec<String>,
+    pub deleted_local: Vec<String>,
+    pub deleted_remote: Vec<String>,
+    /// 两侧内容不同、已生成冲突副本的文件
+    pub conflicts: Vec<String>,
+    /// 传输失败的文件，下次同步时重试
+    pub errors: Vec<SyncError>,
+}
+
+/// 单个文件的同步动作
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Action {
+    Upload,
+    Download,
+    /// 两侧都有修改：比较内容，不同则生成冲突副本
+    Merge,
+    DeleteLocal,
+    DeleteRemote,
+    /// 两侧都已删除，只清理同步状态
+    Forget,
+}
+
+/// 远端配置与正在同步的工作区
+pub struct SyncStore {
+    dir: PathBuf,
+    remotes: Mutex<BTreeMap<String, RemoteConfig>>,
+    running: Mutex<HashSet<PathBuf>>,
+}
+
+/// 同步期间占用工作区，结束时释放
+struct RunningGuard<'a> {
+    store: &'a SyncStore,
+    root: PathBuf,
+}
+
+impl Drop for RunningGuard<'_> {
+    fn drop(&mut self) {
+        if let Ok(mut running) = self.store.running.lock() {
+            running.remove(&self.root);
+        }
+    }
+}
+
+fn root_key(root: &Path) -> String {
+    root.to_string_lossy().to_string()
+}
+
+/// 远端密钥在系统钥匙串中的条目名
+fn secret_key(root: &Path) -> String {
+    format!("sync:{}", root.display())
+}
+
+impl SyncStore {
+    pub fn load(dir: PathBuf) -> Self {
+        let remotes = storage::load_json(&dir.join(REMOTES_FILE));
+        SyncStore {
+            dir,
+            remotes: Mutex::new(remotes),
+            running: Mutex::new(HashSet::new()),
+        }
+    }
+
+    fn remote(&self, root: &Path) -> Option<RemoteConfig> {
+        self.remotes.lock().ok()?.get(&root_key(root)).cloned()
+    }
+
+    /// 修改远端配置，返回配置是否发生变化
+    fn set_remote(&self, root: &Path, remote: Option<RemoteConfig>) -> Result<bool, String> {
+        let mut remotes = self.remotes.lock().map_err(|e| e.to_string())?;
+        let key = root_key(root);
+        let changed = remotes.get(&key) != remote.as_ref();
+        match remote {
+            Some(remote) => remotes.insert(key, remote),
+            None => remotes.remove(&key),
+        };
+        storage::save_json(&self.dir.join(REMOTES_FILE), &*remotes)?;
+        Ok(changed)
+    }
+
+    fn state_path(&self, root: &Path) -> PathBuf {
+        let hash = content_hash(root.to_string_lossy().as_bytes());
+        self.dir.join(format!("{}.sqlite", &hash[..16]))
+    }
+
+    fn begin(&self, root: &Path) -> Result<RunningGuard<'_>, String> {
+        let mut running = self.running.lock().map_err(|e| e.to_string())?;
+        if !running.insert(root.to_path_buf()) {
+            return Err("Sync is already running for this workspace".to_string());
+        }
+        Ok(RunningGuard {
+            store: self,
+            root: root.to_path_buf(),
+        })
+    }
+}
+
+/// 同步状态数据库
+struct SyncState {
+    conn: Mutex<Connection>,
+}
+
+impl SyncState {
+    fn open(path: &Path) -> Result<Self, String> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).map_err(|e| format!("Failed to create directory: {}", e))?;
+        }
+        let conn = Connection::open(path)
+            .and_then(|conn| {
+                conn.execute_batch(
+                    "CREATE TABLE IF NOT EXISTS entries (
+                        path TEXT PRIMARY KEY,
+                        hash TEXT NOT NULL,
+                        remote_tag TEXT NOT NULL,
+                        modified INTEGER NOT NULL,
+                        size INTEGER NOT NULL,
+                        synced_at INTEGER NOT NULL
+                    );",
+                )?;
+                conn.pragma_update(None, "user_version", STATE_VERSION)?;
+                Ok(conn)
+            })
+            .map_err(|e| format!("Failed to open sync state: {}", e))?;
+        Ok(SyncState {
+            conn: Mutex::new(conn),
+        })
+    }
+
+    fn load(&self) -> Result<HashMap<String, Synced>, String> {
+        let conn = self.conn.lock().map_err(|e| e.to_string())?;
+        let mut stmt = conn
+            .prepare("SELECT path, hash, remote_tag, modified, size FROM entries")
+            .map_err(|e| format!("Failed to read sync state: {}", e))?;
+        let rows = stmt
+            .query_map([], |row| {
+                Ok((
+                    row.get::<_, String>(0)?,
+                    Synced {
+                        hash: row.get(1)?,
+                        remote_tag: row.get(2)?,
+                        modified: row.get(3)?,
+                        size: row.get(4)?,
+                    },
+                ))
+            })
+            .and_then(|rows| rows.collect::<rusqlite::Result<HashMap<_, _>>>())
+            .map_err(|e| format!("Failed to read sync state: {}", e))?;
+        Ok(rows)
+    }
+
+    fn record(&self, rel: &str, synced: &Synced) -> Result<(), String> {
+        let conn = self.conn.lock().map_err(|e| e.to_string())?;
+        conn.execute(
+            "INSERT OR REPLACE INTO entries (path, hash, remote_tag, modified, size, synced_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            params![
+                rel,
+                synced.hash,
+                synced.remote_tag,
+                synced.modified,
+                synced.size,
+                storage::now_millis() as i64
+            ],
+        )
+        .map(|_| ())
+        .map_err(|e| format!("Failed to update sync state: {}", e))
+    }
+
+    fn forget(&self, rel: &str) -> Result<(), String> {
+        let conn = self.conn.lock().map_err(|e| e.to_string())?;
+        conn.execute("DELETE FROM entries WHERE path = ?1", params![rel])
+            .map(|_| ())
+            .map_err(|e| format!("Failed to update sync state: {}", e))
+    }
+}
+
+/// 按 RFC 3986 编码，只保留非保留字符；`encode_slash` 为 false 时保留路径分隔符
+pub(crate) fn uri_encode(s: &str, encode_slash: bool) -> String {
+    let mut out = String::with_capacity(s.len());
+    for b in s.bytes() {
+        match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => {
+                out.push(b as char)
+            }
+            b'/' if !encode_slash => out.push('/'),
+            _ => out.push_str(&format!("%{:02X}", b)),
+        }
+    }
+    out
+}
+
+/// 去掉 ETag 的引号和弱标记，不同接口返回的形式才能直接比较
+fn normalize_tag(tag: &str) -> String {
+    tag.trim()
+        .trim_start_matches("W/")
+        .trim_matches('"')
+        .to_string()
+}
+
+/// 远端返回的相对路径能否安全地映射到工作区内：不能为空或绝对路径，各段不能为空、`.`、
+/// `..`，也不能带有 Windows 的盘符或反斜杠
+fn is_safe_rel(rel: &str) -> bool {
+    !rel.is_empty()
+        && rel.split('/').all(|part| {
+            !part.is_empty()
+                && !part.contains(['\\', ':'])
+                && matches!(
+                    Path::new(part).components().collect::<Vec<_>>().as_slice(),
+                    [Component::Normal(_)]
+                )
+        })
+}
+
+/// 相对路径是否位于被跳过的文件或目录中（与工作区扫描保持一致）
+fn is_ignored_path(rel: &str) -> bool {
+    rel.split('/').any(is_ignored)
+}
+
+/// 远端客户端
+enum Remote {
+    WebDav(webdav::WebDav),
+    S3(s3::S3),
+}
+
+impl Remote {
+    fn new(config: &RemoteConfig, secret: &str) -> Result<Self, String> {
+        let client = reqwest::Client::builder()
+            .timeout(REQUEST_TIMEOUT)
+            .user_agent(concat!("VividMark/", env!("CARGO_PKG_VERSION")))
+            .build()
+            .map_err(|e| format!("Failed to create HTTP client: {}", e))?;
+        Ok(match config {
+            RemoteConfig::Webdav(config) => {
+                Remote::WebDav(webdav::WebDav::new(client, config, secret)?)
+            }
+            RemoteConfig::S3(config) => Remote::S3(s3::S3::new(client, config, secret)?),
+        })
+    }
+
+    async fn list(&self) -> Result<BTreeMap<String, RemoteEntry>, String> {
+        let mut files = match self {
+            Remote::WebDav(remote) => remote.list().await?,
+            Remote::S3(remote) => remote.list().await?,
+        };
+        files.retain(|rel, _| {
+            if !is_safe_rel(rel) {
+                log::warn!("[sync] Ignoring unsafe remote path: {:?}", rel);
+                return false;
+            }
+            !is_ignored_path(rel)
+        });
+        for entry in files.values_mut() {
+            entry.tag = normalize_tag(&entry.tag);
+        }
+        Ok(files)
+    }
+
+    async fn get(&self, rel: &str) -> Result<Vec<u8>, String> {
+        match self {
+            Remote::WebDav(remote) => remote.get(rel).await,
+            Remote::S3(remote) => remote.get(rel).await,
+        }
+    }
+
+    /// 上传文件，返回新的变化标记（服务器未返回时为空）
+    async fn put(&self, rel: &str, data: Vec<u8>) -> Result<String, String> {
+        let tag = match self {
+            Remote::WebDav(remote) => remote.put(rel, data).await?,
+            Remote::S3(remote) => remote.put(rel, data).await?,
+        };
+        Ok(tag.map(|t| normalize_tag(&t)).unwrap_or_default())
+    }
+
+    async fn delete(&self, rel: &str) -> Result<(), String> {
+        match self {
+            Remote::WebDav(remote) => remote.delete(rel).await,
+            Remote::S3(remote) => remote.delete(rel).await,
+        }
+    }
+}
+
+/// 文件的修改时间（毫秒）与大小
+fn stamp(metadata: &fs::Metadata) -> (i64, i64) {
+    let modified = metadata
+        .modified()
+        .ok()
+        .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+        .map(|d| d.as_millis() as i64)
+        .unwrap_or(0);
+    (modified, metadata.len() as i64)
+}
+
+fn local_path(root: &Path, rel: &str) -> PathBuf {
+    rel.split('/')
+        .fold(root.to_path_buf(), |path, part| path.join(part))
+}
+
+/// 扫描本地文件；修改时间和大小与上次同步一致的文件沿用记录的哈希
+fn scan_local(root: &Path, base: &HashMap<String, Synced>) -> BTreeMap<String, LocalFile> {
+    let mut files = BTreeMap::new();
+    let mut pending = vec![root.to_path_buf()];
+    while let Some(dir) = pending.pop() {
+        let Ok(entries) = fs::read_dir(&dir) else {
+            log::warn!("[sync] Failed to read directory: {}", dir.display());
+            continue;
+        };
+        for entry in entries.flatten() {
+            if is_ignored(&entry.file_name().to_string_lossy()) {
+                continue;
+            }
+            let path = entry.path();
+            match entry.file_type() {
+                Ok(t) if t.is_dir() => pending.push(path),
+                Ok(t) if t.is_file() => {
+                    let Ok(rel) = path.strip_prefix(root) else {
+                        continue;
+                    };
+                    let rel = rel
+                        .components()
+                        .map(|c| c.as_os_str().to_string_lossy())
+                        .collect::<Vec<_>>()
+                        .join("/");
+                    let Ok(metadata) = entry.metadata() else {
+                        continue;
+                    };
+                    let (modified, size) = stamp(&metadata);
+                    let hash = match base.get(&rel) {
+                        Some(synced) if synced.modified == modified && synced.size == size => {
+                            synced.hash.clone()
+                        }
+                        _ => match fs::read(&path) {
+                            Ok(bytes) => content_hash(&bytes),
+                            Err(e) => {
+                                log::warn!("[sync] Failed to read {:?}: {}", path, e);
+                                continue;
+                            }
+                        },
+                    };
+                    files.insert(
+                        rel,
+                        LocalFile {
+                            hash,
+                            modified,
+                            size,
+                        },
+                    );
+                }
+                _ => {}
+            }
+        }
+    }
+    files
+}
+
+/// 按三方比较（本地、远端、上次同步）决定每个文件的动作
+fn plan(
+    local: &BTreeMap<String, LocalFile>,
+    remote: &BTreeMap<String, RemoteEntry>,
+    base: &HashMap<String, Synced>,
+) -> Vec<(String, Action)> {
+    let paths: BTreeSet<&String> = local
+        .keys()
+        .chain(remote.keys())
+        .chain(base.keys())
+        .collect();
+    paths
+        .into_iter()
+        .filter_map(|rel| {
+            let synced = base.get(rel);
+            let local_changed = |file: &LocalFile| !synced.is_some_and(|s| s.hash == file.hash);
+            // 远端标记未知时视为已修改，下载后按内容判断
+            let remote_changed = |entry: &RemoteEntry| {
+                !synced.is_some_and(|s| !s.remote_tag.is_empty() && s.remote_tag == entry.tag)
+            };
+            let action = match (local.get(rel), remote.get(rel), synced) {
+                (Some(file), Some(entry), _) => {
+                    match (local_changed(file), remote_changed(entry)) {
+                        (false, false) => return None,
+                        (true, false) => Action::Upload,
+                        (false, true) => Action::Download,
+                        (true, true) => Action::Merge,
+                    }
+                }
+                // 远端已删除；本地有修改时修改优先
+                (Some(file), None, Some(_)) if !local_changed(file) => Action::DeleteLocal,
+                (Some(_), None, _) => Action::Upload,
+                (None, Some(entry), Some(_)) if !remote_changed(entry) => Action::DeleteRemote,
+                (None, Some(_), _) => Action::Download,
+                (None, None, Some(_)) => Action::Forget,
+                (None, None, None) => return None,
+            };
+            Some((rel.clone(), action))
+        })
+        .collect()
+}
+
+/// 冲突副本的相对路径：`notes/a.md` → `notes/a (conflict 2024-01-02 150405).md`
+fn conflict_copy(rel: &str, now: DateTime<Local>) -> String {
+    let (dir, name) = match rel.rsplit_once('/') {
+        Some((dir, name)) => (format!("{}/", dir), name),
+        None => (String::new(), rel),
+    };
+    let (stem, ext) = match name.rsplit_once('.') {
+        Some((stem, ext)) if !stem.is_empty() => (stem, format!(".{}", ext)),
+        _ => (name, String::new()),
+    };
+    format!(
+        "{}{} (conflict {}){}",
+        dir,
+        stem,
+        now.format("%Y-%m-%d %H%M%S"),
+        ext
+    )
+}
+
+/// 一次同步中各文件共用的上下文
+struct SyncContext<'a> {
+    root: &'a Path,
+    remote: &'a Remote,
+    state: &'a SyncState,
+    local: &'a BTreeMap<String, LocalFile>,
+    remote_files: &'a BTreeMap<String, RemoteEntry>,
+}
+
+impl SyncContext<'_> {
+    fn read_local(&self, rel: &str) -> Result<(Vec<u8>, Synced), String> {
+        let path = local_path(self.root, rel);
+        let data = fs::read(&path).map_err(|e| format!("Failed to read file: {}", e))?;
+        let (modified, size) = fs::metadata(&path)
+            .map(|m| stamp(&m))
+            .map_err(|e| format!("Failed to read file: {}", e))?;
+        let synced = Synced {
+            hash: content_hash(&data),
+            remote_tag: String::new(),
+            modified,
+            size,
+        };
+        Ok((data, synced))
+    }
+
+    /// 写入本地文件；扫描之后又被修改过的文件不覆盖
+    fn write_local(&self, rel: &str, data: &[u8]) -> Result<(i64, i64), String> {
+        let path = local_path(self.root, rel);
+        let current = fs::metadata(&path).ok().map(|m| stamp(&m));
+        let scanned = self.local.get(rel).map(|f| (f.modified, f.size));
+        if current != scanned {
+            return Err("File was modified during sync".to_string());
+        }
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).map_err(|e| format!("Failed to create directory: {}", e))?;
+        }
+        storage::write_atomic(&path, data)?;
+        fs::metadata(&path)
+            .map(|m| stamp(&m))
+            .map_err(|e| format!("Failed to read file: {}", e))
+    }
+
+    async fn upload(&self, rel: &str) -> Result<(), String> {
+        let (data, mut synced) = self.read_local(rel)?;
+        synced.remote_tag = self.remote.put(rel, data).await?;
+        self.state.record(rel, &synced)
+    }
+
+    /// 下载远端文件，返回本地内容是否发生变化
+    async fn download(&self, rel: &str) -> Result<bool, String> {
+        let data = self.remote.get(rel).await?;
+        let hash = content_hash(&data);
+        let (changed, (modified, size)) = match self.local.get(rel) {
+            Some(file) if file.hash == hash => (false, (file.modified, file.size)),
+            _ => (true, self.write_local(rel, &data)?),
+        };
+        let remote_tag = self
+            .remote_files
+            .get(rel)
+            .map(|e| e.tag.clone())
+            .unwrap_or_default();
+        self.state.record(
+            rel,
+            &Synced {
+                hash,
+                remote_tag,
+                modified,
+                size,
+            },
+        )?;
+        Ok(changed)
+    }
+
+    /// 两侧都有修改：内容相同时只更新状态，否则远端版本另存为冲突副本并上传本地版本
+    async fn merge(&self, rel: &str) -> Result<bool, String> {
+        let data = self.remote.get(rel).await?;
+        let Some(file) = self.local.get(rel) else {
+            return Err("Local file disappeared during sync".to_string());
+        };
+        let remote_tag = self
+            .remote_files
+            .get(rel)
+            .map(|e| e.tag.clone())
+            .unwrap_or_default();
+        if content_hash(&data) == file.hash {
+            self.state.record(
+                rel,
+                &Synced {
+                    hash: file.hash.clone(),
+                    remote_tag,
+                    modified: file.modified,
+                    size: file.size,
+                },
+            )?;
+            return Ok(false);
+        }
+
+        let copy = conflict_copy(rel, Local::now());
+        log::warn!(
+            "[sync] Conflict on {}, saving remote version as {}",
+            rel,
+            copy
+        );
+        let copy_path = local_path(self.root, &copy);
+        if let Some(parent) = copy_path.parent() {
+            fs::create_dir_all(parent).map_err(|e| format!("Failed to create directory: {}", e))?;
+        }
+        storage::write_atomic(&copy_path, &data)?;
+        let (_, mut copy_synced) = self.read_local(&copy)?;
+        copy_synced.remote_tag = self.remote.put(&copy, data).await?;
+        self.state.record(&copy, &copy_synced)?;
+
+        self.upload(rel).await?;
+        Ok(true)
+    }
+
+    async fn apply(
+        &self,
+        rel: &str,
+        action: Action,
+        summary: &mut SyncSummary,
+    ) -> Result<(), String> {
+        match action {
+            Action::Upload => {
+                self.upload(rel).await?;
+                summary.uploaded.push(rel.to_string());
+            }
+            Action::Download => {
+                if self.download(rel).await? {
+                    summary.downloaded.push(rel.to_string());
+                }
+            }
+            Action::Merge => {
+                if self.merge(rel).await? {
+                    summary.conflicts.push(rel.to_string());
+                }
+            }
+            Action::DeleteLocal => {
+                trash::delete(local_path(self.root, rel))
+                    .map_err(|e| format!("Failed to move file to trash: {}", e))?;
+                self.state.forget(rel)?;
+                summary.deleted_local.push(rel.to_string());
+            }
+            Action::DeleteRemote => {
+                self.remote.delete(rel).await?;
+                self.state.forget(rel)?;
+                summary.deleted_remote.push(rel.to_string());
+            }
+            Action::Forget => self.state.forget(rel)?,
+        }
+        Ok(())
+    }
+}
+
+/// 进度回调：阶段、已完成数、总数、当前文件
+type ProgressFn<'a> = dyn Fn(SyncPhase, usize, usize, Option<&str>) + Sync + 'a;
+
+/// 执行一次同步：扫描两侧、规划动作并逐个执行，单个文件失败不影响其它文件
+async fn run(
+    root: &Path,
+    remote: &Remote,
+    state: &SyncState,
+    progress: &ProgressFn<'_>,
+) -> Result<SyncSummary, String> {
+    let base = state.load()?;
+    let scan_root = root.to_path_buf();
+    let (base, local) = tauri::async_runtime::spawn_blocking(move || {
+        let local = scan_local(&scan_root, &base);
+        (base, local)
+    })
+    .await
+    .map_err(|e| format!("Sync task failed: {}", e))?;
+    let remote_files = remote.list().await?;
+
+    // 远端地址或前缀配置错误时列表为空，此时不按“远端已删除”处理
+    if remote_files.is_empty() && !base.is_empty() && !local.is_empty() {
+        return Err(
+            "Remote is empty but files were synced before; check the remote configuration"
+                .to_string(),
+        );
+    }
+
+    let actions = plan(&local, &remote_files, &base);
+    log::debug!(
+        "[sync] {} local, {} remote, {} action(s)",
+        local.len(),
+        remote_files.len(),
+        actions.len()
+    );
+
+    let context = SyncContext {
+        root,
+        remote,
+        state,
+        local: &local,
+        remote_files: &remote_files,
+    };
+    let mut summary = SyncSummary::default();
+    let total = actions.len();
+    for (done, (rel, action)) in actions.into_iter().enumerate() {
+        progress(SyncPhase::Transferring, done, total, Some(&rel));
+        if let Err(message) = context.apply(&rel, action, &mut summary).await {
+            log::warn!("[sync] {:?} {} failed: {}", action, rel, message);
+            summary.errors.push(SyncError { path: rel, message });
+        }
+    }
+    progress(SyncPhase::Done, total, total, None);
+    Ok(summary)
+}
+
+fn emit_progress(app: &AppHandle, progress: SyncProgress) {
+    if let Err(e) = app.emit(SYNC_PROGRESS_EVENT, &progress) {
+        log::warn!("[sync] Failed to emit {}: {}", SYNC_PROGRESS_EVENT, e);
+    }
+}
+
+// 配置工作区的同步远端；`remote` 为空时取消同步，`secret` 为 WebDAV 密码或 S3 私有访问密钥
+#[tauri::command]
+pub fn configure_sync(
+    app: AppHandle,
+    store: State<'_, SyncStore>,
+    root: String,
+    remote: Option<RemoteConfig>,
+    secret: Option<String>,
+) -> Result<(), VividError> {
+    let root = PathBuf::from(&root);
+    access::ensure_access(&app, &root, AccessKind::Write)?;
+    let key = secret_key(&root);
+    match &remote {
+        Some(config) => {
+            let secret = match secret {
+                Some(secret) => secret,
+                None => secrets::get(&key)?.unwrap_or_default(),
+            };
+            // 先校验配置，无效时不保存
+            Remote::new(config, &secret)?;
+            secrets::store(&key, &secret)?;
+        }
+        None => {
+            secrets::delete(&key)?;
+        }
+    }
+
+    // 换了远端后旧的同步状态不再适用
+    if store.set_remote(&root, remote.clone())? {
+        let state = store.state_path(&root);
+        if state.exists() {
+            fs::remove_file(&state).map_err(|e| format!("Failed to reset sync state: {}", e))?;
+        }
+    }
+    log::info!(
+        "[configure_sync] ✓ Success: {} ({})",
+        root.display(),
+        match &remote {
+            Some(RemoteConfig::Webdav(_)) => "webdav",
+            Some(RemoteConfig::S3(_)) => "s3",
+            None => "disabled",
+        }
+    );
+    Ok(())
+}
+
+// 读取工作区的同步远端配置（不含密钥）
+#[tauri::command]
+pub fn get_sync_config(store: State<'_, SyncStore>, root: String) -> Option<RemoteConfig> {
+    store.remote(Path::new(&root))
+}
+
+// 与远端双向同步工作区，默认同步当前打开的工作区
+#[tauri::command]
+pub async fn sync_workspace(
+    app: AppHandle,
+    root: Option<String>,
+) -> Result<SyncSummary, VividError> {
+    let start = Instant::now();
+    let root = match root {
+        Some(root) => PathBuf::from(root),
+        None => app
+            .state::<Workspace>()
+            .root()
+            .ok_or_else(|| "No workspace is open".to_string())?,
+    };
+    access::ensure_access(&app, &root, AccessKind::Write)?;
+    if !root.is_dir() {
+        return Err(VividError::dir_not_found(&root));
+    }
+    let store = app.state::<SyncStore>();
+    let config = store
+        .remote(&root)
+        .ok_or_else(|| "Sync is not configured for this workspace".to_string())?;
+    let _guard = store.begin(&root)?;
+    log::info!("[sync_workspace] Syncing {}", root.display());
+
+    let root_name = root.to_string_lossy().to_string();
+    let progress = |phase, done, total, path: Option<&str>| {
+        emit_progress(
+            &app,
+            SyncProgress {
+                root: root_name.clone(),
+                phase,
+                done,
+                total,
+                path: path.map(str::to_string),
+            },
+        )
+    };
+    progress(SyncPhase::Scanning, 0, 0, None);
+
+    let secret = secrets::get(&secret_key(&root))?.unwrap_or_default();
+    let remote = Remote::new(&config, &secret)?;
+    let state = SyncState::open(&store.state_path(&root))?;
+    let summary = run(&root, &remote, &state, &progress).await.map_err(|e| {
+        log::error!("[sync_workspace] {}", e);
+        e
+    })?;
+
+    log::info!(
+        "[sync_workspace] ✓ Success: {} up, {} down, {} deleted, {} conflict(s), {} error(s) in {:?}",
+        summary.uploaded.len(),
+        summary.downloaded.len(),
+        summary.deleted_local.len() + summary.deleted_remote.len(),
+        summary.conflicts.len(),
+        summary.errors.len(),
+        start.elapsed()
+    );
+    Ok(summary)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn accepts_relative_paths() {
+        assert!(is_safe_rel("note.md"));
+        assert!(is_safe_rel("notes/2024/today.md"));
+        assert!(is_safe_rel("笔记/日记.md"));
+        assert!(is_safe_rel("a..b/.hidden"));
+    }
+
+    #[test]
+    fn rejects_empty_and_absolute_paths() {
+        assert!(!is_safe_rel(""));
+        assert!(!is_safe_rel("/etc/passwd"));
+        assert!(!is_safe_rel("C:/Windows/win.ini"));
+        assert!(!is_safe_rel("C:note.md"));
+    }
+
+    #[test]
+    fn rejects_parent_and_current_dir_segments() {
+        assert!(!is_safe_rel(".."));
+        assert!(!is_safe_rel("../outside.md"));
+        assert!(!is_safe_rel("notes/../../outside.md"));
+        assert!(!is_safe_rel("./note.md"));
+        assert!(!is_safe_rel("notes/."));
+    }
+
+    #[test]
+    fn rejects_empty_segments_and_backslashes() {
+        assert!(!is_safe_rel("notes//note.md"));
+        assert!(!is_safe_rel("notes/"));
+        assert!(!is_safe_rel("notes\\..\\outside.md"));
+        assert!(!is_safe_rel("..\\outside.md"));
+    }
+
+    #[test]
+    fn normalizes_etags() {
+        assert_eq!(normalize_tag("W/\"abc\""), "abc");
+        assert_eq!(normalize_tag(" \"abc\" "), "abc");
+        assert_eq!(normalize_tag("abc"), "abc");
+    }
+}
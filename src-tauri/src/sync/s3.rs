@@ -0,0 +1,311 @@
+//! S3 兼容远端
+//!
+//! 支持 AWS S3 以及 MinIO、Cloudflare R2 等兼容服务。请求使用 AWS Signature V4 签名，
+//! 列表使用 `ListObjectsV2` 分页；`path_style` 为 true 时地址形如 `<endpoint>/<bucket>/<key>`。
+
+use std::collections::BTreeMap;
+
+use chrono::{DateTime, Utc};
+use hmac::{Hmac, Mac};
+use reqwest::{Method, Url};
+use roxmltree::Document;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use super::{uri_encode, RemoteEntry};
+
+/// 未设置内容哈希时的签名占位
+const EMPTY_SHA256: &str = "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855";
+
+fn default_region() -> String {
+    "us-east-1".to_string()
+}
+
+fn default_path_style() -> bool {
+    true
+}
+
+/// S3 远端配置，私有访问密钥保存在系统钥匙串中
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct S3Config {
+    /// 服务地址，如 `https://s3.amazonaws.com`、`http://localhost:9000`
+    pub endpoint: String,
+    pub bucket: String,
+    #[serde(default = "default_region")]
+    pub region: String,
+    pub access_key_id: String,
+    /// 对象键前缀，同一个存储桶可以存放多个工作区
+    #[serde(default)]
+    pub prefix: String,
+    #[serde(default = "default_path_style")]
+    pub path_style: bool,
+}
+
+pub struct S3 {
+    client: reqwest::Client,
+    endpoint: Url,
+    bucket: String,
+    region: String,
+    access_key_id: String,
+    secret_access_key: String,
+    /// 对象键前缀，为空或以 `/` 结尾
+    prefix: String,
+    path_style: bool,
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+fn sha256_hex(data: &[u8]) -> String {
+    hex(&Sha256::digest(data))
+}
+
+fn hmac(key: &[u8], data: &str) -> Vec<u8> {
+    let mut mac = Hmac::<Sha256>::new_from_slice(key).expect("HMAC accepts any key length");
+    mac.update(data.as_bytes());
+    mac.finalize().into_bytes().to_vec()
+}
+
+impl S3 {
+    pub fn new(
+        client: reqwest::Client,
+        config: &S3Config,
+        secret_access_key: &str,
+    ) -> Result<Self, String> {
+        let endpoint = Url::parse(config.endpoint.trim())
+            .map_err(|e| format!("Invalid S3 endpoint: {}", e))?;
+        if endpoint.host_str().is_none() || !matches!(endpoint.scheme(), "http" | "https") {
+            return Err(format!("Invalid S3 endpoint: {}", endpoint));
+        }
+        if config.bucket.trim().is_empty() {
+            return Err("S3 bucket must not be empty".to_string());
+        }
+        let prefix = config.prefix.trim().trim_matches('/');
+        Ok(S3 {
+            client,
+            endpoint,
+            bucket: config.bucket.trim().to_string(),
+            region: config.region.clone(),
+            access_key_id: config.access_key_id.clone(),
+            secret_access_key: secret_access_key.to_string(),
+            prefix: if prefix.is_empty() {
+                String::new()
+            } else {
+                format!("{}/", prefix)
+            },
+            path_style: config.path_style,
+        })
+    }
+
+    fn host(&self) -> String {
+        let host = self.endpoint.host_str().unwrap_or_default();
+        let host = match self.endpoint.port() {
+            Some(port) => format!("{}:{}", host, port),
+            None => host.to_string(),
+        };
+        if self.path_style {
+            host
+        } else {
+            format!("{}.{}", self.bucket, host)
+        }
+    }
+
+    /// 已编码的请求路径；`key` 为空时指向存储桶本身
+    fn path(&self, key: &str) -> String {
+        let key = uri_encode(key, false);
+        match (self.path_style, key.is_empty()) {
+            (true, true) => format!("/{}", uri_encode(&self.bucket, true)),
+            (true, false) => format!("/{}/{}", uri_encode(&self.bucket, true), key),
+            (false, _) => format!("/{}", key),
+        }
+    }
+
+    /// 计算 `Authorization` 头
+    fn authorization(
+        &self,
+        method: &Method,
+        path: &str,
+        query: &str,
+        payload_hash: &str,
+        now: DateTime<Utc>,
+    ) -> String {
+        let amz_date = now.format("%Y%m%dT%H%M%SZ").to_string();
+        let date = now.format("%Y%m%d").to_string();
+        let signed_headers = "host;x-amz-content-sha256;x-amz-date";
+        let canonical_request = format!(
+            "{}\n{}\n{}\nhost:{}\nx-amz-content-sha256:{}\nx-amz-date:{}\n\n{}\n{}",
+            method.as_str(),
+            path,
+            query,
+            self.host(),
+            payload_hash,
+            amz_date,
+            signed_headers,
+            payload_hash
+        );
+        let scope = format!("{}/{}/s3/aws4_request", date, self.region);
+        let string_to_sign = format!(
+            "AWS4-HMAC-SHA256\n{}\n{}\n{}",
+            amz_date,
+            scope,
+            sha256_hex(canonical_request.as_bytes())
+        );
+        let date_key = hmac(format!("AWS4{}", self.secret_access_key).as_bytes(), &date);
+        let region_key = hmac(&date_key, &self.region);
+        let service_key = hmac(&region_key, "s3");
+        let signing_key = hmac(&service_key, "aws4_request");
+        let signature = hex(&hmac(&signing_key, &string_to_sign));
+        format!(
+            "AWS4-HMAC-SHA256 Credential={}/{}, SignedHeaders={}, Signature={}",
+            self.access_key_id, scope, signed_headers, signature
+        )
+    }
+
+    /// 发送签名后的请求
+    async fn send(
+        &self,
+        method: Method,
+        key: &str,
+        query: &[(&str, &str)],
+        body: Vec<u8>,
+    ) -> Result<reqwest::Response, String> {
+        let now = Utc::now();
+        let path = self.path(key);
+        let payload_hash = if body.is_empty() {
+            EMPTY_SHA256.to_string()
+        } else {
+            sha256_hex(&body)
+        };
+        let mut query: Vec<(String, String)> = query
+            .iter()
+            .map(|(k, v)| (uri_encode(k, true), uri_encode(v, true)))
+            .collect();
+        query.sort();
+        let query = query
+            .iter()
+            .map(|(k, v)| format!("{}={}", k, v))
+            .collect::<Vec<_>>()
+            .join("&");
+        let authorization = self.authorization(&method, &path, &query, &payload_hash, now);
+
+        let url = format!(
+            "{}://{}{}{}{}",
+            self.endpoint.scheme(),
+            self.host(),
+            path,
+            if query.is_empty() { "" } else { "?" },
+            query
+        );
+        self.client
+            .request(method, url)
+            .header("x-amz-date", now.format("%Y%m%dT%H%M%SZ").to_string())
+            .header("x-amz-content-sha256", payload_hash)
+            .header(reqwest::header::AUTHORIZATION, authorization)
+            .body(body)
+            .send()
+            .await
+            .map_err(|e| format!("S3 request failed: {}", e))
+    }
+
+    async fn check(response: reqwest::Response, action: &str) -> Result<reqwest::Response, String> {
+        if response.status().is_success() {
+            return Ok(response);
+        }
+        let status = response.status();
+        let body = response.text().await.unwrap_or_default();
+        let message = Document::parse(&body)
+            .ok()
+            .and_then(|doc| {
+                doc.descendants()
+                    .find(|n| n.tag_name().name() == "Message")
+                    .and_then(|n| n.text().map(str::to_string))
+            })
+            .unwrap_or_default();
+        Err(format!("S3 {} failed: HTTP {} {}", action, status, message)
+            .trim_end()
+            .to_string())
+    }
+
+    /// 列出前缀下的所有对象
+    pub async fn list(&self) -> Result<BTreeMap<String, RemoteEntry>, String> {
+        let mut files = BTreeMap::new();
+        let mut token: Option<String> = None;
+        loop {
+            let mut query = vec![("list-type", "2"), ("prefix", self.prefix.as_str())];
+            if let Some(token) = token.as_deref() {
+                query.push(("continuation-token", token));
+            }
+            let response = self.send(Method::GET, "", &query, Vec::new()).await?;
+            let text = Self::check(response, "list")
+                .await?
+                .text()
+                .await
+                .map_err(|e| format!("S3 request failed: {}", e))?;
+            let doc = Document::parse(&text).map_err(|e| format!("Invalid S3 response: {}", e))?;
+
+            let text_of = |node: roxmltree::Node<'_, '_>, name: &str| {
+                node.children()
+                    .find(|n| n.tag_name().name() == name)
+                    .and_then(|n| n.text())
+                    .unwrap_or_default()
+                    .to_string()
+            };
+            let root = doc.root_element();
+            for contents in root
+                .children()
+                .filter(|n| n.tag_name().name() == "Contents")
+            {
+                let key = text_of(contents, "Key");
+                let Some(rel) = key.strip_prefix(&self.prefix) else {
+                    continue;
+                };
+                // 以 `/` 结尾的是控制台创建的“文件夹”占位对象
+                if rel.is_empty() || rel.ends_with('/') {
+                    continue;
+                }
+                files.insert(
+                    rel.to_string(),
+                    RemoteEntry {
+                        tag: text_of(contents, "ETag"),
+                    },
+                );
+            }
+            token = (text_of(root, "IsTruncated") == "true")
+                .then(|| text_of(root, "NextContinuationToken"))
+                .filter(|t| !t.is_empty());
+            if token.is_none() {
+                return Ok(files);
+            }
+        }
+    }
+
+    pub async fn get(&self, rel: &str) -> Result<Vec<u8>, String> {
+        let key = format!("{}{}", self.prefix, rel);
+        let response = self.send(Method::GET, &key, &[], Vec::new()).await?;
+        Self::check(response, "GET")
+            .await?
+            .bytes()
+            .await
+            .map(|b| b.to_vec())
+            .map_err(|e| format!("S3 request failed: {}", e))
+    }
+
+    /// 上传对象，返回新的 ETag
+    pub async fn put(&self, rel: &str, data: Vec<u8>) -> Result<Option<String>, String> {
+        let key = format!("{}{}", self.prefix, rel);
+        let response = self.send(Method::PUT, &key, &[], data).await?;
+        let response = Self::check(response, "PUT").await?;
+        Ok(response
+            .headers()
+            .get(reqwest::header::ETAG)
+            .and_then(|v| v.to_str().ok())
+            .map(str::to_string))
+    }
+
+    pub async fn delete(&self, rel: &str) -> Result<(), String> {
+        let key = format!("{}{}", self.prefix, rel);
+        let response = self.send(Method::DELETE, &key, &[], Vec::new()).await?;
+        Self::check(response, "DELETE").await.map(|_| ())
+    }
+}
@@ -0,0 +1,291 @@
+//! WebDAV 远端
+//!
+//! 用 `PROPFIND`（Depth: 1，逐层展开）列出文件，`GET` / `PUT` / `DELETE` 传输，
+//! 上传前用 `MKCOL` 创建缺失的目录。很多服务器禁用 `Depth: infinity`，因此不依赖它。
+
+use std::collections::{BTreeMap, HashSet};
+use std::sync::Mutex;
+
+use reqwest::{Method, RequestBuilder, StatusCode, Url};
+use roxmltree::{Document, Node};
+use serde::{Deserialize, Serialize};
+
+use super::{is_safe_rel, uri_encode, RemoteEntry};
+use crate::render::percent_decode;
+
+const PROPFIND_BODY: &str = r#"<?xml version="1.0" encoding="utf-8"?>
+<d:propfind xmlns:d="DAV:">
+  <d:prop><d:resourcetype/><d:getetag/><d:getlastmodified/><d:getcontentlength/></d:prop>
+</d:propfind>"#;
+
+/// WebDAV 远端配置，密码保存在系统钥匙串中
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WebDavConfig {
+    /// 远端目录地址，如 `https://dav.example.com/remote.php/dav/files/me/notes/`
+    pub url: String,
+    #[serde(default)]
+    pub username: String,
+}
+
+pub struct WebDav {
+    client: reqwest::Client,
+    /// 远端根目录，总是以 `/` 结尾
+    base: Url,
+    username: String,
+    password: String,
+    /// 本次同步中已确认存在的目录
+    collections: Mutex<HashSet<String>>,
+}
+
+fn method(name: &str) -> Method {
+    Method::from_bytes(name.as_bytes()).expect("valid HTTP method")
+}
+
+/// 子元素（忽略命名空间前缀）
+fn child<'a, 'input>(node: Node<'a, 'input>, name: &str) -> Option<Node<'a, 'input>> {
+    node.descendants()
+        .find(|n| n.is_element() && n.tag_name().name() == name)
+}
+
+fn child_text(node: Node<'_, '_>, name: &str) -> Option<String> {
+    child(node, name)
+        .and_then(|n| n.text())
+        .map(|t| t.trim().to_string())
+        .filter(|t| !t.is_empty())
+}
+
+impl WebDav {
+    pub fn new(
+        client: reqwest::Client,
+        config: &WebDavConfig,
+        password: &str,
+    ) -> Result<Self, String> {
+        let url = config.url.as_str();
+        let mut base = Url::parse(url.trim()).map_err(|e| format!("Invalid WebDAV URL: {}", e))?;
+        if !matches!(base.scheme(), "http" | "https") {
+            return Err(format!("Invalid WebDAV URL: {}", url));
+        }
+        if !base.path().ends_with('/') {
+            base.set_path(&format!("{}/", base.path()));
+        }
+        Ok(WebDav {
+            client,
+            base,
+            username: config.username.clone(),
+            password: password.to_string(),
+            collections: Mutex::new(HashSet::new()),
+        })
+    }
+
+    fn url(&self, rel: &str) -> Result<Url, String> {
+        self.base
+            .join(&uri_encode(rel, false))
+            .map_err(|e| format!("Invalid remote path {}: {}", rel, e))
+    }
+
+    /// 目录的 URL，以 `/` 结尾以免服务器重定向
+    fn dir_url(&self, dir: &str) -> Result<Url, String> {
+        let mut url = self.url(dir)?;
+        if !url.path().ends_with('/') {
+            url.set_path(&format!("{}/", url.path()));
+        }
+        Ok(url)
+    }
+
+    fn request(&self, method: Method, url: Url) -> RequestBuilder {
+        let request = self.client.request(method, url);
+        if self.username.is_empty() {
+            request
+        } else {
+            request.basic_auth(&self.username, Some(&self.password))
+        }
+    }
+
+    /// 列出目录的直接子项，返回（文件，子目录）；目录不存在时返回 `None`
+    async fn propfind(
+        &self,
+        dir: &str,
+    ) -> Result<Option<(Vec<(String, RemoteEntry)>, Vec<String>)>, String> {
+        let response = self
+            .request(method("PROPFIND"), self.dir_url(dir)?)
+            .header("Depth", "1")
+            .header("Content-Type", "application/xml; charset=utf-8")
+            .body(PROPFIND_BODY)
+            .send()
+            .await
+            .map_err(|e| format!("WebDAV request failed: {}", e))?;
+        let status = response.status();
+        if status == StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+        if status.as_u16() != 207 && !status.is_success() {
+            return Err(format!("WebDAV PROPFIND {} failed: HTTP {}", dir, status));
+        }
+        let text = response
+            .text()
+            .await
+            .map_err(|e| format!("WebDAV request failed: {}", e))?;
+        let doc = Document::parse(&text).map_err(|e| format!("Invalid WebDAV response: {}", e))?;
+
+        let base_path = percent_decode(self.base.path());
+        let mut files = Vec::new();
+        let mut dirs = Vec::new();
+        for response in doc
+            .descendants()
+            .filter(|n| n.is_element() && n.tag_name().name() == "response")
+        {
+            let Some(href) = child_text(response, "href") else {
+                continue;
+            };
+            let Ok(url) = self.base.join(&href) else {
+                continue;
+            };
+            let path = percent_decode(url.path());
+            let Some(rel) = path.strip_prefix(&base_path) else {
+                continue;
+            };
+            let rel = rel.trim_end_matches('/').to_string();
+            if rel == dir.trim_end_matches('/') {
+                continue;
+            }
+            if child(response, "collection").is_some() {
+                dirs.push(rel);
+                continue;
+            }
+            let size = child_text(response, "getcontentlength").unwrap_or_default();
+            // 没有 ETag 的服务器用修改时间和大小代替
+            let tag = child_text(response, "getetag").unwrap_or_else(|| {
+                format!(
+                    "{}-{}",
+                    child_text(response, "getlastmodified").unwrap_or_default(),
+                    size
+                )
+            });
+            files.push((rel, RemoteEntry { tag }));
+        }
+        Ok(Some((files, dirs)))
+    }
+
+    /// 递归列出远端所有文件
+    pub async fn list(&self) -> Result<BTreeMap<String, RemoteEntry>, String> {
+        let mut files = BTreeMap::new();
+        let mut pending = vec![String::new()];
+        while let Some(dir) = pending.pop() {
+            let Some((entries, dirs)) = self.propfind(&dir).await? else {
+                if dir.is_empty() {
+                    // 远端根目录还不存在，第一次上传时创建
+                    return Ok(files);
+                }
+                continue;
+            };
+            if let Ok(mut collections) = self.collections.lock() {
+                collections.insert(dir.clone());
+                collections.extend(dirs.iter().cloned());
+            }
+            files.extend(entries);
+            pending.extend(dirs.into_iter().filter(|d| {
+                is_safe_rel(d)
+                    && !d
+                        .rsplit('/')
+                        .next()
+                        .is_some_and(crate::workspace::is_ignored)
+            }));
+        }
+        Ok(files)
+    }
+
+    pub async fn get(&self, rel: &str) -> Result<Vec<u8>, String> {
+        let response = self
+            .request(Method::GET, self.url(rel)?)
+            .send()
+            .await
+            .map_err(|e| format!("WebDAV request failed: {}", e))?;
+        if !response.status().is_success() {
+            return Err(format!(
+                "WebDAV GET {} failed: HTTP {}",
+                rel,
+                response.status()
+            ));
+        }
+        response
+            .bytes()
+            .await
+            .map(|b| b.to_vec())
+            .map_err(|e| format!("WebDAV request failed: {}", e))
+    }
+
+    /// 依次创建文件所在的各级目录（包括远端根目录）
+    async fn ensure_parents(&self, rel: &str) -> Result<(), String> {
+        let mut dirs = vec![String::new()];
+        if let Some((parent, _)) = rel.rsplit_once('/') {
+            let mut dir = String::new();
+            for segment in parent.split('/') {
+                if !dir.is_empty() {
+                    dir.push('/');
+                }
+                dir.push_str(segment);
+                dirs.push(dir.clone());
+            }
+        }
+        for dir in dirs {
+            if self
+                .collections
+                .lock()
+                .map(|c| c.contains(&dir))
+                .unwrap_or(false)
+            {
+                continue;
+            }
+            let response = self
+                .request(method("MKCOL"), self.dir_url(&dir)?)
+                .send()
+                .await
+                .map_err(|e| format!("WebDAV request failed: {}", e))?;
+            // 405：目录已存在
+            let status = response.status();
+            if !status.is_success() && status != StatusCode::METHOD_NOT_ALLOWED {
+                return Err(format!("WebDAV MKCOL {} failed: HTTP {}", dir, status));
+            }
+            if let Ok(mut collections) = self.collections.lock() {
+                collections.insert(dir);
+            }
+        }
+        Ok(())
+    }
+
+    /// 上传文件，返回服务器给出的新 ETag
+    pub async fn put(&self, rel: &str, data: Vec<u8>) -> Result<Option<String>, String> {
+        self.ensure_parents(rel).await?;
+        let response = self
+            .request(Method::PUT, self.url(rel)?)
+            .body(data)
+            .send()
+            .await
+            .map_err(|e| format!("WebDAV request failed: {}", e))?;
+        if !response.status().is_success() {
+            return Err(format!(
+                "WebDAV PUT {} failed: HTTP {}",
+                rel,
+                response.status()
+            ));
+        }
+        Ok(response
+            .headers()
+            .get(reqwest::header::ETAG)
+            .and_then(|v| v.to_str().ok())
+            .map(str::to_string))
+    }
+
+    pub async fn delete(&self, rel: &str) -> Result<(), String> {
+        let response = self
+            .request(Method::DELETE, self.url(rel)?)
+            .send()
+            .await
+            .map_err(|e| format!("WebDAV request failed: {}", e))?;
+        let status = response.status();
+        if !status.is_success() && status != StatusCode::NOT_FOUND {
+            return Err(format!("WebDAV DELETE {} failed: HTTP {}", rel, status));
+        }
+        Ok(())
+    }
+}
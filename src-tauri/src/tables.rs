@@ -0,0 +1,504 @@
+//! GFM 表格格式化与编辑
+//!
+//! 前端表格工具栏的对齐、增删行列、排序和 CSV 互转都在后端完成。
+//! 表格位置由 pulldown-cmark 识别（代码块中的 `|` 不会误判），单元格按未转义的 `|` 拆分；
+//! 编辑后的表格总是以对齐后的形式写回。
+
+use std::cmp::Ordering;
+use std::ops::Range;
+
+use pulldown_cmark::{Alignment, Event, Tag};
+use serde::{Deserialize, Serialize};
+
+use crate::error::VividError;
+use crate::markdown;
+
+/// 分隔行中每列至少使用的 `-` 数量
+const MIN_COLUMN_WIDTH: usize = 3;
+
+/// 表格操作，行号不含表头，列号和行号都从 0 开始
+#[derive(Debug, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum TableOp {
+    /// 在该行之前插入空行，超出行数时追加到末尾
+    InsertRow {
+        index: usize,
+    },
+    DeleteRow {
+        index: usize,
+    },
+    /// 在该列之前插入空列，超出列数时追加到末尾
+    InsertColumn {
+        index: usize,
+    },
+    DeleteColumn {
+        index: usize,
+    },
+    SortByColumn {
+        column: usize,
+        #[serde(default)]
+        descending: bool,
+    },
+    /// 用 CSV 替换表格内容，第一行作为表头；`delimiter` 默认为 `,`，从表格软件粘贴时为 `\t`
+    ImportCsv {
+        csv: String,
+        #[serde(default)]
+        delimiter: Option<char>,
+    },
+    /// 导出为 CSV，文档内容不变
+    ExportCsv {
+        #[serde(default)]
+        delimiter: Option<char>,
+    },
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TableEditResult {
+    /// 修改后的完整文档内容
+    pub content: String,
+    /// `export_csv` 导出的内容
+    pub csv: Option<String>,
+}
+
+/// 文档中的一个表格
+struct Table {
+    /// 表格所在的完整行（不含最后一行的换行符）
+    range: Range<usize>,
+    /// 每行前的容器前缀，如引用块中的 `> `
+    prefix: String,
+    aligns: Vec<Alignment>,
+    header: Vec<String>,
+    rows: Vec<Vec<String>>,
+}
+
+/// 按未转义的 `|` 拆分一行
+fn split_cells(line: &str) -> Vec<String> {
+    let line = line.trim();
+    let line = line.strip_prefix('|').unwrap_or(line);
+    let mut cells = Vec::new();
+    let mut cell = String::new();
+    let mut escaped = false;
+    for c in line.chars() {
+        if c == '|' && !escaped {
+            cells.push(cell.trim().to_string());
+            cell.clear();
+            continue;
+        }
+        escaped = c == '\\' && !escaped;
+        cell.push(c);
+    }
+    // 行尾没有 `|` 时最后一个单元格仍有内容
+    if !cell.trim().is_empty() || cells.is_empty() {
+        cells.push(cell.trim().to_string());
+    }
+    cells
+}
+
+fn parse_table(content: &str, range: Range<usize>, aligns: Vec<Alignment>) -> Table {
+    let start = markdown::line_start(content, range.start);
+    let end = start
+        + content[start..range.end.min(content.len())]
+            .trim_end_matches(['\n', '\r'])
+            .len();
+    let prefix = content[start..range.start].to_string();
+
+    let mut lines = content[start..end].lines().map(|line| {
+        line.strip_prefix(prefix.as_str())
+            .or_else(|| line.strip_prefix(prefix.trim_end()))
+            .unwrap_or(line)
+    });
+    let header = lines.next().map(split_cells).unwrap_or_default();
+    // 跳过分隔行
+    lines.next();
+    let rows = lines.map(split_cells).collect();
+
+    let mut table = Table {
+        range: start..end,
+        prefix,
+        aligns,
+        header,
+        rows,
+    };
+    table.normalize();
+    table
+}
+
+/// 文档中的所有表格
+fn find_tables(content: &str) -> Vec<Table> {
+    markdown::parser(content)
+        .into_offset_iter()
+        .filter_map(|(event, range)| match event {
+            Event::Start(Tag::Table(aligns)) => Some(parse_table(content, range, aligns)),
+            _ => None,
+        })
+        .collect()
+}
+
+/// 等宽字体下的显示宽度，CJK 字符占两列
+fn display_width(text: &str) -> usize {
+    text.chars()
+        .map(|c| if markdown::is_cjk_char(c) { 2 } else { 1 })
+        .sum()
+}
+
+fn pad_cell(text: &str, width: usize, align: Alignment) -> String {
+    let gap = width.saturating_sub(display_width(text));
+    let (left, right) = match align {
+        Alignment::Right => (gap, 0),
+        Alignment::Center => (gap / 2, gap - gap / 2),
+        Alignment::Left | Alignment::None => (0, gap),
+    };
+    format!("{}{}{}", " ".repeat(left), text, " ".repeat(right))
+}
+
+fn delimiter_cell(width: usize, align: Alignment) -> String {
+    match align {
+        Alignment::None => "-".repeat(width),
+        Alignment::Left => format!(":{}", "-".repeat(width - 1)),
+        Alignment::Right => format!("{}:", "-".repeat(width - 1)),
+        Alignment::Center => format!(":{}:", "-".repeat(width - 2)),
+    }
+}
+
+/// 排序时比较两个单元格：都是数字时按数值，否则忽略大小写按文本
+fn compare_cells(a: &str, b: &str) -> Ordering {
+    let number = |s: &str| s.replace(',', "").parse::<f64>().ok();
+    match (number(a), number(b)) {
+        (Some(x), Some(y)) => x.partial_cmp(&y).unwrap_or(Ordering::Equal),
+        _ => a.to_lowercase().cmp(&b.to_lowercase()),
+    }
+}
+
+/// CSV 字段 → 单元格：转义 `|`，换行改为 `<br>`
+fn csv_to_cell(field: &str) -> String {
+    field
+        .trim()
+        .replace('|', "\\|")
+        .replace("\r\n", "<br>")
+        .replace('\n', "<br>")
+}
+
+/// 单元格 → CSV 字段
+fn cell_to_csv(cell: &str) -> String {
+    cell.replace("\\|", "|")
+}
+
+fn csv_delimiter(delimiter: Option<char>) -> Result<u8, String> {
+    match delimiter {
+        None => Ok(b','),
+        Some(c) if c.is_ascii() => Ok(c as u8),
+        Some(c) => Err(format!("Unsupported CSV delimiter: {}", c)),
+    }
+}
+
+impl Table {
+    fn columns(&self) -> usize {
+        self.header.len()
+    }
+
+    /// 补齐所有行的列数（多出的单元格保留，表头相应补空列）
+    fn normalize(&mut self) {
+        let columns = self
+            .rows
+            .iter()
+            .map(Vec::len)
+            .chain([self.header.len(), self.aligns.len(), 1])
+            .max()
+            .unwrap_or(1);
+        self.header.resize(columns, String::new());
+        self.aligns.resize(columns, Alignment::None);
+        for row in &mut self.rows {
+            row.resize(columns, String::new());
+        }
+    }
+
+    /// 对齐后的 Markdown 源码
+    fn render(&self) -> String {
+        let widths: Vec<usize> = (0..self.columns())
+            .map(|i| {
+                self.rows
+                    .iter()
+                    .map(|row| display_width(&row[i]))
+                    .chain([display_width(&self.header[i]), MIN_COLUMN_WIDTH])
+                    .max()
+                    .unwrap_or(MIN_COLUMN_WIDTH)
+            })
+            .collect();
+
+        let line = |cells: Vec<String>| format!("{}| {} |", self.prefix, cells.join(" | "));
+        let row = |cells: &[String]| {
+            line(
+                cells
+                    .iter()
+                    .enumerate()
+                    .map(|(i, cell)| pad_cell(cell, widths[i], self.aligns[i]))
+                    .collect(),
+            )
+        };
+
+        let mut lines = vec![row(&self.header)];
+        lines.push(line(
+            widths
+                .iter()
+                .zip(&self.aligns)
+                .map(|(&width, &align)| delimiter_cell(width, align))
+                .collect(),
+        ));
+        lines.extend(self.rows.iter().map(|cells| row(cells)));
+        lines.join("\n")
+    }
+
+    fn apply(&mut self, op: &TableOp) -> Result<Option<String>, String> {
+        match op {
+            TableOp::InsertRow { index } => {
+                let index = (*index).min(self.rows.len());
+                self.rows.insert(index, vec![String::new(); self.columns()]);
+            }
+            TableOp::DeleteRow { index } => {
+                if *index >= self.rows.len() {
+                    return Err(format!("Row not found: {}", index));
+                }
+                self.rows.remove(*index);
+            }
+            TableOp::InsertColumn { index } => {
+                let index = (*index).min(self.columns());
+                self.header.insert(index, String::new());
+                self.aligns.insert(index, Alignment::None);
+                for row in &mut self.rows {
+                    row.insert(index, String::new());
+                }
+            }
+            TableOp::DeleteColumn { index } => {
+                if *index >= self.columns() {
+                    return Err(format!("Column not found: {}", index));
+                }
+                if self.columns() == 1 {
+                    return Err("Cannot delete the only column".to_string());
+                }
+                self.header.remove(*index);
+                self.aligns.remove(*index);
+                for row in &mut self.rows {
+                    row.remove(*index);
+                }
+            }
+            TableOp::SortByColumn { column, descending } => {
+                if *column >= self.columns() {
+                    return Err(format!("Column not found: {}", column));
+                }
+                self.rows.sort_by(|a, b| {
+                    let ordering = compare_cells(&a[*column], &b[*column]);
+                    if *descending {
+                        ordering.reverse()
+                    } else {
+                        ordering
+                    }
+                });
+            }
+            TableOp::ImportCsv { csv, delimiter } => {
+                let mut reader = csv::ReaderBuilder::new()
+                    .has_headers(false)
+                    .flexible(true)
+                    .delimiter(csv_delimiter(*delimiter)?)
+                    .from_reader(csv.as_bytes());
+                let mut records = Vec::new();
+                for record in reader.records() {
+                    let record = record.map_err(|e| format!("Invalid CSV: {}", e))?;
+                    records.push(record.iter().map(csv_to_cell).collect::<Vec<String>>());
+                }
+                if records.is_empty() {
+                    return Err("CSV is empty".to_string());
+                }
+                self.header = records.remove(0);
+                self.rows = records;
+                self.normalize();
+            }
+            TableOp::ExportCsv { delimiter } => {
+                let mut writer = csv::WriterBuilder::new()
+                    .flexible(true)
+                    .delimiter(csv_delimiter(*delimiter)?)
+                    .from_writer(Vec::new());
+                for record in std::iter::once(&self.header).chain(&self.rows) {
+                    writer
+                        .write_record(record.iter().map(|cell| cell_to_csv(cell)))
+                        .map_err(|e| format!("Failed to write CSV: {}", e))?;
+                }
+                let bytes = writer
+                    .into_inner()
+                    .map_err(|e| format!("Failed to write CSV: {}", e))?;
+                return Ok(Some(String::from_utf8_lossy(&bytes).to_string()));
+            }
+        }
+        Ok(None)
+    }
+}
+
+/// 对齐文档中所有表格
+pub fn format_all(content: &str) -> String {
+    let mut out = content.to_string();
+    for table in find_tables(content).iter().rev() {
+        out.replace_range(table.range.clone(), &table.render());
+    }
+    out
+}
+
+// 对齐文档中所有表格的列
+#[tauri::command]
+pub fn format_tables(content: String) -> Result<String, VividError> {
+    let formatted = format_all(&content);
+    log::debug!(
+        "[format_tables] {} bytes -> {} bytes",
+        content.len(),
+        formatted.len()
+    );
+    Ok(formatted)
+}
+
+// 编辑文档中第 `table_index` 个表格（从 0 开始）
+#[tauri::command]
+pub fn table_edit(
+    content: String,
+    table_index: usize,
+    op: TableOp,
+) -> Result<TableEditResult, VividError> {
+    log::debug!("[table_edit] Table {}: {:?}", table_index, op);
+    let mut tables = find_tables(&content);
+    if table_index >= tables.len() {
+        log::warn!("[table_edit] Table not found: {}", table_index);
+        return Err(VividError::invalid_input(format!(
+            "Table not found: {}",
+            table_index
+        )));
+    }
+    let table = &mut tables[table_index];
+    let csv = table.apply(&op).map_err(|e| {
+        log::warn!("[table_edit] {}", e);
+        e
+    })?;
+    if csv.is_some() {
+        return Ok(TableEditResult { content, csv });
+    }
+
+    let mut updated = content;
+    updated.replace_range(table.range.clone(), &table.render());
+    Ok(TableEditResult {
+        content: updated,
+        csv: None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn table(content: &str) -> Table {
+        find_tables(content).remove(0)
+    }
+
+    #[test]
+    fn split_cells_keeps_escaped_pipes() {
+        assert_eq!(
+            split_cells(r"| a \| b | c |"),
+            vec![r"a \| b".to_string(), "c".to_string()]
+        );
+        // `\\` 转义的是反斜杠本身，其后的 `|` 仍是分隔符
+        assert_eq!(
+            split_cells(r"| a \\| b |"),
+            vec![r"a \\".to_string(), "b".to_string()]
+        );
+    }
+
+    #[test]
+    fn split_cells_without_outer_pipes() {
+        assert_eq!(split_cells("a | b"), vec!["a".to_string(), "b".to_string()]);
+        assert_eq!(split_cells("| |"), vec![String::new()]);
+    }
+
+    #[test]
+    fn format_aligns_columns_by_display_width() {
+        let content = "前文\n\n|名称|值|\n|:-|-:|\n|中文|1|\n|a|100|\n\n后文\n";
+        assert_eq!(
+            format_all(content),
+            "前文\n\n| 名称 |  值 |\n| :--- | --: |\n| 中文 |   1 |\n| a    | 100 |\n\n后文\n"
+        );
+    }
+
+    #[test]
+    fn format_preserves_escaped_pipes() {
+        let content = "| a | b |\n|---|---|\n| x \\| y | z |\n";
+        let formatted = format_all(content);
+        assert_eq!(
+            formatted,
+            "| a      | b   |\n| ------ | --- |\n| x \\| y | z   |\n"
+        );
+        assert_eq!(table(&formatted).rows[0], vec!["x \\| y", "z"]);
+    }
+
+    #[test]
+    fn format_keeps_blockquote_prefix() {
+        let content = "> | a | b |\n> |---|---|\n> | 1 | 2 |\n";
+        assert_eq!(
+            format_all(content),
+            "> | a   | b   |\n> | --- | --- |\n> | 1   | 2   |\n"
+        );
+    }
+
+    #[test]
+    fn pipes_in_code_blocks_are_not_tables() {
+        let content = "```\n| a | b |\n|---|---|\n```\n";
+        assert!(find_tables(content).is_empty());
+        assert_eq!(format_all(content), content);
+    }
+
+    #[test]
+    fn short_rows_are_padded() {
+        let t = table("| a | b | c |\n|---|---|---|\n| 1 |\n");
+        assert_eq!(t.rows[0], vec!["1", "", ""]);
+    }
+
+    #[test]
+    fn sort_compares_numbers_by_value() {
+        let mut t = table("| n |\n|---|\n| 10 |\n| 9 |\n| 1,000 |\n");
+        t.apply(&TableOp::SortByColumn {
+            column: 0,
+            descending: false,
+        })
+        .unwrap();
+        let column: Vec<&str> = t.rows.iter().map(|row| row[0].as_str()).collect();
+        assert_eq!(column, vec!["9", "10", "1,000"]);
+    }
+
+    #[test]
+    fn insert_and_delete_columns() {
+        let mut t = table("| a | b |\n|---|---|\n| 1 | 2 |\n");
+        t.apply(&TableOp::InsertColumn { index: 1 }).unwrap();
+        assert_eq!(t.header, vec!["a", "", "b"]);
+        assert_eq!(t.rows[0], vec!["1", "", "2"]);
+        t.apply(&TableOp::DeleteColumn { index: 0 }).unwrap();
+        t.apply(&TableOp::DeleteColumn { index: 0 }).unwrap();
+        assert!(t.apply(&TableOp::DeleteColumn { index: 0 }).is_err());
+        assert!(t.apply(&TableOp::DeleteRow { index: 5 }).is_err());
+    }
+
+    #[test]
+    fn csv_round_trip_unescapes_pipes() {
+        let mut t = table("| a | b |\n|---|---|\n| x \\| y | z |\n");
+        let csv = t
+            .apply(&TableOp::ExportCsv { delimiter: None })
+            .unwrap()
+            .unwrap();
+        assert_eq!(csv, "a,b\nx | y,z\n");
+
+        t.apply(&TableOp::ImportCsv {
+            csv: "h1\th2\n\"p|q\"\t\"多\n行\"\n".to_string(),
+            delimiter: Some('\t'),
+        })
+        .unwrap();
+        assert_eq!(t.header, vec!["h1", "h2"]);
+        assert_eq!(t.rows[0], vec!["p\\|q", "多<br>行"]);
+    }
+
+    #[test]
+    fn rejects_non_ascii_csv_delimiter() {
+        assert!(csv_delimiter(Some('，')).is_err());
+    }
+}
@@ -0,0 +1,425 @@
+//! 标签索引
+//!
+//! 标签来源有两处：正文中的 `#tag`（支持 `#parent/child` 层级）和 front matter 中的
+//! `tags` / `tag` 字段。标签比较不区分大小写，以首次出现的写法展示。
+
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use pulldown_cmark::{Event, Tag, TagEnd};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use tauri::{AppHandle, State};
+
+use crate::access::{self, AccessKind};
+use crate::error::VividError;
+use crate::frontmatter;
+use crate::markdown;
+use crate::workspace::FileIndex;
+
+/// 标签汇总
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TagSummary {
+    pub name: String,
+    /// 出现总次数
+    pub count: usize,
+    /// 包含该标签的文件数
+    pub file_count: usize,
+}
+
+/// 带有某个标签的文件
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TaggedFile {
+    pub path: String,
+    pub name: String,
+    /// 该文件中匹配的次数
+    pub count: usize,
+}
+
+/// 重命名标签的结果
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RenameTagResult {
+    /// 被修改的文件
+    pub files: Vec<String>,
+    /// 替换的次数
+    pub occurrences: usize,
+}
+
+/// 正文中的一处 `#tag`
+#[derive(Debug, Clone)]
+pub struct InlineTag {
+    pub name: String,
+    /// 标签名（不含 `#`）在文档中的字节区间
+    pub range: Range<usize>,
+}
+
+fn is_tag_char(c: char) -> bool {
+    c.is_alphanumeric() || matches!(c, '_' | '-' | '/')
+}
+
+/// 标签名是否合法：非空、只含允许的字符，且不能全是数字（避免把 `#123` 当标签）
+pub fn is_valid_tag(name: &str) -> bool {
+    !name.is_empty()
+        && name.chars().all(is_tag_char)
+        && !name.chars().all(|c| c.is_ascii_digit() || c == '/')
+        && !name.starts_with('/')
+        && !name.ends_with('/')
+}
+
+/// 标签比较用的规范形式
+pub fn normalize_tag(name: &str) -> String {
+    name.trim().trim_start_matches('#').to_lowercase()
+}
+
+/// 去掉标签开头与 `prefix`（规范形式）对应的部分，返回剩余的子标签路径
+fn strip_tag_prefix<'a>(tag: &'a str, prefix: &str) -> &'a str {
+    let len: usize = tag
+        .chars()
+        .take(prefix.chars().count())
+        .map(char::len_utf8)
+        .sum();
+    &tag[len..]
+}
+
+/// `tag` 是否等于 `parent` 或是其子标签
+fn is_tag_or_child(tag: &str, parent: &str) -> bool {
+    tag == parent
+        || tag
+            .strip_prefix(parent)
+            .is_some_and(|rest| rest.starts_with('/'))
+}
+
+/// 在文档的 `range` 区间内查找 `#tag`
+fn scan_text(content: &str, range: Range<usize>, tags: &mut Vec<InlineTag>) {
+    let text = &content[range.clone()];
+    for (i, _) in text.match_indices('#') {
+        let at = range.start + i;
+        // `#` 前必须是行首或空白，排除 `a#b`、`\#`、URL 片段等
+        let preceded_ok = content[..at]
+            .chars()
+            .next_back()
+            .map_or(true, char::is_whitespace);
+        if !preceded_ok {
+            continue;
+        }
+        let rest = &text[i + 1..];
+        let len: usize = rest
+            .chars()
+            .take_while(|c| is_tag_char(*c))
+            .map(char::len_utf8)
+            .sum();
+        let name = rest[..len].trim_end_matches('/');
+        if is_valid_tag(name) {
+            let start = at + 1;
+            tags.push(InlineTag {
+                name: name.to_string(),
+                range: start..start + name.len(),
+            });
+        }
+    }
+}
+
+/// 提取正文中的 `#tag`（跳过 front matter、代码和公式）
+pub fn inline_tags(content: &str) -> Vec<InlineTag> {
+    let body_start = frontmatter::find_front_matter(content)
+        .map(|block| block.body_start)
+        .unwrap_or(0);
+    let body = &content[body_start..];
+
+    let mut tags = Vec::new();
+    let mut code_depth = 0usize;
+    for (event, range) in markdown::parser(body).into_offset_iter() {
+        match event {
+            Event::Start(Tag::CodeBlock(_)) => code_depth += 1,
+            Event::End(TagEnd::CodeBlock) => code_depth = code_depth.saturating_sub(1),
+            Event::Text(_) if code_depth == 0 => scan_text(
+                content,
+                body_start + range.start..body_start + range.end,
+                &mut tags,
+            ),
+            _ => {}
+        }
+    }
+    tags
+}
+
+/// front matter 中的标签字段名（`tags` 或 `tag`）及其值
+fn front_matter_tag_field(data: &Value) -> Option<(&str, &Value)> {
+    ["tags", "tag"]
+        .into_iter()
+        .find_map(|key| data.get(key).map(|value| (key, value)))
+}
+
+/// 解析 front matter 标签值：数组，或以逗号 / 空白分隔的字符串
+fn tag_list(value: &Value) -> Vec<String> {
+    let raw: Vec<String> = match value {
+        Value::Array(items) => items
+            .iter()
+            .filter_map(|item| match item {
+                Value::String(s) => Some(s.clone()),
+                Value::Number(n) => Some(n.to_string()),
+                _ => None,
+            })
+            .collect(),
+        Value::String(s) => s
+            .split(|c: char| c == ',' || c.is_whitespace())
+            .map(str::to_string)
+            .collect(),
+        _ => Vec::new(),
+    };
+    raw.into_iter()
+        .map(|tag| tag.trim().trim_start_matches('#').to_string())
+        .filter(|tag| !tag.is_empty())
+        .collect()
+}
+
+/// 提取 front matter 中的标签
+pub fn front_matter_tags(content: &str) -> Vec<String> {
+    match frontmatter::parse_front_matter(content) {
+        Ok(Some((_, data))) => front_matter_tag_field(&data)
+            .map(|(_, value)| tag_list(value))
+            .unwrap_or_default(),
+        _ => Vec::new(),
+    }
+}
+
+/// 单个文件中的标签：规范形式 → (展示写法, 次数)
+pub(crate) type FileTags = BTreeMap<String, (String, usize)>;
+
+pub(crate) fn file_tags(content: &str) -> FileTags {
+    let mut tags = FileTags::new();
+    let names = front_matter_tags(content)
+        .into_iter()
+        .chain(inline_tags(content).into_iter().map(|tag| tag.name));
+    for name in names {
+        tags.entry(normalize_tag(&name))
+            .or_insert_with(|| (name, 0))
+            .1 += 1;
+    }
+    tags
+}
+
+/// 工作区标签索引
+#[derive(Default)]
+pub struct TagIndex {
+    files: Mutex<HashMap<PathBuf, FileTags>>,
+}
+
+impl FileIndex for TagIndex {
+    fn rebuild(&self, paths: &[PathBuf]) {
+        let files: HashMap<PathBuf, FileTags> = paths
+            .iter()
+            .filter_map(|path| {
+                let content = fs::read_to_string(path).ok()?;
+                Some((path.clone(), file_tags(&content)))
+            })
+            .collect();
+        if let Ok(mut guard) = self.files.lock() {
+            *guard = files;
+        }
+    }
+
+    fn refresh(&self, path: &Path) {
+        let tags = fs::read_to_string(path).ok().map(|c| file_tags(&c));
+        if let Ok(mut files) = self.files.lock() {
+            match tags {
+                Some(tags) => {
+                    files.insert(path.to_path_buf(), tags);
+                }
+                None => {
+                    files.remove(path);
+                }
+            }
+        }
+    }
+
+    fn remove_under(&self, path: &Path) {
+        if let Ok(mut files) = self.files.lock() {
+            files.retain(|file, _| !file.starts_with(path));
+        }
+    }
+
+    fn clear(&self) {
+        if let Ok(mut files) = self.files.lock() {
+            files.clear();
+        }
+    }
+}
+
+impl TagIndex {
+    fn snapshot(&self) -> Result<Vec<(PathBuf, FileTags)>, String> {
+        let files = self.files.lock().map_err(|e| e.to_string())?;
+        let mut entries: Vec<_> = files
+            .iter()
+            .map(|(path, tags)| (path.clone(), tags.clone()))
+            .collect();
+        entries.sort_by(|a, b| a.0.cmp(&b.0));
+        Ok(entries)
+    }
+}
+
+fn file_name(path: &Path) -> String {
+    path.file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_default()
+}
+
+/// 重写单个文件中的标签，返回新内容与替换次数
+fn rename_in_content(content: &str, old: &str, new: &str) -> Result<(String, usize), String> {
+    let mut updated = String::with_capacity(content.len());
+    let mut last = 0;
+    let mut count = 0;
+    for tag in inline_tags(content) {
+        let normalized = normalize_tag(&tag.name);
+        if !is_tag_or_child(&normalized, old) {
+            continue;
+        }
+        // 只替换匹配的前缀部分，保留子标签
+        updated.push_str(&content[last..tag.range.start]);
+        updated.push_str(new);
+        last = tag.range.end - strip_tag_prefix(&tag.name, old).len();
+        count += 1;
+    }
+    updated.push_str(&content[last..]);
+
+    if let Ok(Some((_, data))) = frontmatter::parse_front_matter(&updated) {
+        if let Some((key, value)) = front_matter_tag_field(&data) {
+            let tags = tag_list(value);
+            let mut changed = 0;
+            let renamed: Vec<String> = tags
+                .iter()
+                .map(|tag| {
+                    let normalized = normalize_tag(tag);
+                    if is_tag_or_child(&normalized, old) {
+                        changed += 1;
+                        format!("{}{}", new, strip_tag_prefix(tag, old))
+                    } else {
+                        tag.clone()
+                    }
+                })
+                .collect();
+            if changed > 0 {
+                // 保持原字段类型：字符串写回为逗号分隔
+                let value = if value.is_string() {
+                    Value::from(renamed.join(", "))
+                } else {
+                    Value::from(renamed)
+                };
+                let mut patch = Map::new();
+                patch.insert(key.to_string(), value);
+                updated = frontmatter::apply_front_matter_patch(&updated, &patch)?;
+                count += changed;
+            }
+        }
+    }
+    Ok((updated, count))
+}
+
+// 获取工作区中的所有标签
+#[tauri::command]
+pub fn get_all_tags(index: State<'_, TagIndex>) -> Result<Vec<TagSummary>, VividError> {
+    let mut summary: BTreeMap<String, TagSummary> = BTreeMap::new();
+    for (_, tags) in index.snapshot()? {
+        for (key, (name, count)) in tags {
+            let entry = summary.entry(key).or_insert_with(|| TagSummary {
+                name,
+                count: 0,
+                file_count: 0,
+            });
+            entry.count += count;
+            entry.file_count += 1;
+        }
+    }
+    log::debug!("[get_all_tags] {} tag(s)", summary.len());
+    Ok(summary.into_values().collect())
+}
+
+// 获取带有某个标签（含子标签）的文件
+#[tauri::command]
+pub fn get_files_by_tag(
+    index: State<'_, TagIndex>,
+    tag: String,
+) -> Result<Vec<TaggedFile>, VividError> {
+    let tag = normalize_tag(&tag);
+    let files: Vec<TaggedFile> = index
+        .snapshot()?
+        .into_iter()
+        .filter_map(|(path, tags)| {
+            let count: usize = tags
+                .iter()
+                .filter(|(key, _)| is_tag_or_child(key, &tag))
+                .map(|(_, (_, count))| count)
+                .sum();
+            (count > 0).then(|| TaggedFile {
+                name: file_name(&path),
+                path: path.to_string_lossy().to_string(),
+                count,
+            })
+        })
+        .collect();
+    log::debug!("[get_files_by_tag] #{} -> {} file(s)", tag, files.len());
+    Ok(files)
+}
+
+// 重命名标签（含子标签），改写工作区内所有相关文件
+#[tauri::command]
+pub fn rename_tag(
+    app: AppHandle,
+    index: State<'_, TagIndex>,
+    old: String,
+    new: String,
+) -> Result<RenameTagResult, VividError> {
+    let old = normalize_tag(&old);
+    let new = new.trim().trim_start_matches('#').to_string();
+    log::info!("[rename_tag] #{} -> #{}", old, new);
+
+    if !is_valid_tag(&new) {
+        log::error!("[rename_tag] Invalid tag name: {}", new);
+        return Err(VividError::invalid_input(format!(
+            "Invalid tag name: {}",
+            new
+        )));
+    }
+
+    let mut result = RenameTagResult {
+        files: Vec::new(),
+        occurrences: 0,
+    };
+    let paths: Vec<PathBuf> = index
+        .snapshot()?
+        .into_iter()
+        .filter(|(_, tags)| tags.keys().any(|key| is_tag_or_child(key, &old)))
+        .map(|(path, _)| path)
+        .collect();
+    // 先检查全部文件，避免改到一半才被拒绝
+    for path in &paths {
+        access::ensure_access(&app, path, AccessKind::Write)?;
+    }
+    for path in paths {
+        let content = fs::read_to_string(&path).map_err(|e| {
+            log::error!("[rename_tag] Failed to read {}: {}", path.display(), e);
+            format!("Failed to read file: {}", e)
+        })?;
+        let (updated, count) = rename_in_content(&content, &old, &new)?;
+        if count == 0 || updated == content {
+            continue;
+        }
+        fs::write(&path, &updated).map_err(|e| {
+            log::error!("[rename_tag] Failed to write {}: {}", path.display(), e);
+            format!("Failed to save file: {}", e)
+        })?;
+        index.refresh(&path);
+        result.files.push(path.to_string_lossy().to_string());
+        result.occurrences += count;
+    }
+
+    log::info!(
+        "[rename_tag] ✓ Success: {} occurrence(s) in {} file(s)",
+        result.occurrences,
+        result.files.len()
+    );
+    Ok(result)
+}
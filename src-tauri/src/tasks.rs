@@ -0,0 +1,322 @@
+//! 任务列表汇总
+//!
+//! 全局待办面板通过 `collect_tasks` 汇总目录下所有笔记中的 GFM 任务项（`- [ ] ...`），
+//! 用 `toggle_task` 勾选或取消勾选。任务项由 pulldown-cmark 识别，代码块中的 `[ ]` 不算任务。
+//!
+//! 截止日期支持两种写法：Obsidian Tasks 的 `📅 2024-06-01` 和 TaskPaper 的 `@due(2024-06-01)`，
+//! 后者允许带时间（`@due(2024-06-01 18:00)`），只取日期部分。
+
+use std::fs;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use std::time::Instant;
+
+use chrono::NaiveDate;
+use pulldown_cmark::{Event, Tag, TagEnd};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager};
+
+use crate::access::{self, AccessKind};
+use crate::backup::BackupStore;
+use crate::error::VividError;
+use crate::largefile::LARGE_FILE_THRESHOLD;
+use crate::parse::LineIndex;
+use crate::settings::SettingsStore;
+use crate::{markdown, revision, storage, workspace};
+
+/// 任务筛选条件，未设置的条件不参与筛选
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct TaskFilters {
+    /// `true` 只返回已完成的任务，`false` 只返回未完成的任务
+    pub completed: Option<bool>,
+    /// 是否有截止日期
+    pub has_due: Option<bool>,
+    /// 截止日期不早于该日期（`YYYY-MM-DD`）
+    pub due_after: Option<String>,
+    /// 截止日期不晚于该日期（`YYYY-MM-DD`）
+    pub due_before: Option<String>,
+    /// 只返回未完成且已过期的任务
+    pub overdue: bool,
+    /// 任务文字中包含的内容，不区分大小写
+    pub query: Option<String>,
+}
+
+/// 一个任务项
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TaskItem {
+    pub path: String,
+    /// 所在行（从 0 开始），`toggle_task` 用它定位任务
+    pub line_index: usize,
+    /// 去掉复选框和截止日期后的任务文字
+    pub text: String,
+    pub completed: bool,
+    /// 截止日期（`YYYY-MM-DD`）
+    pub due: Option<String>,
+    /// 嵌套层级，顶层任务为 0
+    pub depth: usize,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TaskList {
+    pub tasks: Vec<TaskItem>,
+    pub open: usize,
+    pub completed: usize,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ToggleTaskResult {
+    pub path: String,
+    pub line_index: usize,
+    /// 切换后的状态
+    pub completed: bool,
+    /// 修改后的整行内容
+    pub line: String,
+    /// 文件的新修订标记
+    pub revision: String,
+}
+
+fn due_regex() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| {
+        Regex::new(r"(?:📅|🗓\u{FE0F}?)\s*(\d{4}-\d{2}-\d{2})|@due\(\s*(\d{4}-\d{2}-\d{2})[^)]*\)")
+            .expect("valid regex")
+    })
+}
+
+/// 提取截止日期，返回日期和去掉标注后的文字
+fn split_due(text: &str) -> (Option<String>, String) {
+    let mut due = None;
+    for caps in due_regex().captures_iter(text) {
+        let date = caps.get(1).or_else(|| caps.get(2)).map(|m| m.as_str());
+        if let Some(date) = date.filter(|d| NaiveDate::parse_from_str(d, "%Y-%m-%d").is_ok()) {
+            due.get_or_insert_with(|| date.to_string());
+        }
+    }
+    let stripped = due_regex().replace_all(text, "");
+    let text = stripped.split_whitespace().collect::<Vec<_>>().join(" ");
+    (due, text)
+}
+
+fn line_bounds(content: &str, offset: usize) -> (usize, usize) {
+    let start = content[..offset].rfind('\n').map_or(0, |i| i + 1);
+    let end = content[offset..]
+        .find('\n')
+        .map_or(content.len(), |i| offset + i);
+    (start, end)
+}
+
+/// 文档中的任务，返回 (复选框区间, 是否完成, 嵌套层级)
+fn task_markers(content: &str) -> Vec<(Range<usize>, bool, usize)> {
+    let mut depth = 0usize;
+    let mut markers = Vec::new();
+    for (event, range) in markdown::parser(content).into_offset_iter() {
+        match event {
+            Event::Start(Tag::List(_)) => depth += 1,
+            Event::End(TagEnd::List(_)) => depth = depth.saturating_sub(1),
+            Event::TaskListMarker(checked) => {
+                markers.push((range, checked, depth.saturating_sub(1)))
+            }
+            _ => {}
+        }
+    }
+    markers
+}
+
+/// 解析单个文档中的任务
+fn parse_tasks(path: &Path, content: &str) -> Vec<TaskItem> {
+    let lines = LineIndex::new(content);
+    let display = path.to_string_lossy().to_string();
+    task_markers(content)
+        .into_iter()
+        .map(|(range, completed, depth)| {
+            let (_, end) = line_bounds(content, range.start);
+            let rest = content.get(range.end..end).unwrap_or_default();
+            let (due, text) = split_due(rest);
+            TaskItem {
+                path: display.clone(),
+                line_index: lines.line_of(range.start),
+                text,
+                completed,
+                due,
+                depth,
+            }
+        })
+        .collect()
+}
+
+impl TaskFilters {
+    fn matches(&self, task: &TaskItem, today: &str) -> bool {
+        if self
+            .completed
+            .is_some_and(|completed| completed != task.completed)
+        {
+            return false;
+        }
+        if self
+            .has_due
+            .is_some_and(|has_due| has_due != task.due.is_some())
+        {
+            return false;
+        }
+        let due = task.due.as_deref();
+        if let Some(after) = self.due_after.as_deref().filter(|d| !d.is_empty()) {
+            if !due.is_some_and(|due| due >= after) {
+                return false;
+            }
+        }
+        if let Some(before) = self.due_before.as_deref().filter(|d| !d.is_empty()) {
+            if !due.is_some_and(|due| due <= before) {
+                return false;
+            }
+        }
+        if self.overdue && (task.completed || !due.is_some_and(|due| due < today)) {
+            return false;
+        }
+        match self
+            .query
+            .as_deref()
+            .map(str::trim)
+            .filter(|q| !q.is_empty())
+        {
+            Some(query) => task.text.to_lowercase().contains(&query.to_lowercase()),
+            None => true,
+        }
+    }
+}
+
+/// 读取文本文件，大文件和非 UTF-8 文件跳过
+fn read_markdown(path: &Path) -> Option<String> {
+    let metadata = fs::metadata(path).ok()?;
+    if metadata.len() > LARGE_FILE_THRESHOLD {
+        return None;
+    }
+    fs::read_to_string(path).ok()
+}
+
+fn collect(root: &Path, filters: &TaskFilters) -> TaskList {
+    let today = chrono::Local::now().format("%Y-%m-%d").to_string();
+    let mut tasks: Vec<TaskItem> = Vec::new();
+    for path in workspace::markdown_files(root) {
+        let Some(content) = read_markdown(&path) else {
+            continue;
+        };
+        tasks.extend(
+            parse_tasks(&path, &content)
+                .into_iter()
+                .filter(|task| filters.matches(task, &today)),
+        );
+    }
+    tasks.sort_by(|a, b| a.path.cmp(&b.path).then(a.line_index.cmp(&b.line_index)));
+    let completed = tasks.iter().filter(|task| task.completed).count();
+    TaskList {
+        open: tasks.len() - completed,
+        completed,
+        tasks,
+    }
+}
+
+/// 切换文件第 `line` 行的任务，返回新内容和切换后的状态
+fn toggle_in_content(content: &str, line: usize) -> Result<(String, bool, String), String> {
+    let lines = LineIndex::new(content);
+    let (range, checked, _) = task_markers(content)
+        .into_iter()
+        .find(|(range, _, _)| lines.line_of(range.start) == line)
+        .ok_or_else(|| format!("No task on line {}", line + 1))?;
+    let marker = &content[range.clone()];
+    let Some(mark) = marker.find(['x', 'X', ' ']) else {
+        return Err(format!("No task on line {}", line + 1));
+    };
+    let at = range.start + mark;
+    let mut updated = String::with_capacity(content.len());
+    updated.push_str(&content[..at]);
+    updated.push(if checked { ' ' } else { 'x' });
+    updated.push_str(&content[at + 1..]);
+    let (start, end) = line_bounds(&updated, at);
+    let line_text = updated[start..end].trim_end_matches('\r').to_string();
+    Ok((updated, !checked, line_text))
+}
+
+fn toggle(app: &AppHandle, path: &Path, line: usize) -> Result<ToggleTaskResult, VividError> {
+    let content = read_markdown(path)
+        .ok_or_else(|| VividError::invalid_input("File is too large or not valid UTF-8"))?;
+    let (updated, completed, line_text) =
+        toggle_in_content(&content, line).map_err(VividError::invalid_input)?;
+
+    let settings = app.state::<SettingsStore>().get();
+    app.state::<BackupStore>()
+        .before_save(&settings.backup, path);
+    storage::write_atomic(path, updated.as_bytes())?;
+    Ok(ToggleTaskResult {
+        path: path.to_string_lossy().to_string(),
+        line_index: line,
+        completed,
+        line: line_text,
+        revision: revision::revision_for(updated.as_bytes(), fs::metadata(path).ok().as_ref()),
+    })
+}
+
+// 汇总目录下所有笔记中的任务项
+#[tauri::command]
+pub async fn collect_tasks(
+    app: AppHandle,
+    root: String,
+    filters: Option<TaskFilters>,
+) -> Result<TaskList, VividError> {
+    let start = Instant::now();
+    let root = PathBuf::from(root);
+    if !root.is_dir() {
+        log::error!(
+            "[collect_tasks] Directory does not exist: {}",
+            root.display()
+        );
+        return Err(VividError::dir_not_found(&root));
+    }
+    access::ensure_access(&app, &root, AccessKind::Read)?;
+    let filters = filters.unwrap_or_default();
+    log::info!("[collect_tasks] {} {:?}", root.display(), filters);
+
+    let list = tauri::async_runtime::spawn_blocking(move || collect(&root, &filters))
+        .await
+        .map_err(|e| format!("Task scan failed: {}", e))?;
+    log::info!(
+        "[collect_tasks] ✓ Success: {} open, {} completed in {:?}",
+        list.open,
+        list.completed,
+        start.elapsed()
+    );
+    Ok(list)
+}
+
+// 勾选或取消勾选文件第 `line` 行（从 0 开始）的任务
+#[tauri::command]
+pub async fn toggle_task(
+    app: AppHandle,
+    path: String,
+    line: usize,
+) -> Result<ToggleTaskResult, VividError> {
+    let path_buf = PathBuf::from(&path);
+    log::info!("[toggle_task] {}:{}", path, line);
+    if !path_buf.is_file() {
+        log::error!("[toggle_task] File does not exist: {}", path);
+        return Err(VividError::not_found(&path_buf));
+    }
+    access::ensure_access(&app, &path_buf, AccessKind::Write)?;
+
+    let result = tauri::async_runtime::spawn_blocking(move || toggle(&app, &path_buf, line))
+        .await
+        .map_err(|e| format!("Toggle task failed: {}", e))?
+        .map_err(|e| {
+            log::error!("[toggle_task] {}", e);
+            e
+        })?;
+    log::info!(
+        "[toggle_task] ✓ Success: {}:{} -> {}",
+        path,
+        line,
+        if result.completed { "done" } else { "open" }
+    );
+    Ok(result)
+}
@@ -0,0 +1,319 @@
+//! 文档模板
+//!
+//! 模板是用户模板目录（应用数据目录下的 `templates/`）中的 Markdown 文件，
+//! 文件名（不含扩展名）即模板 id。首次使用时写入会议纪要和日记两个示例模板。
+//! 工作区配置了 `templates_dir`（见 `workspace_config`）时，其中的模板优先于同 id 的用户模板。
+//! 模板中的 `{{name}}` 占位符在创建文档时替换：内置变量有 `date`、`time`、`datetime`、
+//! `title`、`filename`，日期时间可以带 strftime 格式（`{{date:%Y/%m/%d}}`），其它为自定义变量。
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use chrono::format::{Item, StrftimeItems};
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, State};
+
+use crate::access::{self, AccessKind};
+use crate::error::VividError;
+use crate::{revision, workspace, workspace_config, FileInfo};
+
+/// 首次使用时写入的示例模板
+const DEFAULT_TEMPLATES: &[(&str, &str)] = &[
+    (
+        "daily-note.md",
+        "---\ntitle: \"{{title}}\"\ndate: {{date}}\ntags: [daily]\n---\n\n# {{date:%A, %B %-d, %Y}}\n\n## Tasks\n\n- [ ] \n\n## Notes\n\n",
+    ),
+    (
+        "meeting-notes.md",
+        "---\ntitle: \"{{title}}\"\ndate: {{date}}\ntags: [meeting]\n---\n\n# {{title}}\n\n**Date:** {{datetime}}\n**Attendees:** {{attendees}}\n\n## Agenda\n\n## Notes\n\n## Action Items\n\n- [ ] \n",
+    ),
+];
+/// 内置变量，不出现在模板的自定义变量列表中
+const BUILTIN_VARIABLES: &[&str] = &["date", "time", "datetime", "title", "filename"];
+
+/// 模板信息
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TemplateInfo {
+    pub id: String,
+    pub name: String,
+    pub path: String,
+    /// 模板中使用的自定义变量，供前端提示用户填写
+    pub variables: Vec<String>,
+}
+
+/// 用户模板目录
+pub struct TemplateStore {
+    dir: PathBuf,
+}
+
+/// `meeting-notes` → `Meeting Notes`
+fn display_name(id: &str) -> String {
+    id.split(['-', '_', ' '])
+        .filter(|word| !word.is_empty())
+        .map(|word| {
+            let mut chars = word.chars();
+            match chars.next() {
+                Some(first) => first.to_uppercase().chain(chars).collect(),
+                None => String::new(),
+            }
+        })
+        .collect::<Vec<String>>()
+        .join(" ")
+}
+
+/// 模板中的占位符：`(字节区间, 变量名, 格式)`
+fn placeholders(template: &str) -> Vec<(std::ops::Range<usize>, &str, Option<&str>)> {
+    let mut found = Vec::new();
+    let mut search = 0;
+    while let Some(open) = template[search..].find("{{").map(|i| search + i) {
+        let Some(close) = template[open + 2..].find("}}").map(|i| open + 2 + i) else {
+            break;
+        };
+        let inner = template[open + 2..close].trim();
+        let (name, format) = match inner.split_once(':') {
+            Some((name, format)) => (name.trim(), Some(format)),
+            None => (inner, None),
+        };
+        let valid = !name.is_empty()
+            && name
+                .chars()
+                .all(|c| c.is_alphanumeric() || matches!(c, '_' | '-' | '.'));
+        if valid {
+            found.push((open..close + 2, name, format));
+            search = close + 2;
+        } else {
+            search = open + 2;
+        }
+    }
+    found
+}
+
+/// 模板中使用的自定义变量（去重，保持出现顺序）
+pub fn custom_variables(template: &str) -> Vec<String> {
+    let mut names: Vec<String> = Vec::new();
+    for (_, name, _) in placeholders(template) {
+        if !BUILTIN_VARIABLES.contains(&name) && !names.iter().any(|n| n == name) {
+            names.push(name.to_string());
+        }
+    }
+    names
+}
+
+/// 按 strftime 格式输出时间，格式无效时返回 `None`
+fn format_time(now: &DateTime<Local>, format: &str) -> Option<String> {
+    let items: Vec<Item<'_>> = StrftimeItems::new(format).collect();
+    if items.iter().any(|item| matches!(item, Item::Error)) {
+        return None;
+    }
+    Some(now.format_with_items(items.into_iter()).to_string())
+}
+
+/// 替换模板中的变量；未提供的自定义变量替换为空
+pub fn render_template(
+    template: &str,
+    variables: &HashMap<String, String>,
+    now: &DateTime<Local>,
+) -> String {
+    let mut out = String::with_capacity(template.len());
+    let mut last = 0;
+    for (range, name, format) in placeholders(template) {
+        out.push_str(&template[last..range.start]);
+        last = range.end;
+
+        let default_format = match name {
+            "date" => Some("%Y-%m-%d"),
+            "time" => Some("%H:%M"),
+            "datetime" => Some("%Y-%m-%d %H:%M"),
+            _ => None,
+        };
+        let value = match (default_format, variables.get(name)) {
+            (_, Some(value)) if format.is_none() => Some(value.clone()),
+            (Some(default), _) => format_time(now, format.unwrap_or(default)),
+            (None, value) => value.cloned(),
+        };
+        match value {
+            Some(value) => out.push_str(&value),
+            None if default_format.is_some() => out.push_str(&template[range]),
+            None => log::debug!("[templates] No value for variable {}", name),
+        }
+    }
+    out.push_str(&template[last..]);
+    out
+}
+
+/// 写入新文档（自动创建父目录），返回与 `read_file` 相同的文件信息
+pub fn write_new_document(target: &Path, content: String) -> Result<FileInfo, String> {
+    if let Some(parent) = target.parent() {
+        fs::create_dir_all(parent).map_err(|e| format!("Failed to create directory: {}", e))?;
+    }
+    fs::write(target, &content).map_err(|e| {
+        log::error!("[templates] Write operation failed: {}", e);
+        format!("Failed to save file: {}", e)
+    })?;
+    let revision = revision::revision_for(content.as_bytes(), fs::metadata(target).ok().as_ref());
+    Ok(FileInfo {
+        path: target.to_string_lossy().to_string(),
+        name: target
+            .file_name()
+            .and_then(|n| n.to_str())
+            .unwrap_or("Untitled.md")
+            .to_string(),
+        content,
+        revision,
+        large: None,
+        encrypted: false,
+        read_only: false,
+    })
+}
+
+/// 目录中的模板文件
+fn templates_in(entries: fs::ReadDir) -> Vec<TemplateInfo> {
+    entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.is_file() && workspace::is_markdown(path))
+        .filter_map(|path| {
+            let id = path.file_stem()?.to_string_lossy().to_string();
+            if workspace::is_ignored(&id) {
+                return None;
+            }
+            let content = fs::read_to_string(&path).unwrap_or_default();
+            Some(TemplateInfo {
+                name: display_name(&id),
+                path: path.to_string_lossy().to_string(),
+                variables: custom_variables(&content),
+                id,
+            })
+        })
+        .collect()
+}
+
+impl TemplateStore {
+    pub fn new(dir: PathBuf) -> Self {
+        TemplateStore { dir }
+    }
+
+    /// 模板目录不存在时创建并写入示例模板
+    fn ensure_dir(&self) -> Result<(), String> {
+        if self.dir.exists() {
+            return Ok(());
+        }
+        fs::create_dir_all(&self.dir).map_err(|e| format!("Failed to create directory: {}", e))?;
+        for (name, content) in DEFAULT_TEMPLATES {
+            fs::write(self.dir.join(name), content)
+                .map_err(|e| format!("Failed to write template {}: {}", name, e))?;
+        }
+        log::info!("[templates] Created templates directory {:?}", self.dir);
+        Ok(())
+    }
+
+    /// 列出模板，`workspace` 为工作区中的任意路径，其模板目录中的模板优先
+    pub fn list(&self, workspace: Option<&Path>) -> Result<Vec<TemplateInfo>, String> {
+        self.ensure_dir()?;
+        let entries =
+            fs::read_dir(&self.dir).map_err(|e| format!("Failed to read directory: {}", e))?;
+        let mut templates = templates_in(entries);
+        if let Some(dir) = workspace.and_then(workspace_config::templates_dir_for) {
+            match fs::read_dir(&dir) {
+                Ok(entries) => {
+                    let shared = templates_in(entries);
+                    templates.retain(|t| !shared.iter().any(|s| s.id == t.id));
+                    templates.extend(shared);
+                }
+                Err(e) => log::warn!("[templates] Failed to read {:?}: {}", dir, e),
+            }
+        }
+        templates.sort_by_key(|t| t.name.to_lowercase());
+        Ok(templates)
+    }
+
+    /// 读取模板并替换变量，`title` 默认为目标文件名（不含扩展名）；模板按目标所在工作区查找
+    pub fn instantiate(
+        &self,
+        id: &str,
+        target: &Path,
+        mut variables: HashMap<String, String>,
+        now: &DateTime<Local>,
+    ) -> Result<String, String> {
+        let template_path = self.template_path(id, target)?;
+        let template = fs::read_to_string(&template_path)
+            .map_err(|e| format!("Failed to read template: {}", e))?;
+
+        let name = target
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_default();
+        variables.insert("filename".to_string(), name);
+        if !variables.contains_key("title") {
+            let title = target
+                .file_stem()
+                .map(|stem| stem.to_string_lossy().to_string())
+                .unwrap_or_default();
+            variables.insert("title".to_string(), title);
+        }
+        Ok(render_template(&template, &variables, now))
+    }
+
+    /// 按 id 查找模板文件，只接受模板目录中的文件
+    fn template_path(&self, id: &str, target: &Path) -> Result<PathBuf, String> {
+        self.list(Some(target))?
+            .into_iter()
+            .find(|template| template.id == id)
+            .map(|template| PathBuf::from(template.path))
+            .ok_or_else(|| format!("Template not found: {}", id))
+    }
+}
+
+// 列出用户模板，传入工作区时包含工作区模板
+#[tauri::command]
+pub fn list_templates(
+    store: State<'_, TemplateStore>,
+    workspace: Option<String>,
+) -> Result<Vec<TemplateInfo>, VividError> {
+    store
+        .list(workspace.as_deref().map(Path::new))
+        .map_err(|e| {
+            log::error!("[list_templates] {}", e);
+            VividError::from(e)
+        })
+}
+
+// 基于模板创建新文档，目标文件已存在时报错
+#[tauri::command]
+pub fn create_from_template(
+    app: AppHandle,
+    store: State<'_, TemplateStore>,
+    template_id: String,
+    target_path: String,
+    variables: Option<HashMap<String, String>>,
+) -> Result<FileInfo, VividError> {
+    log::info!(
+        "[create_from_template] Creating {} from template {}",
+        target_path,
+        template_id
+    );
+    let target = Path::new(&target_path);
+    access::ensure_access(&app, target, AccessKind::Write)?;
+    if target.exists() {
+        return Err(VividError::conflict(
+            target,
+            format!("File already exists: {}", target_path),
+        ));
+    }
+    let content = store
+        .instantiate(
+            &template_id,
+            target,
+            variables.unwrap_or_default(),
+            &Local::now(),
+        )
+        .map_err(|e| {
+            log::error!("[create_from_template] {}", e);
+            e
+        })?;
+    let info = write_new_document(target, content)?;
+    log::info!("[create_from_template] ✓ Success: {}", target_path);
+    Ok(info)
+}
@@ -0,0 +1,261 @@
+//! 外部工具
+//!
+//! 设置的 `external_tools.tools` 中定义的命令（如 `prettier --parser markdown`、自定义脚本）
+//! 通过标准输入接收文档内容，标准输出作为结果。`run_tool` 按 id 手动运行工具，
+//! 标记了 `format_on_save` 的工具在保存前依次格式化文档，格式化失败时保存原内容。
+//!
+//! 运行时：
+//!
+//! - 只传递 `PATH`、`HOME` 等少量环境变量，外加 `VIVIDMARK_FILE`（文档路径）；
+//! - 工作目录为文档所在目录或工作区根目录，需在允许访问的范围内，否则使用运行后删除的临时目录；
+//! - 超过设置的超时时间后终止进程。
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager};
+
+use crate::access::{self, AccessKind};
+use crate::diagram;
+use crate::error::VividError;
+use crate::settings::{ExternalTool, SettingsStore, ToolWorkingDir};
+use crate::storage;
+use crate::workspace::{self, Workspace};
+
+/// 传给外部工具的环境变量，其余变量不传递
+const PASSED_ENV: &[&str] = &[
+    "PATH",
+    "HOME",
+    "USER",
+    "LANG",
+    "LC_ALL",
+    "TMPDIR",
+    "TEMP",
+    "TMP",
+    "USERPROFILE",
+    "APPDATA",
+    "LOCALAPPDATA",
+    "SYSTEMROOT",
+    "PATHEXT",
+];
+/// 新文档在参数中使用的文件名
+const UNTITLED_NAME: &str = "untitled.md";
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ToolResult {
+    pub id: String,
+    /// 工具的标准输出
+    pub output: String,
+    /// 输出是否与输入不同
+    pub changed: bool,
+    pub elapsed_ms: u64,
+}
+
+/// 运行结束后删除的临时工作目录
+struct TempDir(PathBuf);
+
+impl TempDir {
+    fn new(id: &str) -> Result<Self, String> {
+        let dir = std::env::temp_dir().join(format!(
+            "vividmark-tool-{}-{}-{}",
+            id,
+            std::process::id(),
+            storage::now_millis()
+        ));
+        fs::create_dir_all(&dir)
+            .map_err(|e| format!("Failed to create working directory: {}", e))?;
+        Ok(TempDir(dir))
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.0);
+    }
+}
+
+/// 替换参数中的 `{file}` / `{dir}` / `{name}`
+fn expand_arg(arg: &str, path: Option<&Path>) -> String {
+    let file = path.map_or_else(
+        || UNTITLED_NAME.to_string(),
+        |p| p.to_string_lossy().to_string(),
+    );
+    let dir = path
+        .and_then(Path::parent)
+        .map(|p| p.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let name = path.and_then(Path::file_name).map_or_else(
+        || UNTITLED_NAME.to_string(),
+        |n| n.to_string_lossy().to_string(),
+    );
+    arg.replace("{file}", &file)
+        .replace("{dir}", &dir)
+        .replace("{name}", &name)
+}
+
+/// 工具的工作目录，不在允许访问范围内时返回 `None`（使用临时目录）
+fn working_dir(app: &AppHandle, tool: &ExternalTool, path: Option<&Path>) -> Option<PathBuf> {
+    let dir = match tool.working_dir {
+        ToolWorkingDir::Document => path.and_then(Path::parent).map(Path::to_path_buf),
+        ToolWorkingDir::Workspace => app.state::<Workspace>().root(),
+        ToolWorkingDir::Temp => None,
+    }?;
+    if !dir.is_dir() {
+        return None;
+    }
+    match access::ensure_access(app, &dir, AccessKind::Read) {
+        Ok(()) => Some(dir),
+        Err(e) => {
+            log::warn!(
+                "[tools] {}: using a temporary working directory: {}",
+                tool.id,
+                e
+            );
+            None
+        }
+    }
+}
+
+/// 运行工具，返回标准输出
+fn run(
+    app: &AppHandle,
+    tool: &ExternalTool,
+    content: &str,
+    path: Option<&Path>,
+) -> Result<String, String> {
+    if tool.command.trim().is_empty() {
+        return Err(format!("Tool {} has no command", tool.id));
+    }
+    let mut temp = None;
+    let cwd = match working_dir(app, tool, path) {
+        Some(dir) => dir,
+        None => temp.insert(TempDir::new(&tool.id)?).0.clone(),
+    };
+
+    let mut command = Command::new(&tool.command);
+    command
+        .args(tool.args.iter().map(|arg| expand_arg(arg, path)))
+        .current_dir(&cwd)
+        .env_clear()
+        .envs(
+            PASSED_ENV
+                .iter()
+                .filter_map(|name| std::env::var_os(name).map(|value| (name, value))),
+        );
+    if let Some(path) = path {
+        command.env("VIVIDMARK_FILE", path);
+    }
+    log::debug!("[tools] {} in {:?}", tool.id, cwd);
+
+    let output = diagram::run_command(
+        command,
+        &tool.command,
+        content,
+        Duration::from_secs(tool.timeout_secs),
+    )?;
+    String::from_utf8(output).map_err(|_| format!("{} returned invalid UTF-8", tool.command))
+}
+
+fn applies_to(tool: &ExternalTool, path: &Path) -> bool {
+    if tool.extensions.is_empty() {
+        return workspace::is_markdown(path);
+    }
+    let ext = path
+        .extension()
+        .map(|ext| ext.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+    tool.extensions
+        .iter()
+        .any(|e| e.trim_start_matches('.').eq_ignore_ascii_case(&ext))
+}
+
+/// 保存前依次运行格式化工具，内容有变化时返回格式化后的内容
+///
+/// 工具失败或输出为空时跳过该工具，不影响保存。
+pub fn format_on_save(app: &AppHandle, path: &Path, content: &str) -> Option<String> {
+    let settings = app.state::<SettingsStore>().get();
+    let formatters: Vec<&ExternalTool> = settings
+        .external_tools
+        .tools
+        .iter()
+        .filter(|tool| tool.format_on_save && applies_to(tool, path))
+        .collect();
+    if formatters.is_empty() {
+        return None;
+    }
+
+    let mut formatted = content.to_string();
+    for tool in formatters {
+        let start = Instant::now();
+        match run(app, tool, &formatted, Some(path)) {
+            Ok(output) if output.trim().is_empty() && !formatted.trim().is_empty() => {
+                log::warn!("[tools] {} returned no output, skipping", tool.id);
+            }
+            Ok(output) => {
+                log::info!(
+                    "[tools] Formatted {:?} with {} in {:?}",
+                    path,
+                    tool.id,
+                    start.elapsed()
+                );
+                formatted = output;
+            }
+            Err(e) => log::warn!("[tools] Formatter {} failed: {}", tool.id, e),
+        }
+    }
+    (formatted != content).then_some(formatted)
+}
+
+// 运行外部工具：`content` 通过标准输入传入，返回标准输出；`path` 为文档路径（新文档为空）
+#[tauri::command]
+pub async fn run_tool(
+    app: AppHandle,
+    id: String,
+    content: String,
+    path: Option<String>,
+) -> Result<ToolResult, VividError> {
+    let start = Instant::now();
+    let tool = app
+        .state::<SettingsStore>()
+        .get()
+        .external_tools
+        .tools
+        .into_iter()
+        .find(|tool| tool.id == id)
+        .ok_or_else(|| {
+            log::warn!("[run_tool] Tool not found: {}", id);
+            VividError::invalid_input(format!("Tool not found: {}", id))
+        })?;
+    log::info!("[run_tool] {} ({} bytes)", id, content.len());
+
+    let path = path.filter(|p| !p.is_empty()).map(PathBuf::from);
+    let output = tauri::async_runtime::spawn_blocking(move || {
+        run(&app, &tool, &content, path.as_deref()).map(|output| {
+            let changed = output != content;
+            (output, changed)
+        })
+    })
+    .await
+    .map_err(|e| format!("Tool task failed: {}", e))?;
+    let (output, changed) = output.map_err(|e| {
+        log::error!("[run_tool] {}: {}", id, e);
+        VividError::from(e)
+    })?;
+
+    log::info!(
+        "[run_tool] ✓ Success: {} ({} bytes, changed: {}) in {:?}",
+        id,
+        output.len(),
+        changed,
+        start.elapsed()
+    );
+    Ok(ToolResult {
+        id,
+        output,
+        changed,
+        elapsed_ms: start.elapsed().as_millis() as u64,
+    })
+}
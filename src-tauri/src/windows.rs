@@ -0,0 +1,382 @@
+//! 多窗口
+//!
+//! `open_in_new_window` 打开额外的编辑器窗口（标签为 `editor-<序号>`）。每个窗口通过
+//! `register_document` / `unregister_document` 上报自己打开的文档，后端据此统一监听这些文件：
+//!
+//! - 一个窗口保存后，其它打开了同一文件的窗口收到 `document-changed`（`origin` 为保存的窗口）；
+//! - 其它程序修改或删除文件时，所有打开它的窗口收到 `document-changed`（`origin` 为空）。
+//!
+//! 前端收到事件后按自己的修订标记判断：没有未保存修改时重新加载，否则提示冲突。
+//!
+//! 文档以规范化路径（见 `paths`）为键，通过符号链接或不同写法打开的同一个文件视为同一篇
+//! 文档；发给各窗口的事件仍使用该窗口打开时的写法。
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::Mutex;
+
+use notify::{EventKind, RecursiveMode, Watcher};
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter, Manager, State, WebviewUrl, WebviewWindow, WebviewWindowBuilder};
+
+use crate::access::{self, AccessKind};
+use crate::error::VividError;
+use crate::{paths, revision};
+
+/// 打开的文档在磁盘上发生变化时发送给相关窗口的事件
+pub const DOCUMENT_CHANGED_EVENT: &str = "document-changed";
+
+/// 额外窗口的标签前缀，权限配置中按 `editor-*` 匹配
+const WINDOW_LABEL_PREFIX: &str = "editor-";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DocumentChanged {
+    pub path: String,
+    /// 磁盘上的新修订标记，文件被删除时为空
+    pub revision: Option<String>,
+    pub deleted: bool,
+    /// 保存该文件的窗口标签；其它程序修改时为空
+    pub origin: Option<String>,
+}
+
+#[derive(Default)]
+struct WindowState {
+    /// 窗口标签 -> 打开的文档（规范化路径 -> 窗口打开时的路径）
+    documents: HashMap<String, BTreeMap<PathBuf, PathBuf>>,
+    /// 已通知过的磁盘修订标记，键为规范化路径
+    revisions: HashMap<PathBuf, String>,
+    /// 正在监听的目录（监听父目录而不是文件本身，原子写入替换文件后仍然有效）
+    watched: HashSet<PathBuf>,
+}
+
+impl WindowState {
+    /// 打开了该文档的窗口及各自使用的路径
+    fn windows_with(&self, key: &Path) -> Vec<(String, PathBuf)> {
+        self.documents
+            .iter()
+            .filter_map(|(label, docs)| Some((label.clone(), docs.get(key)?.clone())))
+            .collect()
+    }
+
+    fn is_open(&self, key: &Path) -> bool {
+        self.documents.values().any(|docs| docs.contains_key(key))
+    }
+
+    /// 文档的键：规范化路径；硬链接到已打开文档时使用已打开文档的键
+    fn key_of(&self, path: &Path) -> PathBuf {
+        let key = paths::canonicalize(path);
+        if self.is_open(&key) {
+            return key;
+        }
+        let Some(id) = paths::file_id(&key) else {
+            return key;
+        };
+        self.documents
+            .values()
+            .flat_map(BTreeMap::keys)
+            .find(|open| paths::file_id(open) == Some(id))
+            .cloned()
+            .unwrap_or(key)
+    }
+
+    /// 仍有文档打开的目录
+    fn needed_dirs(&self) -> HashSet<PathBuf> {
+        self.documents
+            .values()
+            .flat_map(BTreeMap::keys)
+            .filter_map(|path| path.parent().map(Path::to_path_buf))
+            .collect()
+    }
+}
+
+/// 各窗口打开的文档与文件监听
+#[derive(Default)]
+pub struct WindowManager {
+    state: Mutex<WindowState>,
+    watcher: Mutex<Option<notify::RecommendedWatcher>>,
+    next_id: AtomicU32,
+}
+
+impl WindowManager {
+    /// 按当前打开的文档调整监听的目录
+    fn update_watches(&self, app: &AppHandle) -> Result<(), VividError> {
+        let (added, removed) = {
+            let mut state = self.state.lock().map_err(|e| e.to_string())?;
+            let needed = state.needed_dirs();
+            let added: Vec<PathBuf> = needed.difference(&state.watched).cloned().collect();
+            let removed: Vec<PathBuf> = state.watched.difference(&needed).cloned().collect();
+            state.watched = needed;
+            let open: HashSet<PathBuf> = state
+                .documents
+                .values()
+                .flat_map(BTreeMap::keys)
+                .cloned()
+                .collect();
+            state.revisions.retain(|path, _| open.contains(path));
+            (added, removed)
+        };
+        if added.is_empty() && removed.is_empty() {
+            return Ok(());
+        }
+
+        let mut watcher = self.watcher.lock().map_err(|e| e.to_string())?;
+        if watcher.is_none() {
+            let handle = app.clone();
+            *watcher = Some(
+                notify::recommended_watcher(move |res: notify::Result<notify::Event>| match res {
+                    Ok(event) => handle_event(&handle, event),
+                    Err(e) => log::warn!("[windows] Watch error: {}", e),
+                })
+                .map_err(|e| format!("Failed to watch directory: {}", e))?,
+            );
+        }
+        let Some(watcher) = watcher.as_mut() else {
+            return Ok(());
+        };
+        for dir in removed {
+            let _ = watcher.unwatch(&dir);
+        }
+        for dir in added {
+            if let Err(e) = watcher.watch(&dir, RecursiveMode::NonRecursive) {
+                log::warn!("[windows] Failed to watch {}: {}", dir.display(), e);
+            }
+        }
+        Ok(())
+    }
+
+    /// 某个窗口保存文件后通知其它打开了它的窗口
+    pub fn note_saved(&self, app: &AppHandle, origin: &str, path: &Path, revision: &str) {
+        let targets = match self.state.lock() {
+            Ok(mut state) => {
+                let key = state.key_of(path);
+                if !state.is_open(&key) {
+                    return;
+                }
+                let targets = state.windows_with(&key);
+                state.revisions.insert(key, revision.to_string());
+                targets
+            }
+            Err(_) => return,
+        };
+        for (label, path) in targets.iter().filter(|(label, _)| label.as_str() != origin) {
+            let payload = DocumentChanged {
+                path: path.to_string_lossy().to_string(),
+                revision: Some(revision.to_string()),
+                deleted: false,
+                origin: Some(origin.to_string()),
+            };
+            notify_window(app, label, &payload);
+        }
+    }
+
+    /// 窗口关闭后移除它打开的文档
+    pub fn window_closed(&self, app: &AppHandle, label: &str) {
+        let removed = match self.state.lock() {
+            Ok(mut state) => state.documents.remove(label).is_some(),
+            Err(_) => false,
+        };
+        if removed {
+            log::info!("[windows] Window closed: {}", label);
+            if let Err(e) = self.update_watches(app) {
+                log::warn!("[windows] {}", e);
+            }
+        }
+    }
+}
+
+fn notify_window(app: &AppHandle, label: &str, payload: &DocumentChanged) {
+    if let Err(e) = app.emit_to(label, DOCUMENT_CHANGED_EVENT, payload) {
+        log::warn!("[windows] Failed to notify {}: {}", label, e);
+    }
+}
+
+/// 监听回调：打开的文档在磁盘上的内容变化时通知所有打开它的窗口
+fn handle_event(app: &AppHandle, event: notify::Event) {
+    if matches!(event.kind, EventKind::Access(_) | EventKind::Other) {
+        return;
+    }
+    let manager = app.state::<WindowManager>();
+    for path in event.paths {
+        // 同一目录下没有打开的文件也会触发事件，先过滤再读取内容
+        let Some(path) = manager.state.lock().ok().and_then(|state| {
+            let key = state.key_of(&path);
+            state.is_open(&key).then_some(key)
+        }) else {
+            continue;
+        };
+        let disk = match revision::current_revision(&path) {
+            Ok(disk) => disk.map(|(_, revision)| revision),
+            Err(e) => {
+                log::debug!("[windows] Unable to read {}: {}", path.display(), e);
+                continue;
+            }
+        };
+        let targets = {
+            let Ok(mut state) = manager.state.lock() else {
+                return;
+            };
+            if !state.is_open(&path) {
+                continue;
+            }
+            // 只比较内容：本应用刚保存过的版本和仅修改时间变化的情况不重复通知
+            let known = state.revisions.get(&path);
+            let unchanged = match (&disk, known) {
+                (Some(disk), Some(known)) => revision::same_content(disk, known),
+                (None, None) => true,
+                _ => false,
+            };
+            if unchanged {
+                continue;
+            }
+            match &disk {
+                Some(revision) => {
+                    state.revisions.insert(path.clone(), revision.clone());
+                }
+                None => {
+                    state.revisions.remove(&path);
+                }
+            }
+            state.windows_with(&path)
+        };
+
+        log::info!(
+            "[windows] {} changed on disk, notifying {} window(s)",
+            path.display(),
+            targets.len()
+        );
+        for (label, opened) in &targets {
+            let payload = DocumentChanged {
+                path: opened.to_string_lossy().to_string(),
+                deleted: disk.is_none(),
+                revision: disk.clone(),
+                origin: None,
+            };
+            notify_window(app, label, &payload);
+        }
+    }
+}
+
+// 在新窗口中打开文档（不传路径时打开空白窗口），返回窗口标签
+#[tauri::command]
+pub async fn open_in_new_window(
+    app: AppHandle,
+    manager: State<'_, WindowManager>,
+    path: Option<String>,
+) -> Result<String, VividError> {
+    let path = path.map(PathBuf::from);
+    if let Some(path) = &path {
+        access::ensure_access(&app, path, AccessKind::Read)?;
+        if !path.is_file() {
+            log::error!(
+                "[open_in_new_window] File does not exist: {}",
+                path.display()
+            );
+            return Err(VividError::not_found(path));
+        }
+    }
+
+    let label = format!(
+        "{}{}",
+        WINDOW_LABEL_PREFIX,
+        manager.next_id.fetch_add(1, Ordering::Relaxed) + 1
+    );
+    let title = match &path {
+        Some(path) => format!("{} - VividMark", crate::file_name_of(path)),
+        None => "VividMark".to_string(),
+    };
+    // 新窗口启动后通过 `window_documents` 取得要打开的文档
+    if let Some(path) = &path {
+        register(&app, &manager, &label, path)?;
+    }
+
+    WebviewWindowBuilder::new(&app, &label, WebviewUrl::App("index.html".into()))
+        .title(title)
+        .inner_size(1200.0, 800.0)
+        .min_inner_size(600.0, 400.0)
+        .build()
+        .map_err(|e| {
+            log::error!("[open_in_new_window] {}", e);
+            manager.window_closed(&app, &label);
+            format!("Failed to create window: {}", e)
+        })?;
+
+    log::info!("[open_in_new_window] ✓ Success: {} ({:?})", label, path);
+    Ok(label)
+}
+
+fn register(
+    app: &AppHandle,
+    manager: &WindowManager,
+    label: &str,
+    path: &Path,
+) -> Result<(), VividError> {
+    let revision = revision::current_revision(path)
+        .ok()
+        .flatten()
+        .map(|(_, revision)| revision);
+    {
+        let mut state = manager.state.lock().map_err(|e| e.to_string())?;
+        let key = state.key_of(path);
+        state
+            .documents
+            .entry(label.to_string())
+            .or_default()
+            .insert(key.clone(), path.to_path_buf());
+        if let Some(revision) = revision {
+            state.revisions.entry(key).or_insert(revision);
+        }
+    }
+    manager.update_watches(app)
+}
+
+// 记录当前窗口打开了某个文档
+#[tauri::command]
+pub fn register_document(
+    app: AppHandle,
+    window: WebviewWindow,
+    manager: State<'_, WindowManager>,
+    path: String,
+) -> Result<(), VividError> {
+    log::debug!("[register_document] {}: {}", window.label(), path);
+    let path = Path::new(&path);
+    access::ensure_access(&app, path, AccessKind::Read)?;
+    register(&app, &manager, window.label(), path)
+}
+
+// 当前窗口关闭了某个文档
+#[tauri::command]
+pub fn unregister_document(
+    app: AppHandle,
+    window: WebviewWindow,
+    manager: State<'_, WindowManager>,
+    path: String,
+) -> Result<(), VividError> {
+    log::debug!("[unregister_document] {}: {}", window.label(), path);
+    {
+        let mut state = manager.state.lock().map_err(|e| e.to_string())?;
+        let key = state.key_of(Path::new(&path));
+        if let Some(docs) = state.documents.get_mut(window.label()) {
+            docs.remove(&key);
+        }
+    }
+    manager.update_watches(&app)
+}
+
+// 当前窗口应打开的文档（新窗口启动时调用）
+#[tauri::command]
+pub fn window_documents(
+    window: WebviewWindow,
+    manager: State<'_, WindowManager>,
+) -> Result<Vec<String>, VividError> {
+    let state = manager.state.lock().map_err(|e| e.to_string())?;
+    Ok(state
+        .documents
+        .get(window.label())
+        .map(|docs| {
+            docs.values()
+                .map(|p| p.to_string_lossy().to_string())
+                .collect()
+        })
+        .unwrap_or_default())
+}
@@ -0,0 +1,273 @@
+//! 工作区与文件监听
+//!
+//! 前端打开文件夹时调用 `open_workspace`：后端扫描其中的 Markdown 文件（全文索引还包括
+//! PDF 和 Word 附件）建立索引，并监听文件变化，在文件被新建、修改、删除或重命名时增量更新索引。
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::Instant;
+
+use notify::{EventKind, RecursiveMode, Watcher};
+use tauri::{AppHandle, Manager, State};
+
+use crate::access::{self, AccessKind};
+use crate::error::VividError;
+use crate::extract;
+use crate::links::LinkIndex;
+use crate::metadata::MetadataCache;
+use crate::paths;
+use crate::perf;
+use crate::quickopen::QuickOpenCache;
+use crate::search::SearchIndex;
+use crate::session::SessionStore;
+use crate::tags::TagIndex;
+
+/// 视为 Markdown 文档的扩展名
+const MARKDOWN_EXTENSIONS: &[&str] = &["md", "markdown", "mdown", "mkd"];
+
+/// 随工作区文件变化增量更新的索引
+pub trait FileIndex: Send + Sync {
+    /// 是否索引该文件，默认只索引 Markdown 文档
+    fn accepts(&self, path: &Path) -> bool {
+        is_markdown(path)
+    }
+    /// 用给定的文件列表重建索引
+    fn rebuild(&self, paths: &[PathBuf]);
+    /// 重新索引单个文件，读取失败时移除
+    fn refresh(&self, path: &Path);
+    /// 移除某个文件或目录下的所有文件
+    fn remove_under(&self, path: &Path);
+    fn clear(&self);
+}
+
+/// 所有需要随工作区更新的索引
+fn indexes(app: &AppHandle) -> [&dyn FileIndex; 5] {
+    [
+        app.state::<TagIndex>().inner(),
+        app.state::<LinkIndex>().inner(),
+        app.state::<SearchIndex>().inner(),
+        app.state::<QuickOpenCache>().inner(),
+        app.state::<MetadataCache>().inner(),
+    ]
+}
+
+/// 当前打开的工作区
+#[derive(Default)]
+pub struct Workspace {
+    root: Mutex<Option<PathBuf>>,
+    watcher: Mutex<Option<notify::RecommendedWatcher>>,
+}
+
+impl Workspace {
+    /// 当前工作区根目录
+    pub fn root(&self) -> Option<PathBuf> {
+        self.root.lock().ok().and_then(|root| root.clone())
+    }
+}
+
+/// 是否为 Markdown 文件（只看扩展名）
+pub fn is_markdown(path: &Path) -> bool {
+    path.extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| {
+            MARKDOWN_EXTENSIONS
+                .iter()
+                .any(|m| ext.eq_ignore_ascii_case(m))
+        })
+}
+
+/// 是否跳过该目录项（与文件树保持一致：隐藏文件和依赖 / 构建目录）
+pub fn is_ignored(name: &str) -> bool {
+    name.starts_with('.') || name == "node_modules" || name == "target"
+}
+
+/// 路径是否位于被跳过的目录中
+fn in_ignored_dir(root: &Path, path: &Path) -> bool {
+    path.strip_prefix(root)
+        .map(|rel| {
+            rel.components()
+                .any(|c| is_ignored(&c.as_os_str().to_string_lossy()))
+        })
+        .unwrap_or(true)
+}
+
+/// 递归列出目录下的所有 Markdown 文件
+pub fn markdown_files(root: &Path) -> Vec<PathBuf> {
+    files_where(root, is_markdown)
+}
+
+/// 任一索引需要的文件：Markdown 文档和可提取文本的附件
+fn indexed_files(root: &Path) -> Vec<PathBuf> {
+    files_where(root, |path| {
+        is_markdown(path) || extract::is_extractable(path)
+    })
+}
+
+fn files_where(root: &Path, filter: impl Fn(&Path) -> bool) -> Vec<PathBuf> {
+    let mut files = Vec::new();
+    let mut pending = vec![root.to_path_buf()];
+    while let Some(dir) = pending.pop() {
+        let Ok(entries) = fs::read_dir(&dir) else {
+            log::warn!("[workspace] Failed to read directory: {}", dir.display());
+            continue;
+        };
+        for entry in entries.flatten() {
+            if is_ignored(&entry.file_name().to_string_lossy()) {
+                continue;
+            }
+            let path = entry.path();
+            match entry.file_type() {
+                Ok(t) if t.is_dir() => pending.push(path),
+                Ok(t) if t.is_file() && filter(&path) => files.push(path),
+                _ => {}
+            }
+        }
+    }
+    files.sort();
+    files
+}
+
+/// 重新建立所有索引
+fn rebuild_indexes(app: &AppHandle, root: &Path) {
+    reindex(app, root, &|_, _| {}, &|| false);
+}
+
+/// 逐个重建索引，每个索引完成后调用 `progress(已完成, 总数)`；`cancelled` 返回 `true` 时
+/// 不再重建剩下的索引，返回是否全部完成
+pub(crate) fn reindex(
+    app: &AppHandle,
+    root: &Path,
+    progress: &dyn Fn(usize, usize),
+    cancelled: &dyn Fn() -> bool,
+) -> bool {
+    let start = Instant::now();
+    let files = indexed_files(root);
+    let indexes = indexes(app);
+    for (done, index) in indexes.iter().enumerate() {
+        if cancelled() {
+            log::info!("[workspace] Indexing cancelled after {} index(es)", done);
+            return false;
+        }
+        let accepted: Vec<PathBuf> = files.iter().filter(|p| index.accepts(p)).cloned().collect();
+        index.rebuild(&accepted);
+        progress(done + 1, indexes.len());
+    }
+    let elapsed = start.elapsed();
+    let bytes = files
+        .iter()
+        .filter_map(|path| fs::metadata(path).ok())
+        .map(|metadata| metadata.len())
+        .sum();
+    perf::record(
+        perf::Operation::Index,
+        &root.to_string_lossy(),
+        elapsed,
+        bytes,
+    );
+    log::info!(
+        "[workspace] ✓ Indexed {} file(s) in {:?}",
+        files.len(),
+        elapsed
+    );
+    true
+}
+
+/// 让接受该文件的索引重新索引它
+fn refresh(indexes: &[&dyn FileIndex], path: &Path) {
+    for index in indexes.iter().filter(|index| index.accepts(path)) {
+        index.refresh(path);
+    }
+}
+
+/// 处理文件监听事件，增量更新索引
+///
+/// 根目录经过符号链接时，部分平台（如 macOS）报告的是解析后的路径，先换回根目录的写法，
+/// 与索引中的路径保持一致。
+fn handle_event(app: &AppHandle, root: &Path, canonical_root: &Path, event: notify::Event) {
+    if matches!(event.kind, EventKind::Access(_) | EventKind::Other) {
+        return;
+    }
+    let indexes = indexes(app);
+    for path in event.paths {
+        let path = if path.starts_with(root) {
+            path
+        } else {
+            paths::rebase(&paths::canonicalize(&path), canonical_root, root)
+        };
+        if in_ignored_dir(root, &path) {
+            continue;
+        }
+        if path.is_dir() {
+            // 新建或移入的目录：索引其中的文件
+            for file in indexed_files(&path) {
+                refresh(&indexes, &file);
+            }
+        } else if path.exists() {
+            refresh(&indexes, &path);
+        } else {
+            // 已删除或移出：可能是文件，也可能是整个目录
+            indexes.iter().for_each(|index| index.remove_under(&path));
+        }
+    }
+}
+
+// 打开工作区：建立索引并开始监听文件变化
+#[tauri::command]
+pub async fn open_workspace(app: AppHandle, root: String) -> Result<(), VividError> {
+    let root = PathBuf::from(&root);
+    log::info!("[open_workspace] Opening {}", root.display());
+
+    if !root.is_dir() {
+        log::error!("[open_workspace] Not a directory: {}", root.display());
+        return Err(VividError::dir_not_found(&root));
+    }
+    access::ensure_access(&app, &root, AccessKind::Read)?;
+
+    let handle = app.clone();
+    let watch_root = root.clone();
+    let canonical_root = paths::canonicalize(&root);
+    let mut watcher =
+        notify::recommended_watcher(move |res: notify::Result<notify::Event>| match res {
+            Ok(event) => handle_event(&handle, &watch_root, &canonical_root, event),
+            Err(e) => log::warn!("[workspace] Watch error: {}", e),
+        })
+        .map_err(|e| format!("Failed to watch directory: {}", e))?;
+    watcher
+        .watch(&root, RecursiveMode::Recursive)
+        .map_err(|e| format!("Failed to watch directory: {}", e))?;
+
+    let workspace = app.state::<Workspace>();
+    *workspace.root.lock().map_err(|e| e.to_string())? = Some(root.clone());
+    *workspace.watcher.lock().map_err(|e| e.to_string())? = Some(watcher);
+    if let Err(e) = app.state::<SessionStore>().add_recent_workspace(&root) {
+        log::warn!("[open_workspace] Failed to record recent workspace: {}", e);
+    }
+
+    let handle = app.clone();
+    tauri::async_runtime::spawn_blocking(move || {
+        // 全文索引打不开时其它索引仍然可用，搜索返回错误
+        if let Err(e) = handle.state::<SearchIndex>().open(&root) {
+            log::warn!("[open_workspace] {}", e);
+        }
+        if let Err(e) = handle.state::<MetadataCache>().open(&root) {
+            log::warn!("[open_workspace] {}", e);
+        }
+        rebuild_indexes(&handle, &root)
+    })
+    .await
+    .map_err(|e| format!("Failed to index workspace: {}", e))?;
+    Ok(())
+}
+
+// 关闭工作区：停止监听并清空索引
+#[tauri::command]
+pub fn close_workspace(app: AppHandle, workspace: State<'_, Workspace>) -> Result<(), VividError> {
+    log::info!("[close_workspace] Closing workspace");
+    *workspace.watcher.lock().map_err(|e| e.to_string())? = None;
+    *workspace.root.lock().map_err(|e| e.to_string())? = None;
+    for index in indexes(&app) {
+        index.clear();
+    }
+    Ok(())
+}
@@ -0,0 +1,199 @@
+//! 工作区配置
+//!
+//! 工作区根目录下的 `.vividmark/config.toml` 覆盖部分全局设置，可以随仓库提交，
+//! 让团队成员使用一致的资源目录、链接格式、检查规则、导出选项和模板：
+//!
+//! ```toml
+//! assets_dir = "/attachments"   # 以 `/` 开头时相对工作区根目录，否则相对文档所在目录
+//! link_style = "wiki"           # `relative` 或 `wiki`
+//! templates_dir = ".vividmark/templates"
+//!
+//! [lint]
+//! disabled_rules = ["line_length"]
+//! list_marker = "-"
+//!
+//! [export]
+//! html_theme = "dark"
+//! ```
+//!
+//! 配置按字段合并到全局设置上，未出现的字段沿用全局设置。文档所属的工作区是向上查找到的
+//! 第一个包含该配置文件的目录；配置文件有误时记录警告并使用全局设置。
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use tauri::{AppHandle, Manager};
+
+use crate::access::{self, AccessKind};
+use crate::error::VividError;
+use crate::frontmatter;
+use crate::settings::{ExportSettings, LinkStyle, LintSettings, Settings, SettingsStore};
+
+/// 工作区配置目录
+pub const CONFIG_DIR: &str = ".vividmark";
+const CONFIG_FILE: &str = "config.toml";
+/// 配置文件中可以出现的字段
+const KNOWN_KEYS: &[&str] = &[
+    "assets_dir",
+    "link_style",
+    "templates_dir",
+    "lint",
+    "export",
+];
+
+/// 合并工作区配置后的有效设置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EffectiveConfig {
+    /// 工作区根目录，文档不属于任何带配置的工作区时为空
+    pub root: Option<String>,
+    /// 生效的配置文件
+    pub config_path: Option<String>,
+    /// 资源目录，绝对路径或相对文档所在目录的路径
+    pub assets_dir: String,
+    pub link_style: LinkStyle,
+    pub lint: LintSettings,
+    pub export: ExportSettings,
+    /// 工作区模板目录（绝对路径），其中的模板优先于用户模板
+    pub templates_dir: Option<String>,
+    /// 配置文件中被忽略的字段或解析错误
+    pub warnings: Vec<String>,
+}
+
+impl EffectiveConfig {
+    fn from_settings(settings: &Settings) -> Self {
+        EffectiveConfig {
+            root: None,
+            config_path: None,
+            assets_dir: settings.assets_dir.clone(),
+            link_style: settings.link_style,
+            lint: settings.lint.clone(),
+            export: settings.export.clone(),
+            templates_dir: None,
+            warnings: Vec::new(),
+        }
+    }
+}
+
+fn config_path(root: &Path) -> PathBuf {
+    root.join(CONFIG_DIR).join(CONFIG_FILE)
+}
+
+/// 向上查找包含工作区配置的目录
+pub fn find_root(path: &Path) -> Option<PathBuf> {
+    let start = if path.is_dir() { path } else { path.parent()? };
+    start
+        .ancestors()
+        .find(|dir| config_path(dir).is_file())
+        .map(Path::to_path_buf)
+}
+
+/// 读取配置文件，转换为 JSON 对象
+fn read_config(path: &Path) -> Result<Map<String, Value>, String> {
+    let text = fs::read_to_string(path).map_err(|e| format!("Failed to read {:?}: {}", path, e))?;
+    let document = text
+        .parse::<toml_edit::DocumentMut>()
+        .map_err(|e| format!("Invalid {}: {}", CONFIG_FILE, e))?;
+    match frontmatter::toml_table_to_json(document.as_table()) {
+        Value::Object(map) => Ok(map),
+        _ => Ok(Map::new()),
+    }
+}
+
+/// 把工作区根目录下的配置合并到全局设置上
+pub fn load(root: &Path, settings: &Settings) -> EffectiveConfig {
+    let mut config = EffectiveConfig::from_settings(settings);
+    config.root = Some(root.to_string_lossy().to_string());
+    let path = config_path(root);
+    if !path.is_file() {
+        return config;
+    }
+    config.config_path = Some(path.to_string_lossy().to_string());
+
+    let mut raw = match read_config(&path) {
+        Ok(raw) => raw,
+        Err(e) => {
+            log::warn!("[workspace_config] {}", e);
+            config.warnings.push(e);
+            return config;
+        }
+    };
+    raw.retain(|key, _| {
+        let known = KNOWN_KEYS.contains(&key.as_str());
+        if !known {
+            config.warnings.push(format!("Unknown key: {}", key));
+        }
+        known
+    });
+    let templates_dir = raw.remove("templates_dir");
+    // `/` 开头的资源目录相对工作区根目录
+    if let Some(Value::String(dir)) = raw.get_mut("assets_dir") {
+        if let Some(rel) = dir.strip_prefix('/') {
+            *dir = root.join(rel).to_string_lossy().to_string();
+        }
+    }
+
+    match settings.with_overrides(Value::Object(raw)) {
+        Ok(merged) => {
+            config.assets_dir = merged.assets_dir;
+            config.link_style = merged.link_style;
+            config.lint = merged.lint;
+            config.export = merged.export;
+        }
+        Err(e) => {
+            log::warn!("[workspace_config] {:?}: {}", path, e);
+            config.warnings.push(e);
+        }
+    }
+    match templates_dir {
+        Some(Value::String(dir)) if !dir.trim().is_empty() => {
+            config.templates_dir = Some(root.join(dir.trim()).to_string_lossy().to_string());
+        }
+        Some(Value::String(_)) | None => {}
+        Some(_) => config
+            .warnings
+            .push("templates_dir must be a string".to_string()),
+    }
+    config
+}
+
+/// 文档或目录的有效设置：属于带配置的工作区时合并其配置，否则为全局设置
+pub fn resolve(settings: &Settings, path: &Path) -> EffectiveConfig {
+    match find_root(path) {
+        Some(root) => load(&root, settings),
+        None => EffectiveConfig::from_settings(settings),
+    }
+}
+
+/// 文档或目录所属工作区配置的模板目录
+pub fn templates_dir_for(path: &Path) -> Option<PathBuf> {
+    let root = find_root(path)?;
+    load(&root, &Settings::default())
+        .templates_dir
+        .map(PathBuf::from)
+}
+
+/// 按当前全局设置解析文档的有效设置
+pub fn effective_for(app: &AppHandle, path: &Path) -> EffectiveConfig {
+    resolve(&app.state::<SettingsStore>().get(), path)
+}
+
+// 读取工作区配置并与全局设置合并
+#[tauri::command]
+pub fn get_effective_config(app: AppHandle, root: String) -> Result<EffectiveConfig, VividError> {
+    let root_path = PathBuf::from(&root);
+    access::ensure_access(&app, &root_path, AccessKind::Read)?;
+    if !root_path.is_dir() {
+        log::error!("[get_effective_config] Not a directory: {}", root);
+        return Err(VividError::not_found(&root_path));
+    }
+    let config = load(&root_path, &app.state::<SettingsStore>().get());
+    log::debug!(
+        "[get_effective_config] {}: config {:?}, {} warning(s)",
+        root,
+        config.config_path,
+        config.warnings.len()
+    );
+    Ok(config)
+}
@@ -0,0 +1,453 @@
+//! 写作统计
+//!
+//! 每次保存时比较保存前后的内容，记录新增和删除的字数（中日韩文字按字、其它文字按词，
+//! 与字数统计一致）。同一文档在 `writing.session_gap_minutes` 内的连续保存合并为一个写作时段，
+//! 跨过零点时开始新的时段，以便按天汇总。
+//!
+//! 记录保存在应用数据目录的 `writing.sqlite` 中，`get_writing_stats` 按日期范围返回每日合计、
+//! 写作时段和连续达成目标的天数，供统计面板使用。
+
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use chrono::{Local, NaiveDate};
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use similar::{capture_diff_slices_deadline, Algorithm, DiffTag};
+use tauri::{AppHandle, Manager, State};
+
+use crate::error::VividError;
+use crate::largefile::LARGE_FILE_THRESHOLD;
+use crate::markdown::is_cjk_char;
+use crate::settings::SettingsStore;
+
+const DATABASE_FILE: &str = "writing.sqlite";
+/// 数据库结构版本
+const SCHEMA_VERSION: i64 = 1;
+/// 比较前后内容的时间上限，超时后按已比较的部分估算
+const DIFF_TIMEOUT: Duration = Duration::from_millis(500);
+/// 未指定范围时返回最近的天数
+const DEFAULT_RANGE_DAYS: i64 = 30;
+/// 一次最多返回的天数
+const MAX_RANGE_DAYS: i64 = 366 * 5;
+const DATE_FORMAT: &str = "%Y-%m-%d";
+
+/// 查询范围，日期格式为 `YYYY-MM-DD`（本地时间）
+#[derive(Debug, Default, Deserialize)]
+pub struct StatsRange {
+    /// 起始日期（含），默认为结束日期前 29 天
+    pub from: Option<String>,
+    /// 结束日期（含），默认为今天
+    pub to: Option<String>,
+    /// 只统计某个文档
+    pub path: Option<String>,
+}
+
+/// 一个写作时段
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WritingSession {
+    pub path: String,
+    /// 开始、结束时间（Unix 毫秒）
+    pub started: i64,
+    pub ended: i64,
+    pub added: usize,
+    pub removed: usize,
+    /// 时段结束时文档的字数
+    pub words: usize,
+}
+
+/// 每日合计
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DailyTotal {
+    pub date: String,
+    pub added: usize,
+    pub removed: usize,
+    pub net: i64,
+    pub sessions: usize,
+    /// 各时段从第一次到最后一次保存的时长之和
+    pub minutes: u64,
+    /// 是否达到每日目标；未设目标时有新增即算达成
+    pub goal_met: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct WritingStats {
+    pub from: String,
+    pub to: String,
+    /// 范围内的每一天，没有记录的日子为 0
+    pub daily: Vec<DailyTotal>,
+    /// 范围内的写作时段，按开始时间倒序
+    pub sessions: Vec<WritingSession>,
+    pub total_added: usize,
+    pub total_removed: usize,
+    pub daily_goal: usize,
+    pub today_added: usize,
+    /// 截至今天连续达成目标的天数（今天尚未达成时从昨天算起）
+    pub current_streak: usize,
+    pub longest_streak: usize,
+}
+
+/// 写作记录数据库，第一次使用时打开
+pub struct WritingSessions {
+    path: PathBuf,
+    conn: Mutex<Option<Connection>>,
+}
+
+fn init_schema(conn: &Connection) -> rusqlite::Result<()> {
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS sessions (
+            id INTEGER PRIMARY KEY,
+            path TEXT NOT NULL,
+            day TEXT NOT NULL,
+            started INTEGER NOT NULL,
+            ended INTEGER NOT NULL,
+            added INTEGER NOT NULL,
+            removed INTEGER NOT NULL,
+            words INTEGER NOT NULL
+        );
+        CREATE INDEX IF NOT EXISTS sessions_day ON sessions (day);
+        CREATE INDEX IF NOT EXISTS sessions_path ON sessions (path, ended);",
+    )?;
+    conn.pragma_update(None, "user_version", SCHEMA_VERSION)
+}
+
+/// 按字数统计的规则切分：每个中日韩文字、每个其它文字的词各为一项，标点和空白不计
+fn word_tokens(text: &str) -> Vec<&str> {
+    let mut tokens = Vec::new();
+    let mut word_start: Option<usize> = None;
+    for (i, c) in text.char_indices() {
+        let continues =
+            c.is_alphanumeric() || (word_start.is_some() && matches!(c, '\'' | '’' | '-' | '_'));
+        if is_cjk_char(c) || !continues {
+            if let Some(start) = word_start.take() {
+                tokens.push(&text[start..i]);
+            }
+            if is_cjk_char(c) && c.is_alphanumeric() {
+                tokens.push(&text[i..i + c.len_utf8()]);
+            }
+        } else if word_start.is_none() {
+            word_start = Some(i);
+        }
+    }
+    if let Some(start) = word_start {
+        tokens.push(&text[start..]);
+    }
+    tokens
+}
+
+/// 新增和删除的字数
+fn word_changes(before: &str, after: &str) -> (usize, usize) {
+    let before = word_tokens(before);
+    let after = word_tokens(after);
+    let deadline = Instant::now() + DIFF_TIMEOUT;
+    let mut added = 0;
+    let mut removed = 0;
+    for op in capture_diff_slices_deadline(Algorithm::Myers, &before, &after, Some(deadline)) {
+        let (tag, old, new) = op.as_tag_tuple();
+        match tag {
+            DiffTag::Insert => added += new.len(),
+            DiffTag::Delete => removed += old.len(),
+            DiffTag::Replace => {
+                added += new.len();
+                removed += old.len();
+            }
+            DiffTag::Equal => {}
+        }
+    }
+    (added, removed)
+}
+
+/// 保存前读取原内容，新文件为空；加密文件、大文件和非文本文件返回 `None`，不参与统计
+pub fn read_previous(path: &Path) -> Option<String> {
+    if !path.exists() {
+        return Some(String::new());
+    }
+    let metadata = std::fs::metadata(path).ok()?;
+    if metadata.len() > LARGE_FILE_THRESHOLD {
+        return None;
+    }
+    let bytes = std::fs::read(path).ok()?;
+    if crate::encryption::is_encrypted(&bytes) {
+        return None;
+    }
+    String::from_utf8(bytes).ok()
+}
+
+fn parse_date(value: &str) -> Result<NaiveDate, VividError> {
+    NaiveDate::parse_from_str(value, DATE_FORMAT)
+        .map_err(|_| VividError::invalid_input(format!("Invalid date: {}", value)))
+}
+
+/// 截至 `today` 的连续天数和最长连续天数，`days` 为按日期升序排列的达成目标的日子
+fn streaks(days: &[NaiveDate], today: NaiveDate) -> (usize, usize) {
+    let mut longest = 0;
+    let mut run = 0;
+    let mut previous: Option<NaiveDate> = None;
+    for day in days {
+        run = match previous {
+            Some(prev) if prev.succ_opt() == Some(*day) => run + 1,
+            _ => 1,
+        };
+        longest = longest.max(run);
+        previous = Some(*day);
+    }
+
+    let yesterday = today.pred_opt().unwrap_or(today);
+    let current = match previous {
+        Some(last) if last == today || last == yesterday => run,
+        _ => 0,
+    };
+    (current, longest)
+}
+
+impl WritingSessions {
+    pub fn new(dir: PathBuf) -> Self {
+        WritingSessions {
+            path: dir.join(DATABASE_FILE),
+            conn: Mutex::new(None),
+        }
+    }
+
+    fn with_conn<T>(
+        &self,
+        f: impl FnOnce(&mut Connection) -> rusqlite::Result<T>,
+    ) -> Result<T, String> {
+        let mut conn = self.conn.lock().map_err(|e| e.to_string())?;
+        if conn.is_none() {
+            if let Some(parent) = self.path.parent() {
+                std::fs::create_dir_all(parent)
+                    .map_err(|e| format!("Failed to create directory: {}", e))?;
+            }
+            let opened = Connection::open(&self.path)
+                .and_then(|conn| init_schema(&conn).map(|_| conn))
+                .map_err(|e| format!("Failed to open writing statistics: {}", e))?;
+            *conn = Some(opened);
+        }
+        let Some(conn) = conn.as_mut() else {
+            return Err("Writing statistics are unavailable".to_string());
+        };
+        f(conn).map_err(|e| format!("Writing statistics error: {}", e))
+    }
+
+    /// 记录一次保存，归入文档最近的写作时段或开始新的时段
+    fn record(&self, path: &Path, added: usize, removed: usize, words: usize, gap: Duration) {
+        let now = Local::now();
+        let timestamp = now.timestamp_millis();
+        let day = now.format(DATE_FORMAT).to_string();
+        let path = path.to_string_lossy().to_string();
+        let since = timestamp - gap.as_millis() as i64;
+
+        let result =
+            self.with_conn(|conn| {
+                let tx = conn.transaction()?;
+                let current: Option<i64> = tx
+                    .query_row(
+                        "SELECT id FROM sessions WHERE path = ?1 AND day = ?2 AND ended >= ?3
+                     ORDER BY ended DESC LIMIT 1",
+                        params![path, day, since],
+                        |row| row.get(0),
+                    )
+                    .optional()?;
+                match current {
+                Some(id) => tx.execute(
+                    "UPDATE sessions SET ended = ?2, added = added + ?3, removed = removed + ?4,
+                     words = ?5 WHERE id = ?1",
+                    params![id, timestamp, added as i64, removed as i64, words as i64],
+                )?,
+                None => tx.execute(
+                    "INSERT INTO sessions (path, day, started, ended, added, removed, words)
+                     VALUES (?1, ?2, ?3, ?3, ?4, ?5, ?6)",
+                    params![path, day, timestamp, added as i64, removed as i64, words as i64],
+                )?,
+            };
+                tx.commit()
+            });
+        match result {
+            Ok(()) => log::debug!(
+                "[writing_sessions] {}: +{} / -{} words",
+                path,
+                added,
+                removed
+            ),
+            Err(e) => log::warn!("[writing_sessions] {}", e),
+        }
+    }
+
+    /// 保存成功后在后台比较内容并记录，不阻塞保存
+    pub fn note_saved(app: &AppHandle, path: &Path, previous: String, content: &str) {
+        if content.len() as u64 > LARGE_FILE_THRESHOLD {
+            return;
+        }
+        let app = app.clone();
+        let path = path.to_path_buf();
+        let content = content.to_string();
+        tauri::async_runtime::spawn_blocking(move || {
+            let (added, removed) = word_changes(&previous, &content);
+            if added == 0 && removed == 0 {
+                return;
+            }
+            let words = word_tokens(&content).len();
+            let gap = app
+                .state::<SettingsStore>()
+                .get()
+                .writing
+                .session_gap_minutes;
+            app.state::<WritingSessions>().record(
+                &path,
+                added,
+                removed,
+                words,
+                Duration::from_secs(gap * 60),
+            );
+        });
+    }
+
+    fn stats(
+        &self,
+        from: NaiveDate,
+        to: NaiveDate,
+        path: Option<&str>,
+        goal: usize,
+    ) -> Result<WritingStats, String> {
+        let from_day = from.format(DATE_FORMAT).to_string();
+        let to_day = to.format(DATE_FORMAT).to_string();
+        let (sessions, totals) = self.with_conn(|conn| {
+            let mut stmt = conn.prepare(
+                "SELECT path, started, ended, added, removed, words, day FROM sessions
+                 WHERE day >= ?1 AND day <= ?2 AND (?3 IS NULL OR path = ?3)
+                 ORDER BY started DESC",
+            )?;
+            let sessions = stmt
+                .query_map(params![from_day, to_day, path], |row| {
+                    Ok((
+                        WritingSession {
+                            path: row.get(0)?,
+                            started: row.get(1)?,
+                            ended: row.get(2)?,
+                            added: row.get::<_, i64>(3)? as usize,
+                            removed: row.get::<_, i64>(4)? as usize,
+                            words: row.get::<_, i64>(5)? as usize,
+                        },
+                        row.get::<_, String>(6)?,
+                    ))
+                })?
+                .collect::<rusqlite::Result<Vec<_>>>()?;
+
+            // 连续天数按全部历史计算
+            let mut stmt = conn.prepare(
+                "SELECT day, SUM(added) FROM sessions WHERE (?1 IS NULL OR path = ?1)
+                 GROUP BY day ORDER BY day",
+            )?;
+            let totals = stmt
+                .query_map(params![path], |row| {
+                    Ok((row.get::<_, String>(0)?, row.get::<_, i64>(1)?))
+                })?
+                .collect::<rusqlite::Result<Vec<_>>>()?;
+            Ok((sessions, totals))
+        })?;
+
+        let met = |added: usize| if goal > 0 { added >= goal } else { added > 0 };
+        let mut daily = Vec::new();
+        let mut day = from;
+        while day <= to {
+            let date = day.format(DATE_FORMAT).to_string();
+            let mut total = DailyTotal {
+                date: date.clone(),
+                added: 0,
+                removed: 0,
+                net: 0,
+                sessions: 0,
+                minutes: 0,
+                goal_met: false,
+            };
+            for (session, _) in sessions.iter().filter(|(_, d)| *d == date) {
+                total.added += session.added;
+                total.removed += session.removed;
+                total.sessions += 1;
+                total.minutes += (session.ended - session.started).max(0) as u64 / 60_000;
+            }
+            total.net = total.added as i64 - total.removed as i64;
+            total.goal_met = met(total.added);
+            daily.push(total);
+            let Some(next) = day.succ_opt() else {
+                break;
+            };
+            day = next;
+        }
+
+        let today = Local::now().date_naive();
+        let today_day = today.format(DATE_FORMAT).to_string();
+        let met_days: Vec<NaiveDate> = totals
+            .iter()
+            .filter(|(_, added)| met((*added).max(0) as usize))
+            .filter_map(|(day, _)| NaiveDate::parse_from_str(day, DATE_FORMAT).ok())
+            .collect();
+        let (current_streak, longest_streak) = streaks(&met_days, today);
+
+        Ok(WritingStats {
+            from: from_day,
+            to: to_day,
+            total_added: daily.iter().map(|d| d.added).sum(),
+            total_removed: daily.iter().map(|d| d.removed).sum(),
+            daily,
+            sessions: sessions.into_iter().map(|(session, _)| session).collect(),
+            daily_goal: goal,
+            today_added: totals
+                .iter()
+                .find(|(day, _)| *day == today_day)
+                .map_or(0, |(_, added)| (*added).max(0) as usize),
+            current_streak,
+            longest_streak,
+        })
+    }
+}
+
+// 获取写作统计：每日合计、写作时段和连续达成目标的天数
+#[tauri::command]
+pub async fn get_writing_stats(
+    app: AppHandle,
+    range: Option<StatsRange>,
+) -> Result<WritingStats, VividError> {
+    let start = Instant::now();
+    let range = range.unwrap_or_default();
+    let to = match range.to.as_deref() {
+        Some(to) => parse_date(to)?,
+        None => Local::now().date_naive(),
+    };
+    let from = match range.from.as_deref() {
+        Some(from) => parse_date(from)?,
+        None => to - chrono::Duration::days(DEFAULT_RANGE_DAYS - 1),
+    };
+    if from > to {
+        return Err(VividError::invalid_input("Start date is after end date"));
+    }
+    if (to - from).num_days() >= MAX_RANGE_DAYS {
+        return Err(VividError::invalid_input(format!(
+            "Date range is limited to {} days",
+            MAX_RANGE_DAYS
+        )));
+    }
+    let goal = app.state::<SettingsStore>().get().writing.daily_goal;
+
+    let handle = app.clone();
+    let stats = tauri::async_runtime::spawn_blocking(move || {
+        let sessions: State<'_, WritingSessions> = handle.state();
+        sessions.stats(from, to, range.path.as_deref(), goal)
+    })
+    .await
+    .map_err(|e| format!("Writing statistics task failed: {}", e))?
+    .map_err(|e| {
+        log::error!("[get_writing_stats] {}", e);
+        VividError::from(e)
+    })?;
+
+    log::info!(
+        "[get_writing_stats] ✓ Success: {} to {}, {} session(s) in {:?}",
+        stats.from,
+        stats.to,
+        stats.sessions.len(),
+        start.elapsed()
+    );
+    Ok(stats)
+}